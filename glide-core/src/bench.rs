@@ -0,0 +1,203 @@
+//! `glide bench`: how fast this machine captures, renders each effect and
+//! encodes, on a synthesized recording, for picking settings and attaching
+//! to performance bug reports
+
+use crate::cursor_types::{CursorEvent, EventType};
+use crate::processing::progress::ProgressFormat;
+use crate::processing::{ProcessOptions, Processor};
+use crate::recording::encoder::{self, encode_speed, select_encoder, Codec, Encoder, Quality};
+use crate::recording::metadata::RecordingMetadata;
+use crate::recording::recorder::measure_capture;
+use anyhow::{Context, Result};
+use std::path::Path;
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+/// Size and frame rate of the synthesized recording
+const WIDTH: u32 = 1920;
+const HEIGHT: u32 = 1080;
+const FPS: u32 = 60;
+
+/// How long the display is captured for
+const CAPTURE_TIME: Duration = Duration::from_secs(2);
+
+/// Seconds between the synthesized clicks
+const CLICK_INTERVAL: f64 = 1.5;
+
+/// One processing pass and how long it took
+#[derive(Debug, Clone)]
+pub struct Pass {
+    /// "Composite" for the first pass, then "+ <effect>" for each effect added
+    pub name: &'static str,
+    pub seconds: f64,
+}
+
+/// What `run_bench` measured
+#[derive(Debug)]
+pub struct BenchReport {
+    pub width: u32,
+    pub height: u32,
+    pub fps: u32,
+    /// Length of the synthesized recording (seconds)
+    pub duration: f64,
+    pub frames: u64,
+    /// The display captured and its capture rate (fps)
+    pub capture: Result<(usize, f64)>,
+    /// Processing passes, each adding an effect to the one before
+    pub passes: Vec<Pass>,
+    /// Name of the H.264 encoder timed
+    pub encoder: &'static str,
+    /// Encode rate (fps)
+    pub encode: Result<f64>,
+}
+
+/// Cursor track for the synthesized recording: a loop around the screen
+/// with a click every `CLICK_INTERVAL`, so zooms and motion blur kick in
+fn synthetic_events(duration: f64) -> Vec<CursorEvent> {
+    let (w, h) = (WIDTH as f64, HEIGHT as f64);
+    let mut events = Vec::new();
+    let mut next_click = CLICK_INTERVAL / 2.0;
+    for i in 0..(duration * FPS as f64) as usize {
+        let timestamp = i as f64 / FPS as f64;
+        let angle = timestamp * 0.8;
+        let x = w / 2.0 + w * 0.35 * angle.cos();
+        let y = h / 2.0 + h * 0.35 * (angle * 2.0).sin();
+        let event_type = if timestamp >= next_click {
+            next_click += CLICK_INTERVAL;
+            EventType::LeftClick
+        } else {
+            EventType::Move
+        };
+        events.push(CursorEvent {
+            x,
+            y,
+            timestamp,
+            event_type,
+            display: None,
+            shape: None,
+        });
+    }
+    events
+}
+
+/// Write a test-pattern recording of `duration` seconds with its metadata
+fn synthesize_recording(path: &Path, duration: f64) -> Result<()> {
+    let status = Command::new("ffmpeg")
+        .args(["-v", "error", "-f", "lavfi", "-i"])
+        .arg(format!("testsrc2=size={WIDTH}x{HEIGHT}:rate={FPS}"))
+        .arg("-t")
+        .arg(format!("{:.3}", duration))
+        .args([
+            "-c:v",
+            "libx264",
+            "-preset",
+            "ultrafast",
+            "-pix_fmt",
+            "yuv420p",
+            "-y",
+        ])
+        .arg(path)
+        .status()
+        .context("Failed to run ffmpeg")?;
+    if !status.success() {
+        anyhow::bail!("FFmpeg failed to synthesize the test recording");
+    }
+
+    let mut metadata = RecordingMetadata::new_display(0, WIDTH, HEIGHT, 1.0);
+    metadata.cursor_events = synthetic_events(duration);
+    metadata.cursor_tracking_duration = duration;
+    metadata.save(path)
+}
+
+/// Render `input` once per pass, each adding an effect to the one before
+fn time_passes(input: &Path, output: &Path) -> Result<Vec<Pass>> {
+    // Name, then whether zoom, the cursor (with click highlights) and
+    // motion blur are on
+    let passes = [
+        ("Composite", false, false, false),
+        ("+ Zoom", true, false, false),
+        ("+ Cursor", true, true, false),
+        ("+ Motion blur", true, true, true),
+    ];
+
+    let mut results = Vec::new();
+    for (name, zoom, cursor, motion_blur) in passes {
+        let options = ProcessOptions {
+            no_zoom: !zoom,
+            no_cursor: !cursor,
+            no_click_highlight: !cursor,
+            no_motion_blur: !motion_blur,
+            progress: ProgressFormat::Quiet,
+            ..ProcessOptions::default()
+        };
+        tracing::info!("Rendering: {}", name.trim_start_matches("+ "));
+        let started = Instant::now();
+        Processor::new(options)
+            .process(input, output)
+            .with_context(|| format!("The {} pass failed", name))?;
+        results.push(Pass {
+            name,
+            seconds: started.elapsed().as_secs_f64(),
+        });
+    }
+    Ok(results)
+}
+
+/// Run every benchmark on a `duration`-second recording
+pub fn run_bench(duration: f64) -> Result<BenchReport> {
+    encoder::check_ffmpeg()?;
+    let dir = tempfile::tempdir().context("Failed to create a working directory")?;
+    let input = dir.path().join("bench.mp4");
+    let frames = (duration * FPS as f64).round() as u64;
+
+    tracing::info!(
+        "Synthesizing a {:.0}s {}x{} test recording...",
+        duration,
+        WIDTH,
+        HEIGHT
+    );
+    synthesize_recording(&input, duration)?;
+
+    tracing::info!(
+        "Capturing the main display for {}s...",
+        CAPTURE_TIME.as_secs()
+    );
+    let capture = measure_capture(CAPTURE_TIME);
+
+    let passes = time_passes(&input, &dir.path().join("output.mp4"))?;
+
+    let encoder = select_encoder(Encoder::Auto, Codec::H264, Quality::Standard)?;
+    tracing::info!("Encoding with {}...", encoder.name());
+    let encode = encode_speed(&input, frames, Codec::H264, encoder);
+
+    Ok(BenchReport {
+        width: WIDTH,
+        height: HEIGHT,
+        fps: FPS,
+        duration,
+        frames,
+        capture,
+        passes,
+        encoder: encoder.name(),
+        encode,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_synthetic_events() {
+        let events = synthetic_events(6.0);
+        assert_eq!(events.len(), 360);
+        let clicks = events
+            .iter()
+            .filter(|e| matches!(e.event_type, EventType::LeftClick))
+            .count();
+        assert_eq!(clicks, 4);
+        assert!(events
+            .iter()
+            .all(|e| (0.0..WIDTH as f64).contains(&e.x) && (0.0..HEIGHT as f64).contains(&e.y)));
+    }
+}
@@ -0,0 +1,128 @@
+//! Shared cursor event types used across platforms
+
+use crate::keyboard::Modifiers;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum EventType {
+    Move,
+    LeftClick,
+    RightClick,
+    /// A key was pressed; the event's position is the cursor's at the time
+    KeyDown(KeyPress),
+}
+
+/// A key-down, without auto-repeats or presses of modifier keys alone
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct KeyPress {
+    /// Physical key as a W3C `KeyboardEvent.code` name ("KeyQ", "Enter"),
+    /// labelled later with `KeyboardInfo`. None when recorded privately or
+    /// the key has no code here.
+    pub code: Option<String>,
+    pub modifiers: Modifiers,
+}
+
+/// How much of the keyboard to record
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum KeyCapture {
+    /// Don't record key presses
+    #[default]
+    Off,
+    /// Only record that a key was pressed, not which one or with what modifiers
+    Private,
+    /// Record each key and the modifiers held
+    Full,
+}
+
+impl KeyCapture {
+    /// What to keep of a key press, if anything
+    pub fn record(self, press: KeyPress) -> Option<KeyPress> {
+        match self {
+            KeyCapture::Off => None,
+            KeyCapture::Private => Some(KeyPress::default()),
+            KeyCapture::Full => Some(press),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CursorEvent {
+    pub x: f64,
+    pub y: f64,
+    pub timestamp: f64,
+    pub event_type: EventType,
+    /// Index of the display the cursor was on, in multi-display recordings
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display: Option<usize>,
+    /// Shape the system cursor had, when the platform reports it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shape: Option<CursorShape>,
+}
+
+/// Kind of system cursor on screen, for drawing the matching sprite
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CursorShape {
+    #[default]
+    Arrow,
+    /// Text insertion
+    IBeam,
+    /// Links and buttons
+    PointingHand,
+    ResizeHorizontal,
+    ResizeVertical,
+}
+
+impl CursorShape {
+    /// Shape for an X cursor font or CSS cursor name, as used by cursor
+    /// themes. Names we don't draw differently give None.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "left_ptr" | "default" | "arrow" | "top_left_arrow" => Some(Self::Arrow),
+            "xterm" | "text" | "ibeam" | "vertical-text" => Some(Self::IBeam),
+            "hand" | "hand1" | "hand2" | "pointer" | "pointing_hand" => Some(Self::PointingHand),
+            "sb_h_double_arrow" | "h_double_arrow" | "ew-resize" | "col-resize" | "e-resize"
+            | "w-resize" | "left_side" | "right_side" | "size_hor" => Some(Self::ResizeHorizontal),
+            "sb_v_double_arrow" | "v_double_arrow" | "ns-resize" | "row-resize" | "n-resize"
+            | "s-resize" | "top_side" | "bottom_side" | "size_ver" => Some(Self::ResizeVertical),
+            _ => None,
+        }
+    }
+}
+
+/// On-screen bounds of the UI element under a click (screen points)
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct ClickTarget {
+    /// Timestamp of the click this target belongs to
+    pub timestamp: f64,
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cursor_shape_names() {
+        assert_eq!(CursorShape::from_name("xterm"), Some(CursorShape::IBeam));
+        assert_eq!(
+            CursorShape::from_name("pointer"),
+            Some(CursorShape::PointingHand)
+        );
+        assert_eq!(
+            CursorShape::from_name("col-resize"),
+            Some(CursorShape::ResizeHorizontal)
+        );
+        assert_eq!(CursorShape::from_name("watch"), None);
+
+        // Recordings from before shapes were tracked
+        let event: CursorEvent =
+            serde_json::from_str(r#"{"x":1.0,"y":2.0,"timestamp":0.5,"event_type":"Move"}"#)
+                .unwrap();
+        assert_eq!(event.shape, None);
+    }
+}
@@ -0,0 +1,202 @@
+//! Edit decisions: changes to the detected zooms, saved next to the recording
+//!
+//! A zoom is identified by the timestamp of the click it was detected from,
+//! so the sidecar stays valid as long as the metadata does. Clicks without
+//! an entry are zoomed on as usual.
+
+use crate::cursor_types::{CursorEvent, EventType};
+use crate::processing::zoom::{effective_clicks, ZoomConfig};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Clicks this close together (seconds) are the same click
+const SAME_CLICK: f64 = 1e-6;
+
+/// Changes to one detected zoom
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ZoomEdit {
+    /// Timestamp of the click the zoom was detected from (tracking clock)
+    pub click: f64,
+    /// Don't zoom on this click at all
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub delete: bool,
+    /// Seconds to move the zoom by (negative is earlier)
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub shift: f64,
+    /// Screen point to zoom in on instead of where the click was
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<(f64, f64)>,
+}
+
+impl ZoomEdit {
+    pub fn new(click: f64) -> Self {
+        Self {
+            click,
+            delete: false,
+            shift: 0.0,
+            target: None,
+        }
+    }
+
+    /// Whether this leaves the zoom as detected
+    pub fn is_noop(&self) -> bool {
+        !self.delete && self.shift == 0.0 && self.target.is_none()
+    }
+}
+
+fn is_zero(value: &f64) -> bool {
+    *value == 0.0
+}
+
+/// The `glide edit` sidecar
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct EditDecisions {
+    pub zooms: Vec<ZoomEdit>,
+}
+
+impl EditDecisions {
+    /// Load the sidecar for a recording, if it has one
+    pub fn load(video_path: &Path) -> Result<Option<Self>> {
+        let path = edits_path_for_video(video_path);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let json = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read edits from {:?}", path))?;
+        let edits = serde_json::from_str(&json)
+            .with_context(|| format!("Invalid edits file {:?}", path))?;
+        Ok(Some(edits))
+    }
+
+    /// Write the sidecar, or remove it if nothing is changed
+    pub fn save(&self, video_path: &Path) -> Result<PathBuf> {
+        let path = edits_path_for_video(video_path);
+        let zooms: Vec<_> = self
+            .zooms
+            .iter()
+            .filter(|z| !z.is_noop())
+            .cloned()
+            .collect();
+        if zooms.is_empty() {
+            if path.exists() {
+                fs::remove_file(&path).with_context(|| format!("Failed to remove {:?}", path))?;
+            }
+            return Ok(path);
+        }
+        let json = serde_json::to_string_pretty(&Self { zooms })?;
+        fs::write(&path, json).with_context(|| format!("Failed to write edits to {:?}", path))?;
+        Ok(path)
+    }
+
+    /// The edit for the zoom on the click at `click`, if any
+    pub fn get(&self, click: f64) -> Option<&ZoomEdit> {
+        self.zooms
+            .iter()
+            .find(|z| (z.click - click).abs() < SAME_CLICK)
+    }
+
+    /// The edit for the zoom on the click at `click`, created if needed
+    pub fn get_mut(&mut self, click: f64) -> &mut ZoomEdit {
+        let index = match self
+            .zooms
+            .iter()
+            .position(|z| (z.click - click).abs() < SAME_CLICK)
+        {
+            Some(index) => index,
+            None => {
+                self.zooms.push(ZoomEdit::new(click));
+                self.zooms.len() - 1
+            }
+        };
+        &mut self.zooms[index]
+    }
+
+    /// Number of zooms that are changed
+    pub fn changed(&self) -> usize {
+        self.zooms.iter().filter(|z| !z.is_noop()).count()
+    }
+
+    /// The events for the camera to follow: the clicks that zoom, edited,
+    /// plus every other event. Clicks debounced away stay out, so moving a
+    /// zoom doesn't bring back one that was merged into it.
+    pub fn camera_events(&self, events: &[CursorEvent], config: &ZoomConfig) -> Vec<CursorEvent> {
+        let clicks = effective_clicks(events, config);
+        let mut camera: Vec<CursorEvent> = events
+            .iter()
+            .filter(|e| !matches!(e.event_type, EventType::LeftClick | EventType::RightClick))
+            .cloned()
+            .collect();
+
+        for click in clicks {
+            let mut click = click.clone();
+            if let Some(edit) = self.get(click.timestamp) {
+                if edit.delete {
+                    continue;
+                }
+                click.timestamp += edit.shift;
+                if let Some((x, y)) = edit.target {
+                    click.x = x;
+                    click.y = y;
+                }
+            }
+            camera.push(click);
+        }
+
+        camera.sort_by(|a, b| a.timestamp.total_cmp(&b.timestamp));
+        camera
+    }
+}
+
+/// Get the edits file path for a video file (`recording.edits.json`)
+pub fn edits_path_for_video(video_path: &Path) -> PathBuf {
+    video_path.with_extension("edits.json")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(timestamp: f64, event_type: EventType) -> CursorEvent {
+        CursorEvent {
+            x: 100.0,
+            y: 100.0,
+            timestamp,
+            event_type,
+            display: None,
+            shape: None,
+        }
+    }
+
+    #[test]
+    fn test_camera_events() {
+        let events = vec![
+            event(1.0, EventType::LeftClick),
+            event(1.2, EventType::LeftClick), // debounced
+            event(2.0, EventType::Move),
+            event(5.0, EventType::LeftClick),
+            event(9.0, EventType::RightClick),
+        ];
+        let mut edits = EditDecisions::default();
+        edits.get_mut(1.0).shift = 0.5;
+        edits.get_mut(5.0).delete = true;
+        edits.get_mut(9.0).target = Some((40.0, 60.0));
+
+        let camera = edits.camera_events(&events, &ZoomConfig::default());
+        let times: Vec<_> = camera.iter().map(|e| e.timestamp).collect();
+        assert_eq!(times, vec![1.5, 2.0, 9.0]);
+        assert_eq!((camera[2].x, camera[2].y), (40.0, 60.0));
+    }
+
+    #[test]
+    fn test_noop_edits_not_saved() {
+        let mut edits = EditDecisions::default();
+        edits.get_mut(1.0).shift = 0.0;
+        edits.get_mut(2.0).delete = true;
+        assert_eq!(edits.changed(), 1);
+
+        let json = serde_json::to_string(&edits.zooms[1]).unwrap();
+        assert_eq!(json, r#"{"click":2.0,"delete":true}"#);
+    }
+}
@@ -0,0 +1,10 @@
+//! `glide edit`: adjusting the auto-zoom by hand
+//!
+//! The zooms detected from a recording's clicks are listed in a terminal UI,
+//! where they can be deleted, moved in time or pointed somewhere else. The
+//! changes are saved as `<recording>.edits.json`, which `glide process`
+//! applies when it computes the camera.
+
+pub mod decisions;
+
+pub use decisions::EditDecisions;
@@ -0,0 +1,517 @@
+//! Keyboard layouts and keystroke labels shared across platforms
+//!
+//! Keys are identified by physical position using W3C `KeyboardEvent.code`
+//! names ("KeyQ", "Digit1", "Semicolon"), so the label shown for a key can be
+//! resolved against the layout that was active at record time.
+
+use serde::{Deserialize, Serialize};
+
+/// Keyboard layout active at record time
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum KeyboardLayout {
+    #[default]
+    Qwerty,
+    /// German/Swiss/Austrian style (Y and Z swapped, umlauts)
+    Qwertz,
+    /// French/Belgian style
+    Azerty,
+    Dvorak,
+}
+
+/// Modifier keys held during a keystroke
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Modifiers {
+    #[serde(default)]
+    pub ctrl: bool,
+    #[serde(default)]
+    pub alt: bool,
+    #[serde(default)]
+    pub shift: bool,
+    /// Command on macOS, Super/Windows elsewhere
+    #[serde(default)]
+    pub meta: bool,
+}
+
+/// How modifiers are written in labels
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ModifierStyle {
+    /// ⌃⌥⇧⌘ symbols, no separators
+    Apple,
+    /// "Ctrl+Alt+Shift+Super" words
+    #[default]
+    Pc,
+}
+
+/// Keyboard settings captured at record time
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyboardInfo {
+    pub layout: KeyboardLayout,
+    pub modifier_style: ModifierStyle,
+}
+
+impl KeyboardLayout {
+    /// Classify an XKB layout/variant pair (as reported by `setxkbmap -query`)
+    pub fn from_xkb(layout: &str, variant: Option<&str>) -> Self {
+        // Only the first group matters: it's the one active when recording starts
+        let layout = layout.split(',').next().unwrap_or("").trim();
+        let variant = variant
+            .and_then(|v| v.split(',').next())
+            .unwrap_or("")
+            .trim();
+
+        if variant.contains("dvorak") || layout == "dvorak" {
+            return KeyboardLayout::Dvorak;
+        }
+        match layout {
+            // Polish defaults to QWERTY; only its "qwertz" variant swaps Y/Z
+            "pl" if variant == "qwertz" => KeyboardLayout::Qwertz,
+            "de" | "at" | "ch" | "cz" | "sk" | "hu" | "si" | "hr" if variant != "qwerty" => {
+                KeyboardLayout::Qwertz
+            }
+            "fr" | "be" if variant != "bepo" => KeyboardLayout::Azerty,
+            _ => KeyboardLayout::Qwerty,
+        }
+    }
+
+    /// Classify a macOS input source name (e.g. "German", "French", "Dvorak")
+    pub fn from_macos_name(name: &str) -> Self {
+        let name = name.to_ascii_lowercase();
+        if name.contains("dvorak") {
+            KeyboardLayout::Dvorak
+        } else if [
+            "german",
+            "swiss",
+            "austrian",
+            "czech",
+            "slovak",
+            "hungarian",
+        ]
+        .iter()
+        .any(|n| name.contains(n))
+        {
+            KeyboardLayout::Qwertz
+        } else if name.contains("french") || name.contains("belgian") {
+            KeyboardLayout::Azerty
+        } else {
+            KeyboardLayout::Qwerty
+        }
+    }
+
+    /// Classify a Windows keyboard layout ID (as reported by
+    /// `GetKeyboardLayoutNameW`, e.g. 0x00000407 for German)
+    pub fn from_windows_klid(klid: u32) -> Self {
+        // The variant lives in the high word; the low word is the language ID
+        if matches!(klid, 0x0001_0409 | 0x0003_0409 | 0x0004_0409) {
+            return KeyboardLayout::Dvorak;
+        }
+        let language = klid & 0xffff;
+        match language {
+            // Canadian French is QWERTY; Swiss French is QWERTZ
+            0x0c0c => KeyboardLayout::Qwerty,
+            0x100c => KeyboardLayout::Qwertz,
+            // Belgian Dutch shares the Belgian AZERTY layout
+            0x0813 => KeyboardLayout::Azerty,
+            _ => match language & 0x3ff {
+                // German, Czech, Slovak, Hungarian, Slovenian, Croatian
+                0x07 | 0x05 | 0x1b | 0x0e | 0x24 | 0x1a => KeyboardLayout::Qwertz,
+                0x0c => KeyboardLayout::Azerty,
+                _ => KeyboardLayout::Qwerty,
+            },
+        }
+    }
+
+    /// Label printed on the key at physical position `code` in this layout
+    pub fn key_label(self, code: &str) -> Option<&'static str> {
+        let remapped = match self {
+            KeyboardLayout::Qwerty => None,
+            KeyboardLayout::Qwertz => qwertz_label(code),
+            KeyboardLayout::Azerty => azerty_label(code),
+            KeyboardLayout::Dvorak => dvorak_label(code),
+        };
+        remapped.or_else(|| qwerty_label(code))
+    }
+}
+
+fn qwerty_label(code: &str) -> Option<&'static str> {
+    const LETTERS: [&str; 26] = [
+        "A", "B", "C", "D", "E", "F", "G", "H", "I", "J", "K", "L", "M", "N", "O", "P", "Q", "R",
+        "S", "T", "U", "V", "W", "X", "Y", "Z",
+    ];
+    const DIGITS: [&str; 10] = ["0", "1", "2", "3", "4", "5", "6", "7", "8", "9"];
+
+    if let Some(letter) = code.strip_prefix("Key") {
+        let c = letter.chars().next()?;
+        if letter.len() == 1 && c.is_ascii_uppercase() {
+            return Some(LETTERS[(c as u8 - b'A') as usize]);
+        }
+    }
+    if let Some(digit) = code.strip_prefix("Digit") {
+        let d = digit.parse::<usize>().ok()?;
+        return DIGITS.get(d).copied();
+    }
+
+    Some(match code {
+        "Minus" => "-",
+        "Equal" => "=",
+        "BracketLeft" => "[",
+        "BracketRight" => "]",
+        "Backslash" => "\\",
+        "Semicolon" => ";",
+        "Quote" => "'",
+        "Backquote" => "`",
+        "Comma" => ",",
+        "Period" => ".",
+        "Slash" => "/",
+        "IntlBackslash" => "\\",
+        "Space" => "Space",
+        "Enter" => "↩",
+        "Tab" => "⇥",
+        "Backspace" => "⌫",
+        "Delete" => "⌦",
+        "Escape" => "Esc",
+        "ArrowLeft" => "←",
+        "ArrowRight" => "→",
+        "ArrowUp" => "↑",
+        "ArrowDown" => "↓",
+        "Home" => "Home",
+        "End" => "End",
+        "PageUp" => "PgUp",
+        "PageDown" => "PgDn",
+        "CapsLock" => "⇪",
+        "F1" => "F1",
+        "F2" => "F2",
+        "F3" => "F3",
+        "F4" => "F4",
+        "F5" => "F5",
+        "F6" => "F6",
+        "F7" => "F7",
+        "F8" => "F8",
+        "F9" => "F9",
+        "F10" => "F10",
+        "F11" => "F11",
+        "F12" => "F12",
+        _ => return None,
+    })
+}
+
+fn qwertz_label(code: &str) -> Option<&'static str> {
+    Some(match code {
+        "KeyY" => "Z",
+        "KeyZ" => "Y",
+        "Minus" => "ß",
+        "Equal" => "´",
+        "BracketLeft" => "Ü",
+        "BracketRight" => "+",
+        "Semicolon" => "Ö",
+        "Quote" => "Ä",
+        "Backslash" => "#",
+        "Backquote" => "^",
+        "Slash" => "-",
+        "IntlBackslash" => "<",
+        _ => return None,
+    })
+}
+
+fn azerty_label(code: &str) -> Option<&'static str> {
+    // Digits keep their numeric labels: shortcuts like Ctrl+1 are read that way
+    Some(match code {
+        "KeyQ" => "A",
+        "KeyA" => "Q",
+        "KeyW" => "Z",
+        "KeyZ" => "W",
+        "KeyM" => ",",
+        "Semicolon" => "M",
+        "Comma" => ";",
+        "Period" => ":",
+        "Slash" => "!",
+        "Quote" => "ù",
+        "Minus" => ")",
+        "BracketLeft" => "^",
+        "BracketRight" => "$",
+        "Backslash" => "*",
+        "Backquote" => "²",
+        "IntlBackslash" => "<",
+        _ => return None,
+    })
+}
+
+fn dvorak_label(code: &str) -> Option<&'static str> {
+    Some(match code {
+        "KeyQ" => "'",
+        "KeyW" => ",",
+        "KeyE" => ".",
+        "KeyR" => "P",
+        "KeyT" => "Y",
+        "KeyY" => "F",
+        "KeyU" => "G",
+        "KeyI" => "C",
+        "KeyO" => "R",
+        "KeyP" => "L",
+        "BracketLeft" => "/",
+        "BracketRight" => "=",
+        "KeyS" => "O",
+        "KeyD" => "E",
+        "KeyF" => "U",
+        "KeyG" => "I",
+        "KeyH" => "D",
+        "KeyJ" => "H",
+        "KeyK" => "T",
+        "KeyL" => "N",
+        "Semicolon" => "S",
+        "Quote" => "-",
+        "KeyZ" => ";",
+        "KeyX" => "Q",
+        "KeyC" => "J",
+        "KeyV" => "K",
+        "KeyB" => "X",
+        "KeyN" => "B",
+        "Comma" => "W",
+        "Period" => "V",
+        "Slash" => "Z",
+        "Minus" => "[",
+        "Equal" => "]",
+        _ => return None,
+    })
+}
+
+/// W3C code of a key by its PC scancode (set 1), e.g. 0x10 for "KeyQ".
+/// Linux evdev keycodes are the same numbers for these keys.
+pub fn code_from_scancode(scancode: u32) -> Option<&'static str> {
+    const CODES: [&str; 89] = [
+        "",
+        "Escape",
+        "Digit1",
+        "Digit2",
+        "Digit3",
+        "Digit4",
+        "Digit5",
+        "Digit6",
+        "Digit7",
+        "Digit8",
+        "Digit9",
+        "Digit0",
+        "Minus",
+        "Equal",
+        "Backspace",
+        "Tab",
+        "KeyQ",
+        "KeyW",
+        "KeyE",
+        "KeyR",
+        "KeyT",
+        "KeyY",
+        "KeyU",
+        "KeyI",
+        "KeyO",
+        "KeyP",
+        "BracketLeft",
+        "BracketRight",
+        "Enter",
+        "ControlLeft",
+        "KeyA",
+        "KeyS",
+        "KeyD",
+        "KeyF",
+        "KeyG",
+        "KeyH",
+        "KeyJ",
+        "KeyK",
+        "KeyL",
+        "Semicolon",
+        "Quote",
+        "Backquote",
+        "ShiftLeft",
+        "Backslash",
+        "KeyZ",
+        "KeyX",
+        "KeyC",
+        "KeyV",
+        "KeyB",
+        "KeyN",
+        "KeyM",
+        "Comma",
+        "Period",
+        "Slash",
+        "ShiftRight",
+        "NumpadMultiply",
+        "AltLeft",
+        "Space",
+        "CapsLock",
+        "F1",
+        "F2",
+        "F3",
+        "F4",
+        "F5",
+        "F6",
+        "F7",
+        "F8",
+        "F9",
+        "F10",
+        "NumLock",
+        "ScrollLock",
+        "Numpad7",
+        "Numpad8",
+        "Numpad9",
+        "NumpadSubtract",
+        "Numpad4",
+        "Numpad5",
+        "Numpad6",
+        "NumpadAdd",
+        "Numpad1",
+        "Numpad2",
+        "Numpad3",
+        "Numpad0",
+        "NumpadDecimal",
+        "",
+        "",
+        "IntlBackslash",
+        "F11",
+        "F12",
+    ];
+    CODES
+        .get(scancode as usize)
+        .copied()
+        .filter(|code| !code.is_empty())
+}
+
+/// Whether `code` is a modifier key, which isn't a key press on its own
+pub fn is_modifier(code: &str) -> bool {
+    matches!(
+        code,
+        "ShiftLeft"
+            | "ShiftRight"
+            | "ControlLeft"
+            | "ControlRight"
+            | "AltLeft"
+            | "AltRight"
+            | "MetaLeft"
+            | "MetaRight"
+            | "CapsLock"
+    )
+}
+
+impl KeyboardInfo {
+    /// Full label for a keystroke, e.g. "⌘⇧Z" or "Ctrl+Shift+Z"
+    pub fn format_keystroke(&self, modifiers: Modifiers, code: &str) -> String {
+        let key = self.layout.key_label(code).unwrap_or(code);
+        match self.modifier_style {
+            ModifierStyle::Apple => {
+                // Apple's canonical order: Control, Option, Shift, Command
+                let mut label = String::new();
+                for (held, symbol) in [
+                    (modifiers.ctrl, "⌃"),
+                    (modifiers.alt, "⌥"),
+                    (modifiers.shift, "⇧"),
+                    (modifiers.meta, "⌘"),
+                ] {
+                    if held {
+                        label.push_str(symbol);
+                    }
+                }
+                label.push_str(key);
+                label
+            }
+            ModifierStyle::Pc => {
+                let mut parts: Vec<&str> = [
+                    (modifiers.ctrl, "Ctrl"),
+                    (modifiers.alt, "Alt"),
+                    (modifiers.shift, "Shift"),
+                    (modifiers.meta, "Super"),
+                ]
+                .iter()
+                .filter(|(held, _)| *held)
+                .map(|(_, name)| *name)
+                .collect();
+                parts.push(key);
+                parts.join("+")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layout_detection() {
+        assert_eq!(KeyboardLayout::from_xkb("de", None), KeyboardLayout::Qwertz);
+        assert_eq!(
+            KeyboardLayout::from_xkb("fr,us", None),
+            KeyboardLayout::Azerty
+        );
+        assert_eq!(
+            KeyboardLayout::from_xkb("us", Some("dvorak")),
+            KeyboardLayout::Dvorak
+        );
+        assert_eq!(KeyboardLayout::from_xkb("pl", None), KeyboardLayout::Qwerty);
+        assert_eq!(
+            KeyboardLayout::from_macos_name("German"),
+            KeyboardLayout::Qwertz
+        );
+        assert_eq!(
+            KeyboardLayout::from_macos_name("ABC"),
+            KeyboardLayout::Qwerty
+        );
+        assert_eq!(
+            KeyboardLayout::from_windows_klid(0x0000_0407),
+            KeyboardLayout::Qwertz
+        );
+        assert_eq!(
+            KeyboardLayout::from_windows_klid(0x0000_040c),
+            KeyboardLayout::Azerty
+        );
+        assert_eq!(
+            KeyboardLayout::from_windows_klid(0x0000_0c0c),
+            KeyboardLayout::Qwerty
+        );
+        assert_eq!(
+            KeyboardLayout::from_windows_klid(0x0001_0409),
+            KeyboardLayout::Dvorak
+        );
+    }
+
+    #[test]
+    fn test_key_labels_follow_layout() {
+        assert_eq!(KeyboardLayout::Qwerty.key_label("KeyZ"), Some("Z"));
+        assert_eq!(KeyboardLayout::Qwertz.key_label("KeyZ"), Some("Y"));
+        assert_eq!(KeyboardLayout::Azerty.key_label("KeyQ"), Some("A"));
+        assert_eq!(KeyboardLayout::Dvorak.key_label("KeyS"), Some("O"));
+        // Unaffected keys fall back to the QWERTY label
+        assert_eq!(KeyboardLayout::Azerty.key_label("KeyT"), Some("T"));
+        assert_eq!(KeyboardLayout::Azerty.key_label("Digit1"), Some("1"));
+    }
+
+    #[test]
+    fn test_code_from_scancode() {
+        assert_eq!(code_from_scancode(0x10), Some("KeyQ"));
+        assert_eq!(code_from_scancode(0x0B), Some("Digit0"));
+        assert_eq!(code_from_scancode(0x39), Some("Space"));
+        assert_eq!(code_from_scancode(0x58), Some("F12"));
+        assert_eq!(code_from_scancode(0), None);
+        assert_eq!(code_from_scancode(0x54), None);
+        assert!(is_modifier(code_from_scancode(0x2A).unwrap()));
+        assert!(!is_modifier("KeyA"));
+    }
+
+    #[test]
+    fn test_format_keystroke() {
+        let modifiers = Modifiers {
+            shift: true,
+            meta: true,
+            ..Default::default()
+        };
+        let apple = KeyboardInfo {
+            layout: KeyboardLayout::Qwertz,
+            modifier_style: ModifierStyle::Apple,
+        };
+        assert_eq!(apple.format_keystroke(modifiers, "KeyY"), "⇧⌘Z");
+
+        let pc = KeyboardInfo::default();
+        let ctrl = Modifiers {
+            ctrl: true,
+            ..Default::default()
+        };
+        assert_eq!(pc.format_keystroke(ctrl, "KeyC"), "Ctrl+C");
+    }
+}
@@ -0,0 +1,28 @@
+//! Recording and processing for glide, for apps that embed them instead of
+//! running the `glide` CLI
+//!
+//! A [`Recorder`] captures a display, window or region along with the
+//! cursor until its [`CancelToken`] is cancelled; a [`Processor`] turns the
+//! recording into the final video, reporting [`ProgressEvent`]s as it goes.
+
+pub mod bench;
+pub mod cancel;
+pub mod cursor_types;
+pub mod edit;
+pub mod keyboard;
+pub mod library;
+#[cfg(target_os = "linux")]
+pub mod linux;
+#[cfg(target_os = "macos")]
+pub mod macos;
+pub mod processing;
+pub mod recording;
+#[cfg(target_os = "windows")]
+pub mod windows;
+
+pub use cancel::CancelToken;
+pub use processing::progress::ProgressEvent;
+pub use processing::zoom::ZoomConfig;
+pub use processing::{ProcessOptions, Processor};
+pub use recording::metadata::RecordingMetadata;
+pub use recording::{RecordOptions, RecordTarget, Recorder};
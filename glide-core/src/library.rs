@@ -0,0 +1,418 @@
+//! Index of recordings kept under the config dir, for `glide library`
+
+use crate::recording::metadata::metadata_path_for_video;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local, TimeDelta};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+#[cfg(target_os = "macos")]
+const OPEN_COMMAND: &str = "open";
+#[cfg(target_os = "windows")]
+const OPEN_COMMAND: &str = "explorer";
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+const OPEN_COMMAND: &str = "xdg-open";
+
+/// One recording in the library
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LibraryEntry {
+    pub path: PathBuf,
+    pub recorded_at: DateTime<Local>,
+    /// Recording length (seconds)
+    pub duration: f64,
+    /// Processed outputs, oldest first
+    #[serde(default)]
+    pub outputs: Vec<PathBuf>,
+    /// The raw capture and its metadata were removed after processing
+    #[serde(default)]
+    pub raw_deleted: bool,
+}
+
+impl LibraryEntry {
+    pub fn is_processed(&self) -> bool {
+        !self.outputs.is_empty()
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Library {
+    entries: Vec<LibraryEntry>,
+    /// Delete raw captures of processed recordings this many days after
+    /// they were recorded
+    #[serde(default)]
+    pub retention_days: Option<u32>,
+}
+
+impl Library {
+    /// Location of the library index
+    pub fn path() -> Result<PathBuf> {
+        let config_dir = dirs::config_dir().context("Could not determine config directory")?;
+        Ok(config_dir.join("glide").join("library.json"))
+    }
+
+    pub fn load() -> Result<Self> {
+        Self::load_from(&Self::path()?)
+    }
+
+    pub fn save(&self) -> Result<()> {
+        self.save_to(&Self::path()?)
+    }
+
+    /// Load an index file, treating a missing file as an empty library
+    fn load_from(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let json = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read library index {:?}", path))?;
+        serde_json::from_str(&json).with_context(|| format!("Library index {:?} is corrupt", path))
+    }
+
+    fn save_to(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let json = serde_json::to_string_pretty(self)?;
+        fs::write(path, json).with_context(|| format!("Failed to write library index {:?}", path))
+    }
+
+    /// Entries newest first, as numbered by `glide library list`
+    pub fn entries(&self) -> Vec<&LibraryEntry> {
+        let mut entries: Vec<_> = self.entries.iter().collect();
+        entries.sort_by_key(|e| std::cmp::Reverse(e.recorded_at));
+        entries
+    }
+
+    /// Add a recording, replacing any previous entry for the same file
+    pub fn add_recording(&mut self, path: &Path, duration: f64) {
+        let path = absolute(path);
+        self.entries.retain(|e| e.path != path);
+        self.entries.push(LibraryEntry {
+            path,
+            recorded_at: Local::now(),
+            duration,
+            outputs: Vec::new(),
+            raw_deleted: false,
+        });
+    }
+
+    /// Note that `input` was processed into `output`, indexing it if needed
+    pub fn mark_processed(&mut self, input: &Path, duration: f64, output: &Path) {
+        let input = absolute(input);
+        let output = absolute(output);
+        let entry = match self.entries.iter().position(|e| e.path == input) {
+            Some(index) => &mut self.entries[index],
+            None => {
+                self.add_recording(&input, duration);
+                self.entries.last_mut().unwrap()
+            }
+        };
+        entry.outputs.retain(|o| *o != output);
+        entry.outputs.push(output);
+    }
+
+    /// Note that the raw capture of `input` has been removed
+    pub fn mark_raw_deleted(&mut self, input: &Path) {
+        let input = absolute(input);
+        if let Some(entry) = self.entries.iter_mut().find(|e| e.path == input) {
+            entry.raw_deleted = true;
+        }
+    }
+
+    /// Forget recordings and outputs whose files no longer exist.
+    /// Returns the number of recordings removed.
+    pub fn clean(&mut self) -> usize {
+        let before = self.entries.len();
+        for entry in &mut self.entries {
+            entry.outputs.retain(|o| o.exists());
+        }
+        // Recordings cleaned up after processing stay while their outputs do
+        self.entries
+            .retain(|e| e.path.exists() || (e.raw_deleted && e.is_processed()));
+        before - self.entries.len()
+    }
+
+    /// Raw captures that the retention policy says can go: processed, with an
+    /// output still on disk, and recorded more than `retention_days` ago
+    pub fn expired(&self, now: DateTime<Local>) -> Vec<PathBuf> {
+        let Some(days) = self.retention_days else {
+            return Vec::new();
+        };
+        let cutoff = now - TimeDelta::days(days as i64);
+        self.entries
+            .iter()
+            .filter(|e| !e.raw_deleted && e.recorded_at <= cutoff)
+            .filter(|e| e.path.exists() && e.outputs.iter().any(|o| o.exists()))
+            .map(|e| e.path.clone())
+            .collect()
+    }
+
+    /// Delete raw captures past the retention period.
+    /// Returns the number of recordings removed and bytes freed.
+    pub fn apply_retention(&mut self) -> (usize, u64) {
+        let mut removed = 0;
+        let mut freed = 0;
+        for path in self.expired(Local::now()) {
+            match delete_raw(&path) {
+                Ok(bytes) => {
+                    self.mark_raw_deleted(&path);
+                    removed += 1;
+                    freed += bytes;
+                }
+                Err(e) => tracing::warn!("{:#}", e),
+            }
+        }
+        (removed, freed)
+    }
+}
+
+/// Remove a raw capture and its sidecar metadata, returning the bytes freed
+pub fn delete_raw(video: &Path) -> Result<u64> {
+    let mut freed = 0;
+    for path in [video.to_path_buf(), metadata_path_for_video(video)] {
+        if let Ok(meta) = fs::metadata(&path) {
+            fs::remove_file(&path)
+                .with_context(|| format!("Failed to delete {}", path.display()))?;
+            freed += meta.len();
+        }
+    }
+    Ok(freed)
+}
+
+/// Best-effort absolute path, so entries match regardless of the working directory
+fn absolute(path: &Path) -> PathBuf {
+    fs::canonicalize(path)
+        .or_else(|_| std::path::absolute(path))
+        .unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Update the library, warning instead of failing the command that triggered it
+pub fn update(f: impl FnOnce(&mut Library)) {
+    let result = Library::load().and_then(|mut library| {
+        f(&mut library);
+        library.save()
+    });
+    if let Err(e) = result {
+        tracing::warn!("failed to update recording library: {:#}", e);
+    }
+}
+
+/// Delete raw captures past the retention period, as `glide process` does
+/// once its render succeeds. Failures are only warned about.
+pub fn enforce_retention() {
+    update(|library| {
+        let (removed, freed) = library.apply_retention();
+        if removed > 0 {
+            tracing::info!(
+                "Retention: deleted {} older raw recording(s), freed {}",
+                removed,
+                format_bytes(freed)
+            );
+        }
+    });
+}
+
+/// The library as a numbered table, for `glide library list`
+pub fn format_list() -> Result<String> {
+    let library = Library::load()?;
+    let entries = library.entries();
+    if entries.is_empty() {
+        return Ok("No recordings in the library yet.".to_string());
+    }
+
+    let mut table = "Recordings:".to_string();
+    for (i, entry) in entries.iter().enumerate() {
+        let status = if entry.raw_deleted {
+            "output only".to_string()
+        } else if !entry.path.exists() {
+            "missing".to_string()
+        } else if !entry.is_processed() {
+            "raw".to_string()
+        } else if entry.outputs.len() == 1 {
+            "processed".to_string()
+        } else {
+            format!("processed x{}", entry.outputs.len())
+        };
+        table.push_str(&format!(
+            "\n  [{number}] {date}  {duration:>6}  {status:<13} {path}",
+            number = i + 1,
+            date = entry.recorded_at.format("%Y-%m-%d %H:%M"),
+            duration = format_duration(entry.duration),
+            path = entry.path.display(),
+        ));
+    }
+    Ok(table)
+}
+
+/// Open a recording (or its latest processed output) in the default player
+pub fn open_entry(number: usize, processed: bool) -> Result<()> {
+    let library = Library::load()?;
+    let entries = library.entries();
+    let entry = number
+        .checked_sub(1)
+        .and_then(|i| entries.get(i))
+        .ok_or_else(|| {
+            anyhow::anyhow!("Recording {} not found (see `glide library list`)", number)
+        })?;
+
+    let path = if processed || entry.raw_deleted {
+        entry
+            .outputs
+            .last()
+            .ok_or_else(|| anyhow::anyhow!("Recording {} has not been processed yet", number))?
+    } else {
+        &entry.path
+    };
+    if !path.exists() {
+        anyhow::bail!(
+            "{} no longer exists (run `glide library clean`)",
+            path.display()
+        );
+    }
+
+    Command::new(OPEN_COMMAND)
+        .arg(path)
+        .status()
+        .with_context(|| format!("Failed to run {}", OPEN_COMMAND))?;
+    Ok(())
+}
+
+/// Apply the retention policy, then drop entries for deleted files
+pub fn clean() -> Result<()> {
+    let mut library = Library::load()?;
+    let (expired, freed) = library.apply_retention();
+    if expired > 0 {
+        tracing::info!(
+            "Deleted {} raw recording(s) past retention, freed {}.",
+            expired,
+            format_bytes(freed)
+        );
+    }
+    let removed = library.clean();
+    library.save()?;
+    tracing::info!(
+        "Removed {} missing recording(s), {} left.",
+        removed,
+        library.entries.len()
+    );
+    Ok(())
+}
+
+/// Show or change how long raw captures are kept after processing
+pub fn set_retention(days: Option<u32>, off: bool) -> Result<()> {
+    let mut library = Library::load()?;
+    if off {
+        library.retention_days = None;
+    } else if days.is_some() {
+        library.retention_days = days;
+    }
+    library.save()?;
+
+    match library.retention_days {
+        Some(days) => tracing::info!(
+            "Raw recordings are deleted {} day(s) after recording, once processed (on `glide process` and `glide library clean`).",
+            days
+        ),
+        None => tracing::info!("Raw recordings are kept indefinitely."),
+    }
+    Ok(())
+}
+
+pub fn format_bytes(bytes: u64) -> String {
+    const MB: f64 = 1024.0 * 1024.0;
+    if bytes as f64 >= 1024.0 * MB {
+        format!("{:.1} GB", bytes as f64 / (1024.0 * MB))
+    } else {
+        format!("{:.1} MB", bytes as f64 / MB)
+    }
+}
+
+pub fn format_duration(seconds: f64) -> String {
+    let total = seconds.max(0.0).round() as u64;
+    format!("{}:{:02}", total / 60, total % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mark_processed_and_clean() {
+        let dir = tempfile::tempdir().unwrap();
+        let raw = dir.path().join("raw.mp4");
+        let gone = dir.path().join("gone.mp4");
+        let out = dir.path().join("final.mp4");
+        fs::write(&raw, b"").unwrap();
+        fs::write(&out, b"").unwrap();
+
+        let mut library = Library::default();
+        library.add_recording(&raw, 12.0);
+        library.mark_processed(&raw, 12.0, &out);
+        library.mark_processed(&raw, 12.0, &out);
+        // Processing an unindexed file indexes it
+        library.mark_processed(&gone, 3.0, &out);
+        assert_eq!(library.entries().len(), 2);
+
+        let entry = library
+            .entries
+            .iter()
+            .find(|e| e.path == absolute(&raw))
+            .unwrap();
+        assert!(entry.is_processed());
+        assert_eq!(entry.outputs.len(), 1);
+
+        assert_eq!(library.clean(), 1);
+        assert_eq!(library.entries().len(), 1);
+    }
+
+    #[test]
+    fn test_retention_only_expires_processed_recordings() {
+        let dir = tempfile::tempdir().unwrap();
+        let done = dir.path().join("done.mp4");
+        let pending = dir.path().join("pending.mp4");
+        let out = dir.path().join("final.mp4");
+        for path in [&done, &pending, &out] {
+            fs::write(path, b"data").unwrap();
+        }
+        fs::write(metadata_path_for_video(&done), b"{}").unwrap();
+
+        let mut library = Library::default();
+        library.add_recording(&done, 5.0);
+        library.add_recording(&pending, 5.0);
+        library.mark_processed(&done, 5.0, &out);
+
+        let later = Local::now() + TimeDelta::days(8);
+        assert!(library.expired(later).is_empty());
+        library.retention_days = Some(7);
+        assert!(library.expired(Local::now()).is_empty());
+        assert_eq!(library.expired(later), vec![absolute(&done)]);
+
+        // Counted from the recording, however late it was processed
+        library.entries[0].recorded_at = Local::now() - TimeDelta::days(8);
+        assert_eq!(library.expired(Local::now()), vec![absolute(&done)]);
+
+        assert_eq!(delete_raw(&done).unwrap(), 6);
+        library.mark_raw_deleted(&done);
+        assert!(!metadata_path_for_video(&done).exists());
+        // Kept in the library while its output exists
+        assert_eq!(library.clean(), 0);
+        assert!(library.expired(later).is_empty());
+    }
+
+    #[test]
+    fn test_load_save_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let index = dir.path().join("glide").join("library.json");
+        assert!(Library::load_from(&index).unwrap().entries().is_empty());
+
+        let mut library = Library::default();
+        library.add_recording(&dir.path().join("a.mp4"), 61.4);
+        library.save_to(&index).unwrap();
+
+        let loaded = Library::load_from(&index).unwrap();
+        assert_eq!(loaded.entries().len(), 1);
+        assert_eq!(format_duration(loaded.entries()[0].duration), "1:01");
+    }
+}
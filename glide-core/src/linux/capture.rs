@@ -1,12 +1,17 @@
-//! Linux X11 screen capture using FFmpeg x11grab
+//! Linux screen capture: FFmpeg x11grab on X11, the ScreenCast portal on Wayland
 
+use crate::linux::wayland::{self, PortalSource, PortalStream};
+use crate::linux::window::list_windows;
+use crate::recording::encoder::PcmStream;
+use crate::recording::exclude::{drawbox_filter, Exclusions};
 use anyhow::{Context, Result};
 use std::io::{BufReader, Read};
 use std::process::{Child, Command, Stdio};
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
 use std::sync::mpsc::{self, Receiver};
 use std::sync::Arc;
 use std::thread;
+use std::time::Instant;
 
 /// A captured video frame with raw BGRA pixel data
 pub struct CapturedFrame {
@@ -17,20 +22,14 @@ pub struct CapturedFrame {
 }
 
 /// Capture configuration
+#[derive(Default)]
 pub struct CaptureConfig {
     pub show_cursor: bool,
     pub width: u32,
     pub height: u32,
-}
-
-impl Default for CaptureConfig {
-    fn default() -> Self {
-        Self {
-            show_cursor: false,
-            width: 0,
-            height: 0,
-        }
-    }
+    pub capture_audio: bool,
+    /// Windows to black out
+    pub exclude: Exclusions,
 }
 
 /// Wrapper type to mimic ScreenCaptureKit's display handle
@@ -124,17 +123,46 @@ pub struct CaptureSession {
     receiver: Receiver<CapturedFrame>,
     running: Arc<AtomicBool>,
     reader_thread: Option<thread::JoinHandle<()>>,
+    /// Frames captured but discarded because the encoder fell behind
+    dropped: Arc<AtomicU64>,
+    /// Frames waiting in the channel for the encoder
+    queued: Arc<AtomicUsize>,
+    /// Portal stream the frames come from (Wayland only)
+    portal: Option<PortalStream>,
     pub width: u32,
     pub height: u32,
 }
 
 impl CaptureSession {
     pub fn recv(&self) -> Option<CapturedFrame> {
-        self.receiver.recv().ok()
+        let frame = self.receiver.recv().ok();
+        if frame.is_some() {
+            self.queued.fetch_sub(1, Ordering::Relaxed);
+        }
+        frame
     }
 
     pub fn try_recv(&self) -> Option<CapturedFrame> {
-        self.receiver.try_recv().ok()
+        let frame = self.receiver.try_recv().ok();
+        if frame.is_some() {
+            self.queued.fetch_sub(1, Ordering::Relaxed);
+        }
+        frame
+    }
+
+    /// Frames dropped so far because the channel to the encoder was full
+    pub fn dropped_frames(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Frames captured but not yet taken by the encoder
+    pub fn queued_frames(&self) -> usize {
+        self.queued.load(Ordering::Relaxed)
+    }
+
+    /// System audio isn't captured on Linux
+    pub fn take_audio(&mut self) -> Option<PcmStream> {
+        None
     }
 
     pub fn is_running(&self) -> bool {
@@ -166,6 +194,10 @@ impl CaptureSession {
             let _ = handle.join();
         }
 
+        if let Some(portal) = self.portal.take() {
+            portal.close();
+        }
+
         Ok(())
     }
 }
@@ -175,6 +207,18 @@ pub fn start_display_capture(
     display: &X11Display,
     config: &CaptureConfig,
 ) -> Result<CaptureSession> {
+    reject_audio(config)?;
+    if wayland::is_wayland_session() {
+        // Without XWayland the display is a stand-in for the dialog's pick
+        let requested = (!display.display_string.is_empty()).then_some((display.x, display.y));
+        let session = start_portal_capture(PortalSource::Monitor, config)?;
+        let shared = session.portal.as_ref().and_then(|portal| portal.position);
+        if let Some(why) = wayland::cursor_mismatch(requested, shared) {
+            tracing::warn!("{}", why);
+        }
+        return Ok(session);
+    }
+
     let width = if config.width > 0 {
         config.width
     } else {
@@ -207,15 +251,13 @@ pub fn start_display_capture(
         cmd.args(["-draw_mouse", "0"]);
     }
 
-    cmd.args([
-        "-i",
-        &display_input,
-        "-pix_fmt",
-        "bgra",
-        "-f",
-        "rawvideo",
-        "-",
-    ]);
+    cmd.args(["-i", &display_input]);
+    if let Some(filter) =
+        exclusion_filter(&config.exclude, (display.x, display.y), (width, height))?
+    {
+        cmd.args(["-vf", &filter]);
+    }
+    cmd.args(["-pix_fmt", "bgra", "-f", "rawvideo", "-"]);
 
     cmd.stdout(Stdio::piped());
     cmd.stderr(Stdio::null());
@@ -225,6 +267,11 @@ pub fn start_display_capture(
 
 /// Start capturing a specific window
 pub fn start_window_capture(window: &X11Window, config: &CaptureConfig) -> Result<CaptureSession> {
+    reject_audio(config)?;
+    if wayland::is_wayland_session() {
+        return start_portal_capture(PortalSource::Window, config);
+    }
+
     let width = if config.width > 0 {
         config.width
     } else {
@@ -257,15 +304,12 @@ pub fn start_window_capture(window: &X11Window, config: &CaptureConfig) -> Resul
         cmd.args(["-draw_mouse", "0"]);
     }
 
-    cmd.args([
-        "-i",
-        &display_input,
-        "-pix_fmt",
-        "bgra",
-        "-f",
-        "rawvideo",
-        "-",
-    ]);
+    cmd.args(["-i", &display_input]);
+    if let Some(filter) = exclusion_filter(&config.exclude, (window.x, window.y), (width, height))?
+    {
+        cmd.args(["-vf", &filter]);
+    }
+    cmd.args(["-pix_fmt", "bgra", "-f", "rawvideo", "-"]);
 
     cmd.stdout(Stdio::piped());
     cmd.stderr(Stdio::null());
@@ -273,9 +317,87 @@ pub fn start_window_capture(window: &X11Window, config: &CaptureConfig) -> Resul
     start_capture_process(cmd, width, height)
 }
 
-/// Start the FFmpeg capture process
+/// Start capturing a rectangle of a display, given relative to its top-left
+pub fn start_region_capture(
+    display: &X11Display,
+    region: (i32, i32, u32, u32),
+    config: &CaptureConfig,
+) -> Result<CaptureSession> {
+    if wayland::is_wayland_session() {
+        anyhow::bail!(
+            "Region recording isn't supported on Wayland; record a display or window instead"
+        );
+    }
+
+    // x11grab already captures any rectangle: treat the region as a window
+    let (x, y, width, height) = region;
+    let window = X11Window {
+        id: 0,
+        x: display.x + x,
+        y: display.y + y,
+        width,
+        height,
+        display_string: display.display_string.clone(),
+    };
+    start_window_capture(&window, config)
+}
+
+/// Capturing just one app's windows needs ScreenCaptureKit
+pub fn start_app_capture(
+    _display: &X11Display,
+    app: &str,
+    _config: &CaptureConfig,
+) -> Result<CaptureSession> {
+    anyhow::bail!(
+        "Recording all of {}'s windows is only supported on macOS; use --window instead",
+        app
+    )
+}
+
+/// FFmpeg filter blacking out the excluded windows in a capture of `size`
+/// at `origin` (root window coordinates)
+fn exclusion_filter(
+    exclude: &Exclusions,
+    origin: (i32, i32),
+    size: (u32, u32),
+) -> Result<Option<String>> {
+    if exclude.is_empty() {
+        return Ok(None);
+    }
+    let boxes: Vec<_> = list_windows()?
+        .into_iter()
+        .filter(|w| exclude.matches(w.id, &w.owner))
+        .map(|w| w.bounds)
+        .collect();
+    exclude.warn_if_unmatched(boxes.len());
+    Ok(drawbox_filter(&boxes, origin, size))
+}
+
+/// Capture whatever the user shares in the portal dialog
+fn start_portal_capture(source: PortalSource, config: &CaptureConfig) -> Result<CaptureSession> {
+    if !config.exclude.is_empty() {
+        anyhow::bail!("Windows can't be excluded on Wayland; share a single window instead");
+    }
+    wayland::check_gstreamer()?;
+    let stream = PortalStream::open(source, config.show_cursor)?;
+    let (width, height) = wayland::frame_size(stream.size()?)?;
+
+    let mut session = start_capture_process(stream.command(width, height), width, height)
+        .context("Failed to start GStreamer for PipeWire capture")?;
+    session.portal = Some(stream);
+    Ok(session)
+}
+
+fn reject_audio(config: &CaptureConfig) -> Result<()> {
+    if config.capture_audio {
+        anyhow::bail!("System audio capture is only supported on macOS; record a monitor source with --mic instead (see `pactl list short sources`)");
+    }
+    Ok(())
+}
+
+/// Start the capture process (FFmpeg or GStreamer) writing raw BGRA frames to stdout
 fn start_capture_process(mut cmd: Command, width: u32, height: u32) -> Result<CaptureSession> {
-    let mut ffmpeg_process = cmd.spawn().context("Failed to start FFmpeg for capture")?;
+    let mut ffmpeg_process = cmd.spawn().context("Failed to start capture process")?;
 
     let stdout = ffmpeg_process
         .stdout
@@ -285,6 +407,10 @@ fn start_capture_process(mut cmd: Command, width: u32, height: u32) -> Result<Ca
     let (sender, receiver) = mpsc::sync_channel(3);
     let running = Arc::new(AtomicBool::new(true));
     let running_clone = Arc::clone(&running);
+    let dropped = Arc::new(AtomicU64::new(0));
+    let dropped_clone = Arc::clone(&dropped);
+    let queued = Arc::new(AtomicUsize::new(0));
+    let queued_clone = Arc::clone(&queued);
 
     let frame_size = (width * height * 4) as usize; // BGRA = 4 bytes per pixel
     let w = width as usize;
@@ -293,14 +419,14 @@ fn start_capture_process(mut cmd: Command, width: u32, height: u32) -> Result<Ca
     let reader_thread = thread::spawn(move || {
         let mut reader = BufReader::new(stdout);
         let mut frame_buffer = vec![0u8; frame_size];
-        let mut frame_count: u64 = 0;
+        let started = Instant::now();
 
         while running_clone.load(Ordering::Relaxed) {
             // Read exactly one frame
             match reader.read_exact(&mut frame_buffer) {
                 Ok(()) => {
-                    let timestamp = frame_count as f64 / 60.0;
-                    frame_count += 1;
+                    // Read as soon as the capture process produces it
+                    let timestamp = started.elapsed().as_secs_f64();
 
                     let frame = CapturedFrame {
                         data: frame_buffer.clone(),
@@ -309,9 +435,12 @@ fn start_capture_process(mut cmd: Command, width: u32, height: u32) -> Result<Ca
                         timestamp,
                     };
 
-                    // Send frame (stop if receiver is closed)
+                    // Counted before sending so the receiver never sees it negative
+                    queued_clone.fetch_add(1, Ordering::Relaxed);
                     if sender.try_send(frame).is_err() {
-                        // Channel full or closed, wait a bit
+                        // Channel full or closed: the frame is lost, wait a bit
+                        queued_clone.fetch_sub(1, Ordering::Relaxed);
+                        dropped_clone.fetch_add(1, Ordering::Relaxed);
                         thread::sleep(std::time::Duration::from_millis(1));
                         continue;
                     }
@@ -329,6 +458,9 @@ fn start_capture_process(mut cmd: Command, width: u32, height: u32) -> Result<Ca
         receiver,
         running,
         reader_thread: Some(reader_thread),
+        dropped,
+        queued,
+        portal: None,
         width,
         height,
     })
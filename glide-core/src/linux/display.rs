@@ -0,0 +1,194 @@
+//! Linux display enumeration using the X11 RandR extension (XWayland on Wayland)
+
+use crate::keyboard::{KeyboardInfo, KeyboardLayout, ModifierStyle};
+use crate::linux::wayland;
+use anyhow::{Context, Result};
+use std::process::Command;
+use x11rb::connection::Connection;
+use x11rb::protocol::randr::{self, ConnectionExt as RandrExt};
+use x11rb::rust_connection::RustConnection;
+
+pub struct DisplayInfo {
+    pub index: usize,
+    /// 0 on Wayland without XWayland: the size is only known once the
+    /// portal's stream is negotiated
+    pub width: u32,
+    pub height: u32,
+    pub x: i32,
+    pub y: i32,
+    pub is_main: bool,
+    pub scale_factor: f64,
+    /// X11 display string (e.g., ":0")
+    pub display_string: String,
+}
+
+pub fn list_displays() -> Result<Vec<DisplayInfo>> {
+    if !wayland::is_wayland_session() {
+        return list_x11_displays();
+    }
+
+    // XWayland mirrors the monitors through RandR; without it, all we can
+    // offer is whichever monitor gets picked in the portal's sharing
+    // dialog, whose size the capture takes from the stream
+    Ok(list_x11_displays().unwrap_or_else(|_| {
+        vec![DisplayInfo {
+            index: 0,
+            width: 0,
+            height: 0,
+            x: 0,
+            y: 0,
+            is_main: true,
+            scale_factor: 1.0,
+            display_string: String::new(),
+        }]
+    }))
+}
+
+fn list_x11_displays() -> Result<Vec<DisplayInfo>> {
+    let (conn, screen_num) =
+        RustConnection::connect(None).context("Failed to connect to X11 display")?;
+
+    let setup = conn.setup();
+    let screen = &setup.roots[screen_num];
+    let root = screen.root;
+
+    // Query RandR extension
+    let resources = conn
+        .randr_get_screen_resources(root)
+        .context("Failed to query RandR screen resources")?
+        .reply()
+        .context("Failed to get RandR screen resources reply")?;
+
+    let mut displays = Vec::new();
+    let mut index = 0;
+
+    // Iterate through CRTCs to find active monitors
+    for crtc in &resources.crtcs {
+        let crtc_info = conn
+            .randr_get_crtc_info(*crtc, resources.config_timestamp)
+            .context("Failed to query CRTC info")?
+            .reply()
+            .context("Failed to get CRTC info reply")?;
+
+        // Skip disabled CRTCs (no outputs connected or zero size)
+        if crtc_info.outputs.is_empty() || crtc_info.width == 0 || crtc_info.height == 0 {
+            continue;
+        }
+
+        // Check if any output is connected
+        let has_connected_output = crtc_info.outputs.iter().any(|output| {
+            conn.randr_get_output_info(*output, resources.config_timestamp)
+                .ok()
+                .and_then(|cookie| cookie.reply().ok())
+                .map(|output_info| output_info.connection == randr::Connection::CONNECTED)
+                .unwrap_or(false)
+        });
+
+        if !has_connected_output {
+            continue;
+        }
+
+        // Get the display string from environment or default
+        let display_string = std::env::var("DISPLAY").unwrap_or_else(|_| ":0".to_string());
+
+        // First display is considered main (in X11, we can also check for primary output)
+        let is_main = index == 0;
+
+        displays.push(DisplayInfo {
+            index,
+            width: crtc_info.width as u32,
+            height: crtc_info.height as u32,
+            x: crtc_info.x as i32,
+            y: crtc_info.y as i32,
+            is_main,
+            scale_factor: 1.0, // X11 typically doesn't have HiDPI scaling at the display level
+            display_string,
+        });
+
+        index += 1;
+    }
+
+    // If no CRTCs found, fall back to screen dimensions
+    if displays.is_empty() {
+        let display_string = std::env::var("DISPLAY").unwrap_or_else(|_| ":0".to_string());
+        displays.push(DisplayInfo {
+            index: 0,
+            width: screen.width_in_pixels as u32,
+            height: screen.height_in_pixels as u32,
+            x: 0,
+            y: 0,
+            is_main: true,
+            scale_factor: 1.0,
+            display_string,
+        });
+    }
+
+    Ok(displays)
+}
+
+/// Standard X cursor size in pixels (Xcursor's default theme size)
+const DEFAULT_XCURSOR_SIZE: f64 = 24.0;
+
+/// The user's cursor size relative to the standard size (1.0 = standard).
+///
+/// Checked in the order X clients resolve it: `XCURSOR_SIZE`, the
+/// `Xcursor.size` X resource, then the GNOME `cursor-size` setting.
+pub fn cursor_size_multiplier() -> Option<f64> {
+    let size = std::env::var("XCURSOR_SIZE")
+        .ok()
+        .and_then(|v| v.trim().parse::<u32>().ok())
+        .or_else(|| {
+            let output = Command::new("xrdb").arg("-query").output().ok()?;
+            parse_xcursor_size(&String::from_utf8_lossy(&output.stdout))
+        })
+        .or_else(|| {
+            let output = Command::new("gsettings")
+                .args(["get", "org.gnome.desktop.interface", "cursor-size"])
+                .output()
+                .ok()?;
+            String::from_utf8_lossy(&output.stdout).trim().parse().ok()
+        })?;
+
+    (size > 0).then(|| size as f64 / DEFAULT_XCURSOR_SIZE)
+}
+
+/// The active XKB keyboard layout (from `setxkbmap -query`)
+pub fn keyboard_info() -> Option<KeyboardInfo> {
+    let output = Command::new("setxkbmap").arg("-query").output().ok()?;
+    let query = String::from_utf8_lossy(&output.stdout);
+    let field = |name: &str| {
+        query.lines().find_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            (key.trim() == name).then(|| value.trim().to_string())
+        })
+    };
+    let layout = field("layout")?;
+    Some(KeyboardInfo {
+        layout: KeyboardLayout::from_xkb(&layout, field("variant").as_deref()),
+        modifier_style: ModifierStyle::Pc,
+    })
+}
+
+/// Find `Xcursor.size` in `xrdb -query` output
+fn parse_xcursor_size(resources: &str) -> Option<u32> {
+    resources.lines().find_map(|line| {
+        let (key, value) = line.split_once(':')?;
+        if key.trim() == "Xcursor.size" {
+            value.trim().parse().ok()
+        } else {
+            None
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_xcursor_size() {
+        let resources = "Xft.dpi:\t96\nXcursor.size:\t48\nXcursor.theme:\tAdwaita\n";
+        assert_eq!(parse_xcursor_size(resources), Some(48));
+        assert_eq!(parse_xcursor_size("Xft.dpi:\t96\n"), None);
+    }
+}
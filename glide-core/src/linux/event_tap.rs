@@ -0,0 +1,451 @@
+//! Linux X11 cursor and keyboard tracking using polling, and global hotkeys
+//! using key grabs
+
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use x11rb::connection::Connection;
+use x11rb::protocol::xfixes::{ConnectionExt as _, CursorNotifyMask};
+use x11rb::protocol::xproto::{Atom, ConnectionExt, GrabMode, ModMask, Window};
+use x11rb::protocol::Event;
+use x11rb::rust_connection::RustConnection;
+
+use crate::cursor_types::{ClickTarget, CursorEvent, CursorShape, EventType, KeyCapture, KeyPress};
+use crate::keyboard::{self, Modifiers};
+use crate::linux::wayland;
+use crate::recording::hotkey::{Hotkey, HotkeyKey};
+
+pub struct CursorTracker {
+    events: Arc<Mutex<Vec<CursorEvent>>>,
+    start_time: Instant,
+    keys: KeyCapture,
+    stop_flag: Arc<AtomicBool>,
+    thread_handle: Option<thread::JoinHandle<()>>,
+}
+
+impl CursorTracker {
+    pub fn new(keys: KeyCapture) -> Self {
+        Self {
+            events: Arc::new(Mutex::new(Vec::new())),
+            start_time: Instant::now(),
+            keys,
+            stop_flag: Arc::new(AtomicBool::new(false)),
+            thread_handle: None,
+        }
+    }
+
+    pub fn start(&mut self) -> Result<()> {
+        self.start_time = Instant::now();
+        self.stop_flag.store(false, Ordering::SeqCst);
+
+        let events = Arc::clone(&self.events);
+        let start_time = self.start_time;
+        let stop_flag = Arc::clone(&self.stop_flag);
+        let keys = self.keys;
+
+        let handle = thread::spawn(move || {
+            run_polling_tracking(events, start_time, keys, stop_flag);
+        });
+
+        self.thread_handle = Some(handle);
+        Ok(())
+    }
+
+    /// Number of events recorded so far
+    pub fn event_count(&self) -> usize {
+        self.events.lock().unwrap().len()
+    }
+
+    pub fn stop(&mut self) -> (Vec<CursorEvent>, f64) {
+        let duration = self.start_time.elapsed().as_secs_f64();
+
+        // Signal the thread to stop
+        self.stop_flag.store(true, Ordering::SeqCst);
+
+        // Wait for thread with timeout
+        if let Some(handle) = self.thread_handle.take() {
+            // Give it 500ms to stop gracefully
+            let start = Instant::now();
+            while !handle.is_finished() && start.elapsed() < Duration::from_millis(500) {
+                thread::sleep(Duration::from_millis(10));
+            }
+            if handle.is_finished() {
+                let _ = handle.join();
+            }
+            // If not finished, just abandon the thread
+        }
+
+        let events = self.events.lock().unwrap();
+        (events.clone(), duration)
+    }
+
+    /// Bounds of clicked UI elements. X11 has no accessibility bounds, so
+    /// click callouts fall back to a fixed-size box around the click.
+    pub fn click_targets(&self) -> Vec<ClickTarget> {
+        Vec::new()
+    }
+}
+
+/// Poll cursor position using XQueryPointer, and pressed keys using
+/// XQueryKeymap. The cursor's shape comes from XFixes notifications.
+fn run_polling_tracking(
+    events: Arc<Mutex<Vec<CursorEvent>>>,
+    start_time: Instant,
+    keys: KeyCapture,
+    stop_flag: Arc<AtomicBool>,
+) {
+    let Ok((conn, screen_num)) = RustConnection::connect(None) else {
+        tracing::error!("Failed to connect to X11 display for cursor tracking");
+        return;
+    };
+
+    let setup = conn.setup();
+    let screen = &setup.roots[screen_num];
+    let root = screen.root;
+
+    let mut last_x: i16 = 0;
+    let mut last_y: i16 = 0;
+    let mut last_buttons: u16 = 0;
+
+    let mut last_keymap = [0u8; 32];
+
+    let mut shape = watch_cursor_shape(&conn, root);
+    let mut shape_names = HashMap::new();
+
+    // Poll at ~120Hz
+    let poll_interval = Duration::from_micros(8333);
+
+    loop {
+        // Check for stop signal
+        if stop_flag.load(Ordering::Relaxed) {
+            break;
+        }
+
+        // Query pointer position and button state
+        let Some(reply) = conn
+            .query_pointer(root)
+            .ok()
+            .and_then(|cookie| cookie.reply().ok())
+        else {
+            thread::sleep(poll_interval);
+            continue;
+        };
+
+        let x = reply.root_x;
+        let y = reply.root_y;
+        let buttons = reply.mask.bits();
+
+        let timestamp = start_time.elapsed().as_secs_f64();
+
+        while let Ok(Some(event)) = conn.poll_for_event() {
+            if let Event::XfixesCursorNotify(notify) = event {
+                shape = *shape_names
+                    .entry(notify.name)
+                    .or_insert_with(|| cursor_shape_named(&conn, notify.name));
+            }
+        }
+
+        // Check for button state changes (clicks)
+        let button1_now = (buttons & 0x100) != 0; // Button 1 (left)
+        let button3_now = (buttons & 0x400) != 0; // Button 3 (right)
+        let button1_was = (last_buttons & 0x100) != 0;
+        let button3_was = (last_buttons & 0x400) != 0;
+
+        if let Ok(mut events) = events.lock() {
+            // Left click (button pressed)
+            if button1_now && !button1_was {
+                events.push(CursorEvent {
+                    x: x as f64,
+                    y: y as f64,
+                    timestamp,
+                    event_type: EventType::LeftClick,
+                    display: None,
+                    shape,
+                });
+            }
+
+            // Right click (button pressed)
+            if button3_now && !button3_was {
+                events.push(CursorEvent {
+                    x: x as f64,
+                    y: y as f64,
+                    timestamp,
+                    event_type: EventType::RightClick,
+                    display: None,
+                    shape,
+                });
+            }
+
+            // Movement (only record if position changed significantly)
+            if (x != last_x || y != last_y) && (x - last_x).abs() + (y - last_y).abs() > 2 {
+                events.push(CursorEvent {
+                    x: x as f64,
+                    y: y as f64,
+                    timestamp,
+                    event_type: EventType::Move,
+                    display: None,
+                    shape,
+                });
+            }
+        }
+
+        // Keys that went down since the last poll
+        if keys != KeyCapture::Off {
+            if let Some(keymap) = conn
+                .query_keymap()
+                .ok()
+                .and_then(|cookie| cookie.reply().ok())
+            {
+                let modifiers = Modifiers {
+                    ctrl: buttons & 0x4 != 0,  // ControlMask
+                    alt: buttons & 0x8 != 0,   // Mod1Mask
+                    shift: buttons & 0x1 != 0, // ShiftMask
+                    meta: buttons & 0x40 != 0, // Mod4Mask
+                };
+                for keycode in newly_pressed(&last_keymap, &keymap.keys) {
+                    let code = key_code(keycode);
+                    // Modifiers on their own aren't key presses
+                    if code.is_some_and(keyboard::is_modifier) {
+                        continue;
+                    }
+                    let press = KeyPress {
+                        code: code.map(str::to_string),
+                        modifiers,
+                    };
+                    if let (Some(press), Ok(mut events)) = (keys.record(press), events.lock()) {
+                        events.push(CursorEvent {
+                            x: x as f64,
+                            y: y as f64,
+                            timestamp,
+                            event_type: EventType::KeyDown(press),
+                            display: None,
+                            shape,
+                        });
+                    }
+                }
+                last_keymap = keymap.keys;
+            }
+        }
+
+        last_x = x;
+        last_y = y;
+        last_buttons = buttons;
+
+        thread::sleep(poll_interval);
+    }
+}
+
+/// Ask for XFixes cursor notifications on `root`, returning the current
+/// shape. None if the server can't tell cursor names (XFixes < 2).
+fn watch_cursor_shape(conn: &RustConnection, root: Window) -> Option<CursorShape> {
+    let version = conn.xfixes_query_version(5, 0).ok()?.reply().ok()?;
+    if version.major_version < 2 {
+        return None;
+    }
+    conn.xfixes_select_cursor_input(root, CursorNotifyMask::DISPLAY_CURSOR)
+        .ok()?
+        .check()
+        .ok()?;
+    let current = conn.xfixes_get_cursor_image_and_name().ok()?.reply().ok()?;
+    CursorShape::from_name(&String::from_utf8_lossy(&current.name))
+}
+
+/// Shape for a cursor name atom; themed cursors are named after the
+/// X cursor font or CSS cursors, and unnamed ones (atom 0) are the app's own
+fn cursor_shape_named(conn: &RustConnection, name: Atom) -> Option<CursorShape> {
+    if name == 0 {
+        return None;
+    }
+    let reply = conn.get_atom_name(name).ok()?.reply().ok()?;
+    CursorShape::from_name(&String::from_utf8_lossy(&reply.name))
+}
+
+/// Keycodes set in `now` but not in `before` (XQueryKeymap bit vectors)
+fn newly_pressed<'a>(before: &'a [u8; 32], now: &'a [u8; 32]) -> impl Iterator<Item = u8> + 'a {
+    (0..=255u8).filter(move |&keycode| {
+        let (byte, bit) = (keycode as usize / 8, keycode % 8);
+        now[byte] & (1 << bit) != 0 && before[byte] & (1 << bit) == 0
+    })
+}
+
+/// W3C code of an X11 keycode. With the evdev driver (the default
+/// everywhere now) keycodes are the kernel's, plus 8.
+fn key_code(keycode: u8) -> Option<&'static str> {
+    let evdev = (keycode as u32).checked_sub(8)?;
+    keyboard::code_from_scancode(evdev).or(match evdev {
+        96 => Some("NumpadEnter"),
+        97 => Some("ControlRight"),
+        100 => Some("AltRight"),
+        102 => Some("Home"),
+        103 => Some("ArrowUp"),
+        104 => Some("PageUp"),
+        105 => Some("ArrowLeft"),
+        106 => Some("ArrowRight"),
+        107 => Some("End"),
+        108 => Some("ArrowDown"),
+        109 => Some("PageDown"),
+        111 => Some("Delete"),
+        125 => Some("MetaLeft"),
+        126 => Some("MetaRight"),
+        _ => None,
+    })
+}
+
+/// Listens for a global hotkey while recording
+pub struct HotkeyListener {
+    presses: Arc<AtomicU32>,
+    stop_flag: Arc<AtomicBool>,
+    thread_handle: Option<thread::JoinHandle<()>>,
+}
+
+impl HotkeyListener {
+    /// Grab the hotkey on the root window, so it reaches glide whichever
+    /// window is focused
+    pub fn start(hotkey: &Hotkey) -> Result<Self> {
+        if wayland::is_wayland_session() {
+            anyhow::bail!("Global hotkeys aren't supported on Wayland; use Ctrl+C in the terminal");
+        }
+
+        let presses = Arc::new(AtomicU32::new(0));
+        let stop_flag = Arc::new(AtomicBool::new(false));
+        let (ready_tx, ready_rx) = mpsc::channel();
+
+        let hotkey = *hotkey;
+        let presses_clone = Arc::clone(&presses);
+        let stop_clone = Arc::clone(&stop_flag);
+        let handle = thread::spawn(move || {
+            run_hotkey_grab(hotkey, presses_clone, stop_clone, ready_tx);
+        });
+
+        ready_rx
+            .recv()
+            .context("Hotkey thread exited")?
+            .map_err(|e| anyhow::anyhow!("Failed to register hotkey {}: {}", hotkey, e))?;
+
+        Ok(Self {
+            presses,
+            stop_flag,
+            thread_handle: Some(handle),
+        })
+    }
+
+    /// Whether the hotkey was pressed since the last call
+    pub fn take_press(&self) -> bool {
+        self.presses.swap(0, Ordering::SeqCst) > 0
+    }
+}
+
+impl Drop for HotkeyListener {
+    fn drop(&mut self) {
+        self.stop_flag.store(true, Ordering::SeqCst);
+        if let Some(handle) = self.thread_handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+/// Grab the key (with and without Caps/Num Lock, which X11 counts as
+/// modifiers) and count presses until stopped
+fn run_hotkey_grab(
+    hotkey: Hotkey,
+    presses: Arc<AtomicU32>,
+    stop_flag: Arc<AtomicBool>,
+    ready: mpsc::Sender<std::result::Result<(), String>>,
+) {
+    let setup = || -> Result<(RustConnection, u32, u8, Vec<ModMask>)> {
+        let (conn, screen_num) =
+            RustConnection::connect(None).context("Failed to connect to X11 display")?;
+        let root = conn.setup().roots[screen_num].root;
+        let keycode = find_keycode(&conn, keysym(hotkey.key))?;
+
+        let mut modifiers = ModMask::from(0u16);
+        for (on, mask) in [
+            (hotkey.ctrl, ModMask::CONTROL),
+            (hotkey.alt, ModMask::M1),
+            (hotkey.shift, ModMask::SHIFT),
+            (hotkey.meta, ModMask::M4),
+        ] {
+            if on {
+                modifiers |= mask;
+            }
+        }
+        let variants: Vec<ModMask> = [
+            ModMask::from(0u16),
+            ModMask::LOCK,
+            ModMask::M2,
+            ModMask::LOCK | ModMask::M2,
+        ]
+        .into_iter()
+        .map(|lock| modifiers | lock)
+        .collect();
+
+        for &mask in &variants {
+            conn.grab_key(false, root, mask, keycode, GrabMode::ASYNC, GrabMode::ASYNC)?
+                .check()
+                .context("it's already taken by another application")?;
+        }
+        Ok((conn, root, keycode, variants))
+    };
+
+    let (conn, root, keycode, variants) = match setup() {
+        Ok(grab) => grab,
+        Err(e) => {
+            let _ = ready.send(Err(format!("{:#}", e)));
+            return;
+        }
+    };
+    let _ = ready.send(Ok(()));
+
+    // Holding the key repeats it; only count presses this far apart
+    let debounce = Duration::from_millis(500);
+    let mut last_press: Option<Instant> = None;
+
+    while !stop_flag.load(Ordering::Relaxed) {
+        while let Ok(Some(event)) = conn.poll_for_event() {
+            if let Event::KeyPress(press) = event {
+                if press.detail == keycode && last_press.is_none_or(|t| t.elapsed() > debounce) {
+                    presses.fetch_add(1, Ordering::SeqCst);
+                    last_press = Some(Instant::now());
+                }
+            }
+        }
+        thread::sleep(Duration::from_millis(20));
+    }
+
+    for mask in variants {
+        let _ = conn.ungrab_key(keycode, root, mask);
+    }
+    let _ = conn.flush();
+}
+
+/// X11 keysym for a hotkey key
+fn keysym(key: HotkeyKey) -> u32 {
+    match key {
+        // Latin-1 keysyms are the characters themselves
+        HotkeyKey::Char(c) => c as u32,
+        // XK_F1 onwards
+        HotkeyKey::Function(n) => 0xffbe + n as u32 - 1,
+    }
+}
+
+/// The first keycode whose mapping produces `keysym`
+fn find_keycode(conn: &RustConnection, keysym: u32) -> Result<u8> {
+    let setup = conn.setup();
+    let (min, max) = (setup.min_keycode, setup.max_keycode);
+    let mapping = conn
+        .get_keyboard_mapping(min, max - min + 1)?
+        .reply()
+        .context("Failed to read the keyboard mapping")?;
+
+    let per_keycode = mapping.keysyms_per_keycode.max(1) as usize;
+    mapping
+        .keysyms
+        .chunks(per_keycode)
+        .position(|syms| syms.contains(&keysym))
+        .map(|index| min + index as u8)
+        .context("the key isn't on the current keyboard layout")
+}
@@ -0,0 +1,22 @@
+//! Linux support module
+//!
+//! Provides display enumeration, window enumeration, cursor tracking,
+//! and screen capture for Linux X11 environments, with screen capture
+//! through the desktop portal on Wayland.
+
+pub mod capture;
+pub mod display;
+pub mod event_tap;
+pub mod select;
+pub mod wayland;
+pub mod window;
+
+// Re-export commonly used types
+pub use capture::{
+    find_display, find_window, start_app_capture, start_display_capture, start_region_capture,
+    start_window_capture, CaptureConfig, CaptureSession, CapturedFrame,
+};
+pub use display::{cursor_size_multiplier, keyboard_info, list_displays, DisplayInfo};
+pub use event_tap::{CursorTracker, HotkeyListener};
+pub use select::select_on_screen;
+pub use window::{list_windows, WindowInfo};
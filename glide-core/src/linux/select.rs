@@ -0,0 +1,177 @@
+//! The `--select` overlay on X11: a crosshair pointer grab with the
+//! dragged rectangle drawn inverted over the screen
+
+use crate::linux::wayland;
+use crate::linux::window::{list_windows, stacking_order};
+use crate::recording::select::{drag_region, window_at, Point, Selection};
+use anyhow::{Context, Result};
+use x11rb::connection::Connection;
+use x11rb::protocol::xproto::{
+    ConnectionExt, CreateGCAux, EventMask, GrabMode, GrabStatus, Rectangle, SubwindowMode, GX,
+};
+use x11rb::protocol::Event;
+use x11rb::rust_connection::RustConnection;
+use x11rb::{CURRENT_TIME, NONE};
+
+/// `XC_crosshair` in the X cursor font
+const CROSSHAIR_GLYPH: u16 = 34;
+
+const ESCAPE_KEYSYM: u32 = 0xff1b;
+
+const LEFT_BUTTON: u8 = 1;
+
+/// Let the user drag out a rectangle or click a window; None if they
+/// pressed Escape or another mouse button
+pub fn select_on_screen() -> Result<Option<Selection>> {
+    if wayland::is_wayland_session() {
+        anyhow::bail!(
+            "--select needs X11; on Wayland use --display 0 or --window 0 to pick in the sharing dialog"
+        );
+    }
+    let (conn, screen_num) =
+        RustConnection::connect(None).context("Failed to connect to X11 display")?;
+    let screen = &conn.setup().roots[screen_num];
+    let root = screen.root;
+
+    let font = conn.generate_id()?;
+    conn.open_font(font, b"cursor")?;
+    let crosshair = conn.generate_id()?;
+    conn.create_glyph_cursor(
+        crosshair,
+        font,
+        font,
+        CROSSHAIR_GLYPH,
+        CROSSHAIR_GLYPH + 1,
+        0,
+        0,
+        0,
+        0xffff,
+        0xffff,
+        0xffff,
+    )?;
+    // Inverting what's under the outline lets drawing it again erase it
+    let gc = conn.generate_id()?;
+    conn.create_gc(
+        gc,
+        root,
+        &CreateGCAux::new()
+            .function(GX::XOR)
+            .foreground(screen.white_pixel ^ screen.black_pixel)
+            .subwindow_mode(SubwindowMode::INCLUDE_INFERIORS),
+    )?;
+
+    let grabbed = conn
+        .grab_pointer(
+            false,
+            root,
+            EventMask::BUTTON_PRESS | EventMask::BUTTON_RELEASE | EventMask::POINTER_MOTION,
+            GrabMode::ASYNC,
+            GrabMode::ASYNC,
+            NONE,
+            crosshair,
+            CURRENT_TIME,
+        )?
+        .reply()?;
+    if grabbed.status != GrabStatus::SUCCESS {
+        anyhow::bail!("Another app has grabbed the pointer");
+    }
+    conn.grab_keyboard(false, root, CURRENT_TIME, GrabMode::ASYNC, GrabMode::ASYNC)?
+        .reply()?;
+
+    let result = drag(&conn, root, gc);
+
+    let _ = conn.ungrab_pointer(CURRENT_TIME);
+    let _ = conn.ungrab_keyboard(CURRENT_TIME);
+    let _ = conn.free_gc(gc);
+    let _ = conn.free_cursor(crosshair);
+    let _ = conn.close_font(font);
+    let _ = conn.flush();
+
+    let Some((start, end)) = result? else {
+        return Ok(None);
+    };
+    if let Some(region) = drag_region(start, end) {
+        return Ok(Some(Selection::Region(region)));
+    }
+    let windows: Vec<_> = list_windows()?
+        .into_iter()
+        .map(|w| (w.id, w.bounds))
+        .collect();
+    let stacking = stacking_order(&conn, root)?;
+    window_at(end, &windows, &stacking)
+        .map(|id| Some(Selection::Window(id)))
+        .context("No window there to record; drag out a rectangle instead")
+}
+
+/// Follow the pointer from a left-button press to its release, outlining
+/// the rectangle on the way; the press and release points
+fn drag(conn: &RustConnection, root: u32, gc: u32) -> Result<Option<(Point, Point)>> {
+    let escape = escape_keycodes(conn)?;
+    let mut start = None;
+    let mut outline: Option<Rectangle> = None;
+    let draw = |outline: &Option<Rectangle>| -> Result<()> {
+        if let Some(rectangle) = outline {
+            conn.poly_rectangle(root, gc, &[*rectangle])?;
+            conn.flush()?;
+        }
+        Ok(())
+    };
+
+    loop {
+        match conn.wait_for_event()? {
+            Event::ButtonPress(press) if press.detail == LEFT_BUTTON => {
+                start = Some((press.root_x as i32, press.root_y as i32));
+            }
+            Event::ButtonPress(_) => {
+                draw(&outline)?;
+                return Ok(None);
+            }
+            Event::KeyPress(key) if escape.contains(&key.detail) => {
+                draw(&outline)?;
+                return Ok(None);
+            }
+            Event::MotionNotify(motion) => {
+                let Some(start) = start else { continue };
+                // Erase the old outline, then draw the new one
+                draw(&outline)?;
+                outline =
+                    drag_region(start, (motion.root_x as i32, motion.root_y as i32)).map(|r| {
+                        Rectangle {
+                            x: r.x as i16,
+                            y: r.y as i16,
+                            width: r.width as u16,
+                            height: r.height as u16,
+                        }
+                    });
+                draw(&outline)?;
+            }
+            Event::ButtonRelease(release) if release.detail == LEFT_BUTTON => {
+                let Some(start) = start else { continue };
+                draw(&outline)?;
+                return Ok(Some((
+                    start,
+                    (release.root_x as i32, release.root_y as i32),
+                )));
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Keycodes that produce Escape on this keyboard
+fn escape_keycodes(conn: &RustConnection) -> Result<Vec<u8>> {
+    let setup = conn.setup();
+    let (min, max) = (setup.min_keycode, setup.max_keycode);
+    let mapping = conn
+        .get_keyboard_mapping(min, max - min + 1)?
+        .reply()
+        .context("Failed to read the keyboard mapping")?;
+    let per_keycode = mapping.keysyms_per_keycode.max(1) as usize;
+    Ok(mapping
+        .keysyms
+        .chunks(per_keycode)
+        .enumerate()
+        .filter(|(_, keysyms)| keysyms.contains(&ESCAPE_KEYSYM))
+        .map(|(i, _)| min + i as u8)
+        .collect())
+}
@@ -0,0 +1,279 @@
+//! Wayland screen capture via the xdg-desktop-portal ScreenCast API
+//!
+//! Wayland compositors don't let clients read the screen or enumerate other
+//! clients' windows, so x11grab only sees XWayland windows there. Instead the
+//! portal asks the user which monitor or window to share and hands back a
+//! PipeWire stream, which GStreamer's `pipewiresrc` decodes to the same raw
+//! BGRA frames the X11 path produces.
+
+use anyhow::{Context, Result};
+use ashpd::desktop::screencast::{CursorMode, Screencast, SourceType};
+use ashpd::desktop::{PersistMode, Session};
+use std::os::fd::{AsRawFd, OwnedFd, RawFd};
+use std::process::{Command, Stdio};
+
+/// Window ID that `list_windows` reports on Wayland: the window is picked
+/// in the portal's sharing dialog instead
+pub const PORTAL_WINDOW_ID: u32 = 0;
+
+/// What the portal should offer in its sharing dialog
+#[derive(Debug, Clone, Copy)]
+pub enum PortalSource {
+    Monitor,
+    Window,
+}
+
+/// Whether this is a Wayland session (where the portal backend is used)
+pub fn is_wayland_session() -> bool {
+    std::env::var_os("WAYLAND_DISPLAY").is_some_and(|v| !v.is_empty())
+}
+
+/// A started portal screencast. Dropping it ends the stream.
+pub struct PortalStream {
+    proxy: Screencast<'static>,
+    session: Session<'static, Screencast<'static>>,
+    /// Open PipeWire remote, inherited by the GStreamer process
+    fd: OwnedFd,
+    node_id: u32,
+    /// Size the portal reported, which it doesn't have to
+    size: Option<(u32, u32)>,
+    /// Where a shared monitor is in the compositor's space, if reported
+    pub position: Option<(i32, i32)>,
+}
+
+impl PortalStream {
+    /// Ask the portal for a stream; shows the compositor's sharing dialog
+    pub fn open(source: PortalSource, show_cursor: bool) -> Result<Self> {
+        pollster::block_on(Self::open_async(source, show_cursor)).map_err(|e| match e {
+            ashpd::Error::Response(ashpd::desktop::ResponseError::Cancelled) => {
+                anyhow::anyhow!("Screen sharing was cancelled")
+            }
+            e => anyhow::anyhow!("Screen sharing portal failed: {}", e),
+        })
+    }
+
+    async fn open_async(source: PortalSource, show_cursor: bool) -> ashpd::Result<Self> {
+        let proxy = Screencast::new().await?;
+        let session = proxy.create_session().await?;
+        let cursor_mode = if show_cursor {
+            CursorMode::Embedded
+        } else {
+            CursorMode::Hidden
+        };
+        let source_type = match source {
+            PortalSource::Monitor => SourceType::Monitor,
+            PortalSource::Window => SourceType::Window,
+        };
+        proxy
+            .select_sources(
+                &session,
+                cursor_mode,
+                source_type.into(),
+                false,
+                None,
+                PersistMode::DoNot,
+            )
+            .await?
+            .response()?;
+        let streams = proxy.start(&session, None).await?.response()?;
+        let stream = streams.streams().first().ok_or(ashpd::Error::NoResponse)?;
+        let size = stream
+            .size()
+            .map(|(width, height)| (width.max(0) as u32, height.max(0) as u32));
+        let position = stream.position();
+        let node_id = stream.pipe_wire_node_id();
+        let fd = proxy.open_pipe_wire_remote(&session).await?;
+
+        Ok(Self {
+            proxy,
+            session,
+            fd,
+            node_id,
+            size,
+            position,
+        })
+    }
+
+    /// Size of the stream's frames: as the portal reported it, or else as
+    /// the stream negotiates it with GStreamer
+    pub fn size(&self) -> Result<(u32, u32)> {
+        match self.size {
+            Some(size) => Ok(size),
+            None => self.negotiated_size(),
+        }
+    }
+
+    /// Read one frame through a second PipeWire remote and take the size
+    /// from the caps GStreamer settled on
+    fn negotiated_size(&self) -> Result<(u32, u32)> {
+        let fd = pollster::block_on(self.proxy.open_pipe_wire_remote(&self.session))
+            .context("Failed to open a PipeWire remote")?;
+        let output = self
+            .launch(fd.as_raw_fd(), "-v", &["num-buffers=1", "!", "fakesink"])
+            .output()
+            .context("Failed to run GStreamer")?;
+        caps_size(&String::from_utf8_lossy(&output.stdout))
+            .context("The screen sharing portal didn't report the stream size")
+    }
+
+    /// GStreamer pipeline writing raw BGRA frames of `width`x`height` at 60fps to stdout
+    pub fn command(&self, width: u32, height: u32) -> Command {
+        self.launch(
+            self.fd.as_raw_fd(),
+            "-q",
+            &[
+                "!",
+                "videoconvert",
+                "!",
+                "videoscale",
+                "!",
+                "videorate",
+                "!",
+                &format!(
+                    "video/x-raw,format=BGRA,width={},height={},framerate=60/1",
+                    width, height
+                ),
+                "!",
+                "fdsink",
+                "fd=1",
+            ],
+        )
+    }
+
+    /// `gst-launch-1.0 <verbosity>` reading this stream from the PipeWire
+    /// remote `fd`, then the rest of the pipeline
+    fn launch(&self, fd: RawFd, verbosity: &str, rest: &[&str]) -> Command {
+        let mut cmd = Command::new("gst-launch-1.0");
+        cmd.args([
+            verbosity,
+            "pipewiresrc",
+            &format!("fd={}", fd),
+            &format!("path={}", self.node_id),
+            "do-timestamp=true",
+            "always-copy=true",
+        ]);
+        cmd.args(rest);
+        cmd.stdout(Stdio::piped());
+        cmd.stderr(Stdio::null());
+
+        // The portal's fd is close-on-exec; GStreamer needs to inherit it
+        // SAFETY: fcntl is async-signal-safe, and the fd outlives the spawn
+        unsafe {
+            use std::os::unix::process::CommandExt;
+            cmd.pre_exec(move || {
+                if libc::fcntl(fd, libc::F_SETFD, 0) == -1 {
+                    return Err(std::io::Error::last_os_error());
+                }
+                Ok(())
+            });
+        }
+        cmd
+    }
+
+    /// End the portal session
+    pub fn close(&self) {
+        let _ = pollster::block_on(self.session.close());
+    }
+}
+
+/// GStreamer with the PipeWire plugin is needed to read portal streams
+pub fn check_gstreamer() -> Result<()> {
+    let available = Command::new("gst-inspect-1.0")
+        .arg("pipewiresrc")
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok_and(|status| status.success());
+    if !available {
+        anyhow::bail!(
+            "Recording on Wayland needs GStreamer with the PipeWire plugin.\n\
+             Install gstreamer1.0-tools and gstreamer1.0-pipewire (Debian/Ubuntu) or \
+             gstreamer1-plugins-base-tools and pipewire-gstreamer (Fedora)."
+        );
+    }
+    Ok(())
+}
+
+/// Frame size for a portal stream. The user may share a different monitor
+/// than the one asked for, so the stream's own size is used.
+pub fn frame_size(stream: (u32, u32)) -> Result<(u32, u32)> {
+    let (width, height) = stream;
+    if width == 0 || height == 0 {
+        anyhow::bail!("The screen sharing portal didn't report the stream size");
+    }
+    // Encoders need even dimensions
+    Ok((width & !1, height & !1))
+}
+
+/// Frame size in the caps `gst-launch-1.0 -v` printed last
+fn caps_size(log: &str) -> Option<(u32, u32)> {
+    let caps = log
+        .lines()
+        .rev()
+        .find(|line| line.contains("caps = video/x-raw"))?;
+    let field = |name: &str| -> Option<u32> {
+        caps.split(", ")
+            .find_map(|field| field.strip_prefix(name))?
+            .parse()
+            .ok()
+    };
+    Some((field("width=(int)")?, field("height=(int)")?))
+}
+
+/// Why cursor positions, which are read through XWayland, won't line up
+/// with the shared monitor, if they won't. `requested` is where the display
+/// asked for is (None without XWayland), `shared` where the portal says the
+/// shared monitor is.
+pub fn cursor_mismatch(
+    requested: Option<(i32, i32)>,
+    shared: Option<(i32, i32)>,
+) -> Option<String> {
+    match (requested, shared) {
+        (None, _) => Some("XWayland isn't running, so the cursor can't be tracked".to_string()),
+        (Some(_), None) => Some(
+            "the portal didn't say which monitor was shared, so cursor positions may not line up"
+                .to_string(),
+        ),
+        (Some(requested), Some(shared)) if requested != shared => Some(format!(
+            "the shared monitor (at {},{}) isn't the display asked for (at {},{}), so cursor \
+             positions won't line up",
+            shared.0, shared.1, requested.0, requested.1
+        )),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frame_size() {
+        assert_eq!(frame_size((1919, 1080)).unwrap(), (1918, 1080));
+        assert_eq!(frame_size((2560, 1440)).unwrap(), (2560, 1440));
+        assert!(frame_size((0, 0)).is_err());
+    }
+
+    #[test]
+    fn test_caps_size() {
+        let log = "Setting pipeline to PAUSED ...\n\
+            /GstPipeline:pipeline0/GstPipeWireSrc:pipewiresrc0.GstPad:src: caps = \
+            video/x-raw, format=(string)BGRx, width=(int)2560, height=(int)1440, \
+            framerate=(fraction)0/1\n\
+            Got EOS from element \"pipeline0\".\n";
+        assert_eq!(caps_size(log), Some((2560, 1440)));
+        assert_eq!(caps_size("ERROR: pipeline could not be constructed"), None);
+    }
+
+    #[test]
+    fn test_cursor_mismatch() {
+        assert_eq!(cursor_mismatch(Some((1920, 0)), Some((1920, 0))), None);
+        assert!(cursor_mismatch(Some((0, 0)), Some((1920, 0)))
+            .unwrap()
+            .contains("isn't the display asked for"));
+        assert!(cursor_mismatch(Some((0, 0)), None).is_some());
+        assert!(cursor_mismatch(None, Some((0, 0)))
+            .unwrap()
+            .contains("XWayland"));
+    }
+}
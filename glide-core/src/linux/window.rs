@@ -1,5 +1,6 @@
-//! Linux X11 window enumeration using EWMH
+//! Linux window enumeration using EWMH on X11
 
+use crate::linux::wayland::{self, PORTAL_WINDOW_ID};
 use anyhow::{Context, Result};
 use x11rb::connection::Connection;
 use x11rb::protocol::xproto::{Atom, AtomEnum, ConnectionExt, GetPropertyReply, Window};
@@ -128,7 +129,32 @@ fn get_window_geometry(
     ))
 }
 
+/// Managed windows from bottom to top (EWMH `_NET_CLIENT_LIST_STACKING`)
+pub(crate) fn stacking_order(conn: &RustConnection, root: Window) -> Result<Vec<Window>> {
+    let net_client_list_stacking = get_atom(conn, "_NET_CLIENT_LIST_STACKING")?;
+    let reply = get_property_value(
+        conn,
+        root,
+        net_client_list_stacking,
+        AtomEnum::WINDOW.into(),
+    )?;
+    Ok(reply
+        .and_then(|reply| reply.value32().map(|iter| iter.collect()))
+        .unwrap_or_default())
+}
+
 pub fn list_windows() -> Result<Vec<WindowInfo>> {
+    // Wayland clients can't see each other's windows (X11 would only list
+    // XWayland ones), so the window is picked in the portal's dialog
+    if wayland::is_wayland_session() {
+        return Ok(vec![WindowInfo {
+            id: PORTAL_WINDOW_ID,
+            name: "chosen in the screen sharing dialog".to_string(),
+            owner: "Any window".to_string(),
+            bounds: (0, 0, 0, 0),
+        }]);
+    }
+
     let (conn, screen_num) =
         RustConnection::connect(None).context("Failed to connect to X11 display")?;
 
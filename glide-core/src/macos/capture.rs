@@ -0,0 +1,471 @@
+//! ScreenCaptureKit-based screen and window capture
+//!
+//! This module provides screen capture using Apple's ScreenCaptureKit framework,
+//! which properly supports cursor visibility control.
+
+use crate::recording::encoder::PcmStream;
+use crate::recording::exclude::Exclusions;
+use anyhow::{Context, Result};
+use screencapturekit::cg::CGRect;
+use screencapturekit::cm::CMTime;
+use screencapturekit::cv::CVPixelBufferLockFlags;
+use screencapturekit::prelude::*;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender, SyncSender};
+use std::sync::Arc;
+
+/// A captured video frame with raw BGRA pixel data
+pub struct CapturedFrame {
+    /// Raw BGRA pixel data
+    pub data: Vec<u8>,
+    /// Frame width in pixels
+    pub width: usize,
+    /// Frame height in pixels
+    pub height: usize,
+    /// Presentation timestamp in seconds
+    pub timestamp: f64,
+}
+
+/// Capture configuration
+pub struct CaptureConfig {
+    /// Whether to show the system cursor in the capture
+    pub show_cursor: bool,
+    /// Target width (0 = native resolution)
+    pub width: u32,
+    /// Target height (0 = native resolution)
+    pub height: u32,
+    /// Also capture system audio
+    pub capture_audio: bool,
+    /// Windows to leave out of display and region captures
+    pub exclude: Exclusions,
+}
+
+impl Default for CaptureConfig {
+    fn default() -> Self {
+        Self {
+            show_cursor: false,
+            width: 0,
+            height: 0,
+            capture_audio: false,
+            exclude: Exclusions::default(),
+        }
+    }
+}
+
+/// System audio format requested from ScreenCaptureKit
+const AUDIO_SAMPLE_RATE: u32 = 48_000;
+const AUDIO_CHANNELS: u32 = 2;
+
+/// Frame handler that sends captured frames through a channel
+struct FrameHandler {
+    sender: SyncSender<CapturedFrame>,
+    running: Arc<AtomicBool>,
+    dropped: Arc<AtomicU64>,
+    queued: Arc<AtomicUsize>,
+}
+
+impl SCStreamOutputTrait for FrameHandler {
+    fn did_output_sample_buffer(&self, sample: CMSampleBuffer, of_type: SCStreamOutputType) {
+        if !self.running.load(Ordering::Relaxed) {
+            return;
+        }
+
+        if of_type != SCStreamOutputType::Screen {
+            return;
+        }
+
+        // Get the pixel buffer from the sample
+        let Some(pixel_buffer) = sample.image_buffer() else {
+            return;
+        };
+
+        // Lock the pixel buffer for reading
+        let Ok(guard) = pixel_buffer.lock(CVPixelBufferLockFlags::READ_ONLY) else {
+            return;
+        };
+
+        let width = guard.width();
+        let height = guard.height();
+        let bytes_per_row = guard.bytes_per_row();
+        let pixels = guard.as_slice();
+
+        // Get timestamp
+        let pts = sample.presentation_timestamp();
+        let timestamp = if pts.timescale > 0 {
+            pts.value as f64 / pts.timescale as f64
+        } else {
+            0.0
+        };
+
+        // Copy pixel data, stripping any row padding
+        // CVPixelBuffer may have bytes_per_row > width * 4 for memory alignment
+        let expected_bytes_per_row = width * 4; // BGRA = 4 bytes per pixel
+        let data = if bytes_per_row == expected_bytes_per_row {
+            // No padding, copy directly
+            pixels.to_vec()
+        } else {
+            // Has padding, copy row by row
+            let mut data = Vec::with_capacity(width * height * 4);
+            for y in 0..height {
+                let row_start = y * bytes_per_row;
+                let row_end = row_start + expected_bytes_per_row;
+                if row_end <= pixels.len() {
+                    data.extend_from_slice(&pixels[row_start..row_end]);
+                }
+            }
+            data
+        };
+
+        let frame = CapturedFrame {
+            data,
+            width,
+            height,
+            timestamp,
+        };
+
+        // Counted before sending so the receiver never sees it negative
+        self.queued.fetch_add(1, Ordering::Relaxed);
+        if self.sender.try_send(frame).is_err() {
+            // Channel full (encoder behind) or closed: the frame is lost
+            self.queued.fetch_sub(1, Ordering::Relaxed);
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Audio handler that sends interleaved f32 PCM through a channel
+struct AudioHandler {
+    sender: Sender<Vec<u8>>,
+    running: Arc<AtomicBool>,
+}
+
+impl SCStreamOutputTrait for AudioHandler {
+    fn did_output_sample_buffer(&self, sample: CMSampleBuffer, of_type: SCStreamOutputType) {
+        if !self.running.load(Ordering::Relaxed) || of_type != SCStreamOutputType::Audio {
+            return;
+        }
+
+        let Some(buffer_list) = sample.audio_buffer_list() else {
+            return;
+        };
+        let planes: Vec<&[u8]> = buffer_list.iter().map(|buffer| buffer.data()).collect();
+
+        // ScreenCaptureKit delivers one buffer per channel; FFmpeg reads them interleaved
+        let pcm = interleave_f32(&planes);
+        if !pcm.is_empty() {
+            // Unbounded: audio is small, and losing it would be audible
+            let _ = self.sender.send(pcm);
+        }
+    }
+}
+
+/// Interleave planar 32-bit float samples (a single plane is returned as-is)
+fn interleave_f32(planes: &[&[u8]]) -> Vec<u8> {
+    match planes {
+        [] => Vec::new(),
+        [plane] => plane.to_vec(),
+        _ => {
+            let samples = planes.iter().map(|p| p.len() / 4).min().unwrap_or(0);
+            let mut pcm = Vec::with_capacity(samples * planes.len() * 4);
+            for i in 0..samples {
+                for plane in planes {
+                    pcm.extend_from_slice(&plane[i * 4..i * 4 + 4]);
+                }
+            }
+            pcm
+        }
+    }
+}
+
+/// Active screen capture session
+pub struct CaptureSession {
+    stream: SCStream,
+    receiver: Receiver<CapturedFrame>,
+    running: Arc<AtomicBool>,
+    /// Frames captured but discarded because the encoder fell behind
+    dropped: Arc<AtomicU64>,
+    /// Frames waiting in the channel for the encoder
+    queued: Arc<AtomicUsize>,
+    /// System audio, until handed to the encoder
+    audio: Option<PcmStream>,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl CaptureSession {
+    /// Receive the next captured frame (blocks until available)
+    pub fn recv(&self) -> Option<CapturedFrame> {
+        let frame = self.receiver.recv().ok();
+        if frame.is_some() {
+            self.queued.fetch_sub(1, Ordering::Relaxed);
+        }
+        frame
+    }
+
+    /// Try to receive a frame without blocking
+    pub fn try_recv(&self) -> Option<CapturedFrame> {
+        let frame = self.receiver.try_recv().ok();
+        if frame.is_some() {
+            self.queued.fetch_sub(1, Ordering::Relaxed);
+        }
+        frame
+    }
+
+    /// Frames dropped so far because the channel to the encoder was full
+    pub fn dropped_frames(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Frames captured but not yet taken by the encoder
+    pub fn queued_frames(&self) -> usize {
+        self.queued.load(Ordering::Relaxed)
+    }
+
+    /// Take the system audio stream, if audio is being captured
+    pub fn take_audio(&mut self) -> Option<PcmStream> {
+        self.audio.take()
+    }
+
+    /// Check if the capture is still running
+    pub fn is_running(&self) -> bool {
+        self.running.load(Ordering::Relaxed)
+    }
+
+    /// Stop the capture session
+    pub fn stop(&mut self) -> Result<()> {
+        self.running.store(false, Ordering::SeqCst);
+        self.stream
+            .stop_capture()
+            .map_err(|e| anyhow::anyhow!("Failed to stop capture: {:?}", e))
+    }
+}
+
+/// Find a display by index from ScreenCaptureKit
+pub fn find_display(display_index: usize) -> Result<SCDisplay> {
+    let content = SCShareableContent::get()
+        .context("Failed to get shareable content from ScreenCaptureKit")?;
+
+    let displays = content.displays();
+    displays
+        .into_iter()
+        .nth(display_index)
+        .ok_or_else(|| anyhow::anyhow!("Display {} not found", display_index))
+}
+
+/// Find a window by ID from ScreenCaptureKit
+pub fn find_window(window_id: u32) -> Result<SCWindow> {
+    let content = SCShareableContent::get()
+        .context("Failed to get shareable content from ScreenCaptureKit")?;
+
+    let windows = content.windows();
+    windows
+        .into_iter()
+        .find(|w| w.window_id() == window_id)
+        .ok_or_else(|| anyhow::anyhow!("Window {} not found", window_id))
+}
+
+/// Windows matching `exclude`, looked up by ID and owning app
+fn excluded_windows(exclude: &Exclusions) -> Result<Vec<SCWindow>> {
+    if exclude.is_empty() {
+        return Ok(Vec::new());
+    }
+    let content = SCShareableContent::get()
+        .context("Failed to get shareable content from ScreenCaptureKit")?;
+    let windows: Vec<SCWindow> = content
+        .windows()
+        .into_iter()
+        .filter(|w| {
+            let owner = w
+                .owning_application()
+                .map(|app| app.application_name())
+                .unwrap_or_default();
+            exclude.matches(w.window_id(), &owner)
+        })
+        .collect();
+    exclude.warn_if_unmatched(windows.len());
+    Ok(windows)
+}
+
+/// Start capturing a display
+pub fn start_display_capture(
+    display: &SCDisplay,
+    config: &CaptureConfig,
+) -> Result<CaptureSession> {
+    // Create content filter for the display
+    let excluded = excluded_windows(&config.exclude)?;
+    let excluded: Vec<&SCWindow> = excluded.iter().collect();
+    let filter = SCContentFilter::create()
+        .with_display(display)
+        .with_excluding_windows(&excluded)
+        .build();
+
+    start_capture_with_filter(filter, config, None)
+}
+
+/// Start capturing every window of the app named `app` (ignoring case) on
+/// `display`. The filter follows the app rather than a list of windows, so
+/// windows it opens mid-recording show up and closed ones disappear.
+pub fn start_app_capture(
+    display: &SCDisplay,
+    app: &str,
+    config: &CaptureConfig,
+) -> Result<CaptureSession> {
+    let content = SCShareableContent::get()
+        .context("Failed to get shareable content from ScreenCaptureKit")?;
+    let application = content
+        .applications()
+        .into_iter()
+        .find(|a| a.application_name().eq_ignore_ascii_case(app))
+        .ok_or_else(|| anyhow::anyhow!("No running app named {}", app))?;
+    let excluded = excluded_windows(&config.exclude)?;
+    let excluded: Vec<&SCWindow> = excluded.iter().collect();
+    let filter = SCContentFilter::create()
+        .with_display(display)
+        .with_including_applications(&[&application], &excluded)
+        .build();
+
+    start_capture_with_filter(filter, config, None)
+}
+
+/// Start capturing a specific window
+pub fn start_window_capture(window: &SCWindow, config: &CaptureConfig) -> Result<CaptureSession> {
+    // Create content filter for the window
+    let filter = SCContentFilter::create().with_window(window).build();
+
+    start_capture_with_filter(filter, config, None)
+}
+
+/// Start capturing a rectangle of a display, given in points relative to its top-left
+pub fn start_region_capture(
+    display: &SCDisplay,
+    region: (i32, i32, u32, u32),
+    config: &CaptureConfig,
+) -> Result<CaptureSession> {
+    let excluded = excluded_windows(&config.exclude)?;
+    let excluded: Vec<&SCWindow> = excluded.iter().collect();
+    let filter = SCContentFilter::create()
+        .with_display(display)
+        .with_excluding_windows(&excluded)
+        .build();
+
+    let (x, y, width, height) = region;
+    let source_rect = CGRect::new(x as f64, y as f64, width as f64, height as f64);
+    start_capture_with_filter(filter, config, Some(source_rect))
+}
+
+/// Internal function to start capture with a given filter, optionally
+/// cropped to `source_rect` (in points)
+fn start_capture_with_filter(
+    filter: SCContentFilter,
+    config: &CaptureConfig,
+    source_rect: Option<CGRect>,
+) -> Result<CaptureSession> {
+    // Frame interval for 60 FPS
+    let frame_interval = CMTime::new(1, 60);
+
+    // Determine dimensions
+    // If config specifies 0, we'll use native resolution
+    // The actual dimensions will be determined from the first frame
+    let (width, height) = if config.width > 0 && config.height > 0 {
+        (config.width, config.height)
+    } else {
+        // Use a reasonable default that will be adjusted
+        (1920, 1080)
+    };
+
+    // Configure the stream
+    let mut stream_config = SCStreamConfiguration::new()
+        .with_width(width)
+        .with_height(height)
+        .with_pixel_format(PixelFormat::BGRA)
+        .with_minimum_frame_interval(&frame_interval)
+        .with_shows_cursor(config.show_cursor)
+        // Keep a resized window at 1:1 instead of stretching it to the
+        // output, so cursor positions only need the window's offset.
+        // Displays and regions always match the output size anyway.
+        .with_scales_to_fit(false);
+    if let Some(rect) = source_rect {
+        stream_config = stream_config.with_source_rect(rect);
+    }
+    if config.capture_audio {
+        stream_config = stream_config
+            .with_captures_audio(true)
+            .with_sample_rate(AUDIO_SAMPLE_RATE as i32)
+            .with_channel_count(AUDIO_CHANNELS as i32)
+            // Don't record glide's own sounds
+            .with_excludes_current_process_audio(true);
+    }
+
+    // Create the stream
+    let mut stream = SCStream::new(&filter, &stream_config);
+
+    // Set up the channel for frames (buffer a few frames)
+    let (sender, receiver) = mpsc::sync_channel(3);
+    let running = Arc::new(AtomicBool::new(true));
+    let dropped = Arc::new(AtomicU64::new(0));
+    let queued = Arc::new(AtomicUsize::new(0));
+
+    // Add the frame handler
+    let handler = FrameHandler {
+        sender,
+        running: running.clone(),
+        dropped: dropped.clone(),
+        queued: queued.clone(),
+    };
+    stream.add_output_handler(handler, SCStreamOutputType::Screen);
+
+    let audio = if config.capture_audio {
+        let (sender, receiver) = mpsc::channel();
+        let handler = AudioHandler {
+            sender,
+            running: running.clone(),
+        };
+        stream.add_output_handler(handler, SCStreamOutputType::Audio);
+        Some(PcmStream {
+            receiver,
+            sample_rate: AUDIO_SAMPLE_RATE,
+            channels: AUDIO_CHANNELS,
+        })
+    } else {
+        None
+    };
+
+    // Start capture
+    stream
+        .start_capture()
+        .map_err(|e| anyhow::anyhow!("Failed to start capture: {:?}", e))?;
+
+    Ok(CaptureSession {
+        stream,
+        receiver,
+        running,
+        dropped,
+        queued,
+        audio,
+        width,
+        height,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_capture_config_default() {
+        let config = CaptureConfig::default();
+        assert!(!config.show_cursor);
+        assert_eq!(config.width, 0);
+        assert_eq!(config.height, 0);
+        assert!(!config.capture_audio);
+    }
+
+    #[test]
+    fn test_interleave_f32() {
+        let left = [1.0f32, 2.0].map(f32::to_le_bytes).concat();
+        let right = [3.0f32, 4.0].map(f32::to_le_bytes).concat();
+        let expected = [1.0f32, 3.0, 2.0, 4.0].map(f32::to_le_bytes).concat();
+        assert_eq!(interleave_f32(&[&left, &right]), expected);
+        assert_eq!(interleave_f32(&[&left]), left);
+    }
+}
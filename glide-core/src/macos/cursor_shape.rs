@@ -0,0 +1,119 @@
+//! Which system cursor is showing, via NSCursor
+//!
+//! `currentSystemCursor` hands back a new NSCursor each time, so it's
+//! matched to the standard cursors by comparing their images.
+
+use crate::cursor_types::CursorShape;
+use std::ffi::{c_char, c_void, CStr};
+use std::time::{Duration, Instant};
+
+type Id = *mut c_void;
+
+#[link(name = "AppKit", kind = "framework")]
+extern "C" {}
+
+#[link(name = "objc")]
+extern "C" {
+    fn objc_getClass(name: *const c_char) -> Id;
+    fn sel_registerName(name: *const c_char) -> Id;
+    fn objc_msgSend();
+    fn objc_autoreleasePoolPush() -> *mut c_void;
+    fn objc_autoreleasePoolPop(pool: *mut c_void);
+}
+
+/// Comparing images isn't free; the cursor changes far less often than it moves
+const CHECK_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Looks up the cursor shape, at most every `CHECK_INTERVAL`
+pub struct ShapeProbe {
+    /// TIFF data of each standard cursor's image (retained NSData)
+    known: Vec<(CursorShape, Id)>,
+    last_check: Option<Instant>,
+    shape: Option<CursorShape>,
+}
+
+impl ShapeProbe {
+    pub fn new() -> Self {
+        let known = [
+            (CursorShape::Arrow, c"arrowCursor"),
+            (CursorShape::IBeam, c"IBeamCursor"),
+            (CursorShape::PointingHand, c"pointingHandCursor"),
+            (CursorShape::ResizeHorizontal, c"resizeLeftRightCursor"),
+            (CursorShape::ResizeVertical, c"resizeUpDownCursor"),
+        ]
+        .into_iter()
+        .filter_map(|(shape, name)| unsafe {
+            let pool = objc_autoreleasePoolPush();
+            let cursor = send(objc_getClass(c"NSCursor".as_ptr()), name);
+            let tiff = cursor_tiff(cursor);
+            let tiff = (!tiff.is_null()).then(|| send(tiff, c"retain"));
+            objc_autoreleasePoolPop(pool);
+            Some((shape, tiff?))
+        })
+        .collect();
+
+        Self {
+            known,
+            last_check: None,
+            shape: None,
+        }
+    }
+
+    /// The shape of the cursor now; None for custom or unusual cursors
+    pub fn current(&mut self) -> Option<CursorShape> {
+        if self
+            .last_check
+            .is_some_and(|t| t.elapsed() < CHECK_INTERVAL)
+        {
+            return self.shape;
+        }
+        self.last_check = Some(Instant::now());
+        self.shape = unsafe {
+            let pool = objc_autoreleasePoolPush();
+            let cursor = send(objc_getClass(c"NSCursor".as_ptr()), c"currentSystemCursor");
+            let tiff = cursor_tiff(cursor);
+            let shape = self
+                .known
+                .iter()
+                .find(|(_, known)| !tiff.is_null() && send_bool(tiff, c"isEqualToData:", *known))
+                .map(|(shape, _)| *shape);
+            objc_autoreleasePoolPop(pool);
+            shape
+        };
+        self.shape
+    }
+}
+
+impl Drop for ShapeProbe {
+    fn drop(&mut self) {
+        for (_, tiff) in &self.known {
+            unsafe { send(*tiff, c"release") };
+        }
+    }
+}
+
+/// `[[cursor image] TIFFRepresentation]`, autoreleased; null for a null cursor
+unsafe fn cursor_tiff(cursor: Id) -> Id {
+    if cursor.is_null() {
+        return cursor;
+    }
+    let image = send(cursor, c"image");
+    if image.is_null() {
+        return image;
+    }
+    send(image, c"TIFFRepresentation")
+}
+
+/// Send a message without arguments that returns an object
+unsafe fn send(receiver: Id, selector: &CStr) -> Id {
+    let send: unsafe extern "C" fn(Id, Id) -> Id =
+        std::mem::transmute(objc_msgSend as unsafe extern "C" fn());
+    send(receiver, sel_registerName(selector.as_ptr()))
+}
+
+/// Send a message with an object argument that returns a BOOL
+unsafe fn send_bool(receiver: Id, selector: &CStr, argument: Id) -> bool {
+    let send: unsafe extern "C" fn(Id, Id, Id) -> i8 =
+        std::mem::transmute(objc_msgSend as unsafe extern "C" fn());
+    send(receiver, sel_registerName(selector.as_ptr()), argument) != 0
+}
@@ -1,3 +1,4 @@
+use crate::keyboard::{KeyboardInfo, KeyboardLayout, ModifierStyle};
 use anyhow::{Context, Result};
 use core_graphics::display::CGDisplay;
 use std::process::{Command, Stdio};
@@ -80,6 +81,42 @@ pub fn list_displays() -> Result<Vec<DisplayInfo>> {
     Ok(displays)
 }
 
+/// The user's pointer size from Accessibility > Display (1.0 = standard, up to 4.0)
+pub fn cursor_size_multiplier() -> Option<f64> {
+    let output = Command::new("defaults")
+        .args(["read", "com.apple.universalaccess", "mouseDriverCursorSize"])
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8_lossy(&output.stdout).trim().parse().ok()
+}
+
+/// The selected keyboard input source (e.g. "German", "Dvorak")
+pub fn keyboard_info() -> Option<KeyboardInfo> {
+    let output = Command::new("defaults")
+        .args([
+            "read",
+            "com.apple.HIToolbox",
+            "AppleCurrentKeyboardLayoutInputSourceID",
+        ])
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    // e.g. "com.apple.keylayout.German"
+    let source = String::from_utf8_lossy(&output.stdout);
+    let name = source.trim().rsplit('.').next()?;
+    Some(KeyboardInfo {
+        layout: KeyboardLayout::from_macos_name(name),
+        modifier_style: ModifierStyle::Apple,
+    })
+}
+
 /// Parse FFmpeg's AVFoundation device list to find screen capture indices
 fn get_avfoundation_screen_indices() -> Result<Vec<usize>> {
     let output = Command::new("ffmpeg")
@@ -0,0 +1,595 @@
+use crate::cursor_types::{ClickTarget, CursorEvent, EventType, KeyCapture, KeyPress};
+use crate::keyboard::Modifiers;
+use crate::macos::cursor_shape::ShapeProbe;
+use crate::recording::hotkey::{Hotkey, HotkeyKey};
+use anyhow::{Context, Result};
+use core_foundation::base::{CFRelease, CFTypeRef, TCFType};
+use core_foundation::runloop::{kCFRunLoopCommonModes, kCFRunLoopDefaultMode, CFRunLoop};
+use core_foundation::string::CFString;
+use core_graphics::event::{
+    CGEvent, CGEventFlags, CGEventTap, CGEventTapLocation, CGEventTapOptions, CGEventTapPlacement,
+    CGEventType, EventField,
+};
+use core_graphics::geometry::{CGPoint, CGSize};
+use std::cell::RefCell;
+use std::ffi::c_void;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// Accessibility API, used to find the bounds of the element under each click
+type AXUIElementRef = *const c_void;
+const K_AX_ERROR_SUCCESS: i32 = 0;
+const K_AX_VALUE_CG_POINT_TYPE: u32 = 1;
+const K_AX_VALUE_CG_SIZE_TYPE: u32 = 2;
+
+#[link(name = "ApplicationServices", kind = "framework")]
+extern "C" {
+    fn AXUIElementCreateSystemWide() -> AXUIElementRef;
+    fn AXUIElementCopyElementAtPosition(
+        application: AXUIElementRef,
+        x: f32,
+        y: f32,
+        element: *mut AXUIElementRef,
+    ) -> i32;
+    fn AXUIElementCopyAttributeValue(
+        element: AXUIElementRef,
+        attribute: *const c_void,
+        value: *mut CFTypeRef,
+    ) -> i32;
+    fn AXValueGetValue(value: CFTypeRef, value_type: u32, value_ptr: *mut c_void) -> bool;
+}
+
+pub struct CursorTracker {
+    events: Arc<Mutex<Vec<CursorEvent>>>,
+    click_targets: Arc<Mutex<Vec<ClickTarget>>>,
+    start_time: Instant,
+    keys: KeyCapture,
+    stop_tx: Option<Sender<()>>,
+    thread_handle: Option<thread::JoinHandle<()>>,
+    target_thread: Option<thread::JoinHandle<()>>,
+}
+
+impl CursorTracker {
+    pub fn new(keys: KeyCapture) -> Self {
+        Self {
+            events: Arc::new(Mutex::new(Vec::new())),
+            click_targets: Arc::new(Mutex::new(Vec::new())),
+            start_time: Instant::now(), // Will be reset in start()
+            keys,
+            stop_tx: None,
+            thread_handle: None,
+            target_thread: None,
+        }
+    }
+
+    pub fn start(&mut self) -> Result<()> {
+        // Reset start time NOW (after FFmpeg has been spawned)
+        self.start_time = Instant::now();
+
+        let events = Arc::clone(&self.events);
+        let start_time = self.start_time;
+        let (stop_tx, stop_rx) = mpsc::channel();
+        self.stop_tx = Some(stop_tx);
+
+        // Accessibility lookups can take a few ms, so they run off the tap thread
+        let (click_tx, click_rx) = mpsc::channel();
+        let click_targets = Arc::clone(&self.click_targets);
+        self.target_thread = Some(thread::spawn(move || {
+            run_target_lookup(click_targets, click_rx);
+        }));
+
+        let keys = self.keys;
+        let handle = thread::spawn(move || {
+            run_event_tap(events, start_time, keys, stop_rx, click_tx);
+        });
+
+        self.thread_handle = Some(handle);
+        Ok(())
+    }
+
+    /// Bounds of the UI elements that were clicked (available after `stop`)
+    pub fn click_targets(&self) -> Vec<ClickTarget> {
+        self.click_targets.lock().unwrap().clone()
+    }
+
+    /// Number of events recorded so far
+    pub fn event_count(&self) -> usize {
+        self.events.lock().unwrap().len()
+    }
+
+    /// Stop tracking and return (events, tracking_duration)
+    pub fn stop(&mut self) -> (Vec<CursorEvent>, f64) {
+        // Calculate duration before stopping
+        let duration = self.start_time.elapsed().as_secs_f64();
+
+        // Signal the event tap thread to stop
+        if let Some(tx) = self.stop_tx.take() {
+            let _ = tx.send(());
+        }
+
+        // Wait for thread to finish
+        if let Some(handle) = self.thread_handle.take() {
+            let _ = handle.join();
+        }
+
+        // The tap thread dropped its sender, so the lookup thread drains and exits
+        if let Some(handle) = self.target_thread.take() {
+            let _ = handle.join();
+        }
+
+        // Return collected events and duration
+        let events = self.events.lock().unwrap();
+        (events.clone(), duration)
+    }
+}
+
+fn run_event_tap(
+    events: Arc<Mutex<Vec<CursorEvent>>>,
+    start_time: Instant,
+    keys: KeyCapture,
+    stop_rx: Receiver<()>,
+    click_tx: Sender<(f64, f64, f64)>,
+) {
+    // Event types to monitor
+    let mut event_types = vec![
+        CGEventType::MouseMoved,
+        CGEventType::LeftMouseDown,
+        CGEventType::RightMouseDown,
+        CGEventType::LeftMouseDragged,
+        CGEventType::RightMouseDragged,
+    ];
+    // Key events also need the Input Monitoring permission
+    if keys != KeyCapture::Off {
+        event_types.push(CGEventType::KeyDown);
+    }
+
+    let events_clone = Arc::clone(&events);
+    let shapes = RefCell::new(ShapeProbe::new());
+
+    let tap = CGEventTap::new(
+        CGEventTapLocation::HID,
+        CGEventTapPlacement::HeadInsertEventTap,
+        CGEventTapOptions::ListenOnly,
+        event_types,
+        move |_proxy, event_type, event: &CGEvent| {
+            let location = event.location();
+            let timestamp = start_time.elapsed().as_secs_f64();
+
+            let cursor_event_type = match event_type {
+                CGEventType::MouseMoved
+                | CGEventType::LeftMouseDragged
+                | CGEventType::RightMouseDragged => EventType::Move,
+                CGEventType::LeftMouseDown => EventType::LeftClick,
+                CGEventType::RightMouseDown => EventType::RightClick,
+                CGEventType::KeyDown => {
+                    // Holding a key repeats it
+                    if event.get_integer_value_field(EventField::KEYBOARD_EVENT_AUTOREPEAT) != 0 {
+                        return None;
+                    }
+                    let keycode = event.get_integer_value_field(EventField::KEYBOARD_EVENT_KEYCODE);
+                    let flags = event.get_flags();
+                    let press = KeyPress {
+                        code: key_code(keycode).map(str::to_string),
+                        modifiers: Modifiers {
+                            ctrl: flags.contains(CGEventFlags::CGEventFlagControl),
+                            alt: flags.contains(CGEventFlags::CGEventFlagAlternate),
+                            shift: flags.contains(CGEventFlags::CGEventFlagShift),
+                            meta: flags.contains(CGEventFlags::CGEventFlagCommand),
+                        },
+                    };
+                    EventType::KeyDown(keys.record(press)?)
+                }
+                _ => return None,
+            };
+
+            if matches!(
+                cursor_event_type,
+                EventType::LeftClick | EventType::RightClick
+            ) {
+                let _ = click_tx.send((timestamp, location.x, location.y));
+            }
+
+            let cursor_event = CursorEvent {
+                x: location.x,
+                y: location.y,
+                timestamp,
+                event_type: cursor_event_type,
+                display: None,
+                shape: shapes.borrow_mut().current(),
+            };
+
+            if let Ok(mut events) = events_clone.lock() {
+                events.push(cursor_event);
+            }
+
+            None // Don't modify the event
+        },
+    );
+
+    let tap = match tap {
+        Ok(t) => t,
+        Err(()) => {
+            tracing::error!(
+                "Failed to create event tap. Make sure Accessibility permissions are granted."
+            );
+            return;
+        }
+    };
+
+    // Add to run loop
+    let source = tap
+        .mach_port
+        .create_runloop_source(0)
+        .expect("Failed to create run loop source");
+
+    let run_loop = CFRunLoop::get_current();
+    run_loop.add_source(&source, unsafe { kCFRunLoopCommonModes });
+
+    tap.enable();
+
+    // Run until stop signal
+    loop {
+        // Process events for a short time (use DefaultMode, not CommonModes)
+        CFRunLoop::run_in_mode(
+            unsafe { kCFRunLoopDefaultMode },
+            Duration::from_millis(100),
+            false,
+        );
+
+        // Check for stop signal
+        if stop_rx.try_recv().is_ok() {
+            break;
+        }
+    }
+}
+
+/// Listens for a global hotkey while recording
+pub struct HotkeyListener {
+    presses: Arc<AtomicU32>,
+    stop_tx: Option<Sender<()>>,
+    thread_handle: Option<thread::JoinHandle<()>>,
+}
+
+impl HotkeyListener {
+    /// Watch key presses with a listen-only event tap, which needs the same
+    /// Accessibility permission as cursor tracking
+    pub fn start(hotkey: &Hotkey) -> Result<Self> {
+        let presses = Arc::new(AtomicU32::new(0));
+        let (stop_tx, stop_rx) = mpsc::channel();
+        let (ready_tx, ready_rx) = mpsc::channel();
+
+        let hotkey = *hotkey;
+        let presses_clone = Arc::clone(&presses);
+        let handle = thread::spawn(move || {
+            run_hotkey_tap(hotkey, presses_clone, stop_rx, ready_tx);
+        });
+
+        ready_rx
+            .recv()
+            .context("Hotkey thread exited")?
+            .map_err(|()| {
+                anyhow::anyhow!(
+                "Failed to listen for hotkey {}. Make sure Accessibility permissions are granted.",
+                hotkey
+            )
+            })?;
+
+        Ok(Self {
+            presses,
+            stop_tx: Some(stop_tx),
+            thread_handle: Some(handle),
+        })
+    }
+
+    /// Whether the hotkey was pressed since the last call
+    pub fn take_press(&self) -> bool {
+        self.presses.swap(0, Ordering::SeqCst) > 0
+    }
+}
+
+impl Drop for HotkeyListener {
+    fn drop(&mut self) {
+        if let Some(tx) = self.stop_tx.take() {
+            let _ = tx.send(());
+        }
+        if let Some(handle) = self.thread_handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+fn run_hotkey_tap(
+    hotkey: Hotkey,
+    presses: Arc<AtomicU32>,
+    stop_rx: Receiver<()>,
+    ready: Sender<std::result::Result<(), ()>>,
+) {
+    let keycode = virtual_keycode(hotkey.key);
+    let modifier_mask = CGEventFlags::CGEventFlagControl
+        | CGEventFlags::CGEventFlagAlternate
+        | CGEventFlags::CGEventFlagShift
+        | CGEventFlags::CGEventFlagCommand;
+    let mut wanted = CGEventFlags::empty();
+    for (on, flag) in [
+        (hotkey.ctrl, CGEventFlags::CGEventFlagControl),
+        (hotkey.alt, CGEventFlags::CGEventFlagAlternate),
+        (hotkey.shift, CGEventFlags::CGEventFlagShift),
+        (hotkey.meta, CGEventFlags::CGEventFlagCommand),
+    ] {
+        if on {
+            wanted |= flag;
+        }
+    }
+
+    let tap = CGEventTap::new(
+        CGEventTapLocation::HID,
+        CGEventTapPlacement::HeadInsertEventTap,
+        CGEventTapOptions::ListenOnly,
+        vec![CGEventType::KeyDown],
+        move |_proxy, _event_type, event: &CGEvent| {
+            let pressed = event.get_integer_value_field(EventField::KEYBOARD_EVENT_KEYCODE)
+                == keycode
+                // Holding the key repeats it
+                && event.get_integer_value_field(EventField::KEYBOARD_EVENT_AUTOREPEAT) == 0
+                && event.get_flags() & modifier_mask == wanted;
+            if pressed {
+                presses.fetch_add(1, Ordering::SeqCst);
+            }
+            None
+        },
+    );
+    let Ok(tap) = tap else {
+        let _ = ready.send(Err(()));
+        return;
+    };
+
+    let source = tap
+        .mach_port
+        .create_runloop_source(0)
+        .expect("Failed to create run loop source");
+    let run_loop = CFRunLoop::get_current();
+    run_loop.add_source(&source, unsafe { kCFRunLoopCommonModes });
+    tap.enable();
+    let _ = ready.send(Ok(()));
+
+    loop {
+        CFRunLoop::run_in_mode(
+            unsafe { kCFRunLoopDefaultMode },
+            Duration::from_millis(100),
+            false,
+        );
+        if stop_rx.try_recv().is_ok() {
+            break;
+        }
+    }
+}
+
+/// macOS virtual keycode (kVK_*) of a hotkey key, by its position on an
+/// ANSI keyboard
+fn virtual_keycode(key: HotkeyKey) -> i64 {
+    match key {
+        HotkeyKey::Char(c) => match c {
+            'a' => 0x00,
+            's' => 0x01,
+            'd' => 0x02,
+            'f' => 0x03,
+            'h' => 0x04,
+            'g' => 0x05,
+            'z' => 0x06,
+            'x' => 0x07,
+            'c' => 0x08,
+            'v' => 0x09,
+            'b' => 0x0B,
+            'q' => 0x0C,
+            'w' => 0x0D,
+            'e' => 0x0E,
+            'r' => 0x0F,
+            'y' => 0x10,
+            't' => 0x11,
+            '1' => 0x12,
+            '2' => 0x13,
+            '3' => 0x14,
+            '4' => 0x15,
+            '6' => 0x16,
+            '5' => 0x17,
+            '9' => 0x19,
+            '7' => 0x1A,
+            '8' => 0x1C,
+            '0' => 0x1D,
+            'o' => 0x1F,
+            'u' => 0x20,
+            'i' => 0x22,
+            'p' => 0x23,
+            'l' => 0x25,
+            'j' => 0x26,
+            'k' => 0x28,
+            'n' => 0x2D,
+            'm' => 0x2E,
+            _ => -1,
+        },
+        HotkeyKey::Function(n) => match n {
+            1 => 0x7A,
+            2 => 0x78,
+            3 => 0x63,
+            4 => 0x76,
+            5 => 0x60,
+            6 => 0x61,
+            7 => 0x62,
+            8 => 0x64,
+            9 => 0x65,
+            10 => 0x6D,
+            11 => 0x67,
+            12 => 0x6F,
+            _ => -1,
+        },
+    }
+}
+
+/// W3C code of a macOS virtual keycode (kVK_*, which are physical positions)
+fn key_code(keycode: i64) -> Option<&'static str> {
+    Some(match keycode {
+        0x00 => "KeyA",
+        0x01 => "KeyS",
+        0x02 => "KeyD",
+        0x03 => "KeyF",
+        0x04 => "KeyH",
+        0x05 => "KeyG",
+        0x06 => "KeyZ",
+        0x07 => "KeyX",
+        0x08 => "KeyC",
+        0x09 => "KeyV",
+        0x0A => "IntlBackslash",
+        0x0B => "KeyB",
+        0x0C => "KeyQ",
+        0x0D => "KeyW",
+        0x0E => "KeyE",
+        0x0F => "KeyR",
+        0x10 => "KeyY",
+        0x11 => "KeyT",
+        0x12 => "Digit1",
+        0x13 => "Digit2",
+        0x14 => "Digit3",
+        0x15 => "Digit4",
+        0x16 => "Digit6",
+        0x17 => "Digit5",
+        0x18 => "Equal",
+        0x19 => "Digit9",
+        0x1A => "Digit7",
+        0x1B => "Minus",
+        0x1C => "Digit8",
+        0x1D => "Digit0",
+        0x1E => "BracketRight",
+        0x1F => "KeyO",
+        0x20 => "KeyU",
+        0x21 => "BracketLeft",
+        0x22 => "KeyI",
+        0x23 => "KeyP",
+        0x24 => "Enter",
+        0x25 => "KeyL",
+        0x26 => "KeyJ",
+        0x27 => "Quote",
+        0x28 => "KeyK",
+        0x29 => "Semicolon",
+        0x2A => "Backslash",
+        0x2B => "Comma",
+        0x2C => "Slash",
+        0x2D => "KeyN",
+        0x2E => "KeyM",
+        0x2F => "Period",
+        0x30 => "Tab",
+        0x31 => "Space",
+        0x32 => "Backquote",
+        0x33 => "Backspace",
+        0x35 => "Escape",
+        0x4C => "NumpadEnter",
+        0x60 => "F5",
+        0x61 => "F6",
+        0x62 => "F7",
+        0x63 => "F3",
+        0x64 => "F8",
+        0x65 => "F9",
+        0x67 => "F11",
+        0x6D => "F10",
+        0x6F => "F12",
+        0x73 => "Home",
+        0x74 => "PageUp",
+        0x75 => "Delete",
+        0x76 => "F4",
+        0x77 => "End",
+        0x78 => "F2",
+        0x79 => "PageDown",
+        0x7A => "F1",
+        0x7B => "ArrowLeft",
+        0x7C => "ArrowRight",
+        0x7D => "ArrowDown",
+        0x7E => "ArrowUp",
+        _ => return None,
+    })
+}
+
+/// Resolve clicked positions to accessibility element bounds
+fn run_target_lookup(targets: Arc<Mutex<Vec<ClickTarget>>>, clicks: Receiver<(f64, f64, f64)>) {
+    let system = unsafe { AXUIElementCreateSystemWide() };
+    if system.is_null() {
+        return;
+    }
+
+    for (timestamp, x, y) in clicks {
+        if let Some((ex, ey, width, height)) = unsafe { element_bounds_at(system, x, y) } {
+            if let Ok(mut targets) = targets.lock() {
+                targets.push(ClickTarget {
+                    timestamp,
+                    x: ex,
+                    y: ey,
+                    width,
+                    height,
+                });
+            }
+        }
+    }
+
+    unsafe { CFRelease(system as CFTypeRef) };
+}
+
+/// Bounds (x, y, width, height) in screen points of the element at (x, y)
+unsafe fn element_bounds_at(
+    system: AXUIElementRef,
+    x: f64,
+    y: f64,
+) -> Option<(f64, f64, f64, f64)> {
+    let mut element: AXUIElementRef = std::ptr::null();
+    if AXUIElementCopyElementAtPosition(system, x as f32, y as f32, &mut element)
+        != K_AX_ERROR_SUCCESS
+        || element.is_null()
+    {
+        return None;
+    }
+
+    let mut origin = CGPoint::new(0.0, 0.0);
+    let mut size = CGSize::new(0.0, 0.0);
+    let found = copy_ax_value(
+        element,
+        "AXPosition",
+        K_AX_VALUE_CG_POINT_TYPE,
+        &mut origin as *mut _ as *mut c_void,
+    ) && copy_ax_value(
+        element,
+        "AXSize",
+        K_AX_VALUE_CG_SIZE_TYPE,
+        &mut size as *mut _ as *mut c_void,
+    );
+    CFRelease(element as CFTypeRef);
+
+    (found && size.width > 0.0 && size.height > 0.0).then_some((
+        origin.x,
+        origin.y,
+        size.width,
+        size.height,
+    ))
+}
+
+/// Read an AXValue attribute of `element` into `out`
+unsafe fn copy_ax_value(
+    element: AXUIElementRef,
+    attribute: &str,
+    value_type: u32,
+    out: *mut c_void,
+) -> bool {
+    let attribute = CFString::new(attribute);
+    let mut value: CFTypeRef = std::ptr::null();
+    if AXUIElementCopyAttributeValue(
+        element,
+        attribute.as_concrete_TypeRef() as *const c_void,
+        &mut value,
+    ) != K_AX_ERROR_SUCCESS
+        || value.is_null()
+    {
+        return false;
+    }
+    let ok = AXValueGetValue(value, value_type, out);
+    CFRelease(value);
+    ok
+}
@@ -0,0 +1,307 @@
+//! The `--select` overlay on macOS: a dimmed borderless window over each
+//! display, with the dragged rectangle outlined in a window of its own
+//!
+//! AppKit is reached through the Objective-C runtime directly, as in
+//! `cursor_shape`.
+
+use crate::macos::window::list_windows;
+use crate::recording::select::{drag_region, window_at, Point, Selection};
+use anyhow::{Context, Result};
+use core_foundation::runloop::kCFRunLoopDefaultMode;
+use core_graphics::display::CGDisplay;
+use core_graphics::event::CGEvent;
+use core_graphics::event_source::{CGEventSource, CGEventSourceStateID};
+use core_graphics::geometry::{CGPoint, CGRect, CGSize};
+use std::ffi::{c_char, c_void, CStr};
+
+type Id = *mut c_void;
+
+#[link(name = "AppKit", kind = "framework")]
+extern "C" {}
+
+#[link(name = "objc")]
+extern "C" {
+    fn objc_getClass(name: *const c_char) -> Id;
+    fn sel_registerName(name: *const c_char) -> Id;
+    fn objc_msgSend();
+    fn objc_autoreleasePoolPush() -> *mut c_void;
+    fn objc_autoreleasePoolPop(pool: *mut c_void);
+}
+
+// NSEventType values
+const LEFT_MOUSE_DOWN: usize = 1;
+const LEFT_MOUSE_UP: usize = 2;
+const RIGHT_MOUSE_DOWN: usize = 3;
+const MOUSE_MOVED: usize = 5;
+const LEFT_MOUSE_DRAGGED: usize = 6;
+const KEY_DOWN: usize = 10;
+const OTHER_MOUSE_DOWN: usize = 25;
+
+/// `kVK_Escape`
+const ESCAPE_KEY_CODE: u16 = 53;
+
+/// `NSScreenSaverWindowLevel`, above the menu bar and the Dock
+const OVERLAY_LEVEL: isize = 1000;
+
+/// `NSApplicationActivationPolicyAccessory`: no Dock icon or menu bar
+const ACCESSORY_POLICY: isize = 1;
+
+/// `NSWindowStyleMaskBorderless` and `NSBackingStoreBuffered`
+const BORDERLESS: usize = 0;
+const BUFFERED: usize = 2;
+
+/// `NSWindowCollectionBehaviorCanJoinAllSpaces | FullScreenAuxiliary`, so
+/// the overlay also covers full-screen apps
+const ALL_SPACES: usize = 1 | (1 << 8);
+
+/// `NSBoxCustom` and `NSNoTitle`
+const CUSTOM_BOX: usize = 4;
+const NO_TITLE: usize = 0;
+
+/// Let the user drag out a rectangle or click a window; None if they
+/// pressed Escape or another mouse button
+pub fn select_on_screen() -> Result<Option<Selection>> {
+    let displays = CGDisplay::active_displays()
+        .map_err(|e| anyhow::anyhow!("Failed to get displays: {:?}", e))?;
+    let main_height = CGDisplay::main().bounds().size.height;
+
+    let result = unsafe {
+        let pool = objc_autoreleasePoolPush();
+        let result = overlay(&displays, main_height);
+        objc_autoreleasePoolPop(pool);
+        result
+    };
+
+    let Some((start, end)) = result? else {
+        return Ok(None);
+    };
+    if let Some(region) = drag_region(start, end) {
+        return Ok(Some(Selection::Region(region)));
+    }
+    // The window server lists windows front to back
+    let windows = list_windows()?;
+    let stacking: Vec<u32> = windows.iter().rev().map(|w| w.id).collect();
+    let windows: Vec<_> = windows.into_iter().map(|w| (w.id, w.bounds)).collect();
+    window_at(end, &windows, &stacking)
+        .map(|id| Some(Selection::Window(id)))
+        .context("No window there to record; drag out a rectangle instead")
+}
+
+/// Cover each of `displays` while following a drag, then take the cover
+/// down again; the press and release points
+unsafe fn overlay(displays: &[u32], main_height: f64) -> Result<Option<(Point, Point)>> {
+    if send0::<i8>(class(c"NSThread"), c"isMainThread") == 0 {
+        anyhow::bail!("--select has to run on the main thread");
+    }
+    let app: Id = send0(class(c"NSApplication"), c"sharedApplication");
+    send1::<_, i8>(app, c"setActivationPolicy:", ACCESSORY_POLICY);
+    send0::<()>(app, c"finishLaunching");
+    send1::<_, ()>(app, c"activateIgnoringOtherApps:", 1i8);
+
+    let mut windows: Vec<Id> = displays
+        .iter()
+        .map(|&display| {
+            let cover = window(to_cocoa(CGDisplay::new(display).bounds(), main_height));
+            send1::<_, ()>(cover, c"setBackgroundColor:", gray(0.0, 0.2));
+            send1::<_, ()>(cover, c"setAcceptsMouseMovedEvents:", 1i8);
+            send0::<()>(cover, c"orderFrontRegardless");
+            cover
+        })
+        .collect();
+
+    // The rectangle is a box filling a window that clicks go through
+    let outline = window(CGRect::new(&CGPoint::new(0.0, 0.0), &CGSize::new(1.0, 1.0)));
+    send1::<_, ()>(outline, c"setLevel:", OVERLAY_LEVEL + 1);
+    send1::<_, ()>(outline, c"setIgnoresMouseEvents:", 1i8);
+    send1::<_, ()>(
+        outline,
+        c"setBackgroundColor:",
+        send0::<Id>(class(c"NSColor"), c"clearColor"),
+    );
+    let outline_box: Id = send0(send0::<Id>(class(c"NSBox"), c"alloc"), c"init");
+    send1::<_, ()>(outline_box, c"setBoxType:", CUSTOM_BOX);
+    send1::<_, ()>(outline_box, c"setTitlePosition:", NO_TITLE);
+    send1::<_, ()>(outline_box, c"setBorderWidth:", 1.0f64);
+    send1::<_, ()>(outline_box, c"setBorderColor:", gray(1.0, 1.0));
+    send1::<_, ()>(outline_box, c"setFillColor:", gray(1.0, 0.1));
+    send1::<_, ()>(outline, c"setContentView:", outline_box);
+    send0::<()>(outline_box, c"release");
+    windows.push(outline);
+
+    let result = drag(app, outline, main_height);
+
+    // Closing releases each window, as `releasedWhenClosed` is on
+    for window in windows {
+        send1::<_, ()>(window, c"orderOut:", std::ptr::null_mut::<c_void>());
+        send0::<()>(window, c"close");
+    }
+    send0::<()>(send0::<Id>(class(c"NSCursor"), c"arrowCursor"), c"set");
+    // Give the window server a moment to take the cover down before capture starts
+    next_event(
+        app,
+        send1(class(c"NSDate"), c"dateWithTimeIntervalSinceNow:", 0.1f64),
+    );
+    result
+}
+
+/// Follow the pointer from a left-button press to its release, outlining
+/// the rectangle on the way; the press and release points
+unsafe fn drag(app: Id, outline: Id, main_height: f64) -> Result<Option<(Point, Point)>> {
+    let crosshair: Id = send0(class(c"NSCursor"), c"crosshairCursor");
+    send0::<()>(crosshair, c"set");
+    let forever: Id = send0(class(c"NSDate"), c"distantFuture");
+    let mut start = None;
+
+    loop {
+        let event = next_event(app, forever);
+        if event.is_null() {
+            continue;
+        }
+        match send0::<usize>(event, c"type") {
+            LEFT_MOUSE_DOWN => start = Some(pointer()?),
+            RIGHT_MOUSE_DOWN | OTHER_MOUSE_DOWN => return Ok(None),
+            KEY_DOWN if send0::<u16>(event, c"keyCode") == ESCAPE_KEY_CODE => return Ok(None),
+            // Other keys would only beep
+            KEY_DOWN => {}
+            // Other apps' cursor rects can swap the crosshair out
+            MOUSE_MOVED => send0::<()>(crosshair, c"set"),
+            LEFT_MOUSE_DRAGGED => {
+                let Some(start) = start else { continue };
+                match drag_region(start, pointer()?) {
+                    Some(region) => {
+                        let frame = CGRect::new(
+                            &CGPoint::new(region.x as f64, region.y as f64),
+                            &CGSize::new(region.width as f64, region.height as f64),
+                        );
+                        send2::<_, _, ()>(
+                            outline,
+                            c"setFrame:display:",
+                            to_cocoa(frame, main_height),
+                            1i8,
+                        );
+                        send0::<()>(outline, c"orderFrontRegardless");
+                    }
+                    None => send1::<_, ()>(outline, c"orderOut:", std::ptr::null_mut::<c_void>()),
+                }
+            }
+            LEFT_MOUSE_UP => {
+                let Some(start) = start else { continue };
+                return Ok(Some((start, pointer()?)));
+            }
+            _ => send1::<_, ()>(app, c"sendEvent:", event),
+        }
+    }
+}
+
+/// Where the pointer is, in display coordinates (points from the main
+/// display's top-left, as `list windows` gives bounds)
+fn pointer() -> Result<Point> {
+    let source = CGEventSource::new(CGEventSourceStateID::CombinedSessionState)
+        .map_err(|()| anyhow::anyhow!("Failed to create an event source"))?;
+    let location = CGEvent::new(source)
+        .map_err(|()| anyhow::anyhow!("Failed to read the pointer position"))?
+        .location();
+    Ok((location.x.round() as i32, location.y.round() as i32))
+}
+
+/// `rect` in display coordinates as Cocoa screen coordinates, which start
+/// at the main display's bottom-left and go up
+fn to_cocoa(rect: CGRect, main_height: f64) -> CGRect {
+    CGRect::new(
+        &CGPoint::new(
+            rect.origin.x,
+            main_height - rect.origin.y - rect.size.height,
+        ),
+        &rect.size,
+    )
+}
+
+/// A borderless, see-through window over `frame`, above everything else
+unsafe fn window(frame: CGRect) -> Id {
+    let window: Id = send0(class(c"NSWindow"), c"alloc");
+    let window: Id = send4(
+        window,
+        c"initWithContentRect:styleMask:backing:defer:",
+        frame,
+        BORDERLESS,
+        BUFFERED,
+        0i8,
+    );
+    send1::<_, ()>(window, c"setLevel:", OVERLAY_LEVEL);
+    send1::<_, ()>(window, c"setCollectionBehavior:", ALL_SPACES);
+    send1::<_, ()>(window, c"setOpaque:", 0i8);
+    send1::<_, ()>(window, c"setHasShadow:", 0i8);
+    window
+}
+
+/// `[NSColor colorWithCalibratedWhite:alpha:]`, autoreleased
+unsafe fn gray(white: f64, alpha: f64) -> Id {
+    send2(
+        class(c"NSColor"),
+        c"colorWithCalibratedWhite:alpha:",
+        white,
+        alpha,
+    )
+}
+
+/// The next event of any kind before `until`, taken off the queue; null if
+/// none came
+unsafe fn next_event(app: Id, until: Id) -> Id {
+    send4(
+        app,
+        c"nextEventMatchingMask:untilDate:inMode:dequeue:",
+        u64::MAX,
+        until,
+        kCFRunLoopDefaultMode as Id,
+        1i8,
+    )
+}
+
+unsafe fn class(name: &CStr) -> Id {
+    objc_getClass(name.as_ptr())
+}
+
+/// `objc_msgSend` typed for a message with no arguments
+unsafe fn send0<T>(receiver: Id, selector: &CStr) -> T {
+    let send: unsafe extern "C" fn(Id, Id) -> T =
+        std::mem::transmute(objc_msgSend as unsafe extern "C" fn());
+    send(receiver, sel_registerName(selector.as_ptr()))
+}
+
+/// `objc_msgSend` typed for a message with one argument
+unsafe fn send1<A, T>(receiver: Id, selector: &CStr, a: A) -> T {
+    let send: unsafe extern "C" fn(Id, Id, A) -> T =
+        std::mem::transmute(objc_msgSend as unsafe extern "C" fn());
+    send(receiver, sel_registerName(selector.as_ptr()), a)
+}
+
+/// `objc_msgSend` typed for a message with two arguments
+unsafe fn send2<A, B, T>(receiver: Id, selector: &CStr, a: A, b: B) -> T {
+    let send: unsafe extern "C" fn(Id, Id, A, B) -> T =
+        std::mem::transmute(objc_msgSend as unsafe extern "C" fn());
+    send(receiver, sel_registerName(selector.as_ptr()), a, b)
+}
+
+/// `objc_msgSend` typed for a message with four arguments
+unsafe fn send4<A, B, C, D, T>(receiver: Id, selector: &CStr, a: A, b: B, c: C, d: D) -> T {
+    let send: unsafe extern "C" fn(Id, Id, A, B, C, D) -> T =
+        std::mem::transmute(objc_msgSend as unsafe extern "C" fn());
+    send(receiver, sel_registerName(selector.as_ptr()), a, b, c, d)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_cocoa() {
+        // A 1440x900 main display, and one above it
+        let top = CGRect::new(&CGPoint::new(0.0, 0.0), &CGSize::new(200.0, 100.0));
+        let cocoa = to_cocoa(top, 900.0);
+        assert_eq!((cocoa.origin.x, cocoa.origin.y), (0.0, 800.0));
+        assert_eq!((cocoa.size.width, cocoa.size.height), (200.0, 100.0));
+
+        let above = CGRect::new(&CGPoint::new(0.0, -1080.0), &CGSize::new(1920.0, 1080.0));
+        assert_eq!(to_cocoa(above, 900.0).origin.y, 900.0);
+    }
+}
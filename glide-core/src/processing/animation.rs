@@ -0,0 +1,258 @@
+//! Export of the camera and cursor animation as JSON
+//!
+//! Samples the same zoom and cursor models used for rendering and writes them
+//! out in resolution-independent coordinates, so web players can re-create
+//! glide's effects client-side on top of the raw recording.
+
+use crate::cursor_types::{CursorEvent, EventType};
+use crate::processing::cursor::{get_smoothed_cursor, CursorConfig};
+use crate::processing::pipeline::Camera;
+use crate::processing::zoom::ZoomConfig;
+use crate::recording::metadata::RecordingMetadata;
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::path::Path;
+
+/// Format identifier written into every export
+const ANIMATION_FORMAT: &str = "glide-animation";
+const ANIMATION_VERSION: u32 = 1;
+
+/// Camera/cursor animation synced to the raw recording
+#[derive(Debug, Serialize)]
+pub struct Animation {
+    pub format: &'static str,
+    pub version: u32,
+    /// Raw video dimensions in pixels
+    pub width: u32,
+    pub height: u32,
+    /// Sample rate of `camera` and `cursor`
+    pub fps: f64,
+    /// Time range of the raw video covered by the samples (seconds)
+    pub start: f64,
+    pub duration: f64,
+    pub camera: Vec<CameraSample>,
+    pub cursor: Vec<CursorSample>,
+    pub clicks: Vec<ClickMarker>,
+}
+
+/// Camera state at one sample. Coordinates are normalized to the raw video
+/// (0.0 = left/top edge, 1.0 = right/bottom edge).
+#[derive(Debug, Serialize)]
+pub struct CameraSample {
+    /// Time in the raw video (seconds)
+    pub t: f64,
+    pub zoom: f64,
+    /// Zoom focus point
+    pub x: f64,
+    pub y: f64,
+}
+
+/// Smoothed cursor state at one sample (normalized coordinates)
+#[derive(Debug, Serialize)]
+pub struct CursorSample {
+    pub t: f64,
+    pub x: f64,
+    pub y: f64,
+    pub opacity: f64,
+}
+
+/// A click in the raw video timeline (normalized coordinates)
+#[derive(Debug, Serialize)]
+pub struct ClickMarker {
+    pub t: f64,
+    pub x: f64,
+    pub y: f64,
+    pub button: &'static str,
+}
+
+/// Build the animation for `[start, start + duration)` of the raw video.
+///
+/// `camera` is the one the frames are rendered with. `time_offset` maps
+/// sample time (0 = `start`) to cursor-tracking time, as computed by the
+/// pipeline (tracking offset plus trim).
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn build_animation(
+    metadata: &RecordingMetadata,
+    camera: &Camera,
+    zoom_config: &ZoomConfig,
+    cursor_config: &CursorConfig,
+    fps: f64,
+    start: f64,
+    duration: f64,
+    time_offset: f64,
+) -> Animation {
+    let scale_factor = metadata.scale_factor.max(1.0);
+    let width = metadata.width.max(1) as f64;
+    let height = metadata.height.max(1) as f64;
+
+    // Screen points at tracking time `t` -> normalized raw video coordinates
+    let normalize = |x: f64, y: f64, t: f64| -> (f64, f64) {
+        let (offset_x, offset_y) = metadata.window_offset_at(t);
+        (
+            (x - offset_x) * scale_factor / width,
+            (y - offset_y) * scale_factor / height,
+        )
+    };
+
+    let sample_count = (duration * fps).ceil() as usize;
+    let mut samples = Vec::with_capacity(sample_count);
+    let mut cursor = Vec::with_capacity(sample_count);
+
+    for i in 0..sample_count {
+        let t = i as f64 / fps;
+        // Cursor events are on the tracking clock, which is offset from the video clock
+        let event_time = t + time_offset;
+
+        let ((zoom, cx, cy), _) = camera.at(event_time, zoom_config);
+        // A still camera has no focus; the middle stands in
+        let (x, y) = match camera {
+            Camera::Still => (0.5, 0.5),
+            _ => normalize(cx, cy, event_time),
+        };
+        samples.push(CameraSample {
+            t: round(start + t),
+            zoom: round(zoom),
+            x: round(x),
+            y: round(y),
+        });
+
+        let state = get_smoothed_cursor(event_time, &metadata.cursor_events, cursor_config);
+        let (x, y) = normalize(state.x, state.y, event_time);
+        cursor.push(CursorSample {
+            t: round(start + t),
+            x: round(x),
+            y: round(y),
+            opacity: round(state.opacity),
+        });
+    }
+
+    let clicks = metadata
+        .cursor_events
+        .iter()
+        .filter_map(|e| click_marker(e, time_offset - start, &normalize))
+        .filter(|c| c.t >= start && c.t < start + duration)
+        .collect();
+
+    Animation {
+        format: ANIMATION_FORMAT,
+        version: ANIMATION_VERSION,
+        width: metadata.width,
+        height: metadata.height,
+        fps,
+        start,
+        duration,
+        camera: samples,
+        cursor,
+        clicks,
+    }
+}
+
+fn click_marker(
+    event: &CursorEvent,
+    video_to_event: f64,
+    normalize: &impl Fn(f64, f64, f64) -> (f64, f64),
+) -> Option<ClickMarker> {
+    let button = match event.event_type {
+        EventType::LeftClick => "left",
+        EventType::RightClick => "right",
+        _ => return None,
+    };
+    let (x, y) = normalize(event.x, event.y, event.timestamp);
+    Some(ClickMarker {
+        t: round(event.timestamp - video_to_event),
+        x: round(x),
+        y: round(y),
+        button,
+    })
+}
+
+/// Keep exports compact: 4 decimal places is sub-pixel even at 8K
+fn round(v: f64) -> f64 {
+    (v * 10_000.0).round() / 10_000.0
+}
+
+impl Animation {
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let json = serde_json::to_string(self)?;
+        std::fs::write(path, json)
+            .with_context(|| format!("Failed to write animation to {:?}", path))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_event(x: f64, y: f64, timestamp: f64, event_type: EventType) -> CursorEvent {
+        CursorEvent {
+            x,
+            y,
+            timestamp,
+            event_type,
+            display: None,
+            shape: None,
+        }
+    }
+
+    #[test]
+    fn test_animation_is_normalized_and_synced() {
+        let mut metadata = RecordingMetadata::new_display(0, 1000, 500, 1.0);
+        metadata.cursor_events = vec![
+            make_event(500.0, 250.0, 0.5, EventType::Move),
+            make_event(500.0, 250.0, 1.5, EventType::LeftClick),
+        ];
+
+        // Video starts 0.5s after tracking; export from 1.0s into the video
+        let animation = build_animation(
+            &metadata,
+            &Camera::Clicks(&metadata.cursor_events),
+            &ZoomConfig::default(),
+            &CursorConfig::default(),
+            10.0,
+            1.0,
+            2.0,
+            0.5 + 1.0,
+        );
+
+        assert_eq!(animation.camera.len(), 20);
+        assert!((animation.camera[0].t - 1.0).abs() < 1e-9);
+
+        // Click at tracking time 1.5s = video time 1.0s
+        assert_eq!(animation.clicks.len(), 1);
+        assert!((animation.clicks[0].t - 1.0).abs() < 1e-9);
+        assert!((animation.clicks[0].x - 0.5).abs() < 1e-9);
+        assert!((animation.clicks[0].y - 0.5).abs() < 1e-9);
+
+        // Zoomed in at the click
+        assert!(animation.camera[0].zoom > 1.5);
+    }
+
+    #[test]
+    fn test_still_camera() {
+        let mut metadata = RecordingMetadata::new_display(0, 1000, 500, 1.0);
+        metadata.cursor_events = vec![
+            make_event(200.0, 100.0, 0.5, EventType::Move),
+            make_event(200.0, 100.0, 1.0, EventType::LeftClick),
+        ];
+
+        // --no-zoom: the same camera as the frames, which never moves
+        let animation = build_animation(
+            &metadata,
+            &Camera::Still,
+            &ZoomConfig::default(),
+            &CursorConfig::default(),
+            10.0,
+            0.0,
+            2.0,
+            0.0,
+        );
+        assert!(animation
+            .camera
+            .iter()
+            .all(|c| (c.zoom, c.x, c.y) == (1.0, 0.5, 0.5)));
+        // The cursor and clicks are still there to draw
+        assert_eq!(animation.clicks.len(), 1);
+        assert!((animation.cursor[15].x - 0.2).abs() < 1e-9);
+    }
+}
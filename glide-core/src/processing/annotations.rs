@@ -0,0 +1,454 @@
+//! Timed text callouts loaded from an annotations file
+//!
+//! Each annotation shows a text bubble during a time range of the output
+//! video, optionally with an arrow pointing at a spot on screen. Positions
+//! are fractions of the output frame (0.0 = left/top, 1.0 = right/bottom),
+//! so callouts stay put while the camera zooms underneath them.
+//!
+//! ```yaml
+//! - start: 1.5
+//!   end: 4.0
+//!   text: Open the settings menu
+//!   position: top-right
+//!   arrow: { x: 0.85, y: 0.12 }
+//!   style: { size: 36, background: "#0072b2" }
+//! ```
+//!
+//! Files ending in `.toml` list the same fields under `annotation`, and
+//! either format takes `at` and `duration` in place of `start` and `end`:
+//!
+//! ```toml
+//! annotation = [
+//!   { at = 12.5, text = "Click Settings", duration = 3 },
+//! ]
+//! ```
+
+use crate::processing::effects::parse_hex_color;
+use crate::processing::palette::Palette;
+use crate::processing::shapes::{draw_arrow, fill_rounded_rect};
+use crate::processing::text::{draw_text, line_height, load_font, measure_text, wrap_text};
+use ab_glyph::FontArc;
+use anyhow::{Context, Result};
+use image::{Rgba, RgbaImage};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Fade in/out time for callouts (seconds)
+const FADE_DURATION: f64 = 0.2;
+/// How long callouts without an end or duration show (seconds)
+const DEFAULT_DURATION: f64 = 3.0;
+/// Distance kept between preset-positioned bubbles and the frame edge
+const EDGE_MARGIN: f64 = 0.04;
+const DEFAULT_TEXT_SIZE: f32 = 34.0;
+/// Bubbles wrap text at this fraction of the frame width
+const MAX_BUBBLE_WIDTH: f64 = 0.4;
+const ARROW_WIDTH: f64 = 4.0;
+const ARROW_HEAD: f64 = 18.0;
+
+/// One callout as written in the annotations file
+#[derive(Debug, Clone, Deserialize)]
+pub struct Annotation {
+    /// Output-video time the callout appears (seconds)
+    #[serde(alias = "at")]
+    pub start: f64,
+    /// Output-video time the callout disappears (seconds)
+    #[serde(default)]
+    pub end: Option<f64>,
+    /// How long the callout shows, instead of an end time (seconds)
+    #[serde(default)]
+    pub duration: Option<f64>,
+    pub text: String,
+    #[serde(default)]
+    pub position: Position,
+    /// Point the arrow tip at this spot
+    #[serde(default)]
+    pub arrow: Option<Point>,
+    #[serde(default)]
+    pub style: AnnotationStyle,
+}
+
+impl Annotation {
+    /// Output-video time the callout disappears (seconds)
+    pub fn end_time(&self) -> f64 {
+        self.end
+            .unwrap_or(self.start + self.duration.unwrap_or(DEFAULT_DURATION))
+    }
+}
+
+/// An annotations file in TOML, which has no top-level lists
+#[derive(Debug, Deserialize)]
+struct AnnotationFile {
+    #[serde(default, alias = "annotations")]
+    annotation: Vec<Annotation>,
+}
+
+/// A point in output-frame fractions
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq)]
+pub struct Point {
+    pub x: f64,
+    pub y: f64,
+}
+
+/// Bubble placement: a named spot or an explicit center point
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub enum Position {
+    Preset(PositionPreset),
+    Point(Point),
+}
+
+impl Default for Position {
+    fn default() -> Self {
+        Position::Preset(PositionPreset::Top)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum PositionPreset {
+    TopLeft,
+    Top,
+    TopRight,
+    Left,
+    Center,
+    Right,
+    BottomLeft,
+    Bottom,
+    BottomRight,
+}
+
+impl PositionPreset {
+    /// Horizontal and vertical alignment (0.0, 0.5 or 1.0)
+    fn alignment(self) -> (f64, f64) {
+        match self {
+            PositionPreset::TopLeft => (0.0, 0.0),
+            PositionPreset::Top => (0.5, 0.0),
+            PositionPreset::TopRight => (1.0, 0.0),
+            PositionPreset::Left => (0.0, 0.5),
+            PositionPreset::Center => (0.5, 0.5),
+            PositionPreset::Right => (1.0, 0.5),
+            PositionPreset::BottomLeft => (0.0, 1.0),
+            PositionPreset::Bottom => (0.5, 1.0),
+            PositionPreset::BottomRight => (1.0, 1.0),
+        }
+    }
+}
+
+/// Per-callout overrides; unset fields use the defaults and the palette
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct AnnotationStyle {
+    /// Font family or file path
+    pub font: Option<String>,
+    /// Text size in output pixels
+    pub size: Option<f32>,
+    /// Text color (hex); defaults to black or white for contrast
+    pub color: Option<String>,
+    /// Bubble and arrow color (hex); defaults to the palette's accents in turn
+    pub background: Option<String>,
+}
+
+/// Read annotations from a YAML file, or a TOML one if it ends in `.toml`
+pub fn load_annotations(path: &Path) -> Result<Vec<Annotation>> {
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read annotations from {}", path.display()))?;
+    let annotations: Vec<Annotation> = if path.extension().is_some_and(|ext| ext == "toml") {
+        toml::from_str::<AnnotationFile>(&contents)
+            .map(|file| file.annotation)
+            .with_context(|| format!("Invalid annotations file {}", path.display()))?
+    } else {
+        serde_yaml::from_str(&contents)
+            .with_context(|| format!("Invalid annotations file {}", path.display()))?
+    };
+
+    for (i, annotation) in annotations.iter().enumerate() {
+        if annotation.end.is_some() && annotation.duration.is_some() {
+            anyhow::bail!("Annotation {} has both an end and a duration", i + 1);
+        }
+        if annotation.end_time() <= annotation.start {
+            anyhow::bail!(
+                "Annotation {} ends ({:.2}s) before it starts ({:.2}s)",
+                i + 1,
+                annotation.end_time(),
+                annotation.start
+            );
+        }
+    }
+    Ok(annotations)
+}
+
+/// An annotation with fonts loaded and colors resolved
+struct PreparedAnnotation {
+    start: f64,
+    end: f64,
+    text: String,
+    position: Position,
+    arrow: Option<Point>,
+    font: FontArc,
+    size: f32,
+    color: Rgba<u8>,
+    background: Rgba<u8>,
+}
+
+/// Draws annotations onto output frames
+pub struct AnnotationRenderer {
+    annotations: Vec<PreparedAnnotation>,
+}
+
+impl AnnotationRenderer {
+    pub fn new(annotations: &[Annotation], palette: Palette) -> Result<Self> {
+        let mut fonts: HashMap<Option<String>, FontArc> = HashMap::new();
+        let mut prepared = Vec::with_capacity(annotations.len());
+
+        for (i, annotation) in annotations.iter().enumerate() {
+            let style = &annotation.style;
+            let font = match fonts.get(&style.font) {
+                Some(font) => font.clone(),
+                None => {
+                    let font = load_font(style.font.as_deref())?;
+                    fonts.insert(style.font.clone(), font.clone());
+                    font
+                }
+            };
+
+            let background = match &style.background {
+                Some(hex) => parse_hex_color(hex)
+                    .with_context(|| format!("Annotation {}: invalid background {}", i + 1, hex))?,
+                None => palette.accent(i),
+            };
+            let color = match &style.color {
+                Some(hex) => parse_hex_color(hex)
+                    .with_context(|| format!("Annotation {}: invalid color {}", i + 1, hex))?,
+                None => contrasting_text(background),
+            };
+
+            prepared.push(PreparedAnnotation {
+                start: annotation.start,
+                end: annotation.end_time(),
+                text: annotation.text.clone(),
+                position: annotation.position,
+                arrow: annotation.arrow,
+                font,
+                size: style.size.unwrap_or(DEFAULT_TEXT_SIZE).max(1.0),
+                color,
+                background,
+            });
+        }
+
+        Ok(Self {
+            annotations: prepared,
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.annotations.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.annotations.is_empty()
+    }
+
+    /// Draw every annotation visible at `timestamp` (output-video seconds)
+    pub fn draw(&self, canvas: &mut RgbaImage, timestamp: f64) {
+        for annotation in &self.annotations {
+            let opacity = fade_opacity(timestamp, annotation.start, annotation.end);
+            if opacity > 0.0 {
+                draw_annotation(canvas, annotation, opacity);
+            }
+        }
+    }
+}
+
+/// Black text on light bubbles, white on dark
+fn contrasting_text(background: Rgba<u8>) -> Rgba<u8> {
+    let luma =
+        0.299 * background[0] as f64 + 0.587 * background[1] as f64 + 0.114 * background[2] as f64;
+    if luma > 150.0 {
+        Rgba([0, 0, 0, 255])
+    } else {
+        Rgba([255, 255, 255, 255])
+    }
+}
+
+/// Opacity with short fades at both ends of the time range
+fn fade_opacity(timestamp: f64, start: f64, end: f64) -> f64 {
+    if timestamp < start || timestamp >= end {
+        return 0.0;
+    }
+    let fade = FADE_DURATION.min((end - start) / 2.0);
+    let fade_in = (timestamp - start) / fade;
+    let fade_out = (end - timestamp) / fade;
+    fade_in.min(fade_out).clamp(0.0, 1.0)
+}
+
+/// Top-left corner of a `bw` x `bh` bubble on a `cw` x `ch` frame
+fn bubble_origin(position: Position, cw: f64, ch: f64, bw: f64, bh: f64) -> (f64, f64) {
+    let (x, y) = match position {
+        Position::Preset(preset) => {
+            let (ax, ay) = preset.alignment();
+            let margin_x = cw * EDGE_MARGIN;
+            let margin_y = ch * EDGE_MARGIN;
+            (
+                margin_x + ax * (cw - 2.0 * margin_x - bw),
+                margin_y + ay * (ch - 2.0 * margin_y - bh),
+            )
+        }
+        Position::Point(p) => (p.x * cw - bw / 2.0, p.y * ch - bh / 2.0),
+    };
+    // Keep the whole bubble on screen
+    (
+        x.clamp(0.0, (cw - bw).max(0.0)),
+        y.clamp(0.0, (ch - bh).max(0.0)),
+    )
+}
+
+fn draw_annotation(canvas: &mut RgbaImage, annotation: &PreparedAnnotation, opacity: f64) {
+    let (cw, ch) = (canvas.width() as f64, canvas.height() as f64);
+    let size = annotation.size;
+
+    let lines = wrap_text(
+        &annotation.font,
+        size,
+        &annotation.text,
+        (cw * MAX_BUBBLE_WIDTH) as u32,
+    );
+    let line_h = line_height(&annotation.font, size) as f64;
+    let text_w = lines
+        .iter()
+        .map(|l| measure_text(&annotation.font, size, l).0)
+        .max()
+        .unwrap_or(0) as f64;
+    let padding = size as f64 * 0.5;
+    let bw = text_w + padding * 2.0;
+    let bh = line_h * lines.len() as f64 + padding * 2.0;
+    let (bx, by) = bubble_origin(annotation.position, cw, ch, bw, bh);
+
+    let mut background = annotation.background;
+    background[3] = (background[3] as f64 * opacity) as u8;
+
+    // Arrow first so the bubble covers its tail
+    if let Some(target) = annotation.arrow {
+        let tip = (target.x * cw, target.y * ch);
+        let center = (bx + bw / 2.0, by + bh / 2.0);
+        draw_arrow(canvas, center, tip, ARROW_WIDTH, ARROW_HEAD, background);
+    }
+
+    fill_rounded_rect(canvas, bx, by, bw, bh, padding.min(bh / 2.0), background);
+
+    let mut color = annotation.color;
+    color[3] = (color[3] as f64 * opacity) as u8;
+    for (i, line) in lines.iter().enumerate() {
+        let y = by + padding + line_h * i as f64;
+        draw_text(canvas, &annotation.font, size, bx + padding, y, line, color);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_annotations_yaml() {
+        let yaml = r##"
+- start: 1.5
+  end: 4
+  text: Open settings
+  position: top-right
+  arrow: { x: 0.8, y: 0.1 }
+  style: { size: 30, background: "#0072b2" }
+- start: 5
+  end: 6
+  text: Done
+  position: { x: 0.5, y: 0.5 }
+"##;
+        let annotations: Vec<Annotation> = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(annotations.len(), 2);
+        assert_eq!(
+            annotations[0].position,
+            Position::Preset(PositionPreset::TopRight)
+        );
+        assert_eq!(annotations[0].arrow, Some(Point { x: 0.8, y: 0.1 }));
+        assert_eq!(annotations[0].style.size, Some(30.0));
+        assert_eq!(
+            annotations[1].position,
+            Position::Point(Point { x: 0.5, y: 0.5 })
+        );
+    }
+
+    #[test]
+    fn test_load_annotations_toml() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("notes.toml");
+        std::fs::write(
+            &path,
+            r##"
+annotation = [
+  { at = 12.5, text = "Click Settings", duration = 3 },
+  { start = 20, end = 21.5, text = "Saved", position = "bottom" },
+]
+"##,
+        )
+        .unwrap();
+        let annotations = load_annotations(&path).unwrap();
+        assert_eq!(annotations.len(), 2);
+        assert_eq!(annotations[0].start, 12.5);
+        assert_eq!(annotations[0].end_time(), 15.5);
+        assert_eq!(annotations[1].end_time(), 21.5);
+        assert_eq!(
+            annotations[1].position,
+            Position::Preset(PositionPreset::Bottom)
+        );
+
+        // Tables work too
+        std::fs::write(
+            &path,
+            r##"
+[[annotation]]
+at = 30
+text = "Shown for the default time"
+arrow = { x = 0.2, y = 0.3 }
+"##,
+        )
+        .unwrap();
+        let annotations = load_annotations(&path).unwrap();
+        assert_eq!(annotations[0].end_time(), 30.0 + DEFAULT_DURATION);
+        assert_eq!(annotations[0].arrow, Some(Point { x: 0.2, y: 0.3 }));
+
+        std::fs::write(
+            &path,
+            "annotation = [{ at = 1, end = 2, duration = 1, text = \"x\" }]",
+        )
+        .unwrap();
+        assert!(load_annotations(&path).is_err());
+    }
+
+    #[test]
+    fn test_fade_opacity() {
+        assert_eq!(fade_opacity(0.5, 1.0, 3.0), 0.0);
+        assert!((fade_opacity(1.1, 1.0, 3.0) - 0.5).abs() < 1e-9);
+        assert_eq!(fade_opacity(2.0, 1.0, 3.0), 1.0);
+        assert_eq!(fade_opacity(3.0, 1.0, 3.0), 0.0);
+    }
+
+    #[test]
+    fn test_bubble_stays_on_screen() {
+        let (x, y) = bubble_origin(
+            Position::Preset(PositionPreset::BottomRight),
+            1920.0,
+            1080.0,
+            300.0,
+            100.0,
+        );
+        assert!(x + 300.0 <= 1920.0 && y + 100.0 <= 1080.0);
+
+        // Centered on a point near the corner, clamped inside
+        let (x, y) = bubble_origin(
+            Position::Point(Point { x: 1.0, y: 0.0 }),
+            1920.0,
+            1080.0,
+            300.0,
+            100.0,
+        );
+        assert_eq!((x, y), (1620.0, 0.0));
+    }
+}
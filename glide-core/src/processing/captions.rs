@@ -0,0 +1,345 @@
+//! Burned-in subtitles from an SRT file
+//!
+//! A `CaptionStyle` describes how caption text looks (font, size, colors,
+//! background box) and where it sits. Position presets include safe areas for
+//! vertical social formats, whose platform UI covers the bottom of the frame.
+//! Cue times are output-video times, as a subtitle editor would show them
+//! against the processed video.
+
+use crate::processing::effects::{blend_channel, parse_hex_color};
+use crate::processing::text::{draw_text, line_height, load_font, measure_text, wrap_text};
+use ab_glyph::FontArc;
+use anyhow::{Context, Result};
+use image::{Rgba, RgbaImage};
+use std::path::Path;
+
+/// Where captions are placed on the output frame
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum CaptionPosition {
+    /// Bottom center, just above the edge
+    #[default]
+    Bottom,
+    /// Top center
+    Top,
+    /// Middle of the frame
+    Center,
+    /// Vertical video safe area for TikTok (clears caption/buttons UI)
+    Tiktok,
+    /// Vertical video safe area for Instagram Reels
+    Reels,
+    /// Vertical video safe area for YouTube Shorts
+    Shorts,
+}
+
+impl CaptionPosition {
+    /// Vertical anchor (0.0 = top, 1.0 = bottom) and the margin from that
+    /// edge as a fraction of frame height
+    fn anchor(self) -> (f64, f64) {
+        match self {
+            CaptionPosition::Bottom => (1.0, 0.06),
+            CaptionPosition::Top => (0.0, 0.06),
+            CaptionPosition::Center => (0.5, 0.0),
+            CaptionPosition::Tiktok => (1.0, 0.20),
+            CaptionPosition::Reels => (1.0, 0.22),
+            CaptionPosition::Shorts => (1.0, 0.18),
+        }
+    }
+
+    /// Maximum caption width as a fraction of frame width. Safe-area presets
+    /// leave room for the action buttons along the right edge.
+    fn max_width(self) -> f64 {
+        match self {
+            CaptionPosition::Bottom | CaptionPosition::Top | CaptionPosition::Center => 0.8,
+            CaptionPosition::Tiktok | CaptionPosition::Reels | CaptionPosition::Shorts => 0.7,
+        }
+    }
+}
+
+/// Caption appearance as requested on the command line
+#[derive(Clone, Debug)]
+pub struct CaptionStyle {
+    /// Font family name or font file path (None = system sans-serif)
+    pub font: Option<String>,
+    /// Font size in output pixels
+    pub size: f32,
+    /// Text color (hex)
+    pub color: String,
+    /// Background box color (hex)
+    pub background: String,
+    /// Background box opacity (0.0 = no box, 1.0 = opaque)
+    pub background_opacity: f64,
+    pub position: CaptionPosition,
+}
+
+impl Default for CaptionStyle {
+    fn default() -> Self {
+        Self {
+            font: None,
+            size: 42.0,
+            color: "#ffffff".to_string(),
+            background: "#000000".to_string(),
+            background_opacity: 0.6,
+            position: CaptionPosition::Bottom,
+        }
+    }
+}
+
+/// A caption style with its font loaded and colors parsed, ready to draw
+struct CaptionRenderer {
+    font: FontArc,
+    size: f32,
+    color: Rgba<u8>,
+    background: Rgba<u8>,
+    position: CaptionPosition,
+}
+
+impl CaptionRenderer {
+    fn new(style: &CaptionStyle) -> Result<Self> {
+        let font = load_font(style.font.as_deref())?;
+        let color = parse_hex_color(&style.color)
+            .with_context(|| format!("Invalid caption color: {}", style.color))?;
+        let mut background = parse_hex_color(&style.background)
+            .with_context(|| format!("Invalid caption background: {}", style.background))?;
+        background[3] = (style.background_opacity.clamp(0.0, 1.0) * 255.0).round() as u8;
+
+        Ok(Self {
+            font,
+            size: style.size.max(1.0),
+            color,
+            background,
+            position: style.position,
+        })
+    }
+
+    /// Draw a caption onto the output frame
+    fn draw(&self, canvas: &mut RgbaImage, text: &str) {
+        let (cw, ch) = canvas.dimensions();
+        let max_width = (cw as f64 * self.position.max_width()) as u32;
+        let lines = wrap_text(&self.font, self.size, text, max_width);
+        if lines.iter().all(|l| l.is_empty()) {
+            return;
+        }
+
+        let line_h = line_height(&self.font, self.size) as f64;
+        let widths: Vec<u32> = lines
+            .iter()
+            .map(|l| measure_text(&self.font, self.size, l).0)
+            .collect();
+        let padding = self.size as f64 * 0.4;
+        let box_w = *widths.iter().max().unwrap_or(&0) as f64 + padding * 2.0;
+        let box_h = line_h * lines.len() as f64 + padding * 2.0;
+
+        let (box_x, box_y) = caption_box_origin(cw, ch, box_w, box_h, self.position);
+
+        if self.background[3] > 0 {
+            fill_rect(canvas, box_x, box_y, box_w, box_h, self.background);
+        }
+
+        for (i, (line, width)) in lines.iter().zip(&widths).enumerate() {
+            // Each line is centered within the box
+            let x = box_x + (box_w - *width as f64) / 2.0;
+            let y = box_y + padding + line_h * i as f64;
+            draw_text(canvas, &self.font, self.size, x, y, line, self.color);
+        }
+    }
+}
+
+/// One subtitle: text shown over a time range of the output video
+#[derive(Debug, Clone, PartialEq)]
+struct Cue {
+    start: f64,
+    end: f64,
+    text: String,
+}
+
+/// Parse SRT subtitles. Formatting tags are dropped; cues without a valid
+/// timing line are skipped.
+fn parse_srt(contents: &str) -> Vec<Cue> {
+    let contents = contents
+        .trim_start_matches('\u{feff}')
+        .replace("\r\n", "\n");
+    contents
+        .split("\n\n")
+        .filter_map(|block| {
+            let mut lines = block.lines().skip_while(|l| l.trim().is_empty());
+            let mut timing = lines.next()?;
+            // The counter line is optional in practice
+            if !timing.contains("-->") {
+                timing = lines.next()?;
+            }
+            let (start, end) = timing.split_once("-->")?;
+            // Anything after the end time is positioning we don't support
+            let end = end.split_whitespace().next()?;
+            let text = lines
+                .map(|l| strip_tags(l.trim()))
+                .collect::<Vec<_>>()
+                .join("\n");
+            Some(Cue {
+                start: parse_srt_time(start.trim())?,
+                end: parse_srt_time(end)?,
+                text,
+            })
+        })
+        .filter(|cue| cue.end > cue.start && !cue.text.trim().is_empty())
+        .collect()
+}
+
+/// `HH:MM:SS,mmm` (or with a `.`) in seconds
+fn parse_srt_time(s: &str) -> Option<f64> {
+    let mut parts = s.split(':');
+    let hours: f64 = parts.next()?.trim().parse().ok()?;
+    let minutes: f64 = parts.next()?.trim().parse().ok()?;
+    let seconds: f64 = parts.next()?.trim().replace(',', ".").parse().ok()?;
+    if parts.next().is_some() {
+        return None;
+    }
+    Some(hours * 3600.0 + minutes * 60.0 + seconds)
+}
+
+/// Drop `<i>`-style and `{\\an8}`-style tags
+fn strip_tags(line: &str) -> String {
+    let mut text = String::with_capacity(line.len());
+    let mut closing = None;
+    for c in line.chars() {
+        match (closing, c) {
+            (None, '<') => closing = Some('>'),
+            (None, '{') => closing = Some('}'),
+            (None, c) => text.push(c),
+            (Some(end), c) if c == end => closing = None,
+            _ => {}
+        }
+    }
+    text
+}
+
+/// Subtitles from a file, ready to draw on output frames
+pub struct Subtitles {
+    cues: Vec<Cue>,
+    renderer: CaptionRenderer,
+}
+
+impl Subtitles {
+    /// Load an SRT file to draw in `style`
+    pub fn load(path: &Path, style: &CaptionStyle) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read subtitles from {}", path.display()))?;
+        let cues = parse_srt(&contents);
+        if cues.is_empty() {
+            anyhow::bail!("No subtitles found in {}", path.display());
+        }
+        Ok(Self {
+            cues,
+            renderer: CaptionRenderer::new(style)?,
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.cues.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cues.is_empty()
+    }
+
+    /// Draw the subtitle showing at `timestamp` (output-video seconds), if any
+    pub fn draw(&self, canvas: &mut RgbaImage, timestamp: f64) {
+        // Overlapping cues stack in the order they start
+        let text: Vec<&str> = self
+            .cues
+            .iter()
+            .filter(|cue| (cue.start..cue.end).contains(&timestamp))
+            .map(|cue| cue.text.as_str())
+            .collect();
+        if !text.is_empty() {
+            self.renderer.draw(canvas, &text.join("\n"));
+        }
+    }
+}
+
+/// Top-left corner of a caption box of the given size on a `cw` x `ch` frame
+fn caption_box_origin(
+    cw: u32,
+    ch: u32,
+    box_w: f64,
+    box_h: f64,
+    position: CaptionPosition,
+) -> (f64, f64) {
+    let (anchor, margin) = position.anchor();
+    let x = (cw as f64 - box_w) / 2.0;
+    let y = match anchor {
+        a if a <= 0.0 => ch as f64 * margin,
+        a if a >= 1.0 => ch as f64 * (1.0 - margin) - box_h,
+        a => ch as f64 * a - box_h / 2.0,
+    };
+    (x, y.max(0.0))
+}
+
+/// Alpha-blend a solid rectangle onto the canvas
+fn fill_rect(canvas: &mut RgbaImage, x: f64, y: f64, w: f64, h: f64, color: Rgba<u8>) {
+    let x0 = x.max(0.0) as u32;
+    let y0 = y.max(0.0) as u32;
+    let x1 = ((x + w).max(0.0) as u32).min(canvas.width());
+    let y1 = ((y + h).max(0.0) as u32).min(canvas.height());
+    for py in y0..y1 {
+        for px in x0..x1 {
+            let dest = canvas.get_pixel_mut(px, py);
+            dest[0] = blend_channel(dest[0], color[0], color[3]);
+            dest[1] = blend_channel(dest[1], color[1], color[3]);
+            dest[2] = blend_channel(dest[2], color[2], color[3]);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_safe_area_presets_sit_above_bottom() {
+        let (_, bottom_y) = caption_box_origin(1080, 1920, 600.0, 100.0, CaptionPosition::Bottom);
+        let (_, tiktok_y) = caption_box_origin(1080, 1920, 600.0, 100.0, CaptionPosition::Tiktok);
+        let (_, top_y) = caption_box_origin(1080, 1920, 600.0, 100.0, CaptionPosition::Top);
+        assert!(
+            tiktok_y < bottom_y,
+            "Safe area should be higher than plain bottom"
+        );
+        assert!(tiktok_y + 100.0 <= 1920.0 * 0.8 + 1e-9);
+        assert!((top_y - 1920.0 * 0.06).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_parse_srt() {
+        let srt = "\u{feff}1\r\n00:00:01,500 --> 00:00:04,000\r\nOpen the <i>Settings</i> menu\r\n\r\n\
+                   2\r\n00:00:05.000 --> 00:01:02,250 X1:10 X2:20\r\n{\\an8}Then click\r\nSave\r\n\r\n\
+                   3\r\nnot a timing line\r\nskipped\r\n";
+        let cues = parse_srt(srt);
+        assert_eq!(
+            cues,
+            vec![
+                Cue {
+                    start: 1.5,
+                    end: 4.0,
+                    text: "Open the Settings menu".to_string(),
+                },
+                Cue {
+                    start: 5.0,
+                    end: 62.25,
+                    text: "Then click\nSave".to_string(),
+                },
+            ]
+        );
+        assert_eq!(parse_srt_time("01:00:00,000"), Some(3600.0));
+        assert_eq!(parse_srt_time("00:00"), None);
+    }
+
+    #[test]
+    fn test_invalid_caption_color() {
+        let style = CaptionStyle {
+            color: "white".to_string(),
+            ..Default::default()
+        };
+        // Font lookup may fail in minimal environments; color errors win either way
+        assert!(CaptionRenderer::new(&style).is_err());
+    }
+}
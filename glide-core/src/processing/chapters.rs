@@ -0,0 +1,209 @@
+//! Chapter markers written into the output container
+//!
+//! Chapters are given with `--chapter TIME TITLE`, or found by
+//! `--auto-chapters`: a pause in clicking starts a new chapter at the next
+//! click. Either way they're written as an FFmetadata file for the final
+//! encode to pick up, so players show a chapter list.
+
+use crate::cursor_types::CursorEvent;
+use anyhow::{Context, Result};
+use std::fmt::Write as _;
+use std::path::Path;
+
+/// Seconds without a click that end a cluster of clicks
+const CLUSTER_GAP: f64 = 8.0;
+
+/// Shortest chapter `--auto-chapters` makes (seconds)
+const MIN_CHAPTER_LENGTH: f64 = 10.0;
+
+/// Seconds a found chapter starts before its first click
+const CHAPTER_LEAD: f64 = 1.0;
+
+/// A chapter starting at `start` seconds into the output
+#[derive(Debug, Clone, PartialEq)]
+pub struct Chapter {
+    pub start: f64,
+    pub title: String,
+}
+
+/// Parse `--chapter` values, which come in TIME TITLE pairs
+pub fn parse_chapters(values: &[String]) -> Result<Vec<Chapter>> {
+    let mut chapters = values
+        .chunks(2)
+        .map(|pair| {
+            let start = parse_chapter_time(&pair[0])
+                .map_err(|e| anyhow::anyhow!("Invalid --chapter time: {}", e))?;
+            let title = pair.get(1).map_or("", |t| t.trim());
+            if title.is_empty() {
+                anyhow::bail!("--chapter at {} needs a title", pair[0]);
+            }
+            Ok(Chapter {
+                start,
+                title: title.to_string(),
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    chapters.sort_by(|a, b| a.start.total_cmp(&b.start));
+    Ok(chapters)
+}
+
+/// Parse a chapter time: seconds, `m:ss` or `h:mm:ss`
+pub fn parse_chapter_time(s: &str) -> Result<f64, String> {
+    let invalid = || format!("expected seconds, m:ss or h:mm:ss, got {:?}", s);
+    let mut seconds = 0.0;
+    let parts: Vec<_> = s.trim().split(':').collect();
+    if parts.len() > 3 {
+        return Err(invalid());
+    }
+    for (i, part) in parts.iter().enumerate() {
+        let value = part
+            .parse::<f64>()
+            .ok()
+            .filter(|v| *v >= 0.0 && v.is_finite())
+            .ok_or_else(invalid)?;
+        // Minutes and seconds after the first part stay under 60
+        if i > 0 && value >= 60.0 {
+            return Err(invalid());
+        }
+        seconds = seconds * 60.0 + value;
+    }
+    Ok(seconds)
+}
+
+/// A chapter at each cluster of `clicks` (output clock, `time_offset`
+/// ahead of the output), numbered in order
+pub fn click_chapters(clicks: &[&CursorEvent], time_offset: f64, duration: f64) -> Vec<Chapter> {
+    let mut starts: Vec<f64> = Vec::new();
+    let mut last_click: Option<f64> = None;
+    for click in clicks {
+        let time = click.timestamp - time_offset;
+        if !(0.0..duration).contains(&time) {
+            continue;
+        }
+        let after_pause = last_click.is_none_or(|last| time - last > CLUSTER_GAP);
+        last_click = Some(time);
+        let start = (time - CHAPTER_LEAD).max(0.0);
+        match starts.last() {
+            // The first chapter always covers the start of the video
+            None => starts.push(0.0),
+            Some(&previous) if after_pause && start - previous >= MIN_CHAPTER_LENGTH => {
+                starts.push(start)
+            }
+            _ => {}
+        }
+    }
+    starts
+        .into_iter()
+        .enumerate()
+        .map(|(i, start)| Chapter {
+            start,
+            title: format!("Chapter {}", i + 1),
+        })
+        .collect()
+}
+
+/// Write chapters for a `duration`-second output as an FFmetadata file;
+/// each runs until the next starts. Returns how many were written.
+pub fn write_chapters(path: &Path, chapters: &[Chapter], duration: f64) -> Result<usize> {
+    let ms = |seconds: f64| (seconds * 1000.0).round() as u64;
+    let mut metadata = String::from(";FFMETADATA1\n");
+    let chapters: Vec<_> = chapters.iter().filter(|c| c.start < duration).collect();
+    for (i, chapter) in chapters.iter().enumerate() {
+        let end = chapters.get(i + 1).map_or(duration, |next| next.start);
+        let _ = write!(
+            metadata,
+            "\n[CHAPTER]\nTIMEBASE=1/1000\nSTART={}\nEND={}\ntitle={}\n",
+            ms(chapter.start),
+            ms(end),
+            escape(&chapter.title)
+        );
+    }
+    std::fs::write(path, metadata)
+        .with_context(|| format!("Failed to write chapters to {}", path.display()))?;
+    Ok(chapters.len())
+}
+
+/// Backslash the characters FFmetadata treats specially
+fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '=' | ';' | '#' | '\\' => escaped.push('\\'),
+            '\n' => {
+                escaped.push(' ');
+                continue;
+            }
+            _ => {}
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cursor_types::EventType;
+
+    fn click(timestamp: f64) -> CursorEvent {
+        CursorEvent {
+            x: 0.0,
+            y: 0.0,
+            timestamp,
+            event_type: EventType::LeftClick,
+            display: None,
+            shape: None,
+        }
+    }
+
+    #[test]
+    fn test_chapters() {
+        assert_eq!(parse_chapter_time("1:12"), Ok(72.0));
+        assert_eq!(parse_chapter_time("1:00:05.5"), Ok(3605.5));
+        assert_eq!(parse_chapter_time("90"), Ok(90.0));
+        assert!(parse_chapter_time("1:75").is_err());
+        assert!(parse_chapter_time("abc").is_err());
+
+        let values: Vec<String> = ["1:12", "Settings", "0:00", "Intro"]
+            .map(String::from)
+            .to_vec();
+        let chapters = parse_chapters(&values).unwrap();
+        assert_eq!(chapters[0].title, "Intro");
+        assert_eq!(chapters[1].start, 72.0);
+        assert!(parse_chapters(&["0:00".to_string(), " ".to_string()]).is_err());
+
+        // Pauses start chapters, but not ones shorter than the minimum
+        let events: Vec<_> = [3.0, 5.0, 20.0, 22.0, 33.0, 50.0]
+            .iter()
+            .map(|&t| click(t + 1.0))
+            .collect();
+        let clicks: Vec<_> = events.iter().collect();
+        let starts: Vec<_> = click_chapters(&clicks, 1.0, 60.0)
+            .iter()
+            .map(|c| c.start)
+            .collect();
+        assert_eq!(starts, vec![0.0, 19.0, 32.0, 49.0]);
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("chapters.txt");
+        let chapters = vec![
+            Chapter {
+                start: 0.0,
+                title: "Intro".into(),
+            },
+            Chapter {
+                start: 72.0,
+                title: "A=B; #1".into(),
+            },
+            Chapter {
+                start: 500.0,
+                title: "Past the end".into(),
+            },
+        ];
+        assert_eq!(write_chapters(&path, &chapters, 90.0).unwrap(), 2);
+        let metadata = std::fs::read_to_string(&path).unwrap();
+        assert!(metadata.starts_with(";FFMETADATA1\n"));
+        assert!(metadata.contains("START=0\nEND=72000\ntitle=Intro\n"));
+        assert!(metadata.contains("START=72000\nEND=90000\ntitle=A\\=B\\; \\#1\n"));
+    }
+}
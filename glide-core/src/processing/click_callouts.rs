@@ -0,0 +1,251 @@
+//! Highlight boxes and arrows around clicked UI elements
+//!
+//! For instructional videos: selected clicks get a temporary rectangle (or an
+//! arrow) around the element that was clicked. The element's bounds come from
+//! the accessibility API at record time when available; otherwise a fixed-size
+//! box is centered on the click.
+
+use crate::cursor_types::{ClickTarget, CursorEvent};
+use crate::processing::shapes::{draw_arrow, stroke_rounded_rect};
+use crate::recording::metadata::RecordingMetadata;
+use image::{Rgba, RgbaImage};
+
+/// How a click target is called out
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum CalloutStyle {
+    /// Rectangle around the clicked element
+    Box,
+    /// Arrow pointing at the clicked element
+    Arrow,
+}
+
+/// Configuration for click callouts
+pub struct ClickCalloutConfig {
+    pub style: CalloutStyle,
+    /// 1-based effective click numbers to call out (None = every click)
+    pub clicks: Option<Vec<usize>>,
+    /// How long each callout stays on screen (seconds)
+    pub duration: f64,
+    pub color: Rgba<u8>,
+    /// Box size around the click when no element bounds were recorded (screen points)
+    pub fallback_size: (f64, f64),
+    /// Element bounds covering more than this fraction of the recording are
+    /// containers (windows, web views), not click targets
+    pub max_target_fraction: f64,
+}
+
+impl Default for ClickCalloutConfig {
+    fn default() -> Self {
+        Self {
+            style: CalloutStyle::Box,
+            clicks: None,
+            duration: 1.5,
+            color: Rgba([255, 69, 58, 255]),
+            fallback_size: (120.0, 48.0),
+            max_target_fraction: 0.5,
+        }
+    }
+}
+
+/// A callout on screen; bounds are (x, y, width, height) in screen points
+pub struct ActiveCallout {
+    pub bounds: (f64, f64, f64, f64),
+    pub progress: f64,
+}
+
+/// Find all callouts visible at a given (cursor-tracking) timestamp.
+/// `clicks` are the recording's effective clicks, from `effective_clicks`.
+pub fn get_active_callouts(
+    timestamp: f64,
+    metadata: &RecordingMetadata,
+    clicks: &[&CursorEvent],
+    config: &ClickCalloutConfig,
+) -> Vec<ActiveCallout> {
+    let scale_factor = metadata.scale_factor.max(1.0);
+    let max_width = metadata.width as f64 / scale_factor * config.max_target_fraction;
+    let max_height = metadata.height as f64 / scale_factor * config.max_target_fraction;
+
+    clicks
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| {
+            config
+                .clicks
+                .as_ref()
+                .is_none_or(|selected| selected.contains(&(i + 1)))
+        })
+        .filter_map(|(_, click)| {
+            let elapsed = timestamp - click.timestamp;
+            if !(0.0..config.duration).contains(&elapsed) {
+                return None;
+            }
+            let bounds = find_target(&metadata.click_targets, click.timestamp, click.x, click.y)
+                .filter(|t| t.width <= max_width && t.height <= max_height)
+                .map(|t| (t.x, t.y, t.width, t.height))
+                .unwrap_or_else(|| {
+                    let (w, h) = config.fallback_size;
+                    (click.x - w / 2.0, click.y - h / 2.0, w, h)
+                });
+            Some(ActiveCallout {
+                bounds,
+                progress: elapsed / config.duration,
+            })
+        })
+        .collect()
+}
+
+/// The recorded element for a click: same moment, containing the click point
+fn find_target(targets: &[ClickTarget], timestamp: f64, x: f64, y: f64) -> Option<&ClickTarget> {
+    targets.iter().find(|t| {
+        (t.timestamp - timestamp).abs() < 0.05
+            && x >= t.x
+            && x <= t.x + t.width
+            && y >= t.y
+            && y <= t.y + t.height
+    })
+}
+
+/// Opacity over a callout's lifetime: quick fade in, slower fade out
+fn callout_opacity(progress: f64, duration: f64) -> f64 {
+    let elapsed = progress * duration;
+    let remaining = duration - elapsed;
+    (elapsed / 0.15).min(remaining / 0.3).clamp(0.0, 1.0)
+}
+
+/// Draw callouts whose bounds have been transformed to canvas space
+pub fn draw_click_callouts(
+    canvas: &mut RgbaImage,
+    callouts: &[ActiveCallout],
+    config: &ClickCalloutConfig,
+) {
+    for callout in callouts {
+        let opacity = callout_opacity(callout.progress, config.duration);
+        if opacity < 0.01 {
+            continue;
+        }
+        let mut color = config.color;
+        color[3] = (color[3] as f64 * opacity) as u8;
+        let shadow = Rgba([0, 0, 0, (120.0 * opacity) as u8]);
+
+        let (x, y, w, h) = callout.bounds;
+        match config.style {
+            CalloutStyle::Box => {
+                let pad = 6.0;
+                let (x, y, w, h) = (x - pad, y - pad, w + pad * 2.0, h + pad * 2.0);
+                stroke_rounded_rect(canvas, x, y, w, h, 8.0, 7.0, shadow);
+                stroke_rounded_rect(canvas, x, y, w, h, 8.0, 4.0, color);
+            }
+            CalloutStyle::Arrow => {
+                let (tip, tail) =
+                    arrow_endpoints((x, y, w, h), canvas.width() as f64, canvas.height() as f64);
+                draw_arrow(canvas, tail, tip, 9.0, 26.0, shadow);
+                draw_arrow(canvas, tail, tip, 5.0, 22.0, color);
+            }
+        }
+    }
+}
+
+/// Arrow pointing diagonally at the target's bottom-right corner, flipped
+/// to whichever side keeps the tail on the canvas
+fn arrow_endpoints(
+    (x, y, w, h): (f64, f64, f64, f64),
+    canvas_w: f64,
+    canvas_h: f64,
+) -> ((f64, f64), (f64, f64)) {
+    const LENGTH: f64 = 90.0;
+    const GAP: f64 = 6.0;
+    let right = x + w + GAP + LENGTH < canvas_w;
+    let below = y + h + GAP + LENGTH < canvas_h;
+
+    let tip_x = if right { x + w + GAP } else { x - GAP };
+    let tip_y = if below { y + h + GAP } else { y - GAP };
+    let dir_x = if right { 1.0 } else { -1.0 };
+    let dir_y = if below { 1.0 } else { -1.0 };
+    let offset = LENGTH / std::f64::consts::SQRT_2;
+
+    (
+        (tip_x, tip_y),
+        (tip_x + dir_x * offset, tip_y + dir_y * offset),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cursor_types::EventType;
+    use crate::processing::zoom::{effective_clicks, ZoomConfig};
+
+    fn metadata_with_clicks(clicks: &[(f64, f64, f64)]) -> RecordingMetadata {
+        let mut metadata = RecordingMetadata::new_display(0, 2000, 1000, 1.0);
+        metadata.cursor_events = clicks
+            .iter()
+            .map(|&(x, y, timestamp)| CursorEvent {
+                x,
+                y,
+                timestamp,
+                event_type: EventType::LeftClick,
+                display: None,
+                shape: None,
+            })
+            .collect();
+        metadata
+    }
+
+    #[test]
+    fn test_uses_recorded_target_bounds() {
+        let mut metadata = metadata_with_clicks(&[(105.0, 210.0, 1.0)]);
+        metadata.click_targets = vec![ClickTarget {
+            timestamp: 1.0,
+            x: 80.0,
+            y: 200.0,
+            width: 60.0,
+            height: 24.0,
+        }];
+        let clicks = effective_clicks(&metadata.cursor_events, &ZoomConfig::default());
+        let callouts = get_active_callouts(1.2, &metadata, &clicks, &ClickCalloutConfig::default());
+        assert_eq!(callouts.len(), 1);
+        assert_eq!(callouts[0].bounds, (80.0, 200.0, 60.0, 24.0));
+    }
+
+    #[test]
+    fn test_falls_back_without_targets_and_skips_containers() {
+        let mut metadata = metadata_with_clicks(&[(500.0, 500.0, 1.0)]);
+        // A whole-window element is not a useful target
+        metadata.click_targets = vec![ClickTarget {
+            timestamp: 1.0,
+            x: 0.0,
+            y: 0.0,
+            width: 2000.0,
+            height: 1000.0,
+        }];
+        let config = ClickCalloutConfig::default();
+        let clicks = effective_clicks(&metadata.cursor_events, &ZoomConfig::default());
+        let callouts = get_active_callouts(1.0, &metadata, &clicks, &config);
+        assert_eq!(callouts[0].bounds, (440.0, 476.0, 120.0, 48.0));
+    }
+
+    #[test]
+    fn test_only_selected_clicks() {
+        let metadata = metadata_with_clicks(&[(100.0, 100.0, 1.0), (300.0, 300.0, 5.0)]);
+        let config = ClickCalloutConfig {
+            clicks: Some(vec![2]),
+            ..Default::default()
+        };
+        let clicks = effective_clicks(&metadata.cursor_events, &ZoomConfig::default());
+        assert!(get_active_callouts(1.2, &metadata, &clicks, &config).is_empty());
+        assert_eq!(
+            get_active_callouts(5.2, &metadata, &clicks, &config).len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_arrow_flips_near_edges() {
+        let (tip, tail) = arrow_endpoints((1850.0, 1000.0, 40.0, 40.0), 1920.0, 1080.0);
+        assert!(
+            tail.0 < tip.0 && tail.1 < tip.1,
+            "Arrow should come from top-left"
+        );
+    }
+}
@@ -1,33 +1,61 @@
 use crate::cursor_types::{CursorEvent, EventType};
+use crate::processing::easing::Easing;
 use crate::processing::effects::blend_channel;
 use image::{Rgba, RgbaImage};
+use std::f64::consts::TAU;
+
+/// Dashes around a right-click's ring, and the share of each that's drawn
+const RIGHT_CLICK_DASHES: f64 = 8.0;
+const DASH_FILL: f64 = 0.6;
+
+/// How far into a double-click's animation its second ring starts
+const DOUBLE_PULSE_DELAY: f64 = 0.35;
 
 /// Configuration for click highlighting effect
 pub struct ClickHighlightConfig {
     pub enabled: bool,
-    pub duration: f64,   // How long the ripple animation lasts
-    pub max_radius: f64, // Maximum radius of the expanding ring
-    pub ring_width: f64, // Width of the ring stroke
-    pub color: Rgba<u8>, // Color of the ring (with alpha)
+    pub duration: f64,          // How long the ripple animation lasts
+    pub max_radius: f64,        // Maximum radius of the expanding ring
+    pub ring_width: f64,        // Width of the ring stroke
+    pub color: Rgba<u8>,        // Color of the ring (with alpha)
+    pub right_color: Rgba<u8>,  // Color of the dashed ring on right-clicks
+    pub double_click: f64,      // Max seconds between the clicks of a double-click
+    pub shadow_color: Rgba<u8>, // Outline under the ring for contrast
+    pub easing: Easing,         // Curve of the ring's growth and fade
 }
 
 impl Default for ClickHighlightConfig {
     fn default() -> Self {
         Self {
             enabled: true,
-            duration: 0.4,                     // 400ms animation
-            max_radius: 50.0,                  // 50px max radius
-            ring_width: 3.0,                   // 3px ring width
-            color: Rgba([255, 255, 255, 255]), // White (shadow provides contrast)
+            duration: 0.4,                         // 400ms animation
+            max_radius: 50.0,                      // 50px max radius
+            ring_width: 3.0,                       // 3px ring width
+            color: Rgba([255, 255, 255, 255]),     // White (shadow provides contrast)
+            right_color: Rgba([255, 69, 58, 255]), // Red
+            double_click: 0.5,                     // Typical OS double-click time
+            shadow_color: Rgba([0, 0, 0, 150]),    // Dark semi-transparent shadow
+            easing: Easing::Cubic,                 // Fast burst, slow finish
         }
     }
 }
 
+/// Which kind of click a ripple is for
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ClickKind {
+    Left,
+    /// Dashed ring in the right-click color
+    Right,
+    /// Second click of a double-click: two rings, one after the other
+    Double,
+}
+
 /// Represents an active ripple effect
 pub struct ActiveRipple {
     pub x: f64,
     pub y: f64,
     pub progress: f64, // 0.0 to 1.0
+    pub kind: ClickKind,
 }
 
 /// Find all active ripples at a given timestamp
@@ -36,29 +64,44 @@ pub fn get_active_ripples(
     cursor_events: &[CursorEvent],
     config: &ClickHighlightConfig,
 ) -> Vec<ActiveRipple> {
-    cursor_events
-        .iter()
-        .filter(|e| matches!(e.event_type, EventType::LeftClick | EventType::RightClick))
-        .filter_map(|click| {
-            let elapsed = timestamp - click.timestamp;
-            // Only include clicks that are within the animation window
-            if elapsed >= 0.0 && elapsed < config.duration {
-                let progress = elapsed / config.duration;
-                Some(ActiveRipple {
-                    x: click.x,
-                    y: click.y,
-                    progress,
-                })
-            } else {
-                None
+    let mut ripples = Vec::new();
+    // The last left click, and whether it finished a double-click
+    let mut last_left: Option<(f64, bool)> = None;
+
+    for click in cursor_events {
+        let kind = match click.event_type {
+            EventType::LeftClick => {
+                let double = last_left.is_some_and(|(time, was_double)| {
+                    !was_double && click.timestamp - time <= config.double_click
+                });
+                last_left = Some((click.timestamp, double));
+                if double {
+                    ClickKind::Double
+                } else {
+                    ClickKind::Left
+                }
             }
-        })
-        .collect()
-}
-
-/// Ease-out cubic: starts fast, ends slow
-fn ease_out_cubic(t: f64) -> f64 {
-    1.0 - (1.0 - t).powi(3)
+            EventType::RightClick => ClickKind::Right,
+            _ => continue,
+        };
+
+        // The second ring of a double-click makes it last longer
+        let duration = match kind {
+            ClickKind::Double => config.duration * (1.0 + DOUBLE_PULSE_DELAY),
+            _ => config.duration,
+        };
+        let elapsed = timestamp - click.timestamp;
+        // Only include clicks that are within the animation window
+        if elapsed >= 0.0 && elapsed < duration {
+            ripples.push(ActiveRipple {
+                x: click.x,
+                y: click.y,
+                progress: elapsed / duration,
+                kind,
+            });
+        }
+    }
+    ripples
 }
 
 /// Draw click highlights on the canvas
@@ -72,19 +115,43 @@ pub fn draw_click_highlights(
     }
 
     for ripple in ripples {
-        draw_ring(canvas, ripple.x, ripple.y, ripple.progress, config);
+        match ripple.kind {
+            ClickKind::Left => {
+                draw_ring(canvas, ripple, ripple.progress, &config.color, 0.0, config)
+            }
+            ClickKind::Right => draw_ring(
+                canvas,
+                ripple,
+                ripple.progress,
+                &config.right_color,
+                RIGHT_CLICK_DASHES,
+                config,
+            ),
+            ClickKind::Double => {
+                // Progress is over both rings; each gets a normal ring's time
+                let elapsed = ripple.progress * (1.0 + DOUBLE_PULSE_DELAY);
+                for ring in [elapsed, elapsed - DOUBLE_PULSE_DELAY] {
+                    if (0.0..1.0).contains(&ring) {
+                        draw_ring(canvas, ripple, ring, &config.color, 0.0, config);
+                    }
+                }
+            }
+        }
     }
 }
 
-/// Draw a single expanding ring with shadow for visibility
+/// Draw a single expanding ring with shadow for visibility, broken into
+/// `dashes` dashes if that's not 0
 fn draw_ring(
     canvas: &mut RgbaImage,
-    center_x: f64,
-    center_y: f64,
+    ripple: &ActiveRipple,
     progress: f64,
+    color: &Rgba<u8>,
+    dashes: f64,
     config: &ClickHighlightConfig,
 ) {
-    let eased_progress = ease_out_cubic(progress);
+    let (center_x, center_y) = (ripple.x, ripple.y);
+    let eased_progress = config.easing.ease_out(progress);
 
     // Calculate current radius (expands from 0 to max_radius)
     let radius = config.max_radius * eased_progress;
@@ -100,7 +167,6 @@ fn draw_ring(
     let shadow_width = config.ring_width + 3.0;
     let shadow_inner = (radius - shadow_width / 2.0).max(0.0);
     let shadow_outer = radius + shadow_width / 2.0;
-    let shadow_color = Rgba([0, 0, 0, 150]); // Dark semi-transparent shadow
     draw_ring_pixels(
         canvas,
         center_x,
@@ -108,7 +174,8 @@ fn draw_ring(
         shadow_inner,
         shadow_outer,
         opacity * 0.6,
-        &shadow_color,
+        &config.shadow_color,
+        dashes,
     );
 
     // Draw main ring on top
@@ -121,11 +188,13 @@ fn draw_ring(
         inner_radius,
         outer_radius,
         opacity,
-        &config.color,
+        color,
+        dashes,
     );
 }
 
 /// Draw ring pixels with given radii and color
+#[allow(clippy::too_many_arguments)]
 fn draw_ring_pixels(
     canvas: &mut RgbaImage,
     center_x: f64,
@@ -134,6 +203,7 @@ fn draw_ring_pixels(
     outer_radius: f64,
     opacity: f64,
     color: &Rgba<u8>,
+    dashes: f64,
 ) {
     if outer_radius < 1.0 {
         return;
@@ -165,6 +235,12 @@ fn draw_ring_pixels(
                     1.0
                 };
 
+                let edge_alpha = if dashes > 0.0 {
+                    edge_alpha * dash_coverage(dx, dy, dist, dashes)
+                } else {
+                    edge_alpha
+                };
+
                 let final_alpha = (edge_alpha * opacity * color[3] as f64 / 255.0 * 255.0) as u8;
 
                 if final_alpha > 0 {
@@ -178,6 +254,17 @@ fn draw_ring_pixels(
     }
 }
 
+/// How much of a ring pixel is on a dash, anti-aliased along the ring
+fn dash_coverage(dx: f64, dy: f64, dist: f64, dashes: f64) -> f64 {
+    let along = (dy.atan2(dx) / TAU * dashes).rem_euclid(1.0);
+    if along > DASH_FILL {
+        return 0.0;
+    }
+    // Pixels from the nearer end of the dash
+    let dash_length = TAU * dist / dashes;
+    (along.min(DASH_FILL - along) * dash_length + 0.5).clamp(0.0, 1.0)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -188,6 +275,8 @@ mod tests {
             y,
             timestamp,
             event_type: EventType::LeftClick,
+            display: None,
+            shape: None,
         }
     }
 
@@ -197,6 +286,8 @@ mod tests {
             y,
             timestamp,
             event_type: EventType::Move,
+            display: None,
+            shape: None,
         }
     }
 
@@ -255,6 +346,50 @@ mod tests {
         assert_eq!(ripples.len(), 2, "Should have two overlapping ripples");
     }
 
+    #[test]
+    fn test_click_kinds() {
+        let config = ClickHighlightConfig::default();
+        let mut right = make_click(300.0, 100.0, 3.0);
+        right.event_type = EventType::RightClick;
+        let events = vec![
+            make_click(100.0, 100.0, 1.0),
+            make_click(100.0, 100.0, 1.2),
+            make_click(100.0, 100.0, 1.4), // a third click starts over
+            make_click(100.0, 100.0, 2.5),
+            right,
+        ];
+
+        let kinds = |t: f64| -> Vec<ClickKind> {
+            get_active_ripples(t, &events, &config)
+                .iter()
+                .map(|r| r.kind)
+                .collect()
+        };
+        assert_eq!(kinds(1.1), vec![ClickKind::Left]);
+        // 0.5s in, past a single ring, the double-click's second ring is going
+        assert_eq!(kinds(1.7), vec![ClickKind::Double, ClickKind::Left]);
+        assert_eq!(kinds(2.6), vec![ClickKind::Left]);
+        assert_eq!(kinds(3.1), vec![ClickKind::Right]);
+
+        // Right-clicks are dashed
+        let mut canvas = RgbaImage::from_pixel(200, 200, Rgba([0, 0, 0, 255]));
+        let ring = ActiveRipple {
+            x: 100.0,
+            y: 100.0,
+            progress: 0.3,
+            kind: ClickKind::Right,
+        };
+        draw_click_highlights(&mut canvas, &[ring], &config);
+        let radius = config.max_radius * config.easing.ease_out(0.3);
+        let at = |angle: f64| {
+            let (x, y) = (100.0 + radius * angle.cos(), 100.0 + radius * angle.sin());
+            canvas.get_pixel(x.round() as u32, y.round() as u32)[0]
+        };
+        let dash = TAU / RIGHT_CLICK_DASHES;
+        assert!(at(dash * DASH_FILL / 2.0) > 40, "on a dash");
+        assert_eq!(at(dash * (1.0 + DASH_FILL) / 2.0), 0, "in a gap");
+    }
+
     #[test]
     fn test_draw_ring_modifies_canvas() {
         let config = ClickHighlightConfig::default();
@@ -264,6 +399,7 @@ mod tests {
             x: 100.0,
             y: 100.0,
             progress: 0.5,
+            kind: ClickKind::Left,
         }];
 
         draw_click_highlights(&mut canvas, &ripples, &config);
@@ -0,0 +1,174 @@
+//! `--click-sound`: an audio track with a soft click at each mouse click
+
+use crate::cursor_types::{CursorEvent, EventType};
+use crate::processing::frames::AudioSource;
+use anyhow::{Context, Result};
+use std::f64::consts::TAU;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+const SAMPLE_RATE: u32 = 48_000;
+
+/// Length, peak level and decay time of one click (seconds)
+const CLICK_LENGTH: f64 = 0.03;
+const CLICK_LEVEL: f64 = 0.4;
+const CLICK_DECAY: f64 = 0.004;
+
+/// One click: a short, quickly decaying tone with a soft attack so it
+/// doesn't pop
+fn click_sample() -> Vec<f32> {
+    let length = (CLICK_LENGTH * SAMPLE_RATE as f64) as usize;
+    (0..length)
+        .map(|i| {
+            let t = i as f64 / SAMPLE_RATE as f64;
+            let attack = (t / 0.0005).min(1.0);
+            let tone = 0.6 * (TAU * 1800.0 * t).sin() + 0.4 * (TAU * 3600.0 * t).sin();
+            (CLICK_LEVEL * attack * (-t / CLICK_DECAY).exp() * tone) as f32
+        })
+        .collect()
+}
+
+/// Output times of the clicks in `events`, for output starting at
+/// `time_offset` on the tracking clock and lasting `duration`
+pub fn click_times(events: &[CursorEvent], time_offset: f64, duration: f64) -> Vec<f64> {
+    events
+        .iter()
+        .filter(|e| matches!(e.event_type, EventType::LeftClick | EventType::RightClick))
+        .map(|e| e.timestamp - time_offset)
+        .filter(|t| (0.0..duration).contains(t))
+        .collect()
+}
+
+/// Mono samples `duration` long with a click at each of `times`
+fn render_clicks(times: &[f64], duration: f64) -> Vec<f32> {
+    let mut samples = vec![0.0f32; (duration * SAMPLE_RATE as f64).ceil() as usize];
+    let click = click_sample();
+    for &time in times {
+        let start = (time * SAMPLE_RATE as f64) as usize;
+        for (sample, value) in samples.iter_mut().skip(start).zip(&click) {
+            *sample = (*sample + value).clamp(-1.0, 1.0);
+        }
+    }
+    samples
+}
+
+/// Write mono samples as a 16-bit PCM WAV file
+fn write_wav(path: &Path, samples: &[f32]) -> Result<()> {
+    let data_len = samples.len() as u32 * 2;
+    let mut wav = Vec::with_capacity(44 + data_len as usize);
+    wav.extend_from_slice(b"RIFF");
+    wav.extend_from_slice(&(36 + data_len).to_le_bytes());
+    wav.extend_from_slice(b"WAVEfmt ");
+    wav.extend_from_slice(&16u32.to_le_bytes()); // fmt chunk size
+    wav.extend_from_slice(&1u16.to_le_bytes()); // PCM
+    wav.extend_from_slice(&1u16.to_le_bytes()); // mono
+    wav.extend_from_slice(&SAMPLE_RATE.to_le_bytes());
+    wav.extend_from_slice(&(SAMPLE_RATE * 2).to_le_bytes()); // bytes per second
+    wav.extend_from_slice(&2u16.to_le_bytes()); // bytes per frame
+    wav.extend_from_slice(&16u16.to_le_bytes()); // bits per sample
+    wav.extend_from_slice(b"data");
+    wav.extend_from_slice(&data_len.to_le_bytes());
+    for sample in samples {
+        wav.extend_from_slice(&((sample * i16::MAX as f32) as i16).to_le_bytes());
+    }
+    fs::write(path, wav).with_context(|| format!("Failed to write {:?}", path))
+}
+
+/// Write the click track to `path` as audio for the whole output, mixed
+/// with the recording's own audio if there is any. Returns the audio to
+/// encode and the number of clicks.
+pub fn write_click_track(
+    path: &Path,
+    events: &[CursorEvent],
+    time_offset: f64,
+    duration: f64,
+    recorded: Option<&AudioSource>,
+) -> Result<(AudioSource, usize)> {
+    let times = click_times(events, time_offset, duration);
+    let clicks_path = path.with_extension("clicks.wav");
+    write_wav(&clicks_path, &render_clicks(&times, duration))?;
+
+    let Some(recorded) = recorded else {
+        fs::rename(&clicks_path, path)?;
+        return Ok((audio_source(path, duration), times.len()));
+    };
+
+    // Clicks on top of the recording, without amix halving both
+    let status = Command::new("ffmpeg")
+        .args(["-v", "error", "-ss"])
+        .arg(format!("{:.3}", recorded.start))
+        .arg("-t")
+        .arg(format!("{:.3}", recorded.duration))
+        .arg("-i")
+        .arg(&recorded.path)
+        .arg("-i")
+        .arg(&clicks_path)
+        .args([
+            "-filter_complex",
+            "[0:a][1:a]amix=inputs=2:duration=first:normalize=0",
+            "-c:a",
+            "pcm_s16le",
+            "-y",
+        ])
+        .arg(path)
+        .status()
+        .context("Failed to run ffmpeg")?;
+    if !status.success() {
+        anyhow::bail!("FFmpeg failed to mix the click sounds into the audio");
+    }
+    Ok((audio_source(path, duration), times.len()))
+}
+
+fn audio_source(path: &Path, duration: f64) -> AudioSource {
+    AudioSource {
+        path: path.to_path_buf(),
+        start: 0.0,
+        duration,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(timestamp: f64, event_type: EventType) -> CursorEvent {
+        CursorEvent {
+            x: 0.0,
+            y: 0.0,
+            timestamp,
+            event_type,
+            display: None,
+            shape: None,
+        }
+    }
+
+    #[test]
+    fn test_click_track() {
+        let events = vec![
+            event(0.5, EventType::LeftClick),
+            event(1.5, EventType::Move),
+            event(2.0, EventType::RightClick),
+            event(9.0, EventType::LeftClick), // after the output ends
+        ];
+        let times = click_times(&events, 0.5, 5.0);
+        assert_eq!(times, vec![0.0, 1.5]);
+
+        let samples = render_clicks(&times, 5.0);
+        assert_eq!(samples.len(), 5 * SAMPLE_RATE as usize);
+        let peak = |from: f64, to: f64| {
+            samples[(from * SAMPLE_RATE as f64) as usize..(to * SAMPLE_RATE as f64) as usize]
+                .iter()
+                .fold(0.0f32, |peak, s| peak.max(s.abs()))
+        };
+        assert!(peak(1.5, 1.53) > 0.1, "click at 1.5s");
+        assert_eq!(peak(0.5, 1.4), 0.0, "silence between clicks");
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("clicks.wav");
+        write_wav(&path, &samples).unwrap();
+        let wav = fs::read(&path).unwrap();
+        assert_eq!(&wav[..4], b"RIFF");
+        assert_eq!(wav.len(), 44 + samples.len() * 2);
+    }
+}
@@ -1,7 +1,29 @@
-use crate::cursor_types::CursorEvent;
+use crate::cursor_types::{CursorEvent, CursorShape};
+use crate::processing::cursor_sprites::cursor_sprite;
+use crate::processing::easing::Easing;
 use crate::processing::effects::blend_channel;
-use image::RgbaImage;
-use std::sync::OnceLock;
+use image::{Rgba, RgbaImage};
+
+/// Color treatment of the rendered cursor sprite
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum CursorStyle {
+    /// The bundled cursor as-is
+    #[default]
+    Default,
+    /// Invert the cursor's colors (white arrow with dark border)
+    Inverted,
+    /// Solid, fully opaque black/white cursor without soft edges
+    HighContrast,
+}
+
+/// How the cursor sprite is drawn
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CursorAppearance {
+    pub style: CursorStyle,
+    /// White outline thickness around the cursor in output pixels (0 = none)
+    pub outline: u32,
+}
 
 /// Configuration for cursor rendering and smoothing
 pub struct CursorConfig {
@@ -11,8 +33,12 @@ pub struct CursorConfig {
     pub inactivity_timeout: f64,
     /// Duration of fade animation (seconds)
     pub fade_duration: f64,
+    /// Curve of the fade
+    pub fade_easing: Easing,
     /// Cursor scale factor
     pub cursor_scale: f64,
+    /// Sprite style and outline
+    pub appearance: CursorAppearance,
 }
 
 impl Default for CursorConfig {
@@ -21,12 +47,21 @@ impl Default for CursorConfig {
             smooth_window: 0.15,     // 150ms smoothing window (more noticeable)
             inactivity_timeout: 2.0, // Fade after 2s inactivity
             fade_duration: 0.3,      // 300ms fade animation
-            cursor_scale: 2.0,       // 2.0x cursor size
+            fade_easing: Easing::Cubic,
+            cursor_scale: 2.0, // 2.0x cursor size
+            appearance: CursorAppearance::default(),
         }
     }
 }
 
 impl CursorConfig {
+    /// Default scale for a recording: the usual enlargement, applied to the
+    /// presenter's OS cursor size when it was recorded
+    pub fn default_scale(os_cursor_scale: Option<f64>) -> f64 {
+        let default = Self::default().cursor_scale;
+        os_cursor_scale.map_or(default, |os| default * os)
+    }
+
     pub fn new(cursor_scale: f64, inactivity_timeout: f64) -> Self {
         Self {
             cursor_scale,
@@ -77,8 +112,7 @@ fn get_smoothed_position(
         // Fall back to most recent event before timestamp
         return cursor_events
             .iter()
-            .filter(|e| e.timestamp <= timestamp)
-            .last()
+            .rfind(|e| e.timestamp <= timestamp)
             .map(|e| (e.x, e.y))
             .unwrap_or((0.0, 0.0));
     }
@@ -124,10 +158,7 @@ fn calculate_activity_opacity(
     config: &CursorConfig,
 ) -> f64 {
     // Find last activity (any event - move or click)
-    let last_activity = cursor_events
-        .iter()
-        .filter(|e| e.timestamp <= timestamp)
-        .last();
+    let last_activity = cursor_events.iter().rfind(|e| e.timestamp <= timestamp);
 
     let last_activity_time = match last_activity {
         Some(event) => event.timestamp,
@@ -142,37 +173,112 @@ fn calculate_activity_opacity(
     } else if idle_time < config.inactivity_timeout + config.fade_duration {
         // Fading out
         let fade_progress = (idle_time - config.inactivity_timeout) / config.fade_duration;
-        1.0 - ease_out_cubic(fade_progress)
+        // Clamped for curves that overshoot
+        (1.0 - config.fade_easing.ease_out(fade_progress)).clamp(0.0, 1.0)
     } else {
         // Fully hidden
         0.0
     }
 }
 
-/// Ease-out cubic: starts fast, ends slow
-fn ease_out_cubic(t: f64) -> f64 {
-    1.0 - (1.0 - t).powi(3)
-}
-
-// Embed cursor image at compile time
-const CURSOR_PNG: &[u8] = include_bytes!("../../assets/cursor.png");
-
-/// Get the cursor image (loaded once, cached)
-fn get_cursor_image() -> &'static RgbaImage {
-    static CURSOR: OnceLock<RgbaImage> = OnceLock::new();
-    CURSOR.get_or_init(|| {
-        image::load_from_memory(CURSOR_PNG)
-            .expect("Failed to load embedded cursor image")
-            .to_rgba8()
-    })
+/// Shape of the cursor at a given timestamp: the last one recorded, or the
+/// arrow for recordings (and platforms) without shapes
+pub fn get_cursor_shape(timestamp: f64, cursor_events: &[CursorEvent]) -> CursorShape {
+    cursor_events
+        .iter()
+        .rfind(|e| e.timestamp <= timestamp)
+        .and_then(|e| e.shape)
+        .unwrap_or_default()
 }
 
 // Base cursor height in pixels (before user scale factor is applied)
 const CURSOR_BASE_HEIGHT: f64 = 32.0;
 
+/// Apply the style's color treatment to a scaled cursor sprite
+fn apply_cursor_style(sprite: &mut RgbaImage, style: CursorStyle) {
+    match style {
+        CursorStyle::Default => {}
+        CursorStyle::Inverted => {
+            for pixel in sprite.pixels_mut() {
+                pixel[0] = 255 - pixel[0];
+                pixel[1] = 255 - pixel[1];
+                pixel[2] = 255 - pixel[2];
+            }
+        }
+        CursorStyle::HighContrast => {
+            for pixel in sprite.pixels_mut() {
+                // Drop faint anti-aliasing/shadow pixels, make the rest solid
+                if pixel[3] < 96 {
+                    pixel[3] = 0;
+                    continue;
+                }
+                let luma =
+                    (pixel[0] as u32 * 299 + pixel[1] as u32 * 587 + pixel[2] as u32 * 114) / 1000;
+                let value = if luma < 128 { 0 } else { 255 };
+                *pixel = Rgba([value, value, value, 255]);
+            }
+        }
+    }
+}
+
+/// Surround a sprite with a solid white outline of `thickness` pixels.
+/// Returns the padded sprite; its origin moves by `-thickness` on both axes.
+fn add_cursor_outline(sprite: &RgbaImage, thickness: u32) -> RgbaImage {
+    let pad = thickness as i64;
+    let (w, h) = sprite.dimensions();
+    let mut outlined = RgbaImage::new(w + thickness * 2, h + thickness * 2);
+    let radius_sq = pad * pad;
+
+    for oy in 0..outlined.height() as i64 {
+        for ox in 0..outlined.width() as i64 {
+            // Max alpha of the sprite within a disc of `thickness` around this pixel
+            let mut alpha = 0u8;
+            for dy in -pad..=pad {
+                for dx in -pad..=pad {
+                    if dx * dx + dy * dy > radius_sq {
+                        continue;
+                    }
+                    let sx = ox - pad + dx;
+                    let sy = oy - pad + dy;
+                    if sx >= 0 && sy >= 0 && sx < w as i64 && sy < h as i64 {
+                        alpha = alpha.max(sprite.get_pixel(sx as u32, sy as u32)[3]);
+                    }
+                }
+            }
+            if alpha > 0 {
+                outlined.put_pixel(ox as u32, oy as u32, Rgba([255, 255, 255, alpha]));
+            }
+        }
+    }
+
+    // Sprite on top of its outline
+    for (x, y, pixel) in sprite.enumerate_pixels() {
+        if pixel[3] == 0 {
+            continue;
+        }
+        let dest = outlined.get_pixel_mut(x + thickness, y + thickness);
+        let a = pixel[3];
+        dest[0] = blend_channel(dest[0], pixel[0], a);
+        dest[1] = blend_channel(dest[1], pixel[1], a);
+        dest[2] = blend_channel(dest[2], pixel[2], a);
+        dest[3] = dest[3].max(a);
+    }
+
+    outlined
+}
+
 /// Draw a cursor at the specified position
-pub fn draw_cursor(canvas: &mut RgbaImage, x: f64, y: f64, scale: f64, opacity: f64) {
-    let cursor = get_cursor_image();
+pub fn draw_cursor(
+    canvas: &mut RgbaImage,
+    x: f64,
+    y: f64,
+    scale: f64,
+    opacity: f64,
+    shape: CursorShape,
+    appearance: &CursorAppearance,
+) {
+    let sprite = cursor_sprite(shape);
+    let cursor = &sprite.image;
     let (cw, ch) = cursor.dimensions();
 
     // Normalize cursor to base height, then apply user scale
@@ -184,16 +290,21 @@ pub fn draw_cursor(canvas: &mut RgbaImage, x: f64, y: f64, scale: f64, opacity:
     let scaled_h = (ch as f64 * final_scale) as u32;
 
     // Scale cursor image (use Lanczos3 for high quality downscaling)
-    let scaled_cursor = image::imageops::resize(
+    let mut scaled_cursor = image::imageops::resize(
         cursor,
         scaled_w,
         scaled_h,
         image::imageops::FilterType::Lanczos3,
     );
+    apply_cursor_style(&mut scaled_cursor, appearance.style);
+    if appearance.outline > 0 {
+        scaled_cursor = add_cursor_outline(&scaled_cursor, appearance.outline);
+    }
+    let (scaled_w, scaled_h) = scaled_cursor.dimensions();
 
-    // Calculate position (cursor tip is at x, y)
-    let px = x as i64;
-    let py = y as i64;
+    // Calculate position (the hotspot is at x, y; the outline extends past it)
+    let px = (x - sprite.hotspot.0 * final_scale) as i64 - appearance.outline as i64;
+    let py = (y - sprite.hotspot.1 * final_scale) as i64 - appearance.outline as i64;
 
     // Draw cursor
     for cy in 0..scaled_h {
@@ -230,6 +341,8 @@ mod tests {
             y,
             timestamp,
             event_type: EventType::Move,
+            display: None,
+            shape: None,
         }
     }
 
@@ -295,6 +408,50 @@ mod tests {
         assert!(state.opacity < 0.01, "Should be hidden");
     }
 
+    #[test]
+    fn test_default_scale_respects_os_cursor_size() {
+        assert_eq!(CursorConfig::default_scale(None), 2.0);
+        assert_eq!(CursorConfig::default_scale(Some(1.0)), 2.0);
+        // A slightly bigger OS cursor stays bigger in the output
+        assert_eq!(CursorConfig::default_scale(Some(1.5)), 3.0);
+        assert_eq!(CursorConfig::default_scale(Some(3.5)), 7.0);
+    }
+
+    #[test]
+    fn test_inverted_style() {
+        let mut sprite = RgbaImage::from_pixel(2, 2, Rgba([10, 20, 30, 255]));
+        apply_cursor_style(&mut sprite, CursorStyle::Inverted);
+        assert_eq!(*sprite.get_pixel(0, 0), Rgba([245, 235, 225, 255]));
+    }
+
+    #[test]
+    fn test_high_contrast_style_is_solid() {
+        let mut sprite = RgbaImage::new(3, 1);
+        sprite.put_pixel(0, 0, Rgba([40, 40, 40, 200]));
+        sprite.put_pixel(1, 0, Rgba([220, 220, 220, 150]));
+        sprite.put_pixel(2, 0, Rgba([0, 0, 0, 30]));
+        apply_cursor_style(&mut sprite, CursorStyle::HighContrast);
+        assert_eq!(*sprite.get_pixel(0, 0), Rgba([0, 0, 0, 255]));
+        assert_eq!(*sprite.get_pixel(1, 0), Rgba([255, 255, 255, 255]));
+        assert_eq!(
+            sprite.get_pixel(2, 0)[3],
+            0,
+            "Faint pixels should be dropped"
+        );
+    }
+
+    #[test]
+    fn test_outline_surrounds_sprite() {
+        let sprite = RgbaImage::from_pixel(1, 1, Rgba([0, 0, 0, 255]));
+        let outlined = add_cursor_outline(&sprite, 2);
+        assert_eq!(outlined.dimensions(), (5, 5));
+        // Center keeps the sprite color, neighbours are white outline
+        assert_eq!(*outlined.get_pixel(2, 2), Rgba([0, 0, 0, 255]));
+        assert_eq!(*outlined.get_pixel(0, 2), Rgba([255, 255, 255, 255]));
+        // Corners fall outside the round outline
+        assert_eq!(outlined.get_pixel(0, 0)[3], 0);
+    }
+
     #[test]
     fn test_no_events() {
         let events: Vec<CursorEvent> = vec![];
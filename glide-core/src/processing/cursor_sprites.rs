@@ -0,0 +1,170 @@
+//! Cursor sprites for the recorded cursor shapes
+//!
+//! The arrow is the bundled image; the others are drawn once from signed
+//! distance functions in a 32x32 box of design units, so they can be
+//! scaled like the arrow and outlined crisply.
+
+use crate::cursor_types::CursorShape;
+use crate::processing::shapes::rounded_rect_distance;
+use image::{Rgba, RgbaImage};
+use std::sync::OnceLock;
+
+// Embed cursor image at compile time
+const CURSOR_PNG: &[u8] = include_bytes!("../../assets/cursor.png");
+
+/// Size of the box the drawn shapes are designed in
+const DESIGN_SIZE: f64 = 32.0;
+
+/// Pixels per design unit; sprites are scaled down from this when drawn
+const RESOLUTION: f64 = 4.0;
+
+/// Width of the contrasting border around drawn shapes (design units)
+const BORDER: f64 = 1.5;
+
+const BLACK: Rgba<u8> = Rgba([0, 0, 0, 255]);
+const WHITE: Rgba<u8> = Rgba([255, 255, 255, 255]);
+
+/// A cursor image and the point in it that sits at the cursor position
+pub struct Sprite {
+    pub image: RgbaImage,
+    /// Sprite pixels from the top-left corner
+    pub hotspot: (f64, f64),
+}
+
+/// The sprite for a shape (drawn once, cached)
+pub fn cursor_sprite(shape: CursorShape) -> &'static Sprite {
+    static ARROW: OnceLock<Sprite> = OnceLock::new();
+    static IBEAM: OnceLock<Sprite> = OnceLock::new();
+    static HAND: OnceLock<Sprite> = OnceLock::new();
+    static RESIZE_H: OnceLock<Sprite> = OnceLock::new();
+    static RESIZE_V: OnceLock<Sprite> = OnceLock::new();
+
+    match shape {
+        CursorShape::Arrow => ARROW.get_or_init(|| Sprite {
+            image: image::load_from_memory(CURSOR_PNG)
+                .expect("Failed to load embedded cursor image")
+                .to_rgba8(),
+            hotspot: (0.0, 0.0),
+        }),
+        CursorShape::IBeam => IBEAM.get_or_init(|| render(ibeam, (16.0, 16.0), BLACK, WHITE)),
+        CursorShape::PointingHand => {
+            HAND.get_or_init(|| render(pointing_hand, (13.0, 3.0), WHITE, BLACK))
+        }
+        CursorShape::ResizeHorizontal => {
+            RESIZE_H.get_or_init(|| render(resize_horizontal, (16.0, 16.0), BLACK, WHITE))
+        }
+        CursorShape::ResizeVertical => RESIZE_V
+            .get_or_init(|| render(|x, y| resize_horizontal(y, x), (16.0, 16.0), BLACK, WHITE)),
+    }
+}
+
+/// Rasterize a shape given by its signed distance (design units, negative
+/// inside), filled with `fill` and bordered with `border`
+fn render(
+    distance: impl Fn(f64, f64) -> f64,
+    hotspot: (f64, f64),
+    fill: Rgba<u8>,
+    border: Rgba<u8>,
+) -> Sprite {
+    let size = (DESIGN_SIZE * RESOLUTION) as u32;
+    let image = RgbaImage::from_fn(size, size, |px, py| {
+        let d = distance(
+            (px as f64 + 0.5) / RESOLUTION,
+            (py as f64 + 0.5) / RESOLUTION,
+        );
+        let outer = (0.5 - (d - BORDER) * RESOLUTION).clamp(0.0, 1.0);
+        let inner = (0.5 - d * RESOLUTION).clamp(0.0, 1.0);
+        if outer == 0.0 {
+            return Rgba([0, 0, 0, 0]);
+        }
+        let mix = |c: usize| (border[c] as f64 + (fill[c] as f64 - border[c] as f64) * inner) as u8;
+        Rgba([mix(0), mix(1), mix(2), (outer * 255.0).round() as u8])
+    });
+    Sprite {
+        image,
+        hotspot: (hotspot.0 * RESOLUTION, hotspot.1 * RESOLUTION),
+    }
+}
+
+/// Distance to a segment, less `radius` (a capsule)
+fn capsule(x: f64, y: f64, from: (f64, f64), to: (f64, f64), radius: f64) -> f64 {
+    let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+    let t = (((x - from.0) * dx + (y - from.1) * dy) / (dx * dx + dy * dy)).clamp(0.0, 1.0);
+    (x - from.0 - dx * t).hypot(y - from.1 - dy * t) - radius
+}
+
+/// Approximate distance to a convex polygon: the furthest of its edges
+fn polygon(x: f64, y: f64, points: &[(f64, f64)]) -> f64 {
+    let n = points.len() as f64;
+    let center = (
+        points.iter().map(|p| p.0).sum::<f64>() / n,
+        points.iter().map(|p| p.1).sum::<f64>() / n,
+    );
+    let mut distance = f64::MIN;
+    for (i, a) in points.iter().enumerate() {
+        let b = points[(i + 1) % points.len()];
+        let length = (b.0 - a.0).hypot(b.1 - a.1);
+        let mut normal = ((b.1 - a.1) / length, (a.0 - b.0) / length);
+        // Point it away from the middle, whichever way the points wind
+        if (center.0 - a.0) * normal.0 + (center.1 - a.1) * normal.1 > 0.0 {
+            normal = (-normal.0, -normal.1);
+        }
+        distance = distance.max((x - a.0) * normal.0 + (y - a.1) * normal.1);
+    }
+    distance
+}
+
+fn ibeam(x: f64, y: f64) -> f64 {
+    let stem = capsule(x, y, (16.0, 6.0), (16.0, 26.0), 1.0);
+    let top = capsule(x, y, (12.5, 5.5), (19.5, 5.5), 1.0);
+    let bottom = capsule(x, y, (12.5, 26.5), (19.5, 26.5), 1.0);
+    stem.min(top).min(bottom)
+}
+
+fn pointing_hand(x: f64, y: f64) -> f64 {
+    let index = rounded_rect_distance(x, y, 11.0, 3.0, 4.0, 16.0, 2.0);
+    let middle = rounded_rect_distance(x, y, 15.0, 10.0, 4.0, 10.0, 2.0);
+    let ring = rounded_rect_distance(x, y, 19.0, 11.0, 4.0, 10.0, 2.0);
+    let little = rounded_rect_distance(x, y, 23.0, 13.0, 3.5, 9.0, 1.75);
+    let palm = rounded_rect_distance(x, y, 11.0, 15.0, 15.5, 13.0, 4.0);
+    let thumb = capsule(x, y, (7.0, 17.0), (12.0, 24.0), 2.0);
+    index.min(middle).min(ring).min(little).min(palm).min(thumb)
+}
+
+/// Double-headed arrow along the x axis
+fn resize_horizontal(x: f64, y: f64) -> f64 {
+    let shaft = capsule(x, y, (9.0, 16.0), (23.0, 16.0), 1.25);
+    let left = polygon(x, y, &[(3.0, 16.0), (10.0, 10.5), (10.0, 21.5)]);
+    let right = polygon(x, y, &[(29.0, 16.0), (22.0, 10.5), (22.0, 21.5)]);
+    shaft.min(left).min(right)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_drawn_sprites() {
+        for shape in [
+            CursorShape::IBeam,
+            CursorShape::PointingHand,
+            CursorShape::ResizeHorizontal,
+            CursorShape::ResizeVertical,
+        ] {
+            let sprite = cursor_sprite(shape);
+            assert_eq!(sprite.image.dimensions(), (128, 128));
+            // The hotspot is on the shape
+            let (hx, hy) = sprite.hotspot;
+            let at_hotspot = sprite.image.get_pixel(hx as u32, hy as u32 + 2);
+            assert!(at_hotspot[3] > 200, "{:?}", shape);
+            assert_eq!(sprite.image.get_pixel(0, 0)[3], 0, "{:?}", shape);
+        }
+
+        // Dark I-beam with a light border; light hand with a dark one
+        let ibeam = &cursor_sprite(CursorShape::IBeam).image;
+        assert_eq!(ibeam.get_pixel(64, 64)[0], 0);
+        assert_eq!(ibeam.get_pixel(64 + 8, 64)[0], 255);
+        let hand = &cursor_sprite(CursorShape::PointingHand).image;
+        assert_eq!(hand.get_pixel(76, 84)[0], 255);
+    }
+}
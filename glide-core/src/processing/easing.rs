@@ -0,0 +1,101 @@
+//! Easing curves for zooms, pans, cursor fades and click ripples
+
+use std::f64::consts::PI;
+
+/// Shape of an animation over time. Each curve maps progress 0.0..=1.0
+/// to 0.0..=1.0, except `spring`, which overshoots before settling.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum Easing {
+    /// Cubic: quick but soft
+    #[default]
+    Cubic,
+    /// Quintic: snappier start, longer settle
+    Quint,
+    /// Exponential: the sharpest start
+    Expo,
+    /// Sine: the gentlest curve
+    Sine,
+    /// Damped spring: overshoots a little, then settles
+    Spring,
+    /// Constant speed
+    Linear,
+}
+
+/// Damping and frequency of `Easing::Spring`
+const SPRING_DAMPING: f64 = 6.0;
+const SPRING_FREQUENCY: f64 = 2.5 * PI;
+
+impl Easing {
+    /// Starts fast, ends slow (arriving)
+    pub fn ease_out(self, t: f64) -> f64 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Easing::Cubic => 1.0 - (1.0 - t).powi(3),
+            Easing::Quint => 1.0 - (1.0 - t).powi(5),
+            Easing::Expo if t >= 1.0 => 1.0,
+            Easing::Expo => 1.0 - 2f64.powf(-10.0 * t),
+            Easing::Sine => (t * PI / 2.0).sin(),
+            // Scaled to end exactly at 1
+            Easing::Spring => spring(t) / spring(1.0),
+            Easing::Linear => t,
+        }
+    }
+
+    /// Starts slow, ends fast (leaving): `ease_out` run backwards
+    pub fn ease_in(self, t: f64) -> f64 {
+        1.0 - self.ease_out(1.0 - t)
+    }
+
+    /// Slow start, fast middle, slow end
+    pub fn ease_in_out(self, t: f64) -> f64 {
+        let t = t.clamp(0.0, 1.0);
+        if t < 0.5 {
+            self.ease_in(2.0 * t) / 2.0
+        } else {
+            1.0 - self.ease_in(2.0 - 2.0 * t) / 2.0
+        }
+    }
+}
+
+/// Under-damped spring moving from 0 towards 1
+fn spring(t: f64) -> f64 {
+    let decay = (-SPRING_DAMPING * t).exp();
+    1.0 - decay
+        * ((SPRING_FREQUENCY * t).cos()
+            + SPRING_DAMPING / SPRING_FREQUENCY * (SPRING_FREQUENCY * t).sin())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_easing_endpoints() {
+        for easing in &[
+            Easing::Cubic,
+            Easing::Quint,
+            Easing::Expo,
+            Easing::Sine,
+            Easing::Spring,
+            Easing::Linear,
+        ] {
+            for f in [Easing::ease_in, Easing::ease_out, Easing::ease_in_out] {
+                assert!(f(*easing, 0.0).abs() < 1e-9, "{:?}", easing);
+                assert!((f(*easing, 1.0) - 1.0).abs() < 1e-9, "{:?}", easing);
+            }
+        }
+
+        // The curves the camera always used
+        let t: f64 = 0.3;
+        assert!((Easing::Cubic.ease_out(t) - (1.0 - (1.0 - t).powi(3))).abs() < 1e-12);
+        assert!((Easing::Cubic.ease_in(t) - t.powi(3)).abs() < 1e-12);
+        assert!((Easing::Cubic.ease_in_out(t) - 4.0 * t.powi(3)).abs() < 1e-12);
+
+        assert!(
+            Easing::Spring.ease_out(0.5) > 1.0,
+            "spring should overshoot"
+        );
+        assert_eq!(Easing::Linear.ease_in_out(0.25), 0.25);
+    }
+}
@@ -0,0 +1,1010 @@
+use crate::processing::frames::extract_background_frames;
+use crate::processing::progress::{status, Reporter};
+use anyhow::{Context, Result};
+use image::{DynamicImage, GenericImageView, Rgba, RgbaImage};
+use std::path::Path;
+use std::sync::Arc;
+use tempfile::TempDir;
+
+// Output canvas dimensions
+pub const OUTPUT_WIDTH: u32 = 1920;
+pub const OUTPUT_HEIGHT: u32 = 1080;
+
+// Output dimensions with `--vertical`
+pub const VERTICAL_WIDTH: u32 = 1080;
+pub const VERTICAL_HEIGHT: u32 = 1920;
+
+// Default space around the content, in canvas pixels
+pub const PADDING: u32 = 100;
+
+// Default corner radius for rounded corners
+pub const CORNER_RADIUS: u32 = 12;
+
+// Default shadow settings; the offset is a share of the blur radius
+pub const SHADOW_BLUR_RADIUS: u32 = 20;
+pub const SHADOW_OPACITY: f64 = 80.0 / 255.0;
+const SHADOW_OFFSET_RATIO: f64 = 0.4;
+const SHADOW_COLOR: Rgba<u8> = Rgba([0, 0, 0, 255]);
+
+/// Shadow under the content
+#[derive(Debug, Clone, Copy)]
+pub struct ShadowStyle {
+    /// How far the shadow spreads (canvas pixels)
+    pub size: u32,
+    /// Opacity at the shadow's darkest (0.0 to 1.0)
+    pub opacity: f64,
+}
+
+impl Default for ShadowStyle {
+    fn default() -> Self {
+        Self {
+            size: SHADOW_BLUR_RADIUS,
+            opacity: SHADOW_OPACITY,
+        }
+    }
+}
+
+/// How the content is framed on the canvas
+#[derive(Debug, Clone, Copy)]
+pub struct FrameStyle {
+    pub padding: u32,
+    pub corner_radius: u32,
+    /// None for no shadow
+    pub shadow: Option<ShadowStyle>,
+}
+
+impl Default for FrameStyle {
+    fn default() -> Self {
+        Self {
+            padding: PADDING,
+            corner_radius: CORNER_RADIUS,
+            shadow: Some(ShadowStyle::default()),
+        }
+    }
+}
+
+/// `--background` for a blurred, darkened copy of the recording's first frame
+pub const AUTO_BLUR: &str = "auto-blur";
+
+/// `--background` prefix for a blurred image, e.g. `blur:~/wallpaper.jpg`
+const BLUR_PREFIX: &str = "blur:";
+
+/// Blurred backdrops are made at 1/BACKDROP_DOWNSCALE size, which blurs
+/// them most of the way for little cost
+const BACKDROP_DOWNSCALE: u32 = 8;
+const BACKDROP_BLUR_SIGMA: f32 = 6.0;
+/// Share of a blurred backdrop's brightness kept, so the content stands out
+const BACKDROP_BRIGHTNESS: f64 = 0.6;
+
+/// Background paths with these extensions are played as video
+const VIDEO_EXTENSIONS: &[&str] = &["mp4", "mov", "m4v", "webm", "mkv", "avi", "gif"];
+
+/// Frame rate video backgrounds are decoded at (the output's)
+const VIDEO_BACKGROUND_FPS: f64 = 60.0;
+
+/// Background type for video processing
+#[derive(Clone)]
+pub enum Background {
+    Color(Rgba<u8>),
+    Image(Arc<RgbaImage>),
+    Video(Arc<VideoBackground>),
+}
+
+/// A video background, decoded ahead of time and looped
+pub struct VideoBackground {
+    frames: TempDir,
+    frame_count: usize,
+}
+
+impl VideoBackground {
+    fn decode(path: &Path) -> Result<Self> {
+        let frames = TempDir::new().context("Failed to create temp directory")?;
+        let frame_count = extract_background_frames(
+            path,
+            frames.path(),
+            OUTPUT_WIDTH,
+            OUTPUT_HEIGHT,
+            VIDEO_BACKGROUND_FPS,
+        )?;
+        Ok(Self {
+            frames,
+            frame_count,
+        })
+    }
+
+    /// Frame number (from 1) showing at `time` seconds into the output
+    fn frame_number(&self, time: f64) -> usize {
+        (time.max(0.0) * VIDEO_BACKGROUND_FPS).round() as usize % self.frame_count + 1
+    }
+
+    fn frame_at(&self, time: f64) -> Result<RgbaImage> {
+        let path = self
+            .frames
+            .path()
+            .join(format!("bg_{:06}.jpg", self.frame_number(time)));
+        Ok(image::open(&path)
+            .with_context(|| format!("Failed to load background frame {:?}", path))?
+            .to_rgba8())
+    }
+}
+
+impl Background {
+    /// Parse background from string: hex color (e.g., "#1a1a2e") or image path
+    pub fn parse(input: Option<&str>, reporter: &Reporter) -> Result<Self> {
+        match input {
+            None => {
+                // Default dark gray
+                Ok(Background::Color(Rgba([26, 26, 46, 255])))
+            }
+            Some(s) => {
+                // Check if it's a hex color
+                if let Some(color) = parse_hex_color(s) {
+                    Ok(Background::Color(color))
+                } else if is_video(s) {
+                    let video = VideoBackground::decode(Path::new(s))?;
+                    status!(
+                        reporter,
+                        "  Background: {} ({:.1}s, looped)",
+                        s,
+                        video.frame_count as f64 / VIDEO_BACKGROUND_FPS
+                    );
+                    Ok(Background::Video(Arc::new(video)))
+                } else if let Some(path) = s.strip_prefix(BLUR_PREFIX) {
+                    let img = image::open(path)
+                        .with_context(|| format!("Failed to load background image: {}", path))?;
+                    Ok(Background::blurred(&img))
+                } else {
+                    // Try to load as image
+                    let img = image::open(s)
+                        .with_context(|| format!("Failed to load background image: {}", s))?;
+                    // Resize to output dimensions
+                    let resized = img.resize_to_fill(
+                        OUTPUT_WIDTH,
+                        OUTPUT_HEIGHT,
+                        image::imageops::FilterType::Lanczos3,
+                    );
+                    Ok(Background::Image(Arc::new(resized.to_rgba8())))
+                }
+            }
+        }
+    }
+
+    /// A frosted backdrop: `img` scaled to fill the canvas, heavily
+    /// blurred and darkened
+    pub fn blurred(img: &DynamicImage) -> Self {
+        let small = img.resize_to_fill(
+            OUTPUT_WIDTH / BACKDROP_DOWNSCALE,
+            OUTPUT_HEIGHT / BACKDROP_DOWNSCALE,
+            image::imageops::FilterType::Triangle,
+        );
+        let mut small = image::imageops::blur(&small.to_rgba8(), BACKDROP_BLUR_SIGMA);
+        for pixel in small.pixels_mut() {
+            for c in 0..3 {
+                pixel[c] = (pixel[c] as f64 * BACKDROP_BRIGHTNESS).round() as u8;
+            }
+            pixel[3] = 255;
+        }
+        let backdrop = image::imageops::resize(
+            &small,
+            OUTPUT_WIDTH,
+            OUTPUT_HEIGHT,
+            image::imageops::FilterType::Triangle,
+        );
+        Background::Image(Arc::new(backdrop))
+    }
+
+    /// This background for a `width` x `height` canvas, so images aren't
+    /// resized for every frame
+    pub fn fit(self, width: u32, height: u32) -> Self {
+        match self {
+            Background::Image(img) if img.dimensions() != (width, height) => {
+                Background::Image(Arc::new(fill(&img, width, height)))
+            }
+            other => other,
+        }
+    }
+
+    /// Create a `width` x `height` canvas with this background as it is
+    /// `time` seconds into the output
+    pub fn create_canvas(&self, time: f64, width: u32, height: u32) -> Result<RgbaImage> {
+        let canvas = match self {
+            Background::Color(color) => return Ok(RgbaImage::from_pixel(width, height, *color)),
+            Background::Image(img) => img.as_ref().clone(),
+            Background::Video(video) => video.frame_at(time)?,
+        };
+        Ok(if canvas.dimensions() == (width, height) {
+            canvas
+        } else {
+            fill(&canvas, width, height)
+        })
+    }
+}
+
+/// `img` scaled to cover `width` x `height`, cropped to it
+fn fill(img: &RgbaImage, width: u32, height: u32) -> RgbaImage {
+    DynamicImage::ImageRgba8(img.clone())
+        .resize_to_fill(width, height, image::imageops::FilterType::Triangle)
+        .to_rgba8()
+}
+
+/// Whether a `--background` value names a video file
+fn is_video(value: &str) -> bool {
+    Path::new(value)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| VIDEO_EXTENSIONS.contains(&ext.to_ascii_lowercase().as_str()))
+}
+
+/// Layout info for placing content on canvas
+pub struct ContentLayout {
+    pub scale: f64,
+    pub offset_x: u32,
+    pub offset_y: u32,
+    pub scaled_width: u32,
+    pub scaled_height: u32,
+    /// Size of the canvas the content is placed on
+    pub canvas_width: u32,
+    pub canvas_height: u32,
+}
+
+impl ContentLayout {
+    /// Fit the content in the canvas with at least `padding` pixels on each side
+    pub fn calculate(content_width: u32, content_height: u32, padding: u32) -> Self {
+        let max_content_width = OUTPUT_WIDTH.saturating_sub(padding * 2).max(1);
+        let max_content_height = OUTPUT_HEIGHT.saturating_sub(padding * 2).max(1);
+
+        let scale_x = max_content_width as f64 / content_width as f64;
+        let scale_y = max_content_height as f64 / content_height as f64;
+        let scale = scale_x.min(scale_y).min(1.0); // Don't upscale
+
+        let scaled_width = (content_width as f64 * scale) as u32;
+        let scaled_height = (content_height as f64 * scale) as u32;
+
+        // Center on canvas
+        let offset_x = (OUTPUT_WIDTH - scaled_width) / 2;
+        let offset_y = (OUTPUT_HEIGHT - scaled_height) / 2;
+
+        Self {
+            scale,
+            offset_x,
+            offset_y,
+            scaled_width,
+            scaled_height,
+            canvas_width: OUTPUT_WIDTH,
+            canvas_height: OUTPUT_HEIGHT,
+        }
+    }
+
+    /// Layout for `--vertical`: the content fills the height of a tall
+    /// canvas (less `padding`), which widens past the output for wide
+    /// content so that a column of it can be cut out (`crop_column`)
+    pub fn reframed(content_width: u32, content_height: u32, padding: u32) -> Self {
+        let max_content_height = VERTICAL_HEIGHT.saturating_sub(padding * 2).max(1);
+        // Upscaling is the price of not letterboxing
+        let scale = max_content_height as f64 / content_height as f64;
+
+        let scaled_width = (content_width as f64 * scale) as u32;
+        let scaled_height = (content_height as f64 * scale) as u32;
+        if scaled_width + padding * 2 <= VERTICAL_WIDTH {
+            // Tall content fits as it is
+            let max_content_width = VERTICAL_WIDTH.saturating_sub(padding * 2).max(1);
+            let scale = scale.min(max_content_width as f64 / content_width as f64);
+            let scaled_width = (content_width as f64 * scale) as u32;
+            let scaled_height = (content_height as f64 * scale) as u32;
+            return Self {
+                scale,
+                offset_x: (VERTICAL_WIDTH - scaled_width) / 2,
+                offset_y: (VERTICAL_HEIGHT - scaled_height) / 2,
+                scaled_width,
+                scaled_height,
+                canvas_width: VERTICAL_WIDTH,
+                canvas_height: VERTICAL_HEIGHT,
+            };
+        }
+
+        Self {
+            scale,
+            offset_x: padding,
+            offset_y: (VERTICAL_HEIGHT - scaled_height) / 2,
+            scaled_width,
+            scaled_height,
+            canvas_width: scaled_width + padding * 2,
+            canvas_height: VERTICAL_HEIGHT,
+        }
+    }
+
+    /// The content's rectangle on the canvas: (x, y, width, height)
+    pub fn bounds(&self) -> (f64, f64, f64, f64) {
+        (
+            self.offset_x as f64,
+            self.offset_y as f64,
+            self.scaled_width as f64,
+            self.scaled_height as f64,
+        )
+    }
+}
+
+/// Apply rounded corners to an RGBA image
+pub fn apply_rounded_corners(img: &mut RgbaImage, radius: u32) {
+    let width = img.width();
+    let height = img.height();
+    let radius = radius.min(width / 2).min(height / 2);
+    if radius == 0 {
+        return;
+    }
+
+    for y in 0..height {
+        for x in 0..width {
+            let alpha = corner_alpha(x, y, width, height, radius);
+            if alpha < 255 {
+                let pixel = img.get_pixel_mut(x, y);
+                // Multiply existing alpha by corner alpha
+                let new_alpha = (pixel[3] as u32 * alpha as u32 / 255) as u8;
+                pixel[3] = new_alpha;
+            }
+        }
+    }
+}
+
+/// Calculate alpha value for a pixel based on corner rounding
+fn corner_alpha(x: u32, y: u32, width: u32, height: u32, radius: u32) -> u8 {
+    let radius_f = radius as f64;
+
+    // Check each corner
+    let corners = [
+        (radius, radius),                          // top-left
+        (width - radius - 1, radius),              // top-right
+        (radius, height - radius - 1),             // bottom-left
+        (width - radius - 1, height - radius - 1), // bottom-right
+    ];
+
+    for (cx, cy) in corners {
+        // Check if pixel is in the corner region
+        let in_corner_x =
+            (x <= radius && cx == radius) || (x >= width - radius - 1 && cx == width - radius - 1);
+        let in_corner_y = (y <= radius && cy == radius)
+            || (y >= height - radius - 1 && cy == height - radius - 1);
+
+        if in_corner_x && in_corner_y {
+            let dx = x as f64 - cx as f64;
+            let dy = y as f64 - cy as f64;
+            let dist = (dx * dx + dy * dy).sqrt();
+
+            if dist > radius_f {
+                return 0; // Outside corner
+            } else if dist > radius_f - 1.5 {
+                // Anti-aliasing at edge
+                let alpha = (radius_f - dist + 0.5).clamp(0.0, 1.0);
+                return (alpha * 255.0) as u8;
+            }
+        }
+    }
+
+    255 // Fully opaque
+}
+
+/// Draw a shadow on the canvas
+pub fn draw_shadow(
+    canvas: &mut RgbaImage,
+    x: i64,
+    y: i64,
+    width: u32,
+    height: u32,
+    radius: u32,
+    shadow: &ShadowStyle,
+) {
+    let offset = (shadow.size as f64 * SHADOW_OFFSET_RATIO).round() as i64;
+    let shadow_x = x + offset;
+    let shadow_y = y + offset;
+    let max_alpha = (shadow.opacity.clamp(0.0, 1.0) * 255.0).round() as u32;
+
+    // Draw multiple layers for blur effect
+    for blur_layer in 0..shadow.size {
+        let expand = blur_layer as i64;
+        let layer_alpha = max_alpha * (shadow.size - blur_layer) / (shadow.size * shadow.size);
+
+        if layer_alpha == 0 {
+            continue;
+        }
+
+        let sx = (shadow_x - expand).max(0) as u32;
+        let sy = (shadow_y - expand).max(0) as u32;
+        let sw = (width as i64 + expand * 2).min(canvas.width() as i64 - sx as i64) as u32;
+        let sh = (height as i64 + expand * 2).min(canvas.height() as i64 - sy as i64) as u32;
+
+        for py in sy..sy + sh {
+            for px in sx..sx + sw {
+                if px >= canvas.width() || py >= canvas.height() {
+                    continue;
+                }
+
+                // Check if inside rounded rectangle
+                let local_x = px as i64 - shadow_x + expand;
+                let local_y = py as i64 - shadow_y + expand;
+                let layer_width = width + 2 * expand as u32;
+                let layer_height = height + 2 * expand as u32;
+
+                if is_inside_rounded_rect(
+                    local_x,
+                    local_y,
+                    layer_width,
+                    layer_height,
+                    radius + expand as u32,
+                ) {
+                    let pixel = canvas.get_pixel_mut(px, py);
+                    // Blend shadow with existing pixel
+                    let alpha = layer_alpha as u8;
+                    pixel[0] = blend_channel(pixel[0], SHADOW_COLOR[0], alpha);
+                    pixel[1] = blend_channel(pixel[1], SHADOW_COLOR[1], alpha);
+                    pixel[2] = blend_channel(pixel[2], SHADOW_COLOR[2], alpha);
+                }
+            }
+        }
+    }
+}
+
+fn is_inside_rounded_rect(x: i64, y: i64, width: u32, height: u32, radius: u32) -> bool {
+    if x < 0 || y < 0 || x >= width as i64 || y >= height as i64 {
+        return false;
+    }
+
+    let x = x as u32;
+    let y = y as u32;
+    let radius_f = radius as f64;
+
+    // Check corners
+    let corners = [
+        (radius, radius),
+        (width - radius - 1, radius),
+        (radius, height - radius - 1),
+        (width - radius - 1, height - radius - 1),
+    ];
+
+    for (cx, cy) in corners {
+        let in_corner_x =
+            (x <= radius && cx == radius) || (x >= width - radius - 1 && cx == width - radius - 1);
+        let in_corner_y = (y <= radius && cy == radius)
+            || (y >= height - radius - 1 && cy == height - radius - 1);
+
+        if in_corner_x && in_corner_y {
+            let dx = x as f64 - cx as f64;
+            let dy = y as f64 - cy as f64;
+            if dx * dx + dy * dy > radius_f * radius_f {
+                return false;
+            }
+        }
+    }
+
+    true
+}
+
+/// Parse an opaque "#rrggbb" (or "rrggbb") color
+pub fn parse_hex_color(s: &str) -> Option<Rgba<u8>> {
+    let hex = s.trim_start_matches('#');
+    if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let r = u8::from_str_radix(&hex[0..2], 16).ok()?;
+    let g = u8::from_str_radix(&hex[2..4], 16).ok()?;
+    let b = u8::from_str_radix(&hex[4..6], 16).ok()?;
+    Some(Rgba([r, g, b, 255]))
+}
+
+/// Blend a single color channel with alpha
+pub fn blend_channel(bg: u8, fg: u8, alpha: u8) -> u8 {
+    let bg = bg as u32;
+    let fg = fg as u32;
+    let alpha = alpha as u32;
+    ((bg * (255 - alpha) + fg * alpha) / 255) as u8
+}
+
+/// Crossfade two same-sized images; `t` is the weight of `b` (0.0 to 1.0)
+pub fn blend_images(a: &RgbaImage, b: &RgbaImage, t: f64) -> RgbaImage {
+    let alpha = (t.clamp(0.0, 1.0) * 255.0).round() as u8;
+    let mut out = a.clone();
+    for (dest, src) in out.pixels_mut().zip(b.pixels()) {
+        for c in 0..4 {
+            dest[c] = blend_channel(dest[c], src[c], alpha);
+        }
+    }
+    out
+}
+
+/// A full-height column of `img`, `width` pixels wide and centered on
+/// `center_x` as far as the image allows
+pub fn crop_column(img: &DynamicImage, center_x: f64, width: u32) -> DynamicImage {
+    let width = width.min(img.width());
+    let max_left = (img.width() - width) as f64;
+    let left = (center_x - width as f64 / 2.0).clamp(0.0, max_left).round() as u32;
+    img.crop_imm(left, 0, width, img.height())
+}
+
+/// Apply zoom transformation to an image.
+/// Uses fixed-point zoom: the cursor stays at its screen position while content scales around it.
+/// Both axes use the same zoom factor, ensuring perfectly symmetric motion.
+/// With `bounds` (x, y, width, height on the canvas) the view is kept inside
+/// them along each axis it fits in, and centered on them along any it doesn't.
+pub fn apply_zoom(
+    img: &DynamicImage,
+    zoom: f64,
+    cursor_x: f64,
+    cursor_y: f64,
+    bounds: Option<(f64, f64, f64, f64)>,
+) -> DynamicImage {
+    let (width, height) = img.dimensions();
+    let (view_left, view_top, view_width, view_height) = zoom_view(
+        width as f64,
+        height as f64,
+        zoom,
+        cursor_x,
+        cursor_y,
+        bounds,
+    );
+
+    // Crop and resize (use Lanczos3 filter for sharp, high-quality results)
+    let cropped = img.crop_imm(
+        view_left as u32,
+        view_top as u32,
+        view_width as u32,
+        view_height as u32,
+    );
+
+    cropped.resize_exact(width, height, image::imageops::FilterType::Lanczos3)
+}
+
+/// The part of a `width` x `height` canvas shown at `zoom`: (left, top, width, height)
+pub fn zoom_view(
+    width: f64,
+    height: f64,
+    zoom: f64,
+    cursor_x: f64,
+    cursor_y: f64,
+    bounds: Option<(f64, f64, f64, f64)>,
+) -> (f64, f64, f64, f64) {
+    // Calculate the size of the visible area after zoom
+    let view_width = width / zoom;
+    let view_height = height / zoom;
+
+    // Fixed-point zoom formula: view_pos = cursor * (1 - 1/zoom)
+    // This keeps the cursor at its current screen position while zooming.
+    // Both axes use the SAME factor, guaranteeing symmetric motion.
+    let zoom_factor = 1.0 - 1.0 / zoom;
+    let mut view_left = cursor_x * zoom_factor;
+    let mut view_top = cursor_y * zoom_factor;
+
+    if let Some((x, y, bounds_width, bounds_height)) = bounds {
+        let within = |pos: f64, size: f64, start: f64, len: f64| {
+            if size <= len {
+                pos.clamp(start, start + len - size)
+            } else {
+                start + (len - size) / 2.0
+            }
+        };
+        view_left = within(view_left, view_width, x, bounds_width);
+        view_top = within(view_top, view_height, y, bounds_height);
+    }
+
+    // Clamp to valid bounds (handles edge cases where cursor is outside canvas)
+    let max_left = (width - view_width).max(0.0);
+    let max_top = (height - view_height).max(0.0);
+    (
+        view_left.clamp(0.0, max_left),
+        view_top.clamp(0.0, max_top),
+        view_width,
+        view_height,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_test_image(width: u32, height: u32) -> DynamicImage {
+        // Create a gradient image so we can verify zoom is actually happening
+        let mut img = RgbaImage::new(width, height);
+        for y in 0..height {
+            for x in 0..width {
+                let r = (x * 255 / width) as u8;
+                let g = (y * 255 / height) as u8;
+                img.put_pixel(x, y, Rgba([r, g, 128, 255]));
+            }
+        }
+        DynamicImage::ImageRgba8(img)
+    }
+
+    #[test]
+    fn test_apply_zoom_no_zoom() {
+        let img = create_test_image(1920, 1080);
+        let result = apply_zoom(&img, 1.0, 960.0, 540.0, None);
+
+        assert_eq!(result.dimensions(), (1920, 1080));
+        // At zoom 1.0, output should equal input
+        let orig_pixel = img.get_pixel(960, 540);
+        let result_pixel = result.get_pixel(960, 540);
+        assert_eq!(orig_pixel, result_pixel);
+    }
+
+    #[test]
+    fn test_apply_zoom_center_cursor() {
+        let img = create_test_image(1920, 1080);
+        let zoom = 1.8;
+        let cursor_x = 960.0; // center
+        let cursor_y = 540.0; // center
+
+        let result = apply_zoom(&img, zoom, cursor_x, cursor_y, None);
+
+        assert_eq!(result.dimensions(), (1920, 1080));
+
+        // With fixed-point zoom, cursor should stay at same position
+        // The pixel at cursor position should come from the same source position
+        // Verify the view_left and view_top calculations:
+        let view_width = 1920.0 / zoom;
+        let view_height = 1080.0 / zoom;
+        let zoom_factor = 1.0 - 1.0 / zoom;
+        let view_left = cursor_x * zoom_factor;
+        let view_top = cursor_y * zoom_factor;
+
+        println!("zoom_factor: {}", zoom_factor);
+        println!("view_left: {}, view_top: {}", view_left, view_top);
+        println!("view_width: {}, view_height: {}", view_width, view_height);
+
+        // The cropped area should be smaller than the original
+        assert!(view_width < 1920.0);
+        assert!(view_height < 1080.0);
+    }
+
+    #[test]
+    fn test_apply_zoom_cursor_preserved() {
+        let img = create_test_image(1920, 1080);
+        let zoom = 1.8;
+
+        // Test cursor at center
+        let cursor_x = 960.0;
+        let cursor_y = 540.0;
+
+        // The pixel under the cursor stays under it
+        let result = apply_zoom(&img, zoom, cursor_x, cursor_y, None);
+        assert_eq!(result.dimensions(), (1920, 1080));
+        let before = img.get_pixel(960, 540);
+        let after = result.get_pixel(960, 540);
+        assert!((before[0] as i32 - after[0] as i32).abs() <= 2);
+        assert!((before[1] as i32 - after[1] as i32).abs() <= 2);
+
+        // Calculate where the cursor should appear after zoom
+        // With fixed-point zoom formula: view_left = cursor_x * (1 - 1/zoom)
+        let zoom_factor = 1.0 - 1.0 / zoom;
+        let view_left = cursor_x * zoom_factor;
+        let view_top = cursor_y * zoom_factor;
+        let view_width = 1920.0 / zoom;
+        let view_height = 1080.0 / zoom;
+
+        // Cursor position in cropped image
+        let cursor_in_crop_x: f64 = cursor_x - view_left;
+        let cursor_in_crop_y: f64 = cursor_y - view_top;
+
+        // After resize, cursor should be at:
+        let cursor_after_x: f64 = cursor_in_crop_x * (1920.0 / view_width);
+        let cursor_after_y: f64 = cursor_in_crop_y * (1080.0 / view_height);
+
+        println!(
+            "Cursor should be at: ({}, {})",
+            cursor_after_x, cursor_after_y
+        );
+
+        // Should be approximately at original position
+        assert!(
+            (cursor_after_x - cursor_x).abs() < 1.0,
+            "X position should be preserved"
+        );
+        assert!(
+            (cursor_after_y - cursor_y).abs() < 1.0,
+            "Y position should be preserved"
+        );
+    }
+
+    #[test]
+    fn test_apply_zoom_corner_cursor() {
+        let img = create_test_image(1920, 1080);
+        let zoom = 1.8;
+
+        // Test cursor at bottom-right corner
+        let cursor_x = 1800.0;
+        let cursor_y = 900.0;
+
+        let result = apply_zoom(&img, zoom, cursor_x, cursor_y, None);
+        assert_eq!(result.dimensions(), (1920, 1080));
+
+        // Verify the zoom math works for corner positions
+        let zoom_factor = 1.0 - 1.0 / zoom;
+        let view_width = 1920.0 / zoom;
+        let view_height = 1080.0 / zoom;
+
+        let view_left = (cursor_x * zoom_factor).max(0.0).min(1920.0 - view_width);
+        let view_top = (cursor_y * zoom_factor).max(0.0).min(1080.0 - view_height);
+
+        println!(
+            "Corner zoom - view_left: {}, view_top: {}",
+            view_left, view_top
+        );
+
+        // View should be offset toward the corner
+        assert!(view_left > 0.0, "View should be offset from left");
+        assert!(view_top > 0.0, "View should be offset from top");
+    }
+
+    #[test]
+    fn test_apply_zoom_with_layout_offset() {
+        // Simulate a scenario like the actual pipeline:
+        // - Window content is centered on a 1920x1080 canvas
+        // - Content has layout offset (e.g., offset_x=260, offset_y=140)
+        // - Cursor click is on the content area
+
+        let img = create_test_image(1920, 1080);
+        let zoom = 1.8;
+
+        // Cursor click at canvas position (660, 490) - which is on the content
+        let canvas_cursor_x = 660.0;
+        let canvas_cursor_y = 490.0;
+
+        let result = apply_zoom(&img, zoom, canvas_cursor_x, canvas_cursor_y, None);
+
+        // Verify dimensions preserved
+        assert_eq!(result.dimensions(), (1920, 1080));
+
+        // Verify the view calculations
+        let zoom_factor = 1.0 - 1.0 / zoom;
+        let view_left = canvas_cursor_x * zoom_factor;
+        let view_top = canvas_cursor_y * zoom_factor;
+
+        println!(
+            "Layout offset test: view_left={}, view_top={}, zoom_factor={}",
+            view_left, view_top, zoom_factor
+        );
+
+        // view_left should be positive (zooming toward the cursor)
+        assert!(view_left > 0.0, "view_left should be positive");
+        assert!(view_top > 0.0, "view_top should be positive");
+    }
+
+    #[test]
+    fn test_apply_zoom_zero_cursor() {
+        // Edge case: cursor at (0, 0)
+        let img = create_test_image(1920, 1080);
+        let zoom = 1.8;
+
+        let result = apply_zoom(&img, zoom, 0.0, 0.0, None);
+        assert_eq!(result.dimensions(), (1920, 1080));
+
+        // With cursor at (0, 0), zoom should center on top-left
+        // view_left = 0 * anything = 0
+        // This should zoom into the top-left corner
+        let orig_pixel = img.get_pixel(0, 0);
+        let zoomed_pixel = result.get_pixel(0, 0);
+
+        // The top-left pixel should be the same (zooming from origin)
+        println!(
+            "Zero cursor - orig: {:?}, zoomed: {:?}",
+            orig_pixel, zoomed_pixel
+        );
+    }
+
+    #[test]
+    fn test_fixed_point_zoom_is_symmetric() {
+        // Verify that the fixed-point formula produces symmetric motion
+        // Both axes should use the same factor regardless of cursor position
+        let zoom = 1.8;
+        let zoom_factor = 1.0 - 1.0 / zoom;
+
+        // Test at various cursor positions
+        let test_cases: [(f64, f64, &str); 5] = [
+            (960.0, 540.0, "center"),
+            (100.0, 100.0, "top-left area"),
+            (1800.0, 900.0, "bottom-right area"),
+            (660.0, 490.0, "offset position"),
+            (1500.0, 300.0, "asymmetric position"),
+        ];
+
+        for (cursor_x, cursor_y, label) in test_cases {
+            let view_left = cursor_x * zoom_factor;
+            let view_top = cursor_y * zoom_factor;
+
+            // Verify the ratio: view_pos / cursor_pos should be the same for both axes
+            let x_ratio = if cursor_x > 0.0 {
+                view_left / cursor_x
+            } else {
+                zoom_factor
+            };
+            let y_ratio = if cursor_y > 0.0 {
+                view_top / cursor_y
+            } else {
+                zoom_factor
+            };
+
+            println!("{}: x_ratio={:.4}, y_ratio={:.4}", label, x_ratio, y_ratio);
+
+            // Both ratios should equal zoom_factor (perfectly symmetric)
+            assert!(
+                (x_ratio - zoom_factor).abs() < 0.0001,
+                "X ratio should equal zoom_factor"
+            );
+            assert!(
+                (y_ratio - zoom_factor).abs() < 0.0001,
+                "Y ratio should equal zoom_factor"
+            );
+        }
+    }
+
+    #[test]
+    fn test_zoom_produces_magnification() {
+        // Verify that zoom actually magnifies the content
+        let img = create_test_image(1920, 1080);
+        let zoom = 1.8;
+
+        // Apply zoom at center
+        let result = apply_zoom(&img, zoom, 960.0, 540.0, None);
+
+        // Check that a pixel NOT at the cursor position has changed
+        // (proving that content is being cropped and resized)
+        let orig_pixel = img.get_pixel(200, 200);
+        let zoomed_pixel = result.get_pixel(200, 200);
+
+        println!("Magnification test:");
+        println!("  Original (200,200): {:?}", orig_pixel);
+        println!("  Zoomed (200,200): {:?}", zoomed_pixel);
+
+        // The pixels should be different because we're showing different content
+        assert_ne!(
+            orig_pixel, zoomed_pixel,
+            "Zoom should change visible content at non-cursor positions"
+        );
+    }
+
+    #[test]
+    fn test_zoom_view_within_content() {
+        let content = Some((160.0, 90.0, 1600.0, 900.0));
+
+        // Zoomed on the top-left corner: shifted in to the content's edge
+        let (left, top, w, h) = zoom_view(1920.0, 1080.0, 2.0, 0.0, 0.0, content);
+        assert_eq!((left, top, w, h), (160.0, 90.0, 960.0, 540.0));
+        assert_eq!(
+            zoom_view(1920.0, 1080.0, 2.0, 0.0, 0.0, None),
+            (0.0, 0.0, 960.0, 540.0)
+        );
+
+        // Inside the content: unchanged
+        let inside = zoom_view(1920.0, 1080.0, 2.0, 960.0, 540.0, content);
+        assert_eq!(inside, zoom_view(1920.0, 1080.0, 2.0, 960.0, 540.0, None));
+
+        // Barely zoomed, wider than the content: centered on it
+        let (left, _, w, _) = zoom_view(1920.0, 1080.0, 1.1, 0.0, 0.0, content);
+        assert!((left + w / 2.0 - 960.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_apply_zoom_produces_different_output() {
+        let img = create_test_image(1920, 1080);
+
+        // Get a pixel from the corner at no zoom
+        let corner_pixel_no_zoom = img.get_pixel(100, 100);
+
+        // Apply zoom centered on cursor at (500, 500)
+        let zoomed = apply_zoom(&img, 1.8, 500.0, 500.0, None);
+
+        // The same screen position (100, 100) should now show different content
+        // because we've zoomed and panned
+        let corner_pixel_zoomed = zoomed.get_pixel(100, 100);
+
+        // These should be different (zoom should change the visible content)
+        println!("No zoom pixel at (100,100): {:?}", corner_pixel_no_zoom);
+        println!("Zoomed pixel at (100,100): {:?}", corner_pixel_zoomed);
+
+        // At 1.8x zoom with cursor at (500, 500):
+        // view_left = 500 * (1 - 1/1.8) = 500 * 0.444 = 222
+        // The pixel at (100, 100) in the result comes from around (222 + 100/1.8, ...) in original
+        // So it should be different
+        assert_ne!(
+            corner_pixel_no_zoom, corner_pixel_zoomed,
+            "Zoom should change the visible content"
+        );
+    }
+
+    #[test]
+    fn test_blurred_background() {
+        // Hard black/white halves blur into a soft, darkened gradient
+        let mut img = RgbaImage::from_pixel(640, 360, Rgba([0, 0, 0, 255]));
+        for y in 0..360 {
+            for x in 320..640 {
+                img.put_pixel(x, y, Rgba([255, 255, 255, 255]));
+            }
+        }
+        let Background::Image(backdrop) = Background::blurred(&DynamicImage::ImageRgba8(img))
+        else {
+            panic!("expected an image background");
+        };
+        assert_eq!(backdrop.dimensions(), (OUTPUT_WIDTH, OUTPUT_HEIGHT));
+        let right = backdrop.get_pixel(OUTPUT_WIDTH - 1, 540)[0];
+        assert!(right > 140 && right <= 153, "darkened: {}", right);
+        let middle = backdrop.get_pixel(OUTPUT_WIDTH / 2, 540)[0];
+        assert!(middle > 30 && middle < 120, "blurred: {}", middle);
+    }
+
+    #[test]
+    fn test_vertical_reframe() {
+        // Wide content fills the height of a canvas wider than the output
+        let layout = ContentLayout::reframed(1920, 1080, 100);
+        assert_eq!(layout.scaled_height, VERTICAL_HEIGHT - 200);
+        assert_eq!(layout.canvas_height, VERTICAL_HEIGHT);
+        assert_eq!(layout.canvas_width, layout.scaled_width + 200);
+        assert!(layout.canvas_width > VERTICAL_WIDTH);
+
+        // Tall content fits the output as it is
+        let layout = ContentLayout::reframed(600, 1200, 100);
+        assert_eq!(
+            (layout.canvas_width, layout.canvas_height),
+            (VERTICAL_WIDTH, VERTICAL_HEIGHT)
+        );
+        assert!(layout.scaled_width <= VERTICAL_WIDTH - 200);
+
+        let img = DynamicImage::ImageRgba8(RgbaImage::from_fn(3000, 100, |x, _| {
+            Rgba([(x / 12) as u8, 0, 0, 255])
+        }));
+        let column = crop_column(&img, 1500.0, 1080);
+        assert_eq!(column.dimensions(), (1080, 100));
+        assert_eq!(column.get_pixel(0, 0)[0], (960 / 12) as u8);
+        // Kept inside the image at the edges
+        let column = crop_column(&img, 100.0, 1080);
+        assert_eq!(column.get_pixel(0, 0)[0], 0);
+
+        let canvas = Background::Image(Arc::new(RgbaImage::new(192, 108)))
+            .fit(300, 200)
+            .create_canvas(0.0, 300, 200)
+            .unwrap();
+        assert_eq!(canvas.dimensions(), (300, 200));
+    }
+
+    #[test]
+    fn test_video_background_loops() {
+        assert!(is_video("demo-loop.MP4"));
+        assert!(!is_video("wallpaper.png"));
+        assert!(!is_video("#1a1a2e"));
+
+        let video = VideoBackground {
+            frames: TempDir::new().unwrap(),
+            frame_count: 120, // 2s
+        };
+        assert_eq!(video.frame_number(0.0), 1);
+        assert_eq!(video.frame_number(1.0), 61);
+        assert_eq!(video.frame_number(2.0), 1);
+        assert_eq!(video.frame_number(2.5), 31);
+    }
+
+    #[test]
+    fn test_frame_style() {
+        // 1920x1080 content fills the canvas less the padding
+        let layout = ContentLayout::calculate(1920, 1080, 0);
+        assert_eq!((layout.offset_x, layout.scaled_width), (0, 1920));
+        let layout = ContentLayout::calculate(1920, 1080, 200);
+        assert_eq!(layout.scaled_height, 680);
+        assert_eq!(layout.offset_y, 200);
+
+        // Square corners leave the content untouched
+        let mut img = RgbaImage::from_pixel(50, 50, Rgba([255, 0, 0, 255]));
+        apply_rounded_corners(&mut img, 0);
+        assert_eq!(img.get_pixel(0, 0)[3], 255);
+        apply_rounded_corners(&mut img, 12);
+        assert_eq!(img.get_pixel(0, 0)[3], 0);
+
+        // A bigger, darker shadow reaches further
+        let shadow_at = |shadow: ShadowStyle| {
+            let mut canvas = RgbaImage::from_pixel(400, 400, Rgba([255, 255, 255, 255]));
+            draw_shadow(&mut canvas, 100, 100, 200, 200, 12, &shadow);
+            canvas.get_pixel(200, 310)[0]
+        };
+        let light = shadow_at(ShadowStyle::default());
+        let heavy = shadow_at(ShadowStyle {
+            size: 60,
+            opacity: 0.8,
+        });
+        assert!(heavy < light, "{} vs {}", heavy, light);
+    }
+}
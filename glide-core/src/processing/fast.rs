@@ -0,0 +1,242 @@
+//! `--fast`: the whole render as one FFmpeg filter graph
+//!
+//! Instead of drawing every frame, the camera is sampled once per output
+//! frame, simplified to straight-line pieces and written into `zoompan` and
+//! `crop` expressions; FFmpeg scales the video onto the background and zooms
+//! it in a single pass. The cursor, click effects, overlays and motion blur
+//! are left out, and the background doesn't animate.
+
+use crate::processing::effects::{
+    apply_rounded_corners, draw_shadow, Background, ContentLayout, FrameStyle,
+};
+use anyhow::Result;
+use image::RgbaImage;
+use std::fmt::Write as _;
+
+/// How far simplified paths may stray from the camera's (zoom)
+const ZOOM_TOLERANCE: f64 = 0.002;
+
+/// How far simplified paths may stray from the camera's (pixels)
+const PIXEL_TOLERANCE: f64 = 0.5;
+
+/// What one output frame shows
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Shot {
+    pub zoom: f64,
+    /// Top-left of the zoomed view on the canvas
+    pub left: f64,
+    pub top: f64,
+    /// Left edge of the column cut from the zoomed canvas for vertical output
+    pub column_left: f64,
+}
+
+/// A straight piece of a path, from frame `start`
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Piece {
+    start: usize,
+    value: f64,
+    slope: f64,
+}
+
+/// `values` (one per frame) as straight pieces, each within `tolerance`
+/// of the values it covers
+fn simplify(values: &[f64], tolerance: f64) -> Vec<Piece> {
+    let mut pieces = Vec::new();
+    let mut start = 0;
+    while start < values.len() {
+        let value = values[start];
+        // Narrow the slopes that pass near every value so far until none do
+        let (mut low, mut high) = (f64::NEG_INFINITY, f64::INFINITY);
+        let mut end = start + 1;
+        while let Some(&next) = values.get(end) {
+            let frames = (end - start) as f64;
+            let new_low = low.max((next - tolerance - value) / frames);
+            let new_high = high.min((next + tolerance - value) / frames);
+            if new_low > new_high {
+                break;
+            }
+            (low, high) = (new_low, new_high);
+            end += 1;
+        }
+        let slope = if end - start > 1 {
+            (low + high) / 2.0
+        } else {
+            0.0
+        };
+        pieces.push(Piece {
+            start,
+            value,
+            slope,
+        });
+        start = end;
+    }
+    pieces
+}
+
+/// `values` (one per frame) as an FFmpeg expression of the frame number
+/// `frame`. Pieces at `base` are left out, keeping idle stretches free.
+fn path_expression(values: &[f64], tolerance: f64, base: f64, frame: &str) -> String {
+    let pieces = simplify(values, tolerance);
+    let mut expr = format!("{}", base);
+    for (i, piece) in pieces.iter().enumerate() {
+        if (piece.value - base).abs() < tolerance / 2.0 && piece.slope.abs() < 1e-9 {
+            continue;
+        }
+        let mut term = format!("{:.4}", piece.value - base);
+        if piece.slope.abs() >= 1e-9 {
+            let _ = write!(term, "+({}-{})*{:.6}", frame, piece.start, piece.slope);
+        }
+        let _ = match pieces.get(i + 1) {
+            Some(next) => write!(
+                expr,
+                "+between({},{},{})*({})",
+                frame,
+                piece.start,
+                next.start - 1,
+                term
+            ),
+            None => write!(expr, "+gte({},{})*({})", frame, piece.start, term),
+        };
+    }
+    expr
+}
+
+/// The filter graph for `--fast`: input `first_input` is the video,
+/// the next is the frame from [`frame_image`]; `[out]` is the result.
+/// `column` is the width of vertical output.
+pub fn filter_graph(
+    first_input: usize,
+    layout: &ContentLayout,
+    fps: f64,
+    shots: &[Shot],
+    column: Option<u32>,
+) -> String {
+    let (width, height) = (layout.canvas_width, layout.canvas_height);
+    let path = |value: fn(&Shot) -> f64, tolerance, base, frame| {
+        let values: Vec<_> = shots.iter().map(value).collect();
+        path_expression(&values, tolerance, base, frame)
+    };
+    // The video goes in the frame's hole, over black for any antialiased edge
+    let mut graph = format!(
+        "[{video}:v]fps={fps},scale={sw}:{sh}:flags=lanczos,\
+         pad={width}:{height}:{x}:{y}:color=black[content];\
+         [content][{frame}:v]overlay=shortest=1[canvas];\
+         [canvas]zoompan=z='{zoom}':x='{left}':y='{top}':d=1:s={width}x{height}:fps={fps}",
+        video = first_input,
+        frame = first_input + 1,
+        sw = layout.scaled_width,
+        sh = layout.scaled_height,
+        x = layout.offset_x,
+        y = layout.offset_y,
+        zoom = path(|s| s.zoom, ZOOM_TOLERANCE, 1.0, "on"),
+        left = path(|s| s.left, PIXEL_TOLERANCE, 0.0, "on"),
+        top = path(|s| s.top, PIXEL_TOLERANCE, 0.0, "on"),
+    );
+    if let Some(column) = column {
+        let _ = write!(
+            graph,
+            ",crop={}:{}:x='{}':y=0",
+            column,
+            height,
+            path(|s| s.column_left, PIXEL_TOLERANCE, 0.0, "n")
+        );
+    }
+    graph.push_str("[out]");
+    graph
+}
+
+/// The still parts of the picture: the background and shadow, with a hole
+/// the shape of the rounded content for the video to show through
+pub fn frame_image(
+    background: &Background,
+    layout: &ContentLayout,
+    frame_style: &FrameStyle,
+) -> Result<RgbaImage> {
+    let mut frame = background.create_canvas(0.0, layout.canvas_width, layout.canvas_height)?;
+    if let Some(shadow) = &frame_style.shadow {
+        draw_shadow(
+            &mut frame,
+            layout.offset_x as i64,
+            layout.offset_y as i64,
+            layout.scaled_width,
+            layout.scaled_height,
+            frame_style.corner_radius,
+            shadow,
+        );
+    }
+    let mut hole = RgbaImage::from_pixel(
+        layout.scaled_width,
+        layout.scaled_height,
+        image::Rgba([0, 0, 0, 255]),
+    );
+    apply_rounded_corners(&mut hole, frame_style.corner_radius);
+    for (x, y, pixel) in hole.enumerate_pixels() {
+        let (fx, fy) = (x + layout.offset_x, y + layout.offset_y);
+        if fx < frame.width() && fy < frame.height() {
+            let covered = frame.get_pixel_mut(fx, fy);
+            covered[3] = ((255 - pixel[3]) as u32 * covered[3] as u32 / 255) as u8;
+        }
+    }
+    Ok(frame)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fast_paths() {
+        // Idle, a 10-frame ramp, then held
+        let mut values = vec![1.0; 5];
+        values.extend((1..=10).map(|i| 1.0 + i as f64 * 0.08));
+        values.extend([1.8; 5]);
+        let pieces = simplify(&values, ZOOM_TOLERANCE);
+        assert_eq!(pieces.len(), 3);
+        assert_eq!(pieces[1].start, 5);
+        assert!((pieces[1].slope - 0.08).abs() < 1e-6);
+        for (i, value) in values.iter().enumerate() {
+            let piece = pieces.iter().rfind(|p| p.start <= i).unwrap();
+            let fitted = piece.value + (i - piece.start) as f64 * piece.slope;
+            assert!((fitted - value).abs() <= ZOOM_TOLERANCE);
+        }
+
+        // The idle piece is left to the base
+        let expr = path_expression(&values, ZOOM_TOLERANCE, 1.0, "on");
+        assert!(expr.starts_with("1+between(on,5,14)*(0.0800+(on-5)*0.080000)"));
+        assert!(expr.ends_with("+gte(on,15)*(0.8000)"));
+        assert_eq!(path_expression(&[0.0; 30], 0.5, 0.0, "n"), "0");
+
+        let layout = ContentLayout {
+            scale: 1.0,
+            offset_x: 10,
+            offset_y: 10,
+            scaled_width: 80,
+            scaled_height: 40,
+            canvas_width: 100,
+            canvas_height: 60,
+        };
+        let style = FrameStyle {
+            padding: 10,
+            corner_radius: 8,
+            shadow: None,
+        };
+        let background = Background::Color(image::Rgba([26, 26, 46, 255]));
+        let frame = frame_image(&background, &layout, &style).unwrap();
+        assert_eq!(frame.get_pixel(5, 5)[3], 255);
+        assert_eq!(frame.get_pixel(50, 30)[3], 0);
+        // Corners of the content stay covered
+        assert_eq!(frame.get_pixel(10, 10)[3], 255);
+
+        let shot = Shot {
+            zoom: 1.0,
+            left: 0.0,
+            top: 0.0,
+            column_left: 20.0,
+        };
+        let graph = filter_graph(1, &layout, 30.0, &[shot; 4], Some(50));
+        assert!(graph.starts_with("[1:v]fps=30,scale=80:40"));
+        assert!(graph.contains("[content][2:v]overlay"));
+        assert!(graph.contains("zoompan=z='1':x='0':y='0':d=1:s=100x60:fps=30"));
+        assert!(graph.ends_with(",crop=50:60:x='0+gte(n,0)*(20.0000)':y=0[out]"));
+    }
+}
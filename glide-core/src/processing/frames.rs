@@ -0,0 +1,1333 @@
+use crate::processing::progress::{status, Reporter};
+use crate::recording::encoder::Codec;
+use anyhow::{Context, Result};
+use rayon::iter::{IndexedParallelIterator, IntoParallelIterator, ParallelIterator};
+use image::{DynamicImage, RgbaImage};
+use std::io::{ErrorKind, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+
+/// Decodes a span of a video as RGBA frames at a constant `fps`, streamed
+/// from FFmpeg so only the frame being read is in memory. With
+/// `interpolate`, frames in between the source's are made up by
+/// motion-compensated interpolation rather than repeated.
+pub struct FrameReader {
+    child: Child,
+    stdout: ChildStdout,
+    width: u32,
+    height: u32,
+    done: bool,
+}
+
+impl FrameReader {
+    pub fn open(
+        input: &Path,
+        trim_start: f64,
+        duration: f64,
+        fps: f64,
+        interpolate: bool,
+        threads: Option<usize>,
+    ) -> Result<Self> {
+        let (width, height) = get_video_size(input)?;
+
+        let mut command = Command::new("ffmpeg");
+        command.args(["-v", "error"]);
+        if let Some(threads) = threads {
+            command.args(["-threads", &threads.to_string()]);
+        }
+        // Add seek before input for faster seeking (input seeking)
+        if trim_start > 0.0 {
+            command.args(["-ss", &format!("{:.3}", trim_start)]);
+        }
+        let mut child = command
+            .arg("-i")
+            .arg(input)
+            .args(["-t", &format!("{:.3}", duration)])
+            .args(["-vf", &decode_filter(fps, interpolate)])
+            .args(["-f", "rawvideo", "-pix_fmt", "rgba", "-"])
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .context("Failed to run ffmpeg for frame decoding")?;
+        let stdout = child.stdout.take().expect("stdout is piped");
+
+        Ok(Self {
+            child,
+            stdout,
+            width,
+            height,
+            done: false,
+        })
+    }
+
+    /// Bytes in each decoded frame
+    pub fn frame_bytes(&self) -> usize {
+        self.width as usize * self.height as usize * 4
+    }
+}
+
+impl Iterator for FrameReader {
+    type Item = Result<RgbaImage>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let mut buffer = vec![0u8; self.width as usize * self.height as usize * 4];
+        match self.stdout.read_exact(&mut buffer) {
+            Ok(()) => {
+                Some(Ok(RgbaImage::from_raw(self.width, self.height, buffer)
+                    .expect("buffer fits a frame")))
+            }
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof => {
+                self.done = true;
+                match self.child.wait() {
+                    Ok(status) if status.success() => None,
+                    _ => Some(Err(anyhow::anyhow!("FFmpeg frame decoding failed"))),
+                }
+            }
+            Err(e) => {
+                self.done = true;
+                Some(Err(e).context("Failed to read decoded frames"))
+            }
+        }
+    }
+}
+
+impl Drop for FrameReader {
+    fn drop(&mut self) {
+        // Stopped early: FFmpeg would otherwise block on the full pipe
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// The filter for `FrameReader`: frame n shows time n / fps, however the
+/// source was timed
+fn decode_filter(fps: f64, interpolate: bool) -> String {
+    if interpolate {
+        // Bidirectional motion estimation with overlapped blocks, smoothed,
+        // which holds up best on the hard edges of screen content
+        format!(
+            "minterpolate=fps={}:mi_mode=mci:mc_mode=aobmc:me_mode=bidir:vsbmc=1",
+            fps
+        )
+    } else {
+        format!("fps={}", fps)
+    }
+}
+
+/// Decode a video as JPEG frames (`bg_000001.jpg`, ...) at `fps`, scaled
+/// and cropped to fill `width` x `height`. Returns the number of frames.
+pub fn extract_background_frames(
+    input: &Path,
+    output_dir: &Path,
+    width: u32,
+    height: u32,
+    fps: f64,
+) -> Result<usize> {
+    let filter = format!(
+        "fps={},scale={w}:{h}:force_original_aspect_ratio=increase,crop={w}:{h}",
+        fps,
+        w = width,
+        h = height
+    );
+    let status = Command::new("ffmpeg")
+        .arg("-i")
+        .arg(input)
+        .args(["-vf", &filter, "-q:v", "2"])
+        .arg(output_dir.join("bg_%06d.jpg"))
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .status()
+        .context("Failed to run ffmpeg for background frames")?;
+    if !status.success() {
+        anyhow::bail!("FFmpeg couldn't decode the background video {:?}", input);
+    }
+
+    let count = std::fs::read_dir(output_dir)?
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path().extension().is_some_and(|ext| ext == "jpg"))
+        .count();
+    if count == 0 {
+        anyhow::bail!("Background video {:?} has no frames", input);
+    }
+    Ok(count)
+}
+
+/// Extract a single frame at `time` seconds
+pub fn extract_frame_at(input: &Path, time: f64, output: &Path) -> Result<()> {
+    let time_str = format!("{:.3}", time.max(0.0));
+
+    let status = Command::new("ffmpeg")
+        .args([
+            "-y",
+            "-ss",
+            time_str.as_str(),
+            "-i",
+            input.to_str().unwrap(),
+            "-frames:v",
+            "1",
+            output.to_str().unwrap(),
+        ])
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .status()
+        .context("Failed to run ffmpeg for frame extraction")?;
+
+    if !status.success() {
+        anyhow::bail!("FFmpeg frame extraction failed");
+    }
+
+    Ok(())
+}
+
+/// Get video frame size using ffprobe
+pub fn get_video_size(input: &Path) -> Result<(u32, u32)> {
+    let output = Command::new("ffprobe")
+        .args([
+            "-v",
+            "error",
+            "-select_streams",
+            "v:0",
+            "-show_entries",
+            "stream=width,height",
+            "-of",
+            "csv=p=0:s=x",
+            input.to_str().unwrap(),
+        ])
+        .output()
+        .context("Failed to run ffprobe")?;
+
+    let size_str = String::from_utf8_lossy(&output.stdout);
+    size_str
+        .trim()
+        .split_once('x')
+        .and_then(|(w, h)| Some((w.parse().ok()?, h.parse().ok()?)))
+        .filter(|&(w, h)| w > 0 && h > 0)
+        .ok_or_else(|| anyhow::anyhow!("Couldn't read the frame size of {}", input.display()))
+}
+
+/// Get video frame rate using ffprobe
+pub fn get_video_fps(input: &Path) -> Result<f64> {
+    let output = Command::new("ffprobe")
+        .args([
+            "-v",
+            "error",
+            "-select_streams",
+            "v:0",
+            "-show_entries",
+            "stream=r_frame_rate",
+            "-of",
+            "csv=p=0",
+            input.to_str().unwrap(),
+        ])
+        .output()
+        .context("Failed to run ffprobe")?;
+
+    let fps_str = String::from_utf8_lossy(&output.stdout);
+    let fps_str = fps_str.trim();
+
+    if let Some((num, den)) = fps_str.split_once('/') {
+        let num: f64 = num.parse().unwrap_or(60.0);
+        let den: f64 = den.parse().unwrap_or(1.0);
+        Ok(num / den)
+    } else {
+        Ok(fps_str.parse().unwrap_or(60.0))
+    }
+}
+
+/// Whether the file has an audio track (e.g. recorded with `--mic`)
+pub fn has_audio(input: &Path) -> bool {
+    Command::new("ffprobe")
+        .args([
+            "-v",
+            "error",
+            "-select_streams",
+            "a",
+            "-show_entries",
+            "stream=index",
+            "-of",
+            "csv=p=0",
+        ])
+        .arg(input)
+        .output()
+        .is_ok_and(|output| output.status.success() && !output.stdout.trim_ascii().is_empty())
+}
+
+/// Write the spans of `audio` in `kept` (seconds into it) to `path`, joined
+pub fn cut_audio(audio: &AudioSource, kept: &[(f64, f64)], path: &Path) -> Result<AudioSource> {
+    let select = kept
+        .iter()
+        .map(|(start, end)| format!("between(t,{:.3},{:.3})", start, end))
+        .collect::<Vec<_>>()
+        .join("+");
+    let status = Command::new("ffmpeg")
+        .args(["-v", "error", "-ss"])
+        .arg(format!("{:.3}", audio.start))
+        .arg("-t")
+        .arg(format!("{:.3}", audio.duration))
+        .arg("-i")
+        .arg(&audio.path)
+        .arg("-vn")
+        .arg("-af")
+        .arg(format!("aselect='{}',asetpts=N/SR/TB", select))
+        .args(["-c:a", "pcm_s16le", "-y"])
+        .arg(path)
+        .status()
+        .context("Failed to run ffmpeg")?;
+    if !status.success() {
+        anyhow::bail!("FFmpeg failed to cut the audio");
+    }
+    Ok(AudioSource {
+        path: path.to_path_buf(),
+        start: 0.0,
+        duration: kept.iter().map(|(start, end)| end - start).sum(),
+    })
+}
+
+/// Get video duration using ffprobe
+pub fn get_video_duration(input: &Path) -> Result<f64> {
+    let output = Command::new("ffprobe")
+        .args([
+            "-v",
+            "error",
+            "-show_entries",
+            "format=duration",
+            "-of",
+            "csv=p=0",
+            input.to_str().unwrap(),
+        ])
+        .output()
+        .context("Failed to run ffprobe")?;
+
+    let duration_str = String::from_utf8_lossy(&output.stdout);
+    let duration_str = duration_str.trim();
+
+    Ok(duration_str.parse().unwrap_or(0.0))
+}
+
+/// Check that a processed output exists and is readable before anything
+/// depends on it (e.g. deleting the source recording)
+pub fn verify_output(output: &Path, format: OutputFormat) -> Result<()> {
+    if format.is_image_sequence() {
+        let frames = std::fs::read_dir(output)
+            .with_context(|| format!("Output directory {} is missing", output.display()))?
+            .filter_map(|e| e.ok())
+            .count();
+        if frames == 0 {
+            anyhow::bail!("Output directory {} is empty", output.display());
+        }
+        return Ok(());
+    }
+
+    let size = std::fs::metadata(output)
+        .with_context(|| format!("Output {} is missing", output.display()))?
+        .len();
+    if size == 0 {
+        anyhow::bail!("Output {} is empty", output.display());
+    }
+
+    // ffprobe can't read animated WebP, so a non-empty file is as far as we go
+    if format == OutputFormat::Webp {
+        return Ok(());
+    }
+
+    let probe = Command::new("ffprobe")
+        .args([
+            "-v",
+            "error",
+            "-select_streams",
+            "v:0",
+            "-show_entries",
+            "stream=codec_type",
+            "-of",
+            "csv=p=0",
+            output.to_str().unwrap(),
+        ])
+        .output()
+        .context("Failed to run ffprobe")?;
+
+    if !probe.status.success() || !String::from_utf8_lossy(&probe.stdout).contains("video") {
+        anyhow::bail!("Output {} has no readable video stream", output.display());
+    }
+
+    Ok(())
+}
+
+/// Output frame rate unless `--output-fps` says otherwise, for smooth
+/// zooms and cursor movement
+const DEFAULT_OUTPUT_FPS: f64 = 60.0;
+
+/// Highest frame rate `--output-fps` accepts
+const MAX_OUTPUT_FPS: f64 = 240.0;
+
+/// Frame rate of the processed output
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OutputFps {
+    Fixed(f64),
+    /// Whatever the recording was captured at
+    Source,
+}
+
+impl Default for OutputFps {
+    fn default() -> Self {
+        Self::Fixed(DEFAULT_OUTPUT_FPS)
+    }
+}
+
+impl OutputFps {
+    /// Frames per second for a recording captured at `source_fps`
+    pub fn resolve(self, source_fps: f64) -> f64 {
+        match self {
+            Self::Fixed(fps) => fps,
+            Self::Source => source_fps,
+        }
+    }
+}
+
+/// Parse `--output-fps`: frames per second, or `source` for the recording's
+pub fn parse_output_fps(s: &str) -> Result<OutputFps, String> {
+    if s.eq_ignore_ascii_case("source") {
+        return Ok(OutputFps::Source);
+    }
+    match s.parse::<f64>() {
+        Ok(fps) if (1.0..=MAX_OUTPUT_FPS).contains(&fps) => Ok(OutputFps::Fixed(fps)),
+        _ => Err(format!(
+            "expected a frame rate from 1 to {} or \"source\"",
+            MAX_OUTPUT_FPS
+        )),
+    }
+}
+
+/// Container/format of the processed output
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum OutputFormat {
+    /// Single MP4 file
+    #[default]
+    Mp4,
+    /// Single WebM file (VP9 or AV1), for embedding in web pages
+    Webm,
+    /// HLS playlist (.m3u8) plus MPEG-TS segments
+    Hls,
+    /// DASH manifest (.mpd) plus fragmented MP4 segments
+    Dash,
+    /// Animated WebP (for places that inline images but not video)
+    Webp,
+    /// Animated PNG
+    Apng,
+    /// Animated GIF with an optimized palette (for tools that only inline GIFs)
+    Gif,
+    /// Numbered PNG frames in an output directory
+    PngSequence,
+    /// Numbered OpenEXR (32-bit float) frames in an output directory
+    ExrSequence,
+}
+
+impl OutputFormat {
+    /// File extension expected for the primary output file
+    pub fn extension(&self) -> &'static str {
+        match self {
+            OutputFormat::Mp4 => "mp4",
+            OutputFormat::Webm => "webm",
+            OutputFormat::Hls => "m3u8",
+            OutputFormat::Dash => "mpd",
+            OutputFormat::Webp => "webp",
+            OutputFormat::Apng => "png",
+            OutputFormat::Gif => "gif",
+            OutputFormat::PngSequence => "png",
+            OutputFormat::ExrSequence => "exr",
+        }
+    }
+
+    /// Image sequences are written as a directory of frames instead of a file
+    pub fn is_image_sequence(&self) -> bool {
+        matches!(self, OutputFormat::PngSequence | OutputFormat::ExrSequence)
+    }
+
+    /// Animated image formats are size/fps-capped and skip the video encoders
+    pub fn is_animated_image(&self) -> bool {
+        matches!(
+            self,
+            OutputFormat::Webp | OutputFormat::Apng | OutputFormat::Gif
+        )
+    }
+}
+
+// Defaults for animated image output, which grows quickly with resolution and frame rate
+const ANIMATED_DEFAULT_MAX_WIDTH: u32 = 960;
+const ANIMATED_DEFAULT_MAX_FPS: f64 = 15.0;
+
+/// Options for the final encode
+pub struct EncodeOptions {
+    pub format: OutputFormat,
+    /// Video codec for video formats
+    pub codec: Codec,
+    /// Faststart, regular keyframes and a browser-safe profile/level
+    pub web_optimized: bool,
+    /// Target segment length for HLS/DASH output (seconds)
+    pub segment_seconds: f64,
+    /// Maximum width for animated image output (height keeps aspect ratio)
+    pub max_width: Option<u32>,
+    /// Maximum frame rate for animated image output
+    pub max_fps: Option<f64>,
+    /// Write 16-bit-per-channel PNGs for image sequences: the 8-bit frames
+    /// widened, not rendered at a higher depth
+    pub png_16bit: bool,
+    /// FFmpeg thread count (None = FFmpeg's default)
+    pub threads: Option<usize>,
+    /// Audio to carry over into video output
+    pub audio: Option<AudioSource>,
+    /// FFmetadata file of chapters to write into video output
+    pub chapters: Option<PathBuf>,
+}
+
+/// The span of a recording's audio track that matches the processed frames
+pub struct AudioSource {
+    pub path: PathBuf,
+    /// Seconds into the source where the output starts
+    pub start: f64,
+    pub duration: f64,
+}
+
+impl Default for EncodeOptions {
+    fn default() -> Self {
+        Self {
+            format: OutputFormat::Mp4,
+            codec: Codec::H264,
+            web_optimized: false,
+            segment_seconds: 6.0,
+            max_width: None,
+            max_fps: None,
+            png_16bit: false,
+            threads: None,
+            audio: None,
+            chapters: None,
+        }
+    }
+}
+
+/// Inputs ahead of the frames: the source's audio, trimmed like the
+/// frames, then the chapters file (neither for animated images)
+fn side_input_args(options: &EncodeOptions) -> Vec<String> {
+    let mut args = Vec::new();
+    if options.format.is_animated_image() {
+        return args;
+    }
+    if let Some(audio) = &options.audio {
+        args.extend([
+            "-ss".into(),
+            format!("{:.3}", audio.start),
+            "-t".into(),
+            format!("{:.3}", audio.duration),
+            "-i".into(),
+            audio.path.to_string_lossy().into_owned(),
+        ]);
+    }
+    if let Some(chapters) = &options.chapters {
+        args.extend([
+            "-f".into(),
+            "ffmetadata".into(),
+            "-i".into(),
+            chapters.to_string_lossy().into_owned(),
+        ]);
+    }
+    args
+}
+
+/// Index of the frames input, after the side inputs
+pub fn frames_input(options: &EncodeOptions) -> usize {
+    if options.format.is_animated_image() {
+        return 0;
+    }
+    options.audio.is_some() as usize + options.chapters.is_some() as usize
+}
+
+/// Output options taking chapters from the chapters input, if any
+fn chapter_output_args(options: &EncodeOptions) -> Vec<String> {
+    if options.chapters.is_none() {
+        return Vec::new();
+    }
+    let input = options.audio.is_some() as usize;
+    vec!["-map_chapters".into(), input.to_string()]
+}
+
+/// Output options taking the audio input along, if any
+fn audio_output_args(options: &EncodeOptions) -> Vec<String> {
+    if options.audio.is_none() {
+        return Vec::new();
+    }
+    // WebM carries Opus rather than AAC
+    let codec = match options.format {
+        OutputFormat::Webm => "libopus",
+        _ => "aac",
+    };
+    vec![
+        "-map".into(),
+        "0:a".into(),
+        "-c:a".into(),
+        codec.into(),
+        "-b:a".into(),
+        "160k".into(),
+        "-shortest".into(),
+    ]
+}
+
+/// Output options shared by every encoder for `--web-optimized`:
+/// moov atom up front for progressive playback and a keyframe every two seconds
+/// so browsers and social platforms can seek and re-encode cleanly.
+fn web_optimized_args(options: &EncodeOptions, fps: f64) -> Vec<String> {
+    if !options.web_optimized || options.format != OutputFormat::Mp4 {
+        return Vec::new();
+    }
+    let gop = ((fps * 2.0).round() as u32).max(1).to_string();
+    vec![
+        "-movflags".into(),
+        "+faststart".into(),
+        "-g".into(),
+        gop,
+        "-keyint_min".into(),
+        ((fps.round() as u32).max(1)).to_string(),
+        "-sc_threshold".into(),
+        "0".into(),
+    ]
+}
+
+/// Limit FFmpeg's worker threads for `--threads`
+fn thread_args(options: &EncodeOptions) -> Vec<String> {
+    match options.threads {
+        Some(threads) => vec!["-threads".into(), threads.to_string()],
+        None => Vec::new(),
+    }
+}
+
+/// Profile/level constraints for decoders in browsers and phones
+/// (H.264 High@4.2 covers 1080p60). Only applied to encoders that honor them.
+fn web_profile_args(options: &EncodeOptions) -> Vec<String> {
+    if !options.web_optimized {
+        return Vec::new();
+    }
+    match options.codec {
+        Codec::H264 => vec![
+            "-profile:v".into(),
+            "high".into(),
+            "-level:v".into(),
+            "4.2".into(),
+        ],
+        Codec::H265 => vec!["-profile:v".into(), "main".into()],
+        Codec::Vp9 | Codec::Av1 => Vec::new(),
+    }
+}
+
+/// Apple players only accept HEVC in MP4 when it's tagged hvc1
+fn codec_tag_args(options: &EncodeOptions) -> Vec<String> {
+    match (options.codec, options.format) {
+        (Codec::H265, OutputFormat::Mp4 | OutputFormat::Dash) => {
+            vec!["-tag:v".into(), "hvc1".into()]
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// Muxer options for segmented (HLS/DASH) output.
+/// Keyframes are forced on segment boundaries so every segment is independently seekable.
+fn segmented_args(options: &EncodeOptions, output: &Path) -> Vec<String> {
+    let seg = format!("{}", options.segment_seconds.max(1.0));
+    let force_keyframes = format!("expr:gte(t,n_forced*{})", seg);
+    match options.format {
+        OutputFormat::Hls => {
+            let stem = output.file_stem().unwrap_or_default().to_string_lossy();
+            let segment_pattern = output.with_file_name(format!("{}_%04d.ts", stem));
+            vec![
+                "-force_key_frames".into(),
+                force_keyframes,
+                "-f".into(),
+                "hls".into(),
+                "-hls_time".into(),
+                seg,
+                "-hls_playlist_type".into(),
+                "vod".into(),
+                "-hls_segment_filename".into(),
+                segment_pattern.to_string_lossy().into_owned(),
+            ]
+        }
+        OutputFormat::Dash => vec![
+            "-force_key_frames".into(),
+            force_keyframes,
+            "-f".into(),
+            "dash".into(),
+            "-seg_duration".into(),
+            seg,
+            "-use_template".into(),
+            "1".into(),
+            "-use_timeline".into(),
+            "1".into(),
+        ],
+        _ => Vec::new(),
+    }
+}
+
+/// Downscale/decimate filter for animated image output
+fn animated_filter(options: &EncodeOptions, fps: f64) -> String {
+    let max_width = options.max_width.unwrap_or(ANIMATED_DEFAULT_MAX_WIDTH);
+    let max_fps = options.max_fps.unwrap_or(ANIMATED_DEFAULT_MAX_FPS).min(fps);
+    format!(
+        "fps={},scale='min({},iw)':-2:flags=lanczos",
+        max_fps, max_width
+    )
+}
+
+/// GIF filter: the 256-color palette is built from the whole clip, then
+/// used to map each frame. Only pixels that change between frames are
+/// dithered, which keeps static areas clean and the file small.
+fn gif_filter(options: &EncodeOptions, fps: f64) -> String {
+    format!(
+        "{},split[frames][source];[source]palettegen=stats_mode=diff[palette];\
+         [frames][palette]paletteuse=dither=bayer:bayer_scale=5:diff_mode=rectangle",
+        animated_filter(options, fps)
+    )
+}
+
+/// One way of encoding the frames; several are tried, best first
+struct VideoEncoder {
+    /// Shown while encoding, e.g. "NVENC (NVIDIA GPU)"
+    name: String,
+    /// Options before the frames input (e.g. the VA-API device)
+    input_args: Vec<String>,
+    /// Options for the encoded frames
+    codec_args: Vec<String>,
+}
+
+impl VideoEncoder {
+    fn new(name: impl Into<String>, input_args: &[&str], codec_args: &[&str]) -> Self {
+        Self {
+            name: name.into(),
+            input_args: input_args.iter().map(|arg| arg.to_string()).collect(),
+            codec_args: codec_args.iter().map(|arg| arg.to_string()).collect(),
+        }
+    }
+
+    fn with_args(mut self, args: Vec<String>) -> Self {
+        self.codec_args.extend(args);
+        self
+    }
+
+    /// Whether FFmpeg can encode a test frame this way; hardware encoders
+    /// are listed by FFmpeg builds even where the hardware is missing
+    fn works(&self) -> bool {
+        Command::new("ffmpeg")
+            .args(["-hide_banner", "-loglevel", "error"])
+            .args(&self.input_args)
+            .args([
+                "-f",
+                "lavfi",
+                "-i",
+                "color=size=256x256:rate=30",
+                "-frames:v",
+                "1",
+            ])
+            .args(&self.codec_args)
+            .args(["-f", "null", "-"])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .is_ok_and(|status| status.success())
+    }
+}
+
+/// Encoders for `options`, best first. The frames can only be streamed
+/// once, so the first that works is picked before any are sent.
+fn video_encoders(options: &EncodeOptions, fps: f64) -> Vec<VideoEncoder> {
+    if options.format.is_animated_image() {
+        let filter = match options.format {
+            OutputFormat::Gif => gif_filter(options, fps),
+            _ => animated_filter(options, fps),
+        };
+        let codec_args: &[&str] = match options.format {
+            OutputFormat::Webp => &[
+                "-c:v", "libwebp",
+                "-lossless", "0",
+                "-q:v", "75",
+                "-loop", "0",
+            ],
+            OutputFormat::Apng => &[
+                "-c:v", "apng",
+                "-pix_fmt", "rgb24",
+                "-plays", "0",
+                "-f", "apng",
+            ],
+            OutputFormat::Gif => &[
+                "-loop", "0",
+            ],
+            _ => unreachable!("not an animated image format"),
+        };
+        let name = format!("animated {:?} ({})", options.format, filter);
+        return vec![VideoEncoder::new(name, &[], &["-vf", &filter])
+            .with_args(codec_args.iter().map(|arg| arg.to_string()).collect())];
+    }
+
+    let software = options.codec.software_encoder();
+    let software_name = format!("{} (CPU)", software);
+    if options.codec.fits_webm() {
+        let codec_args: &[&str] = match options.codec {
+            // Constant quality needs -b:v 0; row-mt uses every core
+            Codec::Vp9 => &[
+                "-crf", "31",
+                "-b:v", "0",
+                "-deadline", "good",
+                "-cpu-used", "2",
+                "-row-mt", "1",
+            ],
+            Codec::Av1 => &[
+                "-crf", "30",
+                "-preset", "6",
+            ],
+            _ => unreachable!("not a VP9/AV1 codec"),
+        };
+        return vec![VideoEncoder::new(software_name, &[], &["-c:v", software])
+            .with_args(codec_args.iter().map(|arg| arg.to_string()).collect())
+            .with_args(vec!["-pix_fmt".into(), "yuv420p".into()])];
+    }
+
+    // libx265 needs a higher CRF than libx264 for about the same quality
+    let crf = match options.codec {
+        Codec::H265 => "18",
+        _ => "15",
+    };
+    let hardware = options.codec.hardware_prefix();
+    let cpu = VideoEncoder::new(software_name, &[], &[
+        "-c:v", software,
+        "-preset", "slow",
+        "-crf", crf,
+        "-pix_fmt", "yuv420p",
+    ])
+    .with_args(web_profile_args(options));
+
+    #[cfg(target_os = "macos")]
+    {
+        // macOS: Try VideoToolbox first, then CPU fallback
+        vec![
+            VideoEncoder::new("VideoToolbox (GPU)", &[], &[
+                "-c:v", &format!("{}_videotoolbox", hardware),
+                "-q:v", "80",
+                "-pix_fmt", "yuv420p",
+            ]),
+            cpu,
+        ]
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        // Linux: Try NVENC first, then VAAPI, then CPU fallback
+        vec![
+            VideoEncoder::new("NVENC (NVIDIA GPU)", &[], &[
+                "-c:v", &format!("{}_nvenc", hardware),
+                "-preset", "p4",
+                "-cq", "20",
+                "-pix_fmt", "yuv420p",
+            ])
+            .with_args(web_profile_args(options)),
+            VideoEncoder::new("VAAPI (AMD/Intel GPU)", &["-vaapi_device", "/dev/dri/renderD128"], &[
+                "-vf", "format=nv12,hwupload",
+                "-c:v", &format!("{}_vaapi", hardware),
+                "-qp", "20",
+            ]),
+            cpu,
+        ]
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "linux")))]
+    {
+        // Other platforms: CPU only
+        let _ = hardware;
+        vec![cpu]
+    }
+}
+
+/// Output options every encoder for `options` shares, with `video` (a
+/// stream or filter graph output) as the picture
+fn shared_output_args(
+    options: &EncodeOptions,
+    fps: f64,
+    output: &Path,
+    video: &str,
+) -> Vec<String> {
+    let map = vec!["-map".to_string(), video.to_string()];
+    if options.format.is_animated_image() {
+        return map.into_iter().chain(thread_args(options)).collect();
+    }
+    let segmented = if options.codec.fits_webm() {
+        Vec::new()
+    } else {
+        segmented_args(options, output)
+            .into_iter()
+            .chain(codec_tag_args(options))
+            .collect()
+    };
+    web_optimized_args(options, fps)
+        .into_iter()
+        .chain(segmented)
+        .chain(thread_args(options))
+        .chain(map)
+        .chain(audio_output_args(options))
+        .chain(chapter_output_args(options))
+        .collect()
+}
+
+/// The first encoder for `options` that works, announcing it
+fn pick_encoder(options: &EncodeOptions, fps: f64, reporter: &Reporter) -> VideoEncoder {
+    if options.web_optimized && options.format == OutputFormat::Mp4 {
+        status!(
+            reporter,
+            "Web-optimized output: faststart, 2s keyframe interval, yuv420p"
+        );
+    }
+    if matches!(options.format, OutputFormat::Hls | OutputFormat::Dash) {
+        status!(
+            reporter,
+            "Segmented {:?} output: {:.0}s segments",
+            options.format,
+            options.segment_seconds.max(1.0)
+        );
+    }
+
+    let mut encoders = video_encoders(options, fps);
+    tracing::debug!(
+        "Encoder candidates: {}",
+        encoders
+            .iter()
+            .map(|encoder| encoder.name.as_str())
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+    let fallback = encoders.pop().expect("software encoder comes last");
+    let encoder = encoders
+        .into_iter()
+        .find(|encoder| {
+            let works = encoder.works();
+            if !works {
+                status!(
+                    reporter,
+                    "{} not available, trying the next encoder...",
+                    encoder.name
+                );
+            }
+            works
+        })
+        .unwrap_or_else(|| {
+            tracing::debug!("Falling back to {}", fallback.name);
+            fallback
+        });
+    status!(reporter, "Encoding with {}...", encoder.name);
+    encoder
+}
+
+/// Longest filter graph passed on the command line rather than in a file
+const MAX_INLINE_FILTER: usize = 64 * 1024;
+
+/// Encode the `[out]` pad of `filter`, a filter graph over `inputs` (FFmpeg
+/// input options, numbered from [`frames_input`]), to `output` at `fps`.
+/// This is `--fast`, where FFmpeg does all the pixel work.
+pub fn encode_filter_graph(
+    inputs: &[String],
+    filter: &str,
+    output: &Path,
+    fps: f64,
+    options: &EncodeOptions,
+    reporter: &Reporter,
+) -> Result<()> {
+    let VideoEncoder {
+        input_args,
+        mut codec_args,
+        ..
+    } = pick_encoder(options, fps, reporter);
+
+    // Filters the encoder needs (hardware upload, downscaling) go on the
+    // end of the graph, as a graph can't be combined with -vf
+    let mut filter = filter.to_string();
+    let mut video = "[out]";
+    if let Some(i) = codec_args.iter().position(|arg| arg == "-vf") {
+        let encoder_filter = codec_args.remove(i + 1);
+        codec_args.remove(i);
+        filter = format!("{};[out]{}[encoded]", filter, encoder_filter);
+        video = "[encoded]";
+    }
+
+    // Long recordings make long graphs, past what fits in one argument
+    let script;
+    let graph_args = if filter.len() > MAX_INLINE_FILTER {
+        script = tempfile::NamedTempFile::new().context("Failed to create temp file")?;
+        std::fs::write(script.path(), &filter).context("Failed to write filter graph")?;
+        vec![
+            "-filter_complex_script".to_string(),
+            script.path().to_string_lossy().into_owned(),
+        ]
+    } else {
+        vec!["-filter_complex".to_string(), filter]
+    };
+
+    let status = Command::new("ffmpeg")
+        .args(["-v", "error"])
+        .args(side_input_args(options))
+        .args(&input_args)
+        .args(inputs)
+        .args(graph_args)
+        .args(&codec_args)
+        .args(shared_output_args(options, fps, output, video))
+        .arg("-y")
+        .arg(output)
+        .stdout(Stdio::null())
+        .status()
+        .context("Failed to run ffmpeg for encoding")?;
+    if !status.success() {
+        anyhow::bail!("FFmpeg encoding failed");
+    }
+    Ok(())
+}
+
+/// Where processed frames go, in order: into an FFmpeg encode through its
+/// stdin, or straight to numbered images for image sequences
+pub enum FrameWriter {
+    Encoder {
+        child: Child,
+        stdin: ChildStdin,
+        width: u32,
+        height: u32,
+    },
+    ImageSequence {
+        dir: PathBuf,
+        format: OutputFormat,
+        png_16bit: bool,
+        written: usize,
+    },
+}
+
+impl FrameWriter {
+    /// Start writing `width` x `height` frames at `fps` to `output`
+    pub fn open(
+        output: &Path,
+        fps: f64,
+        width: u32,
+        height: u32,
+        options: &EncodeOptions,
+        reporter: &Reporter,
+    ) -> Result<Self> {
+        if options.format.is_image_sequence() {
+            std::fs::create_dir_all(output)
+                .with_context(|| format!("Failed to create output directory {:?}", output))?;
+            status!(
+                reporter,
+                "Writing frames to {} ({})...",
+                output.display(),
+                match options.format {
+                    OutputFormat::ExrSequence => "32-bit float EXR",
+                    _ if options.png_16bit => "16-bit PNG",
+                    _ => "8-bit PNG",
+                }
+            );
+            return Ok(FrameWriter::ImageSequence {
+                dir: output.to_path_buf(),
+                format: options.format,
+                png_16bit: options.png_16bit,
+                written: 0,
+            });
+        }
+
+        let encoder = pick_encoder(options, fps, reporter);
+        let mut child = Command::new("ffmpeg")
+            .args(["-v", "error"])
+            .args(side_input_args(options))
+            .args(&encoder.input_args)
+            .args(["-f", "rawvideo", "-pix_fmt", "rgba"])
+            .args(["-s", &format!("{}x{}", width, height)])
+            .args(["-framerate", &fps.to_string(), "-i", "-"])
+            .args(&encoder.codec_args)
+            .args(shared_output_args(
+                options,
+                fps,
+                output,
+                &format!("{}:v", frames_input(options)),
+            ))
+            .arg("-y")
+            .arg(output)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .context("Failed to run ffmpeg for encoding")?;
+        let stdin = child.stdin.take().expect("stdin is piped");
+
+        Ok(FrameWriter::Encoder {
+            child,
+            stdin,
+            width,
+            height,
+        })
+    }
+
+    /// Write the next frames
+    pub fn write(&mut self, frames: Vec<RgbaImage>) -> Result<()> {
+        match self {
+            FrameWriter::Encoder {
+                stdin,
+                width,
+                height,
+                ..
+            } => {
+                for frame in frames {
+                    if frame.dimensions() != (*width, *height) {
+                        anyhow::bail!(
+                            "Frame is {}x{}, expected {}x{}",
+                            frame.width(),
+                            frame.height(),
+                            width,
+                            height
+                        );
+                    }
+                    stdin
+                        .write_all(frame.as_raw())
+                        .context("FFmpeg encoding failed")?;
+                }
+                Ok(())
+            }
+            FrameWriter::ImageSequence {
+                dir,
+                format,
+                png_16bit,
+                written,
+            } => {
+                let ext = format.extension();
+                let first = *written + 1;
+                *written += frames.len();
+                frames
+                    .into_par_iter()
+                    .enumerate()
+                    .map(|(i, frame)| {
+                        let dest = dir.join(format!("frame_{:06}.{}", first + i, ext));
+                        let frame = DynamicImage::ImageRgba8(frame);
+                        match format {
+                            OutputFormat::PngSequence if !*png_16bit => frame.save(&dest)?,
+                            OutputFormat::PngSequence => frame.to_rgba16().save(&dest)?,
+                            OutputFormat::ExrSequence => frame.to_rgba32f().save(&dest)?,
+                            _ => unreachable!("not an image sequence format"),
+                        }
+                        Ok(())
+                    })
+                    .collect::<Result<Vec<()>>>()
+                    .context("Failed to write image sequence")?;
+                Ok(())
+            }
+        }
+    }
+
+    /// Finish the output once every frame is written
+    pub fn finish(self) -> Result<()> {
+        match self {
+            FrameWriter::Encoder {
+                mut child, stdin, ..
+            } => {
+                // Closing stdin ends the input
+                drop(stdin);
+                let status = child.wait().context("Failed to wait for ffmpeg")?;
+                if !status.success() {
+                    anyhow::bail!("FFmpeg encoding failed");
+                }
+                Ok(())
+            }
+            FrameWriter::ImageSequence { written, .. } => {
+                if written == 0 {
+                    anyhow::bail!("No frames to write");
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_output_fps() {
+        assert_eq!(parse_output_fps("24"), Ok(OutputFps::Fixed(24.0)));
+        assert_eq!(parse_output_fps("29.97"), Ok(OutputFps::Fixed(29.97)));
+        assert_eq!(parse_output_fps("Source"), Ok(OutputFps::Source));
+        assert!(parse_output_fps("0").is_err());
+        assert!(parse_output_fps("480").is_err());
+        assert_eq!(OutputFps::Source.resolve(30.0), 30.0);
+        assert_eq!(OutputFps::default().resolve(30.0), 60.0);
+    }
+
+    #[test]
+    fn test_decode_filter() {
+        assert_eq!(decode_filter(30.0, false), "fps=30");
+        assert!(decode_filter(60.0, true).starts_with("minterpolate=fps=60:mi_mode=mci"));
+    }
+
+    #[test]
+    fn test_web_optimized_args() {
+        let mut options = EncodeOptions::default();
+        assert!(web_optimized_args(&options, 30.0).is_empty());
+        assert!(web_profile_args(&options).is_empty());
+
+        // Moov atom up front, and a keyframe every 2s (at least every 1s)
+        options.web_optimized = true;
+        assert_eq!(
+            web_optimized_args(&options, 30.0),
+            [
+                "-movflags",
+                "+faststart",
+                "-g",
+                "60",
+                "-keyint_min",
+                "30",
+                "-sc_threshold",
+                "0"
+            ]
+        );
+        assert!(web_optimized_args(&options, 60.0).contains(&"120".to_string()));
+        assert_eq!(
+            web_profile_args(&options),
+            ["-profile:v", "high", "-level:v", "4.2"]
+        );
+
+        // Only MP4 has a moov atom to move
+        for format in [OutputFormat::Hls, OutputFormat::Dash] {
+            options.format = format;
+            assert!(web_optimized_args(&options, 60.0).is_empty());
+        }
+    }
+
+    #[test]
+    fn test_h265_tagged_and_profiled() {
+        let options = EncodeOptions {
+            codec: Codec::H265,
+            web_optimized: true,
+            ..Default::default()
+        };
+        assert_eq!(codec_tag_args(&options), vec!["-tag:v", "hvc1"]);
+        assert_eq!(web_profile_args(&options), vec!["-profile:v", "main"]);
+        assert!(codec_tag_args(&EncodeOptions::default()).is_empty());
+    }
+
+    #[test]
+    fn test_webm_audio_is_opus() {
+        let mut options = EncodeOptions {
+            format: OutputFormat::Webm,
+            audio: Some(AudioSource {
+                path: PathBuf::from("/tmp/in.mp4"),
+                start: 0.0,
+                duration: 1.0,
+            }),
+            ..Default::default()
+        };
+        assert!(audio_output_args(&options).contains(&"libopus".to_string()));
+        options.format = OutputFormat::Mp4;
+        assert!(audio_output_args(&options).contains(&"aac".to_string()));
+
+        // Chapters are read between the audio and the frames
+        options.chapters = Some(PathBuf::from("/tmp/chapters.txt"));
+        assert_eq!(frames_input(&options), 2);
+        assert_eq!(chapter_output_args(&options), vec!["-map_chapters", "1"]);
+        assert_eq!(side_input_args(&options)[6..8], ["-f", "ffmetadata"]);
+    }
+
+    #[test]
+    fn test_thread_args() {
+        let mut options = EncodeOptions::default();
+        assert!(thread_args(&options).is_empty());
+        options.threads = Some(4);
+        assert_eq!(thread_args(&options), vec!["-threads", "4"]);
+    }
+
+    #[test]
+    fn test_segmented_args() {
+        let mut options = EncodeOptions {
+            format: OutputFormat::Hls,
+            segment_seconds: 4.0,
+            ..Default::default()
+        };
+        let output = Path::new("/tmp/out/demo.m3u8");
+
+        // Segments go next to the playlist, named after it
+        assert_eq!(
+            segmented_args(&options, output),
+            [
+                "-force_key_frames",
+                "expr:gte(t,n_forced*4)",
+                "-f",
+                "hls",
+                "-hls_time",
+                "4",
+                "-hls_playlist_type",
+                "vod",
+                "-hls_segment_filename",
+                "/tmp/out/demo_%04d.ts"
+            ]
+        );
+
+        options.format = OutputFormat::Dash;
+        let args = segmented_args(&options, Path::new("/tmp/out/demo.mpd"));
+        assert_eq!(
+            args[..6],
+            ["-force_key_frames", "expr:gte(t,n_forced*4)", "-f", "dash", "-seg_duration", "4"]
+        );
+
+        // Segments are at least a second long
+        options.segment_seconds = 0.2;
+        let args = segmented_args(&options, output);
+        assert!(args.contains(&"expr:gte(t,n_forced*1)".to_string()));
+
+        options.format = OutputFormat::Mp4;
+        assert!(segmented_args(&options, output).is_empty());
+    }
+
+    #[test]
+    fn test_animated_filter_caps_fps_and_width() {
+        let options = EncodeOptions {
+            format: OutputFormat::Webp,
+            max_width: Some(640),
+            max_fps: Some(120.0),
+            ..Default::default()
+        };
+        // max_fps never exceeds the source rate
+        assert_eq!(
+            animated_filter(&options, 60.0),
+            "fps=60,scale='min(640,iw)':-2:flags=lanczos"
+        );
+    }
+
+    #[test]
+    fn test_gif_filter_builds_palette() {
+        let options = EncodeOptions {
+            format: OutputFormat::Gif,
+            ..Default::default()
+        };
+        let filter = gif_filter(&options, 60.0);
+        assert!(filter.starts_with("fps=15,scale='min(960,iw)'"));
+        assert!(filter.contains("palettegen") && filter.contains("paletteuse"));
+    }
+
+    #[test]
+    fn test_export_png16_sequence() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let output_dir = dir.path().join("seq");
+        let options = EncodeOptions {
+            format: OutputFormat::PngSequence,
+            png_16bit: true,
+            ..Default::default()
+        };
+        let mut writer =
+            FrameWriter::open(&output_dir, 60.0, 4, 4, &options, &Reporter::default()).unwrap();
+        let frame = RgbaImage::from_pixel(4, 4, image::Rgba([255, 0, 0, 255]));
+        writer.write(vec![frame.clone()]).unwrap();
+        writer.write(vec![frame]).unwrap();
+        writer.finish().unwrap();
+
+        let frame = image::open(output_dir.join("frame_000002.png")).unwrap();
+        assert_eq!(frame.color(), image::ColorType::Rgba16);
+    }
+}
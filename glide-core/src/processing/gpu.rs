@@ -0,0 +1,416 @@
+//! `--gpu`: zoom, compositing and motion blur on the GPU
+//!
+//! Each pass uploads its images, runs one of the compute shaders in
+//! `gpu.wgsl` and reads the result back, so it drops in wherever the CPU
+//! version runs and gives the same picture to within rounding. Zoom samples
+//! bilinearly rather than with Lanczos. Built without the `gpu` feature,
+//! [`GpuRenderer::new`] fails and processing stays on the CPU.
+
+use crate::processing::motion_blur::Blur;
+use anyhow::Result;
+use image::RgbaImage;
+
+/// Runs effect passes on a GPU, shared by all processing threads
+#[cfg(feature = "gpu")]
+pub struct GpuRenderer {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    layout: wgpu::BindGroupLayout,
+    composite: wgpu::ComputePipeline,
+    zoom: wgpu::ComputePipeline,
+    radial_blur: wgpu::ComputePipeline,
+    directional_blur: wgpu::ComputePipeline,
+    /// Largest image a pass can take (bytes)
+    max_image_size: u64,
+    name: String,
+}
+
+/// Pixels along each side of a shader workgroup
+#[cfg(feature = "gpu")]
+const WORKGROUP_SIZE: u32 = 16;
+
+/// `Params` in gpu.wgsl
+#[cfg(feature = "gpu")]
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, bytemuck::Pod, bytemuck::Zeroable)]
+struct Params {
+    src_size: [u32; 2],
+    dst_size: [u32; 2],
+    offset: [i32; 2],
+    samples: u32,
+    _pad: u32,
+    values: [f32; 4],
+}
+
+#[cfg(feature = "gpu")]
+impl GpuRenderer {
+    /// Open the most capable GPU available
+    pub fn new() -> Result<Self> {
+        use anyhow::Context;
+
+        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor::new_without_display_handle());
+        let adapter = pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+            power_preference: wgpu::PowerPreference::HighPerformance,
+            ..Default::default()
+        }))
+        .context("No GPU found")?;
+        let limits = adapter.limits();
+        let (device, queue) = pollster::block_on(adapter.request_device(&wgpu::DeviceDescriptor {
+            label: Some("glide"),
+            required_limits: limits.clone(),
+            ..Default::default()
+        }))
+        .context("Failed to open the GPU")?;
+
+        let module = device.create_shader_module(wgpu::include_wgsl!("gpu.wgsl"));
+        let buffer = |binding, ty| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::COMPUTE,
+            ty: wgpu::BindingType::Buffer {
+                ty,
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("effect"),
+            entries: &[
+                buffer(0, wgpu::BufferBindingType::Storage { read_only: true }),
+                buffer(1, wgpu::BufferBindingType::Storage { read_only: false }),
+                buffer(2, wgpu::BufferBindingType::Uniform),
+            ],
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("effect"),
+            bind_group_layouts: &[Some(&layout)],
+            immediate_size: 0,
+        });
+        let pipeline = |entry_point| {
+            device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                label: Some(entry_point),
+                layout: Some(&pipeline_layout),
+                module: &module,
+                entry_point: Some(entry_point),
+                compilation_options: Default::default(),
+                cache: None,
+            })
+        };
+
+        Ok(Self {
+            composite: pipeline("composite"),
+            zoom: pipeline("zoom"),
+            radial_blur: pipeline("radial_blur"),
+            directional_blur: pipeline("directional_blur"),
+            max_image_size: limits
+                .max_storage_buffer_binding_size
+                .min(limits.max_buffer_size),
+            name: adapter.get_info().name,
+            device,
+            queue,
+            layout,
+        })
+    }
+
+    /// The GPU's name, for messages
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Draw `layer` over `canvas` with its top-left at (x, y), like
+    /// `image::imageops::overlay`
+    pub fn composite(
+        &self,
+        canvas: &mut RgbaImage,
+        layer: &RgbaImage,
+        x: i64,
+        y: i64,
+    ) -> Result<()> {
+        let params = Params {
+            offset: [x as i32, y as i32],
+            ..Default::default()
+        };
+        let (width, height) = layer.dimensions();
+        *canvas = self.run(
+            &self.composite,
+            params,
+            layer,
+            Target::Onto(canvas),
+            (width, height),
+        )?;
+        Ok(())
+    }
+
+    /// The `view` (left, top, width, height) of `img` scaled up to fill it,
+    /// as `effects::apply_zoom` does with `effects::zoom_view`
+    pub fn zoom(&self, img: &RgbaImage, view: (f64, f64, f64, f64)) -> Result<RgbaImage> {
+        let (left, top, width, height) = view;
+        let params = Params {
+            values: [left as f32, top as f32, width as f32, height as f32],
+            ..Default::default()
+        };
+        self.run(&self.zoom, params, img, Target::Sized, img.dimensions())
+    }
+
+    /// `img` with a motion blur pass, as `motion_blur::apply_blur` does
+    pub fn blur(&self, img: &RgbaImage, blur: &Blur) -> Result<RgbaImage> {
+        let (pipeline, samples, values) = match *blur {
+            Blur::Radial {
+                center_x,
+                center_y,
+                amount,
+                max_dist,
+                samples,
+            } => (
+                &self.radial_blur,
+                samples,
+                [center_x, center_y, amount, max_dist],
+            ),
+            Blur::Directional {
+                dir_x,
+                dir_y,
+                amount,
+                samples,
+            } => (&self.directional_blur, samples, [dir_x, dir_y, amount, 0.0]),
+        };
+        let params = Params {
+            samples,
+            values: values.map(|v| v as f32),
+            ..Default::default()
+        };
+        self.run(pipeline, params, img, Target::Sized, img.dimensions())
+    }
+
+    /// Run one shader over a grid of `size` with `src` as its input, and
+    /// read back what it wrote
+    fn run(
+        &self,
+        pipeline: &wgpu::ComputePipeline,
+        mut params: Params,
+        src: &RgbaImage,
+        target: Target,
+        size: (u32, u32),
+    ) -> Result<RgbaImage> {
+        use wgpu::util::DeviceExt;
+
+        let (dst_width, dst_height) = match target {
+            Target::Onto(canvas) => canvas.dimensions(),
+            Target::Sized => src.dimensions(),
+        };
+        let dst_bytes = dst_width as u64 * dst_height as u64 * 4;
+        let src_bytes = src.as_raw().len() as u64;
+        if src_bytes == 0 || dst_bytes == 0 {
+            return Ok(match target {
+                Target::Onto(canvas) => canvas.clone(),
+                Target::Sized => src.clone(),
+            });
+        }
+        if src_bytes.max(dst_bytes) > self.max_image_size {
+            anyhow::bail!(
+                "{}x{} is too large for the GPU",
+                dst_width.max(src.width()),
+                dst_height.max(src.height())
+            );
+        }
+        params.src_size = [src.width(), src.height()];
+        params.dst_size = [dst_width, dst_height];
+
+        let src_buffer = self
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("src"),
+                contents: src.as_raw(),
+                usage: wgpu::BufferUsages::STORAGE,
+            });
+        let dst_usage = wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC;
+        let dst_buffer = match target {
+            Target::Onto(canvas) => {
+                self.device
+                    .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                        label: Some("dst"),
+                        contents: canvas.as_raw(),
+                        usage: dst_usage,
+                    })
+            }
+            Target::Sized => self.device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("dst"),
+                size: dst_bytes,
+                usage: dst_usage,
+                mapped_at_creation: false,
+            }),
+        };
+        let params_buffer = self
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("params"),
+                contents: bytemuck::bytes_of(&params),
+                usage: wgpu::BufferUsages::UNIFORM,
+            });
+        let readback = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("readback"),
+            size: dst_bytes,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: None,
+            layout: &self.layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: src_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: dst_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: params_buffer.as_entire_binding(),
+                },
+            ],
+        });
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+        {
+            let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor::default());
+            pass.set_pipeline(pipeline);
+            pass.set_bind_group(0, &bind_group, &[]);
+            pass.dispatch_workgroups(
+                size.0.div_ceil(WORKGROUP_SIZE),
+                size.1.div_ceil(WORKGROUP_SIZE),
+                1,
+            );
+        }
+        encoder.copy_buffer_to_buffer(&dst_buffer, 0, &readback, 0, dst_bytes);
+        self.queue.submit([encoder.finish()]);
+
+        let slice = readback.slice(..);
+        let (tx, rx) = std::sync::mpsc::channel();
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = tx.send(result);
+        });
+        self.device
+            .poll(wgpu::PollType::wait_indefinitely())
+            .map_err(|e| anyhow::anyhow!("GPU failed: {}", e))?;
+        rx.recv()?
+            .map_err(|e| anyhow::anyhow!("Failed to read back from the GPU: {}", e))?;
+        let pixels = slice
+            .get_mapped_range()
+            .map_err(|e| anyhow::anyhow!("Failed to read back from the GPU: {}", e))?
+            .to_vec();
+        readback.unmap();
+        RgbaImage::from_raw(dst_width, dst_height, pixels)
+            .ok_or_else(|| anyhow::anyhow!("GPU returned a short image"))
+    }
+}
+
+/// Where a pass writes
+#[cfg(feature = "gpu")]
+enum Target<'a> {
+    /// Over a copy of this image
+    Onto(&'a RgbaImage),
+    /// A new image the size of the input
+    Sized,
+}
+
+/// Stands in for the GPU renderer in builds without the `gpu` feature; it
+/// can't be created, so the CPU path is always taken
+#[cfg(not(feature = "gpu"))]
+pub enum GpuRenderer {}
+
+#[cfg(not(feature = "gpu"))]
+impl GpuRenderer {
+    pub fn new() -> Result<Self> {
+        anyhow::bail!("glide was built without GPU support")
+    }
+
+    pub fn name(&self) -> &str {
+        match *self {}
+    }
+
+    pub fn composite(&self, _: &mut RgbaImage, _: &RgbaImage, _: i64, _: i64) -> Result<()> {
+        match *self {}
+    }
+
+    pub fn zoom(&self, _: &RgbaImage, _: (f64, f64, f64, f64)) -> Result<RgbaImage> {
+        match *self {}
+    }
+
+    pub fn blur(&self, _: &RgbaImage, _: &Blur) -> Result<RgbaImage> {
+        match *self {}
+    }
+}
+
+#[cfg(all(test, feature = "gpu"))]
+mod tests {
+    use super::*;
+    use crate::processing::effects::{apply_zoom, zoom_view};
+    use crate::processing::motion_blur::apply_blur;
+    use image::{DynamicImage, Rgba};
+
+    fn test_image(width: u32, height: u32) -> RgbaImage {
+        RgbaImage::from_fn(width, height, |x, y| {
+            Rgba([(x * 255 / width) as u8, (y * 255 / height) as u8, 128, 255])
+        })
+    }
+
+    /// Largest difference in any channel of any pixel
+    fn max_difference(a: &RgbaImage, b: &RgbaImage) -> u8 {
+        assert_eq!(a.dimensions(), b.dimensions());
+        a.as_raw()
+            .iter()
+            .zip(b.as_raw())
+            .map(|(a, b)| a.abs_diff(*b))
+            .max()
+            .unwrap_or(0)
+    }
+
+    #[test]
+    fn test_gpu_matches_cpu() {
+        // Machines without a GPU (or software driver) have nothing to test
+        let Ok(gpu) = GpuRenderer::new() else {
+            return;
+        };
+        let img = test_image(100, 80);
+
+        for blur in [
+            Blur::Radial {
+                center_x: 40.0,
+                center_y: 30.0,
+                amount: -20.0,
+                max_dist: 50.0,
+                samples: 8,
+            },
+            Blur::Directional {
+                dir_x: 0.6,
+                dir_y: 0.8,
+                amount: 12.0,
+                samples: 6,
+            },
+        ] {
+            let cpu = apply_blur(&img, &blur);
+            assert!(max_difference(&gpu.blur(&img, &blur).unwrap(), &cpu) <= 2);
+        }
+
+        // Bilinear rather than Lanczos, so only close on a smooth gradient
+        let view = zoom_view(100.0, 80.0, 2.0, 50.0, 40.0, None);
+        let cpu = apply_zoom(
+            &DynamicImage::ImageRgba8(img.clone()),
+            2.0,
+            50.0,
+            40.0,
+            None,
+        );
+        assert!(max_difference(&gpu.zoom(&img, view).unwrap(), &cpu.to_rgba8()) <= 8);
+
+        let mut canvas = RgbaImage::from_pixel(100, 80, Rgba([0, 0, 255, 255]));
+        let mut expected = canvas.clone();
+        let layer = RgbaImage::from_pixel(30, 30, Rgba([255, 0, 0, 128]));
+        image::imageops::overlay(&mut expected, &layer, 80, -10);
+        gpu.composite(&mut canvas, &layer, 80, -10).unwrap();
+        assert!(max_difference(&canvas, &expected) <= 1);
+    }
+}
@@ -0,0 +1,119 @@
+// Compute shaders for `--gpu`. Images are storage buffers of RGBA8 pixels
+// packed one to a u32, and the math follows the CPU versions in effects.rs
+// and motion_blur.rs, down to where they round.
+
+struct Params {
+    src_size: vec2<u32>,
+    dst_size: vec2<u32>,
+    // composite: where the top-left of `src` goes on `dst`
+    offset: vec2<i32>,
+    // blurs: points averaged per pixel
+    samples: u32,
+    _pad: u32,
+    // zoom: view left, top, width, height
+    // radial_blur: center x, center y, signed amount, max distance
+    // directional_blur: direction x, y, amount
+    values: vec4<f32>,
+}
+
+@group(0) @binding(0) var<storage, read> src: array<u32>;
+@group(0) @binding(1) var<storage, read_write> dst: array<u32>;
+@group(0) @binding(2) var<uniform> params: Params;
+
+fn load(x: u32, y: u32) -> vec4<f32> {
+    return unpack4x8unorm(src[y * params.src_size.x + x]) * 255.0;
+}
+
+// Truncates like `as u8`
+fn store(color: vec4<f32>) -> u32 {
+    let c = vec4<u32>(clamp(floor(color), vec4<f32>(0.0), vec4<f32>(255.0)));
+    return c.x | (c.y << 8u) | (c.z << 16u) | (c.w << 24u);
+}
+
+fn bilinear(p: vec2<f32>) -> vec4<f32> {
+    let x0 = u32(floor(p.x));
+    let y0 = u32(floor(p.y));
+    let x1 = min(x0 + 1u, params.src_size.x - 1u);
+    let y1 = min(y0 + 1u, params.src_size.y - 1u);
+    let fx = p.x - f32(x0);
+    let fy = p.y - f32(y0);
+    let top = floor(mix(load(x0, y0), load(x1, y0), fx));
+    let bottom = floor(mix(load(x0, y1), load(x1, y1), fx));
+    return floor(mix(top, bottom, fy));
+}
+
+fn last_pixel() -> vec2<f32> {
+    return vec2<f32>(params.src_size - vec2<u32>(1u));
+}
+
+// Average of `samples` points from `pos` to `reach` pixels along `dir`,
+// weighted toward the start
+fn smear(pos: vec2<u32>, dir: vec2<f32>, reach: f32) -> u32 {
+    var sum = vec4<f32>(0.0);
+    var weights = 0.0;
+    for (var i = 0u; i < params.samples; i++) {
+        let t = f32(i) / f32(params.samples - 1u);
+        let p = clamp(vec2<f32>(pos) + dir * t * reach, vec2<f32>(0.0), last_pixel());
+        let weight = 1.0 - t * 0.7;
+        sum += bilinear(p) * weight;
+        weights += weight;
+    }
+    return store(sum / weights);
+}
+
+// `src` drawn over `dst` at `offset`, like image::imageops::overlay
+@compute @workgroup_size(16, 16)
+fn composite(@builtin(global_invocation_id) id: vec3<u32>) {
+    if id.x >= params.src_size.x || id.y >= params.src_size.y {
+        return;
+    }
+    let pos = vec2<i32>(id.xy) + params.offset;
+    if any(pos < vec2<i32>(0)) || any(pos >= vec2<i32>(params.dst_size)) {
+        return;
+    }
+    let i = u32(pos.y) * params.dst_size.x + u32(pos.x);
+    let top = unpack4x8unorm(src[id.y * params.src_size.x + id.x]);
+    let bottom = unpack4x8unorm(dst[i]);
+    let alpha = top.a + bottom.a * (1.0 - top.a);
+    if alpha == 0.0 {
+        dst[i] = 0u;
+        return;
+    }
+    let rgb = (top.rgb * top.a + bottom.rgb * bottom.a * (1.0 - top.a)) / alpha;
+    dst[i] = pack4x8unorm(vec4<f32>(rgb, alpha));
+}
+
+// The view rectangle of `src` stretched over `dst`
+@compute @workgroup_size(16, 16)
+fn zoom(@builtin(global_invocation_id) id: vec3<u32>) {
+    if id.x >= params.dst_size.x || id.y >= params.dst_size.y {
+        return;
+    }
+    let scale = params.values.zw / vec2<f32>(params.dst_size);
+    let p = params.values.xy + (vec2<f32>(id.xy) + 0.5) * scale - 0.5;
+    dst[id.y * params.dst_size.x + id.x] = store(bilinear(clamp(p, vec2<f32>(0.0), last_pixel())));
+}
+
+@compute @workgroup_size(16, 16)
+fn radial_blur(@builtin(global_invocation_id) id: vec3<u32>) {
+    if id.x >= params.dst_size.x || id.y >= params.dst_size.y {
+        return;
+    }
+    let i = id.y * params.dst_size.x + id.x;
+    let d = vec2<f32>(id.xy) - params.values.xy;
+    let dist = max(length(d), 1.0);
+    let blur = params.values.z * min(dist / params.values.w, 1.0);
+    if abs(blur) < 0.5 {
+        dst[i] = src[i];
+        return;
+    }
+    dst[i] = smear(id.xy, d / dist, blur);
+}
+
+@compute @workgroup_size(16, 16)
+fn directional_blur(@builtin(global_invocation_id) id: vec3<u32>) {
+    if id.x >= params.dst_size.x || id.y >= params.dst_size.y {
+        return;
+    }
+    dst[id.y * params.dst_size.x + id.x] = smear(id.xy, params.values.xy, -params.values.z);
+}
@@ -0,0 +1,382 @@
+//! Cursor dwell heatmaps
+//!
+//! Cursor positions are accumulated on a coarse grid weighted by how long the
+//! cursor rested there, blurred, and color-mapped into a translucent layer.
+
+use crate::cursor_types::CursorEvent;
+use crate::recording::metadata::RecordingMetadata;
+use image::imageops::{self, FilterType};
+use image::{Rgba, RgbaImage};
+
+/// Size of a heatmap cell in video pixels
+const CELL_SIZE: u32 = 8;
+/// Blur radius in cells (applied as three box blurs, roughly Gaussian)
+const BLUR_RADIUS: usize = 3;
+/// Longest time a single resting position counts for (seconds), so one long
+/// pause doesn't wash out the rest of the map
+const MAX_DWELL: f64 = 3.0;
+/// Normalized intensity below which the layer is fully transparent
+const MIN_VISIBLE: f32 = 0.03;
+
+/// Opacity of the progressive heatmap drawn by `--overlay heatmap`
+pub const OVERLAY_OPACITY: f32 = 0.55;
+/// Events between the running totals a [`ProgressiveHeatmap`] keeps
+const CHECKPOINT_EVENTS: usize = 256;
+
+/// Color stops from cold to hot
+const COLOR_STOPS: [(f32, [u8; 3]); 5] = [
+    (0.0, [0, 0, 255]),
+    (0.25, [0, 200, 255]),
+    (0.5, [0, 220, 0]),
+    (0.75, [255, 220, 0]),
+    (1.0, [255, 0, 0]),
+];
+
+/// Data layer composited over the recording content
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum Overlay {
+    /// Cursor heatmap that builds up over the course of the video
+    #[default]
+    Heatmap,
+}
+
+/// Cursor dwell time accumulated over a video-sized grid
+pub struct Heatmap {
+    columns: usize,
+    rows: usize,
+    cells: Vec<f32>,
+}
+
+impl Heatmap {
+    /// Empty heatmap covering a video of the given size (pixels)
+    pub fn new(video_width: u32, video_height: u32) -> Self {
+        let columns = video_width.div_ceil(CELL_SIZE).max(1) as usize;
+        let rows = video_height.div_ceil(CELL_SIZE).max(1) as usize;
+        Self {
+            columns,
+            rows,
+            cells: vec![0.0; columns * rows],
+        }
+    }
+
+    /// Heatmap of a recording's cursor dwell up to `until` (tracking
+    /// timestamps), over a video of the given size
+    pub fn from_recording(
+        metadata: &RecordingMetadata,
+        width: u32,
+        height: u32,
+        until: f64,
+    ) -> Self {
+        let mut heatmap = Self::new(width, height);
+        heatmap.accumulate(
+            &metadata.cursor_events,
+            until,
+            to_video(metadata, width, height),
+        );
+        heatmap
+    }
+
+    /// Accumulate cursor dwell from `events` up to `until` (tracking timestamps).
+    ///
+    /// `to_video` maps screen points at a tracking timestamp to video pixels.
+    pub fn accumulate(
+        &mut self,
+        events: &[CursorEvent],
+        until: f64,
+        to_video: impl Fn(f64, f64, f64) -> (f64, f64),
+    ) {
+        for (i, event) in events.iter().enumerate() {
+            if event.timestamp >= until {
+                break;
+            }
+            let next = events.get(i + 1).map_or(until, |e| e.timestamp).min(until);
+            let (x, y) = to_video(event.x, event.y, event.timestamp);
+            self.add(x, y, dwell_weight(next - event.timestamp));
+        }
+    }
+
+    /// Add `weight` at a video pixel position (ignored outside the video)
+    pub fn add(&mut self, x: f64, y: f64, weight: f32) {
+        if let Some(cell) = self.cell(x, y) {
+            self.cells[cell] += weight;
+        }
+    }
+
+    /// Index of the cell at a video pixel position, if it's inside the video
+    fn cell(&self, x: f64, y: f64) -> Option<usize> {
+        if x < 0.0 || y < 0.0 {
+            return None;
+        }
+        let column = (x / CELL_SIZE as f64) as usize;
+        let row = (y / CELL_SIZE as f64) as usize;
+        (column < self.columns && row < self.rows).then_some(row * self.columns + column)
+    }
+
+    /// Whether nothing has been accumulated yet
+    pub fn is_empty(&self) -> bool {
+        self.cells.iter().all(|&v| v <= 0.0)
+    }
+
+    /// Render the heatmap as a translucent color layer of the given size
+    pub fn render(&self, width: u32, height: u32, opacity: f32) -> RgbaImage {
+        let mut values = self.cells.clone();
+        for _ in 0..3 {
+            box_blur(&mut values, self.columns, self.rows, BLUR_RADIUS);
+        }
+
+        let max = values.iter().cloned().fold(0.0f32, f32::max);
+        let mut grid = RgbaImage::new(self.columns as u32, self.rows as u32);
+        if max > 0.0 {
+            for (i, value) in values.iter().enumerate() {
+                let x = (i % self.columns) as u32;
+                let y = (i / self.columns) as u32;
+                grid.put_pixel(x, y, heat_color(value / max, opacity));
+            }
+        }
+
+        imageops::resize(&grid, width, height, FilterType::Triangle)
+    }
+}
+
+/// Maps screen points at a tracking timestamp to pixels in a video of the
+/// given size. Cursor events are in screen points, and the video may be
+/// scaled from the recorded size.
+fn to_video(
+    metadata: &RecordingMetadata,
+    width: u32,
+    height: u32,
+) -> impl Fn(f64, f64, f64) -> (f64, f64) + '_ {
+    let scale_x = metadata.scale_factor.max(1.0) * width as f64 / metadata.width.max(1) as f64;
+    let scale_y = metadata.scale_factor.max(1.0) * height as f64 / metadata.height.max(1) as f64;
+    move |x, y, t| {
+        let (offset_x, offset_y) = metadata.window_offset_at(t);
+        ((x - offset_x) * scale_x, (y - offset_y) * scale_y)
+    }
+}
+
+/// A recording's heatmap as it builds up, for `--overlay heatmap`. The
+/// events are placed on the grid once, with running totals every few
+/// hundred events, so the heatmap at a frame only adds the events since
+/// the last total. Matches [`Heatmap::from_recording`] at every point.
+pub struct ProgressiveHeatmap {
+    columns: usize,
+    rows: usize,
+    /// Each event's tracking timestamp and cell (none outside the video)
+    events: Vec<(f64, Option<usize>)>,
+    /// Weight of each event once the next one has happened
+    weights: Vec<f32>,
+    /// Cells with the first `i * CHECKPOINT_EVENTS` events' weights added
+    checkpoints: Vec<Vec<f32>>,
+}
+
+impl ProgressiveHeatmap {
+    /// Place a recording's cursor events on a heatmap over a video of the
+    /// given size (pixels)
+    pub fn new(metadata: &RecordingMetadata, width: u32, height: u32) -> Self {
+        let mut heatmap = Heatmap::new(width, height);
+        let to_video = to_video(metadata, width, height);
+        let cursor_events = &metadata.cursor_events;
+        let events: Vec<_> = cursor_events
+            .iter()
+            .map(|e| {
+                let (x, y) = to_video(e.x, e.y, e.timestamp);
+                (e.timestamp, heatmap.cell(x, y))
+            })
+            .collect();
+        let weights: Vec<f32> = cursor_events
+            .iter()
+            .zip(cursor_events.iter().skip(1))
+            .map(|(event, next)| dwell_weight(next.timestamp - event.timestamp))
+            .collect();
+
+        let mut checkpoints = vec![heatmap.cells.clone()];
+        for (i, (&(_, cell), &weight)) in events.iter().zip(&weights).enumerate() {
+            if let Some(cell) = cell {
+                heatmap.cells[cell] += weight;
+            }
+            if (i + 1) % CHECKPOINT_EVENTS == 0 {
+                checkpoints.push(heatmap.cells.clone());
+            }
+        }
+        Self {
+            columns: heatmap.columns,
+            rows: heatmap.rows,
+            events,
+            weights,
+            checkpoints,
+        }
+    }
+
+    /// The heatmap of the cursor's dwell up to `until` (tracking timestamp)
+    pub fn at(&self, until: f64) -> Heatmap {
+        // Events are in time order, and the last one before `until` only
+        // counts up to it
+        let count = self.events.partition_point(|&(t, _)| t < until);
+        let Some(last) = count.checked_sub(1) else {
+            return Heatmap {
+                columns: self.columns,
+                rows: self.rows,
+                cells: self.checkpoints[0].clone(),
+            };
+        };
+        let checkpoint = (last / CHECKPOINT_EVENTS).min(self.checkpoints.len() - 1);
+        let mut cells = self.checkpoints[checkpoint].clone();
+        for i in checkpoint * CHECKPOINT_EVENTS..last {
+            if let Some(cell) = self.events[i].1 {
+                cells[cell] += self.weights[i];
+            }
+        }
+        let (timestamp, cell) = self.events[last];
+        if let Some(cell) = cell {
+            cells[cell] += dwell_weight(until - timestamp);
+        }
+        Heatmap {
+            columns: self.columns,
+            rows: self.rows,
+            cells,
+        }
+    }
+}
+
+/// Weight of a cursor position held for `dwell` seconds
+fn dwell_weight(dwell: f64) -> f32 {
+    // Every event counts a little, so fast sweeps still show up
+    (dwell.clamp(0.0, MAX_DWELL) + 0.01) as f32
+}
+
+/// Color for a normalized intensity, transparent when cold
+fn heat_color(value: f32, opacity: f32) -> Rgba<u8> {
+    let value = value.clamp(0.0, 1.0);
+    if value < MIN_VISIBLE {
+        return Rgba([0, 0, 0, 0]);
+    }
+
+    let upper = COLOR_STOPS
+        .iter()
+        .position(|&(stop, _)| stop >= value)
+        .unwrap_or(COLOR_STOPS.len() - 1)
+        .max(1);
+    let (lo, lo_color) = COLOR_STOPS[upper - 1];
+    let (hi, hi_color) = COLOR_STOPS[upper];
+    let t = (value - lo) / (hi - lo);
+    let channel =
+        |i: usize| (lo_color[i] as f32 + (hi_color[i] as f32 - lo_color[i] as f32) * t) as u8;
+
+    // Fade in over the low end so cold areas don't tint the whole frame
+    let alpha = (value * 2.0).min(1.0) * opacity.clamp(0.0, 1.0);
+    Rgba([channel(0), channel(1), channel(2), (alpha * 255.0) as u8])
+}
+
+/// In-place separable box blur over a row-major grid
+fn box_blur(values: &mut [f32], columns: usize, rows: usize, radius: usize) {
+    let mut scratch = vec![0.0f32; values.len()];
+
+    for row in 0..rows {
+        for column in 0..columns {
+            let start = column.saturating_sub(radius);
+            let end = (column + radius).min(columns - 1);
+            let sum: f32 = values[row * columns + start..=row * columns + end]
+                .iter()
+                .sum();
+            scratch[row * columns + column] = sum / (2 * radius + 1) as f32;
+        }
+    }
+
+    for column in 0..columns {
+        for row in 0..rows {
+            let start = row.saturating_sub(radius);
+            let end = (row + radius).min(rows - 1);
+            let sum: f32 = (start..=end).map(|r| scratch[r * columns + column]).sum();
+            values[row * columns + column] = sum / (2 * radius + 1) as f32;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cursor_types::EventType;
+
+    fn make_move(x: f64, y: f64, timestamp: f64) -> CursorEvent {
+        CursorEvent {
+            x,
+            y,
+            timestamp,
+            event_type: EventType::Move,
+            display: None,
+            shape: None,
+        }
+    }
+
+    #[test]
+    fn test_hottest_where_cursor_rests() {
+        let events = vec![make_move(20.0, 20.0, 0.0), make_move(300.0, 200.0, 0.1)];
+        let mut heatmap = Heatmap::new(400, 300);
+        heatmap.accumulate(&events, 2.0, |x, y, _| (x, y));
+
+        let layer = heatmap.render(400, 300, 1.0);
+        let rested = layer.get_pixel(300, 200);
+        let passed = layer.get_pixel(20, 20);
+        assert_eq!(rested[0], 255, "resting spot should be red: {:?}", rested);
+        assert!(rested[3] > passed[3]);
+        assert_eq!(layer.get_pixel(150, 290)[3], 0);
+    }
+
+    #[test]
+    fn test_progressive_matches_from_recording() {
+        let mut metadata = RecordingMetadata::new_display(0, 400, 300, 1.0);
+        // Enough events for several running totals, some resting longer
+        metadata.cursor_events = (0..1000)
+            .map(|i| {
+                let rest = if i % 7 == 0 { 0.01 } else { 0.0 };
+                make_move(
+                    (i % 400) as f64,
+                    (i / 4 % 300) as f64,
+                    i as f64 * 0.02 + rest,
+                )
+            })
+            .collect();
+        let progressive = ProgressiveHeatmap::new(&metadata, 400, 300);
+
+        for until in [-1.0, 0.0, 0.01, 5.13, 5.12, 10.0, 19.99, 25.0] {
+            let expected = Heatmap::from_recording(&metadata, 400, 300, until);
+            assert_eq!(progressive.at(until).cells, expected.cells, "at {}", until);
+        }
+        assert!(progressive.at(0.0).is_empty());
+    }
+
+    #[test]
+    fn test_intensity_builds_up() {
+        let mut metadata = RecordingMetadata::new_display(0, 400, 300, 1.0);
+        metadata.cursor_events = vec![
+            make_move(20.0, 20.0, 0.0),
+            make_move(300.0, 200.0, 0.1),
+            make_move(20.0, 20.0, 2.0),
+        ];
+        let progressive = ProgressiveHeatmap::new(&metadata, 400, 300);
+
+        // The resting spot gets hotter while the cursor stays there
+        let early = progressive.at(0.5);
+        let late = progressive.at(1.5);
+        let cell = early.cell(300.0, 200.0).unwrap();
+        assert!(late.cells[cell] > early.cells[cell]);
+        let layer = late.render(400, 300, 1.0);
+        assert_eq!(layer.get_pixel(300, 200)[0], 255);
+
+        // Then cools relative to the new one, with dwell capped at MAX_DWELL
+        let later = progressive.at(60.0);
+        let corner = later.cell(20.0, 20.0).unwrap();
+        assert!(later.cells[corner] > later.cells[cell]);
+        assert!(later.cells[corner] <= (MAX_DWELL + 0.02 + 0.1) as f32);
+    }
+
+    #[test]
+    fn test_accumulate_stops_at_until() {
+        let events = vec![make_move(20.0, 20.0, 0.0), make_move(300.0, 200.0, 1.0)];
+        let mut heatmap = Heatmap::new(400, 300);
+        heatmap.accumulate(&events, 0.5, |x, y, _| (x, y));
+        assert!(!heatmap.is_empty());
+        assert_eq!(heatmap.render(400, 300, 1.0).get_pixel(300, 200)[3], 0);
+    }
+}
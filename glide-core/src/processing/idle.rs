@@ -0,0 +1,217 @@
+//! Dead air: `--speed-up-idle` plays it faster, `--auto-trim` cuts it from
+//! the ends
+//!
+//! Activity is clicks, key presses and cursor moves beyond a jiggle; stretches
+//! without any for a while are played at a multiple of normal speed, less a
+//! short margin at each end so the speed-up doesn't cut into what's around it.
+
+use crate::cursor_types::{CursorEvent, EventType};
+use crate::processing::shapes::{draw_line, fill_rounded_rect};
+use crate::processing::text::{draw_text, line_height, load_font, measure_text};
+use crate::processing::timeline::Timeline;
+use ab_glyph::FontArc;
+use anyhow::Result;
+use image::{Rgba, RgbaImage};
+
+/// Screen points the cursor has to travel to count as activity, so jitter
+/// and a resting hand don't keep a stretch at normal speed
+const MOVE_THRESHOLD: f64 = 30.0;
+
+/// Seconds kept at normal speed next to activity
+const IDLE_MARGIN: f64 = 0.5;
+
+/// Seconds `--auto-trim` keeps before the first and after the last activity
+const TRIM_MARGIN: f64 = 1.0;
+
+/// Play stretches of at least `min_idle` seconds without activity at
+/// `speed`x. `events` are on the tracking clock, which is `time_offset`
+/// ahead of the video's; the video is `duration` seconds long.
+pub fn plan_speed_up(
+    events: &[CursorEvent],
+    time_offset: f64,
+    duration: f64,
+    min_idle: f64,
+    speed: f64,
+) -> Timeline {
+    let mut activity = activity_times(events, time_offset, duration);
+    activity.insert(0, 0.0);
+    activity.push(duration);
+
+    let stretches: Vec<_> = activity
+        .windows(2)
+        .filter(|gap| gap[1] - gap[0] >= min_idle.max(2.0 * IDLE_MARGIN))
+        .map(|gap| {
+            let (start, end) = (gap[0], gap[1]);
+            // The very start and end of the video need no lead-in
+            (
+                if start > 0.0 {
+                    start + IDLE_MARGIN
+                } else {
+                    0.0
+                },
+                if end < duration {
+                    end - IDLE_MARGIN
+                } else {
+                    duration
+                },
+            )
+        })
+        .collect();
+    Timeline::with_speed_ups(duration, &stretches, speed)
+}
+
+/// Seconds to trim from the start and end of a `duration`-second video so it
+/// runs from just before the first activity to just after the last. None
+/// without any activity.
+pub fn auto_trim(events: &[CursorEvent], time_offset: f64, duration: f64) -> Option<(f64, f64)> {
+    let activity = activity_times(events, time_offset, duration);
+    let (first, last) = (*activity.first()?, *activity.last()?);
+    Some((
+        (first - TRIM_MARGIN).max(0.0),
+        (duration - last - TRIM_MARGIN).max(0.0),
+    ))
+}
+
+/// Video times of activity, in order
+fn activity_times(events: &[CursorEvent], time_offset: f64, duration: f64) -> Vec<f64> {
+    let mut times = Vec::new();
+    let mut anchor: Option<(f64, f64)> = None;
+    for event in events {
+        let active = match event.event_type {
+            EventType::LeftClick | EventType::RightClick | EventType::KeyDown(_) => true,
+            EventType::Move => match anchor {
+                Some((x, y)) => (event.x - x).hypot(event.y - y) >= MOVE_THRESHOLD,
+                None => false,
+            },
+        };
+        if anchor.is_none() || active {
+            anchor = Some((event.x, event.y));
+        }
+        let time = event.timestamp - time_offset;
+        if active && (0.0..duration).contains(&time) {
+            times.push(time);
+        }
+    }
+    times
+}
+
+/// Parse a speed-up factor, more than 1
+pub fn parse_speed(s: &str) -> Result<f64, String> {
+    match s.parse::<f64>() {
+        Ok(speed) if speed > 1.0 && speed.is_finite() => Ok(speed),
+        _ => Err("expected a speed-up factor greater than 1".to_string()),
+    }
+}
+
+const BADGE_TEXT_SIZE: f32 = 30.0;
+const BADGE_MARGIN: f64 = 32.0;
+const BADGE_COLOR: Rgba<u8> = Rgba([0, 0, 0, 160]);
+const BADGE_TEXT_COLOR: Rgba<u8> = Rgba([255, 255, 255, 255]);
+
+/// The "fast-forward" badge shown in the top-right corner while sped up
+pub struct FastForwardBadge {
+    font: FontArc,
+}
+
+impl FastForwardBadge {
+    pub fn new() -> Result<Self> {
+        Ok(Self {
+            font: load_font(None)?,
+        })
+    }
+
+    /// Draw the badge for playback at `speed`x (nothing at normal speed)
+    pub fn draw(&self, canvas: &mut RgbaImage, speed: f64) {
+        if speed <= 1.0 {
+            return;
+        }
+        let size = BADGE_TEXT_SIZE;
+        let label = format!("{}x", speed);
+        let (text_w, _) = measure_text(&self.font, size, &label);
+        let text_h = line_height(&self.font, size) as f64;
+        let padding = size as f64 * 0.5;
+        let icon_w = text_h * 0.9;
+        let w = padding * 3.0 + icon_w + text_w as f64;
+        let h = text_h + padding * 2.0;
+        let x = canvas.width() as f64 - BADGE_MARGIN - w;
+        let y = BADGE_MARGIN;
+        fill_rounded_rect(canvas, x, y, w, h, h / 2.0, BADGE_COLOR);
+
+        // Two chevrons: >>
+        let (mid, reach) = (y + h / 2.0, text_h * 0.3);
+        let stroke = (size as f64 * 0.12).max(2.0);
+        for i in 0..2 {
+            let left = x + padding + i as f64 * icon_w * 0.45;
+            let tip = (left + icon_w * 0.45, mid);
+            for end in [mid - reach, mid + reach] {
+                draw_line(canvas, (left, end), tip, stroke, BADGE_TEXT_COLOR);
+            }
+        }
+        let text_x = x + padding * 2.0 + icon_w;
+        draw_text(
+            canvas,
+            &self.font,
+            size,
+            text_x,
+            y + padding,
+            &label,
+            BADGE_TEXT_COLOR,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(timestamp: f64, x: f64, event_type: EventType) -> CursorEvent {
+        CursorEvent {
+            x,
+            y: 100.0,
+            timestamp,
+            event_type,
+            display: None,
+            shape: None,
+        }
+    }
+
+    #[test]
+    fn test_idle_stretches_speed_up() {
+        let events = vec![
+            event(0.0, 100.0, EventType::Move),
+            event(1.0, 100.0, EventType::LeftClick),
+            // Jitter isn't activity
+            event(3.0, 105.0, EventType::Move),
+            event(11.0, 400.0, EventType::Move),
+            event(12.0, 400.0, EventType::LeftClick),
+        ];
+        let timeline = plan_speed_up(&events, 0.0, 14.0, 3.0, 4.0);
+        assert_eq!(timeline.sped_up(), 1);
+        // 1.5..10.5 plays at 4x: 9s of video in 2.25s
+        assert!((timeline.output_duration() - (14.0 - 9.0 + 2.25)).abs() < 1e-9);
+        assert_eq!(timeline.source_time(1.0), 1.0);
+        assert_eq!(timeline.speed_at(1.0), 1.0);
+        assert_eq!(timeline.speed_at(2.0), 4.0);
+        assert!((timeline.source_time(2.0) - 3.5).abs() < 1e-9);
+        assert!((timeline.source_time(5.25) - 12.0).abs() < 1e-9);
+        assert!((timeline.output_time(12.0).unwrap() - 5.25).abs() < 1e-9);
+
+        // Time on the tracking clock is re-timed, keeping the offset
+        let retimed = timeline.retime(&events, 0.0);
+        assert!((retimed[4].timestamp - 5.25).abs() < 1e-9);
+
+        // Busy recordings are untouched
+        let timeline = plan_speed_up(&events, 0.0, 14.0, 30.0, 4.0);
+        assert_eq!(timeline.sped_up(), 0);
+        assert_eq!(timeline.output_duration(), 14.0);
+        assert_eq!(timeline.source_time(7.0), 7.0);
+
+        // Trimmed to a second either side of the first click and last click
+        assert_eq!(auto_trim(&events, 0.0, 14.0), Some((0.0, 1.0)));
+        assert_eq!(auto_trim(&events, -2.0, 20.0), Some((2.0, 5.0)));
+        assert_eq!(auto_trim(&events[..1], 0.0, 14.0), None);
+
+        assert_eq!(parse_speed("2.5"), Ok(2.5));
+        assert!(parse_speed("1").is_err());
+    }
+}
@@ -0,0 +1,205 @@
+//! `glide inspect`: what a recording contains, as a sanity check before
+//! processing and for bug reports
+
+use crate::cursor_types::{CursorEvent, EventType};
+use crate::processing::frames::{get_video_duration, get_video_fps, get_video_size};
+use crate::processing::zoom::{effective_clicks, ZoomConfig};
+use crate::recording::metadata::{RecordingMetadata, SourceType};
+use anyhow::{Context, Result};
+use std::path::Path;
+
+/// Cursor events in a recording, by type
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EventCounts {
+    pub moves: usize,
+    pub left_clicks: usize,
+    pub right_clicks: usize,
+    pub key_presses: usize,
+}
+
+impl EventCounts {
+    pub fn from_events(events: &[CursorEvent]) -> Self {
+        let mut counts = Self::default();
+        for event in events {
+            match event.event_type {
+                EventType::Move => counts.moves += 1,
+                EventType::LeftClick => counts.left_clicks += 1,
+                EventType::RightClick => counts.right_clicks += 1,
+                EventType::KeyDown(_) => counts.key_presses += 1,
+            }
+        }
+        counts
+    }
+
+    pub fn total(&self) -> usize {
+        self.moves + self.left_clicks + self.right_clicks + self.key_presses
+    }
+
+    pub fn clicks(&self) -> usize {
+        self.left_clicks + self.right_clicks
+    }
+}
+
+/// Shortcuts pressed (keys held with Ctrl, Alt or Meta), labelled for the
+/// recording's keyboard, each once in the order first pressed
+fn shortcuts(metadata: &RecordingMetadata) -> Vec<String> {
+    let keyboard = metadata.keyboard.unwrap_or_default();
+    let mut shortcuts: Vec<String> = Vec::new();
+    for event in &metadata.cursor_events {
+        let EventType::KeyDown(press) = &event.event_type else {
+            continue;
+        };
+        let (Some(code), modifiers) = (&press.code, press.modifiers) else {
+            continue;
+        };
+        if !(modifiers.ctrl || modifiers.alt || modifiers.meta) {
+            continue;
+        }
+        let label = keyboard.format_keystroke(modifiers, code);
+        if !shortcuts.contains(&label) {
+            shortcuts.push(label);
+        }
+    }
+    shortcuts
+}
+
+/// What was recorded, e.g. "display 0" or "window 4711 at (120, 80)"
+fn describe_source(metadata: &RecordingMetadata) -> String {
+    let (x, y) = metadata.window_offset;
+    match metadata.source_type {
+        SourceType::Display => format!("display {}", metadata.source_index),
+        SourceType::Window => format!("window {} at ({x}, {y})", metadata.source_index),
+        SourceType::Region => format!("region of display {} at ({x}, {y})", metadata.source_index),
+    }
+}
+
+/// What a recording holds: the video's properties as probed, and its
+/// metadata with the cursor events summed up
+pub struct Inspection {
+    pub metadata: RecordingMetadata,
+    /// Probe failures are kept rather than fatal: a broken video is
+    /// exactly when inspecting is wanted
+    pub video_duration: Result<f64>,
+    pub video_fps: Result<f64>,
+    pub video_size: Result<(u32, u32)>,
+    /// What was recorded, e.g. "display 0" or "window 4711 at (120, 80)"
+    pub source: String,
+    pub counts: EventCounts,
+    /// Clicks left after debouncing, which start zooms
+    pub effective_clicks: usize,
+    /// Shortcuts pressed, labelled for the recording's keyboard
+    pub shortcuts: Vec<String>,
+}
+
+impl Inspection {
+    /// Probe `input` and read its metadata; `zoom_config` debounces clicks
+    /// as `glide process` would
+    pub fn new(input: &Path, zoom_config: &ZoomConfig) -> Result<Self> {
+        let metadata = RecordingMetadata::load(input)
+            .context("Failed to load recording metadata. Was this video recorded with glide?")?;
+        Ok(Self {
+            video_duration: get_video_duration(input),
+            video_fps: get_video_fps(input),
+            video_size: get_video_size(input),
+            source: describe_source(&metadata),
+            counts: EventCounts::from_events(&metadata.cursor_events),
+            effective_clicks: effective_clicks(&metadata.cursor_events, zoom_config).len(),
+            shortcuts: shortcuts(&metadata),
+            metadata,
+        })
+    }
+
+    /// Seconds cursor tracking ran before the video started, the offset
+    /// `glide process` applies to line events up with frames
+    pub fn offset(&self) -> Option<f64> {
+        let duration = self.video_duration.as_ref().ok()?;
+        (self.metadata.cursor_tracking_duration > 0.0)
+            .then(|| self.metadata.cursor_tracking_duration - duration)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cursor_types::KeyPress;
+    use crate::keyboard::{KeyboardInfo, KeyboardLayout, ModifierStyle, Modifiers};
+
+    fn event(timestamp: f64, event_type: EventType) -> CursorEvent {
+        CursorEvent {
+            x: 0.0,
+            y: 0.0,
+            timestamp,
+            event_type,
+            display: None,
+            shape: None,
+        }
+    }
+
+    #[test]
+    fn test_event_counts() {
+        let events = [
+            event(0.0, EventType::Move),
+            event(0.1, EventType::Move),
+            event(0.2, EventType::LeftClick),
+            event(0.3, EventType::RightClick),
+            event(0.4, EventType::LeftClick),
+            event(0.5, EventType::KeyDown(KeyPress::default())),
+        ];
+        let counts = EventCounts::from_events(&events);
+        assert_eq!(counts.moves, 2);
+        assert_eq!(counts.left_clicks, 2);
+        assert_eq!(counts.right_clicks, 1);
+        assert_eq!(counts.key_presses, 1);
+        assert_eq!(counts.clicks(), 3);
+        assert_eq!(counts.total(), 6);
+    }
+
+    #[test]
+    fn test_shortcuts() {
+        let key = |timestamp, code: &str, modifiers| {
+            event(
+                timestamp,
+                EventType::KeyDown(KeyPress {
+                    code: Some(code.to_string()),
+                    modifiers,
+                }),
+            )
+        };
+        let meta = Modifiers {
+            meta: true,
+            ..Modifiers::default()
+        };
+        let meta_shift = Modifiers {
+            shift: true,
+            ..meta
+        };
+        let mut metadata = RecordingMetadata::new_display(0, 1920, 1080, 2.0);
+        metadata.cursor_events = vec![
+            key(0.0, "KeyA", Modifiers::default()),
+            key(0.5, "KeyS", meta),
+            // Z is where a QWERTY keyboard's Y is
+            key(1.0, "KeyY", meta_shift),
+            key(1.5, "KeyS", meta),
+            event(2.0, EventType::KeyDown(KeyPress::default())),
+        ];
+        metadata.keyboard = Some(KeyboardInfo {
+            layout: KeyboardLayout::Qwertz,
+            modifier_style: ModifierStyle::Apple,
+        });
+        assert_eq!(shortcuts(&metadata), vec!["⌘S", "⇧⌘Z"]);
+
+        // Without the keyboard, QWERTY and PC-style labels
+        metadata.keyboard = None;
+        assert_eq!(shortcuts(&metadata), vec!["Super+S", "Shift+Super+Y"]);
+    }
+
+    #[test]
+    fn test_describe_source() {
+        let display = RecordingMetadata::new_display(1, 1920, 1080, 2.0);
+        assert_eq!(describe_source(&display), "display 1");
+        let window = RecordingMetadata::new_window(4711, 800, 600, 120, 80, 1.0);
+        assert_eq!(describe_source(&window), "window 4711 at (120, 80)");
+        let region = RecordingMetadata::new_region(0, 640, 480, 10, 20, 1.0);
+        assert_eq!(describe_source(&region), "region of display 0 at (10, 20)");
+    }
+}
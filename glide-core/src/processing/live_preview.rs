@@ -0,0 +1,130 @@
+//! `--preview`: frames shown in a player window while they're rendered
+//!
+//! Frames are piped as raw video to `ffplay`, or `mpv` if there's no
+//! ffplay. The player gets them from its own thread and frames it can't
+//! keep up with are skipped, so the render never waits for the window.
+
+use crate::processing::progress::{status, Reporter};
+use anyhow::Result;
+use image::RgbaImage;
+use std::io::{ErrorKind, Write};
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{self, SyncSender};
+use std::thread::JoinHandle;
+
+/// Frames waiting for the player before new ones are skipped
+const PREVIEW_QUEUE: usize = 8;
+
+/// Players tried in order
+const PLAYERS: [&str; 2] = ["ffplay", "mpv"];
+
+/// A player window showing frames as they're rendered
+pub struct LivePreview {
+    frames: SyncSender<RgbaImage>,
+    player: JoinHandle<()>,
+}
+
+/// Arguments for `player` to read `width` x `height` RGBA frames at `fps`
+/// from stdin
+fn player_args(player: &str, width: u32, height: u32, fps: f64) -> Vec<String> {
+    let title = "glide preview".to_string();
+    match player {
+        "ffplay" => vec![
+            "-v".into(),
+            "error".into(),
+            "-window_title".into(),
+            title,
+            "-autoexit".into(),
+            "-f".into(),
+            "rawvideo".into(),
+            "-pixel_format".into(),
+            "rgba".into(),
+            "-video_size".into(),
+            format!("{}x{}", width, height),
+            "-framerate".into(),
+            fps.to_string(),
+            "-i".into(),
+            "-".into(),
+        ],
+        _ => vec![
+            "--really-quiet".into(),
+            format!("--title={}", title),
+            "--demuxer=rawvideo".into(),
+            "--demuxer-rawvideo-mp-format=rgba".into(),
+            format!("--demuxer-rawvideo-w={}", width),
+            format!("--demuxer-rawvideo-h={}", height),
+            format!("--demuxer-rawvideo-fps={}", fps),
+            "-".into(),
+        ],
+    }
+}
+
+impl LivePreview {
+    /// Open a player for `width` x `height` frames at `fps`
+    pub fn open(width: u32, height: u32, fps: f64, reporter: &Reporter) -> Result<Self> {
+        let mut child = None;
+        for player in PLAYERS {
+            match Command::new(player)
+                .args(player_args(player, width, height, fps))
+                .stdin(Stdio::piped())
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .spawn()
+            {
+                Ok(spawned) => {
+                    status!(reporter, "Previewing frames in {}", player);
+                    child = Some(spawned);
+                    break;
+                }
+                Err(e) if e.kind() == ErrorKind::NotFound => continue,
+                Err(e) => return Err(anyhow::anyhow!("Failed to run {}: {}", player, e)),
+            }
+        }
+        let Some(mut child) = child else {
+            anyhow::bail!("--preview needs ffplay (part of FFmpeg) or mpv");
+        };
+        let mut stdin = child.stdin.take().expect("stdin is piped");
+
+        let (frames, queue) = mpsc::sync_channel::<RgbaImage>(PREVIEW_QUEUE);
+        let player = std::thread::spawn(move || {
+            for frame in queue {
+                // The window was closed; keep rendering without it
+                if stdin.write_all(frame.as_raw()).is_err() {
+                    break;
+                }
+            }
+            // Closing stdin ends the video, and the player with it
+            drop(stdin);
+            let _ = child.wait();
+        });
+        Ok(Self { frames, player })
+    }
+
+    /// Show `frame`, unless the player is still busy with earlier ones
+    pub fn show(&self, frame: &RgbaImage) {
+        let _ = self.frames.try_send(frame.clone());
+    }
+
+    /// Let the player finish what it was sent and exit
+    pub fn close(self) {
+        drop(self.frames);
+        let _ = self.player.join();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_player_args() {
+        let args = player_args("ffplay", 1920, 1080, 60.0);
+        let size = args.iter().position(|a| a == "-video_size").unwrap();
+        assert_eq!(args[size + 1], "1920x1080");
+        assert_eq!(args.last().unwrap(), "-");
+
+        let args = player_args("mpv", 1080, 1920, 60.0);
+        assert!(args.contains(&"--demuxer-rawvideo-w=1080".to_string()));
+        assert!(args.contains(&"--demuxer-rawvideo-fps=60".to_string()));
+    }
+}
@@ -0,0 +1,145 @@
+//! Using the markers dropped while recording (`glide mark`, `--mark-hotkey`)
+//!
+//! `--marker-chapters` starts a chapter at each one, `--cut-between-markers`
+//! removes what's between each pair, and `--zoom-on-markers` zooms in at
+//! each one as if the cursor had clicked there.
+
+use crate::cursor_types::{CursorEvent, EventType};
+use crate::processing::chapters::Chapter;
+use crate::processing::timeline::{Cut, Timeline};
+use crate::recording::metadata::Marker;
+
+/// A chapter at each marker, named after it, plus one from the start of the
+/// output if the first marker comes later. `time_offset` is how far the
+/// cursor clock is ahead of the trimmed video.
+pub fn marker_chapters(
+    markers: &[Marker],
+    timeline: &Timeline,
+    time_offset: f64,
+    duration: f64,
+) -> Vec<Chapter> {
+    let mut chapters: Vec<Chapter> = markers
+        .iter()
+        .enumerate()
+        .filter_map(|(i, marker)| {
+            let start = timeline.output_time(marker.timestamp - time_offset)?;
+            (0.0..duration).contains(&start).then(|| Chapter {
+                start,
+                title: marker
+                    .name
+                    .clone()
+                    .unwrap_or_else(|| format!("Marker {}", i + 1)),
+            })
+        })
+        .collect();
+    if chapters.first().is_some_and(|first| first.start > 0.0) {
+        chapters.insert(
+            0,
+            Chapter {
+                start: 0.0,
+                title: "Start".to_string(),
+            },
+        );
+    }
+    chapters
+}
+
+/// Cuts from each marker to the next, pairing them in order, in video time
+/// (`time_offset` behind the cursor clock). A last unpaired marker is left
+/// alone.
+pub fn marker_cuts(markers: &[Marker], time_offset: f64) -> Vec<Cut> {
+    markers
+        .chunks_exact(2)
+        .map(|pair| Cut {
+            start: (pair[0].timestamp - time_offset).max(0.0),
+            end: (pair[1].timestamp - time_offset).max(0.0),
+        })
+        .filter(|cut| cut.end > cut.start)
+        .collect()
+}
+
+/// `events` with a click added at each marker, where the cursor was then,
+/// so the camera zooms in there
+pub fn with_marker_clicks(events: &[CursorEvent], markers: &[Marker]) -> Vec<CursorEvent> {
+    let mut events = events.to_vec();
+    for marker in markers {
+        let last = events
+            .iter()
+            .filter(|e| e.timestamp <= marker.timestamp)
+            .max_by(|a, b| a.timestamp.total_cmp(&b.timestamp));
+        let Some(last) = last else {
+            continue;
+        };
+        events.push(CursorEvent {
+            timestamp: marker.timestamp,
+            event_type: EventType::LeftClick,
+            ..last.clone()
+        });
+    }
+    events.sort_by(|a, b| a.timestamp.total_cmp(&b.timestamp));
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn marker(timestamp: f64, name: Option<&str>) -> Marker {
+        Marker {
+            timestamp,
+            name: name.map(str::to_string),
+        }
+    }
+
+    fn event(timestamp: f64, x: f64, event_type: EventType) -> CursorEvent {
+        CursorEvent {
+            x,
+            y: 100.0,
+            timestamp,
+            event_type,
+            display: None,
+            shape: None,
+        }
+    }
+
+    #[test]
+    fn test_marker_chapters() {
+        let markers = [marker(5.5, Some("Settings")), marker(20.5, None)];
+        let timeline = Timeline::constant(30.0);
+        let chapters = marker_chapters(&markers, &timeline, 0.5, 30.0);
+        let titles: Vec<_> = chapters.iter().map(|c| c.title.as_str()).collect();
+        assert_eq!(titles, ["Start", "Settings", "Marker 2"]);
+        assert_eq!(chapters[1].start, 5.0);
+
+        // A marker in cut video gets no chapter
+        let cut = timeline.cut(&[Cut {
+            start: 15.0,
+            end: 25.0,
+        }]);
+        assert_eq!(marker_chapters(&markers, &cut, 0.5, 20.0).len(), 2);
+    }
+
+    #[test]
+    fn test_marker_cuts() {
+        let markers = [marker(10.5, None), marker(14.5, None), marker(30.5, None)];
+        assert_eq!(
+            marker_cuts(&markers, 0.5),
+            [Cut {
+                start: 10.0,
+                end: 14.0,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_with_marker_clicks() {
+        let events = [
+            event(1.0, 10.0, EventType::Move),
+            event(3.0, 30.0, EventType::Move),
+        ];
+        let events = with_marker_clicks(&events, &[marker(2.0, None), marker(0.5, None)]);
+        assert_eq!(events.len(), 3);
+        assert!(matches!(events[1].event_type, EventType::LeftClick));
+        assert_eq!((events[1].timestamp, events[1].x), (2.0, 10.0));
+    }
+}
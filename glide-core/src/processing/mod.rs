@@ -0,0 +1,38 @@
+pub mod animation;
+pub mod annotations;
+pub mod auto_redact;
+pub mod captions;
+pub mod chapters;
+pub mod click_callouts;
+pub mod click_highlight;
+pub mod click_sound;
+pub mod cursor;
+pub mod cursor_sprites;
+pub mod easing;
+pub mod effects;
+pub mod fast;
+pub mod frames;
+pub mod gpu;
+pub mod heatmap;
+pub mod idle;
+pub mod inspect;
+pub mod live_preview;
+pub mod markers;
+pub mod motion_blur;
+pub mod music;
+pub mod palette;
+pub mod pipeline;
+pub mod progress;
+pub mod redact;
+pub mod shapes;
+pub mod spotlight;
+pub mod stats;
+pub mod steps;
+pub mod text;
+pub mod timeline;
+pub mod trail;
+pub mod zoom;
+pub mod zoom_chart;
+
+// Re-export the main entry point
+pub use pipeline::{CameraOptions, ProcessOptions, Processor};
@@ -0,0 +1,585 @@
+//! Motion blur effects for zoom and pan transitions
+//!
+//! Applies radial blur during zoom-in/zoom-out and directional blur during panning.
+//! The temporal mode instead renders the camera at several moments while
+//! the shutter is open and averages them, which gets combined zoom and pan
+//! right at the cost of a zoom pass per sample.
+
+use crate::cursor_types::CursorEvent;
+use crate::processing::effects::ContentLayout;
+use crate::processing::zoom::{calculate_zoom, ZoomConfig};
+use image::{Rgba, RgbaImage};
+
+/// Motion state at a specific timestamp
+#[derive(Debug, Clone, Default)]
+pub struct MotionState {
+    /// Current zoom level
+    pub zoom: f64,
+    /// Zoom velocity (d(zoom)/dt) - positive = zooming in, negative = zooming out
+    pub zoom_velocity: f64,
+    /// Current cursor position (canvas coordinates)
+    pub cursor_x: f64,
+    pub cursor_y: f64,
+    /// Pan velocity in pixels per second (canvas coordinates)
+    pub pan_velocity_x: f64,
+    pub pan_velocity_y: f64,
+    /// Motion phase for context
+    pub phase: MotionPhase,
+}
+
+/// What phase of motion we're in
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum MotionPhase {
+    #[default]
+    Idle,
+    ZoomIn,
+    Hold,
+    ZoomOut,
+    Pan,
+}
+
+/// How many samples each blurred pixel averages: fewer render faster but
+/// can show banding on long smears
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum MotionBlurQuality {
+    Low,
+    Medium,
+    #[default]
+    High,
+}
+
+impl MotionBlurQuality {
+    /// Samples for the zoom (radial) and pan (directional) blurs, and
+    /// sub-frames for the temporal one
+    fn samples(self) -> (u32, u32, u32) {
+        match self {
+            Self::Low => (6, 4, 3),
+            Self::Medium => (10, 8, 5),
+            Self::High => (16, 12, 8),
+        }
+    }
+}
+
+/// How motion blur is made
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum MotionBlurMode {
+    /// Smear each frame along the zoom or pan
+    #[default]
+    Screen,
+    /// Average the frame rendered at several camera positions in between
+    /// frames: slower, but correct when zooming and panning at once
+    Temporal,
+}
+
+/// Fraction of a frame the temporal blur's shutter is open at the default
+/// strength, as a film camera's 180° shutter
+const SHUTTER: f64 = 0.5;
+
+/// Strongest blur `--motion-blur-strength` allows, as a multiple of the default
+const MAX_STRENGTH: f64 = 4.0;
+
+/// Configuration for motion blur
+#[derive(Debug, Clone)]
+pub struct MotionBlurConfig {
+    /// Enable/disable motion blur
+    pub enabled: bool,
+    /// Maximum blur strength for zoom (pixels at edges)
+    pub zoom_blur_strength: f64,
+    /// Number of samples for radial blur (more = better quality, slower)
+    pub zoom_blur_samples: u32,
+    /// Maximum blur strength for pan (pixels)
+    pub pan_blur_strength: f64,
+    /// Number of samples for directional blur
+    pub pan_blur_samples: u32,
+    /// Minimum velocity threshold to apply blur
+    pub velocity_threshold: f64,
+    /// How the blur is made
+    pub mode: MotionBlurMode,
+    /// Sub-frames averaged per frame in temporal mode
+    pub temporal_samples: u32,
+    /// Fraction of a frame the temporal blur's shutter is open for
+    pub shutter: f64,
+}
+
+impl Default for MotionBlurConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            zoom_blur_strength: 90.0,
+            zoom_blur_samples: 16,
+            pan_blur_strength: 60.0,
+            pan_blur_samples: 12,
+            velocity_threshold: 0.05,
+            mode: MotionBlurMode::default(),
+            temporal_samples: 8,
+            shutter: SHUTTER,
+        }
+    }
+}
+
+impl MotionBlurConfig {
+    /// The default blur at `quality`, `strength` times as strong, with
+    /// `samples` for every blur instead of the quality's if given
+    pub fn tuned(quality: MotionBlurQuality, strength: f64, samples: Option<u32>) -> Self {
+        let default = Self::default();
+        let (zoom_samples, pan_samples, temporal_samples) = quality.samples();
+        Self {
+            zoom_blur_strength: default.zoom_blur_strength * strength,
+            zoom_blur_samples: samples.unwrap_or(zoom_samples),
+            pan_blur_strength: default.pan_blur_strength * strength,
+            pan_blur_samples: samples.unwrap_or(pan_samples),
+            temporal_samples: samples.unwrap_or(temporal_samples),
+            // The shutter can't stay open past the next frame
+            shutter: (SHUTTER * strength).min(1.0),
+            ..default
+        }
+    }
+
+    /// When, relative to a frame `frame_duration` seconds long, to render
+    /// the sub-frames of a temporal blur: spread evenly while the shutter
+    /// is open, centered on the frame
+    pub fn sub_frame_offsets(&self, frame_duration: f64) -> Vec<f64> {
+        let samples = self.temporal_samples.max(2);
+        let open = frame_duration * self.shutter;
+        (0..samples)
+            .map(|i| open * (i as f64 / (samples - 1) as f64 - 0.5))
+            .collect()
+    }
+}
+
+/// The average of same-sized `images`, pixel by pixel
+pub fn average_images(images: &[RgbaImage]) -> RgbaImage {
+    let (width, height) = images[0].dimensions();
+    let count = images.len() as u32;
+    let mut sums = vec![0u32; (width * height * 4) as usize];
+    for image in images {
+        for (sum, &channel) in sums.iter_mut().zip(image.as_raw()) {
+            *sum += channel as u32;
+        }
+    }
+    let averaged = sums
+        .into_iter()
+        .map(|sum| ((sum + count / 2) / count) as u8)
+        .collect();
+    RgbaImage::from_raw(width, height, averaged).expect("buffer matches the dimensions")
+}
+
+/// Parse `--motion-blur-strength`: a multiple of the default strength
+pub fn parse_strength(s: &str) -> Result<f64, String> {
+    match s.parse::<f64>() {
+        Ok(strength) if (0.0..=MAX_STRENGTH).contains(&strength) => Ok(strength),
+        _ => Err(format!("expected a strength from 0 to {}", MAX_STRENGTH)),
+    }
+}
+
+/// Calculate motion state at a given timestamp using finite differences
+pub fn calculate_motion_state(
+    timestamp: f64,
+    cursor_events: &[CursorEvent],
+    zoom_config: &ZoomConfig,
+    layout: &ContentLayout,
+    window_offset: (f64, f64),
+    scale_factor: f64,
+) -> MotionState {
+    // Small time delta for numerical differentiation (~8ms, half a frame at 60fps)
+    const DT: f64 = 1.0 / 120.0;
+
+    // Get zoom state at t-dt, t, and t+dt
+    let (zoom_prev, cx_prev, cy_prev) =
+        calculate_zoom((timestamp - DT).max(0.0), cursor_events, zoom_config);
+    let (zoom_curr, cx_curr, cy_curr) = calculate_zoom(timestamp, cursor_events, zoom_config);
+    let (zoom_next, cx_next, cy_next) = calculate_zoom(timestamp + DT, cursor_events, zoom_config);
+
+    // Central difference for velocity (more accurate than forward/backward)
+    let zoom_velocity = (zoom_next - zoom_prev) / (2.0 * DT);
+
+    // Transform cursor to canvas coordinates
+    let to_canvas = |cx: f64, cy: f64| -> (f64, f64) {
+        let offset_x = window_offset.0 * scale_factor;
+        let offset_y = window_offset.1 * scale_factor;
+        let window_x = cx * scale_factor - offset_x;
+        let window_y = cy * scale_factor - offset_y;
+        (
+            layout.offset_x as f64 + window_x * layout.scale,
+            layout.offset_y as f64 + window_y * layout.scale,
+        )
+    };
+
+    let (canvas_prev_x, canvas_prev_y) = to_canvas(cx_prev, cy_prev);
+    let (canvas_curr_x, canvas_curr_y) = to_canvas(cx_curr, cy_curr);
+    let (canvas_next_x, canvas_next_y) = to_canvas(cx_next, cy_next);
+
+    // Central difference for pan velocity
+    let pan_velocity_x = (canvas_next_x - canvas_prev_x) / (2.0 * DT);
+    let pan_velocity_y = (canvas_next_y - canvas_prev_y) / (2.0 * DT);
+
+    // Determine motion phase
+    let phase = determine_motion_phase(zoom_curr, zoom_velocity, pan_velocity_x, pan_velocity_y);
+
+    MotionState {
+        zoom: zoom_curr,
+        zoom_velocity,
+        cursor_x: canvas_curr_x,
+        cursor_y: canvas_curr_y,
+        pan_velocity_x,
+        pan_velocity_y,
+        phase,
+    }
+}
+
+fn determine_motion_phase(zoom: f64, zoom_velocity: f64, pan_vx: f64, pan_vy: f64) -> MotionPhase {
+    const ZOOM_THRESHOLD: f64 = 0.05; // Lower threshold
+    const PAN_THRESHOLD: f64 = 50.0; // pixels/second
+
+    if zoom < 1.01 {
+        return MotionPhase::Idle;
+    }
+
+    if zoom_velocity > ZOOM_THRESHOLD {
+        return MotionPhase::ZoomIn;
+    }
+
+    if zoom_velocity < -ZOOM_THRESHOLD {
+        return MotionPhase::ZoomOut;
+    }
+
+    let pan_speed = (pan_vx * pan_vx + pan_vy * pan_vy).sqrt();
+    if pan_speed > PAN_THRESHOLD {
+        return MotionPhase::Pan;
+    }
+
+    MotionPhase::Hold
+}
+
+/// A blur pass worked out from the motion, applied on the CPU by
+/// [`apply_blur`] or on the GPU with `--gpu`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Blur {
+    /// Along lines through the center, `amount` pixels at `max_dist` from
+    /// it and less nearer in: outward when positive (zooming in), inward
+    /// when negative (zooming out)
+    Radial {
+        center_x: f64,
+        center_y: f64,
+        amount: f64,
+        max_dist: f64,
+        samples: u32,
+    },
+    /// Trailing `amount` pixels behind the direction of movement
+    Directional {
+        dir_x: f64,
+        dir_y: f64,
+        amount: f64,
+        samples: u32,
+    },
+}
+
+/// The blur for a `width` x `height` frame in this motion state, if any
+pub fn plan_motion_blur(
+    motion: &MotionState,
+    config: &MotionBlurConfig,
+    width: u32,
+    height: u32,
+) -> Option<Blur> {
+    if !config.enabled {
+        return None;
+    }
+
+    match motion.phase {
+        MotionPhase::Idle | MotionPhase::Hold => None,
+        MotionPhase::ZoomIn | MotionPhase::ZoomOut => radial_blur(
+            motion.cursor_x,
+            motion.cursor_y,
+            motion.zoom_velocity,
+            config,
+            width,
+            height,
+        ),
+        MotionPhase::Pan => directional_blur(motion.pan_velocity_x, motion.pan_velocity_y, config),
+    }
+}
+
+/// Radial (zoom) blur, radiating from/toward the center point.
+/// - Positive velocity: blur outward (zoom in - content rushes toward viewer)
+/// - Negative velocity: blur inward (zoom out - content recedes)
+fn radial_blur(
+    center_x: f64,
+    center_y: f64,
+    zoom_velocity: f64,
+    config: &MotionBlurConfig,
+    width: u32,
+    height: u32,
+) -> Option<Blur> {
+    if zoom_velocity.abs() < config.velocity_threshold {
+        return None;
+    }
+
+    // Normalize velocity to 0..1 range
+    // Max expected zoom velocity is ~(max_zoom - 1) / ease_in_duration
+    // With max_zoom=1.8 and ease_in=0.6s: ~1.33 zoom/sec
+    let max_velocity = 2.0;
+    let normalized_velocity = (zoom_velocity.abs() / max_velocity).clamp(0.0, 1.0);
+
+    // Blur strength scales with velocity (linear for more visible effect),
+    // signed by direction: positive velocity = outward blur (zoom in)
+    let blur_amount = config.zoom_blur_strength * normalized_velocity;
+    let direction = if zoom_velocity > 0.0 { 1.0 } else { -1.0 };
+
+    Some(Blur::Radial {
+        center_x,
+        center_y,
+        amount: blur_amount * direction,
+        max_dist: (width.max(height) as f64) * 0.5,
+        samples: config.zoom_blur_samples.max(2),
+    })
+}
+
+/// Directional (motion) blur in the direction of panning
+fn directional_blur(velocity_x: f64, velocity_y: f64, config: &MotionBlurConfig) -> Option<Blur> {
+    let speed = (velocity_x * velocity_x + velocity_y * velocity_y).sqrt();
+
+    // Higher threshold for pan since velocities are in pixels/sec
+    if speed < config.velocity_threshold * 500.0 {
+        return None;
+    }
+
+    // Blur strength proportional to speed (linear)
+    // Typical pan speed: 500-2000 pixels/second
+    let max_speed = 1500.0;
+    let normalized_speed = (speed / max_speed).clamp(0.0, 1.0);
+    let blur_amount = config.pan_blur_strength * normalized_speed;
+
+    if blur_amount < 0.5 {
+        return None;
+    }
+
+    Some(Blur::Directional {
+        dir_x: velocity_x / speed,
+        dir_y: velocity_y / speed,
+        amount: blur_amount,
+        samples: config.pan_blur_samples.max(2),
+    })
+}
+
+/// Apply a blur pass to an image
+pub fn apply_blur(img: &RgbaImage, blur: &Blur) -> RgbaImage {
+    let (width, height) = img.dimensions();
+    RgbaImage::from_fn(width, height, |x, y| match *blur {
+        Blur::Radial {
+            center_x,
+            center_y,
+            amount,
+            max_dist,
+            samples,
+        } => {
+            // Vector from center to this pixel
+            let dx = x as f64 - center_x;
+            let dy = y as f64 - center_y;
+            let dist = (dx * dx + dy * dy).sqrt().max(1.0);
+
+            // Blur amount increases with distance from center
+            let pixel_blur = amount * (dist / max_dist).min(1.0);
+            if pixel_blur.abs() < 0.5 {
+                // No visible blur, just copy pixel
+                return *img.get_pixel(x, y);
+            }
+
+            // Sample positions along the radial line - ASYMMETRIC for motion blur effect:
+            // for zoom-in, from outward (content coming from edges); for
+            // zoom-out, from inward (content going to edges)
+            smear(img, x, y, dx / dist, dy / dist, pixel_blur, samples)
+        }
+        // Motion blur trails BEHIND movement
+        Blur::Directional {
+            dir_x,
+            dir_y,
+            amount,
+            samples,
+        } => smear(img, x, y, dir_x, dir_y, -amount, samples),
+    })
+}
+
+/// Average of `samples` points from (x, y) to `length` pixels along
+/// (dir_x, dir_y), weighted toward the start
+fn smear(
+    img: &RgbaImage,
+    x: u32,
+    y: u32,
+    dir_x: f64,
+    dir_y: f64,
+    length: f64,
+    samples: u32,
+) -> Rgba<u8> {
+    let (width, height) = img.dimensions();
+    let mut sums = [0.0f64; 4];
+    let mut weight_sum = 0.0f64;
+
+    for i in 0..samples {
+        let t = i as f64 / (samples - 1) as f64; // 0 to 1
+        let offset = t * length;
+
+        let sample_x = (x as f64 + dir_x * offset).clamp(0.0, (width - 1) as f64);
+        let sample_y = (y as f64 + dir_y * offset).clamp(0.0, (height - 1) as f64);
+
+        // Bilinear interpolation for smooth sampling
+        let pixel = bilinear_sample(img, sample_x, sample_y);
+
+        // Linear falloff weight (closer samples weighted more)
+        let weight = 1.0 - t * 0.7;
+        for (sum, channel) in sums.iter_mut().zip(pixel.0) {
+            *sum += channel as f64 * weight;
+        }
+        weight_sum += weight;
+    }
+
+    Rgba(sums.map(|sum| (sum / weight_sum) as u8))
+}
+
+/// Bilinear interpolation for smooth sub-pixel sampling
+fn bilinear_sample(img: &RgbaImage, x: f64, y: f64) -> Rgba<u8> {
+    let x0 = x.floor() as u32;
+    let y0 = y.floor() as u32;
+    let x1 = (x0 + 1).min(img.width() - 1);
+    let y1 = (y0 + 1).min(img.height() - 1);
+
+    let fx = x - x0 as f64;
+    let fy = y - y0 as f64;
+
+    let p00 = img.get_pixel(x0, y0);
+    let p10 = img.get_pixel(x1, y0);
+    let p01 = img.get_pixel(x0, y1);
+    let p11 = img.get_pixel(x1, y1);
+
+    let lerp = |a: u8, b: u8, t: f64| -> u8 { (a as f64 * (1.0 - t) + b as f64 * t) as u8 };
+
+    let lerp_pixel = |p1: &Rgba<u8>, p2: &Rgba<u8>, t: f64| -> Rgba<u8> {
+        Rgba([
+            lerp(p1[0], p2[0], t),
+            lerp(p1[1], p2[1], t),
+            lerp(p1[2], p2[2], t),
+            lerp(p1[3], p2[3], t),
+        ])
+    };
+
+    let top = lerp_pixel(p00, p10, fx);
+    let bottom = lerp_pixel(p01, p11, fx);
+    lerp_pixel(&top, &bottom, fy)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_test_image(width: u32, height: u32) -> RgbaImage {
+        let mut img = RgbaImage::new(width, height);
+        for y in 0..height {
+            for x in 0..width {
+                let r = (x * 255 / width) as u8;
+                let g = (y * 255 / height) as u8;
+                img.put_pixel(x, y, Rgba([r, g, 128, 255]));
+            }
+        }
+        img
+    }
+
+    #[test]
+    fn test_motion_phase_idle() {
+        let phase = determine_motion_phase(1.0, 0.0, 0.0, 0.0);
+        assert_eq!(phase, MotionPhase::Idle);
+    }
+
+    #[test]
+    fn test_motion_phase_zoom_in() {
+        let phase = determine_motion_phase(1.5, 0.5, 0.0, 0.0);
+        assert_eq!(phase, MotionPhase::ZoomIn);
+    }
+
+    #[test]
+    fn test_motion_phase_zoom_out() {
+        let phase = determine_motion_phase(1.5, -0.5, 0.0, 0.0);
+        assert_eq!(phase, MotionPhase::ZoomOut);
+    }
+
+    #[test]
+    fn test_motion_phase_pan() {
+        let phase = determine_motion_phase(1.8, 0.0, 200.0, 0.0);
+        assert_eq!(phase, MotionPhase::Pan);
+    }
+
+    #[test]
+    fn test_motion_phase_hold() {
+        let phase = determine_motion_phase(1.8, 0.0, 0.0, 0.0);
+        assert_eq!(phase, MotionPhase::Hold);
+    }
+
+    #[test]
+    fn test_radial_blur_no_velocity() {
+        let config = MotionBlurConfig::default();
+        // Should be unchanged
+        assert_eq!(radial_blur(50.0, 50.0, 0.0, &config, 100, 100), None);
+    }
+
+    #[test]
+    fn test_radial_blur_with_velocity() {
+        let img = create_test_image(100, 100);
+        let config = MotionBlurConfig::default();
+        let blur = radial_blur(50.0, 50.0, 1.0, &config, 100, 100).unwrap();
+        let result = apply_blur(&img, &blur);
+        // Should be blurred (different from original at edges)
+        // Center pixel should be similar since blur radiates outward
+        let orig_center = img.get_pixel(50, 50);
+        let blurred_center = result.get_pixel(50, 50);
+        // Center should be close to original
+        assert!((orig_center[0] as i32 - blurred_center[0] as i32).abs() < 20);
+    }
+
+    #[test]
+    fn test_tuned_config() {
+        let high = MotionBlurConfig::tuned(MotionBlurQuality::High, 1.0, None);
+        assert_eq!(
+            high.zoom_blur_samples,
+            MotionBlurConfig::default().zoom_blur_samples
+        );
+
+        let low = MotionBlurConfig::tuned(MotionBlurQuality::Low, 0.5, Some(3));
+        assert_eq!((low.zoom_blur_samples, low.pan_blur_samples), (3, 3));
+        assert_eq!(low.zoom_blur_strength, 45.0);
+        let blur = radial_blur(50.0, 50.0, 2.0, &low, 100, 100).unwrap();
+        assert!(matches!(blur, Blur::Radial { amount, .. } if amount == 45.0));
+
+        assert_eq!(low.temporal_samples, 3);
+        assert_eq!(low.shutter, 0.25);
+
+        assert_eq!(parse_strength("1.5"), Ok(1.5));
+        assert!(parse_strength("-1").is_err());
+        assert!(parse_strength("5").is_err());
+    }
+
+    #[test]
+    fn test_sub_frame_offsets() {
+        let config = MotionBlurConfig {
+            temporal_samples: 3,
+            ..Default::default()
+        };
+        // Half of a 1/50s frame, around the frame's own time
+        assert_eq!(config.sub_frame_offsets(0.02), [-0.005, 0.0, 0.005]);
+    }
+
+    #[test]
+    fn test_average_images() {
+        let black = RgbaImage::from_pixel(2, 2, Rgba([0, 0, 0, 255]));
+        let white = RgbaImage::from_pixel(2, 2, Rgba([255, 255, 255, 255]));
+        let average = average_images(&[black.clone(), white, black]);
+        assert_eq!(*average.get_pixel(1, 1), Rgba([85, 85, 85, 255]));
+    }
+
+    #[test]
+    fn test_bilinear_sample_integer() {
+        let img = create_test_image(100, 100);
+        let sampled = bilinear_sample(&img, 50.0, 50.0);
+        let direct = *img.get_pixel(50, 50);
+        assert_eq!(sampled, direct);
+    }
+}
@@ -0,0 +1,119 @@
+//! `--music`: a looped music bed under the output, ducked while the
+//! recording's own audio (narration) is playing
+
+use crate::processing::frames::AudioSource;
+use anyhow::{Context, Result};
+use std::path::Path;
+use std::process::Command;
+
+/// Fade the music out over the last this many seconds of the output
+const FADE_OUT: f64 = 2.0;
+
+/// Sidechain compressor settings for ducking: how loud the narration has to
+/// be to duck (linear), how hard, and how fast the music dips and recovers (ms)
+const DUCK_THRESHOLD: f64 = 0.02;
+const DUCK_RATIO: f64 = 8.0;
+const DUCK_ATTACK: f64 = 50.0;
+const DUCK_RELEASE: f64 = 600.0;
+
+/// Parse a music volume from 0 to 1
+pub fn parse_volume(s: &str) -> Result<f64, String> {
+    match s.parse::<f64>() {
+        Ok(volume) if (0.0..=1.0).contains(&volume) => Ok(volume),
+        _ => Err("expected a volume from 0 to 1".to_string()),
+    }
+}
+
+/// Filter graph for the music (input 1) at `volume`, faded out at the end
+/// of `duration`, and ducked under and mixed with the recorded audio
+/// (input 0) if there is any
+fn music_filter(volume: f64, duration: f64, ducked: bool) -> String {
+    let fade_start = (duration - FADE_OUT).max(0.0);
+    let music = format!(
+        "[1:a]volume={volume:.3},atrim=0:{duration:.3},afade=t=out:st={fade_start:.3}:d={FADE_OUT}"
+    );
+    if !ducked {
+        return music;
+    }
+    format!(
+        "{music}[music];\
+         [0:a]asplit=2[voice][key];\
+         [music][key]sidechaincompress=threshold={DUCK_THRESHOLD}:ratio={DUCK_RATIO}\
+         :attack={DUCK_ATTACK}:release={DUCK_RELEASE}[ducked];\
+         [voice][ducked]amix=inputs=2:duration=first:normalize=0"
+    )
+}
+
+/// Write the music bed to `path` as audio for the whole output, ducked under
+/// the recording's own audio if there is any
+pub fn write_music_bed(
+    path: &Path,
+    music: &Path,
+    volume: f64,
+    duration: f64,
+    recorded: Option<&AudioSource>,
+) -> Result<AudioSource> {
+    let mut command = Command::new("ffmpeg");
+    command.args(["-v", "error"]);
+    match recorded {
+        Some(recorded) => {
+            command
+                .arg("-ss")
+                .arg(format!("{:.3}", recorded.start))
+                .arg("-t")
+                .arg(format!("{:.3}", recorded.duration))
+                .arg("-i")
+                .arg(&recorded.path);
+        }
+        // Silence in place of the recording keeps the music on input 1
+        None => {
+            command.args(["-f", "lavfi", "-i", "anullsrc=r=48000:cl=stereo"]);
+        }
+    }
+    let status = command
+        // Loop short tracks to cover the whole output
+        .args(["-stream_loop", "-1", "-i"])
+        .arg(music)
+        .arg("-filter_complex")
+        .arg(music_filter(volume, duration, recorded.is_some()))
+        .arg("-t")
+        .arg(format!("{:.3}", duration))
+        .args(["-c:a", "pcm_s16le", "-y"])
+        .arg(path)
+        .status()
+        .context("Failed to run ffmpeg")?;
+    if !status.success() {
+        anyhow::bail!("FFmpeg failed to mix in the music from {}", music.display());
+    }
+    Ok(AudioSource {
+        path: path.to_path_buf(),
+        start: 0.0,
+        duration,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_music_filter() {
+        let plain = music_filter(0.2, 30.0, false);
+        assert!(plain.starts_with("[1:a]volume=0.200,atrim=0:30.000"));
+        assert!(plain.contains("afade=t=out:st=28.000:d=2"));
+        assert!(!plain.contains("sidechaincompress"));
+
+        let ducked = music_filter(0.2, 1.0, true);
+        // Too short to fade over the full length: the fade starts at once
+        assert!(ducked.contains("afade=t=out:st=0.000"));
+        assert!(ducked.contains("[music][key]sidechaincompress"));
+        assert!(ducked.ends_with("amix=inputs=2:duration=first:normalize=0"));
+    }
+
+    #[test]
+    fn test_parse_volume() {
+        assert_eq!(parse_volume("0.2"), Ok(0.2));
+        assert!(parse_volume("1.5").is_err());
+        assert!(parse_volume("loud").is_err());
+    }
+}
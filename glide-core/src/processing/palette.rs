@@ -0,0 +1,177 @@
+//! Highlight color palettes
+//!
+//! The default palette is a white ring with a dark shadow. The others are
+//! published color-blind-safe sets (Okabe-Ito, IBM Design, Paul Tol "bright")
+//! whose colors stay distinguishable under deuteranopia and protanopia.
+
+use image::Rgba;
+
+/// Color palette for click highlights and annotations
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum Palette {
+    /// White ring with a dark shadow
+    #[default]
+    Default,
+    /// Okabe-Ito palette
+    OkabeIto,
+    /// IBM Design Library color-blind-safe palette
+    Ibm,
+    /// Paul Tol's "bright" qualitative palette
+    Tol,
+}
+
+const fn rgb(hex: u32) -> Rgba<u8> {
+    Rgba([(hex >> 16) as u8, (hex >> 8) as u8, hex as u8, 255])
+}
+
+const DEFAULT_ACCENTS: &[Rgba<u8>] = &[
+    rgb(0xffffff),
+    rgb(0xff453a),
+    rgb(0x30d158),
+    rgb(0x0a84ff),
+    rgb(0xffd60a),
+];
+
+const OKABE_ITO_ACCENTS: &[Rgba<u8>] = &[
+    rgb(0xe69f00), // orange
+    rgb(0x56b4e9), // sky blue
+    rgb(0x009e73), // bluish green
+    rgb(0xf0e442), // yellow
+    rgb(0x0072b2), // blue
+    rgb(0xd55e00), // vermillion
+    rgb(0xcc79a7), // reddish purple
+];
+
+const IBM_ACCENTS: &[Rgba<u8>] = &[
+    rgb(0xffb000), // gold
+    rgb(0x648fff), // ultramarine
+    rgb(0x785ef0), // indigo
+    rgb(0xdc267f), // magenta
+    rgb(0xfe6100), // orange
+];
+
+const TOL_ACCENTS: &[Rgba<u8>] = &[
+    rgb(0xccbb44), // yellow
+    rgb(0x4477aa), // blue
+    rgb(0x66ccee), // cyan
+    rgb(0x228833), // green
+    rgb(0xee6677), // red
+    rgb(0xaa3377), // purple
+];
+
+impl Palette {
+    /// Color of the click highlight ring
+    pub fn click_color(self) -> Rgba<u8> {
+        self.accents()[0]
+    }
+
+    /// Color of the dashed ring on right-clicks
+    pub fn right_click_color(self) -> Rgba<u8> {
+        self.accents()[1]
+    }
+
+    /// Dark outline drawn under highlights for contrast on light content
+    pub fn shadow_color(self) -> Rgba<u8> {
+        Rgba([0, 0, 0, 150])
+    }
+
+    /// Annotation colors, in the order they should be assigned
+    pub fn accents(self) -> &'static [Rgba<u8>] {
+        match self {
+            Palette::Default => DEFAULT_ACCENTS,
+            Palette::OkabeIto => OKABE_ITO_ACCENTS,
+            Palette::Ibm => IBM_ACCENTS,
+            Palette::Tol => TOL_ACCENTS,
+        }
+    }
+
+    /// Accent for the `index`-th annotation, cycling through the palette
+    pub fn accent(self, index: usize) -> Rgba<u8> {
+        let accents = self.accents();
+        accents[index % accents.len()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Machado et al. (2009) dichromacy simulation matrices (severity 1.0, linear RGB)
+    const DEUTERANOPIA: [[f64; 3]; 3] = [
+        [0.367322, 0.860646, -0.227968],
+        [0.280085, 0.672501, 0.047413],
+        [-0.011820, 0.042940, 0.968881],
+    ];
+    const PROTANOPIA: [[f64; 3]; 3] = [
+        [0.152286, 1.052583, -0.204868],
+        [0.114503, 0.786281, 0.099216],
+        [-0.003882, -0.048116, 1.051998],
+    ];
+
+    fn simulate(matrix: &[[f64; 3]; 3], color: Rgba<u8>) -> [f64; 3] {
+        let to_linear = |c: u8| {
+            let c = c as f64 / 255.0;
+            if c <= 0.04045 {
+                c / 12.92
+            } else {
+                ((c + 0.055) / 1.055).powf(2.4)
+            }
+        };
+        let to_srgb = |c: f64| {
+            let c = c.clamp(0.0, 1.0);
+            255.0
+                * if c <= 0.0031308 {
+                    12.92 * c
+                } else {
+                    1.055 * c.powf(1.0 / 2.4) - 0.055
+                }
+        };
+        let lin = [
+            to_linear(color[0]),
+            to_linear(color[1]),
+            to_linear(color[2]),
+        ];
+        let mut out = [0.0; 3];
+        for (row, value) in matrix.iter().zip(out.iter_mut()) {
+            *value = to_srgb(row[0] * lin[0] + row[1] * lin[1] + row[2] * lin[2]);
+        }
+        out
+    }
+
+    #[test]
+    fn test_safe_palettes_distinguishable_under_cvd() {
+        for palette in [Palette::OkabeIto, Palette::Ibm, Palette::Tol] {
+            for matrix in [&DEUTERANOPIA, &PROTANOPIA] {
+                let simulated: Vec<_> = palette
+                    .accents()
+                    .iter()
+                    .map(|c| simulate(matrix, *c))
+                    .collect();
+                for (i, a) in simulated.iter().enumerate() {
+                    for b in &simulated[i + 1..] {
+                        let distance =
+                            ((a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2) + (a[2] - b[2]).powi(2))
+                                .sqrt();
+                        assert!(
+                            distance > 40.0,
+                            "{:?} has colors too close under color-blindness simulation",
+                            palette
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_accent_cycles() {
+        let palette = Palette::Ibm;
+        assert_eq!(palette.accent(0), palette.accent(palette.accents().len()));
+    }
+
+    #[test]
+    fn test_default_palette_keeps_white_ring() {
+        assert_eq!(Palette::Default.click_color(), Rgba([255, 255, 255, 255]));
+    }
+}
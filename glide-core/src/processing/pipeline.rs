@@ -0,0 +1,2346 @@
+use crate::cancel::CancelToken;
+use crate::cursor_types::CursorEvent;
+use crate::edit::EditDecisions;
+use crate::library;
+use crate::processing::animation::build_animation;
+use crate::processing::annotations::{load_annotations, AnnotationRenderer};
+use crate::processing::auto_redact::{detect_secrets, find_secrets};
+use crate::processing::captions::{CaptionStyle, Subtitles};
+use crate::processing::chapters::{click_chapters, write_chapters, Chapter};
+use crate::processing::click_callouts::{
+    draw_click_callouts, get_active_callouts, ActiveCallout, CalloutStyle, ClickCalloutConfig,
+};
+use crate::processing::click_highlight::{
+    draw_click_highlights, get_active_ripples, ClickHighlightConfig,
+};
+use crate::processing::click_sound::write_click_track;
+use crate::processing::cursor::{
+    draw_cursor, get_cursor_shape, get_smoothed_cursor, CursorAppearance, CursorConfig, CursorStyle,
+};
+use crate::processing::effects::{
+    apply_rounded_corners, apply_zoom, blend_images, crop_column, draw_shadow, zoom_view,
+    Background, ContentLayout, FrameStyle, ShadowStyle, AUTO_BLUR, OUTPUT_HEIGHT, OUTPUT_WIDTH,
+    VERTICAL_HEIGHT, VERTICAL_WIDTH,
+};
+use crate::processing::fast::{filter_graph, frame_image, Shot};
+use crate::processing::frames::{
+    cut_audio, encode_filter_graph, extract_frame_at, frames_input, get_video_duration,
+    get_video_fps, has_audio, verify_output, AudioSource, EncodeOptions, FrameReader, FrameWriter,
+    OutputFormat, OutputFps,
+};
+use crate::processing::gpu::GpuRenderer;
+use crate::processing::heatmap::{Overlay, ProgressiveHeatmap, OVERLAY_OPACITY};
+use crate::processing::idle::{auto_trim, plan_speed_up, FastForwardBadge};
+use crate::processing::live_preview::LivePreview;
+use crate::processing::markers::{marker_chapters, marker_cuts, with_marker_clicks};
+use crate::processing::motion_blur::{
+    apply_blur, average_images, calculate_motion_state, plan_motion_blur, MotionBlurConfig,
+    MotionBlurMode, MotionBlurQuality,
+};
+use crate::processing::music::write_music_bed;
+use crate::processing::palette::Palette;
+use crate::processing::progress::{
+    status, ProgressCallback, ProgressEvent, ProgressFormat, Reporter,
+};
+use crate::processing::redact::{BlurRegion, RedactStyle, Redactor};
+use crate::processing::spotlight::{draw_spotlight, SpotlightConfig};
+use crate::processing::steps::{
+    draw_step_badges, get_active_steps, write_step_list, ActiveStep, StepConfig,
+};
+use crate::processing::text::load_font;
+use crate::processing::timeline::{load_cuts, Cut, Timeline};
+use crate::processing::trail::{draw_trail, get_trail_points, TrailConfig, TrailPoint};
+use crate::processing::zoom::{
+    calculate_camera, effective_clicks, Crossfade, FollowCamera, ReframeCamera, ZoomConfig,
+    ZoomOverrides,
+};
+use crate::recording::bundle::{is_bundle, unpack};
+use crate::recording::crypto::{is_encrypted, DecryptKey, PassphraseCallback, SecretString};
+use crate::recording::encoder::Codec;
+use crate::recording::integrity::verify_pair;
+use crate::recording::metadata::{metadata_path_for_video, RecordingMetadata};
+use anyhow::{Context, Result};
+use image::{DynamicImage, RgbaImage};
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::Arc;
+use tempfile::TempDir;
+
+/// Memory for source frames waiting to be processed or being processed
+/// (bytes); big frames are taken in smaller batches to stay inside it
+const SOURCE_FRAME_BUDGET: usize = 1 << 30;
+
+/// Options for `glide process`
+pub struct ProcessOptions {
+    /// Background color (hex), image or video path, `blur:<image>` or `auto-blur`
+    pub background: Option<String>,
+    /// Minimum space around the content (canvas pixels)
+    pub padding: u32,
+    /// Radius of the content's rounded corners (canvas pixels)
+    pub corner_radius: u32,
+    /// How far the shadow under the content spreads (canvas pixels)
+    pub shadow_size: u32,
+    /// Opacity of the shadow at its darkest
+    pub shadow_opacity: f64,
+    pub no_shadow: bool,
+    /// 9:16 output that follows the cursor across wide content
+    pub vertical: bool,
+    /// Seconds to trim from the start of the video
+    pub trim_start: Option<f64>,
+    /// Seconds to trim from the end of the video
+    pub trim_end: Option<f64>,
+    /// Trim the inactivity at both ends instead
+    pub auto_trim: bool,
+    /// Spans of the recording to remove (seconds)
+    pub cuts: Vec<Cut>,
+    /// File listing more spans to remove
+    pub cuts_file: Option<PathBuf>,
+    /// Remove what's between each pair of markers dropped while recording
+    pub cut_between_markers: bool,
+    /// Cursor scale factor (None = derive from the recorded OS cursor size)
+    pub cursor_scale: Option<f64>,
+    /// Seconds of inactivity before the cursor fades
+    pub cursor_timeout: f64,
+    pub no_cursor: bool,
+    /// Length of the trail behind the cursor (seconds)
+    pub cursor_trail: Option<f64>,
+    /// Opacity of the cursor trail at the cursor
+    pub cursor_trail_opacity: f64,
+    /// Cursor sprite style
+    pub cursor_style: CursorStyle,
+    /// White outline around the cursor (output pixels)
+    pub cursor_outline: u32,
+    pub no_motion_blur: bool,
+    /// Smear each frame, or average sub-frames
+    pub motion_blur_mode: MotionBlurMode,
+    /// Samples per blurred pixel
+    pub motion_blur_quality: MotionBlurQuality,
+    /// Multiple of the default blur strength
+    pub motion_blur_strength: f64,
+    /// Samples per blurred pixel, overriding the quality's
+    pub motion_blur_samples: Option<u32>,
+    /// Zoom speed (zoom levels per second) below which nothing is blurred;
+    /// pans need 500 times this in pixels per second
+    pub motion_blur_threshold: f64,
+    /// Frame rate of the output
+    pub output_fps: OutputFps,
+    /// Make up content frames in between the source's instead of repeating
+    /// them, when the source has fewer frames than the output
+    pub interpolate: bool,
+    /// Never zoom or pan the camera
+    pub no_zoom: bool,
+    /// Stay zoomed in and pan with the cursor instead of zooming on clicks
+    pub follow_cursor: bool,
+    /// Zoom in at each marker as if the cursor had clicked there
+    pub zoom_on_markers: bool,
+    /// Play stretches without activity this many times faster
+    pub speed_up_idle: Option<f64>,
+    /// Seconds without activity before a stretch is sped up
+    pub idle_threshold: f64,
+    /// Show a fast-forward badge while sped up
+    pub fast_forward_indicator: bool,
+    pub no_click_highlight: bool,
+    /// Soft click sounds in the output's audio
+    pub click_sound: bool,
+    /// Music to loop under the output, ducked under recorded audio
+    pub music: Option<PathBuf>,
+    /// Volume of the music (0 to 1)
+    pub music_volume: f64,
+    /// Colors for click highlights and annotations
+    pub palette: Palette,
+    /// Draw a box or arrow around clicked elements
+    pub click_callout: Option<CalloutStyle>,
+    /// Effective click numbers (1-based) to call out; empty = all
+    pub callout_clicks: Vec<usize>,
+    /// Numbered badges at each click, plus a step list next to the output
+    pub step_numbers: bool,
+    /// Chapters written into video output
+    pub chapters: Vec<Chapter>,
+    /// Chapters at each burst of clicks after a pause
+    pub auto_chapters: bool,
+    /// Chapters at the markers dropped while recording
+    pub marker_chapters: bool,
+    /// Dim everything but the area around the cursor
+    pub spotlight: bool,
+    /// Radius of the spotlight (output pixels)
+    pub spotlight_radius: f64,
+    /// Data layer composited over the content
+    pub overlay: Option<Overlay>,
+    /// Rectangles of the recording to hide
+    pub blur_regions: Vec<BlurRegion>,
+    /// How to hide them
+    pub blur_style: RedactStyle,
+    /// Also hide text OCR finds that looks like a secret
+    pub auto_redact: bool,
+    /// Comfortable output for motion-sensitive viewers (no blur, slow easing, crossfades)
+    pub reduced_motion: bool,
+    /// Zoom level and timing, replacing the defaults
+    pub zoom: ZoomOverrides,
+    /// Faststart, regular keyframes and browser-safe profile/level
+    pub web_optimized: bool,
+    /// Output container/format
+    pub format: OutputFormat,
+    /// Video codec for video formats (None = H.264, or VP9 for WebM)
+    pub codec: Option<Codec>,
+    /// Segment length for HLS/DASH output (seconds)
+    pub segment_seconds: f64,
+    /// Maximum width for animated image output
+    pub max_width: Option<u32>,
+    /// Maximum frame rate for animated image output
+    pub max_fps: Option<f64>,
+    /// Write 16-bit PNGs for image sequences (8-bit frames, widened)
+    pub png_16bit: bool,
+    /// Also write the camera/cursor animation as JSON for web replay
+    pub export_animation: Option<PathBuf>,
+    /// Timed text callouts to draw on the output
+    pub annotations: Option<PathBuf>,
+    /// SRT subtitles to burn into the output
+    pub subtitles: Option<PathBuf>,
+    /// How subtitles look and where they sit
+    pub caption_style: CaptionStyle,
+    /// Remove the raw recording and metadata once the output is verified
+    pub delete_source_on_success: bool,
+    /// Warn instead of failing when the video and metadata don't match
+    pub ignore_mismatch: bool,
+    /// age identity file for encrypted recordings (None = passphrase)
+    pub identity: Option<PathBuf>,
+    /// Worker threads for frame processing and FFmpeg (None = all cores)
+    pub threads: Option<usize>,
+    /// Render effects on the GPU when one is available
+    pub gpu: bool,
+    /// Leave the pixel work to an FFmpeg filter graph, without the cursor,
+    /// click effects, overlays or motion blur
+    pub fast: bool,
+    /// Show frames in a player window as they're rendered
+    pub preview: bool,
+    /// `Quiet` leaves the status lines out of the log, and `Json` leaves
+    /// warnings to the events from [`Processor::on_progress`]
+    pub progress: ProgressFormat,
+}
+
+impl Default for ProcessOptions {
+    /// The `glide process` defaults
+    fn default() -> Self {
+        Self {
+            background: None,
+            padding: 100,
+            corner_radius: 12,
+            shadow_size: 20,
+            shadow_opacity: 0.31,
+            no_shadow: false,
+            vertical: false,
+            trim_start: None,
+            trim_end: None,
+            auto_trim: false,
+            cuts: Vec::new(),
+            cuts_file: None,
+            cut_between_markers: false,
+            cursor_scale: None,
+            cursor_timeout: 2.0,
+            no_cursor: false,
+            cursor_trail: None,
+            cursor_trail_opacity: 0.5,
+            cursor_style: CursorStyle::default(),
+            cursor_outline: 0,
+            no_motion_blur: false,
+            motion_blur_mode: MotionBlurMode::default(),
+            motion_blur_quality: MotionBlurQuality::default(),
+            motion_blur_strength: 1.0,
+            motion_blur_samples: None,
+            motion_blur_threshold: 0.05,
+            output_fps: OutputFps::default(),
+            interpolate: false,
+            no_zoom: false,
+            follow_cursor: false,
+            zoom_on_markers: false,
+            speed_up_idle: None,
+            idle_threshold: 3.0,
+            fast_forward_indicator: false,
+            no_click_highlight: false,
+            click_sound: false,
+            music: None,
+            music_volume: 0.2,
+            palette: Palette::default(),
+            click_callout: None,
+            callout_clicks: Vec::new(),
+            step_numbers: false,
+            chapters: Vec::new(),
+            auto_chapters: false,
+            marker_chapters: false,
+            spotlight: false,
+            spotlight_radius: 220.0,
+            overlay: None,
+            blur_regions: Vec::new(),
+            blur_style: RedactStyle::default(),
+            auto_redact: false,
+            reduced_motion: false,
+            zoom: ZoomOverrides::default(),
+            web_optimized: false,
+            format: OutputFormat::default(),
+            codec: None,
+            segment_seconds: 6.0,
+            max_width: None,
+            max_fps: None,
+            png_16bit: false,
+            export_animation: None,
+            annotations: None,
+            subtitles: None,
+            caption_style: CaptionStyle::default(),
+            delete_source_on_success: false,
+            ignore_mismatch: false,
+            identity: None,
+            threads: None,
+            gpu: false,
+            fast: false,
+            preview: false,
+            progress: ProgressFormat::default(),
+        }
+    }
+}
+
+impl ProcessOptions {
+    fn camera_options(&self) -> CameraOptions {
+        CameraOptions {
+            no_zoom: self.no_zoom,
+            follow_cursor: self.follow_cursor,
+            zoom_on_markers: self.zoom_on_markers,
+        }
+    }
+}
+
+/// How the camera moves, as for `process --no-zoom`, `--follow-cursor`
+/// and `--zoom-on-markers`. Zooms on clicks by default.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct CameraOptions {
+    pub no_zoom: bool,
+    pub follow_cursor: bool,
+    pub zoom_on_markers: bool,
+}
+
+/// The codec to encode with, checked against the output format
+fn output_codec(options: &ProcessOptions) -> Result<Codec> {
+    let codec = options.codec.unwrap_or(match options.format {
+        OutputFormat::Webm => Codec::Vp9,
+        _ => Codec::H264,
+    });
+    if options.format == OutputFormat::Webm && !codec.fits_webm() {
+        anyhow::bail!("WebM output needs --codec vp9 or av1");
+    }
+    if codec.fits_webm() && !matches!(options.format, OutputFormat::Mp4 | OutputFormat::Webm) {
+        anyhow::bail!("VP9 and AV1 are only written as mp4 or webm");
+    }
+    Ok(codec)
+}
+
+/// Runs `glide process` and `glide preview` on recordings
+pub struct Processor {
+    options: ProcessOptions,
+    on_progress: Option<ProgressCallback>,
+    on_passphrase: Option<PassphraseCallback>,
+    cancel: CancelToken,
+}
+
+impl Processor {
+    pub fn new(options: ProcessOptions) -> Self {
+        Self {
+            options,
+            on_progress: None,
+            on_passphrase: None,
+            cancel: CancelToken::new(),
+        }
+    }
+
+    /// Call `callback` with each [`ProgressEvent`] as it happens
+    pub fn on_progress(
+        mut self,
+        callback: impl Fn(&ProgressEvent) + Send + Sync + 'static,
+    ) -> Self {
+        self.on_progress = Some(Arc::new(callback));
+        self
+    }
+
+    /// Call `callback` for the passphrase of a recording encrypted to one,
+    /// when there's no [`identity`](ProcessOptions::identity)
+    pub fn on_passphrase(
+        mut self,
+        callback: impl Fn() -> Result<SecretString> + Send + Sync + 'static,
+    ) -> Self {
+        self.on_passphrase = Some(Arc::new(callback));
+        self
+    }
+
+    /// A token that stops the render, between frames, when cancelled
+    pub fn cancel_token(&self) -> CancelToken {
+        self.cancel.clone()
+    }
+
+    /// Render `input` into `output`
+    pub fn process(&self, input: &Path, output: &Path) -> Result<()> {
+        self.render(input, output, None)
+    }
+
+    /// The single frame showing at output time `at`, as a PNG
+    pub fn preview(&self, input: &Path, at: f64, output: &Path) -> Result<()> {
+        self.render(input, output, Some(at))
+    }
+
+    fn render(&self, input: &Path, output: &Path, preview_at: Option<f64>) -> Result<()> {
+        let reporter = Reporter::new(
+            self.options.progress,
+            self.on_progress.clone(),
+            Some(self.cancel.clone()),
+        );
+        let pool = worker_pool(self.options.threads)?;
+        let passphrase = self.on_passphrase.as_ref();
+        let render = || {
+            render(
+                input,
+                output,
+                preview_at,
+                &self.options,
+                passphrase,
+                &reporter,
+            )
+        };
+        match pool {
+            Some(pool) => pool.install(render),
+            None => render(),
+        }
+        .inspect_err(|e| reporter.error(e))
+    }
+}
+
+/// A pool of `threads` workers for one render, or None for rayon's global
+/// pool. Each render gets its own, so renders with different thread counts
+/// can share a process without touching the host's global pool.
+fn worker_pool(threads: Option<usize>) -> Result<Option<rayon::ThreadPool>> {
+    threads
+        .map(|threads| rayon::ThreadPoolBuilder::new().num_threads(threads).build())
+        .transpose()
+        .context("Failed to configure worker threads")
+}
+
+/// The whole output, or with `preview_at`, just the frame at that time
+fn render(
+    input: &Path,
+    output: &Path,
+    preview_at: Option<f64>,
+    options: &ProcessOptions,
+    passphrase: Option<&PassphraseCallback>,
+    reporter: &Reporter,
+) -> Result<()> {
+    // A preview is a single frame: no sound, chapters or files beside it
+    let full_render = preview_at.is_none();
+    if !full_render {
+        if options.fast {
+            anyhow::bail!("--fast can't be previewed: FFmpeg renders it over the whole video");
+        }
+        if options.interpolate {
+            anyhow::bail!("--interpolate can't be previewed: a preview shows a recorded frame");
+        }
+    }
+
+    // Bundles from `glide pack` are unpacked to a temp dir; the bundle itself
+    // stays the "source" for the library and --delete-source-on-success
+    let source = input;
+    let bundle_dir = TempDir::new().context("Failed to create temp directory")?;
+    let unpacked;
+    let input = if is_bundle(source) {
+        reporter.stage("unpacking");
+        status!(reporter, "Unpacking bundle {}...", source.display());
+        unpacked = unpack(source, bundle_dir.path(), false)?;
+        unpacked.as_path()
+    } else {
+        source
+    };
+
+    // Encrypted recordings are decrypted transparently
+    let encrypted_video = is_encrypted(input);
+    let decrypt_key = if encrypted_video || is_encrypted(&metadata_path_for_video(input)) {
+        Some(match (&options.identity, passphrase) {
+            (Some(identity), _) => DecryptKey::identity_file(identity)?,
+            (None, Some(passphrase)) => DecryptKey::passphrase(passphrase()?),
+            (None, None) => anyhow::bail!(
+                "{} is encrypted; give an identity file or a passphrase",
+                source.display()
+            ),
+        })
+    } else {
+        None
+    };
+
+    // Load metadata
+    let metadata = match decrypt_key {
+        Some(ref key) => RecordingMetadata::load_with_key(input, key),
+        None => RecordingMetadata::load(input),
+    }
+    .context("Failed to load recording metadata. Was this video recorded with glide?")?;
+
+    if let Err(e) = verify_pair(input, &metadata) {
+        if !options.ignore_mismatch {
+            return Err(e.context(
+                "Video and metadata don't match (use --ignore-mismatch to process anyway)",
+            ));
+        }
+        reporter.warning(format!("{:#}", e));
+    }
+
+    // The checks above ran on the file on disk; FFmpeg needs the plaintext
+    let decrypted_dir = TempDir::new().context("Failed to create temp directory")?;
+    let video = match decrypt_key {
+        Some(ref key) if encrypted_video => {
+            let path = decrypted_dir.path().join("recording.mp4");
+            reporter.stage("decrypting");
+            status!(reporter, "Decrypting {}...", input.display());
+            key.decrypt_file(input, &path)?;
+            path
+        }
+        _ => input.to_path_buf(),
+    };
+    let video = video.as_path();
+
+    // Parse background; auto-blur waits for the first frame to be extracted
+    let auto_blur = options.background.as_deref() == Some(AUTO_BLUR);
+    let mut redactor = Redactor {
+        regions: options.blur_regions.clone(),
+        style: options.blur_style,
+    };
+    if !redactor.is_empty() {
+        status!(
+            reporter,
+            "  Redacting: {} region(s) ({:?})",
+            redactor.regions.len(),
+            redactor.style
+        );
+    }
+    let mut bg = Background::parse(
+        options.background.as_deref().filter(|_| !auto_blur),
+        reporter,
+    )?;
+
+    let frame_style = FrameStyle {
+        padding: options.padding,
+        corner_radius: options.corner_radius,
+        shadow: (!options.no_shadow).then_some(ShadowStyle {
+            size: options.shadow_size,
+            opacity: options.shadow_opacity,
+        }),
+    };
+
+    // Create cursor config
+    let cursor_config = if options.no_cursor {
+        None
+    } else {
+        Some(CursorConfig {
+            appearance: CursorAppearance {
+                style: options.cursor_style,
+                outline: options.cursor_outline,
+            },
+            ..CursorConfig::new(
+                options
+                    .cursor_scale
+                    .unwrap_or_else(|| CursorConfig::default_scale(metadata.os_cursor_scale)),
+                options.cursor_timeout,
+            )
+        })
+    };
+
+    // Create motion blur config
+    let motion_blur_config = MotionBlurConfig {
+        // The blur follows the click camera, so there's nothing to blur
+        // without it, and the follow camera pans too slowly to need it
+        enabled: !options.no_motion_blur
+            && !options.reduced_motion
+            && !options.no_zoom
+            && !options.follow_cursor,
+        velocity_threshold: options.motion_blur_threshold,
+        mode: options.motion_blur_mode,
+        ..MotionBlurConfig::tuned(
+            options.motion_blur_quality,
+            options.motion_blur_strength,
+            options.motion_blur_samples,
+        )
+    };
+
+    // Create click highlight config
+    let click_highlight_config = ClickHighlightConfig {
+        enabled: !options.no_click_highlight,
+        color: options.palette.click_color(),
+        right_color: options.palette.right_click_color(),
+        shadow_color: options.palette.shadow_color(),
+        ..Default::default()
+    };
+
+    let trail_config = options.cursor_trail.map(|length| {
+        TrailConfig::new(
+            length,
+            options.cursor_trail_opacity,
+            options.palette.click_color(),
+        )
+    });
+
+    let spotlight_config = options
+        .spotlight
+        .then(|| SpotlightConfig::new(options.spotlight_radius));
+
+    let click_callout_config = options.click_callout.map(|style| ClickCalloutConfig {
+        style,
+        clicks: (!options.callout_clicks.is_empty()).then(|| options.callout_clicks.clone()),
+        color: options.palette.accent(1),
+        ..Default::default()
+    });
+
+    let step_config = if options.step_numbers {
+        Some(StepConfig::new(load_font(None)?, options.palette.accent(1)))
+    } else {
+        None
+    };
+
+    let mut cuts = options.cuts.clone();
+    if let Some(ref path) = options.cuts_file {
+        cuts.extend(load_cuts(path)?);
+    }
+
+    // Load annotations up front so a bad file fails before the slow steps
+    let annotation_renderer = match options.annotations {
+        Some(ref path) => Some(AnnotationRenderer::new(
+            &load_annotations(path)?,
+            options.palette,
+        )?),
+        None => None,
+    };
+    let subtitles = match options.subtitles {
+        Some(ref path) => Some(Subtitles::load(path, &options.caption_style)?),
+        None => None,
+    };
+
+    let mut encode_options = EncodeOptions {
+        format: options.format,
+        codec: output_codec(options)?,
+        web_optimized: options.web_optimized,
+        segment_seconds: options.segment_seconds,
+        max_width: options.max_width,
+        max_fps: options.max_fps,
+        png_16bit: options.png_16bit,
+        threads: options.threads,
+        audio: None,
+        chapters: None,
+    };
+
+    // Playlists/manifests need the right extension for players to recognize them
+    // (image sequences write into a directory, so the path is used as-is)
+    let output = if !full_render
+        || options.format.is_image_sequence()
+        || output
+            .extension()
+            .is_some_and(|ext| ext == options.format.extension())
+    {
+        output.to_path_buf()
+    } else {
+        let adjusted = output.with_extension(options.format.extension());
+        status!(
+            reporter,
+            "Note: writing {:?} output to {}",
+            options.format,
+            adjusted.display()
+        );
+        adjusted
+    };
+    let output = output.as_path();
+
+    reporter.stage("preparing");
+    status!(reporter, "Processing video: {}", input.display());
+    status!(
+        reporter,
+        "  Source: {:?} ({}x{})",
+        metadata.source_type,
+        metadata.width,
+        metadata.height
+    );
+    if options.vertical {
+        status!(
+            reporter,
+            "  Output: {}x{} (reframed)",
+            VERTICAL_WIDTH,
+            VERTICAL_HEIGHT
+        );
+    } else {
+        status!(reporter, "  Output: {}x{}", OUTPUT_WIDTH, OUTPUT_HEIGHT);
+    }
+    status!(
+        reporter,
+        "  Cursor events: {}",
+        metadata.cursor_events.len()
+    );
+    if let Some(ref config) = cursor_config {
+        status!(
+            reporter,
+            "  Cursor: scale={:.1}x, timeout={:.1}s",
+            config.cursor_scale,
+            config.inactivity_timeout
+        );
+    } else {
+        status!(reporter, "  Cursor: disabled");
+    }
+    if options.no_zoom {
+        status!(reporter, "  Zoom: disabled");
+    } else if options.follow_cursor {
+        status!(reporter, "  Zoom: following the cursor");
+    }
+    status!(
+        reporter,
+        "  Motion blur: {}",
+        match (motion_blur_config.enabled, motion_blur_config.mode) {
+            (false, _) => "disabled",
+            (true, MotionBlurMode::Screen) => "enabled",
+            (true, MotionBlurMode::Temporal) => "temporal",
+        }
+    );
+    status!(
+        reporter,
+        "  Click highlight: {}",
+        if click_highlight_config.enabled {
+            "enabled"
+        } else {
+            "disabled"
+        }
+    );
+    if let Some(ref config) = click_callout_config {
+        status!(
+            reporter,
+            "  Click callouts: {:?} ({} target bounds recorded)",
+            config.style,
+            metadata.click_targets.len()
+        );
+    }
+    if let Some(ref renderer) = annotation_renderer {
+        status!(reporter, "  Annotations: {}", renderer.len());
+    }
+    if let Some(ref subtitles) = subtitles {
+        status!(reporter, "  Subtitles: {}", subtitles.len());
+    }
+    if let Some(overlay) = options.overlay {
+        status!(reporter, "  Overlay: {:?}", overlay);
+    }
+
+    // Get video duration
+    let original_duration = get_video_duration(video)?;
+    status!(reporter, "  Original duration: {:.2}s", original_duration);
+
+    // Calculate timestamp offset for synchronization
+    // If cursor tracking ran longer than video, cursor events are ahead
+    let base_time_offset = if metadata.cursor_tracking_duration > 0.0 {
+        metadata.cursor_tracking_duration - original_duration
+    } else {
+        0.0 // Old recordings without this field
+    };
+    tracing::debug!(
+        "Cursor tracking ran {:.3}s against {:.3}s of video: base time offset {:.3}s",
+        metadata.cursor_tracking_duration,
+        original_duration,
+        base_time_offset
+    );
+
+    // Calculate trim parameters
+    let (trim_start_secs, trim_end_secs) = if options.auto_trim {
+        match auto_trim(&metadata.cursor_events, base_time_offset, original_duration) {
+            Some(trim) => trim,
+            None => {
+                status!(
+                    reporter,
+                    "  Auto-trim: no activity recorded, keeping the whole video"
+                );
+                (0.0, 0.0)
+            }
+        }
+    } else {
+        (
+            options.trim_start.unwrap_or(0.0).max(0.0),
+            options.trim_end.unwrap_or(0.0).max(0.0),
+        )
+    };
+    let trimmed_duration = (original_duration - trim_start_secs - trim_end_secs).max(0.0);
+
+    if trimmed_duration <= 0.0 {
+        anyhow::bail!(
+            "Trim values ({:.2}s + {:.2}s = {:.2}s) exceed video duration ({:.2}s)",
+            trim_start_secs,
+            trim_end_secs,
+            trim_start_secs + trim_end_secs,
+            original_duration
+        );
+    }
+
+    if trim_start_secs > 0.0 || trim_end_secs > 0.0 {
+        status!(
+            reporter,
+            "  Trimming: {:.2}s from start, {:.2}s from end",
+            trim_start_secs,
+            trim_end_secs
+        );
+        status!(reporter, "  Trimmed duration: {:.2}s", trimmed_duration);
+    }
+
+    // Carry recorded audio (e.g. --mic) over, trimmed like the frames
+    if has_audio(video) {
+        status!(reporter, "  Audio: kept from the recording");
+        encode_options.audio = Some(AudioSource {
+            path: video.to_path_buf(),
+            start: trim_start_secs,
+            duration: trimmed_duration,
+        });
+    }
+
+    // Scratch space for audio and chapters; frames are streamed, never written
+    let temp_dir = TempDir::new().context("Failed to create temp directory")?;
+    let work_dir = temp_dir.path();
+
+    if auto_blur {
+        let first_frame_path = work_dir.join("first_frame.png");
+        extract_frame_at(video, trim_start_secs, &first_frame_path)?;
+        let first_frame = image::open(&first_frame_path)
+            .context("Failed to read the first frame for the background")?;
+        bg = Background::blurred(&redactor.apply(&first_frame, 0.0));
+        status!(reporter, "  Background: blurred first frame");
+    }
+
+    let source_fps = get_video_fps(video)
+        .ok()
+        .filter(|fps| fps.is_finite() && *fps > 0.0)
+        .unwrap_or(30.0); // fallback
+    status!(reporter, "  Source FPS: {:.2}", source_fps);
+
+    // Cursor events need to be shifted by trim_start too, since we're
+    // starting from a later point in the video
+    let time_offset = base_time_offset + trim_start_secs;
+    tracing::debug!(
+        "Time offset {:.3}s (base {:.3}s + trim start {:.3}s)",
+        time_offset,
+        base_time_offset,
+        trim_start_secs
+    );
+
+    if base_time_offset.abs() > 0.01 {
+        status!(
+            reporter,
+            "  Time offset: {:.3}s (cursor tracking started before video)",
+            base_time_offset
+        );
+    }
+
+    let timeline = match options.speed_up_idle {
+        Some(speed) => {
+            let timeline = plan_speed_up(
+                &metadata.cursor_events,
+                time_offset,
+                trimmed_duration,
+                options.idle_threshold,
+                speed,
+            );
+            status!(
+                reporter,
+                "  Idle speed-up: {} stretch(es) at {}x ({:.1}s -> {:.1}s)",
+                timeline.sped_up(),
+                speed,
+                trimmed_duration,
+                timeline.output_duration()
+            );
+            timeline
+        }
+        None => Timeline::constant(trimmed_duration),
+    };
+    if options.cut_between_markers {
+        let marked = marker_cuts(&metadata.markers, base_time_offset);
+        status!(
+            reporter,
+            "  Marker cuts: {} ({} markers recorded)",
+            marked.len(),
+            metadata.markers.len()
+        );
+        cuts.extend(marked);
+    }
+    let timeline = if cuts.is_empty() {
+        timeline
+    } else {
+        let shifted: Vec<_> = cuts.iter().map(|c| c.shifted(trim_start_secs)).collect();
+        let cut = timeline.cut(&shifted);
+        status!(
+            reporter,
+            "  Cuts: {} ({:.1}s removed)",
+            cuts.len(),
+            timeline.output_duration() - cut.output_duration()
+        );
+        cut
+    };
+    let output_duration = timeline.output_duration();
+    if options.fast && full_render {
+        if timeline.is_retimed() {
+            anyhow::bail!("--fast can't be combined with --cut, --cuts or --speed-up-idle");
+        }
+        if options.format.is_image_sequence() {
+            anyhow::bail!("--fast can't write image sequences");
+        }
+        if !redactor.is_empty() || options.auto_redact {
+            anyhow::bail!(
+                "--fast can't redact, leave it out when using --blur-region or --auto-redact"
+            );
+        }
+    }
+    if output_duration <= 0.0 {
+        anyhow::bail!("Nothing left of the video after the cuts");
+    }
+    if let Some(at) = preview_at.filter(|at| *at >= output_duration) {
+        anyhow::bail!(
+            "--at {:.2}s is past the end of the output ({:.2}s)",
+            at,
+            output_duration
+        );
+    }
+    if let Some(audio) = encode_options.audio.take_if(|_| timeline.is_retimed()) {
+        if timeline.sped_up() > 0 {
+            status!(
+                reporter,
+                "  Audio: dropped, it can't follow the sped-up video"
+            );
+        } else {
+            encode_options.audio = Some(cut_audio(
+                &audio,
+                &timeline.kept(),
+                &work_dir.join("cut.wav"),
+            )?);
+            status!(reporter, "  Audio: cut to match");
+        }
+    }
+    // A preview OCRs just its own frame, below
+    if options.auto_redact && full_render {
+        reporter.stage("redacting");
+        status!(reporter, "  Auto-redact: reading text in frames...");
+        let found = detect_secrets(video, trim_start_secs, &timeline, work_dir)?;
+        status!(
+            reporter,
+            "  Auto-redact: {} region(s) of likely secrets",
+            found.len()
+        );
+        redactor.regions.extend(found);
+    }
+
+    // Events on the output's clock, for what's timed by the output
+    let output_events = timeline.retime(&metadata.cursor_events, time_offset);
+
+    let target_fps = options.output_fps.resolve(source_fps);
+    let output_frame_count = (output_duration * target_fps).ceil() as usize;
+    status!(
+        reporter,
+        "  Output: {} frames at {:.0}fps",
+        output_frame_count,
+        target_fps
+    );
+
+    if let Some(music) = options.music.as_ref().filter(|_| full_render) {
+        if options.format.is_image_sequence() || options.format.is_animated_image() {
+            status!(
+                reporter,
+                "  Music: skipped, {:?} output has no audio",
+                options.format
+            );
+        } else {
+            let ducked = encode_options.audio.is_some();
+            encode_options.audio = Some(write_music_bed(
+                &work_dir.join("music.wav"),
+                music,
+                options.music_volume,
+                output_duration,
+                encode_options.audio.as_ref(),
+            )?);
+            status!(
+                reporter,
+                "  Music: {}{}",
+                music.display(),
+                if ducked {
+                    ", ducked under the recording"
+                } else {
+                    ""
+                }
+            );
+        }
+    }
+
+    if options.click_sound && full_render {
+        if options.format.is_image_sequence() || options.format.is_animated_image() {
+            status!(
+                reporter,
+                "  Click sounds: skipped, {:?} output has no audio",
+                options.format
+            );
+        } else {
+            let (audio, clicks) = write_click_track(
+                &work_dir.join("audio.wav"),
+                &output_events,
+                time_offset,
+                output_duration,
+                encode_options.audio.as_ref(),
+            )?;
+            status!(reporter, "  Click sounds: {}", clicks);
+            encode_options.audio = Some(audio);
+        }
+    }
+
+    let (points_width, points_height) = metadata.size_in_points();
+    let zoom_config = options
+        .zoom
+        .apply(if options.reduced_motion {
+            status!(reporter, "  Reduced motion: enabled");
+            ZoomConfig::reduced_motion()
+        } else {
+            ZoomConfig::default()
+        })
+        .fit_to(points_width, points_height);
+    // The clicks that start zooms, for the overlays that follow them
+    let clicks = effective_clicks(&metadata.cursor_events, &zoom_config);
+    if tracing::enabled!(tracing::Level::DEBUG) {
+        tracing::debug!(
+            "{} effective click(s) start zooms: {}",
+            clicks.len(),
+            clicks
+                .iter()
+                .map(|click| format!("{:.3}s", click.timestamp - time_offset))
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+    }
+
+    // The source's edit sidecar, even for bundles
+    let camera_options = options.camera_options();
+    let camera_events = camera_events(source, &metadata, &zoom_config, camera_options, reporter)?;
+
+    if options.step_numbers && full_render {
+        let steps_path = output.with_extension("steps.txt");
+        let count = write_step_list(
+            &steps_path,
+            &clicks,
+            &timeline,
+            time_offset,
+            output_duration,
+        )?;
+        status!(
+            reporter,
+            "  Steps: {} ({} steps)",
+            steps_path.display(),
+            count
+        );
+    }
+
+    let chapters = if !full_render {
+        Vec::new()
+    } else if options.auto_chapters {
+        let clicks = effective_clicks(&output_events, &zoom_config);
+        click_chapters(&clicks, time_offset, output_duration)
+    } else if options.marker_chapters {
+        marker_chapters(&metadata.markers, &timeline, time_offset, output_duration)
+    } else {
+        options.chapters.clone()
+    };
+    if !chapters.is_empty() {
+        if options.format.is_image_sequence()
+            || options.format.is_animated_image()
+            || matches!(options.format, OutputFormat::Hls | OutputFormat::Dash)
+        {
+            status!(
+                reporter,
+                "  Chapters: skipped, {:?} output has no chapters",
+                options.format
+            );
+        } else {
+            let path = work_dir.join("chapters.txt");
+            let count = write_chapters(&path, &chapters, output_duration)?;
+            status!(reporter, "  Chapters: {}", count);
+            encode_options.chapters = Some(path);
+        }
+    }
+
+    let follow_camera = options.follow_cursor.then(|| {
+        FollowCamera::plan(
+            &metadata.cursor_events,
+            &zoom_config,
+            time_offset,
+            time_offset + trimmed_duration,
+        )
+    });
+    // Content and canvas are the same size for every frame
+    let layout = if options.vertical {
+        ContentLayout::reframed(metadata.width, metadata.height, frame_style.padding)
+    } else {
+        ContentLayout::calculate(metadata.width, metadata.height, frame_style.padding)
+    };
+    let bg = bg.fit(layout.canvas_width, layout.canvas_height);
+
+    // Wide content on a tall output: plan which part of it is in view
+    let reframe_camera = (layout.canvas_width > VERTICAL_WIDTH).then(|| {
+        let points_per_pixel = 1.0 / (layout.scale * metadata.scale_factor.max(1.0));
+        ReframeCamera::plan(
+            &metadata.cursor_events,
+            &zoom_config,
+            VERTICAL_WIDTH as f64 * points_per_pixel,
+            time_offset,
+            time_offset + trimmed_duration,
+        )
+    });
+
+    let camera = Camera::new(camera_options, &camera_events, follow_camera.as_ref());
+
+    if let Some(animation_path) = options.export_animation.as_ref().filter(|_| full_render) {
+        let animation = build_animation(
+            &metadata,
+            &camera,
+            &zoom_config,
+            cursor_config.as_ref().unwrap_or(&CursorConfig::default()),
+            target_fps,
+            trim_start_secs,
+            trimmed_duration,
+            time_offset,
+        );
+        animation.save(animation_path)?;
+        status!(reporter, "  Animation: {}", animation_path.display());
+    }
+
+    // Placed once; each frame adds up the events so far
+    let heatmap = (options.overlay == Some(Overlay::Heatmap))
+        .then(|| ProgressiveHeatmap::new(&metadata, layout.scaled_width, layout.scaled_height));
+
+    let fast_forward_badge = if options.fast_forward_indicator && timeline.sped_up() > 0 {
+        Some(FastForwardBadge::new()?)
+    } else {
+        None
+    };
+
+    let gpu = if options.gpu {
+        match GpuRenderer::new() {
+            Ok(gpu) => {
+                status!(reporter, "Rendering effects on {}", gpu.name());
+                Some(gpu)
+            }
+            Err(e) => {
+                reporter.warning(format!("{:#}; rendering effects on the CPU", e));
+                None
+            }
+        }
+    } else {
+        None
+    };
+    let renderer = FrameRenderer {
+        timeline: &timeline,
+        metadata: &metadata,
+        zoom_config: &zoom_config,
+        camera: &camera,
+        background: &bg,
+        layout: &layout,
+        frame_style: &frame_style,
+        reframe: reframe_camera.as_ref(),
+        clicks: &clicks,
+        time_offset,
+        cursor_config: cursor_config.as_ref(),
+        motion_blur_config: &motion_blur_config,
+        frame_duration: 1.0 / target_fps,
+        click_highlight_config: &click_highlight_config,
+        gpu: gpu.as_ref(),
+        redactor: &redactor,
+        overlays: FrameOverlays {
+            click_callouts: click_callout_config.as_ref(),
+            steps: step_config.as_ref(),
+            spotlight: spotlight_config.as_ref(),
+            trail: trail_config.as_ref(),
+            annotations: annotation_renderer.as_ref(),
+            subtitles: subtitles.as_ref(),
+            fast_forward: fast_forward_badge.as_ref(),
+            heatmap: heatmap.as_ref(),
+        },
+    };
+
+    reporter.check_cancelled()?;
+    reporter.stage("rendering");
+    if let Some(at) = preview_at {
+        // The frame showing at `at`, through the same effects as a full render
+        let frame_path = work_dir.join("preview_source.png");
+        extract_frame_at(
+            video,
+            trim_start_secs + timeline.source_time(at),
+            &frame_path,
+        )?;
+        let mut content =
+            image::open(&frame_path).context("Failed to read the frame to preview")?;
+        if options.auto_redact {
+            let found = Redactor {
+                regions: find_secrets(&frame_path)?,
+                style: options.blur_style,
+            };
+            status!(
+                reporter,
+                "  Auto-redact: {} region(s) of likely secrets",
+                found.regions.len()
+            );
+            content = found.apply(&content, at).into_owned();
+        }
+        renderer.save_frame(at, &content, output)?;
+        status!(
+            reporter,
+            "\nPreview of {:.2}s saved to: {}",
+            at,
+            output.display()
+        );
+        reporter.done(output);
+        return Ok(());
+    }
+
+    if options.fast {
+        render_fast(
+            video,
+            output,
+            work_dir,
+            trim_start_secs,
+            trimmed_duration,
+            output_frame_count,
+            target_fps,
+            &metadata,
+            &zoom_config,
+            &camera,
+            &bg,
+            &layout,
+            &frame_style,
+            reframe_camera.as_ref(),
+            time_offset,
+            &encode_options,
+            reporter,
+        )?;
+    } else {
+        // The tall output is cut from the wide canvas
+        let output_width = if reframe_camera.is_some() {
+            VERTICAL_WIDTH
+        } else {
+            layout.canvas_width
+        };
+        // Interpolated frames are decoded at the output rate, one for each
+        // output frame. A source at least as fast has nothing to fill in.
+        let interpolate = options.interpolate && source_fps < target_fps;
+        if interpolate {
+            status!(
+                reporter,
+                "  Interpolation: {:.0}fps source to {:.0}fps",
+                source_fps,
+                target_fps
+            );
+        } else if options.interpolate {
+            status!(
+                reporter,
+                "  Interpolation: skipped, the source is already {:.0}fps",
+                source_fps
+            );
+        }
+        let decode_fps = if interpolate { target_fps } else { source_fps };
+        let frames = FrameReader::open(
+            video,
+            trim_start_secs,
+            trimmed_duration,
+            decode_fps,
+            interpolate,
+            options.threads,
+        )?;
+        let writer = FrameWriter::open(
+            output,
+            target_fps,
+            output_width,
+            layout.canvas_height,
+            &encode_options,
+            reporter,
+        )?;
+        let live_preview = if options.preview {
+            match LivePreview::open(output_width, layout.canvas_height, target_fps, reporter) {
+                Ok(preview) => Some(preview),
+                Err(e) => {
+                    reporter.warning(format!("{:#}; rendering without a preview", e));
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        // Decode, process in parallel and encode at once - zoom and cursor
+        // are sampled at every output frame, however few the source has
+        status!(
+            reporter,
+            "\nProcessing frames with zoom effects (parallel)..."
+        );
+        process_frames(
+            frames,
+            writer,
+            output_frame_count,
+            decode_fps,
+            target_fps,
+            &renderer,
+            live_preview,
+            reporter,
+        )?;
+    }
+
+    library::update(|library| library.mark_processed(source, original_duration, output));
+
+    if options.delete_source_on_success {
+        delete_source(source, output, options.format, reporter)?;
+    }
+    status!(reporter, "\nDone! Output saved to: {}", output.display());
+    reporter.done(output);
+
+    Ok(())
+}
+
+/// Remove the raw recording once the processed output checks out
+fn delete_source(
+    input: &Path,
+    output: &Path,
+    format: OutputFormat,
+    reporter: &Reporter,
+) -> Result<()> {
+    if same_file(input, output) {
+        status!(reporter, "\nKeeping source: it is also the output");
+        return Ok(());
+    }
+    if let Err(e) = verify_output(output, format) {
+        status!(
+            reporter,
+            "\nKeeping source: could not verify output ({:#})",
+            e
+        );
+        return Ok(());
+    }
+
+    let freed = library::delete_raw(input)?;
+    library::update(|library| library.mark_raw_deleted(input));
+    status!(
+        reporter,
+        "\nDeleted source recording {} ({} freed)",
+        input.display(),
+        library::format_bytes(freed)
+    );
+    Ok(())
+}
+
+/// Whether two paths name the same file, however they're spelled
+fn same_file(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+/// Optional instructional overlays drawn on each frame
+struct FrameOverlays<'a> {
+    click_callouts: Option<&'a ClickCalloutConfig>,
+    steps: Option<&'a StepConfig>,
+    spotlight: Option<&'a SpotlightConfig>,
+    trail: Option<&'a TrailConfig>,
+    annotations: Option<&'a AnnotationRenderer>,
+    subtitles: Option<&'a Subtitles>,
+    fast_forward: Option<&'a FastForwardBadge>,
+    /// Progressive cursor heatmap over the content
+    heatmap: Option<&'a ProgressiveHeatmap>,
+}
+
+/// Everything that goes into drawing an output frame
+struct FrameRenderer<'a> {
+    timeline: &'a Timeline,
+    metadata: &'a RecordingMetadata,
+    zoom_config: &'a ZoomConfig,
+    camera: &'a Camera<'a>,
+    background: &'a Background,
+    layout: &'a ContentLayout,
+    frame_style: &'a FrameStyle,
+    reframe: Option<&'a ReframeCamera>,
+    /// The clicks that start zooms, from `effective_clicks`
+    clicks: &'a [&'a CursorEvent],
+    time_offset: f64,
+    cursor_config: Option<&'a CursorConfig>,
+    motion_blur_config: &'a MotionBlurConfig,
+    /// Seconds between output frames
+    frame_duration: f64,
+    click_highlight_config: &'a ClickHighlightConfig,
+    gpu: Option<&'a GpuRenderer>,
+    redactor: &'a Redactor,
+    overlays: FrameOverlays<'a>,
+}
+
+/// What drives the camera
+pub(crate) enum Camera<'a> {
+    /// Never zooms (`--no-zoom`)
+    Still,
+    /// Zooms on these events' clicks
+    Clicks(&'a [CursorEvent]),
+    /// `--follow-cursor`
+    Follow(&'a FollowCamera),
+}
+
+impl<'a> Camera<'a> {
+    /// The camera `options` ask for. `events` come from [`camera_events`],
+    /// and `follow` is planned when following the cursor.
+    pub(crate) fn new(
+        options: CameraOptions,
+        events: &'a [CursorEvent],
+        follow: Option<&'a FollowCamera>,
+    ) -> Self {
+        match follow {
+            _ if options.no_zoom => Camera::Still,
+            Some(follow) => Camera::Follow(follow),
+            None => Camera::Clicks(events),
+        }
+    }
+
+    /// Zoom and the screen point it's around at tracking time `timestamp`,
+    /// and the crossfade it's in instead of panning, if any
+    pub(crate) fn at(
+        &self,
+        timestamp: f64,
+        zoom_config: &ZoomConfig,
+    ) -> ((f64, f64, f64), Option<Crossfade>) {
+        match self {
+            Camera::Still => ((1.0, 0.0, 0.0), None),
+            Camera::Clicks(events) => calculate_camera(timestamp, events, zoom_config),
+            Camera::Follow(follow) => (follow.at(timestamp), None),
+        }
+    }
+}
+
+/// The events the click camera zooms on: the recording's, with the zoom
+/// changes `glide edit` saved next to `source` and a zoom at each marker
+/// with `zoom_on_markers`
+pub(crate) fn camera_events(
+    source: &Path,
+    metadata: &RecordingMetadata,
+    zoom_config: &ZoomConfig,
+    options: CameraOptions,
+    reporter: &Reporter,
+) -> Result<Vec<CursorEvent>> {
+    let edits = if options.no_zoom || options.follow_cursor {
+        None
+    } else {
+        EditDecisions::load(source)?
+    };
+    let events = match edits {
+        Some(ref edits) => {
+            status!(reporter, "  Edits: {} zoom(s) changed", edits.changed());
+            edits.camera_events(&metadata.cursor_events, zoom_config)
+        }
+        None => metadata.cursor_events.clone(),
+    };
+    Ok(if options.zoom_on_markers {
+        status!(reporter, "  Marker zooms: {}", metadata.markers.len());
+        with_marker_clicks(&events, &metadata.markers)
+    } else {
+        events
+    })
+}
+
+/// Source frames kept for the output frames being processed: the ones
+/// they show, plus the newest decoded, which stands in past the video's end
+#[derive(Default)]
+struct SourceWindow {
+    frames: Vec<(usize, DynamicImage)>,
+    /// Index of the next frame to decode
+    next: usize,
+}
+
+impl SourceWindow {
+    /// Decode up to the last of `needed` (source frame indices, in order),
+    /// dropping frames that are no longer needed
+    fn advance(&mut self, needed: &[usize], source: &Receiver<Result<RgbaImage>>) -> Result<()> {
+        let newest = self.frames.pop();
+        self.frames.retain(|(i, _)| needed.binary_search(i).is_ok());
+        self.frames.extend(newest);
+
+        let last = needed.last().copied().unwrap_or(0);
+        while self.next <= last {
+            // The video can end a frame or two early
+            let Ok(frame) = source.recv() else {
+                break;
+            };
+            if self
+                .frames
+                .last()
+                .is_some_and(|(i, _)| needed.binary_search(i).is_err())
+            {
+                self.frames.pop();
+            }
+            self.frames
+                .push((self.next, DynamicImage::ImageRgba8(frame?)));
+            self.next += 1;
+        }
+        if self.frames.is_empty() {
+            anyhow::bail!("The video has no frames");
+        }
+        Ok(())
+    }
+
+    /// The frame showing at source frame `index` (nearest neighbor)
+    fn get(&self, index: usize) -> &DynamicImage {
+        let i = self.frames.partition_point(|(j, _)| *j <= index);
+        &self.frames[i.saturating_sub(1)].1
+    }
+}
+
+/// Output frames to process at once: a couple per worker thread, fewer if
+/// that many source frames, decoded ahead and held, don't fit the budget
+fn batch_size(threads: usize, frame_bytes: usize) -> usize {
+    // A batch being processed and up to a batch decoded ahead
+    let fits = SOURCE_FRAME_BUDGET / (2 * frame_bytes.max(1));
+    (threads.max(1) * 2).min(fits).max(1)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn process_frames(
+    source: FrameReader,
+    mut writer: FrameWriter,
+    output_frame_count: usize,
+    source_fps: f64,
+    target_fps: f64,
+    renderer: &FrameRenderer,
+    live_preview: Option<LivePreview>,
+    reporter: &Reporter,
+) -> Result<()> {
+    let progress = reporter.frames(output_frame_count);
+
+    // Output frames are processed a batch at a time, a couple per worker
+    // thread, so memory stays flat however long the recording is
+    let batch_size = batch_size(rayon::current_num_threads(), source.frame_bytes());
+    let source_index = |output_frame_idx: usize| {
+        let source_time = renderer
+            .timeline
+            .source_time(output_frame_idx as f64 / target_fps);
+        (source_time * source_fps).floor() as usize
+    };
+
+    std::thread::scope(|scope| -> Result<()> {
+        // Decoding runs up to a batch ahead of processing, encoding a batch behind
+        let (frame_tx, frame_rx) = mpsc::sync_channel(batch_size);
+        scope.spawn(move || {
+            for frame in source {
+                if frame_tx.send(frame).is_err() {
+                    break;
+                }
+            }
+        });
+        let (batch_tx, batch_rx) = mpsc::sync_channel::<Vec<RgbaImage>>(1);
+        let encoder = scope.spawn(move || -> Result<()> {
+            for frames in batch_rx {
+                writer.write(frames)?;
+            }
+            writer.finish()
+        });
+
+        let mut window = SourceWindow::default();
+        for start in (0..output_frame_count).step_by(batch_size) {
+            reporter.check_cancelled()?;
+            let batch = start..(start + batch_size).min(output_frame_count);
+            let mut needed: Vec<_> = batch.clone().map(source_index).collect();
+            needed.dedup();
+            window.advance(&needed, &frame_rx)?;
+            let window = &window;
+
+            // Process this batch in parallel
+            let frames = batch
+                .clone()
+                .into_par_iter()
+                .map(|output_frame_idx| {
+                    let timestamp = output_frame_idx as f64 / target_fps;
+                    renderer.render(timestamp, window.get(source_index(output_frame_idx)))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            progress.set(batch.end);
+            if let Some(ref preview) = live_preview {
+                for frame in &frames {
+                    preview.show(frame);
+                }
+            }
+
+            // Only fails if encoding stopped, which the encoder reports
+            if batch_tx.send(frames).is_err() {
+                break;
+            }
+        }
+        drop(batch_tx);
+        encoder.join().expect("encoding thread panicked")
+    })?;
+    if let Some(preview) = live_preview {
+        preview.close();
+    }
+
+    Ok(())
+}
+
+impl FrameRenderer<'_> {
+    /// The camera at output time `timestamp`: its zoom and the point on the
+    /// canvas it zooms around, and the crossfade it's in instead of
+    /// panning, if any
+    fn camera_at(&self, timestamp: f64) -> ((f64, f64, f64), Option<Crossfade>) {
+        let &FrameRenderer {
+            timeline,
+            metadata,
+            zoom_config,
+            camera,
+            layout,
+            time_offset,
+            ..
+        } = self;
+        let adjusted_timestamp = timeline.source_time(timestamp) + time_offset;
+
+        let ((zoom, cursor_x, cursor_y), crossfade) = camera.at(adjusted_timestamp, zoom_config);
+
+        // Screen points -> pixels in the window -> canvas space
+        let scale_factor = metadata.scale_factor.max(1.0);
+        let window_offset = metadata.window_offset_at(adjusted_timestamp);
+        let window_cursor_x = (cursor_x - window_offset.0) * scale_factor;
+        let window_cursor_y = (cursor_y - window_offset.1) * scale_factor;
+        let canvas_cursor_x = layout.offset_x as f64 + window_cursor_x * layout.scale;
+        let canvas_cursor_y = layout.offset_y as f64 + window_cursor_y * layout.scale;
+
+        // Ken Burns drift while the click camera is idle
+        let camera = match (zoom_config.idle_drift, camera) {
+            (Some(drift), Camera::Clicks(_)) if crossfade.is_none() => drift.apply(
+                (zoom, canvas_cursor_x, canvas_cursor_y),
+                timestamp,
+                zoom_config.max_zoom,
+                layout.bounds(),
+            ),
+            _ => (zoom, canvas_cursor_x, canvas_cursor_y),
+        };
+        (camera, crossfade)
+    }
+
+    /// The cameras to average for a temporal motion blur of the frame at
+    /// `timestamp`, or none if that's off or the camera is still
+    fn sub_frame_cameras(&self, timestamp: f64) -> Vec<(f64, f64, f64)> {
+        let config = self.motion_blur_config;
+        if !config.enabled
+            || config.mode != MotionBlurMode::Temporal
+            || !matches!(self.camera, Camera::Clicks(_))
+        {
+            return Vec::new();
+        }
+        let cameras: Vec<_> = config
+            .sub_frame_offsets(self.frame_duration)
+            .into_iter()
+            .map(|offset| self.camera_at((timestamp + offset).max(0.0)).0)
+            .collect();
+        // Less than a pixel or a thousandth of a zoom level of movement
+        // wouldn't show, and nor would panning while zoomed out
+        let (zoom, x, y) = cameras[0];
+        let moving = cameras.iter().any(|&(z, cx, cy)| {
+            (z - zoom).abs() > 0.001 || (cx - x).abs() > 0.5 || (cy - y).abs() > 0.5
+        });
+        if moving && cameras.iter().any(|&(z, ..)| z > 1.01) {
+            cameras
+        } else {
+            Vec::new()
+        }
+    }
+
+    /// The output frame at `timestamp` showing `content`, as a PNG
+    fn save_frame(&self, timestamp: f64, content: &DynamicImage, output: &Path) -> Result<()> {
+        self.render(timestamp, content)?
+            .save_with_format(output, image::ImageFormat::Png)
+            .with_context(|| format!("Failed to write {}", output.display()))
+    }
+
+    /// The output frame at `timestamp` (output time), showing `content`
+    fn render(&self, timestamp: f64, content: &DynamicImage) -> Result<RgbaImage> {
+        let &FrameRenderer {
+            timeline,
+            metadata,
+            zoom_config,
+            camera,
+            background,
+            layout,
+            frame_style,
+            reframe,
+            clicks,
+            time_offset,
+            cursor_config,
+            motion_blur_config,
+            click_highlight_config,
+            gpu,
+            redactor,
+            ref overlays,
+            ..
+        } = self;
+
+        // The video time this output frame shows
+        let source_time = timeline.source_time(timestamp);
+
+        // Add time_offset to align cursor timestamps with video timestamps
+        let adjusted_timestamp = source_time + time_offset;
+
+        // Create canvas with background
+        let mut canvas =
+            background.create_canvas(timestamp, layout.canvas_width, layout.canvas_height)?;
+
+        // Draw shadow first (before content)
+        if let Some(shadow) = &frame_style.shadow {
+            draw_shadow(
+                &mut canvas,
+                layout.offset_x as i64,
+                layout.offset_y as i64,
+                layout.scaled_width,
+                layout.scaled_height,
+                frame_style.corner_radius,
+                shadow,
+            );
+        }
+
+        // Hide redacted regions before anything can zoom in on them
+        let content = redactor.apply(content, timestamp);
+
+        // Scale content to fit (use Lanczos3 for sharp, high-quality results)
+        let scaled_content = content.resize_exact(
+            layout.scaled_width,
+            layout.scaled_height,
+            image::imageops::FilterType::Lanczos3,
+        );
+
+        // Apply rounded corners to content
+        let mut rounded_content = scaled_content.to_rgba8();
+
+        // The heatmap covers everything the cursor has done so far
+        if let Some(heatmap) = overlays.heatmap {
+            let layer = heatmap.at(adjusted_timestamp).render(
+                layout.scaled_width,
+                layout.scaled_height,
+                OVERLAY_OPACITY,
+            );
+            image::imageops::overlay(&mut rounded_content, &layer, 0, 0);
+        }
+        apply_rounded_corners(&mut rounded_content, frame_style.corner_radius);
+
+        // Overlay content on canvas
+        let (content_x, content_y) = (layout.offset_x as i64, layout.offset_y as i64);
+        match gpu {
+            Some(gpu) => gpu.composite(&mut canvas, &rounded_content, content_x, content_y)?,
+            None => image::imageops::overlay(&mut canvas, &rounded_content, content_x, content_y),
+        }
+
+        // Calculate zoom for this frame
+        let ((zoom, canvas_cursor_x, canvas_cursor_y), crossfade) = self.camera_at(timestamp);
+
+        // Get scale factor for coordinate conversion (screen points -> pixels)
+        // CGEventTap returns screen points, but video is captured at pixel resolution
+        let scale_factor = metadata.scale_factor.max(1.0);
+
+        // The window's offset on screen, in pixels. It follows the window
+        // if it was moved while recording.
+        let window_offset = metadata.window_offset_at(adjusted_timestamp);
+        let offset_x_scaled = window_offset.0 * scale_factor;
+        let offset_y_scaled = window_offset.1 * scale_factor;
+
+        // Screen points -> canvas space
+        let to_canvas = |x: f64, y: f64| {
+            (
+                layout.offset_x as f64 + (x * scale_factor - offset_x_scaled) * layout.scale,
+                layout.offset_y as f64 + (y * scale_factor - offset_y_scaled) * layout.scale,
+            )
+        };
+
+        // Draw click callouts under the cursor
+        if let Some(callout_cfg) = overlays.click_callouts {
+            let callouts: Vec<_> =
+                get_active_callouts(adjusted_timestamp, metadata, clicks, callout_cfg)
+                    .into_iter()
+                    .map(|c| {
+                        let (x, y, w, h) = c.bounds;
+                        let (cx, cy) = to_canvas(x, y);
+                        let scale = scale_factor * layout.scale;
+                        ActiveCallout {
+                            bounds: (cx, cy, w * scale, h * scale),
+                            progress: c.progress,
+                        }
+                    })
+                    .collect();
+            draw_click_callouts(&mut canvas, &callouts, callout_cfg);
+        }
+
+        // Step badges ride along with the content, like callouts
+        if let Some(step_cfg) = overlays.steps {
+            let steps: Vec<_> = get_active_steps(adjusted_timestamp, clicks, step_cfg)
+                .into_iter()
+                .map(|step| {
+                    let (x, y) = to_canvas(step.x, step.y);
+                    ActiveStep { x, y, ..step }
+                })
+                .collect();
+            draw_step_badges(&mut canvas, &steps, step_cfg);
+        }
+
+        // Spotlight under the cursor, fading out when it does
+        if let Some(spotlight_cfg) = overlays.spotlight {
+            let cursor_state = get_smoothed_cursor(
+                adjusted_timestamp,
+                &metadata.cursor_events,
+                cursor_config.unwrap_or(&CursorConfig::default()),
+            );
+            let (x, y) = to_canvas(cursor_state.x, cursor_state.y);
+            draw_spotlight(&mut canvas, x, y, cursor_state.opacity, spotlight_cfg);
+        }
+
+        // Draw cursor if enabled
+        if let Some(cursor_cfg) = cursor_config {
+            let cursor_state =
+                get_smoothed_cursor(adjusted_timestamp, &metadata.cursor_events, cursor_cfg);
+
+            // Trail first, so the cursor sits on top of it
+            if let Some(trail_cfg) = overlays.trail {
+                let points: Vec<_> = get_trail_points(
+                    adjusted_timestamp,
+                    &metadata.cursor_events,
+                    cursor_cfg,
+                    trail_cfg,
+                )
+                .into_iter()
+                .map(|p| {
+                    let (x, y) = to_canvas(p.x, p.y);
+                    TrailPoint { x, y, ..p }
+                })
+                .collect();
+                draw_trail(
+                    &mut canvas,
+                    &points,
+                    cursor_cfg.cursor_scale * layout.scale,
+                    trail_cfg,
+                );
+            }
+
+            if cursor_state.opacity > 0.01 {
+                // Transform smoothed cursor coordinates to canvas space
+                // Apply scale_factor to convert from screen points to pixels
+                let smoothed_canvas_x = layout.offset_x as f64
+                    + (cursor_state.x * scale_factor - offset_x_scaled) * layout.scale;
+                let smoothed_canvas_y = layout.offset_y as f64
+                    + (cursor_state.y * scale_factor - offset_y_scaled) * layout.scale;
+
+                draw_cursor(
+                    &mut canvas,
+                    smoothed_canvas_x,
+                    smoothed_canvas_y,
+                    cursor_cfg.cursor_scale * layout.scale,
+                    cursor_state.opacity,
+                    get_cursor_shape(adjusted_timestamp, &metadata.cursor_events),
+                    &cursor_cfg.appearance,
+                );
+            }
+        }
+
+        // Draw click highlights if enabled
+        if click_highlight_config.enabled {
+            let ripples = get_active_ripples(
+                adjusted_timestamp,
+                &metadata.cursor_events,
+                click_highlight_config,
+            );
+
+            // Transform ripples to canvas space
+            let canvas_ripples: Vec<_> = ripples
+                .iter()
+                .map(|r| {
+                    // Transform from screen points to canvas space
+                    let ripple_canvas_x = layout.offset_x as f64
+                        + (r.x * scale_factor - offset_x_scaled) * layout.scale;
+                    let ripple_canvas_y = layout.offset_y as f64
+                        + (r.y * scale_factor - offset_y_scaled) * layout.scale;
+                    crate::processing::click_highlight::ActiveRipple {
+                        x: ripple_canvas_x,
+                        y: ripple_canvas_y,
+                        progress: r.progress,
+                        kind: r.kind,
+                    }
+                })
+                .collect();
+
+            // Use fixed sizes in canvas space (don't scale with content)
+            // This ensures the highlight is always visible regardless of content scale
+            draw_click_highlights(&mut canvas, &canvas_ripples, click_highlight_config);
+        }
+
+        let zoom_bounds = zoom_config.clamp_to_content.then(|| layout.bounds());
+
+        // Center of the reframed column on the canvas, kept on the content
+        let reframe_x = reframe.map(|r| {
+            let half = VERTICAL_WIDTH as f64 / 2.0;
+            let (left, _, width, _) = layout.bounds();
+            match r.at(adjusted_timestamp) {
+                Some(x) => to_canvas(x, 0.0).0.clamp(left + half, left + width - half),
+                // Nothing to follow
+                None => left + width / 2.0,
+            }
+        });
+        // ...and where that ends up once zoomed around (x, y)
+        let zoomed_x = |reframe_x: f64, zoom: f64, x: f64, y: f64| {
+            let (left, ..) = zoom_view(
+                layout.canvas_width as f64,
+                layout.canvas_height as f64,
+                zoom,
+                x,
+                y,
+                zoom_bounds,
+            );
+            (reframe_x - left) * zoom
+        };
+
+        let (zoomed_img, reframe_x) = if let Some(crossfade) = crossfade {
+            // Reduced motion: blend between the two cameras instead of panning
+            let (from_x, from_y) = to_canvas(crossfade.from.0, crossfade.from.1);
+            let (to_x, to_y) = to_canvas(crossfade.to.0, crossfade.to.1);
+            let from = zoom_canvas(
+                gpu,
+                canvas.clone(),
+                crossfade.zoom,
+                from_x,
+                from_y,
+                zoom_bounds,
+            )?;
+            let to = zoom_canvas(gpu, canvas, crossfade.zoom, to_x, to_y, zoom_bounds)?;
+            let reframe_x = reframe_x.map(|rx| {
+                let from = zoomed_x(rx, crossfade.zoom, from_x, from_y);
+                let to = zoomed_x(rx, crossfade.zoom, to_x, to_y);
+                from + (to - from) * crossfade.progress
+            });
+            let blended = DynamicImage::ImageRgba8(blend_images(
+                &from.to_rgba8(),
+                &to.to_rgba8(),
+                crossfade.progress,
+            ));
+            (blended, reframe_x)
+        } else {
+            // Tiny zooms are skipped, except while drifting, where the
+            // jump would show
+            let zooms = |zoom: f64| zoom > 1.01 || (zoom > 1.0 && zoom_config.idle_drift.is_some());
+            let zoom_to = |canvas: RgbaImage, (zoom, x, y): (f64, f64, f64)| {
+                if zooms(zoom) {
+                    zoom_canvas(gpu, canvas, zoom, x, y, zoom_bounds)
+                } else {
+                    Ok(DynamicImage::ImageRgba8(canvas))
+                }
+            };
+            let reframe_x = match reframe_x {
+                Some(rx) if zooms(zoom) => {
+                    Some(zoomed_x(rx, zoom, canvas_cursor_x, canvas_cursor_y))
+                }
+                rx => rx,
+            };
+            let cameras = self.sub_frame_cameras(timestamp);
+            let zoomed = if cameras.is_empty() {
+                zoom_to(canvas, (zoom, canvas_cursor_x, canvas_cursor_y))?
+            } else {
+                // Temporal motion blur: everywhere the camera was while
+                // the shutter was open
+                let frames = cameras
+                    .into_iter()
+                    .map(|camera| Ok(zoom_to(canvas.clone(), camera)?.to_rgba8()))
+                    .collect::<Result<Vec<_>>>()?;
+                DynamicImage::ImageRgba8(average_images(&frames))
+            };
+            (zoomed, reframe_x)
+        };
+
+        // Apply motion blur during zoom/pan transitions
+        let final_img =
+            if motion_blur_config.enabled && motion_blur_config.mode == MotionBlurMode::Screen {
+                let motion_state = calculate_motion_state(
+                    adjusted_timestamp,
+                    match camera {
+                        Camera::Clicks(events) => events,
+                        _ => &[],
+                    },
+                    zoom_config,
+                    layout,
+                    window_offset,
+                    scale_factor,
+                );
+                match plan_motion_blur(
+                    &motion_state,
+                    motion_blur_config,
+                    zoomed_img.width(),
+                    zoomed_img.height(),
+                ) {
+                    Some(blur) => {
+                        let zoomed = zoomed_img.to_rgba8();
+                        DynamicImage::ImageRgba8(match gpu {
+                            Some(gpu) => gpu.blur(&zoomed, &blur)?,
+                            None => apply_blur(&zoomed, &blur),
+                        })
+                    }
+                    None => zoomed_img,
+                }
+            } else {
+                zoomed_img
+            };
+
+        // Cut the tall output out of the wide canvas
+        let final_img = match reframe_x {
+            Some(x) => crop_column(&final_img, x, VERTICAL_WIDTH),
+            None => final_img,
+        };
+
+        // Callouts, subtitles and the fast-forward badge sit on top
+        // in output space, unaffected by zoom
+        let final_img = if overlays.annotations.is_some()
+            || overlays.subtitles.is_some()
+            || overlays.fast_forward.is_some()
+        {
+            let mut frame = final_img.to_rgba8();
+            if let Some(annotations) = overlays.annotations {
+                annotations.draw(&mut frame, timestamp);
+            }
+            if let Some(subtitles) = overlays.subtitles {
+                subtitles.draw(&mut frame, timestamp);
+            }
+            if let Some(badge) = overlays.fast_forward {
+                badge.draw(&mut frame, timeline.speed_at(timestamp));
+            }
+            DynamicImage::ImageRgba8(frame)
+        } else {
+            final_img
+        };
+
+        Ok(final_img.into_rgba8())
+    }
+}
+
+/// `--fast`: sample the camera once per output frame and leave the pixels
+/// to FFmpeg. Reduced-motion cross-fades become cuts halfway through.
+#[allow(clippy::too_many_arguments)]
+fn render_fast(
+    video: &Path,
+    output: &Path,
+    work_dir: &Path,
+    trim_start: f64,
+    duration: f64,
+    output_frame_count: usize,
+    target_fps: f64,
+    metadata: &RecordingMetadata,
+    zoom_config: &ZoomConfig,
+    camera: &Camera,
+    background: &Background,
+    layout: &ContentLayout,
+    frame_style: &FrameStyle,
+    reframe: Option<&ReframeCamera>,
+    time_offset: f64,
+    encode_options: &EncodeOptions,
+    reporter: &Reporter,
+) -> Result<()> {
+    status!(
+        reporter,
+        "\nFast mode: FFmpeg renders the zoom in one pass (no cursor, click effects, \
+         overlays or motion blur)"
+    );
+    let frame_path = work_dir.join("frame.png");
+    frame_image(background, layout, frame_style)?
+        .save(&frame_path)
+        .context("Failed to save the background frame")?;
+
+    let scale_factor = metadata.scale_factor.max(1.0);
+    let zoom_bounds = zoom_config.clamp_to_content.then(|| layout.bounds());
+    let (width, height) = (layout.canvas_width as f64, layout.canvas_height as f64);
+    let shots: Vec<_> = (0..output_frame_count)
+        .map(|frame| {
+            let timestamp = frame as f64 / target_fps;
+            let adjusted_timestamp = timestamp + time_offset;
+
+            // Screen points -> canvas space
+            let window_offset = metadata.window_offset_at(adjusted_timestamp);
+            let to_canvas = |(x, y): (f64, f64)| {
+                (
+                    layout.offset_x as f64 + (x - window_offset.0) * scale_factor * layout.scale,
+                    layout.offset_y as f64 + (y - window_offset.1) * scale_factor * layout.scale,
+                )
+            };
+
+            let ((zoom, x, y), crossfade) = camera.at(adjusted_timestamp, zoom_config);
+            let (zoom, (x, y)) = match crossfade {
+                Some(c) if c.progress < 0.5 => (c.zoom, to_canvas(c.from)),
+                Some(c) => (c.zoom, to_canvas(c.to)),
+                None => (zoom, to_canvas((x, y))),
+            };
+            let (zoom, x, y) = match (zoom_config.idle_drift, camera) {
+                (Some(drift), Camera::Clicks(_)) if crossfade.is_none() => drift.apply(
+                    (zoom, x, y),
+                    timestamp,
+                    zoom_config.max_zoom,
+                    layout.bounds(),
+                ),
+                _ => (zoom, x, y),
+            };
+
+            // Tiny zooms are skipped, as in process_frames
+            let zoomed = crossfade.is_some()
+                || zoom > 1.01
+                || (zoom > 1.0 && zoom_config.idle_drift.is_some());
+            let (zoom, left, top) = if zoomed {
+                let (left, top, ..) = zoom_view(width, height, zoom, x, y, zoom_bounds);
+                (zoom, left, top)
+            } else {
+                (1.0, 0.0, 0.0)
+            };
+
+            let column_left = reframe.map_or(0.0, |r| {
+                let half = VERTICAL_WIDTH as f64 / 2.0;
+                let (bounds_left, _, bounds_width, _) = layout.bounds();
+                let center = match r.at(adjusted_timestamp) {
+                    Some(x) => to_canvas((x, 0.0))
+                        .0
+                        .clamp(bounds_left + half, bounds_left + bounds_width - half),
+                    None => bounds_left + bounds_width / 2.0,
+                };
+                ((center - left) * zoom - half).clamp(0.0, width - VERTICAL_WIDTH as f64)
+            });
+
+            Shot {
+                zoom,
+                left,
+                top,
+                column_left,
+            }
+        })
+        .collect();
+
+    let inputs: Vec<String> = vec![
+        "-ss".into(),
+        format!("{:.3}", trim_start),
+        "-t".into(),
+        format!("{:.3}", duration),
+        "-i".into(),
+        video.to_string_lossy().into_owned(),
+        "-loop".into(),
+        "1".into(),
+        "-i".into(),
+        frame_path.to_string_lossy().into_owned(),
+    ];
+    let graph = filter_graph(
+        frames_input(encode_options),
+        layout,
+        target_fps,
+        &shots,
+        reframe.map(|_| VERTICAL_WIDTH),
+    );
+    encode_filter_graph(
+        &inputs,
+        &graph,
+        output,
+        target_fps,
+        encode_options,
+        reporter,
+    )
+}
+
+/// `canvas` zoomed as by `apply_zoom`, on the GPU if there is one
+fn zoom_canvas(
+    gpu: Option<&GpuRenderer>,
+    canvas: RgbaImage,
+    zoom: f64,
+    cursor_x: f64,
+    cursor_y: f64,
+    bounds: Option<(f64, f64, f64, f64)>,
+) -> Result<DynamicImage> {
+    let Some(gpu) = gpu else {
+        let canvas = DynamicImage::ImageRgba8(canvas);
+        return Ok(apply_zoom(&canvas, zoom, cursor_x, cursor_y, bounds));
+    };
+    let view = zoom_view(
+        canvas.width() as f64,
+        canvas.height() as f64,
+        zoom,
+        cursor_x,
+        cursor_y,
+        bounds,
+    );
+    Ok(DynamicImage::ImageRgba8(gpu.zoom(&canvas, view)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cursor_types::EventType;
+
+    const BACKGROUND: image::Rgba<u8> = image::Rgba([26, 26, 46, 255]);
+    const CONTENT: image::Rgba<u8> = image::Rgba([200, 40, 40, 255]);
+
+    /// Run `f` with a renderer for a 640x360 recording whose cursor rests
+    /// in the middle and clicks there at 2s, and a frame of it
+    fn with_renderer(f: impl FnOnce(&FrameRenderer, &DynamicImage)) {
+        let mut metadata = RecordingMetadata::new_display(0, 640, 360, 1.0);
+        metadata.cursor_events = [(0.0, EventType::Move), (2.0, EventType::LeftClick)]
+            .into_iter()
+            .map(|(timestamp, event_type)| CursorEvent {
+                x: 320.0,
+                y: 180.0,
+                timestamp,
+                event_type,
+                display: None,
+                shape: None,
+            })
+            .collect();
+        let zoom_config = ZoomConfig::default();
+        let clicks = effective_clicks(&metadata.cursor_events, &zoom_config);
+        let camera = Camera::Clicks(&metadata.cursor_events);
+        let frame_style = FrameStyle::default();
+        let layout = ContentLayout::calculate(640, 360, frame_style.padding);
+        let background = Background::parse(None, &Reporter::default()).unwrap();
+        let cursor_config = CursorConfig::new(2.0, 0.0);
+        let renderer = FrameRenderer {
+            timeline: &Timeline::constant(5.0),
+            metadata: &metadata,
+            zoom_config: &zoom_config,
+            camera: &camera,
+            background: &background,
+            layout: &layout,
+            frame_style: &frame_style,
+            reframe: None,
+            clicks: &clicks,
+            time_offset: 0.0,
+            cursor_config: Some(&cursor_config),
+            motion_blur_config: &MotionBlurConfig {
+                enabled: false,
+                ..Default::default()
+            },
+            frame_duration: 1.0 / 30.0,
+            click_highlight_config: &ClickHighlightConfig::default(),
+            gpu: None,
+            redactor: &Redactor::default(),
+            overlays: FrameOverlays {
+                click_callouts: None,
+                steps: None,
+                spotlight: None,
+                trail: None,
+                annotations: None,
+                subtitles: None,
+                fast_forward: None,
+                heatmap: None,
+            },
+        };
+        let content = DynamicImage::ImageRgba8(RgbaImage::from_pixel(640, 360, CONTENT));
+        f(&renderer, &content);
+    }
+
+    #[test]
+    fn test_render_frame() {
+        with_renderer(|renderer, content| {
+            let layout = renderer.layout;
+            let (x, y) = (layout.offset_x, layout.offset_y);
+            let frame = renderer.render(0.0, content).unwrap();
+            assert_eq!(frame.dimensions(), (OUTPUT_WIDTH, OUTPUT_HEIGHT));
+            assert_eq!(*frame.get_pixel(5, 5), BACKGROUND);
+            // The content sits in the middle, under the cursor
+            assert_eq!(*frame.get_pixel(x + 50, y + 50), CONTENT);
+            let (tip_x, tip_y) = (x + layout.scaled_width / 2, y + layout.scaled_height / 2);
+            let cursor = (0..10).any(|d| *frame.get_pixel(tip_x + d, tip_y + d) != CONTENT);
+            assert!(cursor);
+
+            // Zoomed in on the click, the content fills more of the frame
+            let zoomed = renderer.render(3.0, content).unwrap();
+            assert_ne!(
+                *zoomed.get_pixel(x - 20, y - 20),
+                *frame.get_pixel(x - 20, y - 20)
+            );
+        });
+    }
+
+    #[test]
+    fn test_preview_frame() {
+        let dir = tempfile::tempdir().unwrap();
+        let output = dir.path().join("frame.png");
+        with_renderer(|renderer, content| {
+            renderer.save_frame(1.5, content, &output).unwrap();
+            let saved = image::open(&output).unwrap().to_rgba8();
+            assert_eq!(saved, renderer.render(1.5, content).unwrap());
+        });
+
+        // Options for a whole render are refused before anything is read
+        for options in [
+            ProcessOptions {
+                fast: true,
+                ..Default::default()
+            },
+            ProcessOptions {
+                interpolate: true,
+                ..Default::default()
+            },
+        ] {
+            let error = Processor::new(options)
+                .preview(&dir.path().join("missing.mp4"), 1.0, &output)
+                .unwrap_err();
+            assert!(
+                error.to_string().contains("can't be previewed"),
+                "{}",
+                error
+            );
+        }
+    }
+
+    #[test]
+    fn test_worker_pool() {
+        assert!(worker_pool(None).unwrap().is_none());
+        // A global pool could only be configured once
+        for threads in [2, 3] {
+            let pool = worker_pool(Some(threads)).unwrap().unwrap();
+            assert_eq!(pool.install(rayon::current_num_threads), threads);
+        }
+    }
+
+    #[test]
+    fn test_same_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("demo.mp4");
+        std::fs::write(&file, b"data").unwrap();
+        let other = dir.path().join("final.mp4");
+        std::fs::write(&other, b"data").unwrap();
+
+        assert!(same_file(&file, &dir.path().join(".").join("demo.mp4")));
+        assert!(!same_file(&file, &other));
+        #[cfg(unix)]
+        {
+            let link = dir.path().join("link.mp4");
+            std::os::unix::fs::symlink(&file, &link).unwrap();
+            assert!(same_file(&file, &link));
+        }
+    }
+
+    #[test]
+    fn test_source_window() {
+        let (tx, rx) = mpsc::sync_channel(16);
+        for i in 0..6u8 {
+            tx.send(Ok(RgbaImage::from_pixel(1, 1, image::Rgba([i, 0, 0, 255]))))
+                .unwrap();
+        }
+        drop(tx);
+        let shade = |window: &SourceWindow, i| window.get(i).to_rgba8().get_pixel(0, 0)[0];
+
+        let mut window = SourceWindow::default();
+        window.advance(&[0, 1], &rx).unwrap();
+        assert_eq!(shade(&window, 1), 1);
+
+        // Skipped frames (cut, or sped past) aren't kept
+        window.advance(&[1, 4], &rx).unwrap();
+        assert_eq!(window.frames.len(), 2);
+        assert_eq!((shade(&window, 1), shade(&window, 4)), (1, 4));
+
+        // Past the end, the last frame stands in
+        window.advance(&[5, 8], &rx).unwrap();
+        assert_eq!((shade(&window, 5), shade(&window, 8)), (5, 5));
+    }
+
+    #[test]
+    fn test_batch_size_fits_budget() {
+        let hd = 1920 * 1080 * 4;
+        assert_eq!(batch_size(8, hd), 16);
+        // 4K frames on a big machine are held to the budget
+        let uhd = 3840 * 2160 * 4;
+        let batch = batch_size(64, uhd);
+        assert!(batch < 128 && 2 * batch * uhd <= SOURCE_FRAME_BUDGET);
+        // Always at least one, however big
+        assert_eq!(batch_size(4, SOURCE_FRAME_BUDGET), 1);
+    }
+}
@@ -0,0 +1,229 @@
+//! How `glide process` reports what it's doing: a log, and events the CLI
+//! draws its progress bar from or, with `--progress json`, writes to stdout
+//! one per line, moving the log to stderr so stdout holds nothing else:
+//!
+//! ```text
+//! {"event":"stage","stage":"rendering"}
+//! {"event":"progress","stage":"rendering","frame":120,"total":3600,"eta":41.5}
+//! {"event":"warning","message":"no GPU adapter found; rendering effects on the CPU"}
+//! {"event":"done","output":"final.mp4"}
+//! ```
+//!
+//! Apps embedding glide get the same events through a callback.
+
+use crate::cancel::CancelToken;
+use anyhow::Result;
+use serde::Serialize;
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Instant;
+
+/// How progress is reported
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum ProgressFormat {
+    /// A log, and a progress bar in the CLI
+    #[default]
+    Bar,
+    /// One JSON event per line on stdout, with the log on stderr
+    Json,
+    /// Nothing but warnings, on stderr
+    Quiet,
+}
+
+/// An info line for the log, left out when `reporter` is quiet. Where it
+/// goes is up to the `tracing` subscriber; the CLI's moves it to stderr
+/// with `--progress json`.
+macro_rules! status {
+    ($reporter:expr, $($arg:tt)*) => {
+        if !$reporter.is_quiet() {
+            tracing::info!($($arg)*)
+        }
+    };
+}
+pub(crate) use status;
+
+/// What a render is up to, for `--progress json` and
+/// [`Processor::on_progress`](crate::Processor::on_progress)
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum ProgressEvent {
+    /// A new stage of the work has started: `unpacking`, `decrypting`,
+    /// `preparing` or `rendering`
+    Stage {
+        stage: String,
+    },
+    Progress {
+        stage: String,
+        frame: usize,
+        total: usize,
+        /// Seconds left, once there's a rate to go by
+        eta: Option<f64>,
+    },
+    /// Something went wrong that the work carries on past
+    Warning {
+        message: String,
+    },
+    /// The work finished and wrote `output`
+    Done {
+        output: PathBuf,
+    },
+    Error {
+        message: String,
+    },
+}
+
+/// Called with each event as it happens
+pub type ProgressCallback = Arc<dyn Fn(&ProgressEvent) + Send + Sync>;
+
+/// How a render reports, where its events go, and whether it should stop
+#[derive(Default)]
+pub struct Reporter {
+    format: ProgressFormat,
+    callback: Option<ProgressCallback>,
+    cancel: Option<CancelToken>,
+}
+
+impl Reporter {
+    pub fn new(
+        format: ProgressFormat,
+        callback: Option<ProgressCallback>,
+        cancel: Option<CancelToken>,
+    ) -> Self {
+        Self {
+            format,
+            callback,
+            cancel,
+        }
+    }
+
+    /// Whether the log is left out
+    pub fn is_quiet(&self) -> bool {
+        self.format == ProgressFormat::Quiet
+    }
+
+    fn emit(&self, event: ProgressEvent) {
+        if let Some(callback) = &self.callback {
+            callback(&event);
+        }
+    }
+
+    pub fn stage(&self, stage: &str) {
+        self.emit(ProgressEvent::Stage {
+            stage: stage.to_string(),
+        });
+    }
+
+    pub fn warning(&self, message: impl Display) {
+        if self.format != ProgressFormat::Json {
+            tracing::warn!("{}", message);
+        }
+        self.emit(ProgressEvent::Warning {
+            message: message.to_string(),
+        });
+    }
+
+    pub fn done(&self, output: &Path) {
+        self.emit(ProgressEvent::Done {
+            output: output.to_path_buf(),
+        });
+    }
+
+    pub fn error(&self, error: &anyhow::Error) {
+        self.emit(ProgressEvent::Error {
+            message: format!("{:#}", error),
+        });
+    }
+
+    /// Fail once the work has been cancelled
+    pub fn check_cancelled(&self) -> Result<()> {
+        if self.cancel.as_ref().is_some_and(CancelToken::is_cancelled) {
+            anyhow::bail!("Cancelled");
+        }
+        Ok(())
+    }
+
+    /// Progress through `total` frames, as `progress` events
+    pub fn frames(&self, total: usize) -> FrameProgress<'_> {
+        FrameProgress {
+            reporter: self,
+            total,
+            started: Instant::now(),
+        }
+    }
+}
+
+/// Frames rendered so far
+pub struct FrameProgress<'a> {
+    reporter: &'a Reporter,
+    total: usize,
+    started: Instant,
+}
+
+impl FrameProgress<'_> {
+    /// `frame` frames are done
+    pub fn set(&self, frame: usize) {
+        self.reporter.emit(ProgressEvent::Progress {
+            stage: "rendering".to_string(),
+            frame,
+            total: self.total,
+            eta: eta(self.started.elapsed().as_secs_f64(), frame, self.total),
+        });
+    }
+}
+
+/// Seconds left for `total` frames, `frame` of them done in `elapsed`
+fn eta(elapsed: f64, frame: usize, total: usize) -> Option<f64> {
+    (frame > 0).then(|| {
+        let left = elapsed / frame as f64 * total.saturating_sub(frame) as f64;
+        (left * 10.0).round() / 10.0
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_events() {
+        assert_eq!(eta(10.0, 0, 100), None);
+        assert_eq!(eta(10.0, 25, 100), Some(30.0));
+        assert_eq!(eta(10.0, 100, 100), Some(0.0));
+
+        let json = serde_json::to_string(&ProgressEvent::Progress {
+            stage: "rendering".to_string(),
+            frame: 120,
+            total: 3600,
+            eta: Some(41.5),
+        })
+        .unwrap();
+        assert_eq!(
+            json,
+            r#"{"event":"progress","stage":"rendering","frame":120,"total":3600,"eta":41.5}"#
+        );
+        let json = serde_json::to_string(&ProgressEvent::Done {
+            output: PathBuf::from("final.mp4"),
+        })
+        .unwrap();
+        assert_eq!(json, r#"{"event":"done","output":"final.mp4"}"#);
+
+        // Callbacks see every event, and a cancelled render stops
+        let events = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let seen = Arc::clone(&events);
+        let cancel = CancelToken::new();
+        let reporter = Reporter::new(
+            ProgressFormat::Json,
+            Some(Arc::new(move |event: &ProgressEvent| {
+                seen.lock().unwrap().push(event.clone())
+            })),
+            Some(cancel.clone()),
+        );
+        reporter.stage("rendering");
+        reporter.done(Path::new("final.mp4"));
+        assert_eq!(events.lock().unwrap().len(), 2);
+        assert!(reporter.check_cancelled().is_ok());
+        cancel.cancel();
+        assert!(reporter.check_cancelled().is_err());
+    }
+}
@@ -0,0 +1,199 @@
+//! `--blur-region`: hide rectangles of the recording (API keys, emails)
+//! before anything else is drawn, so zooming in can't reveal them
+
+use crate::processing::chapters::parse_chapter_time;
+use image::{imageops, DynamicImage, RgbaImage};
+use std::borrow::Cow;
+
+/// How redacted regions are hidden
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum RedactStyle {
+    /// Large mosaic blocks
+    #[default]
+    Pixelate,
+    /// Heavy Gaussian blur
+    Blur,
+}
+
+/// A rectangle of the recording (video pixels) to hide, optionally only
+/// between two output-video times
+#[derive(Clone, Debug, PartialEq)]
+pub struct BlurRegion {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+    pub start: Option<f64>,
+    pub end: Option<f64>,
+}
+
+impl BlurRegion {
+    fn active_at(&self, timestamp: f64) -> bool {
+        self.start.is_none_or(|start| timestamp >= start)
+            && self.end.is_none_or(|end| timestamp < end)
+    }
+}
+
+/// Parse `x,y,w,h` or `x,y,w,h,start-end`; times are seconds, m:ss or
+/// h:mm:ss, and either end of the range may be left open (`1:30-`)
+pub fn parse_blur_region(s: &str) -> Result<BlurRegion, String> {
+    let parts: Vec<&str> = s.split(',').map(str::trim).collect();
+    if !(4..=5).contains(&parts.len()) {
+        return Err("expected x,y,w,h or x,y,w,h,start-end".to_string());
+    }
+    let number = |i: usize| {
+        parts[i]
+            .parse::<u32>()
+            .map_err(|_| format!("invalid number '{}'", parts[i]))
+    };
+    let (x, y, width, height) = (number(0)?, number(1)?, number(2)?, number(3)?);
+    if width == 0 || height == 0 {
+        return Err("width and height must be positive".to_string());
+    }
+
+    let (start, end) = match parts.get(4) {
+        None => (None, None),
+        Some(range) => {
+            let (start, end) = range
+                .split_once('-')
+                .ok_or_else(|| format!("expected a time range like 5-12, got '{range}'"))?;
+            let time = |t: &str| match t.trim() {
+                "" => Ok(None),
+                t => parse_chapter_time(t).map(Some),
+            };
+            let (start, end) = (time(start)?, time(end)?);
+            if let (Some(start), Some(end)) = (start, end) {
+                if end <= start {
+                    return Err(format!("range ends before it starts: '{range}'"));
+                }
+            }
+            (start, end)
+        }
+    };
+
+    Ok(BlurRegion {
+        x,
+        y,
+        width,
+        height,
+        start,
+        end,
+    })
+}
+
+/// The regions to hide and how
+#[derive(Clone, Debug, Default)]
+pub struct Redactor {
+    pub regions: Vec<BlurRegion>,
+    pub style: RedactStyle,
+}
+
+impl Redactor {
+    pub fn is_empty(&self) -> bool {
+        self.regions.is_empty()
+    }
+
+    /// `content` with the regions active at `timestamp` (output-video
+    /// seconds) hidden; borrowed untouched when none are
+    pub fn apply<'c>(&self, content: &'c DynamicImage, timestamp: f64) -> Cow<'c, DynamicImage> {
+        let mut active = self.regions.iter().filter(|r| r.active_at(timestamp));
+        let Some(first) = active.next() else {
+            return Cow::Borrowed(content);
+        };
+        let mut img = content.to_rgba8();
+        for region in std::iter::once(first).chain(active) {
+            self.hide(&mut img, region);
+        }
+        Cow::Owned(DynamicImage::ImageRgba8(img))
+    }
+
+    fn hide(&self, img: &mut RgbaImage, region: &BlurRegion) {
+        // Clip to the frame
+        let x = region.x.min(img.width());
+        let y = region.y.min(img.height());
+        let width = region.width.min(img.width() - x);
+        let height = region.height.min(img.height() - y);
+        if width == 0 || height == 0 {
+            return;
+        }
+
+        let patch = imageops::crop_imm(img, x, y, width, height).to_image();
+        // Coarse enough that text can't be made out at any zoom
+        let strength = (width.min(height) / 4).max(12);
+        let hidden = match self.style {
+            RedactStyle::Pixelate => {
+                let small = imageops::resize(
+                    &patch,
+                    width.div_ceil(strength),
+                    height.div_ceil(strength),
+                    imageops::FilterType::Triangle,
+                );
+                imageops::resize(&small, width, height, imageops::FilterType::Nearest)
+            }
+            RedactStyle::Blur => imageops::blur(&patch, strength as f32),
+        };
+        imageops::replace(img, &hidden, x as i64, y as i64);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgba;
+
+    #[test]
+    fn test_parse_blur_region() {
+        assert_eq!(
+            parse_blur_region("10,20,300,40"),
+            Ok(BlurRegion {
+                x: 10,
+                y: 20,
+                width: 300,
+                height: 40,
+                start: None,
+                end: None,
+            })
+        );
+        let timed = parse_blur_region("0,0,100,50,1:05-1:30").unwrap();
+        assert_eq!((timed.start, timed.end), (Some(65.0), Some(90.0)));
+        let open = parse_blur_region("0,0,100,50,12-").unwrap();
+        assert_eq!((open.start, open.end), (Some(12.0), None));
+
+        assert!(parse_blur_region("0,0,100").is_err());
+        assert!(parse_blur_region("0,0,0,50").is_err());
+        assert!(parse_blur_region("0,0,100,50,9-3").is_err());
+        assert!(parse_blur_region("0,0,100,50,soon").is_err());
+    }
+
+    #[test]
+    fn test_redactor_hides_active_regions() {
+        // A one-pixel "secret" in the middle of the region
+        let mut img = RgbaImage::from_pixel(64, 64, Rgba([0, 0, 0, 255]));
+        img.put_pixel(20, 20, Rgba([255, 255, 255, 255]));
+        let content = DynamicImage::ImageRgba8(img);
+
+        for style in [RedactStyle::Pixelate, RedactStyle::Blur] {
+            let redactor = Redactor {
+                regions: vec![parse_blur_region("8,8,32,32,1-2").unwrap()],
+                style,
+            };
+            assert!(matches!(redactor.apply(&content, 0.5), Cow::Borrowed(_)));
+
+            let redacted = redactor.apply(&content, 1.5).to_rgba8();
+            assert!(redacted.get_pixel(20, 20)[0] < 128, "{style:?}");
+            // Outside the region is untouched
+            assert_eq!(redacted.get_pixel(60, 60), &Rgba([0, 0, 0, 255]));
+        }
+    }
+
+    #[test]
+    fn test_region_clipped_to_frame() {
+        let content = DynamicImage::ImageRgba8(RgbaImage::new(32, 32));
+        let redactor = Redactor {
+            regions: vec![parse_blur_region("24,24,100,100").unwrap()],
+            style: RedactStyle::Pixelate,
+        };
+        assert_eq!(redactor.apply(&content, 0.0).width(), 32);
+    }
+}
@@ -0,0 +1,191 @@
+//! Anti-aliased shape primitives for overlays (boxes, arrows)
+
+use crate::processing::effects::blend_channel;
+use image::{Rgba, RgbaImage};
+
+/// Blend `color` into one pixel with extra coverage in 0.0..=1.0
+pub fn blend_pixel(canvas: &mut RgbaImage, x: i64, y: i64, color: Rgba<u8>, coverage: f64) {
+    if x < 0 || y < 0 || x >= canvas.width() as i64 || y >= canvas.height() as i64 {
+        return;
+    }
+    let alpha = (color[3] as f64 * coverage.clamp(0.0, 1.0)) as u8;
+    if alpha == 0 {
+        return;
+    }
+    let pixel = canvas.get_pixel_mut(x as u32, y as u32);
+    pixel[0] = blend_channel(pixel[0], color[0], alpha);
+    pixel[1] = blend_channel(pixel[1], color[1], alpha);
+    pixel[2] = blend_channel(pixel[2], color[2], alpha);
+}
+
+/// Signed distance from (px, py) to a rounded rectangle's edge (negative inside)
+pub fn rounded_rect_distance(px: f64, py: f64, x: f64, y: f64, w: f64, h: f64, radius: f64) -> f64 {
+    let radius = radius.min(w / 2.0).min(h / 2.0).max(0.0);
+    let qx = (px - (x + w / 2.0)).abs() - (w / 2.0 - radius);
+    let qy = (py - (y + h / 2.0)).abs() - (h / 2.0 - radius);
+    qx.max(0.0).hypot(qy.max(0.0)) + qx.max(qy).min(0.0) - radius
+}
+
+pub fn fill_rounded_rect(
+    canvas: &mut RgbaImage,
+    x: f64,
+    y: f64,
+    w: f64,
+    h: f64,
+    radius: f64,
+    color: Rgba<u8>,
+) {
+    for py in y.floor() as i64..(y + h).ceil() as i64 {
+        for px in x.floor() as i64..(x + w).ceil() as i64 {
+            let dist = rounded_rect_distance(px as f64 + 0.5, py as f64 + 0.5, x, y, w, h, radius);
+            blend_pixel(canvas, px, py, color, 0.5 - dist);
+        }
+    }
+}
+
+/// Outline a rounded rectangle with a stroke centered on its edge
+#[allow(clippy::too_many_arguments)]
+pub fn stroke_rounded_rect(
+    canvas: &mut RgbaImage,
+    x: f64,
+    y: f64,
+    w: f64,
+    h: f64,
+    radius: f64,
+    stroke: f64,
+    color: Rgba<u8>,
+) {
+    let reach = stroke / 2.0 + 1.0;
+    for py in (y - reach).floor() as i64..(y + h + reach).ceil() as i64 {
+        for px in (x - reach).floor() as i64..(x + w + reach).ceil() as i64 {
+            let dist = rounded_rect_distance(px as f64 + 0.5, py as f64 + 0.5, x, y, w, h, radius);
+            blend_pixel(canvas, px, py, color, stroke / 2.0 + 0.5 - dist.abs());
+        }
+    }
+}
+
+/// Straight line segment with round caps
+pub fn draw_line(
+    canvas: &mut RgbaImage,
+    from: (f64, f64),
+    to: (f64, f64),
+    width: f64,
+    color: Rgba<u8>,
+) {
+    let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+    let length_sq = dx * dx + dy * dy;
+    let half = width / 2.0;
+
+    let min_x = from.0.min(to.0) - half - 1.0;
+    let max_x = from.0.max(to.0) + half + 1.0;
+    let min_y = from.1.min(to.1) - half - 1.0;
+    let max_y = from.1.max(to.1) + half + 1.0;
+
+    for py in min_y.floor() as i64..=max_y.ceil() as i64 {
+        for px in min_x.floor() as i64..=max_x.ceil() as i64 {
+            let (fx, fy) = (px as f64 + 0.5, py as f64 + 0.5);
+            let t = if length_sq > 0.0 {
+                (((fx - from.0) * dx + (fy - from.1) * dy) / length_sq).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+            let (sx, sy) = (from.0 + dx * t, from.1 + dy * t);
+            let coverage = half + 0.5 - (fx - sx).hypot(fy - sy);
+            if coverage > 0.0 {
+                blend_pixel(canvas, px, py, color, coverage);
+            }
+        }
+    }
+}
+
+/// Straight arrow from `from` to `tip` with a filled triangular head
+pub fn draw_arrow(
+    canvas: &mut RgbaImage,
+    from: (f64, f64),
+    tip: (f64, f64),
+    width: f64,
+    head: f64,
+    color: Rgba<u8>,
+) {
+    let (dx, dy) = (tip.0 - from.0, tip.1 - from.1);
+    let length = dx.hypot(dy);
+    if length < head {
+        return;
+    }
+    let (ux, uy) = (dx / length, dy / length);
+    // Shaft stops where the head begins
+    let base = (tip.0 - ux * head, tip.1 - uy * head);
+    let half = width / 2.0;
+
+    let min_x = from.0.min(tip.0) - head;
+    let max_x = from.0.max(tip.0) + head;
+    let min_y = from.1.min(tip.1) - head;
+    let max_y = from.1.max(tip.1) + head;
+
+    for py in min_y.floor() as i64..=max_y.ceil() as i64 {
+        for px in min_x.floor() as i64..=max_x.ceil() as i64 {
+            let (fx, fy) = (px as f64 + 0.5, py as f64 + 0.5);
+
+            // Shaft: distance to the segment from..base
+            let along = ((fx - from.0) * ux + (fy - from.1) * uy).clamp(0.0, length - head);
+            let (sx, sy) = (from.0 + ux * along, from.1 + uy * along);
+            let shaft = half + 0.5 - (fx - sx).hypot(fy - sy);
+
+            // Head: triangle narrowing from the base to the tip
+            let rel_along = (fx - base.0) * ux + (fy - base.1) * uy;
+            let rel_across = ((fx - base.0) * -uy + (fy - base.1) * ux).abs();
+            let head_cover = if (0.0..=head).contains(&rel_along) {
+                head * 0.6 * (1.0 - rel_along / head) - rel_across + 0.5
+            } else {
+                0.0
+            };
+
+            let coverage = shaft.max(head_cover);
+            if coverage > 0.0 {
+                blend_pixel(canvas, px, py, color, coverage);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_arrow_reaches_tip() {
+        let mut canvas = RgbaImage::from_pixel(200, 200, Rgba([0, 0, 0, 255]));
+        draw_arrow(
+            &mut canvas,
+            (20.0, 100.0),
+            (180.0, 100.0),
+            4.0,
+            18.0,
+            Rgba([255, 0, 0, 255]),
+        );
+        assert!(canvas.get_pixel(100, 100)[0] > 200, "Shaft should be drawn");
+        assert!(canvas.get_pixel(175, 100)[0] > 200, "Head should be drawn");
+        assert_eq!(canvas.get_pixel(100, 20)[0], 0);
+    }
+
+    #[test]
+    fn test_stroke_leaves_interior() {
+        let mut canvas = RgbaImage::from_pixel(100, 100, Rgba([0, 0, 0, 255]));
+        stroke_rounded_rect(
+            &mut canvas,
+            20.0,
+            20.0,
+            60.0,
+            60.0,
+            6.0,
+            4.0,
+            Rgba([255, 255, 255, 255]),
+        );
+        assert!(canvas.get_pixel(50, 20)[0] > 200, "Edge should be stroked");
+        assert_eq!(
+            canvas.get_pixel(50, 50)[0],
+            0,
+            "Interior should be untouched"
+        );
+    }
+}
@@ -0,0 +1,94 @@
+//! Cursor spotlight: everything but a soft circle around the cursor is dimmed
+
+use image::RgbaImage;
+
+/// Configuration for `--spotlight`
+pub struct SpotlightConfig {
+    /// Radius of the undimmed circle (output pixels)
+    pub radius: f64,
+    /// Width of the soft edge outside the circle (output pixels)
+    pub feather: f64,
+    /// How dark the surroundings get (0.0 = untouched, 1.0 = black)
+    pub dim: f64,
+}
+
+impl Default for SpotlightConfig {
+    fn default() -> Self {
+        Self {
+            radius: 220.0, // Room for a button or two around the cursor
+            feather: 120.0,
+            dim: 0.55,
+        }
+    }
+}
+
+impl SpotlightConfig {
+    pub fn new(radius: f64) -> Self {
+        Self {
+            radius,
+            ..Default::default()
+        }
+    }
+}
+
+/// Dim the canvas outside a circle around (`x`, `y`). `strength` scales the
+/// dimming, so the spotlight fades out with the cursor.
+pub fn draw_spotlight(
+    canvas: &mut RgbaImage,
+    x: f64,
+    y: f64,
+    strength: f64,
+    config: &SpotlightConfig,
+) {
+    let dim = (config.dim * strength).clamp(0.0, 1.0);
+    if dim < 0.01 {
+        return;
+    }
+    let feather = config.feather.max(1.0);
+
+    for (px, py, pixel) in canvas.enumerate_pixels_mut() {
+        let distance = (px as f64 + 0.5 - x).hypot(py as f64 + 0.5 - y);
+        let amount = dim * edge((distance - config.radius) / feather);
+        if amount <= 0.0 {
+            continue;
+        }
+        let keep = 1.0 - amount;
+        for c in 0..3 {
+            pixel[c] = (pixel[c] as f64 * keep).round() as u8;
+        }
+    }
+}
+
+/// Smoothstep from 0 (inside) to 1 (a feather's width outside)
+fn edge(t: f64) -> f64 {
+    let t = t.clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgba;
+
+    #[test]
+    fn test_spotlight_dims_outside() {
+        let mut canvas = RgbaImage::from_pixel(400, 100, Rgba([200, 200, 200, 255]));
+        let config = SpotlightConfig {
+            radius: 50.0,
+            feather: 20.0,
+            dim: 0.5,
+        };
+        draw_spotlight(&mut canvas, 50.0, 50.0, 1.0, &config);
+
+        assert_eq!(canvas.get_pixel(50, 50)[0], 200, "center is untouched");
+        assert_eq!(canvas.get_pixel(300, 50)[0], 100, "outside is dimmed");
+        let edge = canvas.get_pixel(110, 50)[0];
+        assert!(edge > 100 && edge < 200, "soft edge: {}", edge);
+        assert_eq!(canvas.get_pixel(300, 50)[3], 255, "alpha is kept");
+
+        // Faded out with the cursor
+        let mut canvas = RgbaImage::from_pixel(400, 100, Rgba([200, 200, 200, 255]));
+        draw_spotlight(&mut canvas, 50.0, 50.0, 0.0, &config);
+        assert_eq!(canvas.get_pixel(300, 50)[0], 200);
+    }
+}
@@ -0,0 +1,163 @@
+//! `glide stats`: interaction summary for a recording
+
+use crate::cursor_types::{CursorEvent, EventType};
+use crate::processing::frames::{extract_frame_at, get_video_duration};
+use crate::processing::heatmap::Heatmap;
+use crate::recording::metadata::RecordingMetadata;
+use anyhow::{Context, Result};
+use image::imageops;
+use std::path::Path;
+use tempfile::TempDir;
+
+/// Gaps without cursor activity longer than this count as idle (seconds)
+pub const IDLE_THRESHOLD: f64 = 2.0;
+
+/// Opacity of the heatmap layer over the representative frame
+const HEATMAP_OPACITY: f32 = 0.65;
+
+/// Interaction statistics for one recording
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordingStats {
+    /// Length of the recording (seconds)
+    pub duration: f64,
+    pub left_clicks: usize,
+    pub right_clicks: usize,
+    /// Total cursor travel in screen points
+    pub distance: f64,
+    /// Time spent in gaps longer than `IDLE_THRESHOLD` (seconds)
+    pub idle_seconds: f64,
+}
+
+impl RecordingStats {
+    /// Stats for the recording at `input`
+    pub fn for_recording(input: &Path, metadata: &RecordingMetadata) -> Result<Self> {
+        // Cursor timestamps are relative to tracking, which covers the whole recording
+        let duration = if metadata.cursor_tracking_duration > 0.0 {
+            metadata.cursor_tracking_duration
+        } else {
+            get_video_duration(input)?
+        };
+        Ok(Self::from_events(&metadata.cursor_events, duration))
+    }
+
+    /// Compute stats from cursor events spanning `duration` seconds
+    pub fn from_events(events: &[CursorEvent], duration: f64) -> Self {
+        let mut stats = Self {
+            duration,
+            left_clicks: 0,
+            right_clicks: 0,
+            distance: 0.0,
+            idle_seconds: 0.0,
+        };
+
+        let mut last: Option<&CursorEvent> = None;
+        for event in events {
+            match event.event_type {
+                EventType::LeftClick => stats.left_clicks += 1,
+                EventType::RightClick => stats.right_clicks += 1,
+                EventType::Move | EventType::KeyDown(_) => {}
+            }
+            let gap_start = last.map_or(0.0, |prev| {
+                stats.distance += (event.x - prev.x).hypot(event.y - prev.y);
+                prev.timestamp
+            });
+            stats.add_gap(event.timestamp - gap_start);
+            last = Some(event);
+        }
+        stats.add_gap(duration - last.map_or(0.0, |e| e.timestamp));
+
+        stats
+    }
+
+    fn add_gap(&mut self, gap: f64) {
+        if gap > IDLE_THRESHOLD {
+            self.idle_seconds += gap;
+        }
+    }
+
+    pub fn clicks(&self) -> usize {
+        self.left_clicks + self.right_clicks
+    }
+
+    pub fn clicks_per_minute(&self) -> f64 {
+        if self.duration > 0.0 {
+            self.clicks() as f64 * 60.0 / self.duration
+        } else {
+            0.0
+        }
+    }
+
+    pub fn idle_percent(&self) -> f64 {
+        if self.duration > 0.0 {
+            (self.idle_seconds / self.duration * 100.0).min(100.0)
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Render the cursor heatmap over the middle frame of the video; false if
+/// no cursor positions fell inside it, leaving the heatmap blank
+pub fn write_heatmap(input: &Path, metadata: &RecordingMetadata, output: &Path) -> Result<bool> {
+    let temp_dir = TempDir::new().context("Failed to create temp directory")?;
+    let frame_path = temp_dir.path().join("frame.png");
+    let video_duration = get_video_duration(input)?;
+    extract_frame_at(input, video_duration / 2.0, &frame_path)?;
+
+    let mut frame = image::open(&frame_path)
+        .context("Failed to load representative frame")?
+        .to_rgba8();
+
+    let heatmap = Heatmap::from_recording(metadata, frame.width(), frame.height(), f64::INFINITY);
+
+    let layer = heatmap.render(frame.width(), frame.height(), HEATMAP_OPACITY);
+    imageops::overlay(&mut frame, &layer, 0, 0);
+    frame
+        .save(output)
+        .with_context(|| format!("Failed to write heatmap to {}", output.display()))?;
+
+    Ok(!heatmap.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_event(x: f64, y: f64, timestamp: f64, event_type: EventType) -> CursorEvent {
+        CursorEvent {
+            x,
+            y,
+            timestamp,
+            event_type,
+            display: None,
+            shape: None,
+        }
+    }
+
+    #[test]
+    fn test_stats_from_events() {
+        let events = vec![
+            make_event(0.0, 0.0, 0.5, EventType::Move),
+            make_event(30.0, 40.0, 1.0, EventType::LeftClick),
+            make_event(30.0, 40.0, 1.5, EventType::RightClick),
+            // 4.5s pause before this one
+            make_event(30.0, 52.0, 6.0, EventType::LeftClick),
+        ];
+        let stats = RecordingStats::from_events(&events, 30.0);
+
+        assert_eq!(stats.clicks(), 3);
+        assert_eq!(stats.right_clicks, 1);
+        assert!((stats.distance - 62.0).abs() < 1e-9);
+        assert!((stats.clicks_per_minute() - 6.0).abs() < 1e-9);
+        // 4.5s pause + 24s after the last event
+        assert!((stats.idle_seconds - 28.5).abs() < 1e-9);
+        assert!((stats.idle_percent() - 95.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_stats_without_events() {
+        let stats = RecordingStats::from_events(&[], 10.0);
+        assert_eq!(stats.clicks(), 0);
+        assert_eq!(stats.idle_percent(), 100.0);
+    }
+}
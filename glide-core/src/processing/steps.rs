@@ -0,0 +1,242 @@
+//! Numbered step badges for tutorial walkthroughs
+//!
+//! Every effective click becomes a step: a small numbered badge appears
+//! briefly next to it, and a matching step list is written as text.
+
+use crate::cursor_types::{CursorEvent, EventType};
+use crate::processing::shapes::fill_rounded_rect;
+use crate::processing::text::{draw_text, line_height, measure_text};
+use crate::processing::timeline::Timeline;
+use ab_glyph::FontArc;
+use anyhow::{Context, Result};
+use image::{Rgba, RgbaImage};
+use std::fmt::Write as _;
+use std::path::Path;
+
+/// Configuration for step badges
+pub struct StepConfig {
+    pub font: FontArc,
+    /// How long each badge stays visible (seconds)
+    pub duration: f64,
+    /// Badge radius in canvas pixels
+    pub radius: f64,
+    pub color: Rgba<u8>,
+    pub text_color: Rgba<u8>,
+}
+
+impl StepConfig {
+    pub fn new(font: FontArc, color: Rgba<u8>) -> Self {
+        // Same contrast rule as annotation bubbles
+        let luma = 0.299 * color[0] as f64 + 0.587 * color[1] as f64 + 0.114 * color[2] as f64;
+        let text_color = if luma > 150.0 {
+            Rgba([0, 0, 0, 255])
+        } else {
+            Rgba([255, 255, 255, 255])
+        };
+        Self {
+            font,
+            duration: 2.0,
+            radius: 18.0,
+            color,
+            text_color,
+        }
+    }
+}
+
+/// A visible badge; position is the click in canvas or screen space
+pub struct ActiveStep {
+    pub number: usize,
+    pub x: f64,
+    pub y: f64,
+    pub progress: f64,
+}
+
+/// Badges visible at a given (cursor-tracking) timestamp
+pub fn get_active_steps(
+    timestamp: f64,
+    clicks: &[&CursorEvent],
+    config: &StepConfig,
+) -> Vec<ActiveStep> {
+    clicks
+        .iter()
+        .enumerate()
+        .filter_map(|(i, click)| {
+            let elapsed = timestamp - click.timestamp;
+            (0.0..config.duration)
+                .contains(&elapsed)
+                .then(|| ActiveStep {
+                    number: i + 1,
+                    x: click.x,
+                    y: click.y,
+                    progress: elapsed / config.duration,
+                })
+        })
+        .collect()
+}
+
+/// Draw badges (positions already in canvas space) up and to the right of each click
+pub fn draw_step_badges(canvas: &mut RgbaImage, steps: &[ActiveStep], config: &StepConfig) {
+    for step in steps {
+        let elapsed = step.progress * config.duration;
+        let opacity = (elapsed / 0.15)
+            .min((config.duration - elapsed) / 0.3)
+            .clamp(0.0, 1.0);
+        if opacity < 0.01 {
+            continue;
+        }
+
+        let label = step.number.to_string();
+        let size = (config.radius * 1.2) as f32;
+        let (text_w, _) = measure_text(&config.font, size, &label);
+        let text_h = line_height(&config.font, size) as f64;
+        // Pill shape once numbers get wide
+        let height = config.radius * 2.0;
+        let width = height.max(text_w as f64 + config.radius);
+
+        let x = step.x + config.radius * 0.8;
+        let y = step.y - config.radius * 0.8 - height;
+
+        let mut shadow = Rgba([0, 0, 0, 90]);
+        shadow[3] = (shadow[3] as f64 * opacity) as u8;
+        fill_rounded_rect(
+            canvas,
+            x + 1.5,
+            y + 2.5,
+            width,
+            height,
+            height / 2.0,
+            shadow,
+        );
+
+        let mut color = config.color;
+        color[3] = (color[3] as f64 * opacity) as u8;
+        fill_rounded_rect(canvas, x, y, width, height, height / 2.0, color);
+
+        let mut text_color = config.text_color;
+        text_color[3] = (text_color[3] as f64 * opacity) as u8;
+        draw_text(
+            canvas,
+            &config.font,
+            size,
+            x + (width - text_w as f64) / 2.0,
+            y + (height - text_h) / 2.0,
+            &label,
+            text_color,
+        );
+    }
+}
+
+/// Write a numbered step list from the clicks the badges number.
+/// `time_offset` converts cursor-tracking time to video time and `timeline`
+/// that to output time; steps trimmed or cut away are skipped but keep their
+/// numbers so the list matches the badges.
+pub fn write_step_list(
+    path: &Path,
+    clicks: &[&CursorEvent],
+    timeline: &Timeline,
+    time_offset: f64,
+    duration: f64,
+) -> Result<usize> {
+    let mut list = String::new();
+    let mut written = 0;
+    for (i, click) in clicks.iter().enumerate() {
+        let Some(time) = Some(click.timestamp - time_offset)
+            .filter(|time| *time >= 0.0)
+            .and_then(|time| timeline.output_time(time))
+            .filter(|time| *time < duration)
+        else {
+            continue;
+        };
+        let action = match click.event_type {
+            EventType::RightClick => "Right-click",
+            _ => "Click",
+        };
+        let _ = writeln!(
+            list,
+            "{}. [{}] {} at ({:.0}, {:.0})",
+            i + 1,
+            format_timestamp(time),
+            action,
+            click.x,
+            click.y
+        );
+        written += 1;
+    }
+    std::fs::write(path, list)
+        .with_context(|| format!("Failed to write step list to {}", path.display()))?;
+    Ok(written)
+}
+
+/// mm:ss.s
+fn format_timestamp(seconds: f64) -> String {
+    let minutes = (seconds / 60.0).floor();
+    format!("{:02}:{:04.1}", minutes as u64, seconds - minutes * 60.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::processing::timeline::Cut;
+
+    fn make_click(x: f64, y: f64, timestamp: f64) -> CursorEvent {
+        CursorEvent {
+            x,
+            y,
+            timestamp,
+            event_type: EventType::LeftClick,
+            display: None,
+            shape: None,
+        }
+    }
+
+    #[test]
+    fn test_format_timestamp() {
+        assert_eq!(format_timestamp(3.21), "00:03.2");
+        assert_eq!(format_timestamp(75.0), "01:15.0");
+    }
+
+    #[test]
+    fn test_step_list_keeps_numbers_when_trimmed() {
+        let first = make_click(10.0, 20.0, 1.0);
+        let second = make_click(30.0, 40.0, 5.0);
+        let clicks = vec![&first, &second];
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("steps.txt");
+        // Video starts 2s into tracking, so the first click was trimmed away
+        let written =
+            write_step_list(&path, &clicks, &Timeline::constant(10.0), 2.0, 10.0).unwrap();
+        assert_eq!(written, 1);
+        let list = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(list, "2. [00:03.0] Click at (30, 40)\n");
+    }
+
+    #[test]
+    fn test_step_list_follows_timeline() {
+        let clicks = [
+            make_click(10.0, 10.0, 1.0),
+            make_click(20.0, 20.0, 5.0),
+            make_click(30.0, 30.0, 10.0),
+            make_click(40.0, 40.0, 15.0),
+        ];
+        let clicks: Vec<_> = clicks.iter().collect();
+        // 8-12s plays at 4x, then 4-6s is cut
+        let timeline = Timeline::with_speed_ups(20.0, &[(8.0, 12.0)], 4.0).cut(&[Cut {
+            start: 4.0,
+            end: 6.0,
+        }]);
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("steps.txt");
+        let written =
+            write_step_list(&path, &clicks, &timeline, 0.0, timeline.output_duration()).unwrap();
+        assert_eq!(written, 3);
+        let list = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(
+            list,
+            "1. [00:01.0] Click at (10, 10)\n\
+             3. [00:06.5] Click at (30, 30)\n\
+             4. [00:10.0] Click at (40, 40)\n"
+        );
+    }
+}
@@ -0,0 +1,210 @@
+//! Text rendering for burned-in overlays (captions, labels)
+//!
+//! Fonts are resolved by file path or family name (via fontconfig where
+//! available), and glyphs are alpha-blended straight onto the canvas.
+
+use crate::processing::effects::blend_channel;
+use ab_glyph::{Font, FontArc, FontVec, PxScale, ScaleFont};
+use anyhow::{Context, Result};
+use image::{Rgba, RgbaImage};
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// Family used when none is requested
+const DEFAULT_FONT_FAMILY: &str = "sans-serif";
+
+/// Well-known fallback fonts, tried when fontconfig can't resolve a family
+#[cfg(target_os = "macos")]
+const FALLBACK_FONTS: &[&str] = &[
+    "/System/Library/Fonts/Helvetica.ttc",
+    "/System/Library/Fonts/SFNS.ttf",
+];
+#[cfg(target_os = "windows")]
+const FALLBACK_FONTS: &[&str] = &[
+    "C:\\Windows\\Fonts\\segoeui.ttf",
+    "C:\\Windows\\Fonts\\arial.ttf",
+];
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+const FALLBACK_FONTS: &[&str] = &[
+    "/usr/share/fonts/truetype/dejavu/DejaVuSans.ttf",
+    "/usr/share/fonts/dejavu/DejaVuSans.ttf",
+    "/usr/share/fonts/TTF/DejaVuSans.ttf",
+    "/usr/share/fonts/truetype/liberation/LiberationSans-Regular.ttf",
+    "/usr/share/fonts/noto/NotoSans-Regular.ttf",
+];
+
+/// Load a font from a file path or a family name (e.g. "Inter", "monospace")
+pub fn load_font(family: Option<&str>) -> Result<FontArc> {
+    let family = family.unwrap_or(DEFAULT_FONT_FAMILY);
+
+    if Path::new(family).is_file() {
+        return load_font_file(Path::new(family));
+    }
+
+    if let Some(path) = fontconfig_match(family) {
+        if let Ok(font) = load_font_file(Path::new(&path)) {
+            return Ok(font);
+        }
+    }
+
+    FALLBACK_FONTS
+        .iter()
+        .map(Path::new)
+        .filter(|p| p.is_file())
+        .find_map(|p| load_font_file(p).ok())
+        .with_context(|| format!("Font not found: {}", family))
+}
+
+fn load_font_file(path: &Path) -> Result<FontArc> {
+    let data =
+        std::fs::read(path).with_context(|| format!("Failed to read font {}", path.display()))?;
+    // Collections (.ttc) load their first face
+    let font = FontVec::try_from_vec_and_index(data, 0)
+        .with_context(|| format!("Invalid font file {}", path.display()))?;
+    Ok(FontArc::new(font))
+}
+
+/// Resolve a family name to a font file with `fc-match`
+fn fontconfig_match(family: &str) -> Option<String> {
+    let output = Command::new("fc-match")
+        .args(["-f", "%{file}", family])
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    let path = String::from_utf8(output.stdout).ok()?;
+    (output.status.success() && !path.is_empty()).then_some(path)
+}
+
+/// Width and height in pixels of a single line of text
+pub fn measure_text(font: &FontArc, size: f32, text: &str) -> (u32, u32) {
+    let scaled = font.as_scaled(PxScale::from(size));
+    let mut width = 0.0;
+    let mut previous = None;
+    for c in text.chars() {
+        let id = scaled.glyph_id(c);
+        if let Some(prev) = previous {
+            width += scaled.kern(prev, id);
+        }
+        width += scaled.h_advance(id);
+        previous = Some(id);
+    }
+    (width.ceil() as u32, line_height(font, size).ceil() as u32)
+}
+
+/// Distance between baselines of consecutive lines
+pub fn line_height(font: &FontArc, size: f32) -> f32 {
+    let scaled = font.as_scaled(PxScale::from(size));
+    scaled.ascent() - scaled.descent() + scaled.line_gap()
+}
+
+/// Draw a single line of text with its top-left corner at (x, y)
+pub fn draw_text(
+    canvas: &mut RgbaImage,
+    font: &FontArc,
+    size: f32,
+    x: f64,
+    y: f64,
+    text: &str,
+    color: Rgba<u8>,
+) {
+    let scale = PxScale::from(size);
+    let scaled = font.as_scaled(scale);
+    let (cw, ch) = (canvas.width() as i64, canvas.height() as i64);
+
+    let mut caret = x as f32;
+    let baseline = y as f32 + scaled.ascent();
+    let mut previous = None;
+
+    for c in text.chars() {
+        let id = scaled.glyph_id(c);
+        if let Some(prev) = previous {
+            caret += scaled.kern(prev, id);
+        }
+        let glyph = id.with_scale_and_position(scale, ab_glyph::point(caret, baseline));
+        caret += scaled.h_advance(id);
+        previous = Some(id);
+
+        let Some(outlined) = font.outline_glyph(glyph) else {
+            continue; // whitespace
+        };
+        let bounds = outlined.px_bounds();
+        outlined.draw(|gx, gy, coverage| {
+            let px = bounds.min.x as i64 + gx as i64;
+            let py = bounds.min.y as i64 + gy as i64;
+            if px < 0 || py < 0 || px >= cw || py >= ch {
+                return;
+            }
+            let alpha = (coverage.clamp(0.0, 1.0) * color[3] as f32) as u8;
+            if alpha == 0 {
+                return;
+            }
+            let dest = canvas.get_pixel_mut(px as u32, py as u32);
+            dest[0] = blend_channel(dest[0], color[0], alpha);
+            dest[1] = blend_channel(dest[1], color[1], alpha);
+            dest[2] = blend_channel(dest[2], color[2], alpha);
+            dest[3] = dest[3].max(alpha);
+        });
+    }
+}
+
+/// Greedily wrap text into lines no wider than `max_width` pixels.
+/// Explicit newlines are kept; a single word wider than the limit gets its own line.
+pub fn wrap_text(font: &FontArc, size: f32, text: &str, max_width: u32) -> Vec<String> {
+    let mut lines = Vec::new();
+    for paragraph in text.lines() {
+        let mut line = String::new();
+        for word in paragraph.split_whitespace() {
+            let candidate = if line.is_empty() {
+                word.to_string()
+            } else {
+                format!("{} {}", line, word)
+            };
+            if !line.is_empty() && measure_text(font, size, &candidate).0 > max_width {
+                lines.push(std::mem::replace(&mut line, word.to_string()));
+            } else {
+                line = candidate;
+            }
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_font() -> Option<FontArc> {
+        load_font(None).ok()
+    }
+
+    #[test]
+    fn test_wrap_text() {
+        // Nothing to draw with where no system font is installed
+        let Some(font) = test_font() else {
+            return;
+        };
+        let (word_width, _) = measure_text(&font, 20.0, "word word");
+        let lines = wrap_text(&font, 20.0, "word word word word\nnext", word_width);
+        assert_eq!(lines, vec!["word word", "word word", "next"]);
+    }
+
+    #[test]
+    fn test_draw_text_marks_pixels() {
+        // Nothing to draw with where no system font is installed
+        let Some(font) = test_font() else {
+            return;
+        };
+        let mut canvas = RgbaImage::from_pixel(100, 40, Rgba([0, 0, 0, 255]));
+        draw_text(
+            &mut canvas,
+            &font,
+            24.0,
+            2.0,
+            2.0,
+            "Hi",
+            Rgba([255, 255, 255, 255]),
+        );
+        assert!(canvas.pixels().any(|p| p[0] > 200));
+    }
+}
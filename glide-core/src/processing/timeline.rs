@@ -0,0 +1,283 @@
+//! Which moment of the video each moment of the output shows
+//!
+//! Normally the two run together. `--speed-up-idle` plays stretches faster
+//! and `--cut` removes them; frames, cursor and camera all look up the video
+//! time for their output time here, so they stay in step across both.
+
+use crate::cursor_types::CursorEvent;
+use anyhow::{Context, Result};
+use std::path::Path;
+use std::str::FromStr;
+
+/// One stretch of the video, played at `speed`x
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Segment {
+    /// Video time the stretch starts (seconds)
+    source_start: f64,
+    /// Output time it starts (seconds)
+    output_start: f64,
+    /// Video seconds in the stretch
+    length: f64,
+    speed: f64,
+}
+
+impl Segment {
+    fn source_end(&self) -> f64 {
+        self.source_start + self.length
+    }
+}
+
+/// Maps output time to video time and back
+#[derive(Debug, Clone)]
+pub struct Timeline {
+    /// In video order, without overlaps; video time between them is cut
+    segments: Vec<Segment>,
+    /// Length of the video (seconds)
+    duration: f64,
+}
+
+impl Timeline {
+    /// Every moment of a `duration`-second video at normal speed
+    pub fn constant(duration: f64) -> Self {
+        Self::from_segments(duration, vec![(0.0, duration, 1.0)])
+    }
+
+    /// A `duration`-second video with each of `stretches` (start and end,
+    /// in order, not overlapping) played at `speed`x
+    pub fn with_speed_ups(duration: f64, stretches: &[(f64, f64)], speed: f64) -> Self {
+        let mut segments = Vec::new();
+        let mut source = 0.0;
+        for &(start, end) in stretches {
+            segments.push((source, start, 1.0));
+            segments.push((start, end, speed));
+            source = end;
+        }
+        segments.push((source, duration, 1.0));
+        Self::from_segments(duration, segments)
+    }
+
+    /// Lay (start, end, speed) stretches of video end to end, dropping empty ones
+    fn from_segments(duration: f64, stretches: Vec<(f64, f64, f64)>) -> Self {
+        let mut output = 0.0;
+        let segments = stretches
+            .into_iter()
+            .filter(|(start, end, _)| end > start)
+            .map(|(start, end, speed)| {
+                let segment = Segment {
+                    source_start: start,
+                    output_start: output,
+                    length: end - start,
+                    speed,
+                };
+                output += segment.length / speed;
+                segment
+            })
+            .collect();
+        Self { segments, duration }
+    }
+
+    /// This timeline without the video in `cuts`
+    pub fn cut(&self, cuts: &[Cut]) -> Self {
+        let mut stretches = Vec::new();
+        for segment in &self.segments {
+            let mut start = segment.source_start;
+            let end = segment.source_end();
+            let mut cuts: Vec<_> = cuts
+                .iter()
+                .filter(|cut| cut.start < end && cut.end > start)
+                .collect();
+            cuts.sort_by(|a, b| a.start.total_cmp(&b.start));
+            for cut in cuts {
+                if cut.start > start {
+                    stretches.push((start, cut.start, segment.speed));
+                }
+                start = start.max(cut.end);
+            }
+            stretches.push((start, end, segment.speed));
+        }
+        Self::from_segments(self.duration, stretches)
+    }
+
+    /// Whether the output and the video run at different times
+    pub fn is_retimed(&self) -> bool {
+        self.sped_up() > 0 || self.kept() != [(0.0, self.duration)]
+    }
+
+    /// Stretches played faster than normal
+    pub fn sped_up(&self) -> usize {
+        self.segments.iter().filter(|s| s.speed > 1.0).count()
+    }
+
+    /// Video spans kept in the output, in order: (start, end)
+    pub fn kept(&self) -> Vec<(f64, f64)> {
+        let mut kept: Vec<(f64, f64)> = Vec::new();
+        for segment in &self.segments {
+            match kept.last_mut() {
+                Some(last) if last.1 == segment.source_start => last.1 = segment.source_end(),
+                _ => kept.push((segment.source_start, segment.source_end())),
+            }
+        }
+        kept
+    }
+
+    /// Length of the output (seconds)
+    pub fn output_duration(&self) -> f64 {
+        self.segments
+            .last()
+            .map_or(0.0, |s| s.output_start + s.length / s.speed)
+    }
+
+    fn segment_at_output(&self, output_time: f64) -> Option<&Segment> {
+        let i = self
+            .segments
+            .partition_point(|s| s.output_start <= output_time)
+            .max(1);
+        self.segments.get(i - 1)
+    }
+
+    /// Video time shown at `output_time`
+    pub fn source_time(&self, output_time: f64) -> f64 {
+        let Some(segment) = self.segment_at_output(output_time) else {
+            return output_time;
+        };
+        let into = ((output_time - segment.output_start) * segment.speed).min(segment.length);
+        segment.source_start + into.max(0.0)
+    }
+
+    /// Output time that shows video time `source_time`; None if it was cut
+    pub fn output_time(&self, source_time: f64) -> Option<f64> {
+        let i = self
+            .segments
+            .partition_point(|s| s.source_start <= source_time)
+            .max(1);
+        let segment = self.segments.get(i - 1)?;
+        if source_time > segment.source_end() {
+            return None;
+        }
+        Some(segment.output_start + (source_time - segment.source_start).max(0.0) / segment.speed)
+    }
+
+    /// Playback speed at `output_time`
+    pub fn speed_at(&self, output_time: f64) -> f64 {
+        self.segment_at_output(output_time).map_or(1.0, |s| s.speed)
+    }
+
+    /// `events` re-timed onto the output's clock (still `time_offset` ahead),
+    /// without those in cut video
+    pub fn retime(&self, events: &[CursorEvent], time_offset: f64) -> Vec<CursorEvent> {
+        events
+            .iter()
+            .filter_map(|e| {
+                let time = self.output_time(e.timestamp - time_offset)?;
+                Some(CursorEvent {
+                    timestamp: time + time_offset,
+                    ..e.clone()
+                })
+            })
+            .collect()
+    }
+}
+
+/// A span of the recording to remove, as `--cut 10.5-14` (seconds)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Cut {
+    pub start: f64,
+    pub end: f64,
+}
+
+impl Cut {
+    /// This cut on a video trimmed by `trim_start` seconds
+    pub fn shifted(self, trim_start: f64) -> Self {
+        Self {
+            start: self.start - trim_start,
+            end: self.end - trim_start,
+        }
+    }
+}
+
+impl FromStr for Cut {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parse = |part: &str| part.trim().parse::<f64>().ok().filter(|t| *t >= 0.0);
+        match s
+            .split_once('-')
+            .map(|(start, end)| (parse(start), parse(end)))
+        {
+            Some((Some(start), Some(end))) if end > start => Ok(Cut { start, end }),
+            _ => Err(format!(
+                "expected START-END in seconds, e.g. 10.5-14, got {:?}",
+                s
+            )),
+        }
+    }
+}
+
+/// Read cuts from a file: one START-END per line, `#` starting a comment
+pub fn load_cuts(path: &Path) -> Result<Vec<Cut>> {
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read cuts from {}", path.display()))?;
+    contents
+        .lines()
+        .enumerate()
+        .filter_map(|(i, line)| {
+            let line = line.split('#').next().unwrap_or("").trim();
+            (!line.is_empty()).then_some((i, line))
+        })
+        .map(|(i, line)| {
+            line.parse()
+                .map_err(|e: String| anyhow::anyhow!("{}, line {}: {}", path.display(), i + 1, e))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cursor_types::EventType;
+
+    #[test]
+    fn test_cuts_and_speed_ups() {
+        // 2..6 at 4x: 1s of output
+        let timeline = Timeline::with_speed_ups(10.0, &[(2.0, 6.0)], 4.0);
+        assert_eq!(timeline.output_duration(), 7.0);
+        assert_eq!(timeline.source_time(2.5), 4.0);
+        assert_eq!(timeline.output_time(8.0), Some(5.0));
+
+        // Cut 1..3 (half of it sped up) and 7..8
+        let cut = timeline.cut(&["7-8".parse().unwrap(), "1-3".parse().unwrap()]);
+        assert_eq!(cut.output_duration(), 7.0 - 1.0 - 0.25 - 1.0);
+        assert_eq!(cut.kept(), vec![(0.0, 1.0), (3.0, 7.0), (8.0, 10.0)]);
+        assert_eq!(cut.source_time(0.5), 0.5);
+        assert_eq!(cut.source_time(1.0), 3.0);
+        assert_eq!(cut.output_time(2.0), None);
+        assert_eq!(cut.output_time(9.0), Some(3.75));
+        assert!(cut.is_retimed());
+        assert!(!Timeline::constant(5.0).is_retimed());
+
+        let events: Vec<_> = [0.5, 2.0, 9.0]
+            .iter()
+            .map(|&timestamp| CursorEvent {
+                x: 0.0,
+                y: 0.0,
+                timestamp: timestamp + 1.0,
+                event_type: EventType::LeftClick,
+                display: None,
+                shape: None,
+            })
+            .collect();
+        let retimed = cut.retime(&events, 1.0);
+        let times: Vec<_> = retimed.iter().map(|e| e.timestamp).collect();
+        assert_eq!(times, vec![1.5, 4.75]);
+
+        assert!("5-3".parse::<Cut>().is_err());
+        assert!("abc".parse::<Cut>().is_err());
+        assert_eq!(
+            "10.5 - 14".parse::<Cut>(),
+            Ok(Cut {
+                start: 10.5,
+                end: 14.0
+            })
+        );
+    }
+}
@@ -0,0 +1,141 @@
+//! Cursor trail: a fading streak behind the cursor along its smoothed path
+
+use crate::cursor_types::CursorEvent;
+use crate::processing::cursor::{get_smoothed_cursor, CursorConfig};
+use crate::processing::shapes::draw_line;
+use image::{Rgba, RgbaImage};
+
+/// Seconds between points sampled along the trail
+const TRAIL_STEP: f64 = 1.0 / 60.0;
+
+/// Configuration for `--cursor-trail`
+pub struct TrailConfig {
+    /// How far back the trail reaches (seconds)
+    pub length: f64,
+    /// Opacity at the cursor end of the trail (0.0..=1.0)
+    pub opacity: f64,
+    /// Width at the cursor end, per unit of cursor scale (output pixels)
+    pub width: f64,
+    pub color: Rgba<u8>,
+}
+
+impl TrailConfig {
+    pub fn new(length: f64, opacity: f64, color: Rgba<u8>) -> Self {
+        Self {
+            length,
+            opacity,
+            width: 4.0,
+            color,
+        }
+    }
+}
+
+/// A point on the trail, oldest first
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TrailPoint {
+    pub x: f64,
+    pub y: f64,
+    /// 0.0 at the tail, 1.0 at the cursor
+    pub age: f64,
+    /// Cursor opacity at this point, so the trail fades out with it
+    pub opacity: f64,
+}
+
+/// Smoothed cursor positions (screen points) over the trail's length up to
+/// `timestamp`, oldest first
+pub fn get_trail_points(
+    timestamp: f64,
+    events: &[CursorEvent],
+    cursor_config: &CursorConfig,
+    config: &TrailConfig,
+) -> Vec<TrailPoint> {
+    let steps = (config.length / TRAIL_STEP).ceil() as usize;
+    (0..=steps)
+        .rev()
+        .filter_map(|i| {
+            let t = timestamp - config.length * i as f64 / steps.max(1) as f64;
+            if t < 0.0 {
+                return None;
+            }
+            let state = get_smoothed_cursor(t, events, cursor_config);
+            Some(TrailPoint {
+                x: state.x,
+                y: state.y,
+                age: 1.0 - i as f64 / steps.max(1) as f64,
+                opacity: state.opacity,
+            })
+        })
+        .collect()
+}
+
+/// Draw the trail through `points` (canvas pixels), tapering and fading
+/// towards its tail. `scale` is the cursor's scale on the canvas.
+pub fn draw_trail(canvas: &mut RgbaImage, points: &[TrailPoint], scale: f64, config: &TrailConfig) {
+    for pair in points.windows(2) {
+        let (from, to) = (pair[0], pair[1]);
+        if (to.x - from.x).hypot(to.y - from.y) < 0.5 {
+            continue; // Resting cursor: nothing to streak
+        }
+        let alpha = config.opacity * to.age * to.opacity.min(from.opacity);
+        if alpha < 0.01 {
+            continue;
+        }
+        let mut color = config.color;
+        color[3] = (color[3] as f64 * alpha.clamp(0.0, 1.0)).round() as u8;
+        let width = (config.width * scale * (0.3 + 0.7 * to.age)).max(1.0);
+        draw_line(canvas, (from.x, from.y), (to.x, to.y), width, color);
+    }
+}
+
+/// Parse an opacity from 0 to 1
+pub fn parse_opacity(s: &str) -> Result<f64, String> {
+    match s.parse::<f64>() {
+        Ok(opacity) if (0.0..=1.0).contains(&opacity) => Ok(opacity),
+        _ => Err("expected an opacity from 0 to 1".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cursor_types::EventType;
+
+    #[test]
+    fn test_cursor_trail() {
+        let events: Vec<_> = (0..=100)
+            .map(|i| CursorEvent {
+                x: i as f64 * 10.0,
+                y: 50.0,
+                timestamp: i as f64 / 100.0,
+                event_type: EventType::Move,
+                display: None,
+                shape: None,
+            })
+            .collect();
+        let cursor_config = CursorConfig::default();
+        let config = TrailConfig::new(0.25, 0.5, Rgba([255, 255, 255, 255]));
+
+        let points = get_trail_points(0.8, &events, &cursor_config, &config);
+        assert!(points.len() > 10);
+        assert_eq!(points.last().unwrap().age, 1.0);
+        assert!(points.windows(2).all(|p| p[0].x < p[1].x), "oldest first");
+
+        // Only as far back as the recording goes
+        let points = get_trail_points(0.1, &events, &cursor_config, &config);
+        assert!(points.iter().all(|p| p.age > 0.5));
+
+        let mut canvas = RgbaImage::from_pixel(1000, 100, Rgba([0, 0, 0, 255]));
+        let points = get_trail_points(0.8, &events, &cursor_config, &config);
+        draw_trail(&mut canvas, &points, 2.0, &config);
+        let head = points.last().unwrap();
+        let tail = points.first().unwrap();
+        let near_head = canvas.get_pixel(head.x as u32 - 5, 50)[0];
+        let near_tail = canvas.get_pixel(tail.x as u32 + 5, 50)[0];
+        assert!(near_head > near_tail, "{} vs {}", near_head, near_tail);
+        assert!(near_head <= 128, "capped by the trail's opacity");
+        assert_eq!(canvas.get_pixel(head.x as u32 + 50, 50)[0], 0);
+
+        assert_eq!(parse_opacity("0.4"), Ok(0.4));
+        assert!(parse_opacity("1.5").is_err());
+    }
+}
@@ -0,0 +1,964 @@
+use crate::cursor_types::{CursorEvent, EventType};
+use crate::processing::easing::Easing;
+
+/// Screen points kept around a cluster of clicks when zooming to fit it
+const CLUSTER_MARGIN: f64 = 60.0;
+
+/// Zoom configuration
+#[derive(Debug, Clone, Copy)]
+pub struct ZoomConfig {
+    pub max_zoom: f64, // Target zoom level
+    pub ease_in: f64,  // Ease in duration (anticipatory - starts before click)
+    pub hold: f64,     // Hold duration at max zoom; also determines panning behavior
+    pub ease_out: f64, // Ease out duration
+    pub debounce: f64, // Ignore clicks within this time of previous click
+    /// Pans faster than this (screen points/second) become crossfades instead
+    pub max_pan_speed: Option<f64>,
+    /// Clicks during a hold share one zoom while their bounding box fits in
+    /// a square this size (screen points)
+    pub cluster_size: Option<f64>,
+    /// Recording size in screen points, for zooming to fit a cluster
+    pub frame: Option<(f64, f64)>,
+    /// Curve of zooming in and out
+    pub zoom_easing: Easing,
+    /// Curve of panning between clicks
+    pub pan_easing: Easing,
+    /// Keep zoomed views inside the content, off the padding around it
+    pub clamp_to_content: bool,
+    /// Slow zoom and pan while idle
+    pub idle_drift: Option<IdleDrift>,
+}
+
+impl Default for ZoomConfig {
+    fn default() -> Self {
+        Self {
+            max_zoom: 1.8,       // More prominent zoom for better visibility
+            ease_in: 0.6,        // Anticipatory zoom starts 0.6s before click
+            hold: 4.0,           // Hold duration at max zoom
+            ease_out: 0.8,       // Slow zoom out
+            debounce: 0.5,       // Ignore clicks within 0.5s of previous
+            max_pan_speed: None, // Pan at any speed
+            cluster_size: None,  // Every click is its own zoom
+            frame: None,         // Clusters zoom as far as single clicks
+            zoom_easing: Easing::Cubic,
+            pan_easing: Easing::Cubic,
+            clamp_to_content: false,
+            idle_drift: None,
+        }
+    }
+}
+
+impl ZoomConfig {
+    /// Gentler camera for motion-sensitive viewers: longer easings and
+    /// crossfades instead of fast pans
+    pub fn reduced_motion() -> Self {
+        Self {
+            ease_in: 1.2,
+            ease_out: 1.6,
+            max_pan_speed: Some(400.0),
+            ..Default::default()
+        }
+    }
+
+    /// This config for a recording `width` x `height` screen points in size
+    pub fn fit_to(self, width: f64, height: f64) -> Self {
+        Self {
+            frame: Some((width, height)),
+            ..self
+        }
+    }
+
+    pub fn total_duration(&self) -> f64 {
+        self.ease_in + self.hold + self.ease_out
+    }
+}
+
+/// Zoom settings from the command line, each replacing the default
+#[derive(Debug, Default, Clone)]
+#[cfg_attr(feature = "cli", derive(clap::Args))]
+pub struct ZoomOverrides {
+    /// Zoom factor at each click (default: 1.8; 1 disables zooming in)
+    #[cfg_attr(feature = "cli", arg(long, value_name = "FACTOR", value_parser = parse_zoom_level))]
+    pub zoom_level: Option<f64>,
+
+    /// Seconds to hold the zoom after a click (default: 4.0)
+    #[cfg_attr(feature = "cli", arg(long, value_name = "SECONDS", value_parser = parse_seconds))]
+    pub zoom_hold: Option<f64>,
+
+    /// Seconds to zoom in, starting before the click (default: 0.6)
+    #[cfg_attr(feature = "cli", arg(long, value_name = "SECONDS", value_parser = parse_seconds))]
+    pub zoom_ease_in: Option<f64>,
+
+    /// Seconds to zoom back out (default: 0.8)
+    #[cfg_attr(feature = "cli", arg(long, value_name = "SECONDS", value_parser = parse_seconds))]
+    pub zoom_ease_out: Option<f64>,
+
+    /// Ignore clicks within this many seconds of the previous one (default: 0.5)
+    #[cfg_attr(feature = "cli", arg(long, value_name = "SECONDS", value_parser = parse_seconds))]
+    pub zoom_debounce: Option<f64>,
+
+    /// Frame nearby clicks together, up to this many screen points apart (default: off)
+    #[cfg_attr(feature = "cli", arg(long, value_name = "POINTS", value_parser = parse_points))]
+    pub zoom_cluster: Option<f64>,
+
+    /// Easing curve for zooming in and out (default: cubic)
+    #[cfg_attr(feature = "cli", arg(long, value_enum, value_name = "EASING"))]
+    pub zoom_easing: Option<Easing>,
+
+    /// Easing curve for panning between clicks (default: cubic)
+    #[cfg_attr(feature = "cli", arg(long, value_enum, value_name = "EASING"))]
+    pub pan_easing: Option<Easing>,
+
+    /// Keep zoomed views filled with the recording, never showing the background around it
+    #[cfg_attr(feature = "cli", arg(long))]
+    pub zoom_clamp: bool,
+
+    /// Drift slowly while idle, zooming in by up to this much (e.g. 0.05 = 5%)
+    #[cfg_attr(feature = "cli", arg(long, value_name = "AMOUNT", value_parser = parse_drift))]
+    pub idle_drift: Option<f64>,
+
+    /// Seconds for one cycle of the idle drift (default: 20)
+    #[cfg_attr(feature = "cli", arg(long, value_name = "SECONDS", value_parser = parse_seconds, requires = "idle_drift"))]
+    pub idle_drift_period: Option<f64>,
+}
+
+impl ZoomOverrides {
+    /// `config` with the given settings replaced
+    pub fn apply(&self, config: ZoomConfig) -> ZoomConfig {
+        ZoomConfig {
+            max_zoom: self.zoom_level.unwrap_or(config.max_zoom),
+            hold: self.zoom_hold.unwrap_or(config.hold),
+            ease_in: self.zoom_ease_in.unwrap_or(config.ease_in),
+            ease_out: self.zoom_ease_out.unwrap_or(config.ease_out),
+            debounce: self.zoom_debounce.unwrap_or(config.debounce),
+            cluster_size: self.zoom_cluster.or(config.cluster_size),
+            zoom_easing: self.zoom_easing.unwrap_or(config.zoom_easing),
+            pan_easing: self.pan_easing.unwrap_or(config.pan_easing),
+            clamp_to_content: self.zoom_clamp || config.clamp_to_content,
+            idle_drift: self
+                .idle_drift
+                .map(|amplitude| IdleDrift {
+                    amplitude,
+                    period: self.idle_drift_period.unwrap_or(DEFAULT_DRIFT_PERIOD),
+                })
+                .or(config.idle_drift),
+            ..config
+        }
+    }
+}
+
+pub fn parse_zoom_level(s: &str) -> Result<f64, String> {
+    match s.parse::<f64>() {
+        Ok(level) if (1.0..=10.0).contains(&level) => Ok(level),
+        _ => Err("expected a zoom factor from 1 to 10".to_string()),
+    }
+}
+
+pub fn parse_seconds(s: &str) -> Result<f64, String> {
+    match s.parse::<f64>() {
+        Ok(seconds) if seconds.is_finite() && seconds >= 0.0 => Ok(seconds),
+        _ => Err("expected a number of seconds, 0 or more".to_string()),
+    }
+}
+
+pub fn parse_drift(s: &str) -> Result<f64, String> {
+    match s.parse::<f64>() {
+        Ok(amount) if (0.0..=0.5).contains(&amount) => Ok(amount),
+        _ => Err("expected an amount of extra zoom from 0 to 0.5".to_string()),
+    }
+}
+
+pub fn parse_points(s: &str) -> Result<f64, String> {
+    match s.parse::<f64>() {
+        Ok(points) if points.is_finite() && points >= 0.0 => Ok(points),
+        _ => Err("expected a number of screen points, 0 or more".to_string()),
+    }
+}
+
+/// A pan that is too fast for `max_pan_speed`, shown as a crossfade
+/// between the camera at the previous and next click
+#[derive(Debug, Clone, Copy)]
+pub struct Crossfade {
+    pub zoom: f64,
+    pub from: (f64, f64),
+    pub to: (f64, f64),
+    /// Blend weight of `to` (0.0 to 1.0)
+    pub progress: f64,
+}
+
+/// Calculate zoom level and cursor position for a given timestamp.
+/// Uses anticipatory zoom (starts before click) and smart panning between nearby clicks.
+pub fn calculate_zoom(
+    timestamp: f64,
+    cursor_events: &[CursorEvent],
+    config: &ZoomConfig,
+) -> (f64, f64, f64) {
+    calculate_camera(timestamp, cursor_events, config).0
+}
+
+/// Like `calculate_zoom`, but also reports a crossfade when a pan was
+/// replaced by one. During a crossfade the returned focus is whichever end
+/// dominates the blend.
+pub fn calculate_camera(
+    timestamp: f64,
+    cursor_events: &[CursorEvent],
+    config: &ZoomConfig,
+) -> ((f64, f64, f64), Option<Crossfade>) {
+    let stops = plan_camera(cursor_events, config);
+
+    // Find previous stop (most recent before timestamp) and next stop (first after timestamp)
+    let prev_stop = stops.iter().rfind(|s| s.start <= timestamp);
+    let next_stop = stops.iter().find(|s| s.start > timestamp);
+
+    // Find current cursor position for idle state
+    let default_pos = cursor_events
+        .iter()
+        .rfind(|e| e.timestamp <= timestamp)
+        .map(|e| (e.x, e.y))
+        .unwrap_or((0.0, 0.0));
+
+    // Pan if next stop's anticipatory zoom would start before current zoom-out completes.
+    // This ensures smooth transitions with no discontinuity in zoom level.
+    // pan_window = hold + ease_out + ease_in
+    let pan_window = config.hold + config.ease_out + config.ease_in;
+
+    // Case 1: Anticipatory zoom-in (next stop coming soon)
+    if let Some(next) = next_stop {
+        let time_to_next = next.start - timestamp;
+        if time_to_next > 0.0 && time_to_next <= config.ease_in {
+            // We're in the anticipatory zoom-in phase
+            let progress = 1.0 - (time_to_next / config.ease_in);
+            let zoom = 1.0 + (next.zoom - 1.0) * config.zoom_easing.ease_out(progress);
+
+            // Check if we're also transitioning from a previous stop (panning while zooming)
+            if let Some(prev) = prev_stop {
+                let gap = next.start - prev.end;
+                if gap <= pan_window {
+                    // Pan from prev to next while staying zoomed
+                    return pan(prev, next, progress, config.ease_in, config);
+                }
+            }
+
+            return ((zoom, next.x, next.y), None);
+        }
+    }
+
+    // Case 2: Currently at/after a stop; the hold counts from its last click
+    if let Some(prev) = prev_stop {
+        let elapsed = timestamp - prev.end;
+
+        // Check if we should pan to next stop (staying zoomed)
+        if let Some(next) = next_stop {
+            let gap = next.start - prev.end;
+
+            if gap <= pan_window {
+                // We're in pan mode - stay zoomed and interpolate position
+                let time_to_next = next.start - timestamp;
+
+                // During hold phase: stay at prev position
+                if elapsed <= config.hold && time_to_next > config.ease_in {
+                    return ((prev.zoom, prev.x, prev.y), None);
+                }
+
+                // During pan phase: interpolate from prev to next
+                // Pan starts after hold ends OR when we're within ease_in of next stop
+                let pan_start_time = (prev.end + config.hold).min(next.start - config.ease_in);
+                if timestamp >= pan_start_time {
+                    let pan_duration = next.start - pan_start_time;
+                    let pan_elapsed = timestamp - pan_start_time;
+                    let pan_progress = (pan_elapsed / pan_duration).clamp(0.0, 1.0);
+
+                    return pan(prev, next, pan_progress, pan_duration, config);
+                }
+
+                // Still in hold phase
+                return ((prev.zoom, prev.x, prev.y), None);
+            }
+        }
+
+        // No upcoming stop within pan window - normal hold/zoom-out behavior
+        if elapsed <= config.hold {
+            // Hold phase
+            return ((prev.zoom, prev.x, prev.y), None);
+        } else if elapsed <= config.hold + config.ease_out {
+            // Zoom out phase
+            let progress = (elapsed - config.hold) / config.ease_out;
+            let zoom = prev.zoom - (prev.zoom - 1.0) * config.zoom_easing.ease_in(progress);
+            return ((zoom, prev.x, prev.y), None);
+        }
+    }
+
+    // Case 3: Idle (no relevant clicks)
+    ((1.0, default_pos.0, default_pos.1), None)
+}
+
+/// Camera while moving from `prev` to `next`, zoomed in. Pans that would
+/// exceed `max_pan_speed` over `duration` become a crossfade instead.
+fn pan(
+    prev: &CameraStop,
+    next: &CameraStop,
+    progress: f64,
+    duration: f64,
+    config: &ZoomConfig,
+) -> ((f64, f64, f64), Option<Crossfade>) {
+    let eased = config.pan_easing.ease_in_out(progress);
+    let zoom = lerp(prev.zoom, next.zoom, eased);
+
+    if let Some(max_speed) = config.max_pan_speed {
+        let distance = (next.x - prev.x).hypot(next.y - prev.y);
+        if distance > max_speed * duration.max(f64::EPSILON) {
+            let crossfade = Crossfade {
+                // Wide enough for both ends
+                zoom: prev.zoom.min(next.zoom),
+                from: (prev.x, prev.y),
+                to: (next.x, next.y),
+                progress: eased,
+            };
+            let (x, y) = if eased < 0.5 {
+                crossfade.from
+            } else {
+                crossfade.to
+            };
+            return ((crossfade.zoom, x, y), Some(crossfade));
+        }
+    }
+
+    let x = lerp(prev.x, next.x, eased);
+    let y = lerp(prev.y, next.y, eased);
+    ((zoom, x, y), None)
+}
+
+/// Where the camera settles for one click, or for a cluster of clicks
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CameraStop {
+    /// First click of the stop
+    pub start: f64,
+    /// Last click; the hold counts from here
+    pub end: f64,
+    /// Camera focus (screen points)
+    pub x: f64,
+    pub y: f64,
+    pub zoom: f64,
+}
+
+/// Plan where the camera stops: the effective clicks, with clicks made
+/// during a hold and close to it (`cluster_size`) merged into one stop that
+/// frames them all. Without `cluster_size` every click is its own stop.
+pub fn plan_camera(events: &[CursorEvent], config: &ZoomConfig) -> Vec<CameraStop> {
+    let mut stops: Vec<CameraStop> = Vec::new();
+    // Bounding box of the last stop's clicks: (min x, min y, max x, max y)
+    let mut bounds: (f64, f64, f64, f64) = (0.0, 0.0, 0.0, 0.0);
+
+    for click in effective_clicks(events, config) {
+        if let (Some(size), Some(stop)) = (config.cluster_size, stops.last_mut()) {
+            let grown = (
+                bounds.0.min(click.x),
+                bounds.1.min(click.y),
+                bounds.2.max(click.x),
+                bounds.3.max(click.y),
+            );
+            let fits = grown.2 - grown.0 <= size && grown.3 - grown.1 <= size;
+            if fits && click.timestamp - stop.end <= config.hold {
+                bounds = grown;
+                stop.end = click.timestamp;
+                stop.x = (grown.0 + grown.2) / 2.0;
+                stop.y = (grown.1 + grown.3) / 2.0;
+                stop.zoom = fit_zoom(grown, config);
+                continue;
+            }
+        }
+
+        bounds = (click.x, click.y, click.x, click.y);
+        stops.push(CameraStop {
+            start: click.timestamp,
+            end: click.timestamp,
+            x: click.x,
+            y: click.y,
+            zoom: config.max_zoom,
+        });
+    }
+
+    stops
+}
+
+/// Zoom that shows `bounds` with a margin, up to `max_zoom`. Approximate:
+/// the content doesn't quite fill the output.
+fn fit_zoom(bounds: (f64, f64, f64, f64), config: &ZoomConfig) -> f64 {
+    let Some((width, height)) = config.frame else {
+        return config.max_zoom;
+    };
+    let fit_x = width / (bounds.2 - bounds.0 + 2.0 * CLUSTER_MARGIN);
+    let fit_y = height / (bounds.3 - bounds.1 + 2.0 * CLUSTER_MARGIN);
+    fit_x.min(fit_y).clamp(1.0, config.max_zoom.max(1.0))
+}
+
+/// Seconds for one cycle of `--idle-drift` unless given
+const DEFAULT_DRIFT_PERIOD: f64 = 20.0;
+
+/// Slow zoom and pan while the camera is idle (a Ken Burns effect)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct IdleDrift {
+    /// Most extra zoom (0.05 = 5%)
+    pub amplitude: f64,
+    /// Seconds for one cycle
+    pub period: f64,
+}
+
+impl IdleDrift {
+    /// Blend the drift into `camera` (zoom and focus on the canvas) at
+    /// video time `time`, the more the closer the camera is to idle.
+    /// `bounds` is the content's rectangle on the canvas.
+    pub fn apply(
+        &self,
+        camera: (f64, f64, f64),
+        time: f64,
+        max_zoom: f64,
+        bounds: (f64, f64, f64, f64),
+    ) -> (f64, f64, f64) {
+        let (zoom, x, y) = camera;
+        let idle = if max_zoom > 1.0 {
+            ((max_zoom - zoom) / (max_zoom - 1.0)).clamp(0.0, 1.0)
+        } else {
+            1.0
+        };
+        let phase = 2.0 * std::f64::consts::PI * time / self.period.max(f64::EPSILON);
+
+        // Never quite back to 1, so the pan never stops
+        let drift_zoom = 1.0 + self.amplitude * (0.6 + 0.4 * phase.sin());
+        // Wander over the content; out of step so the path doesn't repeat as a line
+        let (left, top, width, height) = bounds;
+        let drift_x = left + width * (0.5 + 0.5 * (phase * 0.5).sin());
+        let drift_y = top + height * (0.5 + 0.5 * (phase * 0.37).cos());
+
+        (
+            zoom + (drift_zoom - 1.0) * idle,
+            lerp(x, drift_x, idle),
+            lerp(y, drift_y, idle),
+        )
+    }
+}
+
+/// Half the dead zone of `--follow-cursor`, as a fraction of half the view
+const FOLLOW_DEAD_ZONE: f64 = 0.4;
+
+/// Time constant (seconds) of the follow camera catching up with the cursor
+const FOLLOW_LAG: f64 = 0.35;
+
+/// Seconds between planned follow camera positions
+const FOLLOW_STEP: f64 = 1.0 / 60.0;
+
+/// Camera for `--follow-cursor`: zoomed in throughout, panning only when
+/// the cursor leaves a dead zone around the focus. Each position depends on
+/// the ones before it, so the whole path is planned up front.
+pub struct FollowCamera {
+    zoom: f64,
+    /// Tracking time of the first position
+    start: f64,
+    /// Focus (screen points) every `FOLLOW_STEP` seconds
+    path: Vec<(f64, f64)>,
+}
+
+impl FollowCamera {
+    /// Plan the camera from `start` to `end` (tracking time)
+    pub fn plan(events: &[CursorEvent], config: &ZoomConfig, start: f64, end: f64) -> Self {
+        // Without the recording's size, assume a common display
+        let (width, height) = config.frame.unwrap_or((1920.0, 1080.0));
+        let dead_x = width / config.max_zoom / 2.0 * FOLLOW_DEAD_ZONE;
+        let dead_y = height / config.max_zoom / 2.0 * FOLLOW_DEAD_ZONE;
+        let catch_up = 1.0 - (-FOLLOW_STEP / FOLLOW_LAG).exp();
+
+        let steps = ((end - start) / FOLLOW_STEP).ceil().max(0.0) as usize + 1;
+        let mut path = Vec::with_capacity(steps);
+        let mut next_event = 0;
+        let mut focus = None;
+        for i in 0..steps {
+            let t = start + i as f64 * FOLLOW_STEP;
+            while next_event < events.len() && events[next_event].timestamp <= t {
+                next_event += 1;
+            }
+            let cursor = cursor_between(events, next_event, t);
+            let (x, y) = focus.get_or_insert(cursor);
+
+            // Only move far enough to put the cursor back on the dead zone's edge
+            let target_x = cursor.0.clamp(*x - dead_x, *x + dead_x);
+            let target_y = cursor.1.clamp(*y - dead_y, *y + dead_y);
+            *x += (cursor.0 - target_x) * catch_up;
+            *y += (cursor.1 - target_y) * catch_up;
+            path.push((*x, *y));
+        }
+
+        Self {
+            zoom: config.max_zoom,
+            start,
+            path,
+        }
+    }
+
+    /// Zoom and focus at `timestamp`, like `calculate_zoom`
+    pub fn at(&self, timestamp: f64) -> (f64, f64, f64) {
+        let Some(&last) = self.path.last() else {
+            return (1.0, 0.0, 0.0);
+        };
+        let position = ((timestamp - self.start) / FOLLOW_STEP).max(0.0);
+        let i = position.floor() as usize;
+        let (x, y) = match (self.path.get(i), self.path.get(i + 1)) {
+            (Some(a), Some(b)) => {
+                let t = position - i as f64;
+                (lerp(a.0, b.0, t), lerp(a.1, b.1, t))
+            }
+            _ => last,
+        };
+        (self.zoom, x, y)
+    }
+}
+
+/// Half the dead zone of `--vertical`'s reframe, as a fraction of half the view
+const REFRAME_DEAD_ZONE: f64 = 0.25;
+
+/// Time constant (seconds) of the reframe catching up with cursor activity
+const REFRAME_LAG: f64 = 0.5;
+
+/// Horizontal camera for `--vertical`: where across a wide recording the
+/// tall output looks. It follows the cursor like `FollowCamera`, but heads
+/// for each click as the click camera starts zooming in on it, so the click
+/// is in view when it lands.
+pub struct ReframeCamera {
+    /// Tracking time of the first position
+    start: f64,
+    /// Focus x (screen points) every `FOLLOW_STEP` seconds
+    path: Vec<f64>,
+}
+
+impl ReframeCamera {
+    /// Plan the camera from `start` to `end` (tracking time) for a view
+    /// `view_width` screen points wide
+    pub fn plan(
+        events: &[CursorEvent],
+        config: &ZoomConfig,
+        view_width: f64,
+        start: f64,
+        end: f64,
+    ) -> Self {
+        let clicks = effective_clicks(events, config);
+        let dead_x = view_width / 2.0 * REFRAME_DEAD_ZONE;
+        let catch_up = 1.0 - (-FOLLOW_STEP / REFRAME_LAG).exp();
+
+        let steps = ((end - start) / FOLLOW_STEP).ceil().max(0.0) as usize + 1;
+        let mut path = Vec::with_capacity(steps);
+        let mut next_event = 0;
+        let mut next_click = 0;
+        let mut focus = None;
+        for i in 0..steps {
+            let t = start + i as f64 * FOLLOW_STEP;
+            while next_event < events.len() && events[next_event].timestamp <= t {
+                next_event += 1;
+            }
+            while next_click < clicks.len() && clicks[next_click].timestamp < t {
+                next_click += 1;
+            }
+            // Activity: the coming click, else wherever the cursor is
+            let target = match clicks.get(next_click) {
+                Some(click) if click.timestamp - t <= config.ease_in => click.x,
+                _ => cursor_between(events, next_event, t).0,
+            };
+            let x = focus.get_or_insert(target);
+
+            *x += (target - target.clamp(*x - dead_x, *x + dead_x)) * catch_up;
+            path.push(*x);
+        }
+
+        Self { start, path }
+    }
+
+    /// Focus x (screen points) at `timestamp`
+    pub fn at(&self, timestamp: f64) -> Option<f64> {
+        let last = *self.path.last()?;
+        let position = ((timestamp - self.start) / FOLLOW_STEP).max(0.0);
+        let i = position.floor() as usize;
+        Some(match (self.path.get(i), self.path.get(i + 1)) {
+            (Some(&a), Some(&b)) => lerp(a, b, position - i as f64),
+            _ => last,
+        })
+    }
+}
+
+/// Cursor position at `t`, interpolated between the events either side of
+/// `next` (the first event after `t`)
+fn cursor_between(events: &[CursorEvent], next: usize, t: f64) -> (f64, f64) {
+    match (next.checked_sub(1).map(|i| &events[i]), events.get(next)) {
+        (Some(a), Some(b)) => {
+            let span = (b.timestamp - a.timestamp).max(f64::EPSILON);
+            let progress = ((t - a.timestamp) / span).clamp(0.0, 1.0);
+            (lerp(a.x, b.x, progress), lerp(a.y, b.y, progress))
+        }
+        (Some(e), None) | (None, Some(e)) => (e.x, e.y),
+        (None, None) => (0.0, 0.0),
+    }
+}
+
+/// Get all effective clicks (filtered by debounce)
+pub fn effective_clicks<'a>(
+    events: &'a [CursorEvent],
+    config: &ZoomConfig,
+) -> Vec<&'a CursorEvent> {
+    let clicks: Vec<_> = events
+        .iter()
+        .filter(|e| matches!(e.event_type, EventType::LeftClick | EventType::RightClick))
+        .collect();
+
+    let mut effective: Vec<&CursorEvent> = Vec::new();
+
+    for click in clicks {
+        match effective.last() {
+            None => effective.push(click),
+            Some(prev) => {
+                if click.timestamp - prev.timestamp > config.debounce {
+                    effective.push(click);
+                }
+            }
+        }
+    }
+
+    effective
+}
+
+/// Linear interpolation
+fn lerp(a: f64, b: f64, t: f64) -> f64 {
+    a + (b - a) * t
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_click(x: f64, y: f64, timestamp: f64) -> CursorEvent {
+        CursorEvent {
+            x,
+            y,
+            timestamp,
+            event_type: EventType::LeftClick,
+            display: None,
+            shape: None,
+        }
+    }
+
+    #[test]
+    fn test_zoom_overrides() {
+        let overrides = ZoomOverrides {
+            zoom_level: Some(2.5),
+            zoom_hold: Some(1.0),
+            ..Default::default()
+        };
+        let config = overrides.apply(ZoomConfig::reduced_motion());
+        assert_eq!(config.max_zoom, 2.5);
+        assert_eq!(config.hold, 1.0);
+        assert_eq!(config.ease_in, ZoomConfig::reduced_motion().ease_in);
+
+        assert!(parse_zoom_level("0.5").is_err());
+        assert!(parse_seconds("-1").is_err());
+        assert_eq!(parse_seconds("0.25"), Ok(0.25));
+    }
+
+    #[test]
+    fn test_anticipatory_zoom_single_click() {
+        let config = ZoomConfig::default();
+        // Click at t=1.0s, hold=4.0s, ease_out=0.8s
+        let events = vec![make_click(100.0, 100.0, 1.0)];
+
+        // Before anticipatory window: should be idle (zoom=1.0)
+        let (zoom, _, _) = calculate_zoom(0.3, &events, &config);
+        assert!(
+            (zoom - 1.0).abs() < 0.01,
+            "Should be idle before anticipatory window"
+        );
+
+        // During anticipatory zoom (0.4s before click)
+        let (zoom, x, y) = calculate_zoom(0.6, &events, &config);
+        assert!(zoom > 1.0 && zoom < config.max_zoom, "Should be zooming in");
+        assert!((x - 100.0).abs() < 0.01, "Should target click position");
+        assert!((y - 100.0).abs() < 0.01, "Should target click position");
+
+        // At click moment: should be at max zoom
+        let (zoom, _, _) = calculate_zoom(1.0, &events, &config);
+        assert!(
+            (zoom - config.max_zoom).abs() < 0.01,
+            "Should be at max zoom at click moment"
+        );
+
+        // During hold
+        let (zoom, _, _) = calculate_zoom(3.0, &events, &config);
+        assert!(
+            (zoom - config.max_zoom).abs() < 0.01,
+            "Should hold at max zoom"
+        );
+
+        // During zoom out (hold ends at 1.0 + 4.0 = 5.0s)
+        let (zoom, _, _) = calculate_zoom(5.5, &events, &config);
+        assert!(
+            zoom > 1.0 && zoom < config.max_zoom,
+            "Should be zooming out"
+        );
+
+        // After zoom out complete (5.0 + 0.8 = 5.8s)
+        let (zoom, _, _) = calculate_zoom(6.0, &events, &config);
+        assert!((zoom - 1.0).abs() < 0.01, "Should be back to idle");
+    }
+
+    #[test]
+    fn test_panning_between_close_clicks() {
+        let config = ZoomConfig::default();
+        // Pan window = hold + ease_out + ease_in = 4.0 + 0.8 + 0.6 = 5.4s
+        // Two clicks 4.0s apart (within pan window)
+        let events = vec![make_click(100.0, 100.0, 1.0), make_click(200.0, 200.0, 5.0)];
+
+        // At first click: max zoom at first position
+        let (zoom, x, _) = calculate_zoom(1.0, &events, &config);
+        assert!((zoom - config.max_zoom).abs() < 0.01);
+        assert!((x - 100.0).abs() < 0.01);
+
+        // During hold at first click
+        let (zoom, x, _) = calculate_zoom(3.0, &events, &config);
+        assert!(
+            (zoom - config.max_zoom).abs() < 0.01,
+            "Should stay at max zoom"
+        );
+        assert!(
+            (x - 100.0).abs() < 0.01,
+            "Should stay at first click position during hold"
+        );
+
+        // During pan phase (approaching second click)
+        let (zoom, x, _) = calculate_zoom(4.7, &events, &config);
+        assert!(
+            (zoom - config.max_zoom).abs() < 0.01,
+            "Should stay at max zoom during pan"
+        );
+        assert!(x > 100.0 && x < 200.0, "Should be interpolating x position");
+
+        // At second click: max zoom at second position
+        let (zoom, x, y) = calculate_zoom(5.0, &events, &config);
+        assert!((zoom - config.max_zoom).abs() < 0.01);
+        assert!((x - 200.0).abs() < 0.01);
+        assert!((y - 200.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_zoom_out_between_far_clicks() {
+        let config = ZoomConfig::default();
+        // Pan window = hold + ease_out + ease_in = 4.0 + 0.8 + 0.6 = 5.4s
+        // Two clicks 10s apart (outside pan window)
+        let events = vec![
+            make_click(100.0, 100.0, 1.0),
+            make_click(200.0, 200.0, 11.0),
+        ];
+
+        // After first click's zoom out completes (1.0 + 4.0 hold + 0.8 ease_out = 5.8s)
+        let (zoom, _, _) = calculate_zoom(6.0, &events, &config);
+        assert!(
+            (zoom - 1.0).abs() < 0.01,
+            "Should zoom out to idle between far clicks"
+        );
+
+        // Before second click's anticipatory zoom
+        let (zoom, _, _) = calculate_zoom(10.0, &events, &config);
+        assert!(
+            (zoom - 1.0).abs() < 0.01,
+            "Should be idle before second click"
+        );
+
+        // During anticipatory zoom to second click
+        let (zoom, x, _) = calculate_zoom(10.6, &events, &config);
+        assert!(zoom > 1.0, "Should be zooming in to second click");
+        assert!(
+            (x - 200.0).abs() < 0.01,
+            "Should target second click position"
+        );
+    }
+
+    #[test]
+    fn test_reduced_motion_crossfades_fast_pans() {
+        let config = ZoomConfig::reduced_motion();
+        // 1000 points apart, 3s gap: the pan is far faster than 400 pt/s
+        let events = vec![make_click(0.0, 0.0, 1.0), make_click(1000.0, 0.0, 4.0)];
+
+        let ((zoom, x, _), crossfade) = calculate_camera(3.8, &events, &config);
+        let crossfade = crossfade.expect("Fast pan should become a crossfade");
+        assert!((zoom - config.max_zoom).abs() < 0.01);
+        assert_eq!(crossfade.from, (0.0, 0.0));
+        assert_eq!(crossfade.to, (1000.0, 0.0));
+        assert!(
+            x == 0.0 || x == 1000.0,
+            "Camera should never sit between the two clicks"
+        );
+
+        // Short pans still move normally
+        let events = vec![make_click(0.0, 0.0, 1.0), make_click(100.0, 0.0, 4.0)];
+        let ((_, x, _), crossfade) = calculate_camera(3.8, &events, &config);
+        assert!(crossfade.is_none());
+        assert!(x > 0.0 && x < 100.0);
+    }
+
+    #[test]
+    fn test_clustered_clicks_share_a_stop() {
+        let config = ZoomConfig {
+            cluster_size: Some(300.0),
+            ..ZoomConfig::default()
+        }
+        .fit_to(1000.0, 800.0);
+        let events = vec![
+            make_click(100.0, 100.0, 1.0),
+            make_click(300.0, 200.0, 3.0),
+            // Too far to join: a stop of its own
+            make_click(900.0, 700.0, 5.0),
+        ];
+
+        let stops = plan_camera(&events, &config);
+        assert_eq!(stops.len(), 2);
+        assert_eq!((stops[0].start, stops[0].end), (1.0, 3.0));
+        assert_eq!((stops[0].x, stops[0].y), (200.0, 150.0));
+        // Small enough to zoom in all the way
+        assert_eq!(stops[0].zoom, config.max_zoom);
+        assert_eq!(stops[1].zoom, config.max_zoom);
+
+        // The camera holds on the cluster's center between its clicks
+        let (_, x, y) = calculate_zoom(2.0, &events, &config);
+        assert_eq!((x, y), (200.0, 150.0));
+
+        // A wide cluster zooms out to fit
+        let events = vec![make_click(0.0, 0.0, 1.0), make_click(700.0, 0.0, 2.0)];
+        let config = ZoomConfig {
+            cluster_size: Some(800.0),
+            ..config
+        };
+        let stops = plan_camera(&events, &config);
+        assert_eq!(stops.len(), 1);
+        assert!((stops[0].zoom - 1000.0 / 820.0).abs() < 1e-9);
+
+        // Off by default
+        assert_eq!(plan_camera(&events, &ZoomConfig::default()).len(), 2);
+    }
+
+    #[test]
+    fn test_follow_camera_dead_zone() {
+        let config = ZoomConfig::default().fit_to(1000.0, 1000.0);
+        let mut events: Vec<_> = (0..=20)
+            .map(|i| CursorEvent {
+                event_type: EventType::Move,
+                ..make_click(500.0 + i as f64 * 5.0, 500.0, i as f64 * 0.1)
+            })
+            .collect();
+        // Then a long move right, held at the end
+        events.push(CursorEvent {
+            event_type: EventType::Move,
+            ..make_click(900.0, 500.0, 3.0)
+        });
+
+        let camera = FollowCamera::plan(&events, &config, 0.0, 6.0);
+        // Small moves stay inside the dead zone
+        let (zoom, x, y) = camera.at(2.0);
+        assert_eq!(zoom, config.max_zoom);
+        assert_eq!((x, y), (500.0, 500.0));
+
+        // The camera catches up until the cursor is back at the dead zone's edge
+        let dead_x = 1000.0 / config.max_zoom / 2.0 * FOLLOW_DEAD_ZONE;
+        let (_, x, _) = camera.at(6.0);
+        assert!((x - (900.0 - dead_x)).abs() < 1.0, "x = {}", x);
+        let (_, mid, _) = camera.at(3.1);
+        assert!(mid > 500.0 && mid < x);
+    }
+
+    #[test]
+    fn test_reframe_camera_heads_for_clicks() {
+        let config = ZoomConfig::default();
+        let events = vec![
+            CursorEvent {
+                event_type: EventType::Move,
+                ..make_click(300.0, 500.0, 0.0)
+            },
+            CursorEvent {
+                event_type: EventType::Move,
+                ..make_click(300.0, 500.0, 3.9)
+            },
+            CursorEvent {
+                event_type: EventType::Move,
+                ..make_click(1500.0, 500.0, 4.0)
+            },
+            make_click(1500.0, 500.0, 4.1),
+        ];
+
+        let camera = ReframeCamera::plan(&events, &config, 600.0, 0.0, 8.0);
+        assert_eq!(camera.at(1.0), Some(300.0));
+        // Heading for the click before the cursor gets there
+        let before = camera.at(3.8).unwrap();
+        assert!(before > 500.0, "x = {}", before);
+        let settled = camera.at(8.0).unwrap();
+        assert!((settled - (1500.0 - 600.0 / 2.0 * REFRAME_DEAD_ZONE)).abs() < 1.0);
+
+        let empty = ReframeCamera::plan(&[], &config, 600.0, 1.0, 0.0);
+        assert_eq!(empty.at(0.0), Some(0.0));
+    }
+
+    #[test]
+    fn test_idle_drift() {
+        let drift = IdleDrift {
+            amplitude: 0.05,
+            period: 20.0,
+        };
+        let bounds = (100.0, 100.0, 1000.0, 800.0);
+
+        // Idle: drifting, within the amplitude and the content
+        for time in [0.0, 3.0, 11.0, 40.0] {
+            let (zoom, x, y) = drift.apply((1.0, 0.0, 0.0), time, 1.8, bounds);
+            assert!(zoom > 1.0 && zoom <= 1.05, "zoom = {}", zoom);
+            assert!((100.0..=1100.0).contains(&x) && (100.0..=900.0).contains(&y));
+        }
+
+        // Zoomed in on a click: untouched
+        assert_eq!(
+            drift.apply((1.8, 400.0, 300.0), 3.0, 1.8, bounds),
+            (1.8, 400.0, 300.0)
+        );
+    }
+
+    #[test]
+    fn test_double_click_debounce() {
+        let config = ZoomConfig::default();
+        // Two clicks 0.1s apart (within debounce of 0.5s)
+        let events = vec![make_click(100.0, 100.0, 1.0), make_click(150.0, 150.0, 1.1)];
+
+        let effective = effective_clicks(&events, &config);
+        assert_eq!(effective.len(), 1, "Second click should be debounced");
+        assert!(
+            (effective[0].timestamp - 1.0).abs() < 0.01,
+            "Should keep first click"
+        );
+    }
+
+    #[test]
+    fn test_three_rapid_clicks_pan_through() {
+        let config = ZoomConfig::default();
+        // Pan window = 5.4s, three clicks each 3s apart (within pan window)
+        let events = vec![
+            make_click(100.0, 100.0, 1.0),
+            make_click(200.0, 200.0, 4.0),
+            make_click(300.0, 300.0, 7.0),
+        ];
+
+        // Should stay zoomed throughout and pan between all three
+        let (zoom, _, _) = calculate_zoom(2.0, &events, &config);
+        assert!((zoom - config.max_zoom).abs() < 0.01, "Should stay zoomed");
+
+        let (zoom, _, _) = calculate_zoom(5.0, &events, &config);
+        assert!(
+            (zoom - config.max_zoom).abs() < 0.01,
+            "Should stay zoomed through second click"
+        );
+
+        // After third click, should eventually zoom out (7.0 + 4.0 hold + 0.8 ease_out = 11.8s)
+        let (zoom, _, _) = calculate_zoom(12.0, &events, &config);
+        assert!(
+            (zoom - 1.0).abs() < 0.01,
+            "Should zoom out after last click"
+        );
+    }
+}
@@ -0,0 +1,521 @@
+//! `glide zoom-chart`: plot what the auto-camera will do, without rendering
+//!
+//! Two stacked panels share a time axis in video seconds: zoom level on top,
+//! camera focus (x and y as fractions of the recording) below. Clicks are
+//! vertical markers, dimmed when debouncing or an edit means they won't move
+//! the camera. The camera is planned as `process` plans it, from the
+//! `glide edit` sidecar and the same camera options.
+
+use crate::cursor_types::EventType;
+use crate::processing::frames::get_video_duration;
+use crate::processing::palette::Palette;
+use crate::processing::pipeline::{camera_events, Camera, CameraOptions};
+use crate::processing::progress::Reporter;
+use crate::processing::shapes::{draw_line, fill_rounded_rect};
+use crate::processing::text::{draw_text, load_font, measure_text};
+use crate::processing::zoom::{effective_clicks, FollowCamera, ZoomConfig};
+use crate::recording::metadata::RecordingMetadata;
+use ab_glyph::FontArc;
+use anyhow::{Context, Result};
+use image::{Rgba, RgbaImage};
+use std::path::Path;
+
+const CHART_WIDTH: u32 = 1600;
+const CHART_HEIGHT: u32 = 640;
+const MARGIN_LEFT: f64 = 80.0;
+const MARGIN_RIGHT: f64 = 30.0;
+const MARGIN_TOP: f64 = 40.0;
+const MARGIN_BOTTOM: f64 = 50.0;
+const PANEL_GAP: f64 = 50.0;
+const LABEL_SIZE: f32 = 16.0;
+
+const BACKGROUND: Rgba<u8> = Rgba([26, 26, 46, 255]);
+const PANEL: Rgba<u8> = Rgba([34, 34, 58, 255]);
+const GRID: Rgba<u8> = Rgba([255, 255, 255, 30]);
+const LABEL: Rgba<u8> = Rgba([200, 200, 215, 255]);
+const CLICK: Rgba<u8> = Rgba([255, 255, 255, 150]);
+const DEBOUNCED_CLICK: Rgba<u8> = Rgba([255, 255, 255, 45]);
+
+/// Camera state at one point in video time
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CameraSample {
+    /// Video time (seconds)
+    pub time: f64,
+    pub zoom: f64,
+    /// Camera focus as a fraction of the recording's width/height
+    pub x: f64,
+    pub y: f64,
+}
+
+/// Click times in video seconds, and whether each one drives the camera
+pub struct ChartClick {
+    pub time: f64,
+    pub effective: bool,
+}
+
+/// Sample `camera` `count` times across `duration` seconds of video
+pub(crate) fn sample_camera(
+    metadata: &RecordingMetadata,
+    camera: &Camera,
+    zoom_config: &ZoomConfig,
+    duration: f64,
+    time_offset: f64,
+    count: usize,
+) -> Vec<CameraSample> {
+    let scale_factor = metadata.scale_factor.max(1.0);
+    let width = metadata.width.max(1) as f64;
+    let height = metadata.height.max(1) as f64;
+
+    (0..count)
+        .map(|i| {
+            let time = duration * i as f64 / (count.max(2) - 1) as f64;
+            let ((zoom, x, y), crossfade) = camera.at(time + time_offset, zoom_config);
+            let (zoom, (x, y)) = match crossfade {
+                Some(c) if c.progress < 0.5 => (c.zoom, c.from),
+                Some(c) => (c.zoom, c.to),
+                None => (zoom, (x, y)),
+            };
+            let (offset_x, offset_y) = metadata.window_offset_at(time + time_offset);
+            let (x, y) = match camera {
+                // Never moves off the whole recording
+                Camera::Still => (0.5, 0.5),
+                _ => (
+                    (x - offset_x) * scale_factor / width,
+                    (y - offset_y) * scale_factor / height,
+                ),
+            };
+            CameraSample {
+                time,
+                zoom,
+                x: x.clamp(0.0, 1.0),
+                y: y.clamp(0.0, 1.0),
+            }
+        })
+        .collect()
+}
+
+/// Clicks within the video, in video time: the recorded ones, and the
+/// zooms an edit moved or a marker added
+pub(crate) fn chart_clicks(
+    metadata: &RecordingMetadata,
+    camera: &Camera,
+    zoom_config: &ZoomConfig,
+    duration: f64,
+    time_offset: f64,
+) -> Vec<ChartClick> {
+    let effective: Vec<f64> = match camera {
+        Camera::Clicks(events) => effective_clicks(events, zoom_config)
+            .iter()
+            .map(|c| c.timestamp)
+            .collect(),
+        _ => Vec::new(),
+    };
+    let recorded: Vec<f64> = metadata
+        .cursor_events
+        .iter()
+        .filter(|e| matches!(e.event_type, EventType::LeftClick | EventType::RightClick))
+        .map(|e| e.timestamp)
+        .collect();
+    let mut clicks: Vec<ChartClick> = recorded
+        .iter()
+        .map(|&timestamp| ChartClick {
+            time: timestamp,
+            effective: effective.contains(&timestamp),
+        })
+        .chain(
+            effective
+                .iter()
+                .filter(|timestamp| !recorded.contains(timestamp))
+                .map(|&timestamp| ChartClick {
+                    time: timestamp,
+                    effective: true,
+                }),
+        )
+        .map(|c| ChartClick {
+            time: c.time - time_offset,
+            ..c
+        })
+        .filter(|c| (0.0..=duration).contains(&c.time))
+        .collect();
+    clicks.sort_by(|a, b| a.time.total_cmp(&b.time));
+    clicks
+}
+
+/// Plot area of one panel
+struct Panel {
+    top: f64,
+    height: f64,
+    min: f64,
+    max: f64,
+}
+
+impl Panel {
+    fn y(&self, value: f64) -> f64 {
+        let t = (value - self.min) / (self.max - self.min).max(f64::EPSILON);
+        self.top + self.height * (1.0 - t.clamp(0.0, 1.0))
+    }
+}
+
+/// Draw the chart. Labels are skipped when no font is available.
+pub fn render_chart(
+    samples: &[CameraSample],
+    clicks: &[ChartClick],
+    duration: f64,
+    max_zoom: f64,
+    font: Option<&FontArc>,
+) -> RgbaImage {
+    let mut canvas = RgbaImage::from_pixel(CHART_WIDTH, CHART_HEIGHT, BACKGROUND);
+    let plot_width = CHART_WIDTH as f64 - MARGIN_LEFT - MARGIN_RIGHT;
+    let panel_height = (CHART_HEIGHT as f64 - MARGIN_TOP - MARGIN_BOTTOM - PANEL_GAP) / 2.0;
+    let x_of = |time: f64| MARGIN_LEFT + plot_width * (time / duration.max(f64::EPSILON));
+
+    let zoom_panel = Panel {
+        top: MARGIN_TOP,
+        height: panel_height,
+        min: 1.0,
+        max: max_zoom.max(1.1) + 0.1,
+    };
+    let pan_panel = Panel {
+        top: MARGIN_TOP + panel_height + PANEL_GAP,
+        height: panel_height,
+        min: 0.0,
+        max: 1.0,
+    };
+
+    let accents = Palette::OkabeIto.accents();
+    let (zoom_color, x_color, y_color) = (accents[0], accents[1], accents[2]);
+
+    for panel in [&zoom_panel, &pan_panel] {
+        fill_rounded_rect(
+            &mut canvas,
+            MARGIN_LEFT,
+            panel.top,
+            plot_width,
+            panel.height,
+            6.0,
+            PANEL,
+        );
+    }
+
+    // Time grid
+    let step = tick_step(duration);
+    let mut tick = 0.0;
+    while tick <= duration + 1e-9 {
+        let x = x_of(tick);
+        for panel in [&zoom_panel, &pan_panel] {
+            draw_line(
+                &mut canvas,
+                (x, panel.top),
+                (x, panel.top + panel.height),
+                1.0,
+                GRID,
+            );
+        }
+        if let Some(font) = font {
+            let label = format_time(tick);
+            let (w, _) = measure_text(font, LABEL_SIZE, &label);
+            let y = pan_panel.top + pan_panel.height + 10.0;
+            draw_text(
+                &mut canvas,
+                font,
+                LABEL_SIZE,
+                x - w as f64 / 2.0,
+                y,
+                &label,
+                LABEL,
+            );
+        }
+        tick += step;
+    }
+
+    // Value grid: 1x and max zoom, 0/0.5/1 of the frame
+    for (panel, value, label) in [
+        (&zoom_panel, 1.0, "1.0x".to_string()),
+        (&zoom_panel, max_zoom, format!("{:.1}x", max_zoom)),
+        (&pan_panel, 0.0, "0%".to_string()),
+        (&pan_panel, 0.5, "50%".to_string()),
+        (&pan_panel, 1.0, "100%".to_string()),
+    ] {
+        let y = panel.y(value);
+        draw_line(
+            &mut canvas,
+            (MARGIN_LEFT, y),
+            (MARGIN_LEFT + plot_width, y),
+            1.0,
+            GRID,
+        );
+        if let Some(font) = font {
+            let (w, h) = measure_text(font, LABEL_SIZE, &label);
+            let (lx, ly) = (MARGIN_LEFT - w as f64 - 10.0, y - h as f64 / 2.0);
+            draw_text(&mut canvas, font, LABEL_SIZE, lx, ly, &label, LABEL);
+        }
+    }
+
+    // Click markers behind the curves
+    for click in clicks {
+        let x = x_of(click.time);
+        let color = if click.effective {
+            CLICK
+        } else {
+            DEBOUNCED_CLICK
+        };
+        for panel in [&zoom_panel, &pan_panel] {
+            draw_line(
+                &mut canvas,
+                (x, panel.top),
+                (x, panel.top + panel.height),
+                2.0,
+                color,
+            );
+        }
+    }
+
+    for pair in samples.windows(2) {
+        let (a, b) = (pair[0], pair[1]);
+        let (ax, bx) = (x_of(a.time), x_of(b.time));
+        draw_line(
+            &mut canvas,
+            (ax, zoom_panel.y(a.zoom)),
+            (bx, zoom_panel.y(b.zoom)),
+            2.5,
+            zoom_color,
+        );
+        draw_line(
+            &mut canvas,
+            (ax, pan_panel.y(a.x)),
+            (bx, pan_panel.y(b.x)),
+            2.0,
+            x_color,
+        );
+        draw_line(
+            &mut canvas,
+            (ax, pan_panel.y(a.y)),
+            (bx, pan_panel.y(b.y)),
+            2.0,
+            y_color,
+        );
+    }
+
+    if let Some(font) = font {
+        let title_y = |panel: &Panel| panel.top - LABEL_SIZE as f64 - 10.0;
+        draw_text(
+            &mut canvas,
+            font,
+            LABEL_SIZE,
+            MARGIN_LEFT,
+            title_y(&zoom_panel),
+            "Zoom",
+            zoom_color,
+        );
+        draw_text(
+            &mut canvas,
+            font,
+            LABEL_SIZE,
+            MARGIN_LEFT,
+            title_y(&pan_panel),
+            "Focus x",
+            x_color,
+        );
+        let (w, _) = measure_text(font, LABEL_SIZE, "Focus x  ");
+        draw_text(
+            &mut canvas,
+            font,
+            LABEL_SIZE,
+            MARGIN_LEFT + w as f64,
+            title_y(&pan_panel),
+            "Focus y",
+            y_color,
+        );
+    }
+
+    canvas
+}
+
+/// Plot the camera `process` would plan for a recording to `output` (PNG)
+pub fn write_zoom_chart(
+    input: &Path,
+    output: &Path,
+    zoom_config: &ZoomConfig,
+    camera_options: CameraOptions,
+) -> Result<()> {
+    let metadata = RecordingMetadata::load(input)
+        .context("Failed to load recording metadata. Was this video recorded with glide?")?;
+    let duration = get_video_duration(input)?;
+    if duration <= 0.0 {
+        anyhow::bail!("Could not read the duration of {}", input.display());
+    }
+    // Same alignment as `process`: cursor tracking may have started before the video
+    let time_offset = if metadata.cursor_tracking_duration > 0.0 {
+        metadata.cursor_tracking_duration - duration
+    } else {
+        0.0
+    };
+
+    let (points_width, points_height) = metadata.size_in_points();
+    let zoom_config = &zoom_config.fit_to(points_width, points_height);
+
+    let events = camera_events(
+        input,
+        &metadata,
+        zoom_config,
+        camera_options,
+        &Reporter::default(),
+    )?;
+    let follow = camera_options.follow_cursor.then(|| {
+        FollowCamera::plan(
+            &metadata.cursor_events,
+            zoom_config,
+            time_offset,
+            time_offset + duration,
+        )
+    });
+    let camera = Camera::new(camera_options, &events, follow.as_ref());
+
+    let plot_width = (CHART_WIDTH as f64 - MARGIN_LEFT - MARGIN_RIGHT) as usize;
+    let samples = sample_camera(
+        &metadata,
+        &camera,
+        zoom_config,
+        duration,
+        time_offset,
+        plot_width,
+    );
+    let clicks = chart_clicks(&metadata, &camera, zoom_config, duration, time_offset);
+    let font = load_font(None).ok();
+    let chart = render_chart(
+        &samples,
+        &clicks,
+        duration,
+        zoom_config.max_zoom,
+        font.as_ref(),
+    );
+    chart
+        .save(output)
+        .with_context(|| format!("Failed to write chart to {}", output.display()))?;
+
+    let effective = clicks.iter().filter(|c| c.effective).count();
+    tracing::info!(
+        "Zoom chart: {} ({:.1}s, {} clicks, {} zooms)",
+        output.display(),
+        duration,
+        clicks.len(),
+        effective
+    );
+    Ok(())
+}
+
+/// Tick spacing giving at most ~12 ticks
+fn tick_step(duration: f64) -> f64 {
+    const STEPS: [f64; 9] = [1.0, 2.0, 5.0, 10.0, 15.0, 30.0, 60.0, 120.0, 300.0];
+    STEPS
+        .into_iter()
+        .find(|step| duration / step <= 12.0)
+        .unwrap_or(600.0)
+}
+
+fn format_time(seconds: f64) -> String {
+    let total = seconds.round() as u64;
+    format!("{}:{:02}", total / 60, total % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cursor_types::CursorEvent;
+    use crate::edit::EditDecisions;
+
+    fn make_event(x: f64, y: f64, timestamp: f64, event_type: EventType) -> CursorEvent {
+        CursorEvent {
+            x,
+            y,
+            timestamp,
+            event_type,
+            display: None,
+            shape: None,
+        }
+    }
+
+    #[test]
+    fn test_samples_follow_clicks() {
+        let mut metadata = RecordingMetadata::new_display(0, 1000, 500, 1.0);
+        metadata.cursor_events = vec![
+            make_event(500.0, 250.0, 0.0, EventType::Move),
+            make_event(750.0, 125.0, 3.0, EventType::LeftClick),
+            // Debounced: too close to the previous click
+            make_event(760.0, 125.0, 3.2, EventType::LeftClick),
+        ];
+        let config = ZoomConfig::default();
+        let camera = Camera::Clicks(&metadata.cursor_events);
+
+        // Video starts 1s into cursor tracking
+        let samples = sample_camera(&metadata, &camera, &config, 10.0, 1.0, 11);
+        assert_eq!(samples.len(), 11);
+        assert_eq!(samples[0].zoom, 1.0);
+        let at_click = samples[2];
+        assert_eq!(at_click.time, 2.0);
+        assert!((at_click.zoom - config.max_zoom).abs() < 1e-9);
+        assert!((at_click.x - 0.75).abs() < 1e-9);
+        assert!((at_click.y - 0.25).abs() < 1e-9);
+
+        let clicks = chart_clicks(&metadata, &camera, &config, 10.0, 1.0);
+        assert_eq!(clicks.len(), 2);
+        assert!(clicks[0].effective);
+        assert!(!clicks[1].effective);
+
+        // --no-zoom never leaves the whole recording
+        let samples = sample_camera(&metadata, &Camera::Still, &config, 10.0, 1.0, 11);
+        assert!(samples.iter().all(|s| s.zoom == 1.0 && s.x == 0.5));
+        let clicks = chart_clicks(&metadata, &Camera::Still, &config, 10.0, 1.0);
+        assert!(clicks.iter().all(|c| !c.effective));
+    }
+
+    #[test]
+    fn test_edits_move_the_camera() {
+        let dir = tempfile::tempdir().unwrap();
+        let video = dir.path().join("rec.mp4");
+        let mut metadata = RecordingMetadata::new_display(0, 1000, 500, 1.0);
+        metadata.cursor_events = vec![
+            make_event(500.0, 250.0, 0.0, EventType::Move),
+            make_event(750.0, 125.0, 3.0, EventType::LeftClick),
+            make_event(250.0, 375.0, 8.0, EventType::LeftClick),
+        ];
+        let config = ZoomConfig::default();
+
+        // Delete the first zoom and move the second one 2s earlier
+        let mut edits = EditDecisions::default();
+        edits.get_mut(3.0).delete = true;
+        edits.get_mut(8.0).shift = -2.0;
+        edits.save(&video).unwrap();
+
+        let options = CameraOptions::default();
+        let events =
+            camera_events(&video, &metadata, &config, options, &Reporter::default()).unwrap();
+        let camera = Camera::new(options, &events, None);
+        let samples = sample_camera(&metadata, &camera, &config, 10.0, 0.0, 11);
+        assert_eq!(samples[3].zoom, 1.0);
+        assert!((samples[6].zoom - config.max_zoom).abs() < 1e-9);
+        assert!((samples[6].x - 0.25).abs() < 1e-9);
+
+        let clicks = chart_clicks(&metadata, &camera, &config, 10.0, 0.0);
+        let times: Vec<_> = clicks.iter().map(|c| (c.time, c.effective)).collect();
+        assert_eq!(times, vec![(3.0, false), (6.0, true), (8.0, false)]);
+
+        // --no-zoom leaves the edits out
+        let options = CameraOptions {
+            no_zoom: true,
+            ..CameraOptions::default()
+        };
+        let events =
+            camera_events(&video, &metadata, &config, options, &Reporter::default()).unwrap();
+        let camera = Camera::new(options, &events, None);
+        let samples = sample_camera(&metadata, &camera, &config, 10.0, 0.0, 11);
+        assert!(samples.iter().all(|s| s.zoom == 1.0));
+    }
+
+    #[test]
+    fn test_tick_step() {
+        assert_eq!(tick_step(8.0), 1.0);
+        assert_eq!(tick_step(45.0), 5.0);
+        assert_eq!(tick_step(600.0), 60.0);
+    }
+}
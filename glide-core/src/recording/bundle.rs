@@ -0,0 +1,249 @@
+//! Recording bundles: a raw capture and its metadata in one file, so one
+//! machine can record and another can process (`glide pack` / `glide unpack`)
+//!
+//! A bundle is an uncompressed tar (the video is already compressed) holding
+//! `manifest.json` first, then the video and its metadata.
+
+use crate::recording::crypto::is_encrypted;
+use crate::recording::integrity::hash_file;
+use crate::recording::metadata::{metadata_path_for_video, RecordingMetadata, SCHEMA_VERSION};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// File extension for bundles
+pub const BUNDLE_EXTENSION: &str = "glide";
+
+const MANIFEST_NAME: &str = "manifest.json";
+
+/// Describes and checksums the contents of a bundle
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Manifest {
+    /// Recording format version (see `metadata::SCHEMA_VERSION`)
+    pub schema_version: u32,
+    /// glide version that made the bundle
+    pub glide_version: String,
+    pub video: String,
+    pub video_sha256: String,
+    pub metadata: String,
+    pub metadata_sha256: String,
+}
+
+/// Whether `path` looks like a bundle made by `glide pack`
+pub fn is_bundle(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == BUNDLE_EXTENSION)
+}
+
+/// Pack a recording and its metadata into a bundle at `output`
+pub fn pack(video: &Path, output: &Path) -> Result<Manifest> {
+    let metadata_path = metadata_path_for_video(video);
+    if !video.is_file() {
+        anyhow::bail!("Recording {} not found", video.display());
+    }
+    if !metadata_path.is_file() {
+        anyhow::bail!(
+            "Metadata {} not found; a bundle needs both the video and its metadata",
+            metadata_path.display()
+        );
+    }
+    // Encrypted metadata can only be checked once decrypted, at process time
+    if !is_encrypted(&metadata_path) {
+        RecordingMetadata::load(video)?;
+    }
+
+    let video_name = file_name(video)?;
+    let manifest = Manifest {
+        schema_version: SCHEMA_VERSION,
+        glide_version: env!("CARGO_PKG_VERSION").to_string(),
+        metadata: Path::new(&video_name)
+            .with_extension("json")
+            .to_string_lossy()
+            .into_owned(),
+        video: video_name,
+        video_sha256: hash_file(video)?,
+        metadata_sha256: hash_file(&metadata_path)?,
+    };
+
+    let file =
+        File::create(output).with_context(|| format!("Failed to create {}", output.display()))?;
+    let mut builder = tar::Builder::new(file);
+
+    let manifest_json = serde_json::to_vec_pretty(&manifest)?;
+    let mut header = tar::Header::new_gnu();
+    header.set_size(manifest_json.len() as u64);
+    header.set_mode(0o644);
+    header.set_cksum();
+    builder.append_data(&mut header, MANIFEST_NAME, manifest_json.as_slice())?;
+
+    builder.append_path_with_name(video, &manifest.video)?;
+    builder.append_path_with_name(&metadata_path, &manifest.metadata)?;
+    builder
+        .into_inner()
+        .with_context(|| format!("Failed to write bundle {}", output.display()))?;
+
+    Ok(manifest)
+}
+
+/// Extract a bundle into `dest_dir`, verifying its schema version and checksums.
+/// Returns the path of the extracted video.
+pub fn unpack(bundle: &Path, dest_dir: &Path, overwrite: bool) -> Result<PathBuf> {
+    let file = File::open(bundle)
+        .with_context(|| format!("Failed to open bundle {}", bundle.display()))?;
+    let mut archive = tar::Archive::new(file);
+    let mut entries = archive
+        .entries()
+        .with_context(|| format!("{} is not a glide bundle", bundle.display()))?;
+
+    let manifest: Manifest = {
+        let mut entry = entries
+            .next()
+            .context("Bundle is empty")?
+            .context("Failed to read bundle")?;
+        if entry.path()?.as_ref() != Path::new(MANIFEST_NAME) {
+            anyhow::bail!("{} is not a glide bundle (no manifest)", bundle.display());
+        }
+        let mut json = String::new();
+        entry.read_to_string(&mut json)?;
+        serde_json::from_str(&json).context("Bundle manifest is invalid")?
+    };
+    check_manifest(&manifest)?;
+
+    fs::create_dir_all(dest_dir)?;
+    let video = dest_dir.join(&manifest.video);
+    let metadata = dest_dir.join(&manifest.metadata);
+    if !overwrite {
+        for path in [&video, &metadata] {
+            if path.exists() {
+                anyhow::bail!(
+                    "{} already exists (use --force to overwrite)",
+                    path.display()
+                );
+            }
+        }
+    }
+
+    for entry in entries {
+        let mut entry = entry.context("Failed to read bundle")?;
+        let name = entry.path()?.to_string_lossy().into_owned();
+        // Only the files the manifest names; never anything with a path
+        if name == manifest.video || name == manifest.metadata {
+            entry
+                .unpack(dest_dir.join(&name))
+                .with_context(|| format!("Failed to extract {}", name))?;
+        }
+    }
+
+    for (path, expected) in [
+        (&video, &manifest.video_sha256),
+        (&metadata, &manifest.metadata_sha256),
+    ] {
+        if !path.exists() {
+            anyhow::bail!(
+                "Bundle is incomplete: {} is missing",
+                path.file_name().unwrap_or_default().to_string_lossy()
+            );
+        }
+        if hash_file(path)? != *expected {
+            anyhow::bail!(
+                "Bundle is corrupt: {} doesn't match its checksum",
+                path.display()
+            );
+        }
+    }
+
+    Ok(video)
+}
+
+/// Reject bundles this version can't read or whose file names aren't plain
+fn check_manifest(manifest: &Manifest) -> Result<()> {
+    if manifest.schema_version > SCHEMA_VERSION {
+        anyhow::bail!(
+            "Bundle was made by glide {} with recording schema v{}; this glide reads up to v{}. Upgrade glide to process it.",
+            manifest.glide_version,
+            manifest.schema_version,
+            SCHEMA_VERSION
+        );
+    }
+    for name in [&manifest.video, &manifest.metadata] {
+        let path = Path::new(name);
+        if path.file_name() != Some(path.as_os_str()) {
+            anyhow::bail!("Bundle manifest has an invalid file name: {}", name);
+        }
+    }
+    if Path::new(&manifest.video).with_extension("json") != Path::new(&manifest.metadata) {
+        anyhow::bail!("Bundle manifest doesn't pair the video with its metadata");
+    }
+    Ok(())
+}
+
+fn file_name(path: &Path) -> Result<String> {
+    path.file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .with_context(|| format!("{} has no file name", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_recording(dir: &Path) -> PathBuf {
+        let video = dir.join("demo.mp4");
+        fs::write(&video, b"not really a video").unwrap();
+        RecordingMetadata::new_display(0, 100, 100, 1.0)
+            .save(&video)
+            .unwrap();
+        video
+    }
+
+    #[test]
+    fn test_pack_unpack_roundtrip() {
+        let src = tempfile::tempdir().unwrap();
+        let dest = tempfile::tempdir().unwrap();
+        let video = make_recording(src.path());
+        let bundle = src.path().join("demo.glide");
+
+        let manifest = pack(&video, &bundle).unwrap();
+        assert_eq!(manifest.metadata, "demo.json");
+        assert!(is_bundle(&bundle));
+
+        let unpacked = unpack(&bundle, dest.path(), false).unwrap();
+        assert_eq!(unpacked, dest.path().join("demo.mp4"));
+        assert_eq!(fs::read(&unpacked).unwrap(), b"not really a video");
+        assert!(RecordingMetadata::load(&unpacked).is_ok());
+
+        // Refuses to clobber without --force
+        assert!(unpack(&bundle, dest.path(), false).is_err());
+        assert!(unpack(&bundle, dest.path(), true).is_ok());
+    }
+
+    #[test]
+    fn test_pack_requires_metadata() {
+        let dir = tempfile::tempdir().unwrap();
+        let video = dir.path().join("demo.mp4");
+        fs::write(&video, b"video").unwrap();
+        assert!(pack(&video, &dir.path().join("demo.glide")).is_err());
+    }
+
+    #[test]
+    fn test_rejects_newer_schema_and_paths() {
+        let mut manifest = Manifest {
+            schema_version: SCHEMA_VERSION,
+            glide_version: "0.1.0".into(),
+            video: "demo.mp4".into(),
+            video_sha256: String::new(),
+            metadata: "demo.json".into(),
+            metadata_sha256: String::new(),
+        };
+        assert!(check_manifest(&manifest).is_ok());
+
+        manifest.schema_version = SCHEMA_VERSION + 1;
+        assert!(check_manifest(&manifest).is_err());
+
+        manifest.schema_version = SCHEMA_VERSION;
+        manifest.video = "../demo.mp4".into();
+        manifest.metadata = "../demo.json".into();
+        assert!(check_manifest(&manifest).is_err());
+    }
+}
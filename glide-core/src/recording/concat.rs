@@ -0,0 +1,274 @@
+//! `glide concat`: join takes into one recording that processes as a
+//! single continuous video
+
+use crate::library;
+use crate::processing::frames::{get_video_duration, get_video_size, has_audio};
+use crate::recording::encoder::concat_segments;
+use crate::recording::integrity::{hash_file, new_recording_id};
+use crate::recording::metadata::{Marker, RecordingMetadata};
+use crate::recording::pause::PauseSpan;
+use anyhow::{bail, Context, Result};
+use std::path::{Path, PathBuf};
+
+/// One recording to join: its metadata and the length of its video
+pub struct Take {
+    pub metadata: RecordingMetadata,
+    pub video_duration: f64,
+}
+
+/// Metadata for the takes played back to back.
+///
+/// Every take's events are moved onto the joined video's timeline (so the
+/// result has no cursor/video offset) and into the first take's window
+/// coordinates, which then hold for the whole recording.
+pub fn merge_metadata(takes: &[Take]) -> Result<RecordingMetadata> {
+    let Some(first) = takes.first() else {
+        bail!("Nothing to join");
+    };
+    let base = &first.metadata;
+    let base_offset = (base.window_offset.0 as f64, base.window_offset.1 as f64);
+
+    let mut merged = RecordingMetadata {
+        cursor_events: Vec::new(),
+        click_targets: Vec::new(),
+        pauses: Vec::new(),
+        window_bounds: Vec::new(),
+        capture_stats: None,
+        markers: Vec::new(),
+        recording_id: None,
+        video_sha256: None,
+        ..base.clone()
+    };
+
+    let mut start = 0.0;
+    for (i, take) in takes.iter().enumerate() {
+        let metadata = &take.metadata;
+        if (metadata.width, metadata.height) != (base.width, base.height) {
+            bail!(
+                "Take {} is {}x{}, but the first is {}x{}; takes must be the same size",
+                i + 1,
+                metadata.width,
+                metadata.height,
+                base.width,
+                base.height
+            );
+        }
+        if metadata.scale_factor != base.scale_factor {
+            bail!(
+                "Take {} was recorded at scale factor {}, but the first at {}",
+                i + 1,
+                metadata.scale_factor,
+                base.scale_factor
+            );
+        }
+
+        // Same offset `glide process` applies: cursor time minus this is video time
+        let time_offset = if metadata.cursor_tracking_duration > 0.0 {
+            metadata.cursor_tracking_duration - take.video_duration
+        } else {
+            0.0
+        };
+        let duration = take.video_duration;
+        let to_merged = |timestamp: f64| {
+            let video_time = timestamp - time_offset;
+            (0.0..=duration)
+                .contains(&video_time)
+                .then_some(start + video_time)
+        };
+        // Moves a screen position from this take's window onto the first's
+        let shift = |timestamp: f64| {
+            let (x, y) = metadata.window_offset_at(timestamp);
+            (base_offset.0 - x, base_offset.1 - y)
+        };
+
+        for event in &metadata.cursor_events {
+            if let Some(timestamp) = to_merged(event.timestamp) {
+                let (dx, dy) = shift(event.timestamp);
+                let mut event = event.clone();
+                event.timestamp = timestamp;
+                event.x += dx;
+                event.y += dy;
+                merged.cursor_events.push(event);
+            }
+        }
+        for target in &metadata.click_targets {
+            if let Some(timestamp) = to_merged(target.timestamp) {
+                let (dx, dy) = shift(target.timestamp);
+                let mut target = *target;
+                target.timestamp = timestamp;
+                target.x += dx;
+                target.y += dy;
+                merged.click_targets.push(target);
+            }
+        }
+        for marker in &metadata.markers {
+            if let Some(timestamp) = to_merged(marker.timestamp) {
+                merged.markers.push(Marker {
+                    timestamp,
+                    ..marker.clone()
+                });
+            }
+        }
+        merged
+            .pauses
+            .extend(metadata.pauses.iter().map(|pause| PauseSpan {
+                at: start + pause.at,
+                duration: pause.duration,
+            }));
+
+        start += duration;
+    }
+    merged.cursor_tracking_duration = start;
+
+    Ok(merged)
+}
+
+/// Join recordings and their metadata into `output`, without re-encoding
+pub fn concat_recordings(inputs: &[PathBuf], output: &Path) -> Result<()> {
+    if inputs.len() < 2 {
+        bail!("Give at least two recordings to join");
+    }
+
+    let mut takes = Vec::with_capacity(inputs.len());
+    let first_size = get_video_size(&inputs[0])?;
+    let first_audio = has_audio(&inputs[0]);
+    for input in inputs {
+        let metadata = RecordingMetadata::load(input).with_context(|| {
+            format!(
+                "Failed to load metadata for {}. Was it recorded with glide?",
+                input.display()
+            )
+        })?;
+        // Stream copy needs matching streams in every file
+        if get_video_size(input)? != first_size {
+            bail!(
+                "{} doesn't match the frame size of {}",
+                input.display(),
+                inputs[0].display()
+            );
+        }
+        if has_audio(input) != first_audio {
+            bail!(
+                "Some recordings have audio and some don't ({} vs {})",
+                input.display(),
+                inputs[0].display()
+            );
+        }
+        takes.push(Take {
+            metadata,
+            video_duration: get_video_duration(input)?,
+        });
+    }
+    let mut metadata = merge_metadata(&takes)?;
+
+    tracing::info!("Joining {} recordings...", inputs.len());
+    let recording_id = new_recording_id();
+    concat_segments(inputs, output, &recording_id)?;
+    metadata.recording_id = Some(recording_id);
+    metadata.video_sha256 = Some(hash_file(output)?);
+    metadata.save(output)?;
+
+    let duration = get_video_duration(output)?;
+    library::update(|library| library.add_recording(output, duration));
+    tracing::info!(
+        "Wrote {} ({:.1}s, {} cursor events)",
+        output.display(),
+        duration,
+        metadata.cursor_events.len()
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cursor_types::{CursorEvent, EventType};
+
+    fn click(timestamp: f64, x: f64, y: f64) -> CursorEvent {
+        CursorEvent {
+            x,
+            y,
+            timestamp,
+            event_type: EventType::LeftClick,
+            display: None,
+            shape: None,
+        }
+    }
+
+    #[test]
+    fn test_merge_offsets_second_take() {
+        let mut a = RecordingMetadata::new_display(0, 1920, 1080, 1.0);
+        a.cursor_events = vec![click(1.0, 10.0, 10.0)];
+        // Cursor tracking started 0.5s before the video
+        let mut b = RecordingMetadata::new_display(0, 1920, 1080, 1.0);
+        b.cursor_tracking_duration = 4.5;
+        b.cursor_events = vec![click(0.2, 0.0, 0.0), click(2.5, 20.0, 20.0)];
+        b.pauses = vec![PauseSpan {
+            at: 1.0,
+            duration: 3.0,
+        }];
+        b.markers = vec![Marker {
+            timestamp: 3.5,
+            name: Some("Settings".to_string()),
+        }];
+
+        let merged = merge_metadata(&[
+            Take {
+                metadata: a,
+                video_duration: 5.0,
+            },
+            Take {
+                metadata: b,
+                video_duration: 4.0,
+            },
+        ])
+        .unwrap();
+
+        // The click before b's video started is dropped
+        let times: Vec<f64> = merged.cursor_events.iter().map(|e| e.timestamp).collect();
+        assert_eq!(times, vec![1.0, 7.0]);
+        assert_eq!(merged.cursor_tracking_duration, 9.0);
+        assert_eq!(merged.pauses[0].at, 6.0);
+        assert_eq!(merged.markers[0].timestamp, 8.0);
+    }
+
+    #[test]
+    fn test_merge_moves_events_into_first_window() {
+        let mut a = RecordingMetadata::new_window(1, 800, 600, 100, 100, 1.0);
+        a.cursor_events = vec![click(0.5, 150.0, 150.0)];
+        let mut b = RecordingMetadata::new_window(1, 800, 600, 300, 200, 1.0);
+        b.cursor_events = vec![click(0.5, 350.0, 250.0)];
+
+        let merged = merge_metadata(&[
+            Take {
+                metadata: a,
+                video_duration: 1.0,
+            },
+            Take {
+                metadata: b,
+                video_duration: 1.0,
+            },
+        ])
+        .unwrap();
+
+        // Both clicks were 50 points into the window
+        let second = &merged.cursor_events[1];
+        assert_eq!((second.x, second.y), (150.0, 150.0));
+        assert_eq!(merged.window_offset, (100, 100));
+    }
+
+    #[test]
+    fn test_merge_rejects_different_sizes() {
+        let takes = [
+            Take {
+                metadata: RecordingMetadata::new_display(0, 1920, 1080, 1.0),
+                video_duration: 1.0,
+            },
+            Take {
+                metadata: RecordingMetadata::new_display(0, 1280, 720, 1.0),
+                video_duration: 1.0,
+            },
+        ];
+        assert!(merge_metadata(&takes).is_err());
+    }
+}
@@ -0,0 +1,638 @@
+//! Controlling a recording from another process: `glide start`, `stop`,
+//! `mark` and `status`, and automation driving a demo, talk to the recorder over
+//! a unix socket, with one JSON request and one JSON reply per connection
+//!
+//! The recorder keeps the socket until its files are saved, so a client
+//! that waits for it to go away knows the recording is complete.
+
+use crate::library::format_duration;
+use crate::recording::pause;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// How long `record --detach` waits for the recorder to answer before
+/// leaving it to start up on its own
+const DETACH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How often clients poll the recorder
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Where the recorder listens: the user's runtime directory, or the temp
+/// directory where there isn't one (macOS gives each user their own)
+pub fn socket_path() -> PathBuf {
+    dirs::runtime_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join("glide.sock")
+}
+
+/// What a client asks the recorder to do
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum Request {
+    /// Begin a recording started with `--wait-for-start`
+    Start,
+    /// Stop and save the recording
+    Stop,
+    Pause,
+    Resume,
+    /// Mark the current moment, optionally with a name
+    Mark {
+        #[serde(default)]
+        name: Option<String>,
+    },
+    /// Report how the recording is going
+    Status,
+}
+
+/// Where a recording is at
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum State {
+    /// Waiting for the first frame (or the hotkey)
+    #[default]
+    Starting,
+    /// Waiting for a start request (`--wait-for-start`)
+    Waiting,
+    Recording,
+    Paused,
+    /// Stopped and writing out the files
+    Saving,
+}
+
+/// The recorder's answer to `Request::Status`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Status {
+    pub pid: u32,
+    pub state: State,
+    /// The file being written, None when streaming
+    pub output: Option<PathBuf>,
+    /// Seconds recorded, without pauses
+    pub recorded: f64,
+    /// Frames encoded into the current file
+    pub frames: u64,
+    /// Markers dropped so far
+    pub markers: usize,
+    /// False when streaming or encrypting, which can't be paused
+    pub can_pause: bool,
+}
+
+/// The recorder's answer to a request
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Reply {
+    pub ok: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<Status>,
+}
+
+/// The latest status, kept up to date by the recording loop
+static STATUS: Mutex<Option<Status>> = Mutex::new(None);
+
+/// Set by a start request, for `wait_for_start`
+static START: AtomicBool = AtomicBool::new(false);
+
+/// Markers requested since the recording loop last took them
+static MARKERS: Mutex<Vec<(Instant, Option<String>)>> = Mutex::new(Vec::new());
+
+/// Change the status reported to clients
+pub fn update(change: impl FnOnce(&mut Status)) {
+    let mut status = STATUS.lock().unwrap_or_else(|e| e.into_inner());
+    change(status.get_or_insert_with(Default::default));
+}
+
+#[cfg(unix)]
+fn current_status() -> Status {
+    let status = STATUS.lock().unwrap_or_else(|e| e.into_inner());
+    Status {
+        pid: std::process::id(),
+        ..status.clone().unwrap_or_default()
+    }
+}
+
+/// Block until a start request comes in, or `running` is cleared
+pub fn wait_for_start(running: &AtomicBool) -> Result<()> {
+    update(|status| status.state = State::Waiting);
+    while !START.swap(false, Ordering::SeqCst) {
+        if !running.load(Ordering::SeqCst) {
+            anyhow::bail!("Recording cancelled before it started");
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    }
+    Ok(())
+}
+
+/// Mark this moment in the recording, returning how many markers there
+/// are now, or None if it isn't recording
+pub fn mark(name: Option<String>) -> Option<usize> {
+    let mut status = STATUS.lock().unwrap_or_else(|e| e.into_inner());
+    let status = status.get_or_insert_with(Default::default);
+    if !matches!(status.state, State::Recording | State::Paused) {
+        return None;
+    }
+    let mut markers = MARKERS.lock().unwrap_or_else(|e| e.into_inner());
+    markers.push((Instant::now(), name));
+    status.markers += 1;
+    Some(status.markers)
+}
+
+/// The markers requested since the last call, with when they were asked for
+pub fn take_markers() -> Vec<(Instant, Option<String>)> {
+    std::mem::take(&mut *MARKERS.lock().unwrap_or_else(|e| e.into_inner()))
+}
+
+#[cfg(unix)]
+fn handle(request: &Request, running: &AtomicBool) -> Reply {
+    let state = current_status().state;
+    let recording = matches!(state, State::Recording | State::Paused);
+    match request {
+        Request::Start => {
+            if state != State::Waiting {
+                return error_reply("not waiting to start (record with --wait-for-start)");
+            }
+            START.store(true, Ordering::SeqCst);
+            update(|status| status.state = State::Starting);
+        }
+        Request::Stop => {
+            running.store(false, Ordering::SeqCst);
+            update(|status| status.state = State::Saving);
+        }
+        Request::Pause | Request::Resume => {
+            if !recording {
+                return error_reply("not recording");
+            }
+            if !current_status().can_pause {
+                return error_reply("pausing isn't available when streaming or encrypting");
+            }
+            let paused = matches!(request, Request::Pause);
+            // Pausing toggles, so only ask when it changes anything
+            if paused != (state == State::Paused) {
+                pause::request();
+                update(|status| {
+                    status.state = if paused {
+                        State::Paused
+                    } else {
+                        State::Recording
+                    }
+                });
+            }
+        }
+        Request::Mark { name } => {
+            if mark(name.clone()).is_none() {
+                return error_reply("not recording");
+            }
+        }
+        Request::Status => {}
+    }
+    Reply {
+        ok: true,
+        error: None,
+        status: Some(current_status()),
+    }
+}
+
+#[cfg(unix)]
+fn error_reply(message: &str) -> Reply {
+    Reply {
+        ok: false,
+        error: Some(message.to_string()),
+        status: Some(current_status()),
+    }
+}
+
+/// Answers requests for a recording until dropped, then removes the socket
+pub struct ControlServer {
+    path: PathBuf,
+    closed: Arc<AtomicBool>,
+}
+
+impl ControlServer {
+    /// Listen at `path`, clearing `running` when asked to stop. None if
+    /// another recording already answers there.
+    #[cfg(unix)]
+    pub fn start(path: &Path, running: Arc<AtomicBool>) -> Result<Option<Self>> {
+        use std::io::{BufRead, BufReader, Write};
+        use std::os::unix::net::{UnixListener, UnixStream};
+
+        if path.exists() {
+            if UnixStream::connect(path).is_ok() {
+                return Ok(None);
+            }
+            // Left behind by a recorder that didn't finish
+            std::fs::remove_file(path)
+                .with_context(|| format!("Failed to remove {}", path.display()))?;
+        }
+        let listener = UnixListener::bind(path)
+            .with_context(|| format!("Failed to listen on {}", path.display()))?;
+        listener.set_nonblocking(true)?;
+        *STATUS.lock().unwrap_or_else(|e| e.into_inner()) = Some(Status::default());
+        START.store(false, Ordering::SeqCst);
+        take_markers();
+
+        let closed = Arc::new(AtomicBool::new(false));
+        let done = Arc::clone(&closed);
+        std::thread::spawn(move || {
+            while !done.load(Ordering::SeqCst) {
+                let Ok((stream, _)) = listener.accept() else {
+                    std::thread::sleep(POLL_INTERVAL);
+                    continue;
+                };
+                let _ = stream.set_nonblocking(false);
+                let mut line = String::new();
+                if BufReader::new(&stream).read_line(&mut line).is_err() {
+                    continue;
+                }
+                let reply = match serde_json::from_str::<Request>(&line) {
+                    Ok(request) => handle(&request, &running),
+                    Err(e) => error_reply(&format!("invalid request: {}", e)),
+                };
+                if let Ok(json) = serde_json::to_string(&reply) {
+                    let _ = writeln!(&stream, "{}", json);
+                }
+            }
+        });
+        Ok(Some(Self {
+            path: path.to_path_buf(),
+            closed,
+        }))
+    }
+
+    #[cfg(not(unix))]
+    pub fn start(_path: &Path, _running: Arc<AtomicBool>) -> Result<Option<Self>> {
+        anyhow::bail!("controlling recordings from another process needs unix sockets")
+    }
+}
+
+impl Drop for ControlServer {
+    fn drop(&mut self) {
+        self.closed.store(true, Ordering::SeqCst);
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// Send `request` to the recorder listening at `path`, or None if no
+/// recording is running
+#[cfg(unix)]
+pub fn send(path: &Path, request: &Request) -> Result<Option<Reply>> {
+    use std::io::{BufRead, BufReader, Write};
+    use std::os::unix::net::UnixStream;
+
+    let Ok(mut stream) = UnixStream::connect(path) else {
+        return Ok(None);
+    };
+    writeln!(stream, "{}", serde_json::to_string(request)?)?;
+    let mut line = String::new();
+    BufReader::new(&stream)
+        .read_line(&mut line)
+        .context("Failed to read the recorder's reply")?;
+    let reply = serde_json::from_str(&line).context("Invalid reply from the recorder")?;
+    Ok(Some(reply))
+}
+
+#[cfg(not(unix))]
+pub fn send(_path: &Path, _request: &Request) -> Result<Option<Reply>> {
+    anyhow::bail!("controlling recordings from another process needs unix sockets")
+}
+
+/// `glide record --detach`: run glide again with `args` (the same
+/// recording, without `--detach`) in the background, logging to a file,
+/// and return once it's listening
+pub fn detach_recording(args: Vec<OsString>) -> Result<()> {
+    if cfg!(not(unix)) {
+        anyhow::bail!("--detach isn't supported on Windows");
+    }
+    let socket = socket_path();
+    let log_path = socket.with_extension("log");
+    let log = std::fs::File::create(&log_path)
+        .with_context(|| format!("Failed to create {}", log_path.display()))?;
+    let mut command = Command::new(std::env::current_exe()?);
+    command
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(log.try_clone()?)
+        .stderr(log);
+    // Out of the terminal's process group, so Ctrl+C there doesn't reach it
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::process_group(&mut command, 0);
+    let mut child = command
+        .spawn()
+        .context("Failed to start the background recorder")?;
+
+    let started = Instant::now();
+    while started.elapsed() < DETACH_TIMEOUT {
+        if let Some(exit) = child.try_wait()? {
+            anyhow::bail!(
+                "The background recorder stopped ({}), see {}",
+                exit,
+                log_path.display()
+            );
+        }
+        let status = send(&socket, &Request::Status)
+            .ok()
+            .flatten()
+            .and_then(|reply| reply.status)
+            .filter(|status| status.pid == child.id() && status.state != State::Starting);
+        if let Some(status) = status {
+            match (status.state, status.output) {
+                (State::Waiting, _) => {
+                    tracing::info!("Waiting in the background, start with `glide start`")
+                }
+                (_, Some(output)) => {
+                    tracing::info!("Recording to {} in the background", output.display())
+                }
+                (_, None) => tracing::info!("Recording in the background"),
+            }
+            break;
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    }
+    tracing::info!(
+        "pid {}, log in {}; stop with `glide stop`",
+        child.id(),
+        log_path.display()
+    );
+    Ok(())
+}
+
+/// `glide start`: begin the recording waiting with `--wait-for-start`
+pub fn start_recording() -> Result<()> {
+    let reply = send(&socket_path(), &Request::Start)?.context("No recording is running")?;
+    if let Some(error) = reply.error {
+        anyhow::bail!("The recorder refused: {}", error);
+    }
+    tracing::info!("Recording started");
+    Ok(())
+}
+
+/// `glide mark`: mark this moment in the running recording
+pub fn mark_recording(name: Option<String>) -> Result<()> {
+    let request = Request::Mark { name };
+    let reply = send(&socket_path(), &request)?.context("No recording is running")?;
+    if let Some(error) = reply.error {
+        anyhow::bail!("The recorder refused: {}", error);
+    }
+    let count = reply.status.map_or(0, |status| status.markers);
+    tracing::info!("Marker {} dropped", count);
+    Ok(())
+}
+
+/// `glide stop`: stop the running recording and wait for it to be saved
+pub fn stop_recording() -> Result<()> {
+    let socket = socket_path();
+    let reply = send(&socket, &Request::Stop)?.context("No recording is running")?;
+    tracing::info!("Stopping...");
+    // The recorder answers until its files are saved
+    while send(&socket, &Request::Status).ok().flatten().is_some() {
+        std::thread::sleep(POLL_INTERVAL);
+    }
+    match reply.status.and_then(|status| status.output) {
+        Some(output) => tracing::info!("Saved to: {}", output.display()),
+        None => tracing::info!("Recording stopped"),
+    }
+    Ok(())
+}
+
+/// `glide status`: what the running recording is doing, if there is one
+pub fn recording_status() -> Result<Option<Status>> {
+    Ok(send(&socket_path(), &Request::Status)?.and_then(|reply| reply.status))
+}
+
+/// `status` as a line for the terminal
+pub fn format_status(status: &Status) -> String {
+    let state = match status.state {
+        State::Starting => "Starting",
+        State::Waiting => "Waiting to start",
+        State::Recording => "Recording",
+        State::Paused => "Paused",
+        State::Saving => "Saving",
+    };
+    let output = status
+        .output
+        .as_ref()
+        .map(|output| format!(" {}", output.display()))
+        .unwrap_or_default();
+    format!(
+        "{}{} (pid {}): {} recorded, {} frames",
+        state,
+        output,
+        status.pid,
+        format_duration(status.recorded),
+        status.frames
+    )
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::recording::pause::PauseControl;
+    use std::sync::MutexGuard;
+
+    /// The recorder's state is global, so tests that change it take turns
+    static STATE: Mutex<()> = Mutex::new(());
+
+    /// Hold the state for a test, starting from `status` with nothing pending
+    fn reset(status: Status) -> MutexGuard<'static, ()> {
+        let guard = STATE.lock().unwrap_or_else(|e| e.into_inner());
+        *STATUS.lock().unwrap_or_else(|e| e.into_inner()) = Some(status);
+        START.store(false, Ordering::SeqCst);
+        take_markers();
+        guard
+    }
+
+    fn recording(can_pause: bool) -> Status {
+        Status {
+            state: State::Recording,
+            can_pause,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_request_json() {
+        assert_eq!(
+            serde_json::to_string(&Request::Stop).unwrap(),
+            r#"{"command":"stop"}"#
+        );
+        assert_eq!(
+            serde_json::from_str::<Request>(r#"{"command":"status"}"#).unwrap(),
+            Request::Status
+        );
+    }
+
+    #[test]
+    fn test_mark_json() {
+        assert_eq!(
+            serde_json::from_str::<Request>(r#"{"command":"mark"}"#).unwrap(),
+            Request::Mark { name: None }
+        );
+        assert_eq!(
+            serde_json::from_str::<Request>(r#"{"command":"mark","name":"intro"}"#).unwrap(),
+            Request::Mark {
+                name: Some("intro".to_string())
+            }
+        );
+    }
+
+    #[test]
+    fn test_start_needs_waiting() {
+        let running = AtomicBool::new(true);
+        for state in [
+            State::Starting,
+            State::Recording,
+            State::Paused,
+            State::Saving,
+        ] {
+            let _state = reset(Status {
+                state,
+                ..Default::default()
+            });
+            let reply = handle(&Request::Start, &running);
+            assert!(!reply.ok);
+            assert_eq!(reply.status.unwrap().state, state);
+            assert!(!START.load(Ordering::SeqCst));
+        }
+
+        let _state = reset(Status {
+            state: State::Waiting,
+            ..Default::default()
+        });
+        let reply = handle(&Request::Start, &running);
+        assert!(reply.ok);
+        assert_eq!(reply.status.unwrap().state, State::Starting);
+        assert!(START.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_pause_twice() {
+        let _state = reset(recording(true));
+        let running = AtomicBool::new(true);
+        let mut pause = PauseControl::new(Instant::now(), true);
+        for _ in 0..2 {
+            let reply = handle(&Request::Pause, &running);
+            assert!(reply.ok);
+            assert_eq!(reply.status.unwrap().state, State::Paused);
+        }
+        // Only the first asked the recording loop to pause
+        assert_eq!(pause.poll(), Some(true));
+        assert_eq!(pause.poll(), None);
+
+        assert!(handle(&Request::Resume, &running).ok);
+        assert_eq!(pause.poll(), Some(false));
+    }
+
+    #[test]
+    fn test_pause_refused() {
+        let _state = reset(recording(false));
+        let running = AtomicBool::new(true);
+        let mut pause = PauseControl::new(Instant::now(), true);
+        let reply = handle(&Request::Pause, &running);
+        assert!(!reply.ok);
+        assert!(reply.error.unwrap().contains("streaming or encrypting"));
+        assert_eq!(reply.status.unwrap().state, State::Recording);
+        assert_eq!(pause.poll(), None);
+    }
+
+    #[test]
+    fn test_mark_queues_markers() {
+        let _state = reset(recording(true));
+        let running = AtomicBool::new(true);
+        let intro = Request::Mark {
+            name: Some("intro".to_string()),
+        };
+        assert!(handle(&intro, &running).ok);
+        let reply = handle(&Request::Mark { name: None }, &running);
+        assert_eq!(reply.status.unwrap().markers, 2);
+
+        let markers = take_markers();
+        let names: Vec<_> = markers.iter().map(|(_, name)| name.as_deref()).collect();
+        assert_eq!(names, [Some("intro"), None]);
+        assert!(markers[0].0 <= markers[1].0);
+        assert!(take_markers().is_empty());
+    }
+
+    #[test]
+    fn test_format_status() {
+        let status = Status {
+            pid: 4242,
+            state: State::Paused,
+            output: Some(PathBuf::from("demo.mp4")),
+            recorded: 83.4,
+            frames: 4980,
+            ..Default::default()
+        };
+        assert_eq!(
+            format_status(&status),
+            "Paused demo.mp4 (pid 4242): 1:23 recorded, 4980 frames"
+        );
+    }
+
+    #[test]
+    fn test_control_server() {
+        let _state = reset(Status::default());
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("glide.sock");
+        assert_eq!(send(&path, &Request::Status).unwrap(), None);
+
+        let running = Arc::new(AtomicBool::new(true));
+        let server = ControlServer::start(&path, Arc::clone(&running))
+            .unwrap()
+            .unwrap();
+        // A second recording leaves the first one's socket alone
+        assert!(ControlServer::start(&path, Arc::clone(&running))
+            .unwrap()
+            .is_none());
+
+        let reply = send(&path, &Request::Status).unwrap().unwrap();
+        assert!(reply.ok);
+        assert_eq!(reply.status.unwrap().pid, std::process::id());
+        assert!(running.load(Ordering::SeqCst));
+
+        // Nothing to start or mark until it's waiting or recording
+        assert!(!send(&path, &Request::Start).unwrap().unwrap().ok);
+        let mark = Request::Mark {
+            name: Some("intro".to_string()),
+        };
+        assert!(!send(&path, &mark).unwrap().unwrap().ok);
+
+        let waiting = {
+            let running = Arc::clone(&running);
+            std::thread::spawn(move || wait_for_start(&running))
+        };
+        while current_status().state != State::Waiting {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert!(send(&path, &Request::Start).unwrap().unwrap().ok);
+        waiting.join().unwrap().unwrap();
+
+        update(|status| {
+            status.state = State::Recording;
+            status.can_pause = true;
+        });
+        let reply = send(&path, &mark).unwrap().unwrap();
+        assert_eq!(reply.status.unwrap().markers, 1);
+        let markers = take_markers();
+        assert_eq!(markers[0].1.as_deref(), Some("intro"));
+        assert!(take_markers().is_empty());
+
+        for _ in 0..2 {
+            let reply = send(&path, &Request::Pause).unwrap().unwrap();
+            assert_eq!(reply.status.unwrap().state, State::Paused);
+        }
+        let reply = send(&path, &Request::Resume).unwrap().unwrap();
+        assert_eq!(reply.status.unwrap().state, State::Recording);
+
+        let reply = send(&path, &Request::Stop).unwrap().unwrap();
+        assert_eq!(reply.status.unwrap().state, State::Saving);
+        assert!(!running.load(Ordering::SeqCst));
+
+        drop(server);
+        assert!(!path.exists());
+        assert_eq!(send(&path, &Request::Status).unwrap(), None);
+    }
+}
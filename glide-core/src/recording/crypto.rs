@@ -0,0 +1,174 @@
+//! Encryption at rest for recordings
+//!
+//! Videos and metadata are written as age files, encrypted either to a
+//! passphrase or to an age X25519 recipient. `process` detects them by their
+//! header and decrypts transparently. Passphrases come from the caller;
+//! nothing here prompts for one.
+
+use age::{x25519, Decryptor, Encryptor, Identity, IdentityFile};
+use anyhow::{Context, Result};
+use std::fs::File;
+use std::io::{self, BufReader, Read, Write};
+use std::path::Path;
+use std::sync::Arc;
+
+pub use age::secrecy::SecretString;
+
+/// Every binary age file starts with this line
+const AGE_MAGIC: &[u8] = b"age-encryption.org/v1";
+
+/// Called for the passphrase when a recording turns out to be encrypted
+pub type PassphraseCallback = Arc<dyn Fn() -> Result<SecretString> + Send + Sync>;
+
+/// What recordings are encrypted to
+#[derive(Clone)]
+pub enum EncryptKey {
+    Passphrase(SecretString),
+    Recipient(x25519::Recipient),
+}
+
+impl EncryptKey {
+    /// Key for `--encrypt --recipient`: an age X25519 public key
+    pub fn recipient(recipient: &str) -> Result<Self> {
+        recipient
+            .parse::<x25519::Recipient>()
+            .map(Self::Recipient)
+            .map_err(|e| anyhow::anyhow!("Invalid age recipient {}: {}", recipient, e))
+    }
+
+    fn encryptor(&self) -> Result<Encryptor> {
+        match self {
+            Self::Passphrase(passphrase) => Ok(Encryptor::with_user_passphrase(passphrase.clone())),
+            Self::Recipient(recipient) => {
+                Encryptor::with_recipients(std::iter::once(recipient as &dyn age::Recipient))
+                    .context("Failed to set up encryption")
+            }
+        }
+    }
+
+    /// Wrap `output` so everything written to it is encrypted.
+    /// The writer's `finish` must be called to complete the file.
+    pub fn wrap<W: Write>(&self, output: W) -> Result<age::stream::StreamWriter<W>> {
+        self.encryptor()?
+            .wrap_output(output)
+            .context("Failed to write encryption header")
+    }
+
+    /// Encrypt `data` into a new file at `path`
+    pub fn write_file(&self, path: &Path, data: &[u8]) -> Result<()> {
+        let file =
+            File::create(path).with_context(|| format!("Failed to create {}", path.display()))?;
+        let mut writer = self.wrap(file)?;
+        writer.write_all(data)?;
+        writer.finish()?;
+        Ok(())
+    }
+}
+
+/// How to decrypt recordings: identities from an age key file, or a passphrase
+pub struct DecryptKey {
+    identities: Vec<Box<dyn Identity>>,
+}
+
+impl DecryptKey {
+    /// Identities from an age key file
+    pub fn identity_file(path: &Path) -> Result<Self> {
+        let identities = IdentityFile::from_file(path.to_string_lossy().into_owned())
+            .with_context(|| format!("Failed to read identity file {}", path.display()))?
+            .into_identities()
+            .context("Unsupported identity file")?;
+        Ok(Self { identities })
+    }
+
+    /// The passphrase recordings were encrypted with
+    pub fn passphrase(passphrase: SecretString) -> Self {
+        Self {
+            identities: vec![Box::new(age::scrypt::Identity::new(passphrase))],
+        }
+    }
+
+    /// Reader over the plaintext of an encrypted file
+    pub fn open(&self, path: &Path) -> Result<impl Read> {
+        let file =
+            File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+        let decryptor = Decryptor::new_buffered(BufReader::new(file))
+            .with_context(|| format!("{} is not an encrypted recording", path.display()))?;
+        decryptor
+            .decrypt(self.identities.iter().map(|i| i.as_ref() as &dyn Identity))
+            .with_context(|| format!("Failed to decrypt {} (wrong key?)", path.display()))
+    }
+
+    /// Decrypt `input` into a new file at `output`
+    pub fn decrypt_file(&self, input: &Path, output: &Path) -> Result<()> {
+        let mut reader = self.open(input)?;
+        let mut file = File::create(output)
+            .with_context(|| format!("Failed to create {}", output.display()))?;
+        io::copy(&mut reader, &mut file)
+            .with_context(|| format!("Failed to decrypt {}", input.display()))?;
+        Ok(())
+    }
+
+    pub fn read_to_string(&self, path: &Path) -> Result<String> {
+        let mut text = String::new();
+        self.open(path)?
+            .read_to_string(&mut text)
+            .with_context(|| format!("Failed to decrypt {}", path.display()))?;
+        Ok(text)
+    }
+}
+
+/// Whether `path` is an age-encrypted file
+pub fn is_encrypted(path: &Path) -> bool {
+    let mut header = [0u8; AGE_MAGIC.len()];
+    File::open(path)
+        .and_then(|mut f| f.read_exact(&mut header))
+        .is_ok_and(|_| header == AGE_MAGIC)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encrypt_roundtrip_with_recipient() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("recording.json");
+        let identity = x25519::Identity::generate();
+
+        let key = EncryptKey::Recipient(identity.to_public());
+        key.write_file(&path, b"{\"width\": 10}").unwrap();
+        assert!(is_encrypted(&path));
+
+        let decrypt = DecryptKey {
+            identities: vec![Box::new(identity)],
+        };
+        assert_eq!(decrypt.read_to_string(&path).unwrap(), "{\"width\": 10}");
+
+        let other = DecryptKey {
+            identities: vec![Box::new(x25519::Identity::generate())],
+        };
+        assert!(other.read_to_string(&path).is_err());
+    }
+
+    #[test]
+    fn test_encrypt_roundtrip_with_passphrase() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("recording.json");
+
+        let key = EncryptKey::Passphrase(SecretString::from("hunter2".to_string()));
+        key.write_file(&path, b"{}").unwrap();
+        let decrypt = DecryptKey::passphrase(SecretString::from("hunter2".to_string()));
+        assert_eq!(decrypt.read_to_string(&path).unwrap(), "{}");
+        let wrong = DecryptKey::passphrase(SecretString::from("hunter3".to_string()));
+        assert!(wrong.read_to_string(&path).is_err());
+    }
+
+    #[test]
+    fn test_plain_file_is_not_encrypted() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("recording.json");
+        std::fs::write(&path, b"{}").unwrap();
+        assert!(!is_encrypted(&path));
+        assert!(!is_encrypted(&dir.path().join("missing.json")));
+    }
+}
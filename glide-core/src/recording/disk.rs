@@ -0,0 +1,130 @@
+//! Free disk space for recordings
+//!
+//! Checked before recording starts against an estimate of the bitrate, and
+//! watched while it runs: an MP4 only gets its index when FFmpeg finishes,
+//! so a disk that fills up mid-recording would leave an unplayable file.
+//! Stopping while there's still room lets the file and metadata be saved.
+
+use crate::library::format_bytes;
+use crate::recording::encoder::Quality;
+use anyhow::{Context, Result};
+use std::path::Path;
+use std::time::Duration;
+
+/// Frame rate the estimate assumes, the capture's target
+const FPS: f64 = 60.0;
+
+/// Encoded bits per pixel per frame of typical screen content
+const STANDARD_BITS_PER_PIXEL: f64 = 0.08;
+const LOSSLESS_BITS_PER_PIXEL: f64 = 1.0;
+
+/// Room kept for finishing the file, its index and the metadata
+const RESERVE: u64 = 256 * 1024 * 1024;
+
+/// Recording doesn't start with room for less than this
+const MIN_RECORDING_TIME: Duration = Duration::from_secs(60);
+
+/// How often free space is checked while recording
+pub const CHECK_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Estimated bytes written per second for a `width`x`height` recording
+pub fn bytes_per_second(width: u32, height: u32, quality: Quality) -> f64 {
+    let bits_per_pixel = match quality {
+        Quality::Standard => STANDARD_BITS_PER_PIXEL,
+        Quality::Lossless => LOSSLESS_BITS_PER_PIXEL,
+    };
+    width as f64 * height as f64 * FPS * bits_per_pixel / 8.0
+}
+
+/// Seconds of recording at `rate` bytes per second that fit in `free`
+/// bytes, or an error if that's too little to start (or to last the
+/// whole `duration`)
+pub fn check_room(free: u64, rate: f64, duration: Option<Duration>) -> Result<f64> {
+    let seconds = free.saturating_sub(RESERVE) as f64 / rate.max(1.0);
+    let needed = duration.unwrap_or(MIN_RECORDING_TIME).as_secs_f64();
+    if seconds < needed {
+        anyhow::bail!(
+            "Not enough disk space: {} free is about {:.0}s of recording at ~{}/s, \
+             {:.0}s needed. Free some space or record somewhere else with -o.",
+            format_bytes(free),
+            seconds,
+            format_bytes(rate as u64),
+            needed
+        );
+    }
+    Ok(seconds)
+}
+
+/// Whether the disk is so full the recording should stop now
+pub fn is_low(free: u64) -> bool {
+    free < RESERVE
+}
+
+/// Bytes free to the user on the volume `path` is (or will be) written to
+pub fn free_space(path: &Path) -> Result<u64> {
+    // The file may not exist yet, so ask about its directory
+    let dir = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    volume_free_space(dir)
+        .with_context(|| format!("Failed to check free space in {}", dir.display()))
+}
+
+#[cfg(unix)]
+fn volume_free_space(dir: &Path) -> Result<u64> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    let c_path = CString::new(dir.as_os_str().as_bytes())?;
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statvfs(c_path.as_ptr(), &mut stat) } != 0 {
+        return Err(std::io::Error::last_os_error().into());
+    }
+    #[allow(clippy::unnecessary_cast)] // the field types differ between platforms
+    Ok(stat.f_bavail as u64 * stat.f_frsize as u64)
+}
+
+#[cfg(windows)]
+fn volume_free_space(dir: &Path) -> Result<u64> {
+    use ::windows::core::HSTRING;
+    use ::windows::Win32::Storage::FileSystem::GetDiskFreeSpaceExW;
+
+    let mut free = 0u64;
+    unsafe { GetDiskFreeSpaceExW(&HSTRING::from(dir), Some(&mut free), None, None)? };
+    Ok(free)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bytes_per_second() {
+        // About 10 Mbit/s for 1080p
+        let rate = bytes_per_second(1920, 1080, Quality::Standard);
+        assert_eq!(rate, 1_244_160.0);
+        assert!(bytes_per_second(1920, 1080, Quality::Lossless) > rate * 10.0);
+    }
+
+    #[test]
+    fn test_check_room() {
+        let gib = 1024 * 1024 * 1024;
+        let rate = 1_000_000.0;
+        let seconds = check_room(gib + RESERVE, rate, None).unwrap();
+        assert_eq!(seconds.round(), 1074.0);
+        // Under a minute left
+        assert!(check_room(RESERVE + 30_000_000, rate, None).is_err());
+        // Enough for a minute, not for the hour asked for
+        assert!(check_room(gib, rate, Some(Duration::from_secs(3600))).is_err());
+        assert!(check_room(0, rate, None).is_err());
+    }
+
+    #[test]
+    fn test_free_space() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(free_space(&dir.path().join("recording.mp4")).unwrap() > 0);
+        assert!(!is_low(RESERVE));
+        assert!(is_low(RESERVE - 1));
+    }
+}
@@ -0,0 +1,938 @@
+//! FFmpeg-based video encoding for raw video frames
+//!
+//! This module provides video encoding by piping raw BGRA frames to FFmpeg's
+//! stdin, each stamped with the time it was captured (see mkv.rs).
+
+use crate::recording::crypto::EncryptKey;
+use crate::recording::integrity;
+use crate::recording::mkv::MkvWriter;
+use anyhow::{Context, Result};
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{Receiver, RecvTimeoutError};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+#[cfg(unix)]
+use std::os::unix::process::CommandExt;
+#[cfg(windows)]
+use std::os::windows::process::CommandExt;
+
+/// Windows' equivalent of a new process group: console Ctrl+C isn't delivered to it
+#[cfg(windows)]
+const CREATE_NEW_PROCESS_GROUP: u32 = 0x0000_0200;
+
+/// Video codec for recorded and processed files
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum Codec {
+    /// H.264: plays everywhere
+    #[default]
+    H264,
+    /// H.265/HEVC: smaller files at the same quality, but not every browser plays it
+    H265,
+    /// VP9: royalty-free, plays in every browser; the default for WebM
+    Vp9,
+    /// AV1: smallest files, but slow to encode
+    Av1,
+}
+
+impl Codec {
+    /// Prefix of FFmpeg's hardware encoder names, as in `hevc_vaapi`
+    pub fn hardware_prefix(self) -> &'static str {
+        match self {
+            Codec::H264 => "h264",
+            Codec::H265 => "hevc",
+            Codec::Vp9 => "vp9",
+            Codec::Av1 => "av1",
+        }
+    }
+
+    /// FFmpeg's software encoder
+    pub fn software_encoder(self) -> &'static str {
+        match self {
+            Codec::H264 => "libx264",
+            Codec::H265 => "libx265",
+            Codec::Vp9 => "libvpx-vp9",
+            // Much faster than libaom at similar quality
+            Codec::Av1 => "libsvtav1",
+        }
+    }
+
+    /// Whether WebM can hold this codec (otherwise it needs MP4)
+    pub fn fits_webm(self) -> bool {
+        matches!(self, Codec::Vp9 | Codec::Av1)
+    }
+
+    /// Output options for encoding a recording as it's captured
+    fn recording_args(self) -> &'static [&'static str] {
+        match self {
+            Codec::H264 => &[
+                "-c:v",
+                "libx264",
+                // Preset: ultrafast for real-time encoding
+                "-preset",
+                "ultrafast",
+                // Quality: good quality
+                "-crf",
+                "18",
+            ],
+            Codec::H265 => &[
+                "-c:v",
+                "libx265",
+                "-preset",
+                "ultrafast",
+                "-crf",
+                "20",
+                // stderr isn't read while recording, so keep x265 quiet
+                "-x265-params",
+                "log-level=error",
+            ],
+            // Fastest settings; both are far slower than H.264 to encode
+            Codec::Vp9 => &[
+                "-c:v",
+                "libvpx-vp9",
+                "-deadline",
+                "realtime",
+                "-cpu-used",
+                "8",
+                "-row-mt",
+                "1",
+                "-crf",
+                "24",
+                "-b:v",
+                "0",
+            ],
+            Codec::Av1 => &["-c:v", "libsvtav1", "-preset", "12", "-crf", "28"],
+        }
+    }
+}
+
+/// How much a recording is compressed as it's captured
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum Quality {
+    /// Near-lossless at a manageable size
+    #[default]
+    Standard,
+    /// The exact captured pixels (RGB H.264), so processing starts from
+    /// pristine source material. Files are several times larger.
+    Lossless,
+}
+
+/// What encodes a recording as it's captured
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum Encoder {
+    /// The first hardware encoder that works, else software
+    #[default]
+    Auto,
+    /// Apple VideoToolbox (macOS)
+    Videotoolbox,
+    /// NVIDIA NVENC
+    Nvenc,
+    /// VA-API on AMD and Intel GPUs (Linux)
+    Vaapi,
+    /// Software: libx264, or the CPU encoder of another codec
+    X264,
+}
+
+/// Hardware encoders `auto` tries, best first
+#[cfg(target_os = "macos")]
+const AUTO_ENCODERS: &[Encoder] = &[Encoder::Videotoolbox];
+#[cfg(target_os = "linux")]
+const AUTO_ENCODERS: &[Encoder] = &[Encoder::Nvenc, Encoder::Vaapi];
+#[cfg(not(any(target_os = "macos", target_os = "linux")))]
+const AUTO_ENCODERS: &[Encoder] = &[Encoder::Nvenc];
+
+/// Render node VA-API encodes on
+const VAAPI_DEVICE: &str = "/dev/dri/renderD128";
+
+impl Encoder {
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            Encoder::Auto | Encoder::X264 => "software",
+            Encoder::Videotoolbox => "VideoToolbox",
+            Encoder::Nvenc => "NVENC",
+            Encoder::Vaapi => "VA-API",
+        }
+    }
+
+    /// Whether this encoder has an FFmpeg encoder for `codec`
+    fn supports(self, codec: Codec) -> bool {
+        match self {
+            Encoder::Auto | Encoder::X264 | Encoder::Vaapi => true,
+            Encoder::Videotoolbox => matches!(codec, Codec::H264 | Codec::H265),
+            Encoder::Nvenc => codec != Codec::Vp9,
+        }
+    }
+
+    /// Input options needed before the frames
+    fn input_args(self) -> &'static [&'static str] {
+        match self {
+            Encoder::Vaapi => &["-vaapi_device", VAAPI_DEVICE],
+            _ => &[],
+        }
+    }
+}
+
+/// Resolve `--encoder` before recording: `auto` becomes the first hardware
+/// encoder that manages to encode a test frame (or software), and an
+/// explicit choice is checked the same way so it fails up front
+pub fn select_encoder(choice: Encoder, codec: Codec, quality: Quality) -> Result<Encoder> {
+    // Lossless recording is software only
+    if quality == Quality::Lossless {
+        return Ok(Encoder::X264);
+    }
+    let selected = match choice {
+        Encoder::X264 => Encoder::X264,
+        Encoder::Auto => AUTO_ENCODERS
+            .iter()
+            .copied()
+            .find(|&encoder| {
+                let works = encoder.supports(codec) && encodes_test_frame(encoder, codec);
+                if !works {
+                    tracing::debug!("{} can't encode {:?} here", encoder.name(), codec);
+                }
+                works
+            })
+            .unwrap_or_else(|| {
+                tracing::debug!("No hardware encoder for {:?}, using software", codec);
+                Encoder::X264
+            }),
+        hardware => {
+            if !hardware.supports(codec) {
+                anyhow::bail!("{} can't encode {:?}", hardware.name(), codec);
+            }
+            check_ffmpeg()?;
+            if !encodes_test_frame(hardware, codec) {
+                anyhow::bail!(
+                    "{} isn't available on this machine (try --encoder auto)",
+                    hardware.name()
+                );
+            }
+            hardware
+        }
+    };
+    if selected != Encoder::X264 {
+        tracing::info!("Encoding with {}", selected.name());
+    }
+    Ok(selected)
+}
+
+/// Whether FFmpeg can encode a frame with `encoder`; hardware encoders
+/// are listed by FFmpeg builds even where the hardware is missing
+fn encodes_test_frame(encoder: Encoder, codec: Codec) -> bool {
+    Command::new("ffmpeg")
+        .args(["-hide_banner", "-loglevel", "error"])
+        .args(encoder.input_args())
+        .args([
+            "-f",
+            "lavfi",
+            "-i",
+            "color=size=256x256:rate=30",
+            "-frames:v",
+            "1",
+        ])
+        .args(video_args(codec, Quality::Standard, encoder))
+        .args(["-f", "null", "-"])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok_and(|status| status.success())
+}
+
+/// Output options for the video stream of a recorded file. `encoder` has
+/// been through `select_encoder`, so `Auto` here means software.
+fn video_args(codec: Codec, quality: Quality, encoder: Encoder) -> Vec<String> {
+    if quality == Quality::Lossless {
+        // libx264rgb skips the conversion to YUV, which alone loses color
+        // detail; -qp 0 makes the encoding itself lossless
+        return [
+            "-c:v",
+            "libx264rgb",
+            "-preset",
+            "ultrafast",
+            "-qp",
+            "0",
+            "-pix_fmt",
+            "bgr0",
+        ]
+        .map(String::from)
+        .to_vec();
+    }
+
+    let hardware = |suffix: &str| format!("{}_{}", codec.hardware_prefix(), suffix);
+    let mut args: Vec<String> = match encoder {
+        Encoder::Auto | Encoder::X264 => codec
+            .recording_args()
+            .iter()
+            .chain(&["-pix_fmt", "yuv420p"])
+            .map(|arg| arg.to_string())
+            .collect(),
+        Encoder::Videotoolbox => vec![
+            "-c:v".into(),
+            hardware("videotoolbox"),
+            "-realtime".into(),
+            "1".into(),
+            "-q:v".into(),
+            "80".into(),
+            "-pix_fmt".into(),
+            "yuv420p".into(),
+        ],
+        // Lowest-latency preset, constant quality
+        Encoder::Nvenc => vec![
+            "-c:v".into(),
+            hardware("nvenc"),
+            "-preset".into(),
+            "p1".into(),
+            "-tune".into(),
+            "ll".into(),
+            "-rc".into(),
+            "vbr".into(),
+            "-cq".into(),
+            "19".into(),
+            "-b:v".into(),
+            "0".into(),
+            "-pix_fmt".into(),
+            "yuv420p".into(),
+        ],
+        // Frames are converted and uploaded to the GPU first
+        Encoder::Vaapi => vec![
+            "-vf".into(),
+            "format=nv12,hwupload".into(),
+            "-c:v".into(),
+            hardware("vaapi"),
+            "-qp".into(),
+            "20".into(),
+        ],
+    };
+    if codec == Codec::H265 {
+        // Apple players only accept HEVC in MP4 tagged hvc1
+        args.extend(["-tag:v".into(), "hvc1".into()]);
+    }
+    args
+}
+
+/// Where the encoded video is written
+pub enum EncoderOutput {
+    /// Write an MP4 file, tagged with the recording ID
+    File {
+        path: PathBuf,
+        recording_id: String,
+        /// Encrypt the file as it's written
+        encrypt: Option<EncryptKey>,
+        codec: Codec,
+        quality: Quality,
+        /// As resolved by `select_encoder`
+        encoder: Encoder,
+    },
+    /// Push a live FLV stream to an RTMP endpoint
+    Rtmp {
+        url: String,
+        /// Letterbox the capture onto a 1920x1080 canvas of this color (e.g. "#1a1a2e")
+        background: Option<String>,
+    },
+}
+
+/// Interleaved 32-bit float PCM delivered by a capture session
+pub struct PcmStream {
+    pub receiver: Receiver<Vec<u8>>,
+    pub sample_rate: u32,
+    pub channels: u32,
+}
+
+/// Audio recorded alongside the frames
+#[derive(Default)]
+pub struct AudioInputs {
+    /// Microphone device, read by FFmpeg itself
+    pub mic: Option<String>,
+    /// System audio from the capture session
+    pub system: Option<PcmStream>,
+}
+
+/// Feeds a `PcmStream` to FFmpeg through a named pipe, since stdin carries the frames
+struct PcmWriter {
+    // Holds the FIFO until FFmpeg is done with it
+    _dir: tempfile::TempDir,
+    path: PathBuf,
+    sample_rate: u32,
+    channels: u32,
+    stop: Arc<AtomicBool>,
+    /// Hands the stream back when done, for the next segment after a pause
+    thread: JoinHandle<PcmStream>,
+}
+
+impl PcmWriter {
+    #[cfg(unix)]
+    fn start(stream: PcmStream) -> Result<Self> {
+        use std::ffi::CString;
+        use std::os::unix::ffi::OsStrExt;
+
+        let dir = tempfile::TempDir::new().context("Failed to create audio pipe directory")?;
+        let path = dir.path().join("audio.pcm");
+        let c_path = CString::new(path.as_os_str().as_bytes())?;
+        // SAFETY: `c_path` is a valid NUL-terminated path
+        if unsafe { libc::mkfifo(c_path.as_ptr(), 0o600) } != 0 {
+            return Err(io::Error::last_os_error()).context("Failed to create audio pipe");
+        }
+
+        let (sample_rate, channels) = (stream.sample_rate, stream.channels);
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread = {
+            let path = path.clone();
+            let stop = Arc::clone(&stop);
+            thread::spawn(move || {
+                // Audio from before this encoder started (e.g. while paused) is stale
+                while stream.receiver.try_recv().is_ok() {}
+
+                // Blocks until FFmpeg opens the pipe for reading
+                if let Ok(mut pipe) = File::options().write(true).open(&path) {
+                    while !stop.load(Ordering::Relaxed) {
+                        match stream.receiver.recv_timeout(Duration::from_millis(100)) {
+                            Ok(pcm) => {
+                                if pipe.write_all(&pcm).is_err() {
+                                    // FFmpeg has exited
+                                    break;
+                                }
+                            }
+                            Err(RecvTimeoutError::Timeout) => {}
+                            Err(RecvTimeoutError::Disconnected) => break,
+                        }
+                    }
+                }
+                stream
+            })
+        };
+
+        Ok(Self {
+            _dir: dir,
+            path,
+            sample_rate,
+            channels,
+            stop,
+            thread,
+        })
+    }
+
+    #[cfg(not(unix))]
+    fn start(_stream: PcmStream) -> Result<Self> {
+        anyhow::bail!("System audio capture is not supported on this platform")
+    }
+
+    /// Stop writing and wait for the thread, getting the stream back.
+    /// FFmpeg must have exited.
+    fn finish(self) -> Option<PcmStream> {
+        self.stop.store(true, Ordering::Relaxed);
+
+        // If FFmpeg never opened the pipe, the thread is still waiting to;
+        // opening the read end ourselves lets it through
+        #[cfg(unix)]
+        let _reader = (!self.thread.is_finished()).then(|| {
+            use std::os::unix::fs::OpenOptionsExt;
+            File::options()
+                .read(true)
+                .custom_flags(libc::O_NONBLOCK)
+                .open(&self.path)
+        });
+        self.thread.join().ok()
+    }
+}
+
+/// FFmpeg video encoder that accepts raw BGRA frames via stdin
+pub struct VideoEncoder {
+    child: Child,
+    stdin: MkvWriter<std::process::ChildStdin>,
+    /// The first frame's capture timestamp and wallclock time (ms), which
+    /// later frames are timed from
+    clock: Option<(f64, u64)>,
+    frame_count: u64,
+    /// Encrypts FFmpeg's output into the destination file
+    writer_thread: Option<JoinHandle<Result<()>>>,
+    /// Feeds system audio to FFmpeg
+    pcm_writer: Option<PcmWriter>,
+    mic: Option<String>,
+}
+
+impl VideoEncoder {
+    /// Spawn a new FFmpeg encoder process
+    ///
+    /// # Arguments
+    /// * `width` - Frame width in pixels
+    /// * `height` - Frame height in pixels
+    /// * `fps` - Nominal frames per second (typically 60), for stream keyframes
+    /// * `output` - Output file or live stream endpoint
+    /// * `audio` - Microphone and/or system audio to record an audio track from
+    pub fn new(
+        width: u32,
+        height: u32,
+        fps: u32,
+        output: &EncoderOutput,
+        audio: AudioInputs,
+    ) -> Result<Self> {
+        let mut cmd = Command::new("ffmpeg");
+        if let EncoderOutput::File { encoder, .. } = output {
+            cmd.args(encoder.input_args());
+        }
+        cmd.args([
+            // BGRA frames, each with its capture time (see mkv.rs)
+            "-f", "matroska", // Read from stdin
+            "-i", "pipe:0",
+        ]);
+
+        let pcm_writer = audio.system.map(PcmWriter::start).transpose()?;
+        let system = pcm_writer
+            .as_ref()
+            .map(|writer| (writer.path.as_path(), writer.sample_rate, writer.channels));
+        cmd.args(audio_args(audio.mic.as_deref(), system));
+        match output {
+            EncoderOutput::File {
+                path,
+                recording_id,
+                encrypt,
+                codec,
+                quality,
+                encoder,
+            } => {
+                cmd.args([
+                    // Pairs the video with its metadata (see integrity.rs)
+                    "-metadata",
+                    &format!("comment={}", integrity::id_tag(recording_id)),
+                ]);
+                cmd.args(video_args(*codec, *quality, *encoder));
+                // Overwrite output
+                cmd.arg("-y");
+                if encrypt.is_some() {
+                    // Plaintext never touches disk: FFmpeg streams a fragmented
+                    // MP4 (no seeking back to write the index) to us instead
+                    cmd.args([
+                        "-movflags",
+                        "frag_keyframe+empty_moov",
+                        "-f",
+                        "mp4",
+                        "pipe:1",
+                    ]);
+                } else {
+                    cmd.arg(path);
+                }
+            }
+            EncoderOutput::Rtmp { url, background } => {
+                if let Some(color) = background {
+                    let color = parse_stream_color(color)?;
+                    cmd.args([
+                        "-vf",
+                        &format!(
+                            "scale=1920:1080:force_original_aspect_ratio=decrease,\
+                             pad=1920:1080:(ow-iw)/2:(oh-ih)/2:color=0x{}",
+                            color
+                        ),
+                    ]);
+                }
+                let gop = (fps * 2).to_string();
+                cmd.args([
+                    // Low-latency H.264 suitable for live ingest servers
+                    "-c:v",
+                    "libx264",
+                    "-preset",
+                    "veryfast",
+                    "-tune",
+                    "zerolatency",
+                    // Ingest servers expect a bounded bitrate and regular keyframes
+                    "-b:v",
+                    "6M",
+                    "-maxrate",
+                    "6M",
+                    "-bufsize",
+                    "12M",
+                    "-g",
+                    &gop,
+                    "-pix_fmt",
+                    "yuv420p",
+                    // RTMP carries FLV
+                    "-f",
+                    "flv",
+                    url,
+                ]);
+            }
+        }
+
+        let encrypt_to = match output {
+            EncoderOutput::File {
+                path,
+                encrypt: Some(key),
+                ..
+            } => Some((path.clone(), key.clone())),
+            _ => None,
+        };
+
+        cmd.stdin(Stdio::piped())
+            .stdout(if encrypt_to.is_some() {
+                Stdio::piped()
+            } else {
+                Stdio::null()
+            })
+            .stderr(Stdio::piped());
+
+        // Put FFmpeg in its own process group so it doesn't receive SIGINT
+        // when user presses Ctrl+C. We control FFmpeg by closing stdin.
+        #[cfg(unix)]
+        cmd.process_group(0);
+        #[cfg(windows)]
+        cmd.creation_flags(CREATE_NEW_PROCESS_GROUP);
+
+        let mut child = cmd.spawn().context("Failed to start FFmpeg encoder")?;
+
+        let stdin = child.stdin.take().context("Failed to get FFmpeg stdin")?;
+        let stdin = MkvWriter::new(stdin, width, height).context("Failed to start FFmpeg input")?;
+
+        let writer_thread = match encrypt_to {
+            Some((path, key)) => {
+                let mut stdout = child.stdout.take().context("Failed to get FFmpeg stdout")?;
+                let file = File::create(&path)
+                    .with_context(|| format!("Failed to create {}", path.display()))?;
+                let mut writer = key.wrap(BufWriter::new(file))?;
+                Some(thread::spawn(move || {
+                    io::copy(&mut stdout, &mut writer)
+                        .context("Failed to write encrypted video")?;
+                    writer.finish()?.flush()?;
+                    Ok(())
+                }))
+            }
+            None => None,
+        };
+
+        Ok(Self {
+            child,
+            stdin,
+            clock: None,
+            frame_count: 0,
+            writer_thread,
+            pcm_writer,
+            mic: audio.mic,
+        })
+    }
+
+    /// Write a raw BGRA frame captured at `timestamp` (seconds, on the
+    /// capture session's clock) to the encoder
+    ///
+    /// The frame data must be exactly `width * height * 4` bytes. Frames are
+    /// timed by the gaps between their timestamps, from the wallclock time
+    /// of the first, so dropped frames leave gaps rather than shortening the
+    /// video, and audio stamped with the wallclock still lines up.
+    pub fn write_frame(&mut self, frame_data: &[u8], timestamp: f64) -> Result<()> {
+        let (first, start) = *self.clock.get_or_insert_with(|| {
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default();
+            (timestamp, now.as_millis() as u64)
+        });
+        let offset = ((timestamp - first) * 1000.0).round().max(0.0) as u64;
+
+        self.stdin
+            .write_frame(frame_data, start + offset)
+            .context("Failed to write frame to FFmpeg")?;
+
+        self.frame_count += 1;
+        Ok(())
+    }
+
+    /// Get the number of frames written
+    pub fn frame_count(&self) -> u64 {
+        self.frame_count
+    }
+
+    /// Finish encoding and wait for FFmpeg to complete. Returns the audio
+    /// inputs, for encoding another segment of the recording.
+    pub fn finish(mut self) -> Result<AudioInputs> {
+        // Close stdin to signal end of input
+        drop(self.stdin);
+
+        // Wait for FFmpeg to finish
+        let status = self
+            .child
+            .wait()
+            .context("Failed to wait for FFmpeg to finish")?;
+
+        let audio = AudioInputs {
+            mic: self.mic.take(),
+            system: self.pcm_writer.take().and_then(PcmWriter::finish),
+        };
+
+        // FFmpeg has closed its stdout, so the encrypting writer can finish the file
+        if let Some(handle) = self.writer_thread.take() {
+            handle
+                .join()
+                .map_err(|_| anyhow::anyhow!("Encryption thread panicked"))??;
+        }
+
+        // Check if FFmpeg exited successfully or was killed by SIGINT (Ctrl+C)
+        // When user presses Ctrl+C, FFmpeg receives signal 2 which is expected
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::ExitStatusExt;
+            if let Some(signal) = status.signal() {
+                if signal == 2 {
+                    // SIGINT is expected when user presses Ctrl+C
+                    return Ok(audio);
+                }
+            }
+        }
+
+        if !status.success() {
+            // Try to read stderr for error info
+            if let Some(ref mut stderr) = self.child.stderr {
+                use std::io::Read;
+                let mut error_output = String::new();
+                let _ = stderr.read_to_string(&mut error_output);
+                if !error_output.is_empty() {
+                    // Get last few lines
+                    let last_lines: Vec<&str> = error_output.lines().rev().take(5).collect();
+                    let error_context = last_lines.into_iter().rev().collect::<Vec<_>>().join("\n");
+                    anyhow::bail!("FFmpeg encoding failed:\n{}", error_context);
+                }
+            }
+            anyhow::bail!("FFmpeg encoding failed with status: {}", status);
+        }
+
+        Ok(audio)
+    }
+}
+
+/// Join recorded segments into `output` without re-encoding, tagging it
+/// with the recording ID
+pub fn concat_segments(segments: &[PathBuf], output: &Path, recording_id: &str) -> Result<()> {
+    let list_path = output.with_extension("segments.txt");
+    let list: String = segments
+        .iter()
+        .map(|path| {
+            // The concat demuxer's quoting: escape ' as '\''
+            let path = path.to_string_lossy().replace('\'', "'\\''");
+            format!("file '{}'\n", path)
+        })
+        .collect();
+    std::fs::write(&list_path, list).context("Failed to write segment list")?;
+
+    let result = Command::new("ffmpeg")
+        .args(["-y", "-f", "concat", "-safe", "0", "-i"])
+        .arg(&list_path)
+        .args([
+            "-c",
+            "copy",
+            "-metadata",
+            &format!("comment={}", integrity::id_tag(recording_id)),
+        ])
+        .arg(output)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status();
+    let _ = std::fs::remove_file(&list_path);
+
+    match result {
+        Ok(status) if status.success() => Ok(()),
+        _ => anyhow::bail!("FFmpeg failed to join the recording segments"),
+    }
+}
+
+/// FFmpeg options adding the audio inputs after the frames (input 0) and
+/// mapping them into the output. `system` is a PCM pipe with its sample
+/// rate and channel count.
+fn audio_args(mic: Option<&str>, system: Option<(&Path, u32, u32)>) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut inputs = 0;
+    if let Some(device) = mic {
+        args.extend(mic_input_args(device));
+        inputs += 1;
+    }
+    if let Some((path, sample_rate, channels)) = system {
+        args.extend([
+            // Stamp audio the same way as frames
+            "-use_wallclock_as_timestamps".to_string(),
+            "1".into(),
+            "-thread_queue_size".into(),
+            "1024".into(),
+            "-f".into(),
+            "f32le".into(),
+            "-ar".into(),
+            sample_rate.to_string(),
+            "-ac".into(),
+            channels.to_string(),
+            "-i".into(),
+            path.to_string_lossy().into_owned(),
+        ]);
+        inputs += 1;
+    }
+    if inputs == 0 {
+        return args;
+    }
+
+    // Wallclock timestamps jitter; resample to keep audio continuous
+    if inputs == 1 {
+        args.extend(["-map", "0:v", "-map", "1:a", "-af", "aresample=async=1"].map(String::from));
+    } else {
+        args.extend(
+            [
+                "-filter_complex",
+                "[1:a][2:a]amix=inputs=2:duration=longest:normalize=0,aresample=async=1[a]",
+                "-map",
+                "0:v",
+                "-map",
+                "[a]",
+            ]
+            .map(String::from),
+        );
+    }
+    args.extend(
+        [
+            // Keep all inputs on the wallclock, so audio lines up with frames
+            // however long a device takes to open; the muxer then shifts the
+            // earliest packet to zero
+            "-copyts",
+            "-avoid_negative_ts",
+            "make_zero",
+            "-c:a",
+            "aac",
+            "-b:a",
+            "160k",
+            // Audio never ends on its own: stop with the frames
+            "-shortest",
+        ]
+        .map(String::from),
+    );
+    args
+}
+
+/// FFmpeg input options reading microphone `device` with the platform's audio API
+fn mic_input_args(device: &str) -> Vec<String> {
+    #[cfg(target_os = "macos")]
+    let (format, input) = ("avfoundation", format!(":{}", device));
+    // PulseAudio, or PipeWire through its PulseAudio server
+    #[cfg(target_os = "linux")]
+    let (format, input) = ("pulse", device.to_string());
+    #[cfg(target_os = "windows")]
+    let (format, input) = ("dshow", format!("audio={}", device));
+
+    vec![
+        // Stamp audio the same way as frames
+        "-use_wallclock_as_timestamps".into(),
+        "1".into(),
+        // Don't drop audio while the encoder is busy with a frame
+        "-thread_queue_size".into(),
+        "1024".into(),
+        "-f".into(),
+        format.into(),
+        "-i".into(),
+        input,
+    ]
+}
+
+/// Validate a "#rrggbb" stream background and return it as bare hex digits
+fn parse_stream_color(color: &str) -> Result<&str> {
+    let hex = color.trim_start_matches('#');
+    if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        anyhow::bail!(
+            "Invalid stream background color: {} (expected #rrggbb)",
+            color
+        );
+    }
+    Ok(hex)
+}
+
+/// Frames per second `encoder` encodes `input`'s `frames` frames at, with
+/// the settings recorded files get. Decoding is timed separately and left out.
+pub fn encode_speed(input: &Path, frames: u64, codec: Codec, encoder: Encoder) -> Result<f64> {
+    let run = |args: &[String]| -> Result<f64> {
+        let started = Instant::now();
+        let status = Command::new("ffmpeg")
+            .args(["-v", "error"])
+            .args(encoder.input_args())
+            .arg("-i")
+            .arg(input)
+            .args(args)
+            .args(["-f", "null", "-"])
+            .status()
+            .context("Failed to run ffmpeg")?;
+        if !status.success() {
+            anyhow::bail!("FFmpeg failed to encode with {}", encoder.name());
+        }
+        Ok(started.elapsed().as_secs_f64())
+    };
+    let decode = run(&[])?;
+    let total = run(&video_args(codec, Quality::Standard, encoder))?;
+    Ok(frames as f64 / (total - decode).max(f64::EPSILON))
+}
+
+/// Check if FFmpeg is available
+pub fn check_ffmpeg() -> Result<()> {
+    Command::new("ffmpeg")
+        .arg("-version")
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .context("FFmpeg not found. Please install it with: brew install ffmpeg")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_ffmpeg() {
+        // This test will pass if FFmpeg is installed
+        let result = check_ffmpeg();
+        assert!(result.is_ok(), "FFmpeg should be available");
+    }
+
+    #[test]
+    fn test_audio_args() {
+        assert!(audio_args(None, None).is_empty());
+
+        let mic = audio_args(Some("default"), None);
+        assert_eq!(mic[..2], ["-use_wallclock_as_timestamps", "1"]);
+        let input = mic.iter().position(|a| a == "-i").unwrap();
+        assert!(mic[input + 1].ends_with("default"));
+        assert!(mic.windows(2).any(|w| w == ["-map", "1:a"]));
+
+        // Both sources are mixed into one track
+        let both = audio_args(Some("default"), Some((Path::new("/tmp/a.pcm"), 48000, 2)));
+        assert_eq!(both.iter().filter(|a| *a == "-i").count(), 2);
+        assert!(both.iter().any(|a| a.contains("amix=inputs=2")));
+        assert!(both.windows(2).any(|w| w == ["-ar", "48000"]));
+    }
+
+    #[test]
+    fn test_video_args() {
+        let h264 = video_args(Codec::H264, Quality::Standard, Encoder::X264);
+        assert_eq!(h264[..2], ["-c:v", "libx264"]);
+        assert!(h264.ends_with(&["-pix_fmt".to_string(), "yuv420p".to_string()]));
+        let h265 = video_args(Codec::H265, Quality::Standard, Encoder::X264);
+        assert_eq!(h265[1], "libx265");
+        assert!(h265.windows(2).any(|w| w == ["-tag:v", "hvc1"]));
+
+        let lossless = video_args(Codec::H264, Quality::Lossless, Encoder::X264);
+        assert_eq!(lossless[..2], ["-c:v", "libx264rgb"]);
+        assert!(lossless.windows(2).any(|w| w == ["-qp", "0"]));
+    }
+
+    #[test]
+    fn test_hardware_video_args() {
+        let nvenc = video_args(Codec::H265, Quality::Standard, Encoder::Nvenc);
+        assert_eq!(nvenc[..2], ["-c:v", "hevc_nvenc"]);
+        let vaapi = video_args(Codec::H264, Quality::Standard, Encoder::Vaapi);
+        assert_eq!(vaapi[..2], ["-vf", "format=nv12,hwupload"]);
+        assert!(vaapi.contains(&"h264_vaapi".to_string()));
+        assert!(!Encoder::Videotoolbox.supports(Codec::Vp9));
+    }
+
+    #[test]
+    fn test_parse_stream_color() {
+        assert_eq!(parse_stream_color("#1a1a2e").unwrap(), "1a1a2e");
+        assert_eq!(parse_stream_color("FFFFFF").unwrap(), "FFFFFF");
+        assert!(parse_stream_color("#12345").is_err());
+        assert!(parse_stream_color("red").is_err());
+    }
+}
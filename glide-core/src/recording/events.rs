@@ -0,0 +1,262 @@
+//! `glide events`: cursor events as CSV, for cleaning up in a spreadsheet
+//! or generating from scripts
+
+use crate::cursor_types::{CursorEvent, CursorShape, EventType, KeyPress};
+use crate::keyboard::Modifiers;
+use crate::processing::frames::get_video_size;
+use crate::recording::metadata::{metadata_path_for_video, RecordingMetadata};
+use anyhow::{bail, Context, Result};
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+
+const HEADER: &str = "timestamp,type,x,y,key,modifiers,display,shape";
+
+fn type_name(event_type: &EventType) -> &'static str {
+    match event_type {
+        EventType::Move => "move",
+        EventType::LeftClick => "left_click",
+        EventType::RightClick => "right_click",
+        EventType::KeyDown(_) => "key_down",
+    }
+}
+
+/// Name `CursorShape::from_name` reads back
+fn shape_name(shape: CursorShape) -> &'static str {
+    match shape {
+        CursorShape::Arrow => "arrow",
+        CursorShape::IBeam => "ibeam",
+        CursorShape::PointingHand => "pointing_hand",
+        CursorShape::ResizeHorizontal => "ew-resize",
+        CursorShape::ResizeVertical => "ns-resize",
+    }
+}
+
+/// Modifiers joined with `+`, e.g. "ctrl+shift"
+fn modifiers_name(modifiers: Modifiers) -> String {
+    [
+        (modifiers.ctrl, "ctrl"),
+        (modifiers.alt, "alt"),
+        (modifiers.shift, "shift"),
+        (modifiers.meta, "meta"),
+    ]
+    .iter()
+    .filter(|(held, _)| *held)
+    .map(|(_, name)| *name)
+    .collect::<Vec<_>>()
+    .join("+")
+}
+
+fn parse_modifiers(s: &str) -> Result<Modifiers> {
+    let mut modifiers = Modifiers::default();
+    for name in s.split('+').map(str::trim).filter(|n| !n.is_empty()) {
+        match name.to_ascii_lowercase().as_str() {
+            "ctrl" => modifiers.ctrl = true,
+            "alt" => modifiers.alt = true,
+            "shift" => modifiers.shift = true,
+            "meta" => modifiers.meta = true,
+            _ => bail!("unknown modifier '{name}' (expected ctrl, alt, shift or meta)"),
+        }
+    }
+    Ok(modifiers)
+}
+
+/// Cursor events as CSV, one row per event under a header
+pub fn events_to_csv(events: &[CursorEvent]) -> String {
+    let mut csv = format!("{HEADER}\n");
+    for event in events {
+        let (key, modifiers) = match &event.event_type {
+            EventType::KeyDown(press) => (
+                press.code.as_deref().unwrap_or(""),
+                modifiers_name(press.modifiers),
+            ),
+            _ => ("", String::new()),
+        };
+        let _ = writeln!(
+            csv,
+            "{:.6},{},{},{},{},{},{},{}",
+            event.timestamp,
+            type_name(&event.event_type),
+            event.x,
+            event.y,
+            key,
+            modifiers,
+            event.display.map(|d| d.to_string()).unwrap_or_default(),
+            event.shape.map(shape_name).unwrap_or_default(),
+        );
+    }
+    csv
+}
+
+/// Parse CSV written by [`events_to_csv`] (or by hand). Only timestamp, type,
+/// x and y are required; blank lines and `#` comments are skipped. Events
+/// come back sorted by time.
+pub fn parse_events_csv(csv: &str) -> Result<Vec<CursorEvent>> {
+    let mut events = Vec::new();
+    for (i, line) in csv.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || line.starts_with("timestamp") {
+            continue;
+        }
+        let event = parse_row(line).with_context(|| format!("Line {}: {line}", i + 1))?;
+        events.push(event);
+    }
+    events.sort_by(|a, b| a.timestamp.total_cmp(&b.timestamp));
+    Ok(events)
+}
+
+fn parse_row(line: &str) -> Result<CursorEvent> {
+    let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+    let field = |i: usize| fields.get(i).copied().unwrap_or("");
+    if fields.len() < 4 {
+        bail!("expected at least timestamp, type, x and y");
+    }
+
+    let number = |i: usize, name: &str| -> Result<f64> {
+        field(i)
+            .parse::<f64>()
+            .ok()
+            .filter(|v| v.is_finite())
+            .with_context(|| format!("invalid {name} '{}'", field(i)))
+    };
+    let timestamp = number(0, "timestamp")?;
+    if timestamp < 0.0 {
+        bail!("timestamp can't be negative");
+    }
+
+    let event_type = match field(1) {
+        "move" => EventType::Move,
+        "left_click" => EventType::LeftClick,
+        "right_click" => EventType::RightClick,
+        "key_down" => EventType::KeyDown(KeyPress {
+            code: Some(field(4)).filter(|k| !k.is_empty()).map(String::from),
+            modifiers: parse_modifiers(field(5))?,
+        }),
+        other => bail!(
+            "unknown event type '{other}' (expected move, left_click, right_click or key_down)"
+        ),
+    };
+
+    let display = match field(6) {
+        "" => None,
+        d => Some(
+            d.parse()
+                .with_context(|| format!("invalid display '{d}'"))?,
+        ),
+    };
+    let shape = match field(7) {
+        "" => None,
+        s => Some(CursorShape::from_name(s).with_context(|| format!("unknown shape '{s}'"))?),
+    };
+
+    Ok(CursorEvent {
+        x: number(2, "x")?,
+        y: number(3, "y")?,
+        timestamp,
+        event_type,
+        display,
+        shape,
+    })
+}
+
+/// Write a recording's cursor events to a CSV file
+pub fn export_events(input: &Path, output: &Path) -> Result<usize> {
+    let metadata = RecordingMetadata::load(input)
+        .context("Failed to load recording metadata. Was this video recorded with glide?")?;
+    fs::write(output, events_to_csv(&metadata.cursor_events))
+        .with_context(|| format!("Failed to write {}", output.display()))?;
+    Ok(metadata.cursor_events.len())
+}
+
+/// Replace a recording's cursor events with those in a CSV file. A video
+/// without metadata gets new metadata treating it as a display recording at
+/// `scale_factor`, with event times on the video's timeline.
+pub fn import_events(events: &Path, video: &Path, scale_factor: f64) -> Result<usize> {
+    if scale_factor.is_nan() || scale_factor <= 0.0 {
+        bail!("--scale-factor must be positive");
+    }
+    let csv = fs::read_to_string(events)
+        .with_context(|| format!("Failed to read {}", events.display()))?;
+    let events = parse_events_csv(&csv)?;
+
+    let mut metadata = if metadata_path_for_video(video).exists() {
+        RecordingMetadata::load(video)?
+    } else {
+        let (width, height) = get_video_size(video)?;
+        RecordingMetadata::new_display(0, width, height, scale_factor)
+    };
+    let count = events.len();
+    metadata.cursor_events = events;
+    metadata.save(video)?;
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_csv_round_trip() {
+        let events = vec![
+            CursorEvent {
+                x: 10.5,
+                y: 20.0,
+                timestamp: 0.25,
+                event_type: EventType::Move,
+                display: Some(1),
+                shape: Some(CursorShape::IBeam),
+            },
+            CursorEvent {
+                x: 11.0,
+                y: 21.0,
+                timestamp: 0.5,
+                event_type: EventType::KeyDown(KeyPress {
+                    code: Some("KeyS".into()),
+                    modifiers: Modifiers {
+                        meta: true,
+                        shift: true,
+                        ..Default::default()
+                    },
+                }),
+                display: None,
+                shape: Some(CursorShape::ResizeVertical),
+            },
+        ];
+        let csv = events_to_csv(&events);
+        assert!(csv.starts_with(HEADER));
+        assert!(csv.contains("0.500000,key_down,11,21,KeyS,shift+meta,,ns-resize"));
+
+        let parsed = parse_events_csv(&csv).unwrap();
+        assert_eq!(parsed.len(), 2);
+        assert_eq!(parsed[0].display, Some(1));
+        assert_eq!(parsed[0].shape, Some(CursorShape::IBeam));
+        match &parsed[1].event_type {
+            EventType::KeyDown(press) => {
+                assert_eq!(press.code.as_deref(), Some("KeyS"));
+                assert!(press.modifiers.meta && press.modifiers.shift);
+                assert!(!press.modifiers.ctrl);
+            }
+            other => panic!("expected key_down, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_parse_minimal_rows() {
+        let csv = "# synthetic track\n2.0,left_click,100,200\n\n1.0,move,0,0\n";
+        let events = parse_events_csv(csv).unwrap();
+        assert_eq!(events.len(), 2);
+        // Sorted by time
+        assert!(matches!(events[0].event_type, EventType::Move));
+        assert!(matches!(events[1].event_type, EventType::LeftClick));
+        assert_eq!(events[1].display, None);
+    }
+
+    #[test]
+    fn test_parse_errors_name_the_line() {
+        let err = parse_events_csv("1.0,move,0,0\n2.0,double_click,0,0\n").unwrap_err();
+        assert!(format!("{err:#}").contains("Line 2"));
+        assert!(parse_events_csv("-1,move,0,0").is_err());
+        assert!(parse_events_csv("1.0,move,0").is_err());
+        assert!(parse_events_csv("1.0,key_down,0,0,KeyA,hyper").is_err());
+    }
+}
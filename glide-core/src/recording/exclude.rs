@@ -0,0 +1,94 @@
+//! `--exclude-app` / `--exclude-window`: keep windows such as chat apps and
+//! notifications out of display recordings
+//!
+//! ScreenCaptureKit leaves excluded windows out of the capture itself. The
+//! FFmpeg-based captures (x11grab, ddagrab) can't, so there the windows are
+//! covered with black boxes where they were when recording started.
+
+/// Windows to leave out, by owning app or window ID
+#[derive(Debug, Clone, Default)]
+pub struct Exclusions {
+    /// App names (`glide list windows`' owner column), matched ignoring case
+    pub apps: Vec<String>,
+    pub windows: Vec<u32>,
+}
+
+impl Exclusions {
+    pub fn is_empty(&self) -> bool {
+        self.apps.is_empty() && self.windows.is_empty()
+    }
+
+    /// Whether window `id`, owned by `owner`, is excluded
+    pub fn matches(&self, id: u32, owner: &str) -> bool {
+        self.windows.contains(&id) || self.apps.iter().any(|app| app.eq_ignore_ascii_case(owner))
+    }
+
+    /// Warn when exclusions were given but no window matched them, since
+    /// the recording would show everything
+    pub fn warn_if_unmatched(&self, matched: usize) {
+        if !self.is_empty() && matched == 0 {
+            tracing::warn!("no open window matches --exclude-app/--exclude-window");
+        }
+    }
+}
+
+/// FFmpeg `drawbox` filters blacking out `boxes` (screen coordinates) in a
+/// capture of the `size` rectangle at `origin`; None if none fall inside it
+pub fn drawbox_filter(
+    boxes: &[(i32, i32, u32, u32)],
+    origin: (i32, i32),
+    size: (u32, u32),
+) -> Option<String> {
+    let filters: Vec<String> = boxes
+        .iter()
+        .filter_map(|&(x, y, w, h)| {
+            let left = (x - origin.0).max(0);
+            let top = (y - origin.1).max(0);
+            let right = (x - origin.0 + w as i32).min(size.0 as i32);
+            let bottom = (y - origin.1 + h as i32).min(size.1 as i32);
+            (right > left && bottom > top).then(|| {
+                format!(
+                    "drawbox=x={}:y={}:w={}:h={}:color=black:t=fill",
+                    left,
+                    top,
+                    right - left,
+                    bottom - top
+                )
+            })
+        })
+        .collect();
+    (!filters.is_empty()).then(|| filters.join(","))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matches() {
+        let exclusions = Exclusions {
+            apps: vec!["Slack".to_string()],
+            windows: vec![42],
+        };
+        assert!(exclusions.matches(1, "slack"));
+        assert!(exclusions.matches(42, "Terminal"));
+        assert!(!exclusions.matches(1, "Terminal"));
+        assert!(Exclusions::default().is_empty());
+    }
+
+    #[test]
+    fn test_drawbox_filter() {
+        // On a second display at x=1920: one box inside, one clipped, one off-screen
+        let boxes = [
+            (2000, 100, 300, 200),
+            (3700, 1000, 400, 200),
+            (0, 0, 500, 500),
+        ];
+        assert_eq!(
+            drawbox_filter(&boxes, (1920, 0), (1920, 1080)).unwrap(),
+            "drawbox=x=80:y=100:w=300:h=200:color=black:t=fill,\
+             drawbox=x=1780:y=1000:w=140:h=80:color=black:t=fill"
+        );
+        assert_eq!(drawbox_filter(&boxes[2..], (1920, 0), (1920, 1080)), None);
+    }
+}
@@ -0,0 +1,123 @@
+//! Key combos for `glide record --hotkey`
+
+use std::fmt;
+use std::str::FromStr;
+
+/// The non-modifier key of a hotkey
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HotkeyKey {
+    /// A letter (lowercase) or digit
+    Char(char),
+    /// A function key, F1 to F12
+    Function(u8),
+}
+
+/// A global key combo: at least one modifier plus one key, so it can't
+/// fire while typing normally
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Hotkey {
+    pub ctrl: bool,
+    pub alt: bool,
+    pub shift: bool,
+    /// Cmd on macOS, the Windows key on Windows, Super on Linux
+    pub meta: bool,
+    pub key: HotkeyKey,
+}
+
+impl FromStr for Hotkey {
+    type Err = String;
+
+    /// Parse "ctrl+shift+r", "cmd+alt+f9", ... (case-insensitive)
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = |why: &str| {
+            format!(
+                "invalid hotkey '{}': {} (expected e.g. ctrl+shift+r or cmd+alt+f9)",
+                s, why
+            )
+        };
+
+        let mut hotkey = Hotkey {
+            ctrl: false,
+            alt: false,
+            shift: false,
+            meta: false,
+            key: HotkeyKey::Char(' '),
+        };
+        let mut key = None;
+        for part in s.split('+').map(|p| p.trim().to_ascii_lowercase()) {
+            let modifier = match part.as_str() {
+                "ctrl" | "control" => &mut hotkey.ctrl,
+                "alt" | "option" | "opt" => &mut hotkey.alt,
+                "shift" => &mut hotkey.shift,
+                "cmd" | "command" | "super" | "win" | "meta" => &mut hotkey.meta,
+                _ => {
+                    if key.is_some() {
+                        return Err(invalid("more than one key"));
+                    }
+                    key = Some(parse_key(&part).ok_or_else(|| invalid("unknown key"))?);
+                    continue;
+                }
+            };
+            *modifier = true;
+        }
+
+        hotkey.key = key.ok_or_else(|| invalid("no key"))?;
+        if !(hotkey.ctrl || hotkey.alt || hotkey.shift || hotkey.meta) {
+            return Err(invalid("needs at least one modifier"));
+        }
+        Ok(hotkey)
+    }
+}
+
+fn parse_key(key: &str) -> Option<HotkeyKey> {
+    let mut chars = key.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) if c.is_ascii_alphanumeric() => Some(HotkeyKey::Char(c)),
+        (Some('f'), Some(_)) => match key[1..].parse() {
+            Ok(n @ 1..=12) => Some(HotkeyKey::Function(n)),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+impl fmt::Display for Hotkey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let modifiers = [
+            (self.ctrl, "Ctrl"),
+            (self.alt, "Alt"),
+            (self.shift, "Shift"),
+            (self.meta, "Meta"),
+        ];
+        for (_, name) in modifiers.iter().filter(|(on, _)| *on) {
+            write!(f, "{}+", name)?;
+        }
+        match self.key {
+            HotkeyKey::Char(c) => write!(f, "{}", c.to_ascii_uppercase()),
+            HotkeyKey::Function(n) => write!(f, "F{}", n),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_hotkey() {
+        let hotkey: Hotkey = "Ctrl+Shift+R".parse().unwrap();
+        assert!(hotkey.ctrl && hotkey.shift && !hotkey.alt && !hotkey.meta);
+        assert_eq!(hotkey.key, HotkeyKey::Char('r'));
+        assert_eq!(hotkey.to_string(), "Ctrl+Shift+R");
+
+        let hotkey: Hotkey = "cmd+opt+f9".parse().unwrap();
+        assert!(hotkey.meta && hotkey.alt);
+        assert_eq!(hotkey.key, HotkeyKey::Function(9));
+
+        assert!("r".parse::<Hotkey>().is_err());
+        assert!("ctrl+shift".parse::<Hotkey>().is_err());
+        assert!("ctrl+f13".parse::<Hotkey>().is_err());
+        assert!("ctrl+a+b".parse::<Hotkey>().is_err());
+        assert!("ctrl+space".parse::<Hotkey>().is_err());
+    }
+}
@@ -0,0 +1,131 @@
+//! Pairing checks between a recording and its metadata
+//!
+//! At record time the video is tagged with a recording ID that is also stored
+//! in the metadata, and the metadata stores a SHA-256 of the finished video.
+//! `process` uses both to catch a video paired with the wrong JSON.
+
+use crate::recording::metadata::RecordingMetadata;
+use anyhow::{Context, Result};
+use sha2::{Digest, Sha256};
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Prefix of the container comment tag that carries the recording ID
+const ID_TAG_PREFIX: &str = "glide:";
+
+/// A fresh ID for a recording
+pub fn new_recording_id() -> String {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or(0);
+    format!("{:x}-{:x}", nanos, std::process::id())
+}
+
+/// Container comment embedding `recording_id` (written with `-metadata comment=...`)
+pub fn id_tag(recording_id: &str) -> String {
+    format!("{}{}", ID_TAG_PREFIX, recording_id)
+}
+
+/// Hex SHA-256 of a file's contents
+pub fn hash_file(path: &Path) -> Result<String> {
+    let mut file =
+        File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; 1 << 20];
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Recording ID from the video's container tags, if it has one
+pub fn read_recording_id(video: &Path) -> Option<String> {
+    let output = Command::new("ffprobe")
+        .args([
+            "-v",
+            "error",
+            "-show_entries",
+            "format_tags=comment",
+            "-of",
+            "csv=p=0",
+            video.to_str()?,
+        ])
+        .output()
+        .ok()?;
+    parse_id_tag(&String::from_utf8_lossy(&output.stdout))
+}
+
+fn parse_id_tag(comment: &str) -> Option<String> {
+    comment
+        .trim()
+        .strip_prefix(ID_TAG_PREFIX)
+        .map(|id| id.to_string())
+}
+
+/// Check that `metadata` belongs to `video`.
+///
+/// The ID tag is compared first since it survives remuxing; the content hash
+/// then catches re-exports. Recordings made before these fields existed pass.
+pub fn verify_pair(video: &Path, metadata: &RecordingMetadata) -> Result<()> {
+    if let Some(ref expected) = metadata.recording_id {
+        match read_recording_id(video) {
+            Some(ref found) if found != expected => anyhow::bail!(
+                "{} is from a different recording than its metadata (recording ID {} vs {})",
+                video.display(),
+                found,
+                expected
+            ),
+            // Re-encoded or ID matched; fall through to the hash
+            _ => {}
+        }
+    }
+
+    if let Some(ref expected) = metadata.video_sha256 {
+        let found = hash_file(video)?;
+        if &found != expected {
+            anyhow::bail!(
+                "{} doesn't match the video its metadata was recorded with \
+                 (it may have been re-exported or replaced)",
+                video.display()
+            );
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_id_tag() {
+        assert_eq!(parse_id_tag("glide:abc-12\n"), Some("abc-12".to_string()));
+        assert_eq!(parse_id_tag("Recorded with OBS"), None);
+        assert_eq!(parse_id_tag(""), None);
+    }
+
+    #[test]
+    fn test_verify_pair_hash() {
+        let dir = tempfile::tempdir().unwrap();
+        let video = dir.path().join("rec.mp4");
+        std::fs::write(&video, b"frames").unwrap();
+
+        let mut metadata = RecordingMetadata::new_display(0, 10, 10, 1.0);
+        assert!(verify_pair(&video, &metadata).is_ok());
+
+        metadata.video_sha256 = Some(hash_file(&video).unwrap());
+        assert!(verify_pair(&video, &metadata).is_ok());
+
+        std::fs::write(&video, b"re-exported").unwrap();
+        assert!(verify_pair(&video, &metadata).is_err());
+    }
+}
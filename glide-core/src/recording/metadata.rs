@@ -0,0 +1,216 @@
+use crate::cursor_types::{ClickTarget, CursorEvent};
+use crate::keyboard::KeyboardInfo;
+use crate::recording::crypto::{is_encrypted, DecryptKey, EncryptKey};
+use crate::recording::pause::PauseSpan;
+use crate::recording::stats::CaptureStats;
+use crate::recording::window_tracker::{self, WindowBounds};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum SourceType {
+    Display,
+    Window,
+    /// A rectangle of a display (`--region`)
+    Region,
+}
+
+/// Version of the recording format (video + metadata pairing and fields).
+/// Bump when a change would make older versions misread a recording.
+pub const SCHEMA_VERSION: u32 = 1;
+
+fn default_scale_factor() -> f64 {
+    1.0
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordingMetadata {
+    pub source_type: SourceType,
+    pub source_index: usize,
+    pub width: u32,
+    pub height: u32,
+    /// Window offset on screen (for translating cursor coordinates)
+    #[serde(default)]
+    pub window_offset: (i32, i32),
+    /// Duration of cursor tracking (for timestamp synchronization)
+    #[serde(default)]
+    pub cursor_tracking_duration: f64,
+    /// Display scale factor (1.0 for standard, 2.0 for Retina)
+    /// Used to convert cursor coordinates from screen points to pixels
+    #[serde(default = "default_scale_factor")]
+    pub scale_factor: f64,
+    /// OS cursor size at record time, relative to the standard size
+    /// (macOS pointer size / X cursor size). None if it couldn't be read.
+    #[serde(default)]
+    pub os_cursor_scale: Option<f64>,
+    /// Keyboard layout at record time, for labelling keystrokes
+    #[serde(default)]
+    pub keyboard: Option<KeyboardInfo>,
+    /// ID also written into the video's comment tag, to pair the two files
+    #[serde(default)]
+    pub recording_id: Option<String>,
+    /// SHA-256 of the recorded video
+    #[serde(default)]
+    pub video_sha256: Option<String>,
+    pub cursor_events: Vec<CursorEvent>,
+    /// Bounds of clicked UI elements, where the platform exposes them
+    #[serde(default)]
+    pub click_targets: Vec<ClickTarget>,
+    /// Pauses cut out of the recording. Cursor events are already on the
+    /// output timeline; these record where the cuts are.
+    #[serde(default)]
+    pub pauses: Vec<PauseSpan>,
+    /// Where a captured window was over time, if it moved or was resized.
+    /// Empty when `window_offset` holds for the whole recording.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub window_bounds: Vec<WindowBounds>,
+    /// How well the capture kept up. None for recordings made before
+    /// these were kept, and for joined or imported ones.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub capture_stats: Option<CaptureStats>,
+    /// Moments marked while recording, in order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub markers: Vec<Marker>,
+}
+
+/// A moment marked while recording, over the control socket
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Marker {
+    /// Seconds on the cursor tracking clock, like the events
+    pub timestamp: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+}
+
+impl RecordingMetadata {
+    pub fn new_display(index: usize, width: u32, height: u32, scale_factor: f64) -> Self {
+        Self {
+            source_type: SourceType::Display,
+            source_index: index,
+            width,
+            height,
+            window_offset: (0, 0),
+            cursor_tracking_duration: 0.0,
+            scale_factor,
+            os_cursor_scale: None,
+            keyboard: None,
+            recording_id: None,
+            video_sha256: None,
+            cursor_events: Vec::new(),
+            click_targets: Vec::new(),
+            pauses: Vec::new(),
+            window_bounds: Vec::new(),
+            capture_stats: None,
+            markers: Vec::new(),
+        }
+    }
+
+    pub fn new_window(
+        window_id: u32,
+        width: u32,
+        height: u32,
+        offset_x: i32,
+        offset_y: i32,
+        scale_factor: f64,
+    ) -> Self {
+        Self {
+            source_type: SourceType::Window,
+            source_index: window_id as usize,
+            width,
+            height,
+            window_offset: (offset_x, offset_y),
+            cursor_tracking_duration: 0.0,
+            scale_factor,
+            os_cursor_scale: None,
+            keyboard: None,
+            recording_id: None,
+            video_sha256: None,
+            cursor_events: Vec::new(),
+            click_targets: Vec::new(),
+            pauses: Vec::new(),
+            window_bounds: Vec::new(),
+            capture_stats: None,
+            markers: Vec::new(),
+        }
+    }
+
+    /// A region of display `display_index`, with its top-left at
+    /// (`offset_x`, `offset_y`) in screen coordinates
+    pub fn new_region(
+        display_index: usize,
+        width: u32,
+        height: u32,
+        offset_x: i32,
+        offset_y: i32,
+        scale_factor: f64,
+    ) -> Self {
+        Self {
+            source_type: SourceType::Region,
+            source_index: display_index,
+            ..Self::new_window(0, width, height, offset_x, offset_y, scale_factor)
+        }
+    }
+
+    /// Size of the recorded area in screen points (the units of cursor events)
+    pub fn size_in_points(&self) -> (f64, f64) {
+        let scale_factor = self.scale_factor.max(1.0);
+        (
+            self.width as f64 / scale_factor,
+            self.height as f64 / scale_factor,
+        )
+    }
+
+    /// Window offset at `timestamp` seconds, following the window if it moved
+    pub fn window_offset_at(&self, timestamp: f64) -> (f64, f64) {
+        window_tracker::offset_at(&self.window_bounds, timestamp)
+            .unwrap_or((self.window_offset.0 as f64, self.window_offset.1 as f64))
+    }
+
+    pub fn save(&self, video_path: &Path) -> Result<()> {
+        let metadata_path = metadata_path_for_video(video_path);
+        let json = serde_json::to_string_pretty(self)?;
+        fs::write(&metadata_path, json)
+            .with_context(|| format!("Failed to write metadata to {:?}", metadata_path))?;
+        Ok(())
+    }
+
+    /// Write the metadata as an encrypted file
+    pub fn save_encrypted(&self, video_path: &Path, key: &EncryptKey) -> Result<()> {
+        let metadata_path = metadata_path_for_video(video_path);
+        let json = serde_json::to_string_pretty(self)?;
+        key.write_file(&metadata_path, json.as_bytes())
+            .with_context(|| format!("Failed to write metadata to {:?}", metadata_path))
+    }
+
+    pub fn load(video_path: &Path) -> Result<Self> {
+        let metadata_path = metadata_path_for_video(video_path);
+        if is_encrypted(&metadata_path) {
+            anyhow::bail!(
+                "Metadata {:?} is encrypted; process it with `glide process` (passphrase or --identity)",
+                metadata_path
+            );
+        }
+        let json = fs::read_to_string(&metadata_path)
+            .with_context(|| format!("Failed to read metadata from {:?}", metadata_path))?;
+        let metadata: Self = serde_json::from_str(&json)?;
+        Ok(metadata)
+    }
+
+    /// Load metadata, decrypting it if it was recorded with `--encrypt`
+    pub fn load_with_key(video_path: &Path, key: &DecryptKey) -> Result<Self> {
+        let metadata_path = metadata_path_for_video(video_path);
+        if !is_encrypted(&metadata_path) {
+            return Self::load(video_path);
+        }
+        let json = key.read_to_string(&metadata_path)?;
+        let metadata: Self = serde_json::from_str(&json)?;
+        Ok(metadata)
+    }
+}
+
+/// Get the metadata file path for a video file (same name with .json extension)
+pub fn metadata_path_for_video(video_path: &Path) -> std::path::PathBuf {
+    video_path.with_extension("json")
+}
@@ -0,0 +1,226 @@
+//! Minimal Matroska muxer for raw BGRA frames
+//!
+//! Raw video piped to FFmpeg carries no timestamps, so frames dropped under
+//! load used to shorten the video against the cursor track. Wrapping each
+//! frame in a Matroska cluster lets it keep the time it was captured at.
+
+use std::io::{self, Write};
+
+const EBML: u32 = 0x1A45_DFA3;
+const EBML_VERSION: u32 = 0x4286;
+const EBML_READ_VERSION: u32 = 0x42F7;
+const EBML_MAX_ID_LENGTH: u32 = 0x42F2;
+const EBML_MAX_SIZE_LENGTH: u32 = 0x42F3;
+const DOC_TYPE: u32 = 0x4282;
+const DOC_TYPE_VERSION: u32 = 0x4287;
+const DOC_TYPE_READ_VERSION: u32 = 0x4285;
+const SEGMENT: u32 = 0x1853_8067;
+const INFO: u32 = 0x1549_A966;
+const TIMESTAMP_SCALE: u32 = 0x2A_D7B1;
+const MUXING_APP: u32 = 0x4D80;
+const WRITING_APP: u32 = 0x5741;
+const TRACKS: u32 = 0x1654_AE6B;
+const TRACK_ENTRY: u32 = 0xAE;
+const TRACK_NUMBER: u32 = 0xD7;
+const TRACK_UID: u32 = 0x73C5;
+const TRACK_TYPE: u32 = 0x83;
+const FLAG_LACING: u32 = 0x9C;
+const CODEC_ID: u32 = 0x86;
+const VIDEO: u32 = 0xE0;
+const PIXEL_WIDTH: u32 = 0xB0;
+const PIXEL_HEIGHT: u32 = 0xBA;
+const COLOUR_SPACE: u32 = 0x2E_B524;
+const CLUSTER: u32 = 0x1F43_B675;
+const TIMESTAMP: u32 = 0xE7;
+const SIMPLE_BLOCK: u32 = 0xA3;
+
+/// Size of an element whose end isn't known up front (the segment, which
+/// grows for as long as the recording runs)
+const UNKNOWN_SIZE: [u8; 8] = [0x01, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF];
+
+/// Track number, relative timestamp and keyframe flag ahead of each frame
+const BLOCK_HEADER: [u8; 4] = [0x81, 0x00, 0x00, 0x80];
+
+/// Writes BGRA frames with millisecond timestamps as a Matroska stream
+pub struct MkvWriter<W: Write> {
+    inner: W,
+    frame_size: usize,
+    last_timestamp: Option<u64>,
+}
+
+impl<W: Write> MkvWriter<W> {
+    /// Write the headers for `width`x`height` frames
+    pub fn new(mut inner: W, width: u32, height: u32) -> io::Result<Self> {
+        let mut header = Vec::new();
+        element(
+            &mut header,
+            EBML,
+            &[
+                uint(EBML_VERSION, 1),
+                uint(EBML_READ_VERSION, 1),
+                uint(EBML_MAX_ID_LENGTH, 4),
+                uint(EBML_MAX_SIZE_LENGTH, 8),
+                element_bytes(DOC_TYPE, b"matroska"),
+                uint(DOC_TYPE_VERSION, 4),
+                uint(DOC_TYPE_READ_VERSION, 2),
+            ]
+            .concat(),
+        );
+        write_id(&mut header, SEGMENT);
+        header.extend_from_slice(&UNKNOWN_SIZE);
+        element(
+            &mut header,
+            INFO,
+            &[
+                // Milliseconds
+                uint(TIMESTAMP_SCALE, 1_000_000),
+                element_bytes(MUXING_APP, b"glide"),
+                element_bytes(WRITING_APP, b"glide"),
+            ]
+            .concat(),
+        );
+        let video = [
+            uint(PIXEL_WIDTH, width as u64),
+            uint(PIXEL_HEIGHT, height as u64),
+            // FourCC FFmpeg maps to its bgra pixel format
+            element_bytes(COLOUR_SPACE, b"BGRA"),
+        ]
+        .concat();
+        let track = [
+            uint(TRACK_NUMBER, 1),
+            uint(TRACK_UID, 1),
+            // Video
+            uint(TRACK_TYPE, 1),
+            uint(FLAG_LACING, 0),
+            element_bytes(CODEC_ID, b"V_UNCOMPRESSED"),
+            element_bytes(VIDEO, &video),
+        ]
+        .concat();
+        element(&mut header, TRACKS, &element_bytes(TRACK_ENTRY, &track));
+        inner.write_all(&header)?;
+
+        Ok(Self {
+            inner,
+            frame_size: width as usize * height as usize * 4,
+            last_timestamp: None,
+        })
+    }
+
+    /// Write a frame shown at `timestamp` milliseconds. Timestamps must
+    /// increase, so one that doesn't is nudged past the previous frame's.
+    pub fn write_frame(&mut self, data: &[u8], timestamp: u64) -> io::Result<()> {
+        if data.len() != self.frame_size {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "expected a {} byte frame, got {}",
+                    self.frame_size,
+                    data.len()
+                ),
+            ));
+        }
+        let timestamp = match self.last_timestamp {
+            Some(last) => timestamp.max(last + 1),
+            None => timestamp,
+        };
+        self.last_timestamp = Some(timestamp);
+
+        // One cluster per frame keeps every block at relative time zero
+        let cluster_timestamp = uint(TIMESTAMP, timestamp);
+        let block_size = (BLOCK_HEADER.len() + data.len()) as u64;
+        let mut head = Vec::with_capacity(32);
+        write_id(&mut head, CLUSTER);
+        write_size(
+            &mut head,
+            cluster_timestamp.len() as u64 + 1 + 8 + block_size,
+        );
+        head.extend_from_slice(&cluster_timestamp);
+        write_id(&mut head, SIMPLE_BLOCK);
+        write_size(&mut head, block_size);
+        head.extend_from_slice(&BLOCK_HEADER);
+
+        self.inner.write_all(&head)?;
+        self.inner.write_all(data)
+    }
+}
+
+/// Element IDs are written as-is, with their length marker bits
+fn write_id(out: &mut Vec<u8>, id: u32) {
+    let bytes = id.to_be_bytes();
+    let skip = bytes.iter().take_while(|&&b| b == 0).count();
+    out.extend_from_slice(&bytes[skip..]);
+}
+
+/// Sizes are always written in 8 bytes, which fits any frame
+fn write_size(out: &mut Vec<u8>, size: u64) {
+    out.push(0x01);
+    out.extend_from_slice(&size.to_be_bytes()[1..]);
+}
+
+fn element(out: &mut Vec<u8>, id: u32, data: &[u8]) {
+    write_id(out, id);
+    write_size(out, data.len() as u64);
+    out.extend_from_slice(data);
+}
+
+fn element_bytes(id: u32, data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::new();
+    element(&mut out, id, data);
+    out
+}
+
+fn uint(id: u32, value: u64) -> Vec<u8> {
+    let bytes = value.to_be_bytes();
+    let skip = bytes.iter().take_while(|&&b| b == 0).count().min(7);
+    element_bytes(id, &bytes[skip..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+        haystack.windows(needle.len()).any(|w| w == needle)
+    }
+
+    #[test]
+    fn test_header() {
+        let writer = MkvWriter::new(Vec::new(), 2, 2).unwrap();
+        let header = writer.inner;
+        assert_eq!(header[..4], [0x1A, 0x45, 0xDF, 0xA3]);
+        assert!(contains(&header, b"V_UNCOMPRESSED"));
+        assert!(contains(&header, b"BGRA"));
+        // Segment of unknown size
+        assert!(contains(
+            &header,
+            &[&[0x18, 0x53, 0x80, 0x67][..], &UNKNOWN_SIZE].concat()
+        ));
+    }
+
+    #[test]
+    fn test_frame_cluster() {
+        let mut writer = MkvWriter::new(Vec::new(), 1, 1).unwrap();
+        let start = writer.inner.len();
+        writer.write_frame(&[1, 2, 3, 4], 300).unwrap();
+        let cluster = &writer.inner[start..];
+
+        assert_eq!(cluster[..4], [0x1F, 0x43, 0xB6, 0x75]);
+        // Timestamp element (id, size, 2-byte value) + block id + size + header + frame
+        let size = u64::from_be_bytes(cluster[4..12].try_into().unwrap()) & 0x00FF_FFFF_FFFF_FFFF;
+        assert_eq!(size, 11 + 1 + 8 + 4 + 4);
+        assert_eq!(cluster.len() as u64, 12 + size);
+        assert!(cluster.ends_with(&[0x81, 0, 0, 0x80, 1, 2, 3, 4]));
+
+        assert!(writer.write_frame(&[0; 8], 400).is_err());
+    }
+
+    #[test]
+    fn test_timestamps_increase() {
+        let mut writer = MkvWriter::new(Vec::new(), 1, 1).unwrap();
+        writer.write_frame(&[0; 4], 100).unwrap();
+        writer.write_frame(&[0; 4], 100).unwrap();
+        assert_eq!(writer.last_timestamp, Some(101));
+        writer.write_frame(&[0; 4], 90).unwrap();
+        assert_eq!(writer.last_timestamp, Some(102));
+    }
+}
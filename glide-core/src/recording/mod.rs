@@ -0,0 +1,29 @@
+pub mod bundle;
+pub mod concat;
+pub mod control;
+pub mod crypto;
+pub mod disk;
+pub mod encoder;
+pub mod events;
+pub mod exclude;
+pub mod hotkey;
+pub mod integrity;
+pub mod metadata;
+pub mod mkv;
+pub mod pause;
+pub mod recorder;
+pub mod region;
+pub mod rollover;
+pub mod select;
+pub mod stats;
+pub mod status;
+pub mod window_filter;
+pub mod window_tracker;
+
+// Re-export commonly used types
+pub use hotkey::Hotkey;
+pub use recorder::{
+    record_app, record_display, record_displays, record_region, record_window, RecordOptions,
+    RecordTarget, Recorder,
+};
+pub use region::Region;
@@ -0,0 +1,271 @@
+//! Pausing and resuming a recording
+//!
+//! Each pause request toggles between recording and paused. Requests come
+//! from [`request`]: the control socket makes them, and so does the `glide`
+//! CLI on SIGUSR1 (`kill -USR1 <pid>`) or `p` and Enter typed in the
+//! terminal. While paused no frames reach the encoder; every stretch of
+//! recording is encoded as its own segment, and the segments are joined
+//! into the output at the end. Cursor events are moved onto the joined
+//! timeline, so processing never sees the pauses.
+
+use crate::recording::encoder;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::Instant;
+use tempfile::TempDir;
+
+/// Pause requests not yet handled. A counter rather than a flag so that
+/// two quick requests (pause, then resume) aren't merged into one.
+static REQUESTS: AtomicU32 = AtomicU32::new(0);
+
+/// A pause in a finished recording
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PauseSpan {
+    /// Seconds into the output video where the pause was cut out
+    pub at: f64,
+    /// Seconds of wallclock time that were skipped
+    pub duration: f64,
+}
+
+/// Tracks pause requests and the stretches of time spent paused
+pub struct PauseControl {
+    start: Instant,
+    enabled: bool,
+    warned: bool,
+    paused_at: Option<f64>,
+    /// Pauses as (start, end) in seconds since `start`
+    spans: Vec<(f64, f64)>,
+}
+
+impl PauseControl {
+    /// Start taking pause requests. `start` is when cursor tracking
+    /// began; `enabled` is false where segments can't be joined afterwards.
+    pub fn new(start: Instant, enabled: bool) -> Self {
+        // Requests made before recording started don't count
+        REQUESTS.store(0, Ordering::SeqCst);
+        Self {
+            start,
+            enabled,
+            warned: false,
+            paused_at: None,
+            spans: Vec::new(),
+        }
+    }
+
+    /// Handle a pending request, returning the new paused state if it changed
+    pub fn poll(&mut self) -> Option<bool> {
+        if REQUESTS.load(Ordering::Relaxed) == 0 {
+            return None;
+        }
+        REQUESTS.fetch_sub(1, Ordering::SeqCst);
+
+        if !self.enabled {
+            if !self.warned {
+                tracing::warn!("pausing isn't available when streaming or encrypting");
+                self.warned = true;
+            }
+            return None;
+        }
+
+        let now = self.start.elapsed().as_secs_f64();
+        match self.paused_at.take() {
+            Some(paused_at) => {
+                self.spans.push((paused_at, now));
+                Some(false)
+            }
+            None => {
+                self.paused_at = Some(now);
+                Some(true)
+            }
+        }
+    }
+
+    /// Seconds recorded so far, not counting time spent paused
+    pub fn recorded_time(&self) -> f64 {
+        let now = self.start.elapsed().as_secs_f64();
+        let paused: f64 = self.spans.iter().map(|(start, end)| end - start).sum();
+        let pausing = self.paused_at.map_or(0.0, |paused_at| now - paused_at);
+        now - paused - pausing
+    }
+
+    /// Stop tracking, closing a pause still in progress
+    pub fn finish(mut self) -> PauseTimeline {
+        if let Some(paused_at) = self.paused_at.take() {
+            self.spans
+                .push((paused_at, self.start.elapsed().as_secs_f64()));
+        }
+        PauseTimeline { spans: self.spans }
+    }
+}
+
+/// Maps cursor-tracking time onto the output, which has the pauses cut out
+#[derive(Debug, Default)]
+pub struct PauseTimeline {
+    /// Pauses as (start, end) in cursor-tracking seconds, in order
+    spans: Vec<(f64, f64)>,
+}
+
+impl PauseTimeline {
+    /// Where tracking time `t` lands in the output, or None if it was paused
+    pub fn output_time(&self, t: f64) -> Option<f64> {
+        let mut skipped = 0.0;
+        for &(start, end) in &self.spans {
+            if t >= end {
+                skipped += end - start;
+            } else if t >= start {
+                return None;
+            }
+        }
+        Some(t - skipped)
+    }
+
+    /// Where tracking time `t` lands in the output; times within a pause
+    /// land where it was cut out
+    pub fn nearest_output_time(&self, t: f64) -> f64 {
+        let mut skipped = 0.0;
+        for &(start, end) in &self.spans {
+            if t >= end {
+                skipped += end - start;
+            } else if t >= start {
+                return start - skipped;
+            }
+        }
+        t - skipped
+    }
+
+    /// Total time spent paused
+    pub fn total(&self) -> f64 {
+        self.spans.iter().map(|(start, end)| end - start).sum()
+    }
+
+    /// The pauses, positioned on the output timeline
+    pub fn spans(&self) -> Vec<PauseSpan> {
+        let mut skipped = 0.0;
+        self.spans
+            .iter()
+            .map(|&(start, end)| {
+                let span = PauseSpan {
+                    at: start - skipped,
+                    duration: end - start,
+                };
+                skipped += end - start;
+                span
+            })
+            .collect()
+    }
+}
+
+/// The encoded stretches of a paused recording
+pub struct Segments {
+    // Next to the output, so segments are moved rather than copied
+    dir: TempDir,
+    paths: Vec<PathBuf>,
+}
+
+impl Segments {
+    /// Set up at the first pause: what was encoded so far becomes the first segment
+    pub fn start(output: &Path) -> Result<Self> {
+        let parent = output
+            .parent()
+            .filter(|p| !p.as_os_str().is_empty())
+            .unwrap_or(Path::new("."));
+        let dir = tempfile::Builder::new()
+            .prefix(".glide-segments-")
+            .tempdir_in(parent)
+            .context("Failed to create a directory for recording segments")?;
+        let mut segments = Self {
+            dir,
+            paths: Vec::new(),
+        };
+        let first = segments.next_path();
+        std::fs::rename(output, &first)
+            .with_context(|| format!("Failed to move {} aside", output.display()))?;
+        Ok(segments)
+    }
+
+    /// Path for the next segment
+    pub fn next_path(&mut self) -> PathBuf {
+        let path = self
+            .dir
+            .path()
+            .join(format!("segment-{:03}.mp4", self.paths.len()));
+        self.paths.push(path.clone());
+        path
+    }
+
+    /// Join the segments into `output`, then remove them. If that fails
+    /// they're kept, so nothing recorded is lost.
+    pub fn join(self, output: &Path, recording_id: &str) -> Result<()> {
+        tracing::info!("Joining {} recorded segments...", self.paths.len());
+        if let Err(e) = encoder::concat_segments(&self.paths, output, recording_id) {
+            let kept = self.dir.keep();
+            return Err(e.context(format!("Segments are kept in {}", kept.display())));
+        }
+        Ok(())
+    }
+}
+
+/// Count a request to pause or resume. Only an atomic add, so it's safe
+/// to call from a signal handler.
+pub fn request() {
+    REQUESTS.fetch_add(1, Ordering::SeqCst);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_recorded_time() {
+        // Started 10s ago, paused from 2s to 5s, and again since 8s. Built
+        // directly, as `new` would drop requests other tests are making.
+        let mut control = PauseControl {
+            start: Instant::now() - Duration::from_secs(10),
+            enabled: true,
+            warned: false,
+            paused_at: Some(8.0),
+            spans: vec![(2.0, 5.0)],
+        };
+        let recorded = control.recorded_time();
+        assert!((recorded - 5.0).abs() < 0.1, "{}", recorded);
+
+        // The open pause doesn't count as it goes on
+        std::thread::sleep(Duration::from_millis(50));
+        assert!((control.recorded_time() - recorded).abs() < 1e-3);
+
+        control.paused_at = None;
+        let recorded = control.recorded_time();
+        assert!((recorded - 7.0).abs() < 0.1, "{}", recorded);
+    }
+
+    #[test]
+    fn test_pause_timeline() {
+        let timeline = PauseTimeline {
+            spans: vec![(2.0, 5.0), (8.0, 9.0)],
+        };
+        assert_eq!(timeline.output_time(1.0), Some(1.0));
+        assert_eq!(timeline.output_time(3.0), None);
+        assert_eq!(timeline.output_time(6.0), Some(3.0));
+        assert_eq!(timeline.output_time(10.0), Some(6.0));
+        assert_eq!(timeline.nearest_output_time(3.0), 2.0);
+        assert_eq!(timeline.nearest_output_time(8.5), 5.0);
+        assert_eq!(timeline.nearest_output_time(10.0), 6.0);
+        assert_eq!(timeline.total(), 4.0);
+        assert_eq!(
+            timeline.spans(),
+            vec![
+                PauseSpan {
+                    at: 2.0,
+                    duration: 3.0
+                },
+                PauseSpan {
+                    at: 5.0,
+                    duration: 1.0
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,1433 @@
+use crate::cancel::CancelToken;
+use crate::cursor_types::{ClickTarget, CursorEvent, EventType, KeyCapture};
+use crate::library::{self, format_bytes};
+#[cfg(target_os = "linux")]
+use crate::linux::{
+    cursor_size_multiplier, find_display, find_window, keyboard_info, list_displays, list_windows,
+    start_app_capture, start_display_capture, start_region_capture, start_window_capture,
+    CaptureConfig, CaptureSession, CapturedFrame, CursorTracker, DisplayInfo, HotkeyListener,
+    WindowInfo,
+};
+#[cfg(target_os = "macos")]
+use crate::macos::{
+    cursor_size_multiplier, find_display, find_window, keyboard_info, list_displays, list_windows,
+    start_app_capture, start_display_capture, start_region_capture, start_window_capture,
+    CaptureConfig, CaptureSession, CapturedFrame, CursorTracker, DisplayInfo, HotkeyListener,
+    WindowInfo,
+};
+use crate::recording::control::{self, ControlServer, State};
+use crate::recording::crypto::EncryptKey;
+use crate::recording::disk;
+use crate::recording::encoder::{
+    self, AudioInputs, Codec, Encoder, EncoderOutput, Quality, VideoEncoder,
+};
+use crate::recording::exclude::Exclusions;
+use crate::recording::hotkey::Hotkey;
+use crate::recording::integrity;
+use crate::recording::metadata::{Marker, RecordingMetadata};
+use crate::recording::pause::{PauseControl, PauseSpan, Segments};
+use crate::recording::region::Region;
+use crate::recording::rollover;
+use crate::recording::stats::{CaptureStats, FrameCounter};
+use crate::recording::status::{RecordingStatus, StatusCallback};
+use crate::recording::window_tracker::{WindowBounds, WindowTracker};
+#[cfg(target_os = "windows")]
+use crate::windows::{
+    cursor_size_multiplier, find_display, find_window, keyboard_info, list_displays, list_windows,
+    start_app_capture, start_display_capture, start_region_capture, start_window_capture,
+    CaptureConfig, CaptureSession, CapturedFrame, CursorTracker, DisplayInfo, HotkeyListener,
+    WindowInfo,
+};
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+/// Options shared by display and window recordings
+pub struct RecordOptions {
+    /// Output file path (None when only streaming)
+    pub output: Option<PathBuf>,
+    /// Capture system cursor in video
+    pub capture_system_cursor: bool,
+    /// RTMP endpoint to push the recording to live
+    pub stream_url: Option<String>,
+    /// Background color for the live stream canvas
+    pub stream_background: Option<String>,
+    /// Encrypt the video and metadata as they're written
+    pub encrypt: Option<EncryptKey>,
+    /// Microphone device to record audio from
+    pub mic: Option<String>,
+    /// Record system audio (macOS)
+    pub system_audio: bool,
+    /// Global key combo that stops the recording
+    pub hotkey: Option<Hotkey>,
+    /// Wait for the hotkey before starting
+    pub wait_for_hotkey: bool,
+    /// Global key combo that drops a marker
+    pub mark_hotkey: Option<Hotkey>,
+    /// Wait for a start request on the control socket before starting
+    pub wait_for_start: bool,
+    /// How much of the keyboard to record
+    pub keys: KeyCapture,
+    /// Stop after this much recorded time
+    pub duration: Option<Duration>,
+    /// Video codec for the recorded file
+    pub codec: Codec,
+    /// Compression of the recorded file
+    pub quality: Quality,
+    /// Encoder for the recorded file, from `encoder::select_encoder`
+    pub encoder: Encoder,
+    /// Stops the recording when cancelled
+    pub stop: Option<CancelToken>,
+    /// Answer `glide stop`, `glide status` and the rest of the control API
+    pub control_socket: bool,
+    /// Windows to keep out of display and region recordings
+    pub exclude: Exclusions,
+    /// Called with the live status a few times a second
+    pub status: Option<StatusCallback>,
+    /// Start a new file after this much recorded time
+    pub segment_duration: Option<Duration>,
+}
+
+impl RecordOptions {
+    /// Pausing splits the recording into segments joined afterwards,
+    /// which needs a plain file
+    fn can_pause(&self) -> bool {
+        self.stream_url.is_none() && self.encrypt.is_none()
+    }
+
+    /// Where to encode to: the stream, or `path`
+    fn encoder_output(&self, path: Option<PathBuf>, recording_id: &str) -> Result<EncoderOutput> {
+        if let Some(url) = &self.stream_url {
+            return Ok(EncoderOutput::Rtmp {
+                url: url.clone(),
+                background: self.stream_background.clone(),
+            });
+        }
+        let path = path.context("Must specify an output file or a stream URL")?;
+        Ok(EncoderOutput::File {
+            path,
+            recording_id: recording_id.to_string(),
+            encrypt: self.encrypt.clone(),
+            codec: self.codec,
+            quality: self.quality,
+            encoder: self.encoder,
+        })
+    }
+
+    /// How to stop (and pause) the recording
+    fn controls_hint(&self) -> String {
+        let mut stop = match &self.hotkey {
+            Some(hotkey) => format!("Press Ctrl+C or {} to stop recording", hotkey),
+            None => "Press Ctrl+C to stop recording".to_string(),
+        };
+        if let Some(duration) = self.duration {
+            stop += &format!(" (stops by itself after {}s)", duration.as_secs());
+        }
+        if !self.can_pause() {
+            format!("{}...\n", stop)
+        } else if cfg!(unix) {
+            format!(
+                "{}, or type p and Enter (or send SIGUSR1) to pause/resume...\n",
+                stop
+            )
+        } else {
+            format!("{}, or type p and Enter to pause/resume...\n", stop)
+        }
+    }
+
+    fn destination(&self) -> String {
+        match (&self.stream_url, &self.output) {
+            (Some(url), _) => url.clone(),
+            (None, Some(path)) => path.display().to_string(),
+            (None, None) => String::new(),
+        }
+    }
+}
+
+/// Result of a finished capture loop
+struct CaptureResult {
+    /// One per capture session, in the order they were given
+    tracks: Vec<TrackResult>,
+    cursor_events: Vec<CursorEvent>,
+    cursor_duration: f64,
+    click_targets: Vec<ClickTarget>,
+    pauses: Vec<PauseSpan>,
+    /// Bounds of the followed window over time, see `run_capture`
+    window_bounds: Vec<WindowBounds>,
+    markers: Vec<Marker>,
+}
+
+/// A finished capture session and where it was encoded to
+struct TrackResult {
+    width: u32,
+    height: u32,
+    /// One file, or one per segment with `--segment-duration`
+    files: Vec<TrackFile>,
+}
+
+/// A file a capture session was encoded to
+struct TrackFile {
+    /// None when streaming
+    output: Option<PathBuf>,
+    recording_id: String,
+    stats: CaptureStats,
+    /// Output time the file starts at
+    start: f64,
+    /// Output time the next file starts at, None for the last one
+    end: Option<f64>,
+}
+
+pub fn record_display(display: &DisplayInfo, options: &RecordOptions) -> Result<()> {
+    // Check FFmpeg availability (still needed for encoding)
+    encoder::check_ffmpeg()?;
+
+    let stop = stop_signal(options);
+    let (running, control) = (&stop.running, &stop.control);
+
+    tracing::info!("Recording screen to {}", options.destination());
+    tracing::info!("{}", options.controls_hint());
+    start_hotkey(options, running)?;
+    wait_for_start(options, running, control)?;
+
+    // Find the display
+    let sc_display = find_display(display.index).context("Failed to find display")?;
+
+    // Get the display frame for dimensions
+    let frame = sc_display.frame();
+    let width = (frame.width * display.scale_factor) as u32;
+    let height = (frame.height * display.scale_factor) as u32;
+
+    // Configure capture
+    let config = CaptureConfig {
+        show_cursor: options.capture_system_cursor,
+        width,
+        height,
+        capture_audio: options.system_audio,
+        exclude: options.exclude.clone(),
+    };
+
+    // Start screen capture
+    let capture_session =
+        start_display_capture(&sc_display, &config).context("Failed to start screen capture")?;
+
+    let start = Instant::now();
+    let result = run_capture(
+        vec![(capture_session, options.output.clone())],
+        running,
+        None,
+        options,
+    )?;
+
+    let track = &result.tracks[0];
+    let metadata = RecordingMetadata::new_display(
+        display.index,
+        track.width,
+        track.height,
+        display.scale_factor,
+    );
+    let duration = print_duration(&result, start);
+    save_recording(metadata, track, &result, None, duration, options)
+}
+
+/// Record several displays at once, each into its own file named after
+/// `options.output` (recording-display0.mp4, ...), sharing one cursor track
+pub fn record_displays(displays: &[DisplayInfo], options: &RecordOptions) -> Result<()> {
+    encoder::check_ffmpeg()?;
+
+    let output = options
+        .output
+        .as_deref()
+        .context("Recording several displays needs an output file")?;
+    let outputs: Vec<PathBuf> = displays
+        .iter()
+        .map(|display| display_output_path(output, display.index))
+        .collect();
+
+    let stop = stop_signal(options);
+    let (running, control) = (&stop.running, &stop.control);
+
+    for (info, path) in displays.iter().zip(&outputs) {
+        tracing::info!("Recording display {} to {}", info.index, path.display());
+    }
+    tracing::info!("{}", options.controls_hint());
+    start_hotkey(options, running)?;
+    wait_for_start(options, running, control)?;
+
+    let mut sessions = Vec::new();
+    for (i, (display, path)) in displays.iter().zip(&outputs).enumerate() {
+        let sc_display = find_display(display.index)
+            .with_context(|| format!("Failed to find display {}", display.index))?;
+        let frame = sc_display.frame();
+        let config = CaptureConfig {
+            show_cursor: options.capture_system_cursor,
+            width: (frame.width * display.scale_factor) as u32,
+            height: (frame.height * display.scale_factor) as u32,
+            // Audio goes into the first display's file only
+            capture_audio: options.system_audio && i == 0,
+            exclude: options.exclude.clone(),
+        };
+        let session = start_display_capture(&sc_display, &config).with_context(|| {
+            format!(
+                "Failed to start screen capture of display {}",
+                display.index
+            )
+        })?;
+        sessions.push((session, Some(path.clone())));
+    }
+
+    let start = Instant::now();
+    let mut result = run_capture(sessions, running, None, options)?;
+    tag_displays(&mut result.cursor_events, displays);
+
+    let duration = print_duration(&result, start);
+    for (display, track) in displays.iter().zip(&result.tracks) {
+        let mut metadata = RecordingMetadata::new_display(
+            display.index,
+            track.width,
+            track.height,
+            display.scale_factor,
+        );
+        // Cursor positions are global, so each file needs its display's origin
+        metadata.window_offset = (display.x, display.y);
+        save_recording(
+            metadata,
+            track,
+            &result,
+            Some(display.index),
+            duration,
+            options,
+        )?;
+    }
+    Ok(())
+}
+
+/// "recording.mp4" -> "recording-display1.mp4"
+fn display_output_path(output: &Path, display_index: usize) -> PathBuf {
+    let stem = output.file_stem().unwrap_or_default().to_string_lossy();
+    let name = match output.extension() {
+        Some(ext) => format!(
+            "{}-display{}.{}",
+            stem,
+            display_index,
+            ext.to_string_lossy()
+        ),
+        None => format!("{}-display{}", stem, display_index),
+    };
+    output.with_file_name(name)
+}
+
+/// Tag each event with the display the cursor was on
+fn tag_displays(events: &mut [CursorEvent], displays: &[DisplayInfo]) {
+    for event in events {
+        event.display = displays
+            .iter()
+            .find(|d| {
+                event.x >= d.x as f64
+                    && event.y >= d.y as f64
+                    && event.x < d.x as f64 + d.width as f64
+                    && event.y < d.y as f64 + d.height as f64
+            })
+            .map(|d| d.index);
+    }
+}
+
+pub fn record_window(window: &WindowInfo, options: &RecordOptions) -> Result<()> {
+    encoder::check_ffmpeg()?;
+
+    let stop = stop_signal(options);
+    let (running, control) = (&stop.running, &stop.control);
+
+    tracing::info!(
+        "Recording window: {} - {} ({}x{})",
+        window.owner,
+        window.name,
+        window.bounds.2,
+        window.bounds.3
+    );
+    tracing::info!("{}", options.controls_hint());
+    start_hotkey(options, running)?;
+    wait_for_start(options, running, control)?;
+
+    // Find the window
+    let sc_window = find_window(window.id).context("Failed to find window")?;
+
+    // Get the display scale factor for dimensions
+    let displays = list_displays()?;
+    let display = displays.into_iter().find(|d| d.is_main).unwrap();
+
+    // Get window frame for dimensions
+    let frame = sc_window.frame();
+    let width = (frame.width * display.scale_factor) as u32;
+    let height = (frame.height * display.scale_factor) as u32;
+
+    // Configure capture
+    let config = CaptureConfig {
+        show_cursor: options.capture_system_cursor,
+        width,
+        height,
+        capture_audio: options.system_audio,
+        exclude: options.exclude.clone(),
+    };
+
+    // Start window capture
+    let capture_session =
+        start_window_capture(&sc_window, &config).context("Failed to start window capture")?;
+
+    // Only ScreenCaptureKit follows a window around; elsewhere the captured
+    // region stays put, so the original offset remains the right one
+    let follow_window = cfg!(target_os = "macos").then_some(window.id);
+
+    let start = Instant::now();
+    let result = run_capture(
+        vec![(capture_session, options.output.clone())],
+        running,
+        follow_window,
+        options,
+    )?;
+
+    let track = &result.tracks[0];
+    let mut metadata = RecordingMetadata::new_window(
+        window.id,
+        track.width,
+        track.height,
+        window.bounds.0, // x offset
+        window.bounds.1, // y offset
+        display.scale_factor,
+    );
+    metadata.window_bounds = result.window_bounds.clone();
+    let duration = print_duration(&result, start);
+    save_recording(metadata, track, &result, None, duration, options)
+}
+
+pub fn record_region(region: &Region, options: &RecordOptions) -> Result<()> {
+    encoder::check_ffmpeg()?;
+
+    // Capture works per display, so the region must fit on one
+    let display = list_displays()?
+        .into_iter()
+        .find(|d| region.is_within(d.x, d.y, d.width, d.height))
+        .with_context(|| {
+            format!(
+                "Region {} isn't within a single display (see `glide list displays`)",
+                region
+            )
+        })?;
+
+    let stop = stop_signal(options);
+    let (running, control) = (&stop.running, &stop.control);
+
+    let index = display.index;
+    tracing::info!("Recording region {} of display {}", region, index);
+    tracing::info!("Recording screen to {}", options.destination());
+    tracing::info!("{}", options.controls_hint());
+    start_hotkey(options, running)?;
+    wait_for_start(options, running, control)?;
+
+    let sc_display = find_display(display.index).context("Failed to find display")?;
+
+    // Encoders need even dimensions
+    let width = (region.width as f64 * display.scale_factor) as u32 & !1;
+    let height = (region.height as f64 * display.scale_factor) as u32 & !1;
+    let config = CaptureConfig {
+        show_cursor: options.capture_system_cursor,
+        width,
+        height,
+        capture_audio: options.system_audio,
+        exclude: options.exclude.clone(),
+    };
+
+    let relative = (
+        region.x - display.x,
+        region.y - display.y,
+        region.width,
+        region.height,
+    );
+    let capture_session = start_region_capture(&sc_display, relative, &config)
+        .context("Failed to start region capture")?;
+
+    let start = Instant::now();
+    let result = run_capture(
+        vec![(capture_session, options.output.clone())],
+        running,
+        None,
+        options,
+    )?;
+
+    let track = &result.tracks[0];
+    let metadata = RecordingMetadata::new_region(
+        display.index,
+        track.width,
+        track.height,
+        region.x,
+        region.y,
+        display.scale_factor,
+    );
+    let duration = print_duration(&result, start);
+    save_recording(metadata, track, &result, None, duration, options)
+}
+
+/// Record every window of the app named `app` on the display showing most
+/// of them, with the rest of the screen left out. Windows the app opens or
+/// closes while recording come and go with it.
+pub fn record_app(app: &str, options: &RecordOptions) -> Result<()> {
+    encoder::check_ffmpeg()?;
+
+    let windows: Vec<WindowInfo> = list_windows()?
+        .into_iter()
+        .filter(|w| w.owner.eq_ignore_ascii_case(app))
+        .collect();
+    if windows.is_empty() {
+        anyhow::bail!("{} has no open windows (see `glide list windows`)", app);
+    }
+    let mut displays = list_displays()?;
+    let position = app_display(&windows, &displays)
+        .or_else(|| displays.iter().position(|d| d.is_main))
+        .context("No display to record on")?;
+    let display = displays.swap_remove(position);
+
+    let stop = stop_signal(options);
+    let (running, control) = (&stop.running, &stop.control);
+
+    let index = display.index;
+    tracing::info!(
+        "Recording {} ({} windows) on display {}",
+        app,
+        windows.len(),
+        index
+    );
+    tracing::info!("Recording screen to {}", options.destination());
+    tracing::info!("{}", options.controls_hint());
+    start_hotkey(options, running)?;
+    wait_for_start(options, running, control)?;
+
+    let sc_display = find_display(display.index).context("Failed to find display")?;
+    let frame = sc_display.frame();
+    let config = CaptureConfig {
+        show_cursor: options.capture_system_cursor,
+        width: (frame.width * display.scale_factor) as u32,
+        height: (frame.height * display.scale_factor) as u32,
+        capture_audio: options.system_audio,
+        exclude: options.exclude.clone(),
+    };
+    let capture_session =
+        start_app_capture(&sc_display, app, &config).context("Failed to start app capture")?;
+
+    let start = Instant::now();
+    let result = run_capture(
+        vec![(capture_session, options.output.clone())],
+        running,
+        None,
+        options,
+    )?;
+
+    let track = &result.tracks[0];
+    let mut metadata = RecordingMetadata::new_display(
+        display.index,
+        track.width,
+        track.height,
+        display.scale_factor,
+    );
+    // Cursor positions are global, so the file needs its display's origin
+    metadata.window_offset = (display.x, display.y);
+    let duration = print_duration(&result, start);
+    save_recording(metadata, track, &result, None, duration, options)
+}
+
+/// Position in `displays` of the one covering the most of `windows`' area
+fn app_display(windows: &[WindowInfo], displays: &[DisplayInfo]) -> Option<usize> {
+    let overlap = |d: &DisplayInfo| -> i64 {
+        windows
+            .iter()
+            .map(|w| {
+                let (x, y, width, height) = w.bounds;
+                let left = x.max(d.x) as i64;
+                let top = y.max(d.y) as i64;
+                let right = (x + width as i32).min(d.x + d.width as i32) as i64;
+                let bottom = (y + height as i32).min(d.y + d.height as i32) as i64;
+                (right - left).max(0) * (bottom - top).max(0)
+            })
+            .sum()
+    };
+    displays
+        .iter()
+        .map(overlap)
+        .enumerate()
+        .filter(|&(_, area)| area > 0)
+        .max_by_key(|&(_, area)| area)
+        .map(|(i, _)| i)
+}
+
+/// Capture the main display for `duration` without encoding, returning its
+/// index and the frames per second delivered
+pub fn measure_capture(duration: Duration) -> Result<(usize, f64)> {
+    let display = list_displays()?
+        .into_iter()
+        .find(|d| d.is_main)
+        .context("No main display")?;
+    let sc_display = find_display(display.index).context("Failed to find display")?;
+    let frame = sc_display.frame();
+    let config = CaptureConfig {
+        show_cursor: false,
+        width: (frame.width * display.scale_factor) as u32,
+        height: (frame.height * display.scale_factor) as u32,
+        capture_audio: false,
+        exclude: Exclusions::default(),
+    };
+    let mut session =
+        start_display_capture(&sc_display, &config).context("Failed to start screen capture")?;
+
+    // Timed from the first frame, so startup doesn't count
+    let waiting = Instant::now();
+    let first = loop {
+        if let Some(frame) = session.try_recv() {
+            break frame.timestamp;
+        }
+        if waiting.elapsed() > Duration::from_secs(5) {
+            session.stop()?;
+            anyhow::bail!("No frames captured within 5s");
+        }
+        std::thread::sleep(Duration::from_millis(1));
+    };
+    let started = Instant::now();
+    let (mut frames, mut last) = (0u64, first);
+    while started.elapsed() < duration {
+        match session.try_recv() {
+            Some(frame) => {
+                frames += 1;
+                last = frame.timestamp;
+            }
+            None => std::thread::sleep(Duration::from_millis(1)),
+        }
+    }
+    session.stop()?;
+    Ok((
+        display.index,
+        frames as f64 / (last - first).max(f64::EPSILON),
+    ))
+}
+
+/// What a [`Recorder`] captures
+pub enum RecordTarget {
+    Display(DisplayInfo),
+    /// Several displays at once, each into its own file
+    Displays(Vec<DisplayInfo>),
+    Window(WindowInfo),
+    Region(Region),
+    /// Every window of the app with this name
+    App(String),
+}
+
+/// Records a target with its cursor track until stopped, for apps that
+/// embed glide
+pub struct Recorder {
+    target: RecordTarget,
+    options: RecordOptions,
+}
+
+impl Recorder {
+    pub fn new(target: RecordTarget, mut options: RecordOptions) -> Self {
+        options.stop.get_or_insert_with(CancelToken::new);
+        Self { target, options }
+    }
+
+    /// A token that stops the recording when cancelled
+    pub fn stop_token(&self) -> CancelToken {
+        self.options.stop.clone().expect("set in Recorder::new")
+    }
+
+    /// Record until stopped (or `duration` is up), then save the video
+    /// and its metadata
+    pub fn record(&self) -> Result<()> {
+        match &self.target {
+            RecordTarget::Display(display) => record_display(display, &self.options),
+            RecordTarget::Displays(displays) => record_displays(displays, &self.options),
+            RecordTarget::Window(window) => record_window(window, &self.options),
+            RecordTarget::Region(region) => record_region(region, &self.options),
+            RecordTarget::App(app) => record_app(app, &self.options),
+        }
+    }
+}
+
+/// What keeps the recording going until it's told to stop
+struct StopSignal {
+    /// Cleared by `options.stop` or a stop request on the control socket
+    running: Arc<AtomicBool>,
+    control: Option<ControlServer>,
+    /// Watches `options.stop`
+    watcher: Option<JoinHandle<()>>,
+}
+
+impl Drop for StopSignal {
+    /// Stop the watcher however the recording ended, errors included
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::SeqCst);
+        if let Some(watcher) = self.watcher.take() {
+            let _ = watcher.join();
+        }
+    }
+}
+
+/// Start watching for the ways the recording can be stopped
+fn stop_signal(options: &RecordOptions) -> StopSignal {
+    StopSignal::new(options.stop.clone(), options.control_socket)
+}
+
+impl StopSignal {
+    fn new(stop: Option<CancelToken>, control_socket: bool) -> Self {
+        let running = Arc::new(AtomicBool::new(true));
+        let watcher = stop.map(|stop| {
+            let running = Arc::clone(&running);
+            std::thread::spawn(move || {
+                while running.load(Ordering::SeqCst) {
+                    if stop.is_cancelled() {
+                        running.store(false, Ordering::SeqCst);
+                    }
+                    std::thread::sleep(Duration::from_millis(20));
+                }
+            })
+        });
+        let control = if control_socket {
+            control_server(&running)
+        } else {
+            None
+        };
+        Self {
+            running,
+            control,
+            watcher,
+        }
+    }
+}
+
+/// Take requests from `glide stop` and `glide status`, where possible
+fn control_server(running: &Arc<AtomicBool>) -> Option<ControlServer> {
+    match ControlServer::start(&control::socket_path(), Arc::clone(running)) {
+        Ok(Some(server)) => Some(server),
+        Ok(None) => {
+            tracing::warn!("another recording is running, `glide stop` will stop that one");
+            None
+        }
+        Err(e) => {
+            tracing::debug!("not listening for `glide stop`: {:#}", e);
+            None
+        }
+    }
+}
+
+/// With `wait_for_start`, block until the control socket gets a start request
+fn wait_for_start(
+    options: &RecordOptions,
+    running: &AtomicBool,
+    control: &Option<ControlServer>,
+) -> Result<()> {
+    if !options.wait_for_start {
+        return Ok(());
+    }
+    if control.is_none() {
+        anyhow::bail!("--wait-for-start needs the control socket, which isn't available");
+    }
+    tracing::info!("Waiting for `glide start` to start recording...");
+    control::wait_for_start(running)?;
+    tracing::info!("Recording started");
+    Ok(())
+}
+
+/// Listen for the hotkeys: the stop hotkey, if there is one, clears
+/// `running` when it's pressed, and with `wait_for_hotkey` this blocks until
+/// its first press. The mark hotkey drops a marker.
+fn start_hotkey(options: &RecordOptions, running: &Arc<AtomicBool>) -> Result<()> {
+    if let Some(hotkey) = &options.mark_hotkey {
+        let listener = HotkeyListener::start(hotkey)?;
+        let running = Arc::clone(running);
+        std::thread::spawn(move || {
+            while running.load(Ordering::SeqCst) {
+                if listener.take_press() {
+                    if let Some(count) = control::mark(None) {
+                        tracing::info!("Marker {} dropped", count);
+                    }
+                }
+                std::thread::sleep(Duration::from_millis(20));
+            }
+        });
+    }
+
+    let Some(hotkey) = &options.hotkey else {
+        return Ok(());
+    };
+    let listener = HotkeyListener::start(hotkey)?;
+
+    if options.wait_for_hotkey {
+        tracing::info!("Waiting for {} to start recording...", hotkey);
+        while !listener.take_press() {
+            if !running.load(Ordering::SeqCst) {
+                anyhow::bail!("Recording cancelled before it started");
+            }
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        tracing::info!("Recording started");
+    }
+
+    // The listener lives until the recording stops
+    let running = Arc::clone(running);
+    std::thread::spawn(move || {
+        while running.load(Ordering::SeqCst) {
+            if listener.take_press() {
+                tracing::info!("Stopping...");
+                running.store(false, Ordering::SeqCst);
+            }
+            std::thread::sleep(Duration::from_millis(20));
+        }
+    });
+    Ok(())
+}
+
+/// One capture session and the encoder its frames go to
+struct Track {
+    session: CaptureSession,
+    output: EncoderOutput,
+    recording_id: String,
+    width: u32,
+    height: u32,
+    /// None while paused
+    encoder: Option<VideoEncoder>,
+    /// Set up at the first pause
+    segments: Option<Segments>,
+    /// Audio inputs held while paused, for the next segment
+    paused_audio: Option<AudioInputs>,
+    counter: FrameCounter,
+    /// The -o path segment files are named after, with `--segment-duration`
+    rollover_base: Option<PathBuf>,
+    /// Number of the file being written, from 1
+    file_number: usize,
+    /// Output time the current file started at
+    file_start: f64,
+    /// Frames the session had dropped when the current file started
+    dropped_before: u64,
+    /// Files closed at earlier rollovers
+    finished: Vec<TrackFile>,
+}
+
+/// ScreenCaptureKit only sends frames when the screen changes, so gaps
+/// there aren't stalls
+fn frame_counter() -> FrameCounter {
+    FrameCounter::new(!cfg!(target_os = "macos"))
+}
+
+impl Track {
+    /// Start encoding at the size of the session's first frame
+    fn start(
+        mut session: CaptureSession,
+        path: Option<PathBuf>,
+        (width, height): (u32, u32),
+        mic: Option<String>,
+        options: &RecordOptions,
+    ) -> Result<Self> {
+        let recording_id = integrity::new_recording_id();
+        let rollover_base = path.clone().filter(|_| options.segment_duration.is_some());
+        let path = match &rollover_base {
+            Some(base) => Some(rollover::segment_path(base, 1)),
+            None => path,
+        };
+        let output = options.encoder_output(path, &recording_id)?;
+        let audio = AudioInputs {
+            mic,
+            system: session.take_audio(),
+        };
+        let encoder = VideoEncoder::new(width, height, 60, &output, audio)
+            .context("Failed to start video encoder")?;
+
+        Ok(Self {
+            session,
+            output,
+            recording_id,
+            width,
+            height,
+            encoder: Some(encoder),
+            segments: None,
+            paused_audio: None,
+            counter: frame_counter(),
+            rollover_base,
+            file_number: 1,
+            file_start: 0.0,
+            dropped_before: 0,
+            finished: Vec::new(),
+        })
+    }
+
+    /// The file being written, None when streaming
+    fn path(&self) -> Option<&Path> {
+        match &self.output {
+            EncoderOutput::File { path, .. } => Some(path),
+            EncoderOutput::Rtmp { .. } => None,
+        }
+    }
+
+    /// Encode a frame, unless paused. Returns whether it was encoded.
+    fn write(&mut self, frame: &CapturedFrame) -> Result<bool> {
+        let Some(encoder) = self.encoder.as_mut() else {
+            return Ok(false);
+        };
+        encoder.write_frame(&frame.data, frame.timestamp)?;
+        self.counter
+            .frame(frame.timestamp, self.session.queued_frames());
+        Ok(true)
+    }
+
+    /// End the current segment; what's recorded so far is safe on disk
+    fn pause(&mut self) -> Result<()> {
+        self.counter.pause();
+        if let Some(encoder) = self.encoder.take() {
+            self.paused_audio = Some(
+                encoder
+                    .finish()
+                    .context("Failed to finish video encoding")?,
+            );
+        }
+        if self.segments.is_none() {
+            if let EncoderOutput::File { path, .. } = &self.output {
+                self.segments = Some(Segments::start(path)?);
+            }
+        }
+        Ok(())
+    }
+
+    /// Start encoding the next segment
+    fn resume(&mut self) -> Result<()> {
+        if let Some(segments) = self.segments.as_mut() {
+            // Segments are joined without re-encoding, so they keep the codec
+            let (codec, quality, encoder) = match self.output {
+                EncoderOutput::File {
+                    codec,
+                    quality,
+                    encoder,
+                    ..
+                } => (codec, quality, encoder),
+                EncoderOutput::Rtmp { .. } => Default::default(),
+            };
+            let output = EncoderOutput::File {
+                path: segments.next_path(),
+                recording_id: self.recording_id.clone(),
+                encrypt: None,
+                codec,
+                quality,
+                encoder,
+            };
+            self.encoder = Some(
+                VideoEncoder::new(
+                    self.width,
+                    self.height,
+                    60,
+                    &output,
+                    self.paused_audio.take().unwrap_or_default(),
+                )
+                .context("Failed to start video encoder")?,
+            );
+        }
+        Ok(())
+    }
+
+    /// Encode what's left, stop capturing and put the segments back
+    /// together. `recorded` is the time spent recording, without pauses.
+    fn finish(mut self, recorded: f64) -> Result<TrackResult> {
+        // Drain any remaining frames from the channel before stopping
+        while let Some(frame) = self.session.try_recv() {
+            self.write(&frame)?;
+        }
+
+        self.session.stop()?;
+
+        self.close_file()?;
+        self.file_done(recorded, true);
+
+        let stats = match self.finished.as_slice() {
+            [file] => file.stats.clone(),
+            files => CaptureStats::combine(
+                &files.iter().map(|f| f.stats.clone()).collect::<Vec<_>>(),
+                recorded,
+            ),
+        };
+        tracing::info!(
+            "Captured {} frames in {:.1}s ({:.1} fps average, {} dropped, {} late)",
+            stats.captured_frames,
+            recorded,
+            stats.average_fps,
+            stats.dropped_frames,
+            stats.late_frames
+        );
+        if let Some(warning) = stats.warning() {
+            tracing::warn!("{}", warning);
+        }
+
+        Ok(TrackResult {
+            width: self.width,
+            height: self.height,
+            files: self.finished,
+        })
+    }
+
+    /// Close the current file at output time `at` and carry on in the next
+    /// one, keeping the audio inputs
+    fn roll_over(&mut self, at: f64) -> Result<()> {
+        let Some(base) = self.rollover_base.clone() else {
+            return Ok(());
+        };
+        let audio = self.close_file()?;
+        self.file_done(at, false);
+
+        self.file_number += 1;
+        self.recording_id = integrity::new_recording_id();
+        if let EncoderOutput::File {
+            path, recording_id, ..
+        } = &mut self.output
+        {
+            *path = rollover::segment_path(&base, self.file_number);
+            *recording_id = self.recording_id.clone();
+        }
+        self.encoder = Some(
+            VideoEncoder::new(self.width, self.height, 60, &self.output, audio)
+                .context("Failed to start video encoder")?,
+        );
+        Ok(())
+    }
+
+    /// Finish encoding the current file, joining any pause segments into it
+    fn close_file(&mut self) -> Result<AudioInputs> {
+        let audio = match self.encoder.take() {
+            Some(encoder) => encoder
+                .finish()
+                .context("Failed to finish video encoding")?,
+            None => self.paused_audio.take().unwrap_or_default(),
+        };
+        if let Some(segments) = self.segments.take() {
+            let path = self.path().expect("only files are paused").to_path_buf();
+            segments.join(&path, &self.recording_id)?;
+        }
+        Ok(audio)
+    }
+
+    /// Note the closed file as ending at output time `end`
+    fn file_done(&mut self, end: f64, last: bool) {
+        let dropped = self.session.dropped_frames();
+        let counter = std::mem::replace(&mut self.counter, frame_counter());
+        self.finished.push(TrackFile {
+            output: self.path().map(Path::to_path_buf),
+            recording_id: self.recording_id.clone(),
+            stats: counter.finish(
+                dropped.saturating_sub(self.dropped_before),
+                end - self.file_start,
+            ),
+            start: self.file_start,
+            end: (!last).then_some(end),
+        });
+        self.file_start = end;
+        self.dropped_before = dropped;
+    }
+}
+
+/// Track the cursor and feed frames from each capture session to its own
+/// encoder until `running` is cleared
+fn run_capture(
+    sessions: Vec<(CaptureSession, Option<PathBuf>)>,
+    running: &AtomicBool,
+    follow_window: Option<u32>,
+    options: &RecordOptions,
+) -> Result<CaptureResult> {
+    // Start cursor tracking
+    let mut cursor_tracker = CursorTracker::new(options.keys);
+    cursor_tracker.start()?;
+    // Window bounds and markers are timestamped on the cursor clock, like the events
+    let tracking_start = Instant::now();
+    let window_tracker = follow_window.map(|id| WindowTracker::start(id, tracking_start));
+
+    // Live status, following the first file
+    let file = match options.stream_url {
+        Some(_) => None,
+        None => sessions.first().and_then(|(_, path)| path.clone()),
+    };
+    let mut status = RecordingStatus::new(file, options.status.clone());
+
+    let start = Instant::now();
+
+    // Wait for every session's first frame to get actual dimensions
+    let mut sessions: Vec<_> = sessions
+        .into_iter()
+        .map(|(session, path)| (session, path, None))
+        .collect();
+    let mut refusal = None;
+    while sessions.iter().any(|(_, _, first)| first.is_none()) {
+        if !running.load(Ordering::SeqCst) {
+            refusal = Some(anyhow::anyhow!("Recording cancelled before first frame"));
+            break;
+        }
+
+        for (session, _, first) in &mut sessions {
+            if first.is_none() {
+                *first = session.try_recv();
+            }
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    // With the frame sizes known, check the files will fit
+    let disk_path = sessions
+        .first()
+        .and_then(|(_, path, _)| path.clone())
+        .filter(|_| options.stream_url.is_none());
+    if let (None, Some(path)) = (&refusal, &disk_path) {
+        let rate = sessions
+            .iter()
+            .filter_map(|(_, _, first)| first.as_ref())
+            .map(|frame| {
+                disk::bytes_per_second(frame.width as u32, frame.height as u32, options.quality)
+            })
+            .sum();
+        refusal = disk::free_space(path)
+            .and_then(|free| disk::check_room(free, rate, options.duration))
+            .err();
+    }
+    if let Some(error) = refusal {
+        status.finish();
+        let _ = cursor_tracker.stop();
+        if let Some(window_tracker) = window_tracker {
+            window_tracker.stop();
+        }
+        for (session, _, _) in &mut sessions {
+            session.stop()?;
+        }
+        return Err(error);
+    }
+
+    // Start an encoder per session with actual dimensions; audio goes
+    // into the first file
+    let mut tracks = Vec::new();
+    for (i, (session, path, first)) in sessions.into_iter().enumerate() {
+        let frame = first.expect("waited for above");
+        let size = (frame.width as u32, frame.height as u32);
+        let mic = if i == 0 { options.mic.clone() } else { None };
+        let mut track = Track::start(session, path, size, mic, options)?;
+        track.write(&frame)?;
+        tracks.push(track);
+    }
+    status.frame();
+    if options.segment_duration.is_some() {
+        status.set_output(tracks[0].path().map(Path::to_path_buf));
+    }
+    control::update(|status| {
+        status.state = State::Recording;
+        status.output = tracks[0].path().map(Path::to_path_buf);
+        status.can_pause = options.can_pause();
+    });
+
+    // Segments can only be joined when they're plain files
+    let mut pause = PauseControl::new(start, options.can_pause());
+
+    // Main recording loop
+    let mut disk_checked = Instant::now();
+    let mut next_rollover = options.segment_duration.map(|every| every.as_secs_f64());
+    while running.load(Ordering::SeqCst) {
+        if let Some(path) = disk_path
+            .as_ref()
+            .filter(|_| disk_checked.elapsed() >= disk::CHECK_INTERVAL)
+        {
+            disk_checked = Instant::now();
+            if let Some(free) = disk::free_space(path)
+                .ok()
+                .filter(|&free| disk::is_low(free))
+            {
+                tracing::warn!(
+                    "only {} of disk space left, stopping to save the recording",
+                    format_bytes(free)
+                );
+                running.store(false, Ordering::SeqCst);
+                break;
+            }
+        }
+        if let Some(duration) = options.duration {
+            if pause.recorded_time() >= duration.as_secs_f64() {
+                tracing::info!("Reached --duration, stopping...");
+                running.store(false, Ordering::SeqCst);
+                break;
+            }
+        }
+        if let (Some(at), Some(every)) = (next_rollover, options.segment_duration) {
+            let recorded = pause.recorded_time();
+            // Not while paused, so every file starts with a frame
+            if recorded >= at && tracks.iter().all(|t| t.encoder.is_some()) {
+                for track in &mut tracks {
+                    track.roll_over(recorded)?;
+                }
+                next_rollover = Some(at + every.as_secs_f64());
+                let path = tracks[0].path().map(Path::to_path_buf);
+                if let Some(path) = &path {
+                    tracing::info!("Continuing in {}", path.display());
+                }
+                control::update(|status| status.output = path.clone());
+                status.set_output(path);
+            }
+        }
+
+        match pause.poll() {
+            Some(true) => {
+                for track in &mut tracks {
+                    track.pause()?;
+                }
+                status.set_paused(true);
+                control::update(|status| status.state = State::Paused);
+            }
+            Some(false) => {
+                for track in &mut tracks {
+                    track.resume()?;
+                }
+                status.set_paused(false);
+                control::update(|status| status.state = State::Recording);
+            }
+            None => {}
+        }
+
+        if status.due() {
+            control::update(|status| {
+                status.recorded = pause.recorded_time();
+                status.frames = tracks[0].counter.frames();
+            });
+            status.refresh(
+                tracks[0].counter.frames(),
+                tracks.iter().map(|t| t.session.dropped_frames()).sum(),
+                tracks.iter().map(|t| t.session.queued_frames()).sum(),
+                cursor_tracker.event_count(),
+            );
+        }
+
+        // Take a frame from each session; while paused they're dropped
+        let mut received = false;
+        for (i, track) in tracks.iter_mut().enumerate() {
+            if let Some(frame) = track.session.try_recv() {
+                received = true;
+                // The frame rate shown is the first session's
+                if track.write(&frame)? && i == 0 {
+                    status.frame();
+                }
+            }
+        }
+        if !received {
+            std::thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    status.finish();
+    control::update(|status| status.state = State::Saving);
+
+    // Stop cursor tracking and get events + duration
+    let (cursor_events, cursor_duration) = cursor_tracker.stop();
+    let click_targets = cursor_tracker.click_targets();
+    let window_bounds = window_tracker.map(WindowTracker::stop).unwrap_or_default();
+    let markers = control::take_markers();
+
+    let pauses = pause.finish();
+    let recorded = (start.elapsed().as_secs_f64() - pauses.total()).max(0.0);
+    let tracks = tracks
+        .into_iter()
+        .map(|track| track.finish(recorded))
+        .collect::<Result<Vec<_>>>()?;
+
+    // Cut the pauses out of the cursor timeline too
+    let cursor_events = cursor_events
+        .into_iter()
+        .filter_map(|event| {
+            let timestamp = pauses.output_time(event.timestamp)?;
+            Some(CursorEvent { timestamp, ..event })
+        })
+        .collect();
+    let click_targets = click_targets
+        .into_iter()
+        .filter_map(|target| {
+            let timestamp = pauses.output_time(target.timestamp)?;
+            Some(ClickTarget {
+                timestamp,
+                ..target
+            })
+        })
+        .collect();
+    let window_bounds = window_bounds
+        .into_iter()
+        .filter_map(|bounds| {
+            let timestamp = pauses.output_time(bounds.timestamp)?;
+            Some(WindowBounds {
+                timestamp,
+                ..bounds
+            })
+        })
+        .collect();
+    // Markers dropped while paused go where the pause was cut out
+    let markers = markers
+        .into_iter()
+        .map(|(at, name)| Marker {
+            timestamp: pauses
+                .nearest_output_time(at.saturating_duration_since(tracking_start).as_secs_f64()),
+            name,
+        })
+        .collect();
+
+    Ok(CaptureResult {
+        tracks,
+        cursor_events,
+        cursor_duration: cursor_duration - pauses.total(),
+        click_targets,
+        pauses: pauses.spans(),
+        window_bounds,
+        markers,
+    })
+}
+
+/// Print how long the recording was, returning its duration in seconds
+/// without the pauses
+fn print_duration(result: &CaptureResult, start: Instant) -> f64 {
+    // Time spent paused isn't part of the recording
+    let paused: f64 = result.pauses.iter().map(|p| p.duration).sum();
+    let duration = start
+        .elapsed()
+        .saturating_sub(Duration::from_secs_f64(paused));
+    tracing::info!(
+        "\nRecording complete! Duration: {:.1}s",
+        duration.as_secs_f64()
+    );
+    if !result.pauses.is_empty() {
+        tracing::info!(
+            "Paused {} time(s), {:.1}s cut out",
+            result.pauses.len(),
+            paused
+        );
+    }
+    duration.as_secs_f64()
+}
+
+/// Save metadata next to each recorded file and print a summary. In
+/// multi-display recordings `display` is the one this track shows.
+fn save_recording(
+    metadata: RecordingMetadata,
+    track: &TrackResult,
+    result: &CaptureResult,
+    display: Option<usize>,
+    duration: f64,
+    options: &RecordOptions,
+) -> Result<()> {
+    for (i, file) in track.files.iter().enumerate() {
+        // Live streams have no file to process later, so there is nothing to attach metadata to
+        let Some(output) = file.output.as_deref() else {
+            tracing::info!("Streamed to: {}", options.destination());
+            continue;
+        };
+
+        // Each segment's timeline starts at zero; the first keeps anything
+        // from before its first frame
+        let shift = file.start;
+        let from = if i == 0 { f64::NEG_INFINITY } else { shift };
+        let in_file = |t: f64| rollover::in_segment(t, from, file.end);
+
+        let mut metadata = metadata.clone();
+        // Moves elsewhere fall outside this display's frame, but clicks there
+        // would zoom to points that aren't in it
+        metadata.cursor_events = result
+            .cursor_events
+            .iter()
+            .filter(|event| {
+                display.is_none()
+                    || event.display == display
+                    || !matches!(
+                        event.event_type,
+                        EventType::LeftClick | EventType::RightClick
+                    )
+            })
+            .filter(|event| in_file(event.timestamp))
+            .map(|event| CursorEvent {
+                timestamp: event.timestamp - shift,
+                ..event.clone()
+            })
+            .collect();
+        metadata.cursor_tracking_duration = file.end.unwrap_or(result.cursor_duration) - shift;
+        metadata.click_targets = result
+            .click_targets
+            .iter()
+            .filter(|target| in_file(target.timestamp))
+            .map(|target| ClickTarget {
+                timestamp: target.timestamp - shift,
+                ..*target
+            })
+            .collect();
+        metadata.pauses = result
+            .pauses
+            .iter()
+            .filter(|pause| in_file(pause.at))
+            .map(|pause| PauseSpan {
+                at: pause.at - shift,
+                ..*pause
+            })
+            .collect();
+        metadata.markers = result
+            .markers
+            .iter()
+            .filter(|marker| in_file(marker.timestamp))
+            .map(|marker| Marker {
+                timestamp: marker.timestamp - shift,
+                ..marker.clone()
+            })
+            .collect();
+        metadata.window_bounds =
+            rollover::segment_window_bounds(&metadata.window_bounds, shift, file.end);
+        metadata.capture_stats = Some(file.stats.clone());
+        metadata.os_cursor_scale = cursor_size_multiplier();
+        metadata.keyboard = keyboard_info();
+        metadata.recording_id = Some(file.recording_id.clone());
+        metadata.video_sha256 = Some(integrity::hash_file(output)?);
+        match options.encrypt {
+            Some(ref key) => metadata.save_encrypted(output, key)?,
+            None => metadata.save(output)?,
+        }
+        let file_duration = file.end.unwrap_or(duration) - shift;
+        library::update(|library| library.add_recording(output, file_duration));
+
+        tracing::info!("Saved to: {}", output.display());
+        tracing::info!(
+            "Metadata: {} ({} cursor events)",
+            output.with_extension("json").display(),
+            metadata.cursor_events.len()
+        );
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stop_signal() {
+        let stop = CancelToken::new();
+        let signal = StopSignal::new(Some(stop.clone()), false);
+        stop.cancel();
+        while signal.running.load(Ordering::SeqCst) {
+            std::thread::sleep(Duration::from_millis(5));
+        }
+
+        // Dropped without being stopped, as when setup fails: the watcher
+        // still ends
+        let signal = StopSignal::new(Some(CancelToken::new()), false);
+        let running = Arc::clone(&signal.running);
+        drop(signal);
+        assert!(!running.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_display_output_path() {
+        assert_eq!(
+            display_output_path(Path::new("out/recording.mp4"), 1),
+            PathBuf::from("out/recording-display1.mp4")
+        );
+        assert_eq!(
+            display_output_path(Path::new("recording"), 0),
+            PathBuf::from("recording-display0")
+        );
+    }
+}
@@ -0,0 +1,96 @@
+//! Screen rectangles for `glide record --region`
+
+use std::fmt;
+use std::str::FromStr;
+
+/// A screen rectangle in global screen coordinates (points on macOS,
+/// pixels elsewhere), the same space `glide list windows` reports bounds in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Region {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Region {
+    /// Whether the region lies entirely inside the rectangle at (x, y) of the given size
+    pub fn is_within(&self, x: i32, y: i32, width: u32, height: u32) -> bool {
+        self.x >= x
+            && self.y >= y
+            && self.x as i64 + self.width as i64 <= x as i64 + width as i64
+            && self.y as i64 + self.height as i64 <= y as i64 + height as i64
+    }
+}
+
+impl FromStr for Region {
+    type Err = String;
+
+    /// Parse "X,Y,WxH", e.g. "100,200,1280x720"
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            format!(
+                "invalid region '{}' (expected X,Y,WxH, e.g. 0,0,1280x720)",
+                s
+            )
+        };
+
+        let mut parts = s.split(',').map(str::trim);
+        let (Some(x), Some(y), Some(size), None) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return Err(invalid());
+        };
+        let (width, height) = size.split_once(['x', 'X']).ok_or_else(invalid)?;
+
+        let region = Region {
+            x: x.parse().map_err(|_| invalid())?,
+            y: y.parse().map_err(|_| invalid())?,
+            width: width.trim().parse().map_err(|_| invalid())?,
+            height: height.trim().parse().map_err(|_| invalid())?,
+        };
+        if region.width == 0 || region.height == 0 {
+            return Err(format!("region '{}' has no area", s));
+        }
+        Ok(region)
+    }
+}
+
+impl fmt::Display for Region {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{},{},{}x{}", self.x, self.y, self.width, self.height)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_region() {
+        let region: Region = "100,-20,1280x720".parse().unwrap();
+        assert_eq!(
+            region,
+            Region {
+                x: 100,
+                y: -20,
+                width: 1280,
+                height: 720
+            }
+        );
+        assert_eq!(region.to_string(), "100,-20,1280x720");
+
+        assert!("100,200".parse::<Region>().is_err());
+        assert!("0,0,1280".parse::<Region>().is_err());
+        assert!("0,0,0x720".parse::<Region>().is_err());
+        assert!("0,0,10x10,5".parse::<Region>().is_err());
+    }
+
+    #[test]
+    fn test_region_within() {
+        let region: Region = "100,100,200x200".parse().unwrap();
+        assert!(region.is_within(0, 0, 1920, 1080));
+        assert!(!region.is_within(0, 0, 250, 1080));
+        assert!(!region.is_within(1920, 0, 1920, 1080));
+    }
+}
@@ -0,0 +1,153 @@
+//! `glide record --segment-duration`: long recordings written as a series
+//! of files (out_001.mp4, out_002.mp4, ...) so a crash or a corrupt file
+//! costs one segment rather than hours of recording
+//!
+//! Each file gets its own metadata, with the cursor timeline cut to the
+//! time it covers and shifted to start at zero.
+
+use crate::recording::window_tracker::WindowBounds;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Shorter segments would spend more time starting encoders than recording
+const MIN_SEGMENT: Duration = Duration::from_secs(10);
+
+/// Parse a segment length: seconds, or a number with units such as `90s`,
+/// `10m` or `1h30m`
+pub fn parse_segment_duration(s: &str) -> Result<Duration, String> {
+    let invalid = || format!("expected a length like 90s, 10m or 1h30m, got {:?}", s);
+    let s = s.trim();
+    let seconds = match s.parse::<u64>() {
+        Ok(seconds) => seconds,
+        Err(_) => {
+            let mut seconds = 0;
+            let mut number = String::new();
+            for c in s.chars() {
+                if c.is_ascii_digit() {
+                    number.push(c);
+                    continue;
+                }
+                let unit = match c {
+                    'h' => 3600,
+                    'm' => 60,
+                    's' => 1,
+                    _ => return Err(invalid()),
+                };
+                let value: u64 = number.parse().map_err(|_| invalid())?;
+                seconds += value * unit;
+                number.clear();
+            }
+            if !number.is_empty() {
+                return Err(invalid());
+            }
+            seconds
+        }
+    };
+    let duration = Duration::from_secs(seconds);
+    if duration < MIN_SEGMENT {
+        return Err(format!(
+            "segments must be at least {}s long",
+            MIN_SEGMENT.as_secs()
+        ));
+    }
+    Ok(duration)
+}
+
+/// "recording.mp4", 2 -> "recording_002.mp4"
+pub fn segment_path(output: &Path, number: usize) -> PathBuf {
+    let stem = output.file_stem().unwrap_or_default().to_string_lossy();
+    let name = match output.extension() {
+        Some(ext) => format!("{}_{:03}.{}", stem, number, ext.to_string_lossy()),
+        None => format!("{}_{:03}", stem, number),
+    };
+    output.with_file_name(name)
+}
+
+/// Whether output time `t` falls in the segment from `start` to `end`
+/// (None for the last segment, which takes everything after its start)
+pub fn in_segment(t: f64, start: f64, end: Option<f64>) -> bool {
+    t >= start && end.is_none_or(|end| t < end)
+}
+
+/// The window bounds for a segment, shifted to its start. Bounds only
+/// change when the window moves, so the last ones before the segment
+/// starts carry over to its first frame.
+pub fn segment_window_bounds(
+    bounds: &[WindowBounds],
+    start: f64,
+    end: Option<f64>,
+) -> Vec<WindowBounds> {
+    let carried = bounds
+        .iter()
+        .rfind(|b| b.timestamp < start)
+        .map(|b| WindowBounds {
+            timestamp: 0.0,
+            ..*b
+        });
+    carried
+        .into_iter()
+        .chain(
+            bounds
+                .iter()
+                .filter(|b| in_segment(b.timestamp, start, end))
+                .map(|b| WindowBounds {
+                    timestamp: b.timestamp - start,
+                    ..*b
+                }),
+        )
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bounds(timestamp: f64, x: i32) -> WindowBounds {
+        WindowBounds {
+            timestamp,
+            x,
+            y: 0,
+            width: 800,
+            height: 600,
+        }
+    }
+
+    #[test]
+    fn test_parse_segment_duration() {
+        assert_eq!(parse_segment_duration("10m"), Ok(Duration::from_secs(600)));
+        assert_eq!(
+            parse_segment_duration("1h30m"),
+            Ok(Duration::from_secs(5400))
+        );
+        assert_eq!(parse_segment_duration("90"), Ok(Duration::from_secs(90)));
+        assert!(parse_segment_duration("5s").is_err());
+        assert!(parse_segment_duration("9").is_err());
+        assert!(parse_segment_duration("10x").is_err());
+        assert!(parse_segment_duration("m").is_err());
+        assert!(parse_segment_duration("1h30").is_err());
+    }
+
+    #[test]
+    fn test_segment_path() {
+        assert_eq!(
+            segment_path(Path::new("out/demo.mp4"), 1),
+            PathBuf::from("out/demo_001.mp4")
+        );
+        assert_eq!(
+            segment_path(Path::new("demo"), 12),
+            PathBuf::from("demo_012")
+        );
+    }
+
+    #[test]
+    fn test_segment_window_bounds() {
+        let all = [bounds(0.0, 0), bounds(250.0, 100), bounds(700.0, 200)];
+        // Nothing moved in the second segment: it starts where the window was
+        let second = segment_window_bounds(&all, 300.0, Some(600.0));
+        assert_eq!(second, [bounds(0.0, 100)]);
+        let last = segment_window_bounds(&all, 600.0, None);
+        assert_eq!(last, [bounds(0.0, 100), bounds(100.0, 200)]);
+        assert!(in_segment(599.9, 300.0, Some(600.0)));
+        assert!(!in_segment(600.0, 300.0, Some(600.0)));
+    }
+}
@@ -0,0 +1,83 @@
+//! `glide record --select`: drag out a rectangle or click a window on the
+//! screen itself, like macOS's ⇧⌘4
+//!
+//! The platform modules run the overlay and turn the drag into a
+//! `Selection` with `drag_region` and `window_at`.
+
+use crate::recording::region::Region;
+
+/// Drags shorter than this many pixels either way count as a click
+const CLICK_SLOP: i32 = 5;
+
+/// A screen position in pixels
+pub type Point = (i32, i32);
+
+/// A window's x, y, width and height, as `list windows` reports them
+pub type Bounds = (i32, i32, u32, u32);
+
+/// What was selected on screen
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Selection {
+    Region(Region),
+    /// A click on a window, by its `list windows` ID
+    Window(u32),
+}
+
+/// The rectangle dragged from `start` to `end` (either corner first), or
+/// None if the pointer barely moved and the drag was really a click
+pub fn drag_region(start: Point, end: Point) -> Option<Region> {
+    let width = (end.0 - start.0).unsigned_abs();
+    let height = (end.1 - start.1).unsigned_abs();
+    if width < CLICK_SLOP as u32 && height < CLICK_SLOP as u32 {
+        return None;
+    }
+    Some(Region {
+        x: start.0.min(end.0),
+        y: start.1.min(end.1),
+        // Encoders need at least a pixel each way
+        width: width.max(1),
+        height: height.max(1),
+    })
+}
+
+/// The window shown at `point`: of the `windows` (ID and bounds)
+/// containing it, the one highest in `stacking` (bottom to top)
+pub fn window_at(point: Point, windows: &[(u32, Bounds)], stacking: &[u32]) -> Option<u32> {
+    windows
+        .iter()
+        .filter(|(_, (x, y, w, h))| {
+            (*x..*x + *w as i32).contains(&point.0) && (*y..*y + *h as i32).contains(&point.1)
+        })
+        .max_by_key(|(id, _)| stacking.iter().position(|s| s == id))
+        .map(|&(id, _)| id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_drag_region() {
+        // Dragged up and to the left
+        assert_eq!(
+            drag_region((500, 400), (100, 100)),
+            Some(Region {
+                x: 100,
+                y: 100,
+                width: 400,
+                height: 300,
+            })
+        );
+        assert_eq!(drag_region((100, 100), (103, 98)), None);
+        assert_eq!(drag_region((100, 100), (100, 300)).unwrap().width, 1);
+    }
+
+    #[test]
+    fn test_window_at() {
+        let windows = [(1, (0, 0, 800, 600)), (2, (400, 300, 800, 600))];
+        assert_eq!(window_at((500, 400), &windows, &[1, 2]), Some(2));
+        assert_eq!(window_at((500, 400), &windows, &[2, 1]), Some(1));
+        assert_eq!(window_at((100, 100), &windows, &[1, 2]), Some(1));
+        assert_eq!(window_at((1500, 100), &windows, &[1, 2]), None);
+    }
+}
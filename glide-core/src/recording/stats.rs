@@ -0,0 +1,190 @@
+//! How well a capture kept up: frames captured, dropped and late, and the
+//! encoder backlog, saved with the recording
+
+use serde::{Deserialize, Serialize};
+
+/// Frame interval the capture sessions aim for
+const FRAME_INTERVAL: f64 = 1.0 / 60.0;
+
+/// A frame arriving this many intervals after the previous one means the
+/// capture itself stalled
+const LATE_FACTOR: f64 = 1.5;
+
+/// Share of missed frames above which the user is warned
+const WARN_RATIO: f64 = 0.01;
+
+/// Counters for one recorded file
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CaptureStats {
+    /// Frames encoded into the file
+    pub captured_frames: u64,
+    /// Frames lost because the encoder fell behind
+    pub dropped_frames: u64,
+    /// Frames that came well after the one before, because capture stalled
+    pub late_frames: u64,
+    /// Captured frames per second of recorded (unpaused) time
+    pub average_fps: f64,
+    /// Most frames waiting for the encoder at once
+    pub max_queued: usize,
+}
+
+impl CaptureStats {
+    /// The counters of several files, as one recording `recorded` seconds long
+    pub fn combine(files: &[CaptureStats], recorded: f64) -> Self {
+        let captured_frames = files.iter().map(|s| s.captured_frames).sum::<u64>();
+        Self {
+            captured_frames,
+            dropped_frames: files.iter().map(|s| s.dropped_frames).sum(),
+            late_frames: files.iter().map(|s| s.late_frames).sum(),
+            average_fps: captured_frames as f64 / recorded.max(f64::EPSILON),
+            max_queued: files.iter().map(|s| s.max_queued).max().unwrap_or(0),
+        }
+    }
+
+    /// What to warn about if too many frames were dropped or late
+    pub fn warning(&self) -> Option<String> {
+        let missed = self.dropped_frames + self.late_frames;
+        let total = self.captured_frames + self.dropped_frames;
+        if total == 0 || (missed as f64) / (total as f64) <= WARN_RATIO {
+            return None;
+        }
+        Some(format!(
+            "capture couldn't keep up ({} dropped, {} late of {} frames, {:.1} fps average). \
+             Try a GPU --encoder, a smaller --region, or closing other apps.",
+            self.dropped_frames, self.late_frames, total, self.average_fps
+        ))
+    }
+}
+
+/// Gathers `CaptureStats` frame by frame while recording
+#[derive(Debug)]
+pub struct FrameCounter {
+    /// Whether gaps between frames mean stalls, which holds for captures
+    /// that send frames at a steady rate
+    detect_late: bool,
+    frames: u64,
+    late: u64,
+    max_queued: usize,
+    /// Capture timestamp of the last frame, None at the start and after a pause
+    last_timestamp: Option<f64>,
+}
+
+impl FrameCounter {
+    pub fn new(detect_late: bool) -> Self {
+        Self {
+            detect_late,
+            frames: 0,
+            late: 0,
+            max_queued: 0,
+            last_timestamp: None,
+        }
+    }
+
+    /// Note an encoded frame captured at `timestamp`, with `queued` frames
+    /// still waiting behind it
+    pub fn frame(&mut self, timestamp: f64, queued: usize) {
+        if let Some(last) = self.last_timestamp.filter(|_| self.detect_late) {
+            if timestamp - last > FRAME_INTERVAL * LATE_FACTOR {
+                self.late += 1;
+            }
+        }
+        self.last_timestamp = Some(timestamp);
+        self.frames += 1;
+        self.max_queued = self.max_queued.max(queued);
+    }
+
+    /// Frames encoded so far
+    pub fn frames(&self) -> u64 {
+        self.frames
+    }
+
+    /// The gap across a pause isn't a stall
+    pub fn pause(&mut self) {
+        self.last_timestamp = None;
+    }
+
+    /// Stats over `recorded` seconds, with `dropped` frames lost on the way
+    pub fn finish(&self, dropped: u64, recorded: f64) -> CaptureStats {
+        CaptureStats {
+            captured_frames: self.frames,
+            dropped_frames: dropped,
+            late_frames: self.late,
+            average_fps: self.frames as f64 / recorded.max(f64::EPSILON),
+            max_queued: self.max_queued,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counts_late_frames() {
+        let mut counter = FrameCounter::new(true);
+        for i in 0..60 {
+            counter.frame(i as f64 / 60.0, 1);
+        }
+        // A 100ms stall, then a pause that shouldn't count as one
+        counter.frame(1.1, 3);
+        counter.pause();
+        counter.frame(5.0, 0);
+
+        let stats = counter.finish(2, 1.0);
+        assert_eq!(stats.captured_frames, 62);
+        assert_eq!(stats.late_frames, 1);
+        assert_eq!(stats.max_queued, 3);
+        assert_eq!(stats.average_fps, 62.0);
+
+        // Gaps are expected where frames only come when the screen changes
+        let mut counter = FrameCounter::new(false);
+        counter.frame(0.0, 0);
+        counter.frame(2.0, 0);
+        assert_eq!(counter.finish(0, 2.0).late_frames, 0);
+    }
+
+    #[test]
+    fn test_warning() {
+        let good = CaptureStats {
+            captured_frames: 600,
+            dropped_frames: 3,
+            ..Default::default()
+        };
+        assert_eq!(good.warning(), None);
+
+        let bad = CaptureStats {
+            captured_frames: 500,
+            dropped_frames: 80,
+            late_frames: 20,
+            average_fps: 48.0,
+            max_queued: 3,
+        };
+        assert!(bad
+            .warning()
+            .unwrap()
+            .contains("80 dropped, 20 late of 580 frames"));
+        assert_eq!(CaptureStats::default().warning(), None);
+    }
+
+    #[test]
+    fn test_combine() {
+        let first = CaptureStats {
+            captured_frames: 600,
+            dropped_frames: 3,
+            max_queued: 4,
+            ..Default::default()
+        };
+        let second = CaptureStats {
+            captured_frames: 300,
+            late_frames: 2,
+            max_queued: 1,
+            ..Default::default()
+        };
+        let total = CaptureStats::combine(&[first, second], 15.0);
+        assert_eq!(total.captured_frames, 900);
+        assert_eq!(total.dropped_frames, 3);
+        assert_eq!(total.late_frames, 2);
+        assert_eq!(total.average_fps, 60.0);
+        assert_eq!(total.max_queued, 4);
+    }
+}
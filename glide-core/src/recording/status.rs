@@ -0,0 +1,188 @@
+//! Live status while recording
+//!
+//! A few times a second, elapsed time, frame counts, capture rate, encoder
+//! backlog, file size and cursor events go to
+//! [`RecordOptions::status`](crate::recording::recorder::RecordOptions::status),
+//! which the CLI shows as a status line redrawn in place.
+
+use crate::library::format_bytes;
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// How often the status is updated
+const REFRESH_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Window over which the instantaneous frame rate is measured
+const FPS_WINDOW: Duration = Duration::from_secs(1);
+
+/// Counters shown on the status line
+#[derive(Debug, Clone, Default)]
+pub struct StatusSnapshot {
+    pub paused: bool,
+    pub elapsed: Duration,
+    pub frames: u64,
+    pub fps: f64,
+    pub dropped: u64,
+    /// Frames captured but not yet handed to the encoder
+    pub queued: usize,
+    /// Size of the output so far (None when streaming)
+    pub file_size: Option<u64>,
+    pub cursor_events: usize,
+}
+
+impl StatusSnapshot {
+    pub fn format(&self) -> String {
+        let secs = self.elapsed.as_secs();
+        let mut line = format!(
+            "{:02}:{:02}:{:02} | {} frames | {:.1} fps | {} dropped | queue {}",
+            secs / 3600,
+            secs / 60 % 60,
+            secs % 60,
+            self.frames,
+            self.fps,
+            self.dropped,
+            self.queued
+        );
+        if let Some(size) = self.file_size {
+            line.push_str(&format!(" | {}", format_bytes(size)));
+        }
+        line.push_str(&format!(" | {} cursor events", self.cursor_events));
+        line
+    }
+}
+
+/// What the status of a recording in progress is doing
+#[derive(Debug, Clone)]
+pub enum StatusEvent {
+    /// The latest counters
+    Update(StatusSnapshot),
+    /// The recording stopped; it's saving from here on
+    Finished,
+}
+
+/// Called with the status a few times a second while recording
+pub type StatusCallback = Arc<dyn Fn(&StatusEvent) + Send + Sync>;
+
+/// Counters for a recording in progress, passed on to a `StatusCallback`
+pub(crate) struct RecordingStatus {
+    callback: Option<StatusCallback>,
+    output: Option<PathBuf>,
+    paused: bool,
+    start: Instant,
+    /// Arrival times of the frames within the last `FPS_WINDOW`
+    recent_frames: VecDeque<Instant>,
+    last_refresh: Option<Instant>,
+}
+
+impl RecordingStatus {
+    /// `output` is polled for its size; pass None when streaming
+    pub fn new(output: Option<PathBuf>, callback: Option<StatusCallback>) -> Self {
+        Self {
+            callback,
+            output,
+            paused: false,
+            start: Instant::now(),
+            recent_frames: VecDeque::new(),
+            last_refresh: None,
+        }
+    }
+
+    /// Note that a frame was handed to the encoder
+    pub fn frame(&mut self) {
+        let now = Instant::now();
+        self.recent_frames.push_back(now);
+        self.expire(now);
+    }
+
+    /// Frames per second over the last `FPS_WINDOW`
+    fn fps(&mut self) -> f64 {
+        self.expire(Instant::now());
+        self.recent_frames.len() as f64 / FPS_WINDOW.as_secs_f64()
+    }
+
+    fn expire(&mut self, now: Instant) {
+        while self
+            .recent_frames
+            .front()
+            .is_some_and(|t| now.duration_since(*t) > FPS_WINDOW)
+        {
+            self.recent_frames.pop_front();
+        }
+    }
+
+    /// Whether an update is due; callers skip gathering counters (some
+    /// take locks) when it isn't
+    pub fn due(&self) -> bool {
+        self.last_refresh
+            .is_none_or(|last| last.elapsed() >= REFRESH_INTERVAL)
+    }
+
+    /// Pass on the latest counters
+    pub fn refresh(&mut self, frames: u64, dropped: u64, queued: usize, cursor_events: usize) {
+        let snapshot = StatusSnapshot {
+            paused: self.paused,
+            elapsed: self.start.elapsed(),
+            frames,
+            fps: self.fps(),
+            dropped,
+            queued,
+            file_size: self
+                .output
+                .as_ref()
+                .and_then(|path| std::fs::metadata(path).ok())
+                .map(|m| m.len()),
+            cursor_events,
+        };
+        self.emit(StatusEvent::Update(snapshot));
+        self.last_refresh = Some(Instant::now());
+    }
+
+    /// Follow a different file, such as the next segment
+    pub fn set_output(&mut self, output: Option<PathBuf>) {
+        self.output = output;
+    }
+
+    /// Switch between recording and paused, with an update right away
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+        self.last_refresh = None;
+    }
+
+    pub fn finish(&self) {
+        self.emit(StatusEvent::Finished);
+    }
+
+    fn emit(&self, event: StatusEvent) {
+        if let Some(callback) = &self.callback {
+            callback(&event);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_status() {
+        let mut snapshot = StatusSnapshot {
+            paused: false,
+            elapsed: Duration::from_secs(3725),
+            frames: 223_500,
+            fps: 59.96,
+            dropped: 4,
+            queued: 1,
+            file_size: Some(48 * 1024 * 1024),
+            cursor_events: 312,
+        };
+        assert_eq!(
+            snapshot.format(),
+            "01:02:05 | 223500 frames | 60.0 fps | 4 dropped | queue 1 | 48.0 MB | 312 cursor events"
+        );
+
+        snapshot.file_size = None;
+        assert!(!snapshot.format().contains("MB"));
+    }
+}
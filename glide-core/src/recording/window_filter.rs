@@ -0,0 +1,58 @@
+//! Finding windows by app or title instead of by ID, since IDs change
+//! every time an app is launched
+
+use anyhow::Result;
+
+/// Whether `text` contains `filter`, ignoring case
+pub fn contains_ignore_case(text: &str, filter: &str) -> bool {
+    text.to_lowercase().contains(&filter.to_lowercase())
+}
+
+/// The only one of `matches`, or an error naming them (with `describe`)
+/// so the user can narrow `filter` down
+pub fn single_match<T>(
+    mut matches: Vec<T>,
+    filter: &str,
+    describe: impl Fn(&T) -> String,
+) -> Result<T> {
+    match matches.len() {
+        0 => anyhow::bail!("No window title contains \"{}\"", filter),
+        1 => Ok(matches.remove(0)),
+        n => anyhow::bail!(
+            "{} windows match \"{}\", be more specific or use --window:\n{}",
+            n,
+            filter,
+            matches
+                .iter()
+                .map(|m| format!("  {}", describe(m)))
+                .collect::<Vec<_>>()
+                .join("\n")
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_contains_ignore_case() {
+        assert!(contains_ignore_case("Google Chrome", "chrome"));
+        assert!(contains_ignore_case(
+            "Fix zoom by x · Pull Request #12",
+            "PULL request"
+        ));
+        assert!(!contains_ignore_case("Terminal", "chrome"));
+    }
+
+    #[test]
+    fn test_single_match() {
+        assert_eq!(single_match(vec![7], "PR", |n| n.to_string()).unwrap(), 7);
+        assert!(single_match(Vec::<u32>::new(), "PR", |n| n.to_string())
+            .unwrap_err()
+            .to_string()
+            .contains("No window title contains \"PR\""));
+        let error = single_match(vec![1, 2], "PR", |n| format!("[{}]", n)).unwrap_err();
+        assert!(error.to_string().ends_with("  [1]\n  [2]"));
+    }
+}
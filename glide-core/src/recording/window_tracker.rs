@@ -0,0 +1,158 @@
+//! Following a recorded window as it's moved or resized
+//!
+//! ScreenCaptureKit keeps capturing a window wherever it goes, so cursor
+//! positions (in screen coordinates) only line up with the video if we know
+//! where the window was at each moment. The window's bounds are polled
+//! while recording and stored whenever they change.
+
+#[cfg(target_os = "linux")]
+use crate::linux::list_windows;
+#[cfg(target_os = "macos")]
+use crate::macos::list_windows;
+#[cfg(target_os = "windows")]
+use crate::windows::list_windows;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How often the window's bounds are checked
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Where the recorded window was at a point in time
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct WindowBounds {
+    /// Seconds on the cursor tracking clock
+    pub timestamp: f64,
+    /// Top-left corner in screen coordinates (points on macOS)
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Polls a window's bounds on a background thread
+pub struct WindowTracker {
+    stop_flag: Arc<AtomicBool>,
+    thread_handle: Option<thread::JoinHandle<Vec<WindowBounds>>>,
+}
+
+impl WindowTracker {
+    /// Start polling window `window_id`, with timestamps relative to `start`
+    pub fn start(window_id: u32, start: Instant) -> Self {
+        let stop_flag = Arc::new(AtomicBool::new(false));
+        let stop_clone = Arc::clone(&stop_flag);
+        let handle = thread::spawn(move || poll_bounds(window_id, start, stop_clone));
+        Self {
+            stop_flag,
+            thread_handle: Some(handle),
+        }
+    }
+
+    /// Stop polling and return the bounds the window had, oldest first.
+    /// Empty if the window never moved.
+    pub fn stop(mut self) -> Vec<WindowBounds> {
+        self.stop_flag.store(true, Ordering::SeqCst);
+        let samples = self
+            .thread_handle
+            .take()
+            .and_then(|handle| handle.join().ok())
+            .unwrap_or_default();
+        if samples.len() < 2 {
+            Vec::new()
+        } else {
+            samples
+        }
+    }
+}
+
+fn poll_bounds(window_id: u32, start: Instant, stop_flag: Arc<AtomicBool>) -> Vec<WindowBounds> {
+    let mut samples: Vec<WindowBounds> = Vec::new();
+    let mut last_poll = 0.0;
+
+    while !stop_flag.load(Ordering::Relaxed) {
+        let now = start.elapsed().as_secs_f64();
+        let bounds = list_windows()
+            .ok()
+            .and_then(|windows| windows.into_iter().find(|w| w.id == window_id))
+            .map(|w| w.bounds);
+
+        // A closed or minimized window keeps its last bounds
+        if let Some((x, y, width, height)) = bounds {
+            let sample = WindowBounds {
+                timestamp: now,
+                x,
+                y,
+                width,
+                height,
+            };
+            match samples.last().copied() {
+                None => samples.push(sample),
+                Some(prev)
+                    if (prev.x, prev.y, prev.width, prev.height) != (x, y, width, height) =>
+                {
+                    // Pin the old bounds at the last poll, so interpolation
+                    // only spans the interval the change happened in
+                    if prev.timestamp < last_poll {
+                        samples.push(WindowBounds {
+                            timestamp: last_poll,
+                            ..prev
+                        });
+                    }
+                    samples.push(sample);
+                }
+                Some(_) => {}
+            }
+        }
+
+        last_poll = now;
+        thread::sleep(POLL_INTERVAL);
+    }
+    samples
+}
+
+/// Window offset at `timestamp`, interpolating between samples
+pub fn offset_at(bounds: &[WindowBounds], timestamp: f64) -> Option<(f64, f64)> {
+    let first = bounds.first()?;
+    let last = bounds.last()?;
+    if timestamp <= first.timestamp {
+        return Some((first.x as f64, first.y as f64));
+    }
+    if timestamp >= last.timestamp {
+        return Some((last.x as f64, last.y as f64));
+    }
+
+    let i = bounds.partition_point(|b| b.timestamp <= timestamp);
+    let (a, b) = (&bounds[i - 1], &bounds[i]);
+    let t = (timestamp - a.timestamp) / (b.timestamp - a.timestamp).max(f64::EPSILON);
+    Some((
+        a.x as f64 + (b.x - a.x) as f64 * t,
+        a.y as f64 + (b.y - a.y) as f64 * t,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(timestamp: f64, x: i32, y: i32) -> WindowBounds {
+        WindowBounds {
+            timestamp,
+            x,
+            y,
+            width: 800,
+            height: 600,
+        }
+    }
+
+    #[test]
+    fn test_offset_at() {
+        let bounds = [at(0.0, 100, 100), at(2.0, 100, 100), at(2.1, 200, 150)];
+        assert_eq!(offset_at(&[], 1.0), None);
+        assert_eq!(offset_at(&bounds, 1.0), Some((100.0, 100.0)));
+        let (x, y) = offset_at(&bounds, 2.05).unwrap();
+        assert!((x - 150.0).abs() < 1e-6 && (y - 125.0).abs() < 1e-6);
+        assert_eq!(offset_at(&bounds, 5.0), Some((200.0, 150.0)));
+    }
+}
@@ -0,0 +1,436 @@
+//! Windows screen capture using DXGI Desktop Duplication (FFmpeg's `ddagrab`)
+//!
+//! `ddagrab` duplicates one output of one adapter on the GPU; frames are
+//! downloaded as BGRA and read from FFmpeg's stdout like the Linux path.
+//! Windows are captured as their region of the output they're on.
+
+use crate::recording::encoder::PcmStream;
+use crate::recording::exclude::{drawbox_filter, Exclusions};
+use crate::windows::window::list_windows;
+use anyhow::{Context, Result};
+use std::io::{BufReader, Read};
+use std::os::windows::process::CommandExt;
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::Arc;
+use std::thread;
+use std::time::Instant;
+
+/// Keeps Ctrl+C in the console from reaching FFmpeg; the session stops it
+const CREATE_NEW_PROCESS_GROUP: u32 = 0x0000_0200;
+
+/// A captured video frame with raw BGRA pixel data
+pub struct CapturedFrame {
+    pub data: Vec<u8>,
+    pub width: usize,
+    pub height: usize,
+    pub timestamp: f64,
+}
+
+/// Capture configuration
+#[derive(Default)]
+pub struct CaptureConfig {
+    pub show_cursor: bool,
+    pub width: u32,
+    pub height: u32,
+    pub capture_audio: bool,
+    /// Windows to black out
+    pub exclude: Exclusions,
+}
+
+/// A DXGI output, shaped like ScreenCaptureKit's display handle
+#[derive(Clone)]
+pub struct DxgiDisplay {
+    pub index: usize,
+    pub adapter: u32,
+    pub output: u32,
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl DxgiDisplay {
+    pub fn frame(&self) -> DisplayFrame {
+        DisplayFrame {
+            width: self.width as f64,
+            height: self.height as f64,
+        }
+    }
+}
+
+pub struct DisplayFrame {
+    pub width: f64,
+    pub height: f64,
+}
+
+/// A window's region of the output it's on
+pub struct DxgiWindow {
+    pub id: u32,
+    pub display: DxgiDisplay,
+    /// Offset of the window within the display
+    pub offset_x: i32,
+    pub offset_y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl DxgiWindow {
+    pub fn frame(&self) -> DisplayFrame {
+        DisplayFrame {
+            width: self.width as f64,
+            height: self.height as f64,
+        }
+    }
+}
+
+/// Active screen capture session
+pub struct CaptureSession {
+    ffmpeg_process: Child,
+    receiver: Receiver<CapturedFrame>,
+    running: Arc<AtomicBool>,
+    reader_thread: Option<thread::JoinHandle<()>>,
+    /// Frames captured but discarded because the encoder fell behind
+    dropped: Arc<AtomicU64>,
+    /// Frames waiting in the channel for the encoder
+    queued: Arc<AtomicUsize>,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl CaptureSession {
+    pub fn recv(&self) -> Option<CapturedFrame> {
+        let frame = self.receiver.recv().ok();
+        if frame.is_some() {
+            self.queued.fetch_sub(1, Ordering::Relaxed);
+        }
+        frame
+    }
+
+    pub fn try_recv(&self) -> Option<CapturedFrame> {
+        let frame = self.receiver.try_recv().ok();
+        if frame.is_some() {
+            self.queued.fetch_sub(1, Ordering::Relaxed);
+        }
+        frame
+    }
+
+    /// Frames dropped so far because the channel to the encoder was full
+    pub fn dropped_frames(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Frames captured but not yet taken by the encoder
+    pub fn queued_frames(&self) -> usize {
+        self.queued.load(Ordering::Relaxed)
+    }
+
+    /// System audio isn't captured on Windows
+    pub fn take_audio(&mut self) -> Option<PcmStream> {
+        None
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running.load(Ordering::Relaxed)
+    }
+
+    pub fn stop(&mut self) -> Result<()> {
+        self.running.store(false, Ordering::SeqCst);
+
+        // Raw frames need no finalizing, so FFmpeg can simply be ended
+        let _ = self.ffmpeg_process.kill();
+        let _ = self.ffmpeg_process.wait();
+
+        if let Some(handle) = self.reader_thread.take() {
+            let _ = handle.join();
+        }
+
+        Ok(())
+    }
+}
+
+/// Find a display by index
+pub fn find_display(display_index: usize) -> Result<DxgiDisplay> {
+    let displays = crate::windows::list_displays()?;
+    let display = displays
+        .into_iter()
+        .find(|d| d.index == display_index)
+        .ok_or_else(|| anyhow::anyhow!("Display {} not found", display_index))?;
+
+    Ok(DxgiDisplay {
+        index: display.index,
+        adapter: display.adapter,
+        output: display.output,
+        x: display.x,
+        y: display.y,
+        width: display.width,
+        height: display.height,
+    })
+}
+
+/// Find a window by ID, and the display it's on
+pub fn find_window(window_id: u32) -> Result<DxgiWindow> {
+    let window = crate::windows::list_windows()?
+        .into_iter()
+        .find(|w| w.id == window_id)
+        .ok_or_else(|| anyhow::anyhow!("Window {} not found", window_id))?;
+    let (x, y, width, height) = window.bounds;
+
+    // Duplication works per output, so use the one holding the window's center
+    let (cx, cy) = (x + width as i32 / 2, y + height as i32 / 2);
+    let display = crate::windows::list_displays()?
+        .into_iter()
+        .find(|d| cx >= d.x && cx < d.x + d.width as i32 && cy >= d.y && cy < d.y + d.height as i32)
+        .context("Window is not on any display")?;
+
+    // Clip to the display; duplication can't see past the output's edges
+    let left = x.max(display.x);
+    let top = y.max(display.y);
+    let right = (x + width as i32).min(display.x + display.width as i32);
+    let bottom = (y + height as i32).min(display.y + display.height as i32);
+
+    Ok(DxgiWindow {
+        id: window.id,
+        offset_x: left - display.x,
+        offset_y: top - display.y,
+        width: (right - left).max(0) as u32,
+        height: (bottom - top).max(0) as u32,
+        display: DxgiDisplay {
+            index: display.index,
+            adapter: display.adapter,
+            output: display.output,
+            x: display.x,
+            y: display.y,
+            width: display.width,
+            height: display.height,
+        },
+    })
+}
+
+/// Start capturing a display
+pub fn start_display_capture(
+    display: &DxgiDisplay,
+    config: &CaptureConfig,
+) -> Result<CaptureSession> {
+    reject_audio(config)?;
+    let width = if config.width > 0 {
+        config.width
+    } else {
+        display.width
+    };
+    let height = if config.height > 0 {
+        config.height
+    } else {
+        display.height
+    };
+
+    let source = format!(
+        "ddagrab=output_idx={}:framerate=60:draw_mouse={}",
+        display.output, config.show_cursor as u8
+    );
+    // Scale only when asked for something other than the display's size
+    let scale = ((width, height) != (display.width, display.height)).then_some((width, height));
+    // Boxes go on before scaling, in display pixels
+    let boxes = exclusion_filter(
+        &config.exclude,
+        (display.x, display.y),
+        (display.width, display.height),
+    )?;
+    let cmd = ddagrab_command(display.adapter, &source, boxes, scale);
+    start_capture_process(cmd, width, height)
+}
+
+/// Start capturing a specific window
+pub fn start_window_capture(window: &DxgiWindow, config: &CaptureConfig) -> Result<CaptureSession> {
+    reject_audio(config)?;
+    let width = if config.width > 0 {
+        config.width
+    } else {
+        window.width
+    };
+    let height = if config.height > 0 {
+        config.height
+    } else {
+        window.height
+    };
+    // Encoders need even dimensions
+    let (width, height) = (width & !1, height & !1);
+
+    let source = format!(
+        "ddagrab=output_idx={}:framerate=60:draw_mouse={}:offset_x={}:offset_y={}:video_size={}x{}",
+        window.display.output,
+        config.show_cursor as u8,
+        window.offset_x,
+        window.offset_y,
+        width,
+        height
+    );
+    let boxes = exclusion_filter(
+        &config.exclude,
+        (
+            window.display.x + window.offset_x,
+            window.display.y + window.offset_y,
+        ),
+        (width, height),
+    )?;
+    let cmd = ddagrab_command(window.display.adapter, &source, boxes, None);
+    start_capture_process(cmd, width, height)
+}
+
+/// Start capturing a rectangle of a display, given relative to its top-left
+pub fn start_region_capture(
+    display: &DxgiDisplay,
+    region: (i32, i32, u32, u32),
+    config: &CaptureConfig,
+) -> Result<CaptureSession> {
+    // ddagrab crops any rectangle of an output: treat the region as a window
+    let (x, y, width, height) = region;
+    let window = DxgiWindow {
+        id: 0,
+        display: display.clone(),
+        offset_x: x,
+        offset_y: y,
+        width,
+        height,
+    };
+    start_window_capture(&window, config)
+}
+
+/// Capturing just one app's windows needs ScreenCaptureKit
+pub fn start_app_capture(
+    _display: &DxgiDisplay,
+    app: &str,
+    _config: &CaptureConfig,
+) -> Result<CaptureSession> {
+    anyhow::bail!(
+        "Recording all of {}'s windows is only supported on macOS; use --window instead",
+        app
+    )
+}
+
+/// FFmpeg filter blacking out the excluded windows in a capture of `size`
+/// at `origin` (desktop coordinates)
+fn exclusion_filter(
+    exclude: &Exclusions,
+    origin: (i32, i32),
+    size: (u32, u32),
+) -> Result<Option<String>> {
+    if exclude.is_empty() {
+        return Ok(None);
+    }
+    let boxes: Vec<_> = list_windows()?
+        .into_iter()
+        .filter(|w| exclude.matches(w.id, &w.owner))
+        .map(|w| w.bounds)
+        .collect();
+    exclude.warn_if_unmatched(boxes.len());
+    Ok(drawbox_filter(&boxes, origin, size))
+}
+
+/// FFmpeg reading `source` on `adapter`'s GPU and writing BGRA frames to
+/// stdout, with `boxes` drawn over the frames before any scaling
+fn ddagrab_command(
+    adapter: u32,
+    source: &str,
+    boxes: Option<String>,
+    scale: Option<(u32, u32)>,
+) -> Command {
+    let boxes = boxes.map(|b| format!(",{}", b)).unwrap_or_default();
+    let scale = scale
+        .map(|(w, h)| format!(",scale={}:{}", w, h))
+        .unwrap_or_default();
+
+    let mut cmd = Command::new("ffmpeg");
+    cmd.args([
+        "-init_hw_device",
+        &format!("d3d11va=glide:{}", adapter),
+        "-filter_hw_device",
+        "glide",
+        "-filter_complex",
+        &format!("{},hwdownload,format=bgra{}{}", source, boxes, scale),
+        "-pix_fmt",
+        "bgra",
+        "-f",
+        "rawvideo",
+        "-",
+    ]);
+    cmd.stdout(Stdio::piped());
+    cmd.stderr(Stdio::null());
+    cmd.creation_flags(CREATE_NEW_PROCESS_GROUP);
+    cmd
+}
+
+fn reject_audio(config: &CaptureConfig) -> Result<()> {
+    if config.capture_audio {
+        anyhow::bail!("System audio capture is only supported on macOS; record a loopback device (e.g. Stereo Mix) with --mic instead");
+    }
+    Ok(())
+}
+
+/// Start the FFmpeg capture process
+fn start_capture_process(mut cmd: Command, width: u32, height: u32) -> Result<CaptureSession> {
+    let mut ffmpeg_process = cmd.spawn().context("Failed to start FFmpeg for capture")?;
+
+    let stdout = ffmpeg_process
+        .stdout
+        .take()
+        .context("Failed to get FFmpeg stdout")?;
+
+    let (sender, receiver) = mpsc::sync_channel(3);
+    let running = Arc::new(AtomicBool::new(true));
+    let running_clone = Arc::clone(&running);
+    let dropped = Arc::new(AtomicU64::new(0));
+    let dropped_clone = Arc::clone(&dropped);
+    let queued = Arc::new(AtomicUsize::new(0));
+    let queued_clone = Arc::clone(&queued);
+
+    let frame_size = (width * height * 4) as usize; // BGRA = 4 bytes per pixel
+    let w = width as usize;
+    let h = height as usize;
+
+    let reader_thread = thread::spawn(move || {
+        let mut reader = BufReader::new(stdout);
+        let mut frame_buffer = vec![0u8; frame_size];
+        let started = Instant::now();
+
+        while running_clone.load(Ordering::Relaxed) {
+            match reader.read_exact(&mut frame_buffer) {
+                Ok(()) => {
+                    // Read as soon as the capture process produces it
+                    let timestamp = started.elapsed().as_secs_f64();
+
+                    let frame = CapturedFrame {
+                        data: frame_buffer.clone(),
+                        width: w,
+                        height: h,
+                        timestamp,
+                    };
+
+                    // Counted before sending so the receiver never sees it negative
+                    queued_clone.fetch_add(1, Ordering::Relaxed);
+                    if sender.try_send(frame).is_err() {
+                        // Channel full or closed: the frame is lost
+                        queued_clone.fetch_sub(1, Ordering::Relaxed);
+                        dropped_clone.fetch_add(1, Ordering::Relaxed);
+                    }
+                }
+                Err(_) => {
+                    // EOF or error, stop reading
+                    break;
+                }
+            }
+        }
+    });
+
+    Ok(CaptureSession {
+        ffmpeg_process,
+        receiver,
+        running,
+        reader_thread: Some(reader_thread),
+        dropped,
+        queued,
+        width,
+        height,
+    })
+}
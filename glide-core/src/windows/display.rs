@@ -0,0 +1,140 @@
+//! Windows display enumeration using DXGI outputs
+
+use crate::keyboard::{KeyboardInfo, KeyboardLayout, ModifierStyle};
+use ::windows::core::w;
+use ::windows::Win32::Graphics::Dxgi::{CreateDXGIFactory1, IDXGIFactory1};
+use ::windows::Win32::Graphics::Gdi::{GetMonitorInfoW, MONITORINFO};
+use ::windows::Win32::System::Registry::{RegGetValueW, HKEY_CURRENT_USER, RRF_RT_REG_DWORD};
+use ::windows::Win32::UI::HiDpi::{
+    GetDpiForSystem, SetProcessDpiAwarenessContext, DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE_V2,
+};
+use ::windows::Win32::UI::Input::KeyboardAndMouse::GetKeyboardLayoutNameW;
+use anyhow::{Context, Result};
+use std::sync::Once;
+
+/// `MONITORINFOF_PRIMARY`
+const PRIMARY_MONITOR: u32 = 1;
+
+pub struct DisplayInfo {
+    pub index: usize,
+    pub width: u32,
+    pub height: u32,
+    pub x: i32,
+    pub y: i32,
+    pub is_main: bool,
+    /// Always 1.0: glide runs per-monitor DPI aware, so every size and
+    /// cursor position is already in physical pixels
+    pub scale_factor: f64,
+    /// DXGI adapter the display is attached to
+    pub adapter: u32,
+    /// Output index on that adapter (FFmpeg's `ddagrab` `output_idx`)
+    pub output: u32,
+}
+
+/// Opt into physical-pixel coordinates for monitors, windows and the
+/// cursor hook. Without it, Windows scales everything to 96 DPI on HiDPI
+/// displays and the cursor track no longer lines up with the capture.
+pub fn ensure_dpi_aware() {
+    static DPI_AWARE: Once = Once::new();
+    DPI_AWARE.call_once(|| {
+        // SAFETY: no preconditions; fails harmlessly if already set (e.g. by a manifest)
+        let _ =
+            unsafe { SetProcessDpiAwarenessContext(DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE_V2) };
+    });
+}
+
+pub fn list_displays() -> Result<Vec<DisplayInfo>> {
+    ensure_dpi_aware();
+
+    // SAFETY: plain COM calls on interfaces we own; out-structs are zeroed by the bindings
+    unsafe {
+        let factory: IDXGIFactory1 =
+            CreateDXGIFactory1().context("Failed to create DXGI factory")?;
+
+        let mut displays = Vec::new();
+        let mut adapter_index = 0;
+        while let Ok(adapter) = factory.EnumAdapters1(adapter_index) {
+            let mut output_index = 0;
+            while let Ok(output) = adapter.EnumOutputs(output_index) {
+                let desc = output.GetDesc().context("Failed to query display output")?;
+                if desc.AttachedToDesktop.as_bool() {
+                    let rect = desc.DesktopCoordinates;
+                    let mut info = MONITORINFO {
+                        cbSize: std::mem::size_of::<MONITORINFO>() as u32,
+                        ..Default::default()
+                    };
+                    let is_main = GetMonitorInfoW(desc.Monitor, &mut info).as_bool()
+                        && info.dwFlags & PRIMARY_MONITOR != 0;
+
+                    displays.push(DisplayInfo {
+                        index: displays.len(),
+                        width: (rect.right - rect.left) as u32,
+                        height: (rect.bottom - rect.top) as u32,
+                        x: rect.left,
+                        y: rect.top,
+                        is_main,
+                        scale_factor: 1.0,
+                        adapter: adapter_index,
+                        output: output_index,
+                    });
+                }
+                output_index += 1;
+            }
+            adapter_index += 1;
+        }
+
+        // Some drivers don't flag a primary output
+        if !displays.iter().any(|d| d.is_main) {
+            if let Some(first) = displays.first_mut() {
+                first.is_main = true;
+            }
+        }
+        Ok(displays)
+    }
+}
+
+/// Standard Windows cursor size in pixels at 100% scaling
+const DEFAULT_CURSOR_SIZE: f64 = 32.0;
+
+/// The user's cursor size relative to the standard size (1.0 = standard),
+/// including display scaling since Windows enlarges the cursor with it
+pub fn cursor_size_multiplier() -> Option<f64> {
+    ensure_dpi_aware();
+
+    let mut size: u32 = 0;
+    let mut bytes = std::mem::size_of::<u32>() as u32;
+    // SAFETY: `size` and `bytes` outlive the call and `bytes` matches the buffer
+    let result = unsafe {
+        RegGetValueW(
+            HKEY_CURRENT_USER,
+            w!("Control Panel\\Cursors"),
+            w!("CursorBaseSize"),
+            RRF_RT_REG_DWORD,
+            None,
+            Some(&mut size as *mut u32 as *mut _),
+            Some(&mut bytes),
+        )
+    };
+    // Missing when the size was never changed from the default
+    let base = if result.is_ok() && size > 0 {
+        size as f64 / DEFAULT_CURSOR_SIZE
+    } else {
+        1.0
+    };
+    // SAFETY: no preconditions
+    let dpi = unsafe { GetDpiForSystem() };
+    Some(base * dpi as f64 / 96.0)
+}
+
+/// The active keyboard layout (from its KLID, e.g. "00000407" for German)
+pub fn keyboard_info() -> Option<KeyboardInfo> {
+    let mut klid = [0u16; 9];
+    // SAFETY: the buffer is the KL_NAMELENGTH the API requires
+    unsafe { GetKeyboardLayoutNameW(&mut klid) }.ok()?;
+    let klid = String::from_utf16_lossy(&klid);
+    let klid = u32::from_str_radix(klid.trim_end_matches('\0'), 16).ok()?;
+    Some(KeyboardInfo {
+        layout: KeyboardLayout::from_windows_klid(klid),
+        modifier_style: ModifierStyle::Pc,
+    })
+}
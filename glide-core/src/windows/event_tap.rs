@@ -0,0 +1,478 @@
+//! Windows cursor and keyboard tracking using low-level hooks (WH_MOUSE_LL,
+//! WH_KEYBOARD_LL), and global hotkeys using RegisterHotKey
+
+use crate::cursor_types::{ClickTarget, CursorEvent, CursorShape, EventType, KeyCapture, KeyPress};
+use crate::keyboard::{self, Modifiers};
+use crate::recording::hotkey::{Hotkey, HotkeyKey};
+use crate::windows::display::ensure_dpi_aware;
+use ::windows::core::PCWSTR;
+use ::windows::Win32::Foundation::{HINSTANCE, HWND, LPARAM, LRESULT, POINT, WPARAM};
+use ::windows::Win32::System::LibraryLoader::GetModuleHandleW;
+use ::windows::Win32::System::Threading::GetCurrentThreadId;
+use ::windows::Win32::UI::Input::KeyboardAndMouse::{
+    GetAsyncKeyState, RegisterHotKey, UnregisterHotKey, HOT_KEY_MODIFIERS, MOD_ALT, MOD_CONTROL,
+    MOD_NOREPEAT, MOD_SHIFT, MOD_WIN, VIRTUAL_KEY, VK_CONTROL, VK_LWIN, VK_MENU, VK_RWIN, VK_SHIFT,
+};
+use ::windows::Win32::UI::WindowsAndMessaging::{
+    CallNextHookEx, GetCursorInfo, GetCursorPos, GetMessageW, LoadCursorW, PostThreadMessageW,
+    SetWindowsHookExW, UnhookWindowsHookEx, CURSORINFO, HC_ACTION, HHOOK, IDC_ARROW, IDC_HAND,
+    IDC_IBEAM, IDC_SIZENS, IDC_SIZEWE, KBDLLHOOKSTRUCT, LLKHF_EXTENDED, MSG, MSLLHOOKSTRUCT,
+    WH_KEYBOARD_LL, WH_MOUSE_LL, WM_HOTKEY, WM_KEYDOWN, WM_KEYUP, WM_LBUTTONDOWN, WM_MOUSEMOVE,
+    WM_QUIT, WM_RBUTTONDOWN, WM_SYSKEYDOWN, WM_SYSKEYUP,
+};
+use anyhow::{Context, Result};
+use std::cell::RefCell;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// State the hook procedure records into; hooks run on the thread that
+/// installed them, and their callback has no user-data pointer
+struct HookState {
+    events: Arc<Mutex<Vec<CursorEvent>>>,
+    start_time: Instant,
+    keys: KeyCapture,
+    /// Keys currently down, to skip auto-repeats
+    held: Vec<u32>,
+}
+
+thread_local! {
+    static HOOK_STATE: RefCell<Option<HookState>> = const { RefCell::new(None) };
+}
+
+pub struct CursorTracker {
+    events: Arc<Mutex<Vec<CursorEvent>>>,
+    start_time: Instant,
+    keys: KeyCapture,
+    /// Thread running the hook's message loop, to post WM_QUIT to
+    hook_thread_id: Option<u32>,
+    thread_handle: Option<thread::JoinHandle<()>>,
+}
+
+impl CursorTracker {
+    pub fn new(keys: KeyCapture) -> Self {
+        Self {
+            events: Arc::new(Mutex::new(Vec::new())),
+            start_time: Instant::now(),
+            keys,
+            hook_thread_id: None,
+            thread_handle: None,
+        }
+    }
+
+    pub fn start(&mut self) -> Result<()> {
+        ensure_dpi_aware();
+        self.start_time = Instant::now();
+
+        let events = Arc::clone(&self.events);
+        let start_time = self.start_time;
+        let keys = self.keys;
+        let (ready_tx, ready_rx) = mpsc::channel();
+
+        let handle = thread::spawn(move || {
+            run_hook_loop(events, start_time, keys, ready_tx);
+        });
+
+        // The hook thread reports its ID once the hook is installed
+        let thread_id = ready_rx
+            .recv()
+            .context("Mouse hook thread exited")?
+            .map_err(|e| anyhow::anyhow!("Failed to install mouse hook: {}", e))?;
+
+        self.hook_thread_id = Some(thread_id);
+        self.thread_handle = Some(handle);
+        Ok(())
+    }
+
+    /// Number of events recorded so far
+    pub fn event_count(&self) -> usize {
+        self.events.lock().unwrap().len()
+    }
+
+    pub fn stop(&mut self) -> (Vec<CursorEvent>, f64) {
+        let duration = self.start_time.elapsed().as_secs_f64();
+
+        // End the message loop, which removes the hook
+        if let Some(thread_id) = self.hook_thread_id.take() {
+            // SAFETY: posting to a thread ID has no memory-safety preconditions
+            let _ = unsafe { PostThreadMessageW(thread_id, WM_QUIT, WPARAM(0), LPARAM(0)) };
+        }
+
+        if let Some(handle) = self.thread_handle.take() {
+            // Give it 500ms to stop gracefully
+            let start = Instant::now();
+            while !handle.is_finished() && start.elapsed() < Duration::from_millis(500) {
+                thread::sleep(Duration::from_millis(10));
+            }
+            if handle.is_finished() {
+                let _ = handle.join();
+            }
+        }
+
+        let events = self.events.lock().unwrap();
+        (events.clone(), duration)
+    }
+
+    /// Bounds of clicked UI elements. Not collected on Windows yet, so
+    /// click callouts fall back to a fixed-size box around the click.
+    pub fn click_targets(&self) -> Vec<ClickTarget> {
+        Vec::new()
+    }
+}
+
+/// Install the hooks and pump messages until WM_QUIT
+fn run_hook_loop(
+    events: Arc<Mutex<Vec<CursorEvent>>>,
+    start_time: Instant,
+    keys: KeyCapture,
+    ready: mpsc::Sender<std::result::Result<u32, String>>,
+) {
+    HOOK_STATE.with(|state| {
+        *state.borrow_mut() = Some(HookState {
+            events,
+            start_time,
+            keys,
+            held: Vec::new(),
+        })
+    });
+
+    // SAFETY: the hook is removed on this thread before it exits, and the
+    // message loop below is what lets Windows call it
+    unsafe {
+        let module = match GetModuleHandleW(PCWSTR::null()) {
+            Ok(module) => module,
+            Err(e) => {
+                let _ = ready.send(Err(e.to_string()));
+                return;
+            }
+        };
+        let hook: HHOOK =
+            match SetWindowsHookExW(WH_MOUSE_LL, Some(mouse_hook), HINSTANCE::from(module), 0) {
+                Ok(hook) => hook,
+                Err(e) => {
+                    let _ = ready.send(Err(e.to_string()));
+                    return;
+                }
+            };
+        let keyboard_hook = if keys == KeyCapture::Off {
+            None
+        } else {
+            match SetWindowsHookExW(
+                WH_KEYBOARD_LL,
+                Some(keyboard_hook),
+                HINSTANCE::from(module),
+                0,
+            ) {
+                Ok(hook) => Some(hook),
+                Err(e) => {
+                    let _ = UnhookWindowsHookEx(hook);
+                    let _ = ready.send(Err(e.to_string()));
+                    return;
+                }
+            }
+        };
+        let _ = ready.send(Ok(GetCurrentThreadId()));
+
+        let mut msg = MSG::default();
+        // Returns 0 on WM_QUIT and -1 on error
+        while GetMessageW(&mut msg, HWND::default(), 0, 0).0 > 0 {}
+
+        let _ = UnhookWindowsHookEx(hook);
+        if let Some(keyboard_hook) = keyboard_hook {
+            let _ = UnhookWindowsHookEx(keyboard_hook);
+        }
+    }
+}
+
+unsafe extern "system" fn mouse_hook(code: i32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+    if code == HC_ACTION as i32 {
+        let event_type = match wparam.0 as u32 {
+            WM_MOUSEMOVE => Some(EventType::Move),
+            WM_LBUTTONDOWN => Some(EventType::LeftClick),
+            WM_RBUTTONDOWN => Some(EventType::RightClick),
+            _ => None,
+        };
+        if let Some(event_type) = event_type {
+            let info = &*(lparam.0 as *const MSLLHOOKSTRUCT);
+            HOOK_STATE.with(|state| {
+                if let Some(state) = state.borrow().as_ref() {
+                    // Physical pixels, since the process is per-monitor DPI aware
+                    state.events.lock().unwrap().push(CursorEvent {
+                        x: info.pt.x as f64,
+                        y: info.pt.y as f64,
+                        timestamp: state.start_time.elapsed().as_secs_f64(),
+                        event_type,
+                        display: None,
+                        shape: current_cursor_shape(),
+                    });
+                }
+            });
+        }
+    }
+    // Never swallow input
+    CallNextHookEx(HHOOK::default(), code, wparam, lparam)
+}
+
+unsafe extern "system" fn keyboard_hook(code: i32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+    if code == HC_ACTION as i32 {
+        let info = &*(lparam.0 as *const KBDLLHOOKSTRUCT);
+        let down = matches!(wparam.0 as u32, WM_KEYDOWN | WM_SYSKEYDOWN);
+        let up = matches!(wparam.0 as u32, WM_KEYUP | WM_SYSKEYUP);
+        HOOK_STATE.with(|state| {
+            let mut state = state.borrow_mut();
+            let Some(state) = state.as_mut() else {
+                return;
+            };
+            if up {
+                state.held.retain(|&vk| vk != info.vkCode);
+                return;
+            }
+            // Holding a key repeats its key-down
+            if !down || state.held.contains(&info.vkCode) {
+                return;
+            }
+            state.held.push(info.vkCode);
+
+            // Modifiers on their own aren't key presses
+            let code = key_code(info.scanCode, info.flags.0 & LLKHF_EXTENDED.0 != 0);
+            if code.is_some_and(keyboard::is_modifier) {
+                return;
+            }
+            let press = KeyPress {
+                code: code.map(str::to_string),
+                modifiers: Modifiers {
+                    ctrl: is_down(VK_CONTROL),
+                    alt: is_down(VK_MENU),
+                    shift: is_down(VK_SHIFT),
+                    meta: is_down(VK_LWIN) || is_down(VK_RWIN),
+                },
+            };
+            let Some(press) = state.keys.record(press) else {
+                return;
+            };
+            let mut point = POINT::default();
+            let _ = GetCursorPos(&mut point);
+            state.events.lock().unwrap().push(CursorEvent {
+                x: point.x as f64,
+                y: point.y as f64,
+                timestamp: state.start_time.elapsed().as_secs_f64(),
+                event_type: EventType::KeyDown(press),
+                display: None,
+                shape: current_cursor_shape(),
+            });
+        });
+    }
+    // Never swallow input
+    CallNextHookEx(HHOOK::default(), code, wparam, lparam)
+}
+
+/// Shape of the system cursor, if it's one of the standard cursors. Apps
+/// that set their own cursor images give None.
+unsafe fn current_cursor_shape() -> Option<CursorShape> {
+    let mut info = CURSORINFO {
+        cbSize: std::mem::size_of::<CURSORINFO>() as u32,
+        ..Default::default()
+    };
+    GetCursorInfo(&mut info).ok()?;
+    STANDARD_CURSORS
+        .into_iter()
+        // Standard cursors are shared, so the handles match
+        .find(|(id, _)| LoadCursorW(None, *id).is_ok_and(|cursor| cursor == info.hCursor))
+        .map(|(_, shape)| shape)
+}
+
+/// The system cursors glide knows the shape of
+const STANDARD_CURSORS: [(PCWSTR, CursorShape); 5] = [
+    (IDC_ARROW, CursorShape::Arrow),
+    (IDC_IBEAM, CursorShape::IBeam),
+    (IDC_HAND, CursorShape::PointingHand),
+    (IDC_SIZEWE, CursorShape::ResizeHorizontal),
+    (IDC_SIZENS, CursorShape::ResizeVertical),
+];
+
+unsafe fn is_down(key: VIRTUAL_KEY) -> bool {
+    // The high bit is set while the key is down
+    GetAsyncKeyState(key.0 as i32) < 0
+}
+
+/// W3C code of a key by its scancode, which (unlike the virtual-key
+/// code) is its physical position whatever the layout. Extended keys
+/// share scancodes with the numpad and left-hand modifiers.
+fn key_code(scan_code: u32, extended: bool) -> Option<&'static str> {
+    if !extended {
+        return keyboard::code_from_scancode(scan_code);
+    }
+    Some(match scan_code {
+        0x1C => "NumpadEnter",
+        0x1D => "ControlRight",
+        0x38 => "AltRight",
+        0x47 => "Home",
+        0x48 => "ArrowUp",
+        0x49 => "PageUp",
+        0x4B => "ArrowLeft",
+        0x4D => "ArrowRight",
+        0x4F => "End",
+        0x50 => "ArrowDown",
+        0x51 => "PageDown",
+        0x53 => "Delete",
+        0x5B => "MetaLeft",
+        0x5C => "MetaRight",
+        _ => return None,
+    })
+}
+
+/// Listens for a global hotkey while recording
+pub struct HotkeyListener {
+    presses: Arc<AtomicU32>,
+    /// Thread running the hotkey's message loop, to post WM_QUIT to
+    thread_id: u32,
+    thread_handle: Option<thread::JoinHandle<()>>,
+}
+
+impl HotkeyListener {
+    pub fn start(hotkey: &Hotkey) -> Result<Self> {
+        let presses = Arc::new(AtomicU32::new(0));
+        let (ready_tx, ready_rx) = mpsc::channel();
+
+        let hotkey = *hotkey;
+        let presses_clone = Arc::clone(&presses);
+        let handle = thread::spawn(move || {
+            run_hotkey_loop(hotkey, presses_clone, ready_tx);
+        });
+
+        let thread_id = ready_rx
+            .recv()
+            .context("Hotkey thread exited")?
+            .map_err(|e| anyhow::anyhow!("Failed to register hotkey {}: {}", hotkey, e))?;
+
+        Ok(Self {
+            presses,
+            thread_id,
+            thread_handle: Some(handle),
+        })
+    }
+
+    /// Whether the hotkey was pressed since the last call
+    pub fn take_press(&self) -> bool {
+        self.presses.swap(0, Ordering::SeqCst) > 0
+    }
+}
+
+impl Drop for HotkeyListener {
+    fn drop(&mut self) {
+        // SAFETY: posting to a thread ID has no memory-safety preconditions
+        let _ = unsafe { PostThreadMessageW(self.thread_id, WM_QUIT, WPARAM(0), LPARAM(0)) };
+        if let Some(handle) = self.thread_handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+/// Register the hotkey for this thread and count WM_HOTKEY until WM_QUIT
+fn run_hotkey_loop(
+    hotkey: Hotkey,
+    presses: Arc<AtomicU32>,
+    ready: mpsc::Sender<std::result::Result<u32, String>>,
+) {
+    let modifiers = hotkey_modifiers(&hotkey);
+
+    // SAFETY: the hotkey is unregistered on this thread before it exits
+    unsafe {
+        if let Err(e) = RegisterHotKey(HWND::default(), 1, modifiers, virtual_key(hotkey.key)) {
+            let _ = ready.send(Err(e.to_string()));
+            return;
+        }
+        let _ = ready.send(Ok(GetCurrentThreadId()));
+
+        // Hotkeys without a window are posted to this thread's queue
+        let mut msg = MSG::default();
+        while GetMessageW(&mut msg, HWND::default(), 0, 0).0 > 0 {
+            if msg.message == WM_HOTKEY {
+                presses.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+
+        let _ = UnregisterHotKey(HWND::default(), 1);
+    }
+}
+
+/// RegisterHotKey modifier flags of a hotkey
+fn hotkey_modifiers(hotkey: &Hotkey) -> HOT_KEY_MODIFIERS {
+    [
+        (hotkey.ctrl, MOD_CONTROL),
+        (hotkey.alt, MOD_ALT),
+        (hotkey.shift, MOD_SHIFT),
+        (hotkey.meta, MOD_WIN),
+    ]
+    .into_iter()
+    .filter(|(on, _)| *on)
+    // Holding the key doesn't repeat it
+    .fold(MOD_NOREPEAT, |mask, (_, flag)| mask | flag)
+}
+
+/// Virtual-key code of a hotkey key
+fn virtual_key(key: HotkeyKey) -> u32 {
+    match key {
+        // VK_A..VK_Z and VK_0..VK_9 are the uppercase ASCII codes
+        HotkeyKey::Char(c) => c.to_ascii_uppercase() as u32,
+        // VK_F1 onwards
+        HotkeyKey::Function(n) => 0x70 + n as u32 - 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::windows::Win32::UI::WindowsAndMessaging::IDC_WAIT;
+
+    #[test]
+    fn test_key_code() {
+        // The same scancodes name different keys when extended
+        assert_eq!(key_code(0x1C, false), Some("Enter"));
+        assert_eq!(key_code(0x1C, true), Some("NumpadEnter"));
+        assert_eq!(key_code(0x1D, false), Some("ControlLeft"));
+        assert_eq!(key_code(0x1D, true), Some("ControlRight"));
+        assert_eq!(key_code(0x48, true), Some("ArrowUp"));
+        assert_eq!(key_code(0x53, true), Some("Delete"));
+        assert_eq!(key_code(0x5B, true), Some("MetaLeft"));
+        assert_eq!(key_code(0x1E, false), Some("KeyA"));
+        assert_eq!(key_code(0x7F, true), None);
+    }
+
+    #[test]
+    fn test_hotkey_modifiers() {
+        let hotkey: Hotkey = "ctrl+shift+r".parse().unwrap();
+        assert_eq!(
+            hotkey_modifiers(&hotkey),
+            MOD_NOREPEAT | MOD_CONTROL | MOD_SHIFT
+        );
+        let hotkey: Hotkey = "cmd+alt+f9".parse().unwrap();
+        assert_eq!(hotkey_modifiers(&hotkey), MOD_NOREPEAT | MOD_ALT | MOD_WIN);
+    }
+
+    #[test]
+    fn test_virtual_key() {
+        assert_eq!(virtual_key(HotkeyKey::Char('r')), 0x52);
+        assert_eq!(virtual_key(HotkeyKey::Char('5')), 0x35);
+        assert_eq!(virtual_key(HotkeyKey::Function(1)), 0x70);
+        assert_eq!(virtual_key(HotkeyKey::Function(12)), 0x7B);
+    }
+
+    #[test]
+    fn test_standard_cursors() {
+        let shape = |id: PCWSTR| {
+            STANDARD_CURSORS
+                .iter()
+                .find(|(cursor, _)| cursor.0 == id.0)
+                .map(|(_, shape)| *shape)
+        };
+        assert_eq!(shape(IDC_ARROW), Some(CursorShape::Arrow));
+        assert_eq!(shape(IDC_IBEAM), Some(CursorShape::IBeam));
+        assert_eq!(shape(IDC_HAND), Some(CursorShape::PointingHand));
+        assert_eq!(shape(IDC_SIZEWE), Some(CursorShape::ResizeHorizontal));
+        assert_eq!(shape(IDC_SIZENS), Some(CursorShape::ResizeVertical));
+        assert_eq!(shape(IDC_WAIT), None);
+    }
+}
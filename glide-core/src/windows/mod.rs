@@ -0,0 +1,19 @@
+//! Windows support module
+//!
+//! Provides display enumeration, window enumeration, cursor tracking,
+//! and screen capture for Windows 10 and later, using DXGI Desktop
+//! Duplication for capture and a low-level mouse hook for the cursor.
+
+pub mod capture;
+pub mod display;
+pub mod event_tap;
+pub mod window;
+
+// Re-export commonly used types
+pub use capture::{
+    find_display, find_window, start_app_capture, start_display_capture, start_region_capture,
+    start_window_capture, CaptureConfig, CaptureSession, CapturedFrame,
+};
+pub use display::{cursor_size_multiplier, keyboard_info, list_displays, DisplayInfo};
+pub use event_tap::{CursorTracker, HotkeyListener};
+pub use window::{list_windows, select_on_screen, WindowInfo};
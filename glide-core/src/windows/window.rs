@@ -0,0 +1,151 @@
+//! Windows window enumeration using EnumWindows and DWM frame bounds
+
+use crate::recording::select::Selection;
+use crate::windows::display::ensure_dpi_aware;
+use ::windows::core::PWSTR;
+use ::windows::Win32::Foundation::{CloseHandle, BOOL, HWND, LPARAM, RECT, TRUE};
+use ::windows::Win32::Graphics::Dwm::{
+    DwmGetWindowAttribute, DWMWA_CLOAKED, DWMWA_EXTENDED_FRAME_BOUNDS,
+};
+use ::windows::Win32::System::Threading::{
+    OpenProcess, QueryFullProcessImageNameW, PROCESS_NAME_WIN32, PROCESS_QUERY_LIMITED_INFORMATION,
+};
+use ::windows::Win32::UI::WindowsAndMessaging::{
+    EnumWindows, GetWindow, GetWindowTextLengthW, GetWindowTextW, GetWindowThreadProcessId,
+    IsIconic, IsWindowVisible, GW_OWNER,
+};
+use anyhow::{Context, Result};
+use std::path::Path;
+
+pub struct WindowInfo {
+    pub id: u32,
+    pub name: String,
+    pub owner: String,
+    pub bounds: (i32, i32, u32, u32), // x, y, width, height
+}
+
+pub fn list_windows() -> Result<Vec<WindowInfo>> {
+    ensure_dpi_aware();
+
+    let mut handles: Vec<HWND> = Vec::new();
+    // SAFETY: the callback only runs during EnumWindows, while `handles` is borrowed
+    unsafe {
+        EnumWindows(
+            Some(collect_window),
+            LPARAM(&mut handles as *mut Vec<HWND> as isize),
+        )
+        .context("Failed to enumerate windows")?;
+    }
+
+    let mut result = Vec::new();
+    for hwnd in handles {
+        // SAFETY: handles came from EnumWindows; a window closing meanwhile just fails the queries
+        unsafe {
+            if !is_capturable(hwnd) {
+                continue;
+            }
+            let name = window_title(hwnd);
+            let Some(bounds) = frame_bounds(hwnd) else {
+                continue;
+            };
+
+            // Filter out small windows (like toolbars, etc.)
+            if bounds.2 > 100 && bounds.3 > 100 && !name.is_empty() {
+                result.push(WindowInfo {
+                    // Window handles only use their low 32 bits
+                    id: hwnd.0 as usize as u32,
+                    name,
+                    owner: process_name(hwnd).unwrap_or_default(),
+                    bounds,
+                });
+            }
+        }
+    }
+
+    Ok(result)
+}
+
+/// `--select`'s overlay is macOS and X11 only so far
+pub fn select_on_screen() -> Result<Option<Selection>> {
+    anyhow::bail!(
+        "--select isn't supported on Windows yet; use --pick, or --region with bounds from `glide list windows`"
+    )
+}
+
+unsafe extern "system" fn collect_window(hwnd: HWND, lparam: LPARAM) -> BOOL {
+    let handles = &mut *(lparam.0 as *mut Vec<HWND>);
+    handles.push(hwnd);
+    TRUE
+}
+
+/// Visible, unowned top-level windows that aren't minimized or cloaked
+/// (hidden UWP frames and windows on other virtual desktops)
+unsafe fn is_capturable(hwnd: HWND) -> bool {
+    if !IsWindowVisible(hwnd).as_bool() || IsIconic(hwnd).as_bool() {
+        return false;
+    }
+    if GetWindow(hwnd, GW_OWNER).is_ok_and(|owner| !owner.is_invalid()) {
+        return false;
+    }
+    let mut cloaked: u32 = 0;
+    let cloaked_ok = DwmGetWindowAttribute(
+        hwnd,
+        DWMWA_CLOAKED,
+        &mut cloaked as *mut u32 as *mut _,
+        std::mem::size_of::<u32>() as u32,
+    )
+    .is_ok();
+    !(cloaked_ok && cloaked != 0)
+}
+
+unsafe fn window_title(hwnd: HWND) -> String {
+    let length = GetWindowTextLengthW(hwnd);
+    if length <= 0 {
+        return String::new();
+    }
+    let mut buffer = vec![0u16; length as usize + 1];
+    let copied = GetWindowTextW(hwnd, &mut buffer);
+    String::from_utf16_lossy(&buffer[..copied.max(0) as usize])
+}
+
+/// Visible bounds in physical pixels, without the invisible resize borders
+/// that GetWindowRect includes on Windows 10 and later
+unsafe fn frame_bounds(hwnd: HWND) -> Option<(i32, i32, u32, u32)> {
+    let mut rect = RECT::default();
+    DwmGetWindowAttribute(
+        hwnd,
+        DWMWA_EXTENDED_FRAME_BOUNDS,
+        &mut rect as *mut RECT as *mut _,
+        std::mem::size_of::<RECT>() as u32,
+    )
+    .ok()?;
+    Some((
+        rect.left,
+        rect.top,
+        (rect.right - rect.left).max(0) as u32,
+        (rect.bottom - rect.top).max(0) as u32,
+    ))
+}
+
+/// Executable name of the window's process, e.g. "Code" for Code.exe
+unsafe fn process_name(hwnd: HWND) -> Option<String> {
+    let mut pid = 0u32;
+    GetWindowThreadProcessId(hwnd, Some(&mut pid as *mut u32));
+    let process = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid).ok()?;
+
+    let mut buffer = [0u16; 1024];
+    let mut size = buffer.len() as u32;
+    let queried = QueryFullProcessImageNameW(
+        process,
+        PROCESS_NAME_WIN32,
+        PWSTR(buffer.as_mut_ptr()),
+        &mut size,
+    );
+    let _ = CloseHandle(process);
+    queried.ok()?;
+
+    let path = String::from_utf16_lossy(&buffer[..size as usize]);
+    Path::new(&path)
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+}
@@ -1,82 +1,816 @@
-use clap::{Parser, Subcommand, ValueEnum};
+use clap::{ArgAction, Args, Parser, Subcommand, ValueEnum};
+use glide_core::cursor_types::KeyCapture;
+use glide_core::processing::captions::CaptionPosition;
+use glide_core::processing::chapters::parse_chapter_time;
+use glide_core::processing::click_callouts::CalloutStyle;
+use glide_core::processing::cursor::CursorStyle;
+use glide_core::processing::frames::{parse_output_fps, OutputFormat, OutputFps};
+use glide_core::processing::heatmap::Overlay;
+use glide_core::processing::idle::parse_speed;
+use glide_core::processing::motion_blur::{parse_strength, MotionBlurMode, MotionBlurQuality};
+use glide_core::processing::music::parse_volume;
+use glide_core::processing::palette::Palette;
+use glide_core::processing::progress::ProgressFormat;
+use glide_core::processing::redact::{parse_blur_region, BlurRegion, RedactStyle};
+use glide_core::processing::timeline::Cut;
+use glide_core::processing::trail::parse_opacity;
+use glide_core::processing::zoom::ZoomOverrides;
+use glide_core::recording::encoder::{Codec, Encoder, Quality};
+use glide_core::recording::rollover::parse_segment_duration;
+use glide_core::recording::{Hotkey, Region};
 use std::path::PathBuf;
+use std::time::Duration;
 
 #[derive(Parser)]
 #[command(name = "glide")]
 #[command(about = "CLI screen recorder for macOS with auto-zoom on clicks")]
 #[command(version)]
+// A later option replaces an earlier one, so the command line overrides config.toml
+#[command(args_override_self = true)]
 pub struct Cli {
     #[command(subcommand)]
     pub command: Commands,
+
+    /// Log more detail: -v for timing and fallback decisions, -vv for everything
+    #[arg(short, long, global = true, action = ArgAction::Count)]
+    pub verbose: u8,
+
+    /// Only log warnings and errors
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    pub quiet: bool,
 }
 
 #[derive(Subcommand)]
+#[allow(clippy::large_enum_variant)] // parsed once at startup
 pub enum Commands {
     /// List available displays or windows
     List {
         #[arg(value_enum)]
         target: ListTarget,
+
+        /// Print a JSON array (IDs, bounds, scale factors, owners) for scripts and GUIs
+        #[arg(long)]
+        json: bool,
+
+        /// Only list windows of apps whose name contains this text (ignoring case)
+        #[arg(long, value_name = "NAME")]
+        app: Option<String>,
     },
 
     /// Record screen or window
     Record {
-        /// Display ID to record
-        #[arg(long, conflicts_with = "window")]
-        display: Option<u32>,
+        /// Display ID to record; repeat to record several displays at once into separate files
+        #[arg(long, conflicts_with_all = ["window", "window_title", "region", "app"])]
+        display: Vec<u32>,
 
         /// Window ID to record
-        #[arg(long, conflicts_with = "display")]
+        #[arg(long, conflicts_with_all = ["display", "window_title", "region", "app"])]
         window: Option<u32>,
 
-        /// Output file path
-        #[arg(short, long)]
-        output: PathBuf,
+        /// Record the window whose title contains this text (ignoring case)
+        #[arg(long, value_name = "TEXT", conflicts_with_all = ["display", "window", "region", "app"])]
+        window_title: Option<String>,
+
+        /// Screen rectangle to record, as X,Y,WxH in screen coordinates (e.g. 0,0,1280x720)
+        #[arg(long, value_name = "X,Y,WxH", conflicts_with_all = ["display", "window", "window_title", "app"])]
+        region: Option<Region>,
+
+        /// Record every window of this app (macOS), including ones it opens while recording
+        #[arg(long, value_name = "NAME", conflicts_with_all = ["display", "window", "window_title", "region"])]
+        app: Option<String>,
+
+        /// Choose the display or window from a searchable list in the terminal
+        #[arg(long, conflicts_with_all = ["display", "window", "window_title", "region", "app"])]
+        pick: bool,
+
+        /// Drag out the rectangle to record on screen, or click a window (macOS, X11)
+        #[arg(long, conflicts_with_all = ["display", "window", "window_title", "region", "app", "pick"])]
+        select: bool,
+
+        /// Keep this app's windows out of a display or region recording; repeatable
+        /// (on Linux and Windows they're blacked out where they were when recording started)
+        #[arg(long, value_name = "APP", conflicts_with_all = ["window", "window_title"])]
+        exclude_app: Vec<String>,
+
+        /// Keep this window (ID from `glide list windows`) out of a display or region recording; repeatable
+        #[arg(long, value_name = "ID", conflicts_with_all = ["window", "window_title"])]
+        exclude_window: Vec<u32>,
+
+        /// Output file path (default: named from the date in `output_dir`, or ~/Movies/glide)
+        // A recording has one encoder and one output, and --rtmp's stream
+        // takes the file's place, so there's no file to name
+        #[arg(short, long, conflicts_with = "rtmp")]
+        output: Option<PathBuf>,
 
         /// Capture system cursor in video (default: false, custom cursor rendered during processing)
         #[arg(long)]
         capture_system_cursor: bool,
+
+        /// Stream live to an RTMP endpoint instead of writing a file (e.g. rtmp://host/app/key)
+        #[arg(long, value_name = "URL")]
+        rtmp: Option<String>,
+
+        /// Letterbox the live stream onto a 1920x1080 canvas of this color (hex)
+        #[arg(long, value_name = "COLOR", requires = "rtmp")]
+        stream_background: Option<String>,
+
+        /// Encrypt the video and metadata as they're written (passphrase prompt, or --recipient)
+        #[arg(long, conflicts_with = "rtmp")]
+        encrypt: bool,
+
+        /// Encrypt to this age public key (age1...) instead of a passphrase
+        #[arg(long, value_name = "AGE_KEY", requires = "encrypt")]
+        recipient: Option<String>,
+
+        /// Record microphone audio from this device ("default" on Linux, an index or name on macOS)
+        #[arg(long, value_name = "DEVICE")]
+        mic: Option<String>,
+
+        /// Record system audio (macOS; mixed with --mic if both are given)
+        #[arg(long)]
+        system_audio: bool,
+
+        /// Global key combo that stops the recording from any app (e.g. ctrl+shift+r)
+        #[arg(long, value_name = "COMBO")]
+        hotkey: Option<Hotkey>,
+
+        /// Don't start recording until the hotkey is pressed
+        #[arg(long, requires = "hotkey")]
+        wait_for_hotkey: bool,
+
+        /// Global key combo that marks the moment, for --marker-chapters and friends when processing
+        #[arg(long, value_name = "COMBO")]
+        mark_hotkey: Option<Hotkey>,
+
+        /// Don't start recording until `glide start` (or a start request on the control socket)
+        #[arg(long, conflicts_with = "wait_for_hotkey")]
+        wait_for_start: bool,
+
+        /// Record key presses alongside the cursor (off, private or full)
+        #[arg(long, value_enum, value_name = "MODE", default_value = "off")]
+        keys: KeyCapture,
+
+        /// Stop automatically after this many seconds of recording
+        #[arg(long, value_name = "SECONDS", value_parser = clap::value_parser!(u64).range(1..))]
+        duration: Option<u64>,
+
+        /// Start a new file after this much recording (e.g. 10m), writing out_001.mp4, out_002.mp4, ...
+        #[arg(long, value_name = "LENGTH", value_parser = parse_segment_duration, conflicts_with = "rtmp")]
+        segment_duration: Option<Duration>,
+
+        /// Video codec for the recorded file (h265, vp9 and av1 give smaller files)
+        #[arg(long, value_enum, default_value = "h264", conflicts_with = "rtmp")]
+        codec: Codec,
+
+        /// Compression of the recorded file (lossless keeps the exact pixels, in much larger files)
+        #[arg(long, value_enum, default_value = "standard", conflicts_with_all = ["rtmp", "codec", "encoder"])]
+        quality: Quality,
+
+        /// Encoder for the recorded file: a GPU encoder takes load off the CPU (default: auto)
+        #[arg(long, value_enum, default_value = "auto", conflicts_with = "rtmp")]
+        encoder: Encoder,
+
+        /// Record in the background and return; stop it with `glide stop` (macOS and Linux)
+        #[arg(long, conflicts_with_all = ["pick", "select"])]
+        detach: bool,
+    },
+
+    /// Start the recording waiting with `record --wait-for-start`
+    Start,
+
+    /// Mark this moment in the running recording
+    Mark {
+        /// What the marker is for, used as its chapter title
+        name: Option<String>,
+    },
+
+    /// Stop the running recording (from `record --detach` or another terminal)
+    /// and wait for it to be saved
+    Stop,
+
+    /// Show whether a recording is running, what it's writing and for how long
+    Status {
+        /// Print the status as JSON (null when not recording)
+        #[arg(long)]
+        json: bool,
     },
 
     /// Process recorded video with effects
     Process {
-        /// Input video file
+        /// Input video file, or a bundle from `glide pack`
         input: PathBuf,
 
-        /// Output video file
+        /// Output video file (or directory for image-sequence formats)
         #[arg(short, long)]
         output: PathBuf,
 
-        /// Background color (hex) or image path
-        #[arg(long)]
-        background: Option<String>,
+        #[command(flatten)]
+        args: ProcessArgs,
+    },
 
-        /// Trim N seconds from the start of the video
-        #[arg(long, value_name = "SECONDS")]
-        trim_start: Option<f64>,
+    /// Render one frame of `process`'s output, with every effect, as a PNG
+    Preview {
+        /// Input video file, or a bundle from `glide pack`
+        input: PathBuf,
 
-        /// Trim N seconds from the end of the video
-        #[arg(long, value_name = "SECONDS")]
-        trim_end: Option<f64>,
+        /// Output time of the frame, in seconds, m:ss or h:mm:ss
+        #[arg(long, value_name = "TIME", value_parser = parse_chapter_time)]
+        at: f64,
 
-        /// Cursor scale factor (default: 2.0)
-        #[arg(long, default_value = "2.0")]
-        cursor_scale: f64,
+        /// Output image file (PNG)
+        #[arg(short, long)]
+        output: PathBuf,
 
-        /// Seconds of inactivity before cursor fades (default: 2.0)
-        #[arg(long, default_value = "2.0")]
-        cursor_timeout: f64,
+        #[command(flatten)]
+        args: ProcessArgs,
+    },
+
+    /// Time capture, each processing effect and encoding on this machine,
+    /// using a synthesized recording
+    Bench {
+        /// Length of the synthesized recording
+        #[arg(long, value_name = "SECONDS", default_value = "5", value_parser = clap::value_parser!(u64).range(1..=60))]
+        duration: u64,
+    },
 
-        /// Disable custom cursor rendering
+    /// Show click and cursor statistics for a recording
+    Stats {
+        /// Recorded video file (with its .json metadata alongside)
+        input: PathBuf,
+
+        /// Also write a cursor-position heatmap over a representative frame (PNG)
+        #[arg(long, value_name = "PATH")]
+        heatmap: Option<PathBuf>,
+    },
+
+    /// Print what a recording contains: video properties, source, cursor
+    /// events and timing
+    Inspect {
+        /// Recorded video file (with its .json metadata alongside)
+        input: PathBuf,
+
+        #[command(flatten)]
+        zoom: ZoomOverrides,
+    },
+
+    /// Delete, retime or retarget the detected zooms before processing
+    Edit {
+        /// Recorded video file (with its .json metadata alongside)
+        input: PathBuf,
+
+        #[command(flatten)]
+        zoom: ZoomOverrides,
+    },
+
+    /// Plot zoom level and camera position over time, with click markers
+    ZoomChart {
+        /// Recorded video file (with its .json metadata alongside)
+        input: PathBuf,
+
+        /// Output chart (PNG)
+        #[arg(short, long)]
+        output: PathBuf,
+
+        /// Chart the gentler camera used by `process --reduced-motion`
         #[arg(long)]
-        no_cursor: bool,
+        reduced_motion: bool,
 
-        /// Disable motion blur during zoom/pan transitions
+        /// Chart the still camera of `process --no-zoom`
         #[arg(long)]
-        no_motion_blur: bool,
+        no_zoom: bool,
+
+        /// Chart the camera of `process --follow-cursor`
+        #[arg(long, conflicts_with = "no_zoom")]
+        follow_cursor: bool,
+
+        /// Chart the camera of `process --zoom-on-markers`
+        #[arg(long, conflicts_with_all = ["no_zoom", "follow_cursor"])]
+        zoom_on_markers: bool,
+
+        #[command(flatten)]
+        zoom: ZoomOverrides,
+    },
+
+    /// Bundle a recording and its metadata into one file for processing elsewhere
+    Pack {
+        /// Recorded video file (with its .json metadata alongside)
+        input: PathBuf,
 
-        /// Disable click highlight effect (expanding ring on clicks)
+        /// Bundle path (default: <input>.glide)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// Extract and validate a bundle made by `glide pack`
+    Unpack {
+        /// Bundle file (.glide)
+        bundle: PathBuf,
+
+        /// Directory to extract into (default: next to the bundle)
+        #[arg(short = 'd', long, value_name = "DIR")]
+        dir: Option<PathBuf>,
+
+        /// Overwrite existing files
         #[arg(long)]
-        no_click_highlight: bool,
+        force: bool,
+    },
+
+    /// Join recordings and their metadata into one, to process several takes
+    /// as a single video
+    Concat {
+        /// Recorded video files, in order (each with its .json metadata alongside)
+        #[arg(required = true, num_args = 2..)]
+        inputs: Vec<PathBuf>,
+
+        /// Joined video file
+        #[arg(short, long)]
+        output: PathBuf,
+    },
+
+    /// Find, open and tidy indexed recordings
+    Library {
+        #[command(subcommand)]
+        action: LibraryAction,
+    },
+
+    /// Export or replace a recording's cursor events as CSV
+    Events {
+        #[command(subcommand)]
+        action: EventsAction,
+    },
+}
+
+/// Effect and output options for `glide process`, which `glide preview` shares
+#[derive(Args)]
+pub struct ProcessArgs {
+    /// Background: hex color, image or video path, blur:<image> for a blurred image, or auto-blur for the first frame blurred
+    #[arg(long)]
+    pub background: Option<String>,
+
+    /// Minimum space around the content in pixels (default: 100)
+    #[arg(
+        long,
+        value_name = "PIXELS",
+        default_value = "100",
+        value_parser = clap::value_parser!(u32).range(0..=400)
+    )]
+    pub padding: u32,
+
+    /// Radius of the content's rounded corners in pixels (0 = square)
+    #[arg(long, value_name = "PIXELS", default_value = "12")]
+    pub corner_radius: u32,
+
+    /// How far the shadow under the content spreads, in pixels
+    #[arg(
+        long,
+        value_name = "PIXELS",
+        default_value = "20",
+        conflicts_with = "no_shadow"
+    )]
+    pub shadow_size: u32,
+
+    /// Opacity of the shadow at its darkest, 0 to 1
+    #[arg(
+        long,
+        value_name = "OPACITY",
+        default_value = "0.31",
+        value_parser = parse_opacity,
+        conflicts_with = "no_shadow"
+    )]
+    pub shadow_opacity: f64,
+
+    /// Don't draw a shadow under the content
+    #[arg(long)]
+    pub no_shadow: bool,
+
+    /// 9:16 output (1080x1920) for social video: wide content fills the height
+    /// and the view follows the cursor across it
+    #[arg(long, conflicts_with = "export_animation")]
+    pub vertical: bool,
+
+    /// Trim N seconds from the start of the video
+    #[arg(long, value_name = "SECONDS")]
+    pub trim_start: Option<f64>,
+
+    /// Trim N seconds from the end of the video
+    #[arg(long, value_name = "SECONDS")]
+    pub trim_end: Option<f64>,
+
+    /// Trim inactivity before the first and after the last click, keypress or cursor move
+    #[arg(long, conflicts_with_all = ["trim_start", "trim_end"])]
+    pub auto_trim: bool,
+
+    /// Remove a span of the recording, in seconds (repeatable): --cut 10.5-14
+    #[arg(long, value_name = "START-END", conflicts_with = "export_animation")]
+    pub cut: Vec<Cut>,
+
+    /// File of spans to remove, one START-END per line
+    #[arg(long, value_name = "PATH", conflicts_with = "export_animation")]
+    pub cuts: Option<PathBuf>,
+
+    /// Remove what's between each pair of markers dropped while recording (1st to 2nd, 3rd to 4th, ...)
+    #[arg(long, conflicts_with = "export_animation")]
+    pub cut_between_markers: bool,
+
+    /// Cursor scale factor (default: 2.0 times the recorded OS cursor size)
+    #[arg(long)]
+    pub cursor_scale: Option<f64>,
+
+    /// Seconds of inactivity before cursor fades (default: 2.0)
+    #[arg(long, default_value = "2.0")]
+    pub cursor_timeout: f64,
+
+    /// Disable custom cursor rendering
+    #[arg(long)]
+    pub no_cursor: bool,
+
+    /// Draw a fading trail this many seconds long behind the cursor
+    #[arg(long, value_name = "SECONDS", conflicts_with = "no_cursor")]
+    pub cursor_trail: Option<f64>,
+
+    /// Opacity of the cursor trail where it meets the cursor, 0 to 1
+    #[arg(
+        long,
+        value_name = "OPACITY",
+        default_value = "0.5",
+        value_parser = parse_opacity,
+        requires = "cursor_trail"
+    )]
+    pub cursor_trail_opacity: f64,
+
+    /// Cursor color style for visibility on any background
+    #[arg(long, value_enum, default_value = "default")]
+    pub cursor_style: CursorStyle,
+
+    /// White outline thickness around the cursor in pixels (0 = none)
+    #[arg(long, value_name = "PIXELS", default_value = "0")]
+    pub cursor_outline: u32,
+
+    /// Disable motion blur during zoom/pan transitions
+    #[arg(long)]
+    pub no_motion_blur: bool,
+
+    /// How motion blur is made: smear each frame, or average the camera's positions in between (slower)
+    #[arg(
+        long,
+        value_enum,
+        default_value = "screen",
+        conflicts_with = "no_motion_blur"
+    )]
+    pub motion_blur_mode: MotionBlurMode,
+
+    /// Motion blur samples per pixel: low renders fastest, high is smoothest
+    #[arg(
+        long,
+        value_enum,
+        default_value = "high",
+        conflicts_with = "no_motion_blur"
+    )]
+    pub motion_blur_quality: MotionBlurQuality,
+
+    /// Motion blur strength as a multiple of the default, 0 to 4
+    #[arg(
+        long,
+        value_name = "FACTOR",
+        default_value = "1.0",
+        value_parser = parse_strength,
+        conflicts_with = "no_motion_blur"
+    )]
+    pub motion_blur_strength: f64,
+
+    /// Samples per blurred pixel, overriding --motion-blur-quality
+    #[arg(
+        long,
+        value_name = "N",
+        value_parser = clap::value_parser!(u32).range(2..=64),
+        conflicts_with = "no_motion_blur"
+    )]
+    pub motion_blur_samples: Option<u32>,
+
+    /// Zoom speed, in zoom levels per second, below which nothing is blurred (pans scale it by 500 px/s)
+    #[arg(
+        long,
+        value_name = "SPEED",
+        default_value = "0.05",
+        conflicts_with = "no_motion_blur"
+    )]
+    pub motion_blur_threshold: f64,
+
+    /// Output frame rate, or "source" for the recording's own (default: 60)
+    #[arg(long, value_name = "FPS", default_value = "60", value_parser = parse_output_fps)]
+    pub output_fps: OutputFps,
+
+    /// Make up frames in between the source's by motion-compensated interpolation, so 30fps captures play smoothly at 60fps (slow)
+    #[arg(long, conflicts_with = "fast")]
+    pub interpolate: bool,
+
+    /// Keep the camera still: no zooming or panning on clicks
+    #[arg(long)]
+    pub no_zoom: bool,
+
+    /// Stay zoomed in (--zoom-level) and pan to keep the cursor in view, ignoring clicks
+    #[arg(long, conflicts_with_all = ["no_zoom", "export_animation"])]
+    pub follow_cursor: bool,
+
+    /// Zoom in at each marker dropped while recording, as if the cursor had clicked there
+    #[arg(long, conflicts_with_all = ["no_zoom", "follow_cursor"])]
+    pub zoom_on_markers: bool,
+
+    /// Play stretches without clicks, typing or much cursor movement faster (default: 4x)
+    #[arg(
+        long,
+        value_name = "FACTOR",
+        num_args = 0..=1,
+        default_missing_value = "4",
+        value_parser = parse_speed,
+        conflicts_with = "export_animation"
+    )]
+    pub speed_up_idle: Option<f64>,
+
+    /// Seconds without activity before a stretch is sped up
+    #[arg(
+        long,
+        value_name = "SECONDS",
+        default_value = "3",
+        requires = "speed_up_idle"
+    )]
+    pub idle_threshold: f64,
+
+    /// Show a fast-forward badge while idle stretches are sped up
+    #[arg(long, requires = "speed_up_idle")]
+    pub fast_forward_indicator: bool,
+
+    /// Disable click highlight effect (expanding ring on clicks)
+    #[arg(long)]
+    pub no_click_highlight: bool,
+
+    /// Add a soft click sound at each click, mixed into any recorded audio
+    #[arg(long)]
+    pub click_sound: bool,
+
+    /// Music to loop under the output, ducked while recorded narration plays
+    #[arg(long, value_name = "PATH")]
+    pub music: Option<PathBuf>,
+
+    /// Volume of the music, from 0 to 1
+    #[arg(long, value_name = "VOLUME", default_value = "0.2", value_parser = parse_volume, requires = "music")]
+    pub music_volume: f64,
+
+    /// Highlight color palette (okabe-ito, ibm and tol are color-blind safe)
+    #[arg(long, value_enum, default_value = "default")]
+    pub palette: Palette,
+
+    /// Draw a box or arrow around each clicked element (uses accessibility bounds on macOS)
+    #[arg(long, value_enum, value_name = "STYLE")]
+    pub click_callout: Option<CalloutStyle>,
+
+    /// Only call out these clicks, numbered from 1 (e.g. 1,3,4)
+    #[arg(
+        long,
+        value_name = "N,...",
+        value_delimiter = ',',
+        requires = "click_callout"
+    )]
+    pub callout_clicks: Vec<usize>,
+
+    /// Show a numbered badge at each click and write a matching <output>.steps.txt
+    #[arg(long)]
+    pub step_numbers: bool,
+
+    /// Add a chapter to the output at an output time (repeatable): --chapter 1:12 Settings
+    #[arg(long, num_args = 2, value_names = ["TIME", "TITLE"])]
+    pub chapter: Vec<String>,
+
+    /// Add a chapter at each burst of clicks after a pause
+    #[arg(long, conflicts_with = "chapter")]
+    pub auto_chapters: bool,
+
+    /// Add a chapter at each marker dropped while recording, titled with its name
+    #[arg(long, conflicts_with_all = ["chapter", "auto_chapters"])]
+    pub marker_chapters: bool,
+
+    /// Dim everything except a soft circle around the cursor
+    #[arg(long)]
+    pub spotlight: bool,
+
+    /// Radius of the spotlight's circle in output pixels
+    #[arg(
+        long,
+        value_name = "PIXELS",
+        default_value = "220",
+        requires = "spotlight"
+    )]
+    pub spotlight_radius: f64,
+
+    /// Composite a data layer over the content (heatmap: where the cursor has been so far)
+    #[arg(long, value_enum, value_name = "LAYER")]
+    pub overlay: Option<Overlay>,
+
+    /// Hide a rectangle of the recording (video pixels): x,y,w,h, optionally
+    /// with an output time range (x,y,w,h,5-12 or x,y,w,h,1:05-); repeatable
+    #[arg(long, value_name = "REGION", value_parser = parse_blur_region, conflicts_with = "fast")]
+    pub blur_region: Vec<BlurRegion>,
+
+    /// How to hide --blur-region rectangles and --auto-redact finds
+    #[arg(long, value_enum, value_name = "STYLE", default_value = "pixelate")]
+    pub blur_style: RedactStyle,
+
+    /// Hide text that looks like a secret (emails, API tokens, card numbers),
+    /// found by OCR'ing a frame a second with Tesseract; best effort
+    #[arg(long, conflicts_with = "fast")]
+    pub auto_redact: bool,
+
+    /// Motion-sensitive output: no motion blur, slower easing, crossfades instead of fast pans
+    #[arg(long)]
+    pub reduced_motion: bool,
+
+    #[command(flatten)]
+    pub zoom: ZoomOverrides,
+
+    /// Optimize MP4 for browser playback (faststart, regular keyframes, yuv420p)
+    #[arg(long)]
+    pub web_optimized: bool,
+
+    /// Bundle of settings: tutorial, social, minimal, cinematic, or one from config.toml
+    #[arg(long, value_name = "NAME")]
+    pub preset: Option<String>,
+
+    /// Output format
+    #[arg(long, value_enum, default_value = "mp4")]
+    pub format: OutputFormat,
+
+    /// Video codec for video formats (default: h264, or vp9 for webm)
+    #[arg(long, value_enum)]
+    pub codec: Option<Codec>,
+
+    /// Segment length in seconds for HLS/DASH output
+    #[arg(long, value_name = "SECONDS", default_value = "6")]
+    pub segment_seconds: f64,
+
+    /// Maximum width for animated image formats (default: 960)
+    #[arg(long, value_name = "PIXELS")]
+    pub max_width: Option<u32>,
+
+    /// Maximum frame rate for animated image formats (default: 15)
+    #[arg(long, value_name = "FPS")]
+    pub max_fps: Option<f64>,
+
+    /// Write 16-bit-per-channel PNGs for png-sequence output, for compositors
+    /// that expect them. Frames are still rendered with 8 bits per channel
+    /// and only widened, so this adds no precision.
+    #[arg(long)]
+    pub png_16bit: bool,
+
+    /// Also export the camera path, cursor path and clicks as JSON for web replay
+    #[arg(long, value_name = "PATH")]
+    pub export_animation: Option<PathBuf>,
+
+    /// YAML or TOML file of timed text callouts (start/at, end/duration, text, position, arrow, style)
+    #[arg(long, value_name = "PATH")]
+    pub annotations: Option<PathBuf>,
+
+    /// SRT file of subtitles to burn into the output (cue times are output-video times)
+    #[arg(long, value_name = "PATH")]
+    pub subtitles: Option<PathBuf>,
+
+    /// Font family or font file for subtitles (default: system sans-serif)
+    #[arg(long, value_name = "FONT", requires = "subtitles")]
+    pub caption_font: Option<String>,
+
+    /// Subtitle text size in output pixels
+    #[arg(
+        long,
+        value_name = "PIXELS",
+        default_value = "42",
+        requires = "subtitles"
+    )]
+    pub caption_size: f32,
+
+    /// Subtitle text color (hex)
+    #[arg(
+        long,
+        value_name = "HEX",
+        default_value = "#ffffff",
+        requires = "subtitles"
+    )]
+    pub caption_color: String,
+
+    /// Color of the box behind subtitles (hex)
+    #[arg(
+        long,
+        value_name = "HEX",
+        default_value = "#000000",
+        requires = "subtitles"
+    )]
+    pub caption_background: String,
+
+    /// Opacity of the box behind subtitles, 0 (no box) to 1
+    #[arg(
+        long,
+        value_name = "OPACITY",
+        default_value = "0.6",
+        value_parser = parse_opacity,
+        requires = "subtitles"
+    )]
+    pub caption_background_opacity: f64,
+
+    /// Where subtitles sit; tiktok, reels and shorts clear those apps' controls
+    #[arg(long, value_enum, default_value = "bottom", requires = "subtitles")]
+    pub caption_position: CaptionPosition,
+
+    /// Delete the raw recording and its metadata once the output is verified readable
+    #[arg(long)]
+    pub delete_source_on_success: bool,
+
+    /// Process even if the video doesn't match the recorded checksum/ID in its metadata
+    #[arg(long)]
+    pub ignore_mismatch: bool,
+
+    /// age identity file for decrypting an encrypted recording (default: passphrase)
+    #[arg(long, value_name = "PATH")]
+    pub identity: Option<PathBuf>,
+
+    /// Worker threads for frame processing and FFmpeg (default: all cores)
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+    pub threads: Option<u32>,
+
+    /// Run at this niceness (0-19) so renders don't starve the machine
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(i32).range(0..=19))]
+    pub nice: Option<i32>,
+
+    /// Shorthand for --nice 10
+    #[arg(long, conflicts_with = "nice")]
+    pub low_priority: bool,
+
+    /// Render zoom, compositing and motion blur on the GPU, falling back
+    /// to the CPU if there isn't one
+    #[arg(long)]
+    pub gpu: bool,
+
+    /// Quick preview: FFmpeg does the zooming in one pass, leaving out
+    /// the cursor, click effects, overlays and motion blur
+    #[arg(long, conflicts_with = "gpu")]
+    pub fast: bool,
+
+    /// Show frames in a player window (ffplay or mpv) as they're rendered
+    #[arg(long, conflicts_with = "fast")]
+    pub preview: bool,
+
+    /// How to report progress: a bar, or JSON events on stdout (with the log on stderr)
+    #[arg(long, value_enum, value_name = "FORMAT", default_value = "bar")]
+    pub progress: ProgressFormat,
+}
+
+#[derive(Subcommand)]
+pub enum LibraryAction {
+    /// List indexed recordings, newest first
+    List,
+    /// Open a recording in the default player
+    Open {
+        /// Recording number from `glide library list`
+        number: usize,
+
+        /// Open the latest processed output instead of the raw recording
+        #[arg(long)]
+        processed: bool,
+    },
+    /// Apply the retention policy and forget recordings whose files no longer exist
+    Clean,
+    /// Show or set how long raw captures are kept after recording, once processed
+    Retention {
+        /// Delete raw captures this many days after recording, once processed
+        #[arg(value_name = "DAYS")]
+        days: Option<u32>,
+
+        /// Keep raw captures indefinitely
+        #[arg(long, conflicts_with = "days")]
+        off: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum EventsAction {
+    /// Write the cursor events to a CSV file
+    Export {
+        /// Recorded video file (with its .json metadata alongside)
+        input: PathBuf,
+
+        /// CSV file to write
+        #[arg(short, long)]
+        output: PathBuf,
+    },
+    /// Replace the cursor events with those in a CSV file, creating the
+    /// metadata if the video has none
+    Import {
+        /// CSV file of events (timestamp,type,x,y[,key,modifiers,display,shape])
+        events: PathBuf,
+
+        /// Video to attach the events to
+        input: PathBuf,
+
+        /// Screen points to pixels, for videos without metadata
+        #[arg(long, default_value = "1.0")]
+        scale_factor: f64,
     },
 }
 
@@ -0,0 +1,636 @@
+//! User defaults from `config.toml` in glide's config directory
+//!
+//! Each table is named after a subcommand and holds defaults for its long
+//! options, written without the dashes:
+//!
+//! ```toml
+//! output_dir = "~/Videos/glide"
+//! output_name = "{app}-{date}-{time}"
+//!
+//! [record]
+//! encoder = "nvenc"
+//!
+//! [process]
+//! background = "#1a1a2e"
+//! cursor_scale = 2.5
+//! no_motion_blur = true
+//!
+//! [presets.team]
+//! background = "#0f172a"
+//! cursor_outline = 2
+//! ```
+//!
+//! They become clap defaults, so anything on the command line wins: an
+//! option given there, or one that conflicts with it, leaves the config
+//! value unused. Flags the config file turns on can be turned back off
+//! with `--flag=false`. A `process --preset` sits between the two, over
+//! the config file. `preview` takes the `[process]` defaults and presets
+//! as well, apart from `fast` and `interpolate`.
+
+use anyhow::{Context, Result};
+use chrono::{Local, NaiveDateTime};
+use clap::parser::ValueSource;
+use clap::{ArgAction, ArgMatches};
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+use toml::Value;
+
+/// Top-level key for the directory relative output paths are put in
+const OUTPUT_DIR_KEY: &str = "output_dir";
+
+/// Top-level key for the file name template of recordings made without `-o`
+const OUTPUT_NAME_KEY: &str = "output_name";
+
+/// File name of recordings made without `-o`, unless the config file says
+/// otherwise: `{date}`, `{time}` and `{app}` are filled in
+const DEFAULT_OUTPUT_NAME: &str = "recording-{date}-{time}";
+
+/// `process` options that shape the whole render, which a preview refuses:
+/// the `[process]` defaults and presets don't pass them on to it
+const WHOLE_RENDER_KEYS: [&str; 2] = ["fast", "interpolate"];
+
+/// Top-level table of custom `process` presets
+const PRESETS_KEY: &str = "presets";
+
+/// Presets that ship with glide, in the config file's format
+const BUILTIN_PRESETS: &str = r##"
+# Screencasts that teach: big, outlined cursor and numbered steps
+[tutorial]
+background = "#1e1e2e"
+cursor_scale = 2.5
+cursor_outline = 2
+step_numbers = true
+zoom_level = 2.0
+zoom_hold = 5.0
+zoom_cluster = 400
+
+# Feeds that autoplay: large cursor, browser-safe MP4
+[social]
+background = "#0f172a"
+cursor_scale = 3.0
+zoom_level = 2.2
+web_optimized = true
+format = "mp4"
+
+# Just the content: no blur, rings or extra decoration
+[minimal]
+background = "#ffffff"
+cursor_scale = 1.5
+zoom_level = 1.4
+no_motion_blur = true
+no_click_highlight = true
+
+# Dark frame, no click rings, cursor out of the way when idle
+[cinematic]
+background = "#0b0b0f"
+cursor_timeout = 1.0
+no_click_highlight = true
+zoom_ease_in = 1.0
+zoom_ease_out = 1.4
+zoom_easing = "sine"
+pan_easing = "sine"
+"##;
+
+#[derive(Debug, Default)]
+pub struct Config {
+    /// Relative `-o` paths are resolved against this
+    output_dir: Option<PathBuf>,
+    /// Template for recordings made without `-o`
+    output_name: Option<String>,
+    /// Subcommand name -> option defaults
+    commands: toml::value::Table,
+    /// Custom preset name -> `process` options
+    presets: toml::value::Table,
+}
+
+impl Config {
+    /// Location of the config file
+    pub fn path() -> Result<PathBuf> {
+        let config_dir = dirs::config_dir().context("Could not determine config directory")?;
+        Ok(config_dir.join("glide").join("config.toml"))
+    }
+
+    /// Load the config file; a missing file means no defaults
+    pub fn load() -> Result<Self> {
+        let path = Self::path()?;
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let text = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        Self::parse(&text).with_context(|| format!("Invalid config file {}", path.display()))
+    }
+
+    fn parse(text: &str) -> Result<Self> {
+        let mut commands: toml::value::Table = toml::from_str(text)?;
+        let output_dir = match commands.remove(OUTPUT_DIR_KEY) {
+            Some(Value::String(dir)) => Some(expand_home(&dir)),
+            Some(_) => anyhow::bail!("{} must be a path", OUTPUT_DIR_KEY),
+            None => None,
+        };
+        let output_name = match commands.remove(OUTPUT_NAME_KEY) {
+            Some(Value::String(name)) => Some(name),
+            Some(_) => anyhow::bail!("{} must be a file name template", OUTPUT_NAME_KEY),
+            None => None,
+        };
+        let presets = match commands.remove(PRESETS_KEY) {
+            Some(Value::Table(presets)) => presets,
+            Some(_) => anyhow::bail!("{} must be a table of presets", PRESETS_KEY),
+            None => Default::default(),
+        };
+        Ok(Self {
+            output_dir,
+            output_name,
+            commands,
+            presets,
+        })
+    }
+
+    /// Parse `args` with `command`, the CLI, its subcommand's defaults
+    /// filling in what the command line leaves out. Errors from clap itself
+    /// come back as a `clap::Error` to print and exit with.
+    pub fn matches(&self, args: Vec<OsString>, command: clap::Command) -> Result<ArgMatches> {
+        for (name, table) in &self.commands {
+            if command.find_subcommand(name).is_none() {
+                anyhow::bail!(
+                    "Config file has a [{}] table, but there's no such command",
+                    name
+                );
+            }
+            if !table.is_table() {
+                anyhow::bail!("Config file: {} should be a [{}] table", name, name);
+            }
+        }
+
+        // Parsed once as given, to find the subcommand (after any global
+        // flags) and what the command line sets. Any flag may be turned off
+        // here; the real parse below only allows it for the config's.
+        let lenient = command
+            .clone()
+            .mut_subcommands(|subcommand| subcommand.mut_args(optional_bool));
+        let Ok(matches) = lenient.try_get_matches_from(&args) else {
+            // Help, or an error that the real parse gives too
+            return Ok(command.try_get_matches_from(args)?);
+        };
+        let Some((name, given)) = matches.subcommand() else {
+            return Ok(command.try_get_matches_from(args)?);
+        };
+        let subcommand = command
+            .find_subcommand(name)
+            .expect("matched subcommands exist");
+
+        // A preview shows what `process` would make, so it takes those defaults too
+        let tables = match name {
+            "preview" => vec!["process", name],
+            _ => vec![name],
+        };
+        let mut defaults = Vec::new();
+        for table in tables {
+            if let Some(Value::Table(options)) = self.commands.get(table) {
+                defaults.extend(option_defaults(subcommand, options).context("Config file")?);
+            }
+        }
+        if matches!(name, "process" | "preview") {
+            // Named on the command line, or else in the config file
+            let preset = match given.value_source("preset") {
+                Some(ValueSource::CommandLine) => given.get_one::<String>("preset").cloned(),
+                _ => [name, "process"]
+                    .iter()
+                    .find_map(|table| self.commands.get(*table)?.get("preset"))
+                    .and_then(Value::as_str)
+                    .map(String::from),
+            };
+            if let Some(preset) = preset {
+                let options = self.preset(&preset)?;
+                defaults.extend(
+                    option_defaults(subcommand, &options)
+                        .with_context(|| format!("Preset '{}'", preset))?,
+                );
+            }
+        }
+
+        if name == "preview" {
+            defaults.retain(|(long, _)| !WHOLE_RENDER_KEYS.contains(&long.as_str()));
+        }
+
+        // The command line wins, over the option itself and anything it
+        // conflicts with (declared on either side)
+        let on_command_line = |arg: &clap::Arg| {
+            given.value_source(arg.get_id().as_str()) == Some(ValueSource::CommandLine)
+        };
+        let conflicts = |a: &clap::Arg, b: &clap::Arg| {
+            subcommand.get_arg_conflicts_with(a).contains(&b)
+                || subcommand.get_arg_conflicts_with(b).contains(&a)
+        };
+        let defaults: Vec<_> = defaults
+            .into_iter()
+            .map(|(long, values)| {
+                let arg = find_arg(subcommand, &long).expect("checked by option_defaults");
+                let overridden = on_command_line(arg)
+                    || subcommand
+                        .get_arguments()
+                        .any(|other| on_command_line(other) && conflicts(arg, other));
+                (arg.get_id().clone(), (!overridden).then_some(values))
+            })
+            .collect();
+        let name = name.to_string();
+        let command = command.mut_subcommand(&name, |mut subcommand| {
+            // Later defaults (presets) replace earlier ones
+            for (id, values) in defaults {
+                subcommand = subcommand.mut_arg(id, |arg| set_default(arg, values));
+            }
+            subcommand
+        });
+        Ok(command.try_get_matches_from(args)?)
+    }
+
+    /// A custom preset, or else a built-in one
+    fn preset(&self, name: &str) -> Result<toml::value::Table> {
+        if let Some(preset) = self.presets.get(name) {
+            return preset.as_table().cloned().with_context(|| {
+                format!("Config file: [{}.{}] should be a table", PRESETS_KEY, name)
+            });
+        }
+        let builtin: toml::value::Table =
+            toml::from_str(BUILTIN_PRESETS).expect("built-in presets are valid TOML");
+        builtin
+            .get(name)
+            .and_then(Value::as_table)
+            .cloned()
+            .with_context(|| {
+                format!(
+                    "Unknown preset '{}' (built in: {})",
+                    name,
+                    builtin.keys().cloned().collect::<Vec<_>>().join(", ")
+                )
+            })
+    }
+
+    /// Put a relative output path into the configured output directory
+    pub fn output_path(&self, path: PathBuf) -> PathBuf {
+        match &self.output_dir {
+            Some(dir) if path.is_relative() => dir.join(path),
+            _ => path,
+        }
+    }
+
+    /// Where a recording made without `-o` goes: `output_name` filled in
+    /// for `app` (what's recorded), in `output_dir` or a glide folder in
+    /// the user's videos directory (~/Movies/glide on macOS)
+    pub fn auto_output_path(&self, app: &str) -> Result<PathBuf> {
+        let dir = match &self.output_dir {
+            Some(dir) => dir.clone(),
+            None => dirs::video_dir()
+                .or_else(|| dirs::home_dir().map(|home| home.join("Videos")))
+                .context("Could not determine the videos directory; pass -o")?
+                .join("glide"),
+        };
+        fs::create_dir_all(&dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+        let template = self.output_name.as_deref().unwrap_or(DEFAULT_OUTPUT_NAME);
+        let name = fill_name(template, Local::now().naive_local(), app);
+        // Never overwrite, e.g. two recordings started within a second
+        let mut path = dir.join(format!("{}.mp4", name));
+        let mut n = 2;
+        while path.exists() {
+            path = dir.join(format!("{}-{}.mp4", name, n));
+            n += 1;
+        }
+        Ok(path)
+    }
+}
+
+/// `template` with `{date}` (2024-06-01), `{time}` (14-30-05) and `{app}`
+/// filled in, keeping characters file systems reject out of the app name
+fn fill_name(template: &str, now: NaiveDateTime, app: &str) -> String {
+    let app: String = app
+        .trim()
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || "-_. ".contains(c) {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    template
+        .replace("{date}", &now.format("%Y-%m-%d").to_string())
+        .replace("{time}", &now.format("%H-%M-%S").to_string())
+        .replace("{app}", &app)
+}
+
+/// `subcommand`'s option with this long name
+fn find_arg<'a>(subcommand: &'a clap::Command, long: &str) -> Option<&'a clap::Arg> {
+    subcommand
+        .get_arguments()
+        .find(|arg| arg.get_long() == Some(long))
+}
+
+/// The long names and values of a table of `subcommand`'s options, for
+/// `set_default`. A flag that's false is left out.
+fn option_defaults(
+    subcommand: &clap::Command,
+    options: &toml::value::Table,
+) -> Result<Vec<(String, Vec<String>)>> {
+    let mut defaults = Vec::new();
+    for (key, value) in options {
+        let long = key.replace('_', "-");
+        if find_arg(subcommand, &long).is_none() {
+            anyhow::bail!("`glide {}` has no --{} option", subcommand.get_name(), long);
+        }
+        let mut values = Vec::new();
+        push_values(&mut values, value).with_context(|| format!("Bad value for {}", key))?;
+        if !values.is_empty() {
+            defaults.push((long, values));
+        }
+    }
+    Ok(defaults)
+}
+
+/// The command-line values for a config value: a flag is on for `true`,
+/// and arrays repeat the option
+fn push_values(values: &mut Vec<String>, value: &Value) -> Result<()> {
+    match value {
+        Value::Boolean(true) => values.push("true".to_string()),
+        Value::Boolean(false) => {}
+        Value::String(s) => values.push(s.clone()),
+        Value::Integer(n) => values.push(n.to_string()),
+        Value::Float(x) => values.push(x.to_string()),
+        Value::Array(array) => {
+            for value in array {
+                push_values(values, value)?;
+            }
+        }
+        Value::Datetime(_) | Value::Table(_) => {
+            anyhow::bail!("expected a string, number or boolean")
+        }
+    }
+    Ok(())
+}
+
+/// `arg` defaulting to `values`, unless the command line overrides it. A
+/// flag set in the config takes an optional `=false` on the command line to
+/// turn it back off.
+fn set_default(arg: clap::Arg, values: Option<Vec<String>>) -> clap::Arg {
+    let arg = optional_bool(arg);
+    match values {
+        Some(values) => arg.default_values(values.into_iter().map(clap::builder::OsStr::from)),
+        None => arg,
+    }
+}
+
+/// A flag that also takes `--flag=true` and `--flag=false`; other options
+/// are unchanged
+fn optional_bool(arg: clap::Arg) -> clap::Arg {
+    match arg.get_action() {
+        ArgAction::SetTrue => arg
+            .action(ArgAction::Set)
+            .value_parser(clap::value_parser!(bool))
+            .num_args(0..=1)
+            .require_equals(true)
+            .default_missing_value("true")
+            .default_value("false"),
+        _ => arg,
+    }
+}
+
+/// Expand a leading `~/` to the home directory
+fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), dirs::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest),
+        _ => Path::new(path).to_path_buf(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::{Cli, Commands, ProcessArgs};
+    use clap::{CommandFactory, FromArgMatches};
+
+    fn parse(config: &Config, list: &[&str]) -> Result<Cli> {
+        let args = list.iter().map(OsString::from).collect();
+        let matches = config.matches(args, Cli::command())?;
+        Ok(Cli::from_arg_matches(&matches)?)
+    }
+
+    fn process_args(cli: Cli) -> ProcessArgs {
+        match cli.command {
+            Commands::Process { args, .. } | Commands::Preview { args, .. } => args,
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_cli_overrides_config() {
+        let config = Config::parse(
+            r##"
+            output_dir = "/videos"
+
+            [process]
+            background = "#1a1a2e"
+            cursor_scale = 2.5
+            no_motion_blur = true
+            no_cursor = false
+            nice = 5
+            "##,
+        )
+        .unwrap();
+
+        let cli = parse(
+            &config,
+            &[
+                "glide",
+                "process",
+                "in.mp4",
+                "-o",
+                "out.mp4",
+                "--cursor-scale",
+                "3",
+            ],
+        )
+        .unwrap();
+        let args = process_args(cli);
+        assert_eq!(args.cursor_scale, Some(3.0));
+        assert_eq!(args.background.as_deref(), Some("#1a1a2e"));
+        assert!(args.no_motion_blur);
+        assert!(!args.no_cursor);
+        assert_eq!(args.nice, Some(5));
+
+        // A flag the config turns on can be turned off, and an option that
+        // conflicts with a config value replaces it
+        let cli = parse(
+            &config,
+            &[
+                "glide",
+                "process",
+                "in.mp4",
+                "-o",
+                "out.mp4",
+                "--no-motion-blur=false",
+                "--low-priority",
+            ],
+        )
+        .unwrap();
+        let args = process_args(cli);
+        assert!(!args.no_motion_blur);
+        assert!(args.low_priority);
+        assert_eq!(args.nice, None);
+
+        // Global flags ahead of the subcommand don't hide it
+        let cli = parse(
+            &config,
+            &["glide", "-v", "process", "in.mp4", "-o", "out.mp4"],
+        )
+        .unwrap();
+        assert_eq!(cli.verbose, 1);
+        assert_eq!(process_args(cli).cursor_scale, Some(2.5));
+
+        assert_eq!(
+            config.output_path(PathBuf::from("out.mp4")),
+            PathBuf::from("/videos/out.mp4")
+        );
+        assert_eq!(
+            config.output_path(PathBuf::from("/tmp/out.mp4")),
+            PathBuf::from("/tmp/out.mp4")
+        );
+    }
+
+    #[test]
+    fn test_presets() {
+        let config = Config::parse(
+            r##"
+            [process]
+            cursor_scale = 4.0
+
+            [presets.team]
+            background = "#123456"
+            "##,
+        )
+        .unwrap();
+
+        // Config defaults, then the preset, then the command line
+        let args = process_args(
+            parse(
+                &config,
+                &[
+                    "glide", "process", "in.mp4", "-o", "o.mp4", "--preset", "social",
+                ],
+            )
+            .unwrap(),
+        );
+        assert_eq!(args.cursor_scale, Some(3.0));
+        assert!(args.web_optimized);
+
+        let args = process_args(
+            parse(
+                &config,
+                &["glide", "process", "in.mp4", "-o", "o.mp4", "--preset=team"],
+            )
+            .unwrap(),
+        );
+        assert_eq!(args.background.as_deref(), Some("#123456"));
+        assert_eq!(args.cursor_scale, Some(4.0));
+
+        // Previews look like the output they stand in for
+        let cli = parse(
+            &config,
+            &[
+                "glide", "preview", "in.mp4", "--at", "1:05", "-o", "f.png", "--preset", "team",
+            ],
+        )
+        .unwrap();
+        match &cli.command {
+            Commands::Preview { at, .. } => assert_eq!(*at, 65.0),
+            _ => unreachable!(),
+        }
+        let args = process_args(cli);
+        assert_eq!(args.cursor_scale, Some(4.0));
+        assert_eq!(args.background.as_deref(), Some("#123456"));
+
+        assert!(parse(
+            &config,
+            &["glide", "process", "in.mp4", "-o", "o.mp4", "--preset", "nope"]
+        )
+        .is_err());
+
+        // A preset named in the config file
+        let config = Config::parse(
+            "[process]\npreset = \"team\"\n[presets.team]\nbackground = \"#654321\"\n",
+        )
+        .unwrap();
+        let args =
+            process_args(parse(&config, &["glide", "process", "in.mp4", "-o", "o.mp4"]).unwrap());
+        assert_eq!(args.background.as_deref(), Some("#654321"));
+
+        // A preview can't be --fast or --interpolate, so those stay behind
+        let config = Config::parse("[process]\nfast = true\ninterpolate = true\n").unwrap();
+        let args = process_args(
+            parse(
+                &config,
+                &["glide", "preview", "in.mp4", "--at", "1", "-o", "f.png"],
+            )
+            .unwrap(),
+        );
+        assert!(!args.fast && !args.interpolate);
+        let args =
+            process_args(parse(&config, &["glide", "process", "in.mp4", "-o", "o.mp4"]).unwrap());
+        assert!(args.fast && args.interpolate);
+    }
+
+    #[test]
+    fn test_builtin_presets_parse() {
+        let config = Config::default();
+        for preset in ["tutorial", "social", "minimal", "cinematic"] {
+            assert!(
+                parse(
+                    &config,
+                    &["glide", "process", "in.mp4", "-o", "o.mp4", "--preset", preset],
+                )
+                .is_ok(),
+                "{}",
+                preset
+            );
+        }
+    }
+
+    #[test]
+    fn test_unknown_keys_rejected() {
+        let config = Config::parse("[record]\nbackground = \"#000000\"\n").unwrap();
+        assert!(parse(&config, &["glide", "list", "displays"]).is_ok());
+        assert!(parse(&config, &["glide", "record", "--display", "0"]).is_err());
+        assert!(parse(&config, &["glide", "-v", "record", "--display", "0"]).is_err());
+
+        let config = Config::parse("[nonsense]\nx = 1\n").unwrap();
+        assert!(parse(&config, &["glide", "list", "displays"]).is_err());
+    }
+
+    #[test]
+    fn test_auto_output_path() {
+        let now =
+            NaiveDateTime::parse_from_str("2024-06-01 14:30:05", "%Y-%m-%d %H:%M:%S").unwrap();
+        assert_eq!(
+            fill_name(DEFAULT_OUTPUT_NAME, now, "Safari"),
+            "recording-2024-06-01-14-30-05"
+        );
+        assert_eq!(
+            fill_name("{app}-{date}", now, "Code: Insiders/"),
+            "Code- Insiders--2024-06-01"
+        );
+
+        let dir = tempfile::tempdir().unwrap();
+        let config = Config::parse(&format!(
+            "output_dir = {:?}\noutput_name = \"{{app}}\"",
+            dir.path().display().to_string()
+        ))
+        .unwrap();
+        let path = config.auto_output_path("Terminal").unwrap();
+        assert_eq!(path, dir.path().join("Terminal.mp4"));
+        fs::write(&path, b"").unwrap();
+        assert_eq!(
+            config.auto_output_path("Terminal").unwrap(),
+            dir.path().join("Terminal-2.mp4")
+        );
+    }
+}
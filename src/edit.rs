@@ -0,0 +1,379 @@
+//! The `glide edit` terminal UI: a list of the detected zooms to change
+
+use anyhow::{Context, Result};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::style::{Print, PrintStyledContent, Stylize};
+use crossterm::terminal::{self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen};
+use crossterm::{cursor, execute, queue};
+use glide_core::edit::decisions::{EditDecisions, ZoomEdit};
+use glide_core::processing::frames::get_video_duration;
+use glide_core::processing::zoom::{effective_clicks, ZoomConfig};
+use glide_core::recording::metadata::RecordingMetadata;
+use std::io::{self, Write};
+use std::path::Path;
+
+/// Seconds a zoom moves per key press (Shift: `RETIME_STEP * 10`)
+const RETIME_STEP: f64 = 0.1;
+
+/// Screen points a target moves per key press (Shift: `RETARGET_STEP * 10`)
+const RETARGET_STEP: f64 = 10.0;
+
+/// Rows taken by the header and key help
+const CHROME_ROWS: u16 = 5;
+
+const HELP: &str =
+    "↑/↓ select  d delete  ←/→ retime (Shift: 1s)  t retarget  r reset  s save  q quit";
+const RETARGET_HELP: &str = "Arrows move the target (Shift: 100pt)  Enter keep  Esc cancel";
+
+/// A detected zoom: the click it's on
+struct Segment {
+    click: f64,
+    x: f64,
+    y: f64,
+}
+
+enum Outcome {
+    Continue,
+    Save,
+    Quit,
+}
+
+struct Editor {
+    segments: Vec<Segment>,
+    edits: EditDecisions,
+    zoom_config: ZoomConfig,
+    /// Tracking clock minus video time
+    time_offset: f64,
+    selected: usize,
+    /// While retargeting: the target to go back to on Esc
+    retargeting: Option<Option<(f64, f64)>>,
+    dirty: bool,
+    /// Set after `q` with unsaved changes; a second `q` discards them
+    confirm_quit: bool,
+    message: Option<String>,
+}
+
+/// Edit the zooms of a recording and save them next to it for `glide process`
+pub fn run(input: &Path, zoom_config: ZoomConfig) -> Result<()> {
+    let metadata = RecordingMetadata::load(input)
+        .context("Failed to load recording metadata. Was this video recorded with glide?")?;
+    let segments: Vec<Segment> = effective_clicks(&metadata.cursor_events, &zoom_config)
+        .into_iter()
+        .map(|click| Segment {
+            click: click.timestamp,
+            x: click.x,
+            y: click.y,
+        })
+        .collect();
+    if segments.is_empty() {
+        println!(
+            "No clicks in {}, so there are no zooms to edit",
+            input.display()
+        );
+        return Ok(());
+    }
+
+    // Show video time, like the processed output; fall back to the tracking clock
+    let time_offset = match get_video_duration(input) {
+        Ok(duration) if metadata.cursor_tracking_duration > 0.0 => {
+            metadata.cursor_tracking_duration - duration
+        }
+        _ => 0.0,
+    };
+
+    let mut editor = Editor {
+        segments,
+        edits: EditDecisions::load(input)?.unwrap_or_default(),
+        zoom_config,
+        time_offset,
+        selected: 0,
+        retargeting: None,
+        dirty: false,
+        confirm_quit: false,
+        message: None,
+    };
+
+    let mut stdout = io::stdout();
+    terminal::enable_raw_mode().context("Failed to set up the terminal")?;
+    let result = execute!(stdout, EnterAlternateScreen, cursor::Hide)
+        .map_err(anyhow::Error::from)
+        .and_then(|()| edit_loop(&mut editor, input, &mut stdout));
+    let _ = execute!(stdout, cursor::Show, LeaveAlternateScreen);
+    let _ = terminal::disable_raw_mode();
+    result?;
+
+    if editor.dirty {
+        println!("Edits discarded");
+    } else if editor.edits.changed() > 0 {
+        println!(
+            "{} zoom(s) changed; `glide process` will use them",
+            editor.edits.changed()
+        );
+    }
+    Ok(())
+}
+
+fn edit_loop(editor: &mut Editor, input: &Path, out: &mut impl Write) -> Result<()> {
+    loop {
+        draw(editor, input, out)?;
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        match editor.handle(key) {
+            Outcome::Continue => {}
+            Outcome::Save => {
+                let path = editor.edits.save(input)?;
+                editor.dirty = false;
+                editor.message = Some(format!("Saved {}", path.display()));
+            }
+            Outcome::Quit => return Ok(()),
+        }
+    }
+}
+
+impl Editor {
+    fn handle(&mut self, key: KeyEvent) -> Outcome {
+        let confirm_quit = std::mem::take(&mut self.confirm_quit);
+        self.message = None;
+        let big = key.modifiers.contains(KeyModifiers::SHIFT);
+
+        if let Some(previous) = self.retargeting {
+            let step = if big {
+                RETARGET_STEP * 10.0
+            } else {
+                RETARGET_STEP
+            };
+            let (dx, dy) = match key.code {
+                KeyCode::Left => (-step, 0.0),
+                KeyCode::Right => (step, 0.0),
+                KeyCode::Up => (0.0, -step),
+                KeyCode::Down => (0.0, step),
+                KeyCode::Enter | KeyCode::Char('t') => {
+                    self.retargeting = None;
+                    return Outcome::Continue;
+                }
+                KeyCode::Esc => {
+                    let click = self.segments[self.selected].click;
+                    self.edits.get_mut(click).target = previous;
+                    self.retargeting = None;
+                    return Outcome::Continue;
+                }
+                _ => return Outcome::Continue,
+            };
+            let (x, y) = self.target(self.selected);
+            let click = self.segments[self.selected].click;
+            self.edits.get_mut(click).target = Some((x + dx, y + dy));
+            self.dirty = true;
+            return Outcome::Continue;
+        }
+
+        let click = self.segments[self.selected].click;
+        match key.code {
+            KeyCode::Up | KeyCode::Char('k') => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.selected = (self.selected + 1).min(self.segments.len() - 1)
+            }
+            KeyCode::Left | KeyCode::Right => {
+                let step = if big { RETIME_STEP * 10.0 } else { RETIME_STEP };
+                let edit = self.edits.get_mut(click);
+                edit.shift += if key.code == KeyCode::Left {
+                    -step
+                } else {
+                    step
+                };
+                // Keep repeated steps from drifting off round numbers
+                edit.shift = (edit.shift * 1000.0).round() / 1000.0;
+                self.dirty = true;
+            }
+            KeyCode::Char('d') | KeyCode::Delete | KeyCode::Backspace => {
+                let edit = self.edits.get_mut(click);
+                edit.delete = !edit.delete;
+                self.dirty = true;
+            }
+            KeyCode::Char('t') => {
+                self.retargeting = Some(self.edits.get(click).and_then(|e| e.target));
+            }
+            KeyCode::Char('r') => {
+                *self.edits.get_mut(click) = ZoomEdit::new(click);
+                self.dirty = true;
+            }
+            KeyCode::Char('s') => return Outcome::Save,
+            KeyCode::Char('q') | KeyCode::Esc => {
+                if !self.dirty || confirm_quit {
+                    return Outcome::Quit;
+                }
+                self.confirm_quit = true;
+                self.message = Some("Unsaved changes: s to save, q again to discard".into());
+            }
+            _ => {}
+        }
+        Outcome::Continue
+    }
+
+    /// Where the camera zooms in on for segment `index`
+    fn target(&self, index: usize) -> (f64, f64) {
+        let segment = &self.segments[index];
+        self.edits
+            .get(segment.click)
+            .and_then(|e| e.target)
+            .unwrap_or((segment.x, segment.y))
+    }
+
+    /// One row of the list
+    fn row(&self, index: usize) -> String {
+        let segment = &self.segments[index];
+        let edit = self.edits.get(segment.click);
+        let shift = edit.map_or(0.0, |e| e.shift);
+        let click = segment.click + shift - self.time_offset;
+        let start = click - self.zoom_config.ease_in;
+        let end = click + self.zoom_config.hold + self.zoom_config.ease_out;
+        let (x, y) = self.target(index);
+
+        let mut changes = Vec::new();
+        if edit.is_some_and(|e| e.delete) {
+            changes.push("deleted".to_string());
+        }
+        if shift != 0.0 {
+            changes.push(format!("moved {:+.1}s", shift));
+        }
+        if edit.is_some_and(|e| e.target.is_some()) {
+            changes.push("retargeted".to_string());
+        }
+
+        format!(
+            "{:>3}  {:>8}  {:>8}-{:<8}  ({:>5.0}, {:>5.0})  {}",
+            index + 1,
+            format_timestamp(click),
+            format_timestamp(start),
+            format_timestamp(end),
+            x,
+            y,
+            changes.join(", ")
+        )
+    }
+}
+
+fn draw(editor: &Editor, input: &Path, out: &mut impl Write) -> Result<()> {
+    let (_, rows) = terminal::size().unwrap_or((80, 24));
+    let visible = rows.saturating_sub(CHROME_ROWS).max(1) as usize;
+    // Keep the selection in view
+    let first = editor.selected.saturating_sub(visible - 1);
+
+    queue!(out, Clear(ClearType::All), cursor::MoveTo(0, 0))?;
+    queue!(
+        out,
+        Print(format!(
+            "glide edit: {} ({} zooms, {} changed)",
+            input.display(),
+            editor.segments.len(),
+            editor.edits.changed()
+        )),
+        cursor::MoveTo(0, 2),
+        PrintStyledContent(
+            format!(
+                "{:>3}  {:>8}  {:^17}  {:^14}  {}",
+                "#", "Click", "Zoom", "Target", "Changes"
+            )
+            .bold()
+        ),
+    )?;
+
+    for (row, index) in (first..editor.segments.len()).take(visible).enumerate() {
+        queue!(out, cursor::MoveTo(0, 3 + row as u16))?;
+        let line = editor.row(index);
+        if index == editor.selected {
+            queue!(out, PrintStyledContent(line.reverse()))?;
+        } else {
+            queue!(out, Print(line))?;
+        }
+    }
+
+    let help = match (&editor.message, editor.retargeting) {
+        (Some(message), _) => message.as_str(),
+        (None, Some(_)) => RETARGET_HELP,
+        (None, None) => HELP,
+    };
+    queue!(
+        out,
+        cursor::MoveTo(0, rows.saturating_sub(1)),
+        PrintStyledContent(help.dim())
+    )?;
+    out.flush()?;
+    Ok(())
+}
+
+/// mm:ss.s, or -mm:ss.s before the video starts
+fn format_timestamp(seconds: f64) -> String {
+    let sign = if seconds < 0.0 { "-" } else { "" };
+    let seconds = seconds.abs();
+    let minutes = (seconds / 60.0).floor();
+    format!(
+        "{}{:02}:{:04.1}",
+        sign,
+        minutes as u64,
+        seconds - minutes * 60.0
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent::new(code, modifiers)
+    }
+
+    #[test]
+    fn test_editor_keys() {
+        let mut editor = Editor {
+            segments: vec![
+                Segment {
+                    click: 1.0,
+                    x: 100.0,
+                    y: 100.0,
+                },
+                Segment {
+                    click: 6.0,
+                    x: 300.0,
+                    y: 200.0,
+                },
+            ],
+            edits: EditDecisions::default(),
+            zoom_config: ZoomConfig::default(),
+            time_offset: 0.0,
+            selected: 0,
+            retargeting: None,
+            dirty: false,
+            confirm_quit: false,
+            message: None,
+        };
+
+        for _ in 0..3 {
+            editor.handle(press(KeyCode::Right, KeyModifiers::NONE));
+        }
+        editor.handle(press(KeyCode::Left, KeyModifiers::SHIFT));
+        assert_eq!(editor.edits.get(1.0).unwrap().shift, -0.7);
+
+        editor.handle(press(KeyCode::Down, KeyModifiers::NONE));
+        editor.handle(press(KeyCode::Char('t'), KeyModifiers::NONE));
+        editor.handle(press(KeyCode::Right, KeyModifiers::SHIFT));
+        editor.handle(press(KeyCode::Enter, KeyModifiers::NONE));
+        editor.handle(press(KeyCode::Char('d'), KeyModifiers::NONE));
+        let edit = editor.edits.get(6.0).unwrap();
+        assert_eq!(edit.target, Some((400.0, 200.0)));
+        assert!(edit.delete);
+
+        // Unsaved changes need a second q
+        assert!(matches!(
+            editor.handle(press(KeyCode::Char('q'), KeyModifiers::NONE)),
+            Outcome::Continue
+        ));
+        assert!(matches!(
+            editor.handle(press(KeyCode::Char('q'), KeyModifiers::NONE)),
+            Outcome::Quit
+        ));
+    }
+}
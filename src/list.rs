@@ -0,0 +1,131 @@
+//! `glide list --json`: displays and windows in the same shape on every
+//! platform, leaving out each platform's capture details
+
+#[cfg(target_os = "linux")]
+use glide_core::linux::{DisplayInfo, WindowInfo};
+#[cfg(target_os = "macos")]
+use glide_core::macos::{DisplayInfo, WindowInfo};
+#[cfg(target_os = "windows")]
+use glide_core::windows::{DisplayInfo, WindowInfo};
+use serde::Serialize;
+
+/// A display as `glide list displays --json` prints it
+#[derive(Debug, Serialize)]
+pub struct DisplayEntry {
+    index: usize,
+    x: i32,
+    y: i32,
+    /// None on Wayland until the sharing dialog picks a monitor
+    width: Option<u32>,
+    height: Option<u32>,
+    scale_factor: f64,
+    is_main: bool,
+}
+
+impl From<&DisplayInfo> for DisplayEntry {
+    fn from(display: &DisplayInfo) -> Self {
+        let size = known_size(display.width, display.height);
+        Self {
+            index: display.index,
+            x: display.x,
+            y: display.y,
+            width: size.map(|(width, _)| width),
+            height: size.map(|(_, height)| height),
+            scale_factor: display.scale_factor,
+            is_main: display.is_main,
+        }
+    }
+}
+
+/// A window as `glide list windows --json` prints it
+#[derive(Debug, Serialize)]
+pub struct WindowEntry {
+    id: u32,
+    owner: String,
+    name: String,
+    x: i32,
+    y: i32,
+    /// None for the Wayland entry standing in for the sharing dialog's pick
+    width: Option<u32>,
+    height: Option<u32>,
+}
+
+impl From<&WindowInfo> for WindowEntry {
+    fn from(window: &WindowInfo) -> Self {
+        let (x, y, width, height) = window.bounds;
+        let size = known_size(width, height);
+        Self {
+            id: window.id,
+            owner: window.owner.clone(),
+            name: window.name.clone(),
+            x,
+            y,
+            width: size.map(|(width, _)| width),
+            height: size.map(|(_, height)| height),
+        }
+    }
+}
+
+/// The size, unless it's the 0x0 given where it's only known once recording
+fn known_size(width: u32, height: u32) -> Option<(u32, u32)> {
+    (width > 0 && height > 0).then_some((width, height))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_display_json() {
+        let display = DisplayEntry {
+            index: 1,
+            x: 1512,
+            y: 0,
+            width: Some(2560),
+            height: Some(1440),
+            scale_factor: 1.0,
+            is_main: false,
+        };
+        assert_eq!(
+            serde_json::to_value(&display).unwrap(),
+            json!({
+                "index": 1,
+                "x": 1512,
+                "y": 0,
+                "width": 2560,
+                "height": 1440,
+                "scale_factor": 1.0,
+                "is_main": false,
+            })
+        );
+    }
+
+    #[test]
+    fn test_unknown_size_is_null() {
+        assert_eq!(known_size(1920, 1080), Some((1920, 1080)));
+        assert_eq!(known_size(0, 0), None);
+
+        let window = WindowEntry {
+            id: 0,
+            owner: "Any window".to_string(),
+            name: "chosen in the screen sharing dialog".to_string(),
+            x: 0,
+            y: 0,
+            width: known_size(0, 0).map(|(width, _)| width),
+            height: known_size(0, 0).map(|(_, height)| height),
+        };
+        assert_eq!(
+            serde_json::to_value(&window).unwrap(),
+            json!({
+                "id": 0,
+                "owner": "Any window",
+                "name": "chosen in the screen sharing dialog",
+                "x": 0,
+                "y": 0,
+                "width": null,
+                "height": null,
+            })
+        );
+    }
+}
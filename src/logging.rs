@@ -0,0 +1,128 @@
+//! Where glide's log goes: `tracing_subscriber::fmt` set up for the terminal
+//!
+//! Info is the plain log, on stdout (stderr with `--progress json`, which
+//! keeps stdout for events). Warnings and errors go to stderr, and `-v` /
+//! `-vv` add debug and trace lines about timing and fallback decisions.
+
+use std::fmt;
+use tracing::level_filters::LevelFilter;
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::fmt::format::{self, FormatEvent, FormatFields};
+use tracing_subscriber::fmt::writer::MakeWriterExt;
+use tracing_subscriber::fmt::{FmtContext, MakeWriter};
+use tracing_subscriber::registry::LookupSpan;
+
+/// Log level for `-v` count and `--quiet`
+pub fn level(verbose: u8, quiet: bool) -> LevelFilter {
+    match (quiet, verbose) {
+        (true, _) => LevelFilter::WARN,
+        (false, 0) => LevelFilter::INFO,
+        (false, 1) => LevelFilter::DEBUG,
+        _ => LevelFilter::TRACE,
+    }
+}
+
+/// Send the log to the terminal, up to `max_level`; `json` keeps stdout
+/// for `--progress json` events
+pub fn init(max_level: LevelFilter, json: bool) {
+    let info = std::io::stdout.with_filter(move |meta| *meta.level() == Level::INFO && !json);
+    // Only fails if a subscriber is already set, which leaves that one in place
+    let _ = tracing::subscriber::set_global_default(subscriber(
+        max_level,
+        info.or_else(std::io::stderr),
+    ));
+}
+
+fn subscriber<W>(max_level: LevelFilter, writer: W) -> impl Subscriber + Send + Sync
+where
+    W: for<'a> MakeWriter<'a> + Send + Sync + 'static,
+{
+    tracing_subscriber::fmt()
+        .with_max_level(max_level)
+        .event_format(Line)
+        .with_writer(writer)
+        .finish()
+}
+
+/// Each event on a line: info as-is, warnings and errors labelled, and
+/// debug output with where it came from
+struct Line;
+
+impl<S, N> FormatEvent<S, N> for Line
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    fn format_event(
+        &self,
+        ctx: &FmtContext<'_, S, N>,
+        mut writer: format::Writer<'_>,
+        event: &Event<'_>,
+    ) -> fmt::Result {
+        let metadata = event.metadata();
+        match *metadata.level() {
+            Level::INFO => {}
+            Level::WARN => write!(writer, "Warning: ")?,
+            Level::ERROR => write!(writer, "Error: ")?,
+            level => write!(writer, "{:<5} {}: ", level.as_str(), metadata.target())?,
+        }
+        ctx.field_format().format_fields(writer.by_ref(), event)?;
+        writeln!(writer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Clone, Default)]
+    struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+    impl io::Write for Buffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_level() {
+        assert_eq!(level(0, false), LevelFilter::INFO);
+        assert_eq!(level(1, false), LevelFilter::DEBUG);
+        assert_eq!(level(3, false), LevelFilter::TRACE);
+        assert_eq!(level(2, true), LevelFilter::WARN);
+    }
+
+    #[test]
+    fn test_lines() {
+        let buffer = Buffer::default();
+        let writer = buffer.clone();
+        let log = subscriber(LevelFilter::DEBUG, move || writer.clone());
+        tracing::subscriber::with_default(log, || {
+            tracing::info!("Saved");
+            tracing::warn!("no fonts");
+            tracing::debug!(target: "glide_core::processing", "offset 0.2s");
+            tracing::trace!("hidden");
+        });
+        let log = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        assert_eq!(
+            log,
+            "Saved\nWarning: no fonts\nDEBUG glide_core::processing: offset 0.2s\n"
+        );
+
+        // --quiet keeps warnings
+        let buffer = Buffer::default();
+        let writer = buffer.clone();
+        let log = subscriber(level(0, true), move || writer.clone());
+        tracing::subscriber::with_default(log, || {
+            tracing::info!("Saved");
+            tracing::error!("failed");
+        });
+        assert_eq!(buffer.0.lock().unwrap().as_slice(), b"Error: failed\n");
+    }
+}
@@ -1,53 +1,118 @@
 mod cli;
-mod cursor_types;
-#[cfg(target_os = "linux")]
-mod linux;
-#[cfg(target_os = "macos")]
-mod macos;
-mod processing;
-mod recording;
+mod config;
+mod edit;
+mod list;
+mod logging;
+mod passphrase;
+mod picker;
+mod progress;
+mod report;
+mod signals;
 
 use anyhow::Result;
-use clap::Parser;
-use cli::{Cli, Commands, ListTarget};
+use clap::{CommandFactory, FromArgMatches};
+use cli::{Cli, Commands, EventsAction, LibraryAction, ListTarget, ProcessArgs};
+use config::Config;
+use glide_core::bench::run_bench;
+use glide_core::library;
 #[cfg(target_os = "linux")]
-use linux::{list_displays, list_windows};
+use glide_core::linux::{list_displays, list_windows, select_on_screen};
 #[cfg(target_os = "macos")]
-use macos::{list_displays, list_windows};
-use processing::process_video;
-use recording::{record_display, record_window};
+use glide_core::macos::{list_displays, list_windows, select_on_screen};
+use glide_core::processing::captions::CaptionStyle;
+use glide_core::processing::chapters::parse_chapters;
+use glide_core::processing::progress::ProgressFormat;
+use glide_core::processing::zoom::ZoomConfig;
+use glide_core::processing::zoom_chart::write_zoom_chart;
+use glide_core::processing::{CameraOptions, ProcessOptions, Processor};
+use glide_core::recording::bundle::{self, BUNDLE_EXTENSION};
+use glide_core::recording::concat::concat_recordings;
+use glide_core::recording::control;
+use glide_core::recording::crypto::EncryptKey;
+use glide_core::recording::encoder::{select_encoder, Encoder};
+use glide_core::recording::events;
+use glide_core::recording::exclude::Exclusions;
+use glide_core::recording::select::Selection;
+use glide_core::recording::window_filter::{contains_ignore_case, single_match};
+use glide_core::recording::{
+    record_app, record_display, record_displays, record_region, record_window, RecordOptions,
+};
+#[cfg(target_os = "windows")]
+use glide_core::windows::{list_displays, list_windows, select_on_screen};
+use list::{DisplayEntry, WindowEntry};
+use picker::{pick_target, PickedTarget};
+use report::{print_bench, print_inspect, print_stats};
+use std::path::Path;
+use std::time::Duration;
+
+/// Niceness used by `process --low-priority`
+const LOW_PRIORITY_NICE: i32 = 10;
 
 fn main() -> Result<()> {
-    let cli = Cli::parse();
+    // Defaults from the config file fill in what the command line leaves out
+    let config = Config::load()?;
+    let matches = match config.matches(std::env::args_os().collect(), Cli::command()) {
+        Ok(matches) => matches,
+        Err(e) => match e.downcast::<clap::Error>() {
+            Ok(e) => e.exit(),
+            Err(e) => return Err(e),
+        },
+    };
+    let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    let json = matches!(
+        &cli.command,
+        Commands::Process { args, .. } | Commands::Preview { args, .. }
+            if args.progress == ProgressFormat::Json
+    );
+    logging::init(logging::level(cli.verbose, cli.quiet), json);
+    let quiet = cli.quiet;
 
     match cli.command {
-        Commands::List { target } => match target {
+        Commands::List { target, json, app } => match target {
             ListTarget::Displays => {
                 let displays = list_displays()?;
-                if displays.is_empty() {
+                if json {
+                    let displays: Vec<DisplayEntry> = displays.iter().map(Into::into).collect();
+                    println!("{}", serde_json::to_string_pretty(&displays)?);
+                } else if displays.is_empty() {
                     println!("No displays found.");
                 } else {
                     println!("Available displays:");
                     for d in displays {
+                        // Size is unknown (0) until the Wayland sharing dialog picks a source
+                        let size = if d.width > 0 {
+                            format!("{}x{}", d.width, d.height)
+                        } else {
+                            "picked when recording".to_string()
+                        };
                         println!(
-                            "  [{index}] {width}x{height}{main}",
+                            "  [{index}] {size}{main}",
                             index = d.index,
-                            width = d.width,
-                            height = d.height,
                             main = if d.is_main { " (main)" } else { "" }
                         );
                     }
                 }
             }
             ListTarget::Windows => {
-                let windows = list_windows()?;
-                if windows.is_empty() {
+                let mut windows = list_windows()?;
+                if let Some(app) = &app {
+                    windows.retain(|w| contains_ignore_case(&w.owner, app));
+                }
+                if json {
+                    let windows: Vec<WindowEntry> = windows.iter().map(Into::into).collect();
+                    println!("{}", serde_json::to_string_pretty(&windows)?);
+                } else if windows.is_empty() {
                     println!("No windows found.");
                 } else {
                     println!("Available windows:");
                     for w in windows {
+                        let size = if w.bounds.2 > 0 {
+                            format!(" ({}x{})", w.bounds.2, w.bounds.3)
+                        } else {
+                            String::new()
+                        };
                         println!(
-                            "  [{id}] {owner} - {name} ({width}x{height})",
+                            "  [{id}] {owner} - {name}{size}",
                             id = w.id,
                             owner = w.owner,
                             name = if w.name.is_empty() {
@@ -55,8 +120,6 @@ fn main() -> Result<()> {
                             } else {
                                 &w.name
                             },
-                            width = w.bounds.2,
-                            height = w.bounds.3,
                         );
                     }
                 }
@@ -65,54 +128,504 @@ fn main() -> Result<()> {
         Commands::Record {
             display,
             window,
+            window_title,
+            region,
+            app,
+            pick,
+            select,
+            exclude_app,
+            exclude_window,
             output,
             capture_system_cursor,
+            rtmp,
+            stream_background,
+            encrypt,
+            recipient,
+            mic,
+            system_audio,
+            hotkey,
+            wait_for_hotkey,
+            mark_hotkey,
+            wait_for_start,
+            keys,
+            duration,
+            segment_duration,
+            codec,
+            quality,
+            encoder,
+            detach,
         } => {
-            if let Some(display_index) = display {
-                // Look up the display info
-                let displays = list_displays()?;
-                let display_info = displays
+            if detach {
+                let args = std::env::args_os()
+                    .skip(1)
+                    .filter(|arg| arg != "--detach")
+                    .collect();
+                return control::detach_recording(args);
+            }
+            // Pick before the encoder check and passphrase prompt print anything
+            let picked = if pick {
+                match pick_target()? {
+                    Some(picked) => Some(picked),
+                    None => {
+                        println!("Nothing picked, not recording");
+                        return Ok(());
+                    }
+                }
+            } else {
+                None
+            };
+            // A selection on screen stands in for --window or --region
+            let selection = if select {
+                match select_on_screen()? {
+                    Some(selection) => Some(selection),
+                    None => {
+                        println!("Nothing selected, not recording");
+                        return Ok(());
+                    }
+                }
+            } else {
+                None
+            };
+            let (window, region) = match selection {
+                Some(Selection::Window(id)) => (Some(id), region),
+                Some(Selection::Region(selected)) => (window, Some(selected)),
+                None => (window, region),
+            };
+            let (picked_display, picked_window) = match picked {
+                Some(PickedTarget::Display(display)) => (Some(display), None),
+                Some(PickedTarget::Window(window)) => (None, Some(window)),
+                None => (None, None),
+            };
+            // Look the window up now, since its app can name the output
+            let window = if let Some(window) = picked_window {
+                Some(window)
+            } else if let Some(title) = window_title {
+                let matches = list_windows()?
                     .into_iter()
-                    .find(|d| d.index == display_index as usize)
-                    .ok_or_else(|| anyhow::anyhow!("Display {} not found", display_index))?;
-                record_display(&display_info, &output, capture_system_cursor)?;
+                    .filter(|w| contains_ignore_case(&w.name, &title))
+                    .collect();
+                Some(single_match(matches, &title, |w| {
+                    format!("[{}] {} - {}", w.id, w.owner, w.name)
+                })?)
             } else if let Some(window_id) = window {
-                let windows = list_windows()?;
-                let window_info = windows
+                let window = list_windows()?
                     .into_iter()
                     .find(|w| w.id == window_id)
                     .ok_or_else(|| anyhow::anyhow!("Window {} not found", window_id))?;
-                record_window(&window_info, &output, capture_system_cursor)?;
+                Some(window)
             } else {
-                anyhow::bail!("Must specify either --display or --window");
+                None
+            };
+            let output = match output {
+                Some(output) => Some(config.output_path(output)),
+                None if rtmp.is_some() => None,
+                None => {
+                    let target = match (&picked_display, &window, &app, display.as_slice()) {
+                        (Some(picked), _, _, _) => format!("display{}", picked.index),
+                        (_, Some(window), _, _) => window.owner.clone(),
+                        (_, _, Some(app), _) => app.clone(),
+                        (_, _, _, [index]) => format!("display{}", index),
+                        _ if region.is_some() => "region".to_string(),
+                        _ => "screen".to_string(),
+                    };
+                    Some(config.auto_output_path(&target)?)
+                }
+            };
+            // Streams are always encoded in software
+            let encoder = if rtmp.is_some() {
+                Encoder::X264
+            } else {
+                select_encoder(encoder, codec, quality)?
+            };
+            // Ask for the passphrase before capture starts
+            let encrypt = match (encrypt, recipient) {
+                (false, _) => None,
+                (true, Some(recipient)) => Some(EncryptKey::recipient(&recipient)?),
+                (true, None) => Some(EncryptKey::Passphrase(passphrase::read(true)?)),
+            };
+            let options = RecordOptions {
+                output,
+                capture_system_cursor,
+                stream_url: rtmp,
+                stream_background,
+                encrypt,
+                mic,
+                system_audio,
+                hotkey,
+                wait_for_hotkey,
+                mark_hotkey,
+                wait_for_start,
+                keys,
+                duration: duration.map(Duration::from_secs),
+                codec,
+                quality,
+                encoder,
+                exclude: Exclusions {
+                    apps: exclude_app,
+                    windows: exclude_window,
+                },
+                stop: Some(signals::stop_on_ctrlc()?),
+                control_socket: true,
+                segment_duration,
+                status: Some(progress::status_line()),
+            };
+            signals::pause_on_request();
+            if let Some(display) = picked_display {
+                record_display(&display, &options)?;
+            } else if !display.is_empty() {
+                // Look up the display info
+                let mut displays = list_displays()?;
+                let mut selected = Vec::new();
+                for display_index in display {
+                    let position = displays
+                        .iter()
+                        .position(|d| d.index == display_index as usize)
+                        .ok_or_else(|| {
+                            anyhow::anyhow!("Display {} not found (or given twice)", display_index)
+                        })?;
+                    selected.push(displays.remove(position));
+                }
+                if selected.len() == 1 {
+                    record_display(&selected[0], &options)?;
+                } else if options.stream_url.is_some() {
+                    anyhow::bail!("Streaming records a single display; pass one --display");
+                } else {
+                    record_displays(&selected, &options)?;
+                }
+            } else if let Some(window) = window {
+                record_window(&window, &options)?;
+            } else if let Some(region) = region {
+                record_region(&region, &options)?;
+            } else if let Some(app) = app {
+                record_app(&app, &options)?;
+            } else {
+                anyhow::bail!(
+                    "Must specify --display, --window, --window-title, --region, --app, --pick or --select"
+                );
             }
         }
         Commands::Process {
             input,
             output,
-            background,
-            trim_start,
-            trim_end,
-            cursor_scale,
-            cursor_timeout,
-            no_cursor,
-            no_motion_blur,
-            no_click_highlight,
+            args,
         } => {
-            process_video(
-                &input,
-                &output,
-                background.as_deref(),
-                trim_start,
-                trim_end,
-                cursor_scale,
-                cursor_timeout,
-                no_cursor,
-                no_motion_blur,
-                no_click_highlight,
-            )?;
+            lower_priority(&args);
+            processor(args, quiet)?.process(&input, &config.output_path(output))?;
+            // Only the CLI tidies up other recordings; the render touches its own
+            library::enforce_retention();
+        }
+        Commands::Preview {
+            input,
+            at,
+            output,
+            args,
+        } => {
+            lower_priority(&args);
+            processor(args, quiet)?.preview(&input, at, &config.output_path(output))?;
+        }
+        Commands::Bench { duration } => {
+            print_bench(&run_bench(duration as f64)?);
+        }
+        Commands::Stats { input, heatmap } => {
+            print_stats(&input, heatmap.as_deref())?;
+        }
+        Commands::Inspect { input, zoom } => {
+            print_inspect(&input, &zoom.apply(ZoomConfig::default()))?;
         }
+        Commands::Edit { input, zoom } => {
+            edit::run(&input, zoom.apply(ZoomConfig::default()))?;
+        }
+        Commands::ZoomChart {
+            input,
+            output,
+            reduced_motion,
+            no_zoom,
+            follow_cursor,
+            zoom_on_markers,
+            zoom,
+        } => {
+            let zoom_config = zoom.apply(if reduced_motion {
+                ZoomConfig::reduced_motion()
+            } else {
+                ZoomConfig::default()
+            });
+            let camera = CameraOptions {
+                no_zoom,
+                follow_cursor,
+                zoom_on_markers,
+            };
+            write_zoom_chart(&input, &output, &zoom_config, camera)?;
+        }
+        Commands::Pack { input, output } => {
+            let output = output.unwrap_or_else(|| input.with_extension(BUNDLE_EXTENSION));
+            let manifest = bundle::pack(&input, &output)?;
+            println!(
+                "Packed {} and {} into {}",
+                manifest.video,
+                manifest.metadata,
+                output.display()
+            );
+        }
+        Commands::Unpack { bundle, dir, force } => {
+            let dir =
+                dir.unwrap_or_else(|| bundle.parent().map(Path::to_path_buf).unwrap_or_default());
+            let video = bundle::unpack(&bundle, &dir, force)?;
+            println!("Unpacked and verified: {}", video.display());
+        }
+        Commands::Start => control::start_recording()?,
+        Commands::Mark { name } => control::mark_recording(name)?,
+        Commands::Stop => control::stop_recording()?,
+        Commands::Status { json } => {
+            let status = control::recording_status()?;
+            if json {
+                // null when nothing is recording
+                println!("{}", serde_json::to_string_pretty(&status)?);
+            } else {
+                match status {
+                    Some(status) => println!("{}", control::format_status(&status)),
+                    None => println!("Not recording"),
+                }
+            }
+        }
+        Commands::Concat { inputs, output } => concat_recordings(&inputs, &output)?,
+        Commands::Library { action } => match action {
+            LibraryAction::List => println!("{}", library::format_list()?),
+            LibraryAction::Open { number, processed } => library::open_entry(number, processed)?,
+            LibraryAction::Clean => library::clean()?,
+            LibraryAction::Retention { days, off } => library::set_retention(days, off)?,
+        },
+        Commands::Events { action } => match action {
+            EventsAction::Export { input, output } => {
+                let count = events::export_events(&input, &output)?;
+                println!("Exported {} events to {}", count, output.display());
+            }
+            EventsAction::Import {
+                events: path,
+                input,
+                scale_factor,
+            } => {
+                let count = events::import_events(&path, &input, scale_factor)?;
+                println!("Imported {} events into {}", count, input.display());
+            }
+        },
     }
 
     Ok(())
 }
+
+/// Lower the process's scheduling priority for `--nice` or
+/// `--low-priority`, before any render threads or FFmpeg processes exist,
+/// so they all inherit it
+fn lower_priority(args: &ProcessArgs) {
+    let Some(nice) = args.nice.or(args.low_priority.then_some(LOW_PRIORITY_NICE)) else {
+        return;
+    };
+    #[cfg(unix)]
+    {
+        // SAFETY: setpriority has no memory-safety preconditions
+        let result = unsafe { libc::setpriority(libc::PRIO_PROCESS as _, 0, nice) };
+        if result != 0 {
+            tracing::warn!(
+                "could not set priority: {}",
+                std::io::Error::last_os_error()
+            );
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = nice;
+        tracing::warn!("--nice is not supported on this platform");
+    }
+}
+
+/// `glide process` options from the command line; `quiet` hides the
+/// progress bar along with the log
+fn process_options(args: ProcessArgs, quiet: bool) -> Result<ProcessOptions> {
+    let ProcessArgs {
+        background,
+        padding,
+        corner_radius,
+        shadow_size,
+        shadow_opacity,
+        no_shadow,
+        vertical,
+        trim_start,
+        trim_end,
+        auto_trim,
+        cut,
+        cuts,
+        cut_between_markers,
+        cursor_scale,
+        cursor_timeout,
+        no_cursor,
+        cursor_trail,
+        cursor_trail_opacity,
+        cursor_style,
+        cursor_outline,
+        no_motion_blur,
+        motion_blur_mode,
+        motion_blur_quality,
+        motion_blur_strength,
+        motion_blur_samples,
+        motion_blur_threshold,
+        output_fps,
+        interpolate,
+        no_zoom,
+        follow_cursor,
+        zoom_on_markers,
+        speed_up_idle,
+        idle_threshold,
+        fast_forward_indicator,
+        no_click_highlight,
+        click_sound,
+        music,
+        music_volume,
+        palette,
+        click_callout,
+        callout_clicks,
+        step_numbers,
+        chapter,
+        auto_chapters,
+        marker_chapters,
+        spotlight,
+        spotlight_radius,
+        overlay,
+        blur_region,
+        blur_style,
+        auto_redact,
+        reduced_motion,
+        zoom,
+        web_optimized,
+        // Already expanded into the other options (see config.rs)
+        preset: _,
+        format,
+        codec,
+        segment_seconds,
+        max_width,
+        max_fps,
+        png_16bit,
+        export_animation,
+        annotations,
+        subtitles,
+        caption_font,
+        caption_size,
+        caption_color,
+        caption_background,
+        caption_background_opacity,
+        caption_position,
+        delete_source_on_success,
+        ignore_mismatch,
+        identity,
+        threads,
+        // Applied to the whole process by `lower_priority`
+        nice: _,
+        low_priority: _,
+        gpu,
+        fast,
+        preview,
+        progress,
+    } = args;
+    Ok(ProcessOptions {
+        background,
+        padding,
+        corner_radius,
+        shadow_size,
+        shadow_opacity,
+        no_shadow,
+        vertical,
+        trim_start,
+        trim_end,
+        auto_trim,
+        cuts: cut,
+        cuts_file: cuts,
+        cut_between_markers,
+        cursor_scale,
+        cursor_timeout,
+        no_cursor,
+        cursor_trail,
+        cursor_trail_opacity,
+        cursor_style,
+        cursor_outline,
+        no_motion_blur,
+        motion_blur_mode,
+        motion_blur_quality,
+        motion_blur_strength,
+        motion_blur_samples,
+        motion_blur_threshold,
+        output_fps,
+        interpolate,
+        no_zoom,
+        follow_cursor,
+        zoom_on_markers,
+        speed_up_idle,
+        idle_threshold,
+        fast_forward_indicator,
+        no_click_highlight,
+        click_sound,
+        music,
+        music_volume,
+        palette,
+        click_callout,
+        callout_clicks,
+        step_numbers,
+        chapters: parse_chapters(&chapter)?,
+        auto_chapters,
+        marker_chapters,
+        spotlight,
+        spotlight_radius,
+        overlay,
+        blur_regions: blur_region,
+        blur_style,
+        auto_redact,
+        reduced_motion,
+        zoom,
+        web_optimized,
+        format,
+        codec,
+        segment_seconds,
+        max_width,
+        max_fps,
+        png_16bit,
+        export_animation,
+        annotations,
+        subtitles,
+        caption_style: CaptionStyle {
+            font: caption_font,
+            size: caption_size,
+            color: caption_color,
+            background: caption_background,
+            background_opacity: caption_background_opacity,
+            position: caption_position,
+        },
+        delete_source_on_success,
+        ignore_mismatch,
+        identity,
+        threads: threads.map(|n| n as usize),
+        gpu,
+        fast,
+        preview,
+        progress: match progress {
+            ProgressFormat::Bar if quiet => ProgressFormat::Quiet,
+            progress => progress,
+        },
+    })
+}
+
+/// A processor for `args` that asks for passphrases on the terminal and
+/// shows a progress bar or, with `--progress json`, writes each event to
+/// stdout as a JSON line
+fn processor(args: ProcessArgs, quiet: bool) -> Result<Processor> {
+    let options = process_options(args, quiet)?;
+    let format = options.progress;
+    let processor = Processor::new(options).on_passphrase(|| passphrase::read(false));
+    Ok(match format {
+        ProgressFormat::Bar => processor.on_progress(progress::render_bar()),
+        ProgressFormat::Json => processor.on_progress(|event| {
+            println!(
+                "{}",
+                serde_json::to_string(event).expect("events serialize")
+            )
+        }),
+        ProgressFormat::Quiet => processor,
+    })
+}
@@ -0,0 +1,29 @@
+//! Passphrases for `--encrypt` and for processing encrypted recordings:
+//! `GLIDE_PASSPHRASE` for scripts and CI, otherwise asked on the terminal
+
+use anyhow::{Context, Result};
+use glide_core::recording::crypto::SecretString;
+
+/// Passphrase source for scripts and CI
+const PASSPHRASE_ENV: &str = "GLIDE_PASSPHRASE";
+
+/// Passphrase from the environment, or prompted (twice when `confirm`)
+pub fn read(confirm: bool) -> Result<SecretString> {
+    if let Ok(passphrase) = std::env::var(PASSPHRASE_ENV) {
+        return Ok(SecretString::from(passphrase));
+    }
+
+    let passphrase = rpassword::prompt_password("Recording passphrase: ")
+        .context("Failed to read passphrase")?;
+    if passphrase.is_empty() {
+        anyhow::bail!("Passphrase must not be empty");
+    }
+    if confirm {
+        let again = rpassword::prompt_password("Confirm passphrase: ")
+            .context("Failed to read passphrase")?;
+        if again != passphrase {
+            anyhow::bail!("Passphrases don't match");
+        }
+    }
+    Ok(SecretString::from(passphrase))
+}
@@ -0,0 +1,274 @@
+//! `glide record --pick`: choose the display or window to record from a
+//! searchable list instead of looking up its ID with `glide list` first
+
+use anyhow::{Context, Result};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::style::{Print, PrintStyledContent, Stylize};
+use crossterm::terminal::{self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen};
+use crossterm::{cursor, execute, queue};
+#[cfg(target_os = "linux")]
+use glide_core::linux::{list_displays, list_windows, DisplayInfo, WindowInfo};
+#[cfg(target_os = "macos")]
+use glide_core::macos::{list_displays, list_windows, DisplayInfo, WindowInfo};
+#[cfg(target_os = "windows")]
+use glide_core::windows::{list_displays, list_windows, DisplayInfo, WindowInfo};
+use std::io::{self, Write};
+
+/// Rows taken by the header, search line and key help
+const CHROME_ROWS: u16 = 5;
+
+const HELP: &str = "Type to search  ↑/↓ select  Enter record  Esc cancel";
+
+/// What was picked to record
+pub enum PickedTarget {
+    Display(DisplayInfo),
+    Window(WindowInfo),
+}
+
+enum Outcome {
+    Continue,
+    Pick(usize),
+    Cancel,
+}
+
+struct Picker {
+    labels: Vec<String>,
+    query: String,
+    /// Indices into `labels` matching `query`, best first
+    matches: Vec<usize>,
+    /// Position in `matches`
+    selected: usize,
+}
+
+/// Show every display and window and return the one chosen, or None if
+/// the picker was cancelled
+pub fn pick_target() -> Result<Option<PickedTarget>> {
+    let mut displays: Vec<Option<DisplayInfo>> = list_displays()?.into_iter().map(Some).collect();
+    let mut windows: Vec<Option<WindowInfo>> = list_windows()?.into_iter().map(Some).collect();
+    let labels: Vec<String> = displays
+        .iter()
+        .flatten()
+        .map(|d| {
+            let size = if d.width > 0 {
+                format!("{}x{}", d.width, d.height)
+            } else {
+                "picked when recording".to_string()
+            };
+            let main = if d.is_main { " (main)" } else { "" };
+            format!("Display {}  {}{}", d.index, size, main)
+        })
+        .chain(windows.iter().flatten().map(|w| {
+            let name = if w.name.is_empty() {
+                "(untitled)"
+            } else {
+                &w.name
+            };
+            format!("Window  [{}] {} - {}", w.id, w.owner, name)
+        }))
+        .collect();
+    if labels.is_empty() {
+        anyhow::bail!("No displays or windows found to record");
+    }
+
+    let mut picker = Picker::new(labels);
+    let mut stdout = io::stdout();
+    terminal::enable_raw_mode().context("Failed to set up the terminal")?;
+    let result = execute!(stdout, EnterAlternateScreen, cursor::Hide)
+        .map_err(anyhow::Error::from)
+        .and_then(|()| pick_loop(&mut picker, &mut stdout));
+    let _ = execute!(stdout, cursor::Show, LeaveAlternateScreen);
+    let _ = terminal::disable_raw_mode();
+
+    let Some(index) = result? else {
+        return Ok(None);
+    };
+    let picked = match index.checked_sub(displays.len()) {
+        None => displays[index].take().map(PickedTarget::Display),
+        Some(window) => windows[window].take().map(PickedTarget::Window),
+    };
+    Ok(picked)
+}
+
+fn pick_loop(picker: &mut Picker, out: &mut impl Write) -> Result<Option<usize>> {
+    loop {
+        draw(picker, out)?;
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        match picker.handle(key) {
+            Outcome::Continue => {}
+            Outcome::Pick(index) => return Ok(Some(index)),
+            Outcome::Cancel => return Ok(None),
+        }
+    }
+}
+
+impl Picker {
+    fn new(labels: Vec<String>) -> Self {
+        let mut picker = Self {
+            labels,
+            query: String::new(),
+            matches: Vec::new(),
+            selected: 0,
+        };
+        picker.search();
+        picker
+    }
+
+    /// Re-rank the labels against the query, back at the best match
+    fn search(&mut self) {
+        let mut scored: Vec<(i64, usize)> = self
+            .labels
+            .iter()
+            .enumerate()
+            .filter_map(|(i, label)| Some((fuzzy_score(label, &self.query)?, i)))
+            .collect();
+        // Stable, so equal scores keep displays ahead of windows
+        scored.sort_by_key(|&(score, _)| -score);
+        self.matches = scored.into_iter().map(|(_, i)| i).collect();
+        self.selected = 0;
+    }
+
+    fn handle(&mut self, key: KeyEvent) -> Outcome {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Esc => return Outcome::Cancel,
+            KeyCode::Char('c') if ctrl => return Outcome::Cancel,
+            KeyCode::Enter => {
+                if let Some(&index) = self.matches.get(self.selected) {
+                    return Outcome::Pick(index);
+                }
+            }
+            KeyCode::Up => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Down => {
+                self.selected = (self.selected + 1).min(self.matches.len().saturating_sub(1))
+            }
+            KeyCode::Backspace if self.query.pop().is_some() => self.search(),
+            KeyCode::Char(c) if !ctrl => {
+                self.query.push(c);
+                self.search();
+            }
+            _ => {}
+        }
+        Outcome::Continue
+    }
+}
+
+/// How well `text` matches `query`: None unless the query's characters
+/// all appear in order (ignoring case and spaces), higher for runs of
+/// adjacent characters and for ones starting a word
+fn fuzzy_score(text: &str, query: &str) -> Option<i64> {
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut from = 0;
+    let mut previous: Option<usize> = None;
+    for c in query.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+        let found = from + text[from..].iter().position(|&t| t == c)?;
+        score += 1;
+        if previous.is_some_and(|p| p + 1 == found) {
+            score += 5;
+        }
+        if found == 0 || !text[found - 1].is_alphanumeric() {
+            score += 3;
+        }
+        previous = Some(found);
+        from = found + 1;
+    }
+    Some(score)
+}
+
+fn draw(picker: &Picker, out: &mut impl Write) -> Result<()> {
+    let (_, rows) = terminal::size().unwrap_or((80, 24));
+    let visible = rows.saturating_sub(CHROME_ROWS).max(1) as usize;
+    // Keep the selection in view
+    let first = picker.selected.saturating_sub(visible - 1);
+
+    queue!(out, Clear(ClearType::All), cursor::MoveTo(0, 0))?;
+    queue!(
+        out,
+        Print(format!(
+            "glide record: pick what to record ({} of {})",
+            picker.matches.len(),
+            picker.labels.len()
+        )),
+        cursor::MoveTo(0, 1),
+        PrintStyledContent(format!("> {}", picker.query).bold()),
+    )?;
+
+    for (row, position) in (first..picker.matches.len()).take(visible).enumerate() {
+        queue!(out, cursor::MoveTo(0, 3 + row as u16))?;
+        let line = &picker.labels[picker.matches[position]];
+        if position == picker.selected {
+            queue!(out, PrintStyledContent(line.as_str().reverse()))?;
+        } else {
+            queue!(out, Print(line))?;
+        }
+    }
+
+    queue!(
+        out,
+        cursor::MoveTo(0, rows.saturating_sub(1)),
+        PrintStyledContent(HELP.dim())
+    )?;
+    out.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(picker: &mut Picker, code: KeyCode) -> Outcome {
+        picker.handle(KeyEvent::new(code, KeyModifiers::NONE))
+    }
+
+    #[test]
+    fn test_fuzzy_score() {
+        assert_eq!(fuzzy_score("anything", ""), Some(0));
+        assert_eq!(fuzzy_score("Window  [7] Slack - general", "chrome"), None);
+        // Adjacent characters at a word start beat scattered ones
+        let tight = fuzzy_score("Window  [3] Google Chrome - PR", "chr").unwrap();
+        let loose = fuzzy_score("Window  [4] Terminal - cargo hack run", "chr").unwrap();
+        assert!(tight > loose);
+    }
+
+    #[test]
+    fn test_picker_keys() {
+        let mut picker = Picker::new(vec![
+            "Display 0  1920x1080 (main)".to_string(),
+            "Window  [3] Google Chrome - My PR".to_string(),
+            "Window  [4] Terminal - zsh".to_string(),
+        ]);
+        assert_eq!(picker.matches, [0, 1, 2]);
+
+        for c in "term".chars() {
+            press(&mut picker, KeyCode::Char(c));
+        }
+        assert_eq!(picker.matches, [2]);
+        press(&mut picker, KeyCode::Down);
+        assert!(matches!(
+            press(&mut picker, KeyCode::Enter),
+            Outcome::Pick(2)
+        ));
+
+        for _ in 0..4 {
+            press(&mut picker, KeyCode::Backspace);
+        }
+        press(&mut picker, KeyCode::Down);
+        assert!(matches!(
+            press(&mut picker, KeyCode::Enter),
+            Outcome::Pick(1)
+        ));
+
+        press(&mut picker, KeyCode::Char('x'));
+        press(&mut picker, KeyCode::Char('q'));
+        assert!(matches!(
+            press(&mut picker, KeyCode::Enter),
+            Outcome::Continue
+        ));
+        assert!(matches!(press(&mut picker, KeyCode::Esc), Outcome::Cancel));
+    }
+}
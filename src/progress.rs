@@ -0,0 +1,82 @@
+//! The progress bar under `glide process` and the status line under
+//! `glide record`, drawn from glide-core's events
+
+use glide_core::processing::progress::ProgressEvent;
+use glide_core::recording::status::{StatusCallback, StatusEvent};
+use indicatif::{ProgressBar, ProgressStyle};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// A bar over the frames of each render, left in place once it's done
+pub fn render_bar() -> impl Fn(&ProgressEvent) + Send + Sync {
+    let bar: Mutex<Option<ProgressBar>> = Mutex::new(None);
+    move |event| {
+        let mut bar = bar.lock().unwrap();
+        match event {
+            ProgressEvent::Progress { frame, total, .. } => {
+                let current = bar.get_or_insert_with(|| {
+                    let bar = ProgressBar::new(*total as u64);
+                    bar.set_style(
+                        ProgressStyle::default_bar()
+                            .template("{spinner:.green} [{bar:40.cyan/blue}] {pos}/{len} ({eta})")
+                            .unwrap()
+                            .progress_chars("#>-"),
+                    );
+                    bar
+                });
+                current.set_position(*frame as u64);
+                if frame >= total {
+                    current.finish();
+                    *bar = None;
+                }
+            }
+            ProgressEvent::Warning { .. } => {}
+            ProgressEvent::Stage { .. }
+            | ProgressEvent::Done { .. }
+            | ProgressEvent::Error { .. } => {
+                if let Some(bar) = bar.take() {
+                    bar.finish();
+                }
+            }
+        }
+    }
+}
+
+/// A spinner with the recording's counters, redrawn in place and cleared
+/// when it stops
+pub fn status_line() -> StatusCallback {
+    // Started by the first update, so nothing shows until capture starts
+    let line: Mutex<Option<(ProgressBar, bool)>> = Mutex::new(None);
+    Arc::new(move |event| {
+        let mut line = line.lock().unwrap();
+        match event {
+            StatusEvent::Update(snapshot) => {
+                let (bar, paused) = line.get_or_insert_with(|| {
+                    let bar = ProgressBar::new_spinner();
+                    bar.set_style(style(false));
+                    bar.enable_steady_tick(Duration::from_millis(100));
+                    (bar, false)
+                });
+                if *paused != snapshot.paused {
+                    *paused = snapshot.paused;
+                    bar.set_style(style(snapshot.paused));
+                }
+                bar.set_message(snapshot.format());
+            }
+            StatusEvent::Finished => {
+                if let Some((bar, _)) = line.take() {
+                    bar.finish_and_clear();
+                }
+            }
+        }
+    })
+}
+
+fn style(paused: bool) -> ProgressStyle {
+    let template = if paused {
+        "{spinner:.yellow} PAUSED {msg}"
+    } else {
+        "{spinner:.red} REC {msg}"
+    };
+    ProgressStyle::default_spinner().template(template).unwrap()
+}
@@ -0,0 +1,213 @@
+//! What `glide inspect`, `glide stats` and `glide bench` print
+
+use anyhow::{Context, Result};
+use glide_core::bench::{BenchReport, Pass};
+use glide_core::processing::inspect::Inspection;
+use glide_core::processing::stats::{write_heatmap, RecordingStats, IDLE_THRESHOLD};
+use glide_core::processing::zoom::ZoomConfig;
+use glide_core::recording::metadata::RecordingMetadata;
+use std::path::Path;
+
+/// Print what a recording contains
+pub fn print_inspect(input: &Path, zoom_config: &ZoomConfig) -> Result<()> {
+    let inspection = Inspection::new(input, zoom_config)?;
+    let metadata = &inspection.metadata;
+
+    println!("Recording: {}", input.display());
+
+    println!("  Video:");
+    match &inspection.video_duration {
+        Ok(duration) => println!("    Duration: {duration:.3}s"),
+        Err(e) => println!("    Duration: unreadable ({e})"),
+    }
+    match &inspection.video_fps {
+        Ok(fps) => println!("    FPS: {fps:.2}"),
+        Err(e) => println!("    FPS: unreadable ({e})"),
+    }
+    match &inspection.video_size {
+        Ok((w, h)) => println!("    Resolution: {w}x{h}"),
+        Err(e) => println!("    Resolution: unreadable ({e})"),
+    }
+
+    println!("  Source: {}", inspection.source);
+    println!("    Size: {}x{} px", metadata.width, metadata.height);
+    println!("    Scale factor: {}", metadata.scale_factor);
+    match metadata.os_cursor_scale {
+        Some(scale) => println!("    OS cursor scale: {scale}"),
+        None => println!("    OS cursor scale: unknown"),
+    }
+    if !metadata.window_bounds.is_empty() {
+        println!("    Window moves: {}", metadata.window_bounds.len());
+    }
+    if let Some(id) = &metadata.recording_id {
+        println!("    Recording ID: {id}");
+    }
+
+    let counts = &inspection.counts;
+    println!("  Cursor events: {}", counts.total());
+    println!("    Moves: {}", counts.moves);
+    println!(
+        "    Clicks: {} ({} left, {} right)",
+        counts.clicks(),
+        counts.left_clicks,
+        counts.right_clicks
+    );
+    println!(
+        "    Effective clicks: {} ({} debounced within {:.2}s)",
+        inspection.effective_clicks,
+        counts.clicks() - inspection.effective_clicks,
+        zoom_config.debounce
+    );
+    println!("    Key presses: {}", counts.key_presses);
+    if !inspection.shortcuts.is_empty() {
+        println!("    Shortcuts: {}", inspection.shortcuts.join(", "));
+    }
+    println!("    Click targets: {}", metadata.click_targets.len());
+
+    println!("  Timing:");
+    if let (Some(first), Some(last)) = (
+        metadata.cursor_events.first(),
+        metadata.cursor_events.last(),
+    ) {
+        println!(
+            "    Events: {:.3}s to {:.3}s",
+            first.timestamp, last.timestamp
+        );
+    }
+    if metadata.cursor_tracking_duration > 0.0 {
+        println!(
+            "    Cursor tracking: {:.3}s",
+            metadata.cursor_tracking_duration
+        );
+        if let Some(offset) = inspection.offset() {
+            println!("    Offset: {:.3}s (cursor tracking minus video)", offset);
+        }
+    } else {
+        println!("    Cursor tracking: not recorded (offset 0)");
+    }
+    if !metadata.pauses.is_empty() {
+        let paused: f64 = metadata.pauses.iter().map(|p| p.duration).sum();
+        println!(
+            "    Pauses: {} ({:.1}s cut out)",
+            metadata.pauses.len(),
+            paused
+        );
+    }
+
+    if let Some(stats) = &metadata.capture_stats {
+        println!("  Capture:");
+        println!(
+            "    Frames: {} ({:.1} fps average)",
+            stats.captured_frames, stats.average_fps
+        );
+        println!(
+            "    Dropped: {}, late: {}",
+            stats.dropped_frames, stats.late_frames
+        );
+        println!("    Encoder backlog: up to {} frames", stats.max_queued);
+    }
+
+    Ok(())
+}
+
+/// Print stats for a recording, optionally writing a cursor heatmap PNG
+pub fn print_stats(input: &Path, heatmap: Option<&Path>) -> Result<()> {
+    let metadata = RecordingMetadata::load(input)
+        .context("Failed to load recording metadata. Was this video recorded with glide?")?;
+    let stats = RecordingStats::for_recording(input, &metadata)?;
+
+    println!("Recording: {}", input.display());
+    println!("  Duration: {:.1}s", stats.duration);
+    println!(
+        "  Clicks: {} ({} left, {} right)",
+        stats.clicks(),
+        stats.left_clicks,
+        stats.right_clicks
+    );
+    println!("  Clicks per minute: {:.1}", stats.clicks_per_minute());
+    println!(
+        "  Cursor distance: {:.0} points ({:.0} px)",
+        stats.distance,
+        stats.distance * metadata.scale_factor.max(1.0)
+    );
+    println!(
+        "  Idle: {:.0}% ({:.1}s in pauses over {:.0}s)",
+        stats.idle_percent(),
+        stats.idle_seconds,
+        IDLE_THRESHOLD
+    );
+
+    if let Some(path) = heatmap {
+        if !write_heatmap(input, &metadata, path)? {
+            println!("  Note: no cursor positions inside the video, heatmap is blank");
+        }
+        println!("  Heatmap: {}", path.display());
+    }
+
+    Ok(())
+}
+
+/// Print what `glide bench` measured
+pub fn print_bench(report: &BenchReport) {
+    println!();
+    println!(
+        "Benchmark ({}x{}, {:.0}s at {} fps, {} frames)",
+        report.width, report.height, report.duration, report.fps, report.frames
+    );
+    match &report.capture {
+        Ok((display, fps)) => println!("  Capture: {:.1} fps (display {})", fps, display),
+        Err(e) => println!("  Capture: unavailable ({:#})", e),
+    }
+    println!("  Processing:");
+    for line in format_passes(&report.passes, report.frames) {
+        println!("{}", line);
+    }
+    match &report.encode {
+        Ok(fps) => println!("  Encode (H.264, {}): {:.1} fps", report.encoder, fps),
+        Err(e) => println!("  Encode (H.264, {}): failed ({:#})", report.encoder, e),
+    }
+}
+
+/// The processing part of the bench report: the speed of each pass, and
+/// what each effect adds per frame over the pass before
+fn format_passes(passes: &[Pass], frames: u64) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut previous: Option<f64> = None;
+    for pass in passes {
+        let ms_per_frame = pass.seconds * 1000.0 / frames as f64;
+        let cost = match previous {
+            None => format!("{:.1} ms/frame", ms_per_frame),
+            Some(before) => format!("{:+.1} ms/frame", ms_per_frame - before),
+        };
+        lines.push(format!(
+            "    {:<14} {:>6.1} fps  ({})",
+            format!("{}:", pass.name),
+            frames as f64 / pass.seconds,
+            cost
+        ));
+        previous = Some(ms_per_frame);
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_passes() {
+        let passes = [
+            Pass {
+                name: "Composite",
+                seconds: 3.0,
+            },
+            Pass {
+                name: "+ Zoom",
+                seconds: 3.6,
+            },
+        ];
+        let lines = format_passes(&passes, 300);
+        assert_eq!(lines[0], "    Composite:      100.0 fps  (10.0 ms/frame)");
+        assert_eq!(lines[1], "    + Zoom:          83.3 fps  (+2.0 ms/frame)");
+    }
+}
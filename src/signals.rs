@@ -0,0 +1,61 @@
+//! The process-wide handlers `glide record` installs: Ctrl+C stops the
+//! recording (a second press quits at once), and SIGUSR1 or `p` and Enter
+//! typed in the terminal pause and resume it
+//!
+//! glide-core leaves signals and stdin alone, since they belong to
+//! whatever program embeds it.
+
+use anyhow::{Context, Result};
+use glide_core::recording::pause;
+use glide_core::CancelToken;
+use std::io::{BufRead, IsTerminal};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// A token cancelled by the first Ctrl+C; the second exits
+pub fn stop_on_ctrlc() -> Result<CancelToken> {
+    let stop = CancelToken::new();
+    let token = stop.clone();
+    let pressed = Arc::new(AtomicBool::new(false));
+    ctrlc::set_handler(move || {
+        if pressed.swap(true, Ordering::SeqCst) {
+            eprintln!("\nForce exit...");
+            std::process::exit(1);
+        }
+        eprintln!("\nStopping... (press Ctrl+C again to force quit)");
+        token.cancel();
+    })
+    .context("Failed to set Ctrl+C handler")?;
+    Ok(stop)
+}
+
+/// Turn SIGUSR1 and `p` lines on a terminal's stdin into pause requests
+pub fn pause_on_request() {
+    #[cfg(unix)]
+    // SAFETY: the handler only touches an atomic
+    unsafe {
+        libc::signal(
+            libc::SIGUSR1,
+            on_sigusr1 as extern "C" fn(libc::c_int) as libc::sighandler_t,
+        );
+    }
+
+    // Lines typed while recording; the thread ends with the process
+    if std::io::stdin().is_terminal() {
+        std::thread::spawn(|| {
+            for line in std::io::stdin().lock().lines() {
+                let Ok(line) = line else {
+                    break;
+                };
+                if line.trim().eq_ignore_ascii_case("p") {
+                    pause::request();
+                }
+            }
+        });
+    }
+}
+
+#[cfg(unix)]
+extern "C" fn on_sigusr1(_signal: libc::c_int) {
+    pause::request();
+}